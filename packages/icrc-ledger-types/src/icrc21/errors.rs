@@ -0,0 +1,22 @@
+use candid::{CandidType, Deserialize, Nat};
+use serde::Serialize;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ErrorInfo {
+    pub description: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Icrc21Error {
+    // The canister does not provide consent messages for the requested call.
+    UnsupportedCanisterCall(ErrorInfo),
+    // The canister could not produce a consent message, e.g., because the
+    // argument of the call could not be decoded.
+    ConsentMessageUnavailable(ErrorInfo),
+    // The caller did not pay enough cycles for the consent message.
+    InsufficientPayment(ErrorInfo),
+    GenericError {
+        error_code: Nat,
+        description: String,
+    },
+}
@@ -0,0 +1,582 @@
+use super::errors::{ErrorInfo, Icrc21Error};
+use super::requests::{ConsentMessageMetadata, ConsentMessageRequest, DisplayMessageType};
+use super::responses::{ConsentInfo, ConsentMessage, LineDisplayPage};
+use crate::icrc1::account::Account;
+use crate::icrc1::transfer::{Memo, TransferArg};
+use crate::icrc2::approve::ApproveArgs;
+use crate::icrc2::transfer_from::TransferFromArgs;
+use candid::{Nat, Principal};
+
+/// The maximum size of the argument of a call for which a consent message is
+/// produced. It bounds the cost of decoding the argument.
+pub const MAX_CONSENT_MESSAGE_ARG_SIZE_BYTES: usize = 500;
+
+/// The methods of the ledger for which consent messages are produced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Icrc21Function {
+    Transfer,
+    Approve,
+    TransferFrom,
+}
+
+impl Icrc21Function {
+    pub fn from_method_name(method: &str) -> Option<Self> {
+        match method {
+            "icrc1_transfer" => Some(Self::Transfer),
+            "icrc2_approve" => Some(Self::Approve),
+            "icrc2_transfer_from" => Some(Self::TransferFrom),
+            _ => None,
+        }
+    }
+}
+
+/// The languages in which consent messages are available.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    /// Maps a BCP-47 language tag to a supported language.
+    /// Falls back to English if the language is not supported.
+    fn from_tag(tag: &str) -> Self {
+        let primary_subtag = tag.split('-').next().unwrap_or_default();
+        match primary_subtag.to_lowercase().as_str() {
+            "de" => Self::German,
+            _ => Self::English,
+        }
+    }
+
+    fn tag(&self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::German => "de",
+        }
+    }
+}
+
+/// The labels of the fields of a consent message.
+enum Label {
+    Amount,
+    From,
+    To,
+    Spender,
+    ExpectedAllowance,
+    ExpiresAt,
+    Fee,
+    FeePaidBy,
+    Memo,
+    CreatedAt,
+}
+
+impl Label {
+    fn text(&self, language: Language) -> &'static str {
+        match (self, language) {
+            (Self::Amount, Language::English) => "Amount",
+            (Self::Amount, Language::German) => "Betrag",
+            (Self::From, Language::English) => "From",
+            (Self::From, Language::German) => "Von",
+            (Self::To, Language::English) => "To",
+            (Self::To, Language::German) => "An",
+            (Self::Spender, Language::English) => "Spender",
+            (Self::Spender, Language::German) => "Berechtigter",
+            (Self::ExpectedAllowance, Language::English) => "Current allowance",
+            (Self::ExpectedAllowance, Language::German) => "Aktuelle Freigabe",
+            (Self::ExpiresAt, Language::English) => "Approval expiration",
+            (Self::ExpiresAt, Language::German) => "Ablauf der Freigabe",
+            (Self::Fee, Language::English) => "Fee",
+            (Self::Fee, Language::German) => "Gebühr",
+            (Self::FeePaidBy, Language::English) => "Fee paid by",
+            (Self::FeePaidBy, Language::German) => "Gebühr bezahlt von",
+            (Self::Memo, Language::English) => "Memo",
+            (Self::Memo, Language::German) => "Memo",
+            (Self::CreatedAt, Language::English) => "Created at",
+            (Self::CreatedAt, Language::German) => "Erstellt am",
+        }
+    }
+}
+
+/// A consent message independent of the display it is rendered on.
+struct ConsentMessageContent {
+    title: String,
+    fields: Vec<(String, String)>,
+}
+
+impl ConsentMessageContent {
+    fn new(title: String) -> Self {
+        Self {
+            title,
+            fields: vec![],
+        }
+    }
+
+    fn add_field(&mut self, label: Label, language: Language, value: String) {
+        self.fields.push((label.text(language).to_string(), value));
+    }
+
+    fn to_generic_display_message(&self) -> String {
+        let mut message = format!("# {}\n", self.title);
+        for (label, value) in &self.fields {
+            message.push_str(&format!("\n**{}:**\n{}\n", label, value));
+        }
+        message
+    }
+
+    fn to_line_display_message(
+        &self,
+        characters_per_line: u16,
+        lines_per_page: u16,
+    ) -> Result<Vec<LineDisplayPage>, Icrc21Error> {
+        if characters_per_line == 0 || lines_per_page == 0 {
+            return Err(Icrc21Error::ConsentMessageUnavailable(ErrorInfo {
+                description: "The line display must have at least one line of one character."
+                    .to_string(),
+            }));
+        }
+        let mut lines = wrap_text(&self.title, characters_per_line as usize);
+        for (label, value) in &self.fields {
+            lines.extend(wrap_text(
+                &format!("{}:", label),
+                characters_per_line as usize,
+            ));
+            lines.extend(wrap_text(value, characters_per_line as usize));
+        }
+        Ok(lines
+            .chunks(lines_per_page as usize)
+            .map(|lines| LineDisplayPage {
+                lines: lines.to_vec(),
+            })
+            .collect())
+    }
+}
+
+/// Splits the text into lines of at most `characters_per_line` characters.
+/// Words are kept together unless they are longer than a line.
+fn wrap_text(text: &str, characters_per_line: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current_line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let current_len = current_line.chars().count();
+        if current_len > 0 && current_len + 1 + word.len() <= characters_per_line {
+            current_line.push(' ');
+            current_line.extend(word);
+            continue;
+        }
+        if current_len > 0 {
+            lines.push(std::mem::take(&mut current_line));
+        }
+        while word.len() > characters_per_line {
+            lines.push(word.drain(..characters_per_line).collect());
+        }
+        current_line = word.into_iter().collect();
+    }
+    if !current_line.is_empty() {
+        lines.push(current_line);
+    }
+    lines
+}
+
+/// Formats an amount of token units as a decimal number of tokens.
+pub fn format_amount(amount: &Nat, decimals: u8) -> String {
+    let digits = amount.0.to_str_radix(10);
+    let decimals = decimals as usize;
+    let (integer_part, fractional_part) = if digits.len() > decimals {
+        let (integer_part, fractional_part) = digits.split_at(digits.len() - decimals);
+        (integer_part.to_string(), fractional_part.to_string())
+    } else {
+        (
+            "0".to_string(),
+            format!("{}{}", "0".repeat(decimals - digits.len()), digits),
+        )
+    };
+    let fractional_part = fractional_part.trim_end_matches('0');
+    if fractional_part.is_empty() {
+        integer_part
+    } else {
+        format!("{}.{}", integer_part, fractional_part)
+    }
+}
+
+/// Formats a timestamp in nanoseconds since the UNIX epoch in the timezone
+/// with the given offset from UTC.
+pub fn format_timestamp(timestamp_nanos: u64, utc_offset_minutes: Option<i16>) -> String {
+    let offset_minutes = utc_offset_minutes.unwrap_or(0) as i64;
+    let seconds = (timestamp_nanos / 1_000_000_000) as i64 + offset_minutes * 60;
+    let days = seconds.div_euclid(86_400);
+    let seconds_of_day = seconds.rem_euclid(86_400);
+
+    // Converts days since the UNIX epoch into a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let offset_sign = if offset_minutes < 0 { '-' } else { '+' };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC{}{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        (seconds_of_day % 3_600) / 60,
+        seconds_of_day % 60,
+        offset_sign,
+        offset_minutes.abs() / 60,
+        offset_minutes.abs() % 60,
+    )
+}
+
+fn format_memo(memo: &Memo) -> String {
+    match std::str::from_utf8(memo.0.as_slice()) {
+        Ok(text) if !text.chars().any(char::is_control) => text.to_string(),
+        _ => format!("0x{}", hex::encode(memo.0.as_slice())),
+    }
+}
+
+fn decode_arg<T: candid::CandidType + for<'a> candid::Deserialize<'a>>(
+    arg: &[u8],
+) -> Result<T, Icrc21Error> {
+    candid::decode_one(arg).map_err(|err| {
+        Icrc21Error::UnsupportedCanisterCall(ErrorInfo {
+            description: format!("Failed to decode the argument of the call: {}", err),
+        })
+    })
+}
+
+/// Builds the ICRC-21 consent message for a call of `caller_principal` to
+/// one of the ledger methods `icrc1_transfer`, `icrc2_approve` and
+/// `icrc2_transfer_from`.
+pub fn build_icrc21_consent_info(
+    consent_msg_request: ConsentMessageRequest,
+    caller_principal: Principal,
+    ledger_fee: Nat,
+    token_symbol: String,
+    decimals: u8,
+) -> Result<ConsentInfo, Icrc21Error> {
+    if consent_msg_request.arg.len() > MAX_CONSENT_MESSAGE_ARG_SIZE_BYTES {
+        return Err(Icrc21Error::UnsupportedCanisterCall(ErrorInfo {
+            description: format!(
+                "The argument of the call must not be larger than {} bytes.",
+                MAX_CONSENT_MESSAGE_ARG_SIZE_BYTES
+            ),
+        }));
+    }
+    let function =
+        Icrc21Function::from_method_name(&consent_msg_request.method).ok_or_else(|| {
+            Icrc21Error::UnsupportedCanisterCall(ErrorInfo {
+                description: format!(
+                    "No consent message is available for the method {}.",
+                    consent_msg_request.method
+                ),
+            })
+        })?;
+
+    let preferences = consent_msg_request.user_preferences;
+    let language = Language::from_tag(&preferences.metadata.language);
+    let utc_offset_minutes = preferences.metadata.utc_offset_minutes;
+    let tokens = |amount: &Nat| format!("{} {}", format_amount(amount, decimals), token_symbol);
+    let arg = consent_msg_request.arg.as_slice();
+
+    let content = match function {
+        Icrc21Function::Transfer => {
+            let arg: TransferArg = decode_arg(arg)?;
+            let from = Account {
+                owner: caller_principal,
+                subaccount: arg.from_subaccount,
+            };
+            let mut content = ConsentMessageContent::new(match language {
+                Language::English => format!("Transfer {}", token_symbol),
+                Language::German => format!("{} überweisen", token_symbol),
+            });
+            content.add_field(Label::Amount, language, tokens(&arg.amount));
+            content.add_field(Label::From, language, from.to_string());
+            content.add_field(Label::To, language, arg.to.to_string());
+            content.add_field(
+                Label::Fee,
+                language,
+                tokens(arg.fee.as_ref().unwrap_or(&ledger_fee)),
+            );
+            if let Some(memo) = &arg.memo {
+                content.add_field(Label::Memo, language, format_memo(memo));
+            }
+            if let Some(created_at_time) = arg.created_at_time {
+                content.add_field(
+                    Label::CreatedAt,
+                    language,
+                    format_timestamp(created_at_time, utc_offset_minutes),
+                );
+            }
+            content
+        }
+        Icrc21Function::Approve => {
+            let arg: ApproveArgs = decode_arg(arg)?;
+            let from = Account {
+                owner: caller_principal,
+                subaccount: arg.from_subaccount,
+            };
+            let mut content = ConsentMessageContent::new(match language {
+                Language::English => format!("Approve spending of {}", token_symbol),
+                Language::German => format!("Ausgabe von {} freigeben", token_symbol),
+            });
+            content.add_field(Label::From, language, from.to_string());
+            content.add_field(Label::Spender, language, arg.spender.to_string());
+            content.add_field(Label::Amount, language, tokens(&arg.amount));
+            if let Some(expected_allowance) = &arg.expected_allowance {
+                content.add_field(
+                    Label::ExpectedAllowance,
+                    language,
+                    tokens(expected_allowance),
+                );
+            }
+            content.add_field(
+                Label::ExpiresAt,
+                language,
+                match arg.expires_at {
+                    Some(expires_at) => format_timestamp(expires_at, utc_offset_minutes),
+                    None => match language {
+                        Language::English => "This approval does not expire.".to_string(),
+                        Language::German => "Diese Freigabe läuft nicht ab.".to_string(),
+                    },
+                },
+            );
+            content.add_field(
+                Label::Fee,
+                language,
+                tokens(arg.fee.as_ref().unwrap_or(&ledger_fee)),
+            );
+            content.add_field(Label::FeePaidBy, language, from.to_string());
+            if let Some(memo) = &arg.memo {
+                content.add_field(Label::Memo, language, format_memo(memo));
+            }
+            if let Some(created_at_time) = arg.created_at_time {
+                content.add_field(
+                    Label::CreatedAt,
+                    language,
+                    format_timestamp(created_at_time, utc_offset_minutes),
+                );
+            }
+            content
+        }
+        Icrc21Function::TransferFrom => {
+            let arg: TransferFromArgs = decode_arg(arg)?;
+            let spender = Account {
+                owner: caller_principal,
+                subaccount: arg.spender_subaccount,
+            };
+            let mut content = ConsentMessageContent::new(match language {
+                Language::English => format!("Spend {}", token_symbol),
+                Language::German => format!("{} ausgeben", token_symbol),
+            });
+            content.add_field(Label::Amount, language, tokens(&arg.amount));
+            content.add_field(Label::From, language, arg.from.to_string());
+            content.add_field(Label::Spender, language, spender.to_string());
+            content.add_field(Label::To, language, arg.to.to_string());
+            content.add_field(
+                Label::Fee,
+                language,
+                tokens(arg.fee.as_ref().unwrap_or(&ledger_fee)),
+            );
+            content.add_field(Label::FeePaidBy, language, arg.from.to_string());
+            if let Some(memo) = &arg.memo {
+                content.add_field(Label::Memo, language, format_memo(memo));
+            }
+            if let Some(created_at_time) = arg.created_at_time {
+                content.add_field(
+                    Label::CreatedAt,
+                    language,
+                    format_timestamp(created_at_time, utc_offset_minutes),
+                );
+            }
+            content
+        }
+    };
+
+    let consent_message = match preferences.device_spec {
+        None | Some(DisplayMessageType::GenericDisplay) => {
+            ConsentMessage::GenericDisplayMessage(content.to_generic_display_message())
+        }
+        Some(DisplayMessageType::LineDisplay {
+            characters_per_line,
+            lines_per_page,
+        }) => ConsentMessage::LineDisplayMessage {
+            pages: content.to_line_display_message(characters_per_line, lines_per_page)?,
+        },
+    };
+
+    Ok(ConsentInfo {
+        consent_message,
+        metadata: ConsentMessageMetadata {
+            language: language.tag().to_string(),
+            utc_offset_minutes,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icrc21::requests::ConsentMessageSpec;
+    use candid::Encode;
+    use serde_bytes::ByteBuf;
+
+    fn request(
+        method: &str,
+        arg: Vec<u8>,
+        language: &str,
+        device_spec: Option<DisplayMessageType>,
+    ) -> ConsentMessageRequest {
+        ConsentMessageRequest {
+            method: method.to_string(),
+            arg: ByteBuf::from(arg),
+            user_preferences: ConsentMessageSpec {
+                metadata: ConsentMessageMetadata {
+                    language: language.to_string(),
+                    utc_offset_minutes: None,
+                },
+                device_spec,
+            },
+        }
+    }
+
+    fn transfer_arg() -> TransferArg {
+        TransferArg {
+            from_subaccount: None,
+            to: Account {
+                owner: Principal::management_canister(),
+                subaccount: None,
+            },
+            fee: None,
+            created_at_time: None,
+            memo: None,
+            amount: Nat::from(150_000_000u64),
+        }
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(&Nat::from(0u64), 8), "0");
+        assert_eq!(format_amount(&Nat::from(1u64), 8), "0.00000001");
+        assert_eq!(format_amount(&Nat::from(150_000_000u64), 8), "1.5");
+        assert_eq!(
+            format_amount(&Nat::from(123_456_789_000u64), 8),
+            "1234.56789"
+        );
+        assert_eq!(format_amount(&Nat::from(42u64), 0), "42");
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0, None), "1970-01-01 00:00:00 UTC+00:00");
+        // 2024-02-29 12:34:56 UTC
+        let timestamp = 1_709_210_096_000_000_000;
+        assert_eq!(
+            format_timestamp(timestamp, Some(120)),
+            "2024-02-29 14:34:56 UTC+02:00"
+        );
+        assert_eq!(
+            format_timestamp(timestamp, Some(-780)),
+            "2024-02-28 23:34:56 UTC-13:00"
+        );
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(wrap_text("Transfer ICP", 20), vec!["Transfer ICP"]);
+        assert_eq!(wrap_text("Transfer ICP", 8), vec!["Transfer", "ICP"]);
+        assert_eq!(wrap_text("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_transfer_consent_message() {
+        let arg = Encode!(&transfer_arg()).unwrap();
+        let consent_info = build_icrc21_consent_info(
+            request("icrc1_transfer", arg, "en-US", None),
+            Principal::anonymous(),
+            Nat::from(10_000u64),
+            "ICP".to_string(),
+            8,
+        )
+        .unwrap();
+        assert_eq!(consent_info.metadata.language, "en");
+        match consent_info.consent_message {
+            ConsentMessage::GenericDisplayMessage(message) => {
+                assert!(message.starts_with("# Transfer ICP"));
+                assert!(message.contains("**Amount:**\n1.5 ICP"));
+                assert!(message.contains("**Fee:**\n0.0001 ICP"));
+            }
+            message => panic!("unexpected consent message {:?}", message),
+        }
+    }
+
+    #[test]
+    fn test_line_display_consent_message() {
+        let arg = Encode!(&transfer_arg()).unwrap();
+        let consent_info = build_icrc21_consent_info(
+            request(
+                "icrc1_transfer",
+                arg,
+                "de",
+                Some(DisplayMessageType::LineDisplay {
+                    characters_per_line: 20,
+                    lines_per_page: 4,
+                }),
+            ),
+            Principal::anonymous(),
+            Nat::from(10_000u64),
+            "ICP".to_string(),
+            8,
+        )
+        .unwrap();
+        assert_eq!(consent_info.metadata.language, "de");
+        match consent_info.consent_message {
+            ConsentMessage::LineDisplayMessage { pages } => {
+                assert_eq!(pages[0].lines[0], "ICP überweisen");
+                assert_eq!(pages[0].lines[1], "Betrag:");
+                assert_eq!(pages[0].lines[2], "1.5 ICP");
+                for page in pages {
+                    assert!(page.lines.len() <= 4);
+                    assert!(page.lines.iter().all(|line| line.chars().count() <= 20));
+                }
+            }
+            message => panic!("unexpected consent message {:?}", message),
+        }
+    }
+
+    #[test]
+    fn test_unsupported_calls() {
+        let arg = Encode!(&transfer_arg()).unwrap();
+        let build = |method: &str, arg: Vec<u8>| {
+            build_icrc21_consent_info(
+                request(method, arg, "en", None),
+                Principal::anonymous(),
+                Nat::from(10_000u64),
+                "ICP".to_string(),
+                8,
+            )
+        };
+        assert!(matches!(
+            build("icrc1_balance_of", arg.clone()),
+            Err(Icrc21Error::UnsupportedCanisterCall(_))
+        ));
+        assert!(matches!(
+            build("icrc2_approve", arg),
+            Err(Icrc21Error::UnsupportedCanisterCall(_))
+        ));
+        assert!(matches!(
+            build(
+                "icrc1_transfer",
+                vec![0; MAX_CONSENT_MESSAGE_ARG_SIZE_BYTES + 1]
+            ),
+            Err(Icrc21Error::UnsupportedCanisterCall(_))
+        ));
+    }
+}
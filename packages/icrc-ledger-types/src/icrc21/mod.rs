@@ -0,0 +1,4 @@
+pub mod errors;
+pub mod lib;
+pub mod requests;
+pub mod responses;
@@ -0,0 +1,37 @@
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use serde_bytes::ByteBuf;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConsentMessageMetadata {
+    /// The BCP-47 language tag of the consent message.
+    pub language: String,
+    /// The offset of the user's timezone from UTC in minutes.
+    #[serde(default)]
+    pub utc_offset_minutes: Option<i16>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum DisplayMessageType {
+    GenericDisplay,
+    LineDisplay {
+        characters_per_line: u16,
+        lines_per_page: u16,
+    },
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConsentMessageSpec {
+    pub metadata: ConsentMessageMetadata,
+    #[serde(default)]
+    pub device_spec: Option<DisplayMessageType>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConsentMessageRequest {
+    /// The name of the method that is going to be called.
+    pub method: String,
+    /// The Candid encoded argument of the call.
+    pub arg: ByteBuf,
+    pub user_preferences: ConsentMessageSpec,
+}
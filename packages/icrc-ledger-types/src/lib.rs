@@ -1,4 +1,5 @@
 pub mod icrc;
 pub mod icrc1;
 pub mod icrc2;
+pub mod icrc21;
 pub mod icrc3;
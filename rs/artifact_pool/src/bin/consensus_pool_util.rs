@@ -1,3 +1,5 @@
+mod timeline;
+
 use clap::{arg, Arg, Command};
 use ic_artifact_pool::{
     certification_pool::CertificationPoolImpl,
@@ -7,10 +9,12 @@ use ic_config::artifact_pool::ArtifactPoolConfig;
 use ic_interfaces::consensus_pool::*;
 use ic_logger::{LoggerImpl, ReplicaLogger};
 use ic_metrics::MetricsRegistry;
+use ic_registry_client::client::RegistryClientImpl;
+use ic_registry_local_store::LocalStoreImpl;
 use ic_types::{
     consensus::{certification::CertificationMessage, CatchUpPackage, ConsensusMessageHashable},
     time::current_time,
-    NodeId, PrincipalId,
+    Height, NodeId, PrincipalId, SubnetId,
 };
 use prost::Message;
 use serde::{Deserialize, Serialize};
@@ -20,6 +24,8 @@ use std::convert::TryFrom;
 use std::io::BufRead;
 use std::io::Write;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
 
 fn main() {
     let mut app = Command::new("ic-consensus-pool-util")
@@ -51,6 +57,51 @@ fn main() {
                         .takes_value(true),
                 ),
         )
+        .subcommand(
+            Command::new("timeline")
+                .about(
+                    "Print a per-height timeline of block making, notarization, finalization \
+                     and certification, followed by rank-0 block maker statistics",
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("HEIGHT")
+                        .help("First height to analyze (default: lowest height with a block)")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("HEIGHT")
+                        .help("Last height to analyze (default: highest height with a block)")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("registry-local-store")
+                        .long("registry-local-store")
+                        .value_name("PATH")
+                        .help(
+                            "Registry local store used to determine the committee and the \
+                             rank-0 block maker of each height",
+                        )
+                        .takes_value(true)
+                        .requires("subnet-id"),
+                )
+                .arg(
+                    Arg::new("subnet-id")
+                        .long("subnet-id")
+                        .value_name("ID")
+                        .help("Id of the subnet the pools belong to")
+                        .takes_value(true)
+                        .requires("registry-local-store"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the timeline as JSON instead of text"),
+                ),
+        )
         .arg(arg!(<PATH>       "PATH to the consensus pool directory"));
     let mut help = Vec::new();
     app.write_help(&mut help)
@@ -65,6 +116,8 @@ fn main() {
         import(path)
     } else if let Some(matches) = matches.subcommand_matches("export-cup-proto") {
         export_cup_proto(path, matches)
+    } else if let Some(matches) = matches.subcommand_matches("timeline") {
+        print_timeline(path, matches)
     } else {
         eprintln!(
             "{}",
@@ -274,3 +327,54 @@ fn export_cup_proto(path: &str, matches: &clap::ArgMatches) {
     file.write_all(&buf)
         .unwrap_or_else(|err| panic!("Cannot write to file {}: {:?}", filename, err));
 }
+
+fn print_timeline(path: &str, matches: &clap::ArgMatches) {
+    let parse_height = |name: &str| {
+        matches.value_of(name).map(|height| {
+            Height::from(
+                height
+                    .parse::<u64>()
+                    .unwrap_or_else(|err| panic!("Invalid height '{}': {}", height, err)),
+            )
+        })
+    };
+    let registry_client = matches.value_of("registry-local-store").map(|local_store| {
+        let registry_client =
+            RegistryClientImpl::new(Arc::new(LocalStoreImpl::new(local_store)), None);
+        registry_client
+            .poll_once()
+            .unwrap_or_else(|err| panic!("Cannot read the registry local store: {:?}", err));
+        Arc::new(registry_client)
+    });
+    let subnet_id = matches.value_of("subnet-id").map(|subnet_id| {
+        SubnetId::from(
+            PrincipalId::from_str(subnet_id)
+                .unwrap_or_else(|err| panic!("Invalid subnet id '{}': {}", subnet_id, err)),
+        )
+    });
+    let registry = registry_client
+        .zip(subnet_id)
+        .map(
+            |(registry_client, subnet_id)| timeline::SubnetRegistryView {
+                registry_client,
+                subnet_id,
+            },
+        );
+    let consensus_pool = open_consensus_pool(path, true);
+    let certification_pool = open_certification_pool(path, true);
+    let timeline = timeline::collect(
+        &consensus_pool,
+        &certification_pool,
+        registry.as_ref(),
+        parse_height("from"),
+        parse_height("to"),
+    );
+    if matches.is_present("json") {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&timeline).expect("Failed to serialize to JSON")
+        );
+    } else {
+        print!("{}", timeline::render_text(&timeline));
+    }
+}
@@ -0,0 +1,938 @@
+//! Per-height timeline of block making, notarization, finalization and
+//! certification, reconstructed from the consensus and certification pools of
+//! a single node.
+//!
+//! All times are the times at which the artifacts were added to the validated
+//! pool of that node, so they reflect when the node learned about them, not
+//! when they were created. Certification artifacts carry no such timestamp.
+//!
+//! The pools contain no registry information. If a registry is given, the
+//! committee of each height is read from it, and the rank-0 block maker of
+//! each height is computed from the committee and the random beacon of the
+//! previous height, the same way consensus does. Otherwise, the committee is
+//! taken to be every node that signed anything in the analyzed height range,
+//! and a node that signed nothing at all in the range is not reported as
+//! missing.
+//!
+//! Notarization and finalization shares below the finalized height are purged
+//! from the pool, and so are certification shares and certifications below
+//! the height of the latest CUP minus `CERTIFICATION_RETAINED_HEIGHTS`. The
+//! shares of such heights are reported as unknown.
+use ic_artifact_pool::certification_pool::CertificationPoolImpl;
+use ic_consensus_utils::membership::Membership;
+use ic_crypto_prng::RandomnessPurpose;
+use ic_interfaces::consensus_pool::{ConsensusPool, ConsensusPoolCache, HeightRange};
+use ic_interfaces_registry::RegistryClient;
+use ic_protobuf::types::v1 as pb;
+use ic_types::{
+    consensus::{Block, CatchUpPackage, ConsensusMessageHashable, HasHeight},
+    crypto::CryptoHashOf,
+    Height, NodeId, SubnetId, Time,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// The number of hex characters of block hashes and node IDs shown in the
+/// text output.
+const SHORT_ID_LEN: usize = 8;
+
+/// The number of heights below the latest CUP for which the certifier keeps
+/// certification shares and certifications, see `MINIMUM_CHAIN_LENGTH` in
+/// `ic_consensus::consensus`.
+const CERTIFICATION_RETAINED_HEIGHTS: u64 = 50;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Proposal {
+    pub rank: u64,
+    pub proposer: String,
+    pub block: String,
+    /// Milliseconds since the first artifact at this height arrived.
+    pub arrived_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Share {
+    pub signer: String,
+    pub block: String,
+    /// Milliseconds since the first artifact at this height arrived.
+    pub arrived_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct HeightTimeline {
+    pub height: u64,
+    /// Arrival time of the first artifact at this height, in nanoseconds since
+    /// the Unix epoch. All `arrived_ms` at this height are relative to it.
+    pub start_time_ns: Option<u64>,
+    /// Ordered by rank.
+    pub proposals: Vec<Proposal>,
+    pub notarization_shares: Vec<Share>,
+    pub notarized_block: Option<String>,
+    pub notarized_ms: Option<u64>,
+    pub missing_notarization_shares: Vec<String>,
+    /// Set if the notarization shares of this height were purged from the
+    /// pool, in which case it is unknown which nodes sent one.
+    pub notarization_shares_purged: bool,
+    pub finalization_shares: Vec<Share>,
+    /// The block at this height on the finalized chain, whether it was
+    /// finalized explicitly or as an ancestor of a finalized block.
+    pub finalized_block: Option<String>,
+    /// Only set if the block was finalized explicitly.
+    pub finalized_ms: Option<u64>,
+    pub missing_finalization_shares: Vec<String>,
+    /// Set if the finalization shares of this height were purged from the
+    /// pool, in which case it is unknown which nodes sent one.
+    pub finalization_shares_purged: bool,
+    pub certification_share_signers: Vec<String>,
+    pub certified: bool,
+    pub missing_certification_shares: Vec<String>,
+    /// Set if the certification shares and the certification of this height
+    /// were purged from the pool, in which case it is unknown which nodes sent
+    /// a share and whether the height was certified.
+    pub certification_shares_purged: bool,
+    /// The node that was supposed to propose the rank-0 block, if the
+    /// committee and the random beacon of the previous height are known.
+    pub rank0_block_maker: Option<String>,
+}
+
+impl HeightTimeline {
+    fn finalized_rank(&self) -> Option<u64> {
+        let finalized_block = self.finalized_block.as_ref()?;
+        self.proposals
+            .iter()
+            .find(|proposal| &proposal.block == finalized_block)
+            .map(|proposal| proposal.rank)
+    }
+}
+
+/// Rank-0 block making statistics of a single node.
+///
+/// The heights at which a node was the rank-0 block maker are only known if
+/// the registry is given. Otherwise, they are taken to be the heights at which
+/// the node proposed a rank-0 block, so a rank-0 block maker that did not
+/// propose at all is indistinguishable from any other node. Such heights are
+/// counted in `Summary::heights_without_rank0_proposal` instead.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct BlockMakerStats {
+    pub node: String,
+    pub rank0_heights: u64,
+    pub rank0_proposals: u64,
+    pub rank0_finalized: u64,
+    /// The fraction of the heights at which the node was the rank-0 block
+    /// maker that were not finalized at rank 0.
+    pub rank0_failure_rate: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct Summary {
+    /// Heights whose block on the finalized chain is known, so that the outcome
+    /// of block making is decided.
+    pub decided_heights: u64,
+    pub heights_finalized_at_rank0: u64,
+    pub heights_without_rank0_proposal: u64,
+    /// Decided heights whose rank-0 block maker is known from the registry.
+    pub heights_with_known_rank0_block_maker: u64,
+    pub block_makers: Vec<BlockMakerStats>,
+}
+
+/// The registry of the subnet whose pools are analyzed.
+pub(crate) struct SubnetRegistryView {
+    pub registry_client: Arc<dyn RegistryClient>,
+    pub subnet_id: SubnetId,
+}
+
+/// A consensus pool cache that only knows a CUP and a summary block. This is
+/// all `Membership` needs to look up the registry version of the heights
+/// covered by either of them.
+struct SummaryBlockCache {
+    catch_up_package: CatchUpPackage,
+    summary_block: Block,
+}
+
+impl ConsensusPoolCache for SummaryBlockCache {
+    fn finalized_block(&self) -> Block {
+        self.summary_block.clone()
+    }
+
+    fn catch_up_package(&self) -> CatchUpPackage {
+        self.catch_up_package.clone()
+    }
+
+    fn cup_as_protobuf(&self) -> pb::CatchUpPackage {
+        pb::CatchUpPackage::from(&self.catch_up_package)
+    }
+
+    fn summary_block(&self) -> Block {
+        self.summary_block.clone()
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct Timeline {
+    pub heights: Vec<HeightTimeline>,
+    pub summary: Summary,
+}
+
+fn hash_to_string(hash: &CryptoHashOf<Block>) -> String {
+    hash.get_ref()
+        .0
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn short(id: &str) -> &str {
+    &id[..id.len().min(SHORT_ID_LEN)]
+}
+
+fn millis_since(start: Option<Time>, time: Option<Time>) -> Option<u64> {
+    let (start, time) = (start?, time?);
+    Some(
+        time.as_nanos_since_unix_epoch()
+            .saturating_sub(start.as_nanos_since_unix_epoch())
+            / 1_000_000,
+    )
+}
+
+/// Reads the timeline of all heights in `[from, to]` from the pools. Missing
+/// bounds default to the range of heights for which there are block
+/// proposals.
+pub(crate) fn collect(
+    consensus_pool: &dyn ConsensusPool,
+    certification_pool: &CertificationPoolImpl,
+    registry: Option<&SubnetRegistryView>,
+    from: Option<Height>,
+    to: Option<Height>,
+) -> Timeline {
+    let validated = consensus_pool.validated();
+    let Some(proposal_range) = validated.block_proposal().height_range() else {
+        return Timeline {
+            heights: vec![],
+            summary: summarize(&[]),
+        };
+    };
+    let range = HeightRange::new(
+        from.unwrap_or(proposal_range.min),
+        to.unwrap_or(proposal_range.max),
+    );
+    let node_to_string = |node: NodeId| node.get().to_string();
+
+    // Reconstruct the finalized chain from the finalizations, walking back
+    // through the parents of the finalized blocks.
+    let mut parents = BTreeMap::new();
+    for proposal in validated
+        .block_proposal()
+        .get_by_height_range(range.clone())
+    {
+        parents.insert(
+            proposal.content.get_hash().clone(),
+            proposal.content.as_ref().parent.clone(),
+        );
+    }
+    let mut finalized_blocks = BTreeMap::new();
+    let mut finalizations = validated
+        .finalization()
+        .get_by_height_range(range.clone())
+        .collect::<Vec<_>>();
+    finalizations.sort_by_key(|finalization| std::cmp::Reverse(finalization.content.height));
+    for finalization in finalizations {
+        let mut next = Some((finalization.content.height, finalization.content.block));
+        while let Some((height, hash)) = next.take() {
+            if finalized_blocks.contains_key(&height) {
+                break;
+            }
+            if height > range.min {
+                next = parents
+                    .get(&hash)
+                    .map(|parent| (height.decrement(), parent.clone()));
+            }
+            finalized_blocks.insert(height, hash);
+        }
+    }
+
+    // Shares below the finalized height may have been purged, see
+    // `Purger::purge_validated_shares_by_finalized_height`.
+    let finalized_height = validated
+        .finalization()
+        .max_height()
+        .max(validated.catch_up_package().max_height())
+        .unwrap_or_default();
+    // Certification artifacts are purged relative to the latest CUP, see
+    // `CertifierImpl::get_purge_height`.
+    let certification_purge_height = Height::from(
+        validated
+            .catch_up_package()
+            .max_height()
+            .unwrap_or_default()
+            .get()
+            .saturating_sub(CERTIFICATION_RETAINED_HEIGHTS),
+    );
+
+    // The summary blocks determine the registry version of each height.
+    let summary_blocks = validated
+        .catch_up_package()
+        .get_all()
+        .map(|cup| cup.content.block.into_inner())
+        .chain(
+            validated
+                .block_proposal()
+                .get_all()
+                .filter(|proposal| {
+                    validated
+                        .finalization()
+                        .get_by_height(proposal.height())
+                        .any(|finalization| {
+                            &finalization.content.block == proposal.content.get_hash()
+                        })
+                })
+                .map(|proposal| proposal.content.into_inner()),
+        )
+        .filter(|block| block.payload.is_summary())
+        .collect::<Vec<_>>();
+    let memberships: Vec<Membership> = match (registry, validated.catch_up_package().get_highest())
+    {
+        (Some(registry), Ok(catch_up_package)) => summary_blocks
+            .into_iter()
+            .map(|summary_block| {
+                let cache = SummaryBlockCache {
+                    catch_up_package: catch_up_package.clone(),
+                    summary_block,
+                };
+                Membership::new(
+                    Arc::new(cache),
+                    registry.registry_client.clone(),
+                    registry.subnet_id,
+                )
+            })
+            .collect(),
+        _ => vec![],
+    };
+    // The membership of the first summary block covering `height`, together
+    // with the committee at that height.
+    let registry_membership = |height: Height| {
+        memberships.iter().find_map(|membership| {
+            let nodes = membership.get_nodes(height).ok()?;
+            (!nodes.is_empty()).then_some((membership, nodes))
+        })
+    };
+
+    let mut committee = BTreeSet::new();
+    let mut registry_committees = vec![];
+    let mut heights = vec![];
+    let mut height = range.min;
+    while height <= range.max {
+        let timestamp = |id| validated.get_timestamp(&id);
+
+        let proposals = validated
+            .block_proposal()
+            .get_by_height(height)
+            .map(|proposal| {
+                let time = timestamp(proposal.get_id());
+                let block = proposal.content.as_ref();
+                (
+                    block.rank.0,
+                    node_to_string(proposal.signature.signer),
+                    hash_to_string(proposal.content.get_hash()),
+                    time,
+                )
+            })
+            .collect::<Vec<_>>();
+        let notarization_shares = validated
+            .notarization_share()
+            .get_by_height(height)
+            .map(|share| {
+                (
+                    node_to_string(share.signature.signer),
+                    hash_to_string(&share.content.block),
+                    timestamp(share.get_id()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let notarization = validated
+            .notarization()
+            .get_by_height(height)
+            .map(|notarization| {
+                (
+                    hash_to_string(&notarization.content.block),
+                    timestamp(notarization.get_id()),
+                )
+            })
+            .next();
+        let finalization_shares = validated
+            .finalization_share()
+            .get_by_height(height)
+            .map(|share| {
+                (
+                    node_to_string(share.signature.signer),
+                    hash_to_string(&share.content.block),
+                    timestamp(share.get_id()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let finalization_time = validated
+            .finalization()
+            .get_by_height(height)
+            .map(|finalization| timestamp(finalization.get_id()))
+            .next()
+            .flatten();
+        let certification_share_signers = certification_pool
+            .persistent_pool
+            .certification_shares()
+            .get_by_height(height)
+            .map(|share| node_to_string(share.signed.signature.signer))
+            .collect::<BTreeSet<_>>();
+        let certified = certification_pool
+            .persistent_pool
+            .certifications()
+            .get_by_height(height)
+            .next()
+            .is_some();
+        let height_membership = registry_membership(height);
+        let block_maker = height_membership.as_ref().and_then(|(membership, _)| {
+            if height == Height::from(0) {
+                return None;
+            }
+            let previous_beacon = validated
+                .random_beacon()
+                .get_by_height(height.decrement())
+                .next()?;
+            membership
+                .get_shuffled_nodes(
+                    height,
+                    &previous_beacon,
+                    &RandomnessPurpose::BlockmakerRanking,
+                )
+                .ok()?
+                .first()
+                .copied()
+        });
+        registry_committees.push(
+            height_membership
+                .map(|(_, nodes)| nodes.into_iter().map(node_to_string).collect::<Vec<_>>()),
+        );
+
+        committee.extend(proposals.iter().map(|(_, proposer, _, _)| proposer.clone()));
+        committee.extend(
+            notarization_shares
+                .iter()
+                .map(|(signer, _, _)| signer.clone()),
+        );
+        committee.extend(
+            finalization_shares
+                .iter()
+                .map(|(signer, _, _)| signer.clone()),
+        );
+        committee.extend(certification_share_signers.iter().cloned());
+
+        let start = proposals
+            .iter()
+            .map(|(_, _, _, time)| time)
+            .chain(notarization_shares.iter().map(|(_, _, time)| time))
+            .chain(finalization_shares.iter().map(|(_, _, time)| time))
+            .filter_map(|time| *time)
+            .min();
+        let to_shares = |shares: Vec<(String, String, Option<Time>)>| {
+            let mut shares = shares
+                .into_iter()
+                .map(|(signer, block, time)| Share {
+                    signer,
+                    block,
+                    arrived_ms: millis_since(start, time),
+                })
+                .collect::<Vec<_>>();
+            shares.sort_by_key(|share| share.arrived_ms);
+            shares
+        };
+
+        let mut proposals = proposals
+            .into_iter()
+            .map(|(rank, proposer, block, time)| Proposal {
+                rank,
+                proposer,
+                block,
+                arrived_ms: millis_since(start, time),
+            })
+            .collect::<Vec<_>>();
+        proposals.sort_by_key(|proposal| proposal.rank);
+        let (notarized_block, notarization_time) = notarization.unzip();
+        let below_finalized_height = height < finalized_height;
+        heights.push(HeightTimeline {
+            notarization_shares_purged: below_finalized_height && notarization_shares.is_empty(),
+            finalization_shares_purged: below_finalized_height && finalization_shares.is_empty(),
+            certification_shares_purged: height < certification_purge_height
+                && certification_share_signers.is_empty()
+                && !certified,
+            height: height.get(),
+            start_time_ns: start.map(Time::as_nanos_since_unix_epoch),
+            proposals,
+            notarization_shares: to_shares(notarization_shares),
+            notarized_block,
+            notarized_ms: millis_since(start, notarization_time.flatten()),
+            finalization_shares: to_shares(finalization_shares),
+            finalized_block: finalized_blocks.get(&height).map(hash_to_string),
+            finalized_ms: millis_since(start, finalization_time),
+            certification_share_signers: certification_share_signers.into_iter().collect(),
+            certified,
+            rank0_block_maker: block_maker.map(node_to_string),
+            ..Default::default()
+        });
+        height = height.increment();
+    }
+
+    let committee = committee.into_iter().collect::<Vec<_>>();
+    for (timeline, registry_committee) in heights.iter_mut().zip(registry_committees) {
+        let committee = registry_committee.as_ref().unwrap_or(&committee);
+        let missing = |signers: Vec<&String>| {
+            committee
+                .iter()
+                .filter(|node| !signers.contains(node))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        if !timeline.notarization_shares_purged {
+            timeline.missing_notarization_shares = missing(
+                timeline
+                    .notarization_shares
+                    .iter()
+                    .map(|share| &share.signer)
+                    .collect(),
+            );
+        }
+        if !timeline.finalization_shares_purged {
+            timeline.missing_finalization_shares = missing(
+                timeline
+                    .finalization_shares
+                    .iter()
+                    .map(|share| &share.signer)
+                    .collect(),
+            );
+        }
+        if !timeline.certification_shares_purged {
+            timeline.missing_certification_shares =
+                missing(timeline.certification_share_signers.iter().collect());
+        }
+    }
+
+    let summary = summarize(&heights);
+    Timeline { heights, summary }
+}
+
+/// Computes the rank-0 block making statistics over the heights whose
+/// finalized block is known.
+pub(crate) fn summarize(heights: &[HeightTimeline]) -> Summary {
+    let decided = heights
+        .iter()
+        .filter(|timeline| timeline.finalized_block.is_some())
+        .collect::<Vec<_>>();
+
+    // Per node: the rank-0 heights, proposals and finalized blocks.
+    let mut block_makers: BTreeMap<&str, (u64, u64, u64)> = BTreeMap::new();
+    let mut heights_without_rank0_proposal = 0;
+    let mut heights_with_known_rank0_block_maker = 0;
+    for timeline in decided.iter() {
+        let rank0_proposers = timeline
+            .proposals
+            .iter()
+            .filter(|proposal| proposal.rank == 0)
+            .map(|proposal| proposal.proposer.as_str())
+            .collect::<BTreeSet<_>>();
+        if rank0_proposers.is_empty() {
+            heights_without_rank0_proposal += 1;
+        }
+        for proposer in rank0_proposers.iter() {
+            block_makers.entry(proposer).or_default().1 += 1;
+        }
+
+        let rank0_block_makers = match &timeline.rank0_block_maker {
+            Some(block_maker) => {
+                heights_with_known_rank0_block_maker += 1;
+                BTreeSet::from([block_maker.as_str()])
+            }
+            None => rank0_proposers,
+        };
+        let finalized_at_rank0 = timeline.finalized_rank() == Some(0);
+        for block_maker in rank0_block_makers {
+            let (heights, _, finalized) = block_makers.entry(block_maker).or_default();
+            *heights += 1;
+            if finalized_at_rank0 {
+                *finalized += 1;
+            }
+        }
+    }
+
+    Summary {
+        decided_heights: decided.len() as u64,
+        heights_finalized_at_rank0: decided
+            .iter()
+            .filter(|timeline| timeline.finalized_rank() == Some(0))
+            .count() as u64,
+        heights_without_rank0_proposal,
+        heights_with_known_rank0_block_maker,
+        block_makers: block_makers
+            .into_iter()
+            .map(|(node, (heights, proposals, finalized))| BlockMakerStats {
+                node: node.to_string(),
+                rank0_heights: heights,
+                rank0_proposals: proposals,
+                rank0_finalized: finalized,
+                rank0_failure_rate: if heights == 0 {
+                    0.0
+                } else {
+                    (heights - finalized) as f64 / heights as f64
+                },
+            })
+            .collect(),
+    }
+}
+
+fn render_shares(shares: &[Share], missing: &[String], purged: bool) -> String {
+    if purged {
+        return "unknown (purged)".to_string();
+    }
+    let mut out = shares
+        .iter()
+        .map(|share| match share.arrived_ms {
+            Some(ms) => format!("{} +{}ms", short(&share.signer), ms),
+            None => short(&share.signer).to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !missing.is_empty() {
+        let missing = missing
+            .iter()
+            .map(|node| short(node))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = write!(out, "; missing: {}", missing);
+    }
+    out
+}
+
+fn render_time(ms: Option<u64>) -> String {
+    ms.map_or("-".to_string(), |ms| format!("+{}ms", ms))
+}
+
+pub(crate) fn render_text(timeline: &Timeline) -> String {
+    let mut out = String::new();
+    for height in &timeline.heights {
+        let finalized = match (&height.finalized_block, height.finalized_rank()) {
+            (Some(block), Some(rank)) => format!("rank {} ({})", rank, short(block)),
+            (Some(block), None) => format!("unknown rank ({})", short(block)),
+            (None, _) => "no".to_string(),
+        };
+        let _ = writeln!(out, "Height {}  finalized: {}", height.height, finalized);
+        if let Some(block_maker) = &height.rank0_block_maker {
+            let _ = writeln!(out, "  rank-0 maker  {}", short(block_maker));
+        }
+        for proposal in &height.proposals {
+            let _ = writeln!(
+                out,
+                "  proposal      rank {} by {} {} ({})",
+                proposal.rank,
+                short(&proposal.proposer),
+                render_time(proposal.arrived_ms),
+                short(&proposal.block)
+            );
+        }
+        let _ = writeln!(
+            out,
+            "  notarized     {}  shares: {}",
+            render_time(height.notarized_ms),
+            render_shares(
+                &height.notarization_shares,
+                &height.missing_notarization_shares,
+                height.notarization_shares_purged,
+            )
+        );
+        let _ = writeln!(
+            out,
+            "  finalized     {}  shares: {}",
+            render_time(height.finalized_ms),
+            render_shares(
+                &height.finalization_shares,
+                &height.missing_finalization_shares,
+                height.finalization_shares_purged,
+            )
+        );
+        let certification_shares = height
+            .certification_share_signers
+            .iter()
+            .map(|signer| Share {
+                signer: signer.clone(),
+                block: String::new(),
+                arrived_ms: None,
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(
+            out,
+            "  certified     {}  shares: {}",
+            match (height.certification_shares_purged, height.certified) {
+                (true, _) => "unknown",
+                (false, true) => "yes",
+                (false, false) => "no",
+            },
+            render_shares(
+                &certification_shares,
+                &height.missing_certification_shares,
+                height.certification_shares_purged,
+            )
+        );
+    }
+
+    let summary = &timeline.summary;
+    let _ = writeln!(
+        out,
+        "\n{} of {} decided heights were finalized at rank 0; {} had no rank-0 proposal; \
+         the rank-0 block maker of {} is known from the registry.",
+        summary.heights_finalized_at_rank0,
+        summary.decided_heights,
+        summary.heights_without_rank0_proposal,
+        summary.heights_with_known_rank0_block_maker
+    );
+    let _ = writeln!(
+        out,
+        "{:<12} {:>14} {:>16} {:>10} {:>13}",
+        "node", "rank-0 heights", "rank-0 proposals", "finalized", "failure rate"
+    );
+    for stats in &summary.block_makers {
+        let _ = writeln!(
+            out,
+            "{:<12} {:>14} {:>16} {:>10} {:>12.1}%",
+            short(&stats.node),
+            stats.rank0_heights,
+            stats.rank0_proposals,
+            stats.rank0_finalized,
+            stats.rank0_failure_rate * 100.0
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proposal(rank: u64, proposer: &str, block: &str) -> Proposal {
+        Proposal {
+            rank,
+            proposer: proposer.to_string(),
+            block: block.to_string(),
+            arrived_ms: Some(0),
+        }
+    }
+
+    fn height(
+        height: u64,
+        proposals: Vec<Proposal>,
+        finalized_block: Option<&str>,
+    ) -> HeightTimeline {
+        HeightTimeline {
+            height,
+            proposals,
+            finalized_block: finalized_block.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn summarize_attributes_rank0_failures_to_proposers() {
+        let heights = vec![
+            // Rank 0 by node "a" made it.
+            height(1, vec![proposal(0, "a", "b1")], Some("b1")),
+            // Rank 0 by node "b" was superseded by rank 1.
+            height(
+                2,
+                vec![proposal(0, "b", "b2"), proposal(1, "a", "b2'")],
+                Some("b2'"),
+            ),
+            // Nobody proposed at rank 0.
+            height(3, vec![proposal(1, "b", "b3")], Some("b3")),
+            // Not decided yet, so not counted.
+            height(4, vec![proposal(0, "b", "b4")], None),
+        ];
+
+        let summary = summarize(&heights);
+
+        assert_eq!(summary.decided_heights, 3);
+        assert_eq!(summary.heights_finalized_at_rank0, 1);
+        assert_eq!(summary.heights_without_rank0_proposal, 1);
+        assert_eq!(
+            summary.block_makers,
+            vec![
+                BlockMakerStats {
+                    node: "a".to_string(),
+                    rank0_heights: 1,
+                    rank0_proposals: 1,
+                    rank0_finalized: 1,
+                    rank0_failure_rate: 0.0,
+                },
+                BlockMakerStats {
+                    node: "b".to_string(),
+                    rank0_heights: 1,
+                    rank0_proposals: 1,
+                    rank0_finalized: 0,
+                    rank0_failure_rate: 1.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn summarize_attributes_rank0_failures_to_known_block_makers() {
+        let with_block_maker = |mut timeline: HeightTimeline, block_maker: &str| {
+            timeline.rank0_block_maker = Some(block_maker.to_string());
+            timeline
+        };
+        let heights = vec![
+            // Rank 0 by node "a" made it.
+            with_block_maker(height(1, vec![proposal(0, "a", "b1")], Some("b1")), "a"),
+            // Node "b" was the rank-0 block maker but did not propose.
+            with_block_maker(height(2, vec![proposal(1, "a", "b2")], Some("b2")), "b"),
+        ];
+
+        let summary = summarize(&heights);
+
+        assert_eq!(summary.decided_heights, 2);
+        assert_eq!(summary.heights_without_rank0_proposal, 1);
+        assert_eq!(summary.heights_with_known_rank0_block_maker, 2);
+        assert_eq!(
+            summary.block_makers,
+            vec![
+                BlockMakerStats {
+                    node: "a".to_string(),
+                    rank0_heights: 1,
+                    rank0_proposals: 1,
+                    rank0_finalized: 1,
+                    rank0_failure_rate: 0.0,
+                },
+                BlockMakerStats {
+                    node: "b".to_string(),
+                    rank0_heights: 1,
+                    rank0_proposals: 0,
+                    rank0_finalized: 0,
+                    rank0_failure_rate: 1.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn collect_reads_timeline_from_pools() {
+        use ic_logger::replica_logger::no_op_logger;
+        use ic_metrics::MetricsRegistry;
+        use ic_test_artifact_pool::consensus_pool::TestConsensusPool;
+        use ic_test_utilities::{
+            crypto::CryptoReturningOk,
+            state_manager::FakeStateManager,
+            types::ids::{node_test_id, subnet_test_id},
+        };
+        use ic_test_utilities_registry::{setup_registry, SubnetRecordBuilder};
+        use ic_test_utilities_time::FastForwardTimeSource;
+
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            let subnet_id = subnet_test_id(1);
+            let committee = (0..4).map(node_test_id).collect::<Vec<_>>();
+            let registry_client = setup_registry(
+                subnet_id,
+                vec![(
+                    1,
+                    SubnetRecordBuilder::from(&committee)
+                        .with_dkg_interval_length(9)
+                        .build(),
+                )],
+            );
+            let certification_pool = CertificationPoolImpl::new(
+                node_test_id(0),
+                pool_config.clone(),
+                no_op_logger(),
+                MetricsRegistry::new(),
+            );
+            let mut pool = TestConsensusPool::new(
+                node_test_id(0),
+                subnet_id,
+                pool_config,
+                FastForwardTimeSource::new(),
+                registry_client.clone(),
+                Arc::new(CryptoReturningOk::default()),
+                Arc::new(FakeStateManager::new()),
+                None,
+            );
+            // Every height is finalized at rank 0, with a CUP every 10 heights.
+            assert_eq!(pool.advance_round_normal_operation_n(60), Height::from(60));
+            let registry = SubnetRegistryView {
+                registry_client,
+                subnet_id,
+            };
+
+            let timeline = collect(&pool, &certification_pool, Some(&registry), None, None);
+
+            assert_eq!(timeline.heights.len(), 60);
+            assert_eq!(timeline.summary.decided_heights, 60);
+            assert_eq!(timeline.summary.heights_finalized_at_rank0, 60);
+            assert_eq!(timeline.summary.heights_with_known_rank0_block_maker, 60);
+
+            // The rank-0 block maker is the one consensus computes.
+            let membership = Membership::new(
+                pool.get_cache(),
+                registry.registry_client.clone(),
+                subnet_id,
+            );
+            let previous_beacon = pool
+                .validated()
+                .random_beacon()
+                .get_by_height(Height::from(59))
+                .next()
+                .unwrap();
+            let block_maker = membership
+                .get_shuffled_nodes(
+                    Height::from(60),
+                    &previous_beacon,
+                    &RandomnessPurpose::BlockmakerRanking,
+                )
+                .unwrap()[0];
+            let tip = &timeline.heights[59];
+            assert_eq!(tip.rank0_block_maker, Some(block_maker.get().to_string()));
+
+            // Notarization shares are purged below the finalized height, and
+            // certification shares below the latest CUP minus 50 heights.
+            let committee = committee
+                .iter()
+                .map(|node| node.get().to_string())
+                .collect::<BTreeSet<_>>();
+            let missing = |shares: &[String]| shares.iter().cloned().collect::<BTreeSet<_>>();
+            assert!(!tip.notarization_shares_purged);
+            assert_eq!(missing(&tip.missing_notarization_shares), committee);
+            let height_9 = &timeline.heights[8];
+            assert!(height_9.notarization_shares_purged);
+            assert!(height_9.certification_shares_purged);
+            assert!(height_9.missing_certification_shares.is_empty());
+            let height_10 = &timeline.heights[9];
+            assert!(height_10.notarization_shares_purged);
+            assert!(!height_10.certification_shares_purged);
+            assert_eq!(missing(&height_10.missing_certification_shares), committee);
+
+            let text = render_text(&timeline);
+            assert!(text.contains("certified     unknown  shares: unknown (purged)"));
+        })
+    }
+
+    #[test]
+    fn render_shares_lists_missing_signers() {
+        let shares = vec![Share {
+            signer: "4inqb-2zcvk-f6yeo".to_string(),
+            block: "b1".to_string(),
+            arrived_ms: Some(12),
+        }];
+        assert_eq!(
+            render_shares(&shares, &["xyz".to_string()], false),
+            "4inqb-2z +12ms; missing: xyz"
+        );
+    }
+
+    #[test]
+    fn render_shares_reports_purged_shares_as_unknown() {
+        assert_eq!(render_shares(&[], &[], true), "unknown (purged)");
+    }
+}
@@ -81,8 +81,15 @@ impl CkBtcMinterAgent {
         owner: Option<Principal>,
         subaccount: Option<Subaccount>,
     ) -> Result<String, CkBtcMinterAgentError> {
-        self.update("get_btc_address", GetBtcAddressArgs { owner, subaccount })
-            .await
+        self.update(
+            "get_btc_address",
+            GetBtcAddressArgs {
+                owner,
+                subaccount,
+                address_type: None,
+            },
+        )
+        .await
     }
 
     pub async fn get_withdrawal_account(&self) -> Result<Account, CkBtcMinterAgentError> {
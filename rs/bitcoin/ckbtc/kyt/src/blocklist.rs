@@ -342,3 +342,8 @@ pub const BTC_ADDRESS_BLOCKLIST: &[&str] = &[
     "bc1qwa6zu6qhl6wqnlxp642vcf89nptsassle25ulf",
     "bc1qx3e2axj3wsfn0ndtvlwmkghmmgm4583nqg8ngk",
 ];
+
+/// Returns true if the address is on the blocklist.
+pub fn is_blocked(address: &str) -> bool {
+    BTC_ADDRESS_BLOCKLIST.binary_search(&address).is_ok()
+}
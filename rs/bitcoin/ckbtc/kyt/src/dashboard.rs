@@ -11,4 +11,6 @@ pub struct DashboardTemplate {
     pub events: Vec<Event>,
     pub mode: KytMode,
     pub last_api_key_update_date: String,
+    pub quorum: u32,
+    pub blocked_addresses: Vec<String>,
 }
@@ -179,6 +179,7 @@ impl Error {
 
 pub async fn http_call<I: Serialize, O: DeserializeOwned>(
     method: HttpMethod,
+    base_url: &str,
     api_key: String,
     endpoint: String,
     payload: I,
@@ -186,7 +187,7 @@ pub async fn http_call<I: Serialize, O: DeserializeOwned>(
     const KIB: u64 = 1024;
     let payload = serde_json::to_string(&payload).unwrap();
     let request = CanisterHttpRequestArgument {
-        url: format!("https://api.chainalysis.com/api/kyt/{}", endpoint),
+        url: format!("{}/{}", base_url.trim_end_matches('/'), endpoint),
         max_response_bytes: Some(100 * KIB),
         method,
         headers: vec![
@@ -2,6 +2,8 @@ use candid::{CandidType, Deserialize, Principal};
 use serde::Serialize;
 use std::fmt;
 
+pub mod blocklist;
+
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Serialize, Deserialize)]
 pub enum KytMode {
     /// In this mode, the canister will not make any HTTP calls and return empty
@@ -11,6 +13,12 @@ pub enum KytMode {
     RejectAll,
     /// In this mode, the canister will call Chainalysis API for each request.
     Normal,
+    /// In this mode, the canister will not make any HTTP calls and check
+    /// withdrawal addresses against the ckBTC blocklist and the configured
+    /// blocked addresses. Deposits always pass the check because the canister
+    /// cannot learn their source addresses without an analytics provider.
+    /// The canister reports itself as the provider of such checks.
+    LocalBlocklist,
 }
 
 impl fmt::Display for KytMode {
@@ -19,6 +27,7 @@ impl fmt::Display for KytMode {
             KytMode::AcceptAll => write!(f, "AcceptAll"),
             KytMode::RejectAll => write!(f, "RejectAll"),
             KytMode::Normal => write!(f, "Normal"),
+            KytMode::LocalBlocklist => write!(f, "LocalBlocklist"),
         }
     }
 }
@@ -31,11 +40,32 @@ pub struct InitArg {
     pub maintainers: Vec<Principal>,
     /// The mode in which this canister runs.
     pub mode: KytMode,
+    /// The number of distinct providers that must agree on the outcome of a
+    /// check in the normal mode. Defaults to 1.
+    pub quorum: Option<u32>,
+    /// The addresses that the canister reports in the local blocklist mode in
+    /// addition to the ckBTC blocklist.
+    pub blocked_addresses: Option<Vec<String>>,
+}
+
+/// The external service that an API key gives access to.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, CandidType, Serialize, Deserialize,
+)]
+pub enum ProviderKind {
+    /// The Chainalysis KYT API.
+    #[default]
+    Chainalysis,
+    /// A service implementing the Chainalysis KYT v2 API at the given HTTPS
+    /// base URL, e.g. another analytics vendor behind a compatible gateway.
+    ChainalysisCompatible { base_url: String },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
 pub struct SetApiKeyArg {
     pub api_key: String,
+    /// The provider that the API key belongs to. Defaults to Chainalysis.
+    pub provider_kind: Option<ProviderKind>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
@@ -43,6 +73,9 @@ pub struct UpgradeArg {
     pub minter_id: Option<Principal>,
     pub maintainers: Option<Vec<Principal>>,
     pub mode: Option<KytMode>,
+    pub quorum: Option<u32>,
+    /// Replaces the list of blocked addresses.
+    pub blocked_addresses: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
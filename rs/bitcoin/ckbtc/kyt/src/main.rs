@@ -1,33 +1,25 @@
 use candid::Principal;
 use ic_canisters_http_types as http;
-use ic_cdk::api::management_canister::http_request::{HttpMethod, HttpResponse, TransformArgs};
+use ic_cdk::api::management_canister::http_request::{HttpResponse, TransformArgs};
 use ic_cdk_macros::{init, post_upgrade, query, update};
 use ic_ckbtc_kyt::SetApiKeyArg;
 use ic_ckbtc_kyt::{
     Alert, AlertLevel, DepositRequest, Error, ExposureType, FetchAlertsResponse, KytMode,
-    LifecycleArg, WithdrawalAttempt,
+    LifecycleArg, ProviderKind, WithdrawalAttempt,
 };
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory as VM};
 use ic_stable_structures::storable::{Bound, Storable};
 use ic_stable_structures::{DefaultMemoryImpl, RestrictedMemory as RM, StableCell, StableLog};
+use providers::{ChainalysisProvider, KytCheckError, KytProvider, LocalBlocklistProvider};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
 mod dashboard;
 mod json_rpc;
-
-/// The max number of times we poll a summary method before giving up.
-/// The Chainalysis docs says that the processing should take up to 30 seconds:
-///
-/// > For transfers that are valid and KYT can process, the transfer should process within 30 seconds.
-///
-/// In practice, the registration almost always happened instantaneously.
-///
-/// See: https://docs.chainalysis.com/api/kyt/guides/#workflows-polling-the-summary-endpoints
-const MAX_SUMMARY_POLLS: usize = 10;
+mod providers;
 
 /// The number of Wasm pages to use for the canister metadata.
 const METADATA_PAGES: u64 = 16;
@@ -83,6 +75,10 @@ fn default_kyt_mode() -> KytMode {
     KytMode::Normal
 }
 
+fn default_quorum() -> u32 {
+    1
+}
+
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct Config {
     api_keys: BTreeMap<Principal, String>,
@@ -93,6 +89,18 @@ struct Config {
     /// The IC timestamp of the last API key update.
     #[serde(skip_serializing_if = "Option::is_none")]
     last_api_key_update: Option<u64>,
+    /// The provider kinds of the API keys. API keys without an entry belong
+    /// to Chainalysis.
+    #[serde(default)]
+    provider_kinds: BTreeMap<Principal, ProviderKind>,
+    /// The number of distinct provider kinds that must agree on the outcome
+    /// of a check.
+    #[serde(default = "default_quorum")]
+    quorum: u32,
+    /// The addresses blocked in the local blocklist mode in addition to the
+    /// ckBTC blocklist.
+    #[serde(default)]
+    blocked_addresses: BTreeSet<String>,
 }
 
 impl Default for Config {
@@ -103,6 +111,9 @@ impl Default for Config {
             maintainers: vec![],
             mode: default_kyt_mode(),
             last_api_key_update: None,
+            provider_kinds: Default::default(),
+            quorum: default_quorum(),
+            blocked_addresses: Default::default(),
         }
     }
 }
@@ -217,22 +228,15 @@ pub enum EventKind {
         #[serde(rename = "provider")]
         #[serde(skip_serializing_if = "Option::is_none")]
         provider: Option<Principal>,
+
+        #[serde(rename = "provider_kind")]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        provider_kind: Option<ProviderKind>,
     },
     #[serde(rename = "api_key_expired")]
     ApiKeyExpired { provider: Principal },
 }
 
-enum KytCheckError {
-    RpcError(json_rpc::Error),
-    TimedOut(String),
-}
-
-impl From<json_rpc::Error> for KytCheckError {
-    fn from(e: json_rpc::Error) -> Self {
-        Self::RpcError(e)
-    }
-}
-
 thread_local! {
     static MEMORY_MANAGER: MemoryManager<RestrictedMemory> =
         MemoryManager::init(
@@ -355,6 +359,13 @@ fn caller_is_minter() -> Result<(), String> {
     }
 }
 
+fn validate_quorum(quorum: u32) -> u32 {
+    if quorum == 0 {
+        ic_cdk::trap("the quorum must be positive");
+    }
+    quorum
+}
+
 #[init]
 fn init(arg: LifecycleArg) {
     let arg = match arg {
@@ -369,6 +380,9 @@ fn init(arg: LifecycleArg) {
                 maintainers: arg.maintainers,
                 mode: arg.mode,
                 last_api_key_update: Some(ic_cdk::api::time()),
+                provider_kinds: BTreeMap::default(),
+                quorum: validate_quorum(arg.quorum.unwrap_or_else(default_quorum)),
+                blocked_addresses: arg.blocked_addresses.into_iter().flatten().collect(),
             }))
             .expect("failed to initialize the config");
     })
@@ -392,6 +406,12 @@ fn post_upgrade(arg: LifecycleArg) {
         if let Some(mode) = arg.mode {
             config.mode = mode;
         }
+        if let Some(quorum) = arg.quorum {
+            config.quorum = validate_quorum(quorum);
+        }
+        if let Some(blocked_addresses) = arg.blocked_addresses {
+            config.blocked_addresses = blocked_addresses.into_iter().collect();
+        }
 
         cell.borrow_mut()
             .set(config)
@@ -401,10 +421,17 @@ fn post_upgrade(arg: LifecycleArg) {
 
 #[update(guard = "caller_is_maintainer")]
 fn set_api_key(arg: SetApiKeyArg) {
+    let provider_kind = arg.provider_kind.unwrap_or_default();
+    if let ProviderKind::ChainalysisCompatible { base_url } = &provider_kind {
+        if !base_url.starts_with("https://") {
+            ic_cdk::trap("the base URL of a provider must start with https://");
+        }
+    }
     CONFIG_CELL.with(|cell| {
         let caller = ic_cdk::api::caller();
         let mut config = cell.borrow().get().clone();
         config.api_keys.insert(caller, arg.api_key);
+        config.provider_kinds.insert(caller, provider_kind.clone());
         config.last_api_key_update = Some(ic_cdk::api::time());
 
         cell.borrow_mut()
@@ -414,6 +441,7 @@ fn set_api_key(arg: SetApiKeyArg) {
             caller: Some(caller),
             // The provider can only be the caller for now.
             provider: None,
+            provider_kind: Some(provider_kind),
         });
     });
 }
@@ -422,156 +450,238 @@ fn expire_key(provider: Principal) {
     modify_config(|mut config| {
         record_event(EventKind::ApiKeyExpired { provider });
         config.api_keys.remove(&provider);
+        config.provider_kinds.remove(&provider);
         config
     });
 }
 
-async fn get_utxo_alerts(
-    api_key: String,
-    request: DepositRequest,
-) -> Result<(json_rpc::ExternalId, Vec<Alert>), KytCheckError> {
-    let response = http_register_tx(api_key.clone(), request.clone()).await?;
-    let mut ready = response.ready();
-    if !ready {
-        for _ in 0..MAX_SUMMARY_POLLS {
-            ready = http_is_transfer_ready(api_key.clone(), response.external_id.clone()).await?;
-            if ready {
-                break;
+/// Returns the provider kind of the API key registered by `provider`.
+fn provider_kind(
+    provider_kinds: &BTreeMap<Principal, ProviderKind>,
+    provider: &Principal,
+) -> ProviderKind {
+    provider_kinds.get(provider).cloned().unwrap_or_default()
+}
+
+/// A request that the canister forwards to KYT providers.
+enum CheckRequest {
+    Utxo(DepositRequest),
+    Withdrawal(WithdrawalAttempt),
+}
+
+impl CheckRequest {
+    async fn check(
+        &self,
+        provider: &impl KytProvider,
+    ) -> Result<(json_rpc::ExternalId, Vec<Alert>), KytCheckError> {
+        match self {
+            CheckRequest::Utxo(request) => provider.utxo_alerts(request.clone()).await,
+            CheckRequest::Withdrawal(withdrawal) => {
+                provider.withdrawal_alerts(withdrawal.clone()).await
             }
         }
     }
-    if !ready {
-        return Err(KytCheckError::TimedOut(
-            "transfer registration took too long".to_string(),
-        ));
-    }
-    let alerts = http_get_utxo_alerts(api_key, response.external_id.clone()).await?;
-    Ok((response.external_id, alerts))
 }
 
-#[update(guard = "caller_is_minter")]
-async fn fetch_utxo_alerts(request: DepositRequest) -> Result<FetchAlertsResponse, Error> {
-    loop {
-        let (provider, api_key) = pick_api_key()?;
-        let (external_id, alerts) = match kyt_mode() {
-            KytMode::Normal => match get_utxo_alerts(api_key, request.clone()).await {
-                Ok(result) => result,
-                Err(KytCheckError::TimedOut(msg)) => {
-                    return Err(Error::TemporarilyUnavailable(msg))
-                }
-                Err(KytCheckError::RpcError(err)) => {
-                    if err.is_access_denied_error() {
-                        expire_key(provider);
-                        // Try again with a different provider.
-                        continue;
-                    } else {
-                        return Err(Error::TemporarilyUnavailable(err.to_string()));
-                    }
+/// The outcome of a check by a single provider kind.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Verdict {
+    provider: Principal,
+    external_id: json_rpc::ExternalId,
+    alerts: Vec<Alert>,
+}
+
+/// Returns the first verdict of a group of at least `quorum` verdicts that
+/// agree on whether the request deserves alerts. The verdicts must come from
+/// distinct provider kinds.
+fn find_quorum(verdicts: &[Verdict], quorum: u32) -> Option<&Verdict> {
+    let (flagged, clean): (Vec<_>, Vec<_>) = verdicts.iter().partition(|v| !v.alerts.is_empty());
+    [flagged, clean]
+        .into_iter()
+        .find(|group| !group.is_empty() && group.len() >= quorum as usize)
+        .map(|group| group[0])
+}
+
+/// Returns the API keys in the order of the providers, starting from `first`
+/// and wrapping around.
+fn rotate_api_keys(
+    api_keys: &BTreeMap<Principal, String>,
+    first: Principal,
+) -> Vec<(Principal, String)> {
+    api_keys
+        .range(first..)
+        .chain(api_keys.range(..first))
+        .map(|(p, k)| (*p, k.clone()))
+        .collect()
+}
+
+/// Asks the providers to check the request one by one until enough distinct
+/// provider kinds agree on the outcome. The canister skips the providers that
+/// fail and expires the API keys that the providers reject. Once a provider
+/// kind gave a verdict, the canister skips the other API keys of that kind:
+/// several keys of the same service agreeing do not make a check more
+/// reliable.
+///
+/// The canister takes a snapshot of the API keys so that concurrent checks
+/// advancing the round-robin do not make this check skip providers.
+async fn check_with_providers(request: &CheckRequest) -> Result<Verdict, Error> {
+    let (quorum, api_keys, provider_kinds) = CONFIG_CELL.with(|cell| {
+        let config = cell.borrow();
+        (
+            config.get().quorum,
+            config.get().api_keys.clone(),
+            config.get().provider_kinds.clone(),
+        )
+    });
+    let (first_provider, _) = pick_api_key_from(&api_keys)?;
+    let distinct_kinds = api_keys
+        .keys()
+        .map(|provider| provider_kind(&provider_kinds, provider))
+        .collect::<BTreeSet<_>>()
+        .len();
+    if distinct_kinds < quorum as usize {
+        return Err(Error::TemporarilyUnavailable(format!(
+            "the quorum is {} but only {} distinct providers have API keys",
+            quorum, distinct_kinds
+        )));
+    }
+    let mut verdicts = vec![];
+    let mut decided_kinds = BTreeSet::new();
+    let mut last_error = None;
+
+    for (provider, api_key) in rotate_api_keys(&api_keys, first_provider) {
+        // Another check might have expired the key in the meantime.
+        if !CONFIG_CELL.with(|cell| cell.borrow().get().api_keys.contains_key(&provider)) {
+            continue;
+        }
+        let kind = provider_kind(&provider_kinds, &provider);
+        if decided_kinds.contains(&kind) {
+            continue;
+        }
+        match request
+            .check(&ChainalysisProvider::new(&kind, api_key))
+            .await
+        {
+            Ok((external_id, alerts)) => {
+                decided_kinds.insert(kind);
+                verdicts.push(Verdict {
+                    provider,
+                    external_id,
+                    alerts,
+                });
+                if let Some(verdict) = find_quorum(&verdicts, quorum) {
+                    return Ok(verdict.clone());
                 }
-            },
-            KytMode::AcceptAll => (ic_cdk::api::time().to_string(), vec![]),
-            KytMode::RejectAll => (
-                ic_cdk::api::time().to_string(),
-                vec![Alert {
+            }
+            Err(KytCheckError::RpcError(e)) if e.is_access_denied_error() => {
+                expire_key(provider);
+            }
+            Err(e) => {
+                last_error = Some(Error::TemporarilyUnavailable(e.to_string()));
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        Error::TemporarilyUnavailable(format!(
+            "fewer than {} providers agreed on the outcome",
+            quorum
+        ))
+    }))
+}
+
+async fn fetch_alerts(request: &CheckRequest) -> Result<Verdict, Error> {
+    match kyt_mode() {
+        KytMode::Normal => check_with_providers(request).await,
+        KytMode::AcceptAll => {
+            let (provider, _) = pick_api_key()?;
+            Ok(Verdict {
+                provider,
+                external_id: ic_cdk::api::time().to_string(),
+                alerts: vec![],
+            })
+        }
+        KytMode::RejectAll => {
+            let (provider, _) = pick_api_key()?;
+            Ok(Verdict {
+                provider,
+                external_id: ic_cdk::api::time().to_string(),
+                alerts: vec![Alert {
                     level: AlertLevel::Severe,
                     category: None,
                     service: None,
                     exposure_type: ExposureType::Direct,
                 }],
-            ),
-        };
-
-        UTXO_CHECKS_COUNT.with(|c| c.set(c.get() + 1));
-
-        record_event(EventKind::UtxoCheck {
-            txid: request.txid,
-            vout: request.vout,
-            caller: Some(request.caller),
-            alerts: alerts.clone(),
-            external_id: external_id.clone(),
-        });
-        return Ok(FetchAlertsResponse {
-            external_id,
-            alerts,
-            provider,
-        });
+            })
+        }
+        KytMode::LocalBlocklist => {
+            let blocked_addresses =
+                CONFIG_CELL.with(|cell| cell.borrow().get().blocked_addresses.clone());
+            let (external_id, alerts) = request
+                .check(&LocalBlocklistProvider {
+                    blocked_addresses: &blocked_addresses,
+                })
+                .await
+                .map_err(|e| Error::TemporarilyUnavailable(e.to_string()))?;
+            Ok(Verdict {
+                // There is no external provider to pay for the check.
+                provider: ic_cdk::id(),
+                external_id,
+                alerts,
+            })
+        }
     }
 }
 
-async fn get_withdrawal_alerts(
-    api_key: String,
-    withdrawal: WithdrawalAttempt,
-) -> Result<(json_rpc::ExternalId, Vec<Alert>), KytCheckError> {
-    let response = http_register_withdrawal(api_key.clone(), withdrawal.clone()).await?;
-    let mut ready = response.ready();
-    if !ready {
-        for _ in 0..MAX_SUMMARY_POLLS {
-            ready = http_is_withdrawal_ready(api_key.clone(), response.external_id.clone()).await?;
-            if ready {
-                break;
-            }
-        }
-    }
-    if !ready {
-        return Err(KytCheckError::TimedOut(
-            "withdrawal registration took too long".to_string(),
-        ));
-    }
-    let alerts = http_get_withdrawal_alerts(api_key, response.external_id.clone()).await?;
-    Ok((response.external_id, alerts))
+#[update(guard = "caller_is_minter")]
+async fn fetch_utxo_alerts(request: DepositRequest) -> Result<FetchAlertsResponse, Error> {
+    let Verdict {
+        provider,
+        external_id,
+        alerts,
+    } = fetch_alerts(&CheckRequest::Utxo(request.clone())).await?;
+
+    UTXO_CHECKS_COUNT.with(|c| c.set(c.get() + 1));
+
+    record_event(EventKind::UtxoCheck {
+        txid: request.txid,
+        vout: request.vout,
+        caller: Some(request.caller),
+        alerts: alerts.clone(),
+        external_id: external_id.clone(),
+    });
+    Ok(FetchAlertsResponse {
+        external_id,
+        alerts,
+        provider,
+    })
 }
 
 #[update(guard = "caller_is_minter")]
 async fn fetch_withdrawal_alerts(
     withdrawal: WithdrawalAttempt,
 ) -> Result<FetchAlertsResponse, Error> {
-    loop {
-        let (provider, api_key) = pick_api_key()?;
-
-        let (external_id, alerts) = match kyt_mode() {
-            KytMode::Normal => match get_withdrawal_alerts(api_key, withdrawal.clone()).await {
-                Ok(result) => result,
-                Err(KytCheckError::TimedOut(msg)) => {
-                    return Err(Error::TemporarilyUnavailable(msg))
-                }
-                Err(KytCheckError::RpcError(e)) => {
-                    if e.is_access_denied_error() {
-                        expire_key(provider);
-                        // Try again with a different provider.
-                        continue;
-                    } else {
-                        return Err(Error::TemporarilyUnavailable(e.to_string()));
-                    }
-                }
-            },
-            KytMode::AcceptAll => (ic_cdk::api::time().to_string(), vec![]),
-            KytMode::RejectAll => (
-                ic_cdk::api::time().to_string(),
-                vec![Alert {
-                    level: AlertLevel::Severe,
-                    service: None,
-                    category: None,
-                    exposure_type: ExposureType::Direct,
-                }],
-            ),
-        };
-
-        ADDRESS_CHECKS_COUNT.with(|c| c.set(c.get() + 1));
-
-        record_event(EventKind::AddressCheck {
-            caller: Some(withdrawal.caller),
-            withdrawal_id: withdrawal.id,
-            address: withdrawal.address,
-            amount: withdrawal.amount,
-            alerts: alerts.clone(),
-            external_id: external_id.clone(),
-        });
-        return Ok(FetchAlertsResponse {
-            external_id,
-            alerts,
-            provider,
-        });
-    }
+    let Verdict {
+        provider,
+        external_id,
+        alerts,
+    } = fetch_alerts(&CheckRequest::Withdrawal(withdrawal.clone())).await?;
+
+    ADDRESS_CHECKS_COUNT.with(|c| c.set(c.get() + 1));
+
+    record_event(EventKind::AddressCheck {
+        caller: Some(withdrawal.caller),
+        withdrawal_id: withdrawal.id,
+        address: withdrawal.address,
+        amount: withdrawal.amount,
+        alerts: alerts.clone(),
+        external_id: external_id.clone(),
+    });
+    Ok(FetchAlertsResponse {
+        external_id,
+        alerts,
+        provider,
+    })
 }
 
 #[query]
@@ -681,6 +791,8 @@ fn http_request(req: http::HttpRequest) -> http::HttpResponse {
                 config.last_api_key_update.unwrap_or_default(),
             ),
             mode: config.mode,
+            quorum: config.quorum,
+            blocked_addresses: config.blocked_addresses.into_iter().collect(),
         }
         .render()
         .unwrap();
@@ -693,118 +805,6 @@ fn http_request(req: http::HttpRequest) -> http::HttpResponse {
     }
 }
 
-async fn http_register_tx(
-    api_key: String,
-    req: DepositRequest,
-) -> Result<json_rpc::RegisterTransferResponse, json_rpc::Error> {
-    let response: json_rpc::RegisterTransferResponse = json_rpc::http_call(
-        HttpMethod::POST,
-        api_key,
-        format!("v2/users/{}/transfers", req.caller),
-        json_rpc::RegisterTransferRequest {
-            network: json_rpc::Network::Bitcoin,
-            asset: json_rpc::Asset::Btc,
-            transfer_reference: format!("{}:{}", DisplayTxid(&req.txid), req.vout),
-            direction: json_rpc::Direction::Received,
-        },
-    )
-    .await
-    .expect("failed to register transfer")?;
-    Ok(response)
-}
-
-async fn http_is_transfer_ready(
-    api_key: String,
-    external_id: json_rpc::ExternalId,
-) -> Result<bool, json_rpc::Error> {
-    let response: json_rpc::TransferSummaryResponse = json_rpc::http_call(
-        HttpMethod::GET,
-        api_key,
-        format!("v2/transfers/{}", external_id),
-        json_rpc::GetSummaryRequest { external_id },
-    )
-    .await
-    .expect("failed to get a transfer summary")?;
-
-    Ok(response.updated_at.is_some())
-}
-
-async fn http_get_utxo_alerts(
-    api_key: String,
-    external_id: json_rpc::ExternalId,
-) -> Result<Vec<Alert>, json_rpc::Error> {
-    let response: json_rpc::GetAlertsResponse = json_rpc::http_call(
-        HttpMethod::GET,
-        api_key,
-        format!("v2/transfers/{}/alerts", external_id),
-        json_rpc::GetAlertsRequest { external_id },
-    )
-    .await
-    .expect("failed to fetch alerts")?;
-    Ok(response
-        .alerts
-        .into_iter()
-        .map(json_alert_to_candid)
-        .collect())
-}
-
-async fn http_register_withdrawal(
-    api_key: String,
-    withdrawal: WithdrawalAttempt,
-) -> Result<json_rpc::RegisterWithdrawalResponse, json_rpc::Error> {
-    let response: json_rpc::RegisterWithdrawalResponse = json_rpc::http_call(
-        HttpMethod::POST,
-        api_key,
-        format!("v2/users/{}/withdrawal-attempts", withdrawal.caller),
-        json_rpc::RegisterWithdrawalRequest {
-            network: json_rpc::Network::Bitcoin,
-            asset: json_rpc::Asset::Btc,
-            attempt_identifier: withdrawal.id,
-            asset_amount: withdrawal.amount as f64 / 1e8,
-            address: withdrawal.address,
-            attempt_timestamp: format_timestamp(withdrawal.timestamp_nanos),
-        },
-    )
-    .await
-    .expect("failed to register a withdrawal")?;
-    Ok(response)
-}
-
-async fn http_is_withdrawal_ready(
-    api_key: String,
-    external_id: json_rpc::ExternalId,
-) -> Result<bool, json_rpc::Error> {
-    let response: json_rpc::WithdrawalSummaryResponse = json_rpc::http_call(
-        HttpMethod::GET,
-        api_key,
-        format!("v2/withdrawal-attempts/{}", external_id),
-        json_rpc::GetSummaryRequest { external_id },
-    )
-    .await
-    .expect("failed to get a transfer summary")?;
-
-    Ok(response.updated_at.is_some())
-}
-
-async fn http_get_withdrawal_alerts(
-    api_key: String,
-    external_id: json_rpc::ExternalId,
-) -> Result<Vec<Alert>, json_rpc::Error> {
-    let response: json_rpc::GetAlertsResponse = json_rpc::http_call(
-        HttpMethod::GET,
-        api_key,
-        format!("v2/withdrawal-attempts/{}/alerts", external_id),
-        json_rpc::GetAlertsRequest { external_id },
-    )
-    .await
-    .expect("failed to fetch alerts")?;
-    Ok(response
-        .alerts
-        .into_iter()
-        .map(json_alert_to_candid)
-        .collect())
-}
-
 fn format_timestamp(ts_nanos: u64) -> String {
     let dt_offset = time::OffsetDateTime::from_unix_timestamp_nanos(ts_nanos as i128).unwrap();
     // 2020-12-09T17:25:40+00:00
@@ -814,23 +814,6 @@ fn format_timestamp(ts_nanos: u64) -> String {
     dt_offset.format(&format).unwrap()
 }
 
-fn json_alert_to_candid(alert: json_rpc::Alert) -> Alert {
-    Alert {
-        level: match alert.alert_level {
-            json_rpc::AlertLevel::Severe => AlertLevel::Severe,
-            json_rpc::AlertLevel::High => AlertLevel::High,
-            json_rpc::AlertLevel::Medium => AlertLevel::Medium,
-            json_rpc::AlertLevel::Low => AlertLevel::Low,
-        },
-        category: alert.category,
-        service: alert.service,
-        exposure_type: match alert.exposure_type {
-            json_rpc::ExposureType::Direct => ExposureType::Direct,
-            json_rpc::ExposureType::Indirect => ExposureType::Indirect,
-        },
-    }
-}
-
 fn main() {}
 
 #[test]
@@ -855,6 +838,64 @@ fn test_key_rotation() {
     assert!(result.is_err(), "expected an error, got: {:?}", result);
 }
 
+#[test]
+fn test_rotate_api_keys() {
+    let p1 = Principal::management_canister();
+    let p2 = Principal::from_slice(&[1]);
+    let p3 = Principal::anonymous();
+    let m = BTreeMap::from([
+        (p1, "A".to_string()),
+        (p2, "B".to_string()),
+        (p3, "C".to_string()),
+    ]);
+
+    let providers = |first| {
+        rotate_api_keys(&m, first)
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(providers(p1), vec![p1, p2, p3]);
+    assert_eq!(providers(p2), vec![p2, p3, p1]);
+    assert_eq!(providers(p3), vec![p3, p1, p2]);
+}
+
+#[test]
+fn test_find_quorum() {
+    fn verdict(provider: Principal, alerts: usize) -> Verdict {
+        Verdict {
+            provider,
+            external_id: provider.to_string(),
+            alerts: vec![
+                Alert {
+                    level: AlertLevel::High,
+                    category: None,
+                    service: None,
+                    exposure_type: ExposureType::Indirect,
+                };
+                alerts
+            ],
+        }
+    }
+
+    let p1 = Principal::management_canister();
+    let p2 = Principal::anonymous();
+    let p3 = Principal::from_slice(&[1]);
+
+    assert_eq!(find_quorum(&[], 1), None);
+    assert_eq!(find_quorum(&[verdict(p1, 0)], 1), Some(&verdict(p1, 0)));
+    assert_eq!(find_quorum(&[verdict(p1, 0)], 2), None);
+    assert_eq!(find_quorum(&[verdict(p1, 1), verdict(p2, 0)], 2), None);
+    assert_eq!(
+        find_quorum(&[verdict(p1, 1), verdict(p2, 0), verdict(p3, 2)], 2),
+        Some(&verdict(p1, 1))
+    );
+    assert_eq!(
+        find_quorum(&[verdict(p1, 1), verdict(p2, 0), verdict(p3, 0)], 2),
+        Some(&verdict(p2, 0))
+    );
+}
+
 #[test]
 fn check_candid_interface_compatibility() {
     use candid_parser::utils::{service_equal, CandidSource};
@@ -0,0 +1,342 @@
+//! Services that tell whether Bitcoin transfers are linked to illicit activity.
+
+use crate::{format_timestamp, json_rpc, DisplayTxid};
+use ic_cdk::api::management_canister::http_request::HttpMethod;
+use ic_ckbtc_kyt::{
+    Alert, AlertLevel, DepositRequest, ExposureType, ProviderKind, WithdrawalAttempt,
+};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The max number of times we poll a summary method before giving up.
+/// The Chainalysis docs says that the processing should take up to 30 seconds:
+///
+/// > For transfers that are valid and KYT can process, the transfer should process within 30 seconds.
+///
+/// In practice, the registration almost always happened instantaneously.
+///
+/// See: https://docs.chainalysis.com/api/kyt/guides/#workflows-polling-the-summary-endpoints
+const MAX_SUMMARY_POLLS: usize = 10;
+
+/// The base URL of the Chainalysis KYT API.
+const CHAINALYSIS_BASE_URL: &str = "https://api.chainalysis.com/api/kyt";
+
+pub enum KytCheckError {
+    RpcError(json_rpc::Error),
+    TimedOut(String),
+}
+
+impl From<json_rpc::Error> for KytCheckError {
+    fn from(e: json_rpc::Error) -> Self {
+        Self::RpcError(e)
+    }
+}
+
+impl fmt::Display for KytCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KytCheckError::RpcError(e) => write!(f, "{}", e),
+            KytCheckError::TimedOut(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// A service checking deposits and withdrawals.
+pub(crate) trait KytProvider {
+    /// Returns the external id of the check and the list of alerts for the
+    /// specified deposit.
+    async fn utxo_alerts(
+        &self,
+        request: DepositRequest,
+    ) -> Result<(json_rpc::ExternalId, Vec<Alert>), KytCheckError>;
+
+    /// Returns the external id of the check and the list of alerts for the
+    /// specified withdrawal attempt.
+    async fn withdrawal_alerts(
+        &self,
+        withdrawal: WithdrawalAttempt,
+    ) -> Result<(json_rpc::ExternalId, Vec<Alert>), KytCheckError>;
+}
+
+/// The Chainalysis KYT API, or a service implementing it, accessed through
+/// HTTP outcalls.
+pub struct ChainalysisProvider {
+    pub api_key: String,
+    pub base_url: String,
+}
+
+impl ChainalysisProvider {
+    pub fn new(kind: &ProviderKind, api_key: String) -> Self {
+        let base_url = match kind {
+            ProviderKind::Chainalysis => CHAINALYSIS_BASE_URL.to_string(),
+            ProviderKind::ChainalysisCompatible { base_url } => base_url.clone(),
+        };
+        Self { api_key, base_url }
+    }
+}
+
+impl KytProvider for ChainalysisProvider {
+    async fn utxo_alerts(
+        &self,
+        request: DepositRequest,
+    ) -> Result<(json_rpc::ExternalId, Vec<Alert>), KytCheckError> {
+        let (base_url, api_key) = (&self.base_url, self.api_key.clone());
+        let response = http_register_tx(base_url, api_key.clone(), request).await?;
+        let mut ready = response.ready();
+        if !ready {
+            for _ in 0..MAX_SUMMARY_POLLS {
+                ready =
+                    http_is_transfer_ready(base_url, api_key.clone(), response.external_id.clone())
+                        .await?;
+                if ready {
+                    break;
+                }
+            }
+        }
+        if !ready {
+            return Err(KytCheckError::TimedOut(
+                "transfer registration took too long".to_string(),
+            ));
+        }
+        let alerts = http_get_utxo_alerts(base_url, api_key, response.external_id.clone()).await?;
+        Ok((response.external_id, alerts))
+    }
+
+    async fn withdrawal_alerts(
+        &self,
+        withdrawal: WithdrawalAttempt,
+    ) -> Result<(json_rpc::ExternalId, Vec<Alert>), KytCheckError> {
+        let (base_url, api_key) = (&self.base_url, self.api_key.clone());
+        let response = http_register_withdrawal(base_url, api_key.clone(), withdrawal).await?;
+        let mut ready = response.ready();
+        if !ready {
+            for _ in 0..MAX_SUMMARY_POLLS {
+                ready = http_is_withdrawal_ready(
+                    base_url,
+                    api_key.clone(),
+                    response.external_id.clone(),
+                )
+                .await?;
+                if ready {
+                    break;
+                }
+            }
+        }
+        if !ready {
+            return Err(KytCheckError::TimedOut(
+                "withdrawal registration took too long".to_string(),
+            ));
+        }
+        let alerts =
+            http_get_withdrawal_alerts(base_url, api_key, response.external_id.clone()).await?;
+        Ok((response.external_id, alerts))
+    }
+}
+
+/// Checks addresses against the ckBTC blocklist and a configurable list of
+/// blocked addresses without making any HTTP calls.
+pub struct LocalBlocklistProvider<'a> {
+    pub blocked_addresses: &'a BTreeSet<String>,
+}
+
+impl LocalBlocklistProvider<'_> {
+    /// Returns a severe alert if the address is blocked.
+    pub fn address_alerts(&self, address: &str) -> Vec<Alert> {
+        let address = address.trim();
+        if ic_ckbtc_kyt::blocklist::is_blocked(address) || self.blocked_addresses.contains(address)
+        {
+            vec![Alert {
+                level: AlertLevel::Severe,
+                category: Some("blocked address".to_string()),
+                service: None,
+                exposure_type: ExposureType::Direct,
+            }]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl KytProvider for LocalBlocklistProvider<'_> {
+    async fn utxo_alerts(
+        &self,
+        request: DepositRequest,
+    ) -> Result<(json_rpc::ExternalId, Vec<Alert>), KytCheckError> {
+        Ok((
+            format!("{}:{}", DisplayTxid(&request.txid), request.vout),
+            vec![],
+        ))
+    }
+
+    async fn withdrawal_alerts(
+        &self,
+        withdrawal: WithdrawalAttempt,
+    ) -> Result<(json_rpc::ExternalId, Vec<Alert>), KytCheckError> {
+        Ok((withdrawal.id, self.address_alerts(&withdrawal.address)))
+    }
+}
+
+async fn http_register_tx(
+    base_url: &str,
+    api_key: String,
+    req: DepositRequest,
+) -> Result<json_rpc::RegisterTransferResponse, json_rpc::Error> {
+    let response: json_rpc::RegisterTransferResponse = json_rpc::http_call(
+        HttpMethod::POST,
+        base_url,
+        api_key,
+        format!("v2/users/{}/transfers", req.caller),
+        json_rpc::RegisterTransferRequest {
+            network: json_rpc::Network::Bitcoin,
+            asset: json_rpc::Asset::Btc,
+            transfer_reference: format!("{}:{}", DisplayTxid(&req.txid), req.vout),
+            direction: json_rpc::Direction::Received,
+        },
+    )
+    .await
+    .expect("failed to register transfer")?;
+    Ok(response)
+}
+
+async fn http_is_transfer_ready(
+    base_url: &str,
+    api_key: String,
+    external_id: json_rpc::ExternalId,
+) -> Result<bool, json_rpc::Error> {
+    let response: json_rpc::TransferSummaryResponse = json_rpc::http_call(
+        HttpMethod::GET,
+        base_url,
+        api_key,
+        format!("v2/transfers/{}", external_id),
+        json_rpc::GetSummaryRequest { external_id },
+    )
+    .await
+    .expect("failed to get a transfer summary")?;
+
+    Ok(response.updated_at.is_some())
+}
+
+async fn http_get_utxo_alerts(
+    base_url: &str,
+    api_key: String,
+    external_id: json_rpc::ExternalId,
+) -> Result<Vec<Alert>, json_rpc::Error> {
+    let response: json_rpc::GetAlertsResponse = json_rpc::http_call(
+        HttpMethod::GET,
+        base_url,
+        api_key,
+        format!("v2/transfers/{}/alerts", external_id),
+        json_rpc::GetAlertsRequest { external_id },
+    )
+    .await
+    .expect("failed to fetch alerts")?;
+    Ok(response
+        .alerts
+        .into_iter()
+        .map(json_alert_to_candid)
+        .collect())
+}
+
+async fn http_register_withdrawal(
+    base_url: &str,
+    api_key: String,
+    withdrawal: WithdrawalAttempt,
+) -> Result<json_rpc::RegisterWithdrawalResponse, json_rpc::Error> {
+    let response: json_rpc::RegisterWithdrawalResponse = json_rpc::http_call(
+        HttpMethod::POST,
+        base_url,
+        api_key,
+        format!("v2/users/{}/withdrawal-attempts", withdrawal.caller),
+        json_rpc::RegisterWithdrawalRequest {
+            network: json_rpc::Network::Bitcoin,
+            asset: json_rpc::Asset::Btc,
+            attempt_identifier: withdrawal.id,
+            asset_amount: withdrawal.amount as f64 / 1e8,
+            address: withdrawal.address,
+            attempt_timestamp: format_timestamp(withdrawal.timestamp_nanos),
+        },
+    )
+    .await
+    .expect("failed to register a withdrawal")?;
+    Ok(response)
+}
+
+async fn http_is_withdrawal_ready(
+    base_url: &str,
+    api_key: String,
+    external_id: json_rpc::ExternalId,
+) -> Result<bool, json_rpc::Error> {
+    let response: json_rpc::WithdrawalSummaryResponse = json_rpc::http_call(
+        HttpMethod::GET,
+        base_url,
+        api_key,
+        format!("v2/withdrawal-attempts/{}", external_id),
+        json_rpc::GetSummaryRequest { external_id },
+    )
+    .await
+    .expect("failed to get a transfer summary")?;
+
+    Ok(response.updated_at.is_some())
+}
+
+async fn http_get_withdrawal_alerts(
+    base_url: &str,
+    api_key: String,
+    external_id: json_rpc::ExternalId,
+) -> Result<Vec<Alert>, json_rpc::Error> {
+    let response: json_rpc::GetAlertsResponse = json_rpc::http_call(
+        HttpMethod::GET,
+        base_url,
+        api_key,
+        format!("v2/withdrawal-attempts/{}/alerts", external_id),
+        json_rpc::GetAlertsRequest { external_id },
+    )
+    .await
+    .expect("failed to fetch alerts")?;
+    Ok(response
+        .alerts
+        .into_iter()
+        .map(json_alert_to_candid)
+        .collect())
+}
+
+fn json_alert_to_candid(alert: json_rpc::Alert) -> Alert {
+    Alert {
+        level: match alert.alert_level {
+            json_rpc::AlertLevel::Severe => AlertLevel::Severe,
+            json_rpc::AlertLevel::High => AlertLevel::High,
+            json_rpc::AlertLevel::Medium => AlertLevel::Medium,
+            json_rpc::AlertLevel::Low => AlertLevel::Low,
+        },
+        category: alert.category,
+        service: alert.service,
+        exposure_type: match alert.exposure_type {
+            json_rpc::ExposureType::Direct => ExposureType::Direct,
+            json_rpc::ExposureType::Indirect => ExposureType::Indirect,
+        },
+    }
+}
+
+#[test]
+fn test_local_blocklist() {
+    let blocked_addresses: BTreeSet<String> =
+        ["bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string()]
+            .into_iter()
+            .collect();
+    let provider = LocalBlocklistProvider {
+        blocked_addresses: &blocked_addresses,
+    };
+
+    assert_eq!(
+        provider.address_alerts("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq")[0].level,
+        AlertLevel::Severe
+    );
+    assert_eq!(
+        provider.address_alerts(ic_ckbtc_kyt::blocklist::BTC_ADDRESS_BLOCKLIST[0])[0].level,
+        AlertLevel::Severe
+    );
+    assert_eq!(
+        provider.address_alerts("bc1q34aq5drpuwy3wgl9lhup9892qp6svr8ldzyy7c"),
+        vec![]
+    );
+}
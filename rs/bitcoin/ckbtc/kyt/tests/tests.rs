@@ -2,7 +2,7 @@ use assert_matches::assert_matches;
 use candid::{Decode, Encode, Principal};
 use ic_ckbtc_kyt::{
     Alert, AlertLevel, DepositRequest, Error as KytError, ExposureType, FetchAlertsResponse,
-    InitArg, KytMode, LifecycleArg, SetApiKeyArg,
+    InitArg, KytMode, LifecycleArg, ProviderKind, SetApiKeyArg, UpgradeArg, WithdrawalAttempt,
 };
 use ic_state_machine_tests::{
     CanisterHttpRequestContext, CanisterHttpResponsePayload, Cycles, IngressState, IngressStatus,
@@ -11,6 +11,7 @@ use ic_state_machine_tests::{
 use ic_test_utilities_load_wasm::load_wasm;
 
 const MAX_TICKS: usize = 10;
+const CHAINALYSIS_BASE_URL: &str = "https://api.chainalysis.com/api/kyt";
 
 fn assert_has_header(req: &CanisterHttpRequestContext, name: &str, value: &str) {
     assert!(req
@@ -48,6 +49,79 @@ fn tick_until_next_request(env: &StateMachine) {
     );
 }
 
+fn respond_to_transfer_check(
+    env: &StateMachine,
+    api_key: &str,
+    external_id: &str,
+    alerts: &'static [u8],
+) {
+    respond_to_transfer_check_at(env, CHAINALYSIS_BASE_URL, api_key, external_id, alerts)
+}
+
+fn respond_to_transfer_check_at(
+    env: &StateMachine,
+    base_url: &str,
+    api_key: &str,
+    external_id: &str,
+    alerts: &'static [u8],
+) {
+    tick_until_next_request(env);
+
+    handle_http_call("register transfer", env, |req| {
+        assert_has_header(req, "Token", api_key);
+        assert!(
+            req.url.starts_with(base_url),
+            "expected a call to {}, got: {:?}",
+            base_url,
+            req
+        );
+        assert!(
+            req.url.ends_with("/transfers"),
+            "expected a transfer registration, got: {:?}",
+            req
+        );
+        CanisterHttpResponsePayload {
+            status: 200,
+            headers: vec![],
+            body: format!(
+                r#"{{"externalId": "{}", "updatedAt": "2023-03-02T15:23:27+00:00", "transferReference":"0000000000000000000000000000000000000000000000000000000000000000:0"}}"#,
+                external_id
+            )
+            .into_bytes(),
+        }
+    });
+
+    tick_until_next_request(env);
+
+    handle_http_call("fetch alerts", env, |req| {
+        assert_has_header(req, "Token", api_key);
+        assert!(
+            req.url.starts_with(base_url),
+            "expected a call to {}, got: {:?}",
+            base_url,
+            req
+        );
+        assert!(
+            req.url
+                .ends_with(&format!("/v2/transfers/{}/alerts", external_id)),
+            "expected a call to fetch alerts, got: {:?}",
+            req
+        );
+        CanisterHttpResponsePayload {
+            status: 200,
+            headers: vec![],
+            body: alerts.to_vec(),
+        }
+    });
+}
+
+fn decode_fetch_alerts_result(result: WasmResult) -> Result<FetchAlertsResponse, KytError> {
+    match &result {
+        WasmResult::Reply(bytes) => Decode!(bytes, Result<FetchAlertsResponse, KytError>).unwrap(),
+        WasmResult::Reject(msg) => panic!("unexpected reject: {}", msg),
+    }
+}
+
 fn kyt_wasm() -> Vec<u8> {
     load_wasm(
         std::env::var("CARGO_MANIFEST_DIR").unwrap(),
@@ -70,6 +144,8 @@ fn test_key_recovery() {
                 minter_id,
                 maintainers: vec![p1, p2],
                 mode: KytMode::Normal,
+                quorum: None,
+                blocked_addresses: None,
             }))
             .unwrap(),
             None,
@@ -82,7 +158,8 @@ fn test_key_recovery() {
         kyt,
         "set_api_key",
         Encode!(&SetApiKeyArg {
-            api_key: "Key1".to_string()
+            api_key: "Key1".to_string(),
+            provider_kind: None,
         })
         .unwrap(),
     )
@@ -93,7 +170,8 @@ fn test_key_recovery() {
         kyt,
         "set_api_key",
         Encode!(&SetApiKeyArg {
-            api_key: "Key2".to_string()
+            api_key: "Key2".to_string(),
+            provider_kind: None,
         })
         .unwrap(),
     )
@@ -199,3 +277,313 @@ fn test_key_recovery() {
         WasmResult::Reject(msg) => panic!("unexpected reject: {}", msg),
     }
 }
+
+#[test]
+fn test_quorum() {
+    let env = StateMachine::new();
+    let p1 = Principal::management_canister();
+    let p2 = Principal::from_slice(&[1]);
+    let p3 = Principal::anonymous();
+    let minter_id = Principal::anonymous();
+    const PROVIDER_A_URL: &str = "https://kyt.provider-a.example.com/api/kyt";
+    const PROVIDER_B_URL: &str = "https://kyt.provider-b.example.com/api/kyt";
+
+    let kyt = env
+        .install_canister_with_cycles(
+            kyt_wasm(),
+            Encode!(&LifecycleArg::InitArg(InitArg {
+                minter_id,
+                maintainers: vec![p1, p2, p3],
+                mode: KytMode::Normal,
+                quorum: Some(2),
+                blocked_addresses: None,
+            }))
+            .unwrap(),
+            None,
+            Cycles::from(100_000_000_000_000u64),
+        )
+        .expect("failed to install the KYT canister");
+
+    for (maintainer, api_key, provider_kind) in [
+        (p1, "Key1", ProviderKind::Chainalysis),
+        (
+            p2,
+            "Key2",
+            ProviderKind::ChainalysisCompatible {
+                base_url: PROVIDER_A_URL.to_string(),
+            },
+        ),
+        (
+            p3,
+            "Key3",
+            ProviderKind::ChainalysisCompatible {
+                base_url: PROVIDER_B_URL.to_string(),
+            },
+        ),
+    ] {
+        env.execute_ingress_as(
+            maintainer.into(),
+            kyt,
+            "set_api_key",
+            Encode!(&SetApiKeyArg {
+                api_key: api_key.to_string(),
+                provider_kind: Some(provider_kind),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+    }
+
+    let call_id = env.send_ingress(
+        minter_id.into(),
+        kyt,
+        "fetch_utxo_alerts",
+        Encode!(&DepositRequest {
+            caller: minter_id,
+            txid: [0; 32],
+            vout: 0
+        })
+        .unwrap(),
+    );
+
+    const HIGH_ALERT: &[u8] = br#"{"alerts": [{"alertLevel": "HIGH", "category": "C", "service": "S", "exposureType": "DIRECT"}]}"#;
+
+    respond_to_transfer_check_at(&env, CHAINALYSIS_BASE_URL, "Key1", "id-1", HIGH_ALERT);
+    respond_to_transfer_check_at(&env, PROVIDER_A_URL, "Key2", "id-2", br#"{"alerts": []}"#);
+    // The first two providers disagree, so the canister asks the third one.
+    respond_to_transfer_check_at(&env, PROVIDER_B_URL, "Key3", "id-3", HIGH_ALERT);
+
+    let result = env
+        .await_ingress(call_id, /*max_ticks=*/ MAX_TICKS)
+        .expect("the fetch request didn't finish");
+
+    assert_eq!(
+        decode_fetch_alerts_result(result),
+        Ok(FetchAlertsResponse {
+            external_id: "id-1".to_string(),
+            provider: p1,
+            alerts: vec![Alert {
+                level: AlertLevel::High,
+                category: Some("C".to_string()),
+                service: Some("S".to_string()),
+                exposure_type: ExposureType::Direct,
+            }],
+        })
+    );
+}
+
+#[test]
+fn should_count_api_keys_of_the_same_provider_once() {
+    let env = StateMachine::new();
+    let p1 = Principal::management_canister();
+    let p2 = Principal::from_slice(&[1]);
+    let minter_id = Principal::anonymous();
+
+    let kyt = env
+        .install_canister_with_cycles(
+            kyt_wasm(),
+            Encode!(&LifecycleArg::InitArg(InitArg {
+                minter_id,
+                maintainers: vec![p1, p2],
+                mode: KytMode::Normal,
+                quorum: None,
+                blocked_addresses: None,
+            }))
+            .unwrap(),
+            None,
+            Cycles::from(100_000_000_000_000u64),
+        )
+        .expect("failed to install the KYT canister");
+
+    for (maintainer, api_key) in [(p1, "Key1"), (p2, "Key2")] {
+        env.execute_ingress_as(
+            maintainer.into(),
+            kyt,
+            "set_api_key",
+            Encode!(&SetApiKeyArg {
+                api_key: api_key.to_string(),
+                provider_kind: Some(ProviderKind::Chainalysis),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+    }
+
+    env.upgrade_canister(
+        kyt,
+        kyt_wasm(),
+        Encode!(&LifecycleArg::UpgradeArg(UpgradeArg {
+            minter_id: None,
+            maintainers: None,
+            mode: None,
+            quorum: Some(2),
+            blocked_addresses: None,
+        }))
+        .unwrap(),
+    )
+    .expect("failed to upgrade the KYT canister");
+
+    // Both API keys query Chainalysis, so they cannot reach a quorum of two.
+    let result = env
+        .execute_ingress_as(
+            minter_id.into(),
+            kyt,
+            "fetch_utxo_alerts",
+            Encode!(&DepositRequest {
+                caller: minter_id,
+                txid: [0; 32],
+                vout: 0
+            })
+            .unwrap(),
+        )
+        .expect("failed to call fetch_utxo_alerts");
+    assert_matches!(
+        decode_fetch_alerts_result(result),
+        Err(KytError::TemporarilyUnavailable(msg)) if msg.contains("only 1 distinct providers")
+    );
+    assert!(env.canister_http_request_contexts().is_empty());
+}
+
+#[test]
+fn should_rotate_providers_between_checks() {
+    let env = StateMachine::new();
+    let p1 = Principal::management_canister();
+    let p2 = Principal::from_slice(&[1]);
+    let minter_id = Principal::anonymous();
+
+    let kyt = env
+        .install_canister_with_cycles(
+            kyt_wasm(),
+            Encode!(&LifecycleArg::InitArg(InitArg {
+                minter_id,
+                maintainers: vec![p1, p2],
+                mode: KytMode::Normal,
+                quorum: None,
+                blocked_addresses: None,
+            }))
+            .unwrap(),
+            None,
+            Cycles::from(100_000_000_000_000u64),
+        )
+        .expect("failed to install the KYT canister");
+
+    for (maintainer, api_key) in [(p1, "Key1"), (p2, "Key2")] {
+        env.execute_ingress_as(
+            maintainer.into(),
+            kyt,
+            "set_api_key",
+            Encode!(&SetApiKeyArg {
+                api_key: api_key.to_string(),
+                provider_kind: None,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+    }
+
+    for (vout, provider, api_key) in [(0, p1, "Key1"), (1, p2, "Key2"), (2, p1, "Key1")] {
+        let external_id = format!("id-{}", vout);
+        let call_id = env.send_ingress(
+            minter_id.into(),
+            kyt,
+            "fetch_utxo_alerts",
+            Encode!(&DepositRequest {
+                caller: minter_id,
+                txid: [0; 32],
+                vout
+            })
+            .unwrap(),
+        );
+        respond_to_transfer_check(&env, api_key, &external_id, br#"{"alerts": []}"#);
+
+        let result = env
+            .await_ingress(call_id, /*max_ticks=*/ MAX_TICKS)
+            .expect("the fetch request didn't finish");
+        assert_eq!(
+            decode_fetch_alerts_result(result),
+            Ok(FetchAlertsResponse {
+                external_id,
+                provider,
+                alerts: vec![],
+            })
+        );
+    }
+}
+
+#[test]
+fn test_local_blocklist_mode() {
+    const BLOCKED_ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+    let env = StateMachine::new();
+    let minter_id = Principal::anonymous();
+
+    let kyt = env
+        .install_canister_with_cycles(
+            kyt_wasm(),
+            Encode!(&LifecycleArg::InitArg(InitArg {
+                minter_id,
+                maintainers: vec![],
+                mode: KytMode::LocalBlocklist,
+                quorum: None,
+                blocked_addresses: Some(vec![BLOCKED_ADDRESS.to_string()]),
+            }))
+            .unwrap(),
+            None,
+            Cycles::from(100_000_000_000_000u64),
+        )
+        .expect("failed to install the KYT canister");
+
+    let check_withdrawal = |address: &str| {
+        let result = env
+            .execute_ingress_as(
+                minter_id.into(),
+                kyt,
+                "fetch_withdrawal_alerts",
+                Encode!(&WithdrawalAttempt {
+                    caller: minter_id,
+                    id: "1".to_string(),
+                    amount: 100_000,
+                    address: address.to_string(),
+                    timestamp_nanos: 0,
+                })
+                .unwrap(),
+            )
+            .expect("failed to fetch withdrawal alerts");
+        assert!(
+            env.canister_http_request_contexts().is_empty(),
+            "the local blocklist mode must not make HTTP calls"
+        );
+        decode_fetch_alerts_result(result).expect("the check failed")
+    };
+
+    let response = check_withdrawal(BLOCKED_ADDRESS);
+    assert_eq!(response.provider, Principal::from(kyt));
+    assert_eq!(response.alerts.len(), 1);
+    assert_eq!(response.alerts[0].level, AlertLevel::Severe);
+
+    let response = check_withdrawal(ic_ckbtc_kyt::blocklist::BTC_ADDRESS_BLOCKLIST[0]);
+    assert_eq!(response.alerts.len(), 1);
+
+    let response = check_withdrawal("bc1q34aq5drpuwy3wgl9lhup9892qp6svr8ldzyy7c");
+    assert_eq!(response.alerts, vec![]);
+
+    let result = env
+        .execute_ingress_as(
+            minter_id.into(),
+            kyt,
+            "fetch_utxo_alerts",
+            Encode!(&DepositRequest {
+                caller: minter_id,
+                txid: [0; 32],
+                vout: 0
+            })
+            .unwrap(),
+        )
+        .expect("failed to fetch UTXO alerts");
+    assert_eq!(
+        decode_fetch_alerts_result(result)
+            .expect("the check failed")
+            .alerts,
+        vec![]
+    );
+}
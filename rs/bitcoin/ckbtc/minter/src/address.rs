@@ -38,6 +38,22 @@ pub enum BitcoinAddress {
     P2sh([u8; 20]),
 }
 
+/// The type of the deposit address that the minter derives for an account.
+#[derive(
+    candid::CandidType, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum DepositAddressType {
+    /// Pay to witness public key hash address.
+    /// See BIP-173.
+    #[default]
+    #[serde(rename = "p2wpkh")]
+    P2wpkh,
+    /// Pay to taproot address that can be spent only using the key path.
+    /// See BIP-86.
+    #[serde(rename = "p2tr")]
+    P2tr,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum WitnessVersion {
     V0 = 0,
@@ -139,6 +155,79 @@ pub fn account_to_bitcoin_address(
     BitcoinAddress::P2wpkhV0(crate::tx::hash160(&pk))
 }
 
+/// Derives a Taproot address for the specified account from the minter's
+/// Schnorr public key and converts it into bech32m textual representation.
+pub fn account_to_p2tr_address(
+    network: Network,
+    schnorr_public_key: &ECDSAPublicKey,
+    account: &Account,
+) -> String {
+    account_to_taproot_address(schnorr_public_key, account).display(network)
+}
+
+/// Constructs the Taproot address corresponding to the specified account.
+///
+/// The account key derived from the minter's Schnorr public key is the
+/// internal key of the output. The output commits to no script path, so the
+/// minter can spend it only using the key path.
+pub fn account_to_taproot_address(
+    schnorr_public_key: &ECDSAPublicKey,
+    account: &Account,
+) -> BitcoinAddress {
+    let pk = derive_public_key(schnorr_public_key, account).public_key;
+    BitcoinAddress::P2trV1(taproot_output_key(&pk))
+}
+
+/// Computes the x-only output key of a Taproot output without a script path
+/// as described in [BIP-0086](https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki):
+///
+/// ```text
+/// Q = lift_x(P) + int(hash_TapTweak(bytes(P))) * G
+/// ```
+///
+/// # Panics
+///
+/// This function panics if the public key in not compressed or is not a valid
+/// point on the secp256k1 curve.
+pub fn taproot_output_key(public_key: &[u8]) -> [u8; 32] {
+    use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+    use k256::elliptic_curve::PrimeField;
+    use k256::{AffinePoint, EncodedPoint, ProjectivePoint, Scalar};
+
+    assert_eq!(public_key.len(), 33);
+    assert!(public_key[0] == 0x02 || public_key[0] == 0x03);
+
+    let internal_key: [u8; 32] = public_key[1..].try_into().unwrap();
+
+    // BIP-340 public keys are x-only, lift_x picks the point with an even Y
+    // coordinate.
+    let mut even_key = public_key.to_vec();
+    even_key[0] = 0x02;
+    let internal_point = EncodedPoint::from_bytes(&even_key)
+        .ok()
+        .and_then(|p| Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&p)))
+        .expect("bug: the internal key must be a valid secp256k1 point");
+
+    let tweak = {
+        let mut hasher = crate::tx::tagged_hasher("TapTweak");
+        hasher.write(&internal_key);
+        Option::<Scalar>::from(Scalar::from_repr(hasher.finish().into()))
+            .expect("bug: the Taproot tweak must be smaller than the curve order")
+    };
+
+    let output_point = AffinePoint::from(
+        ProjectivePoint::from(internal_point) + ProjectivePoint::GENERATOR * tweak,
+    )
+    .to_encoded_point(true);
+
+    output_point
+        .x()
+        .expect("bug: the Taproot output key must not be the identity point")
+        .as_slice()
+        .try_into()
+        .unwrap()
+}
+
 fn encode_bech32(network: Network, hash: &[u8], version: WitnessVersion) -> String {
     use bech32::u5;
 
@@ -576,4 +665,41 @@ mod tests {
             .unwrap_err()
         );
     }
+
+    #[test]
+    fn should_compute_taproot_output_key() {
+        use super::taproot_output_key;
+
+        // The first vector comes from
+        // https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki#test-vectors,
+        // the second from
+        // https://github.com/bitcoin/bips/blob/master/bip-0341/wallet-test-vectors.json.
+        let vectors = [
+            (
+                "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115",
+                "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+            ),
+            (
+                "d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+                "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+                "bc1p2wsldez5mud2yam29q22wgfh9439spgduvct83k3pm50fcxa5dps59h4z5",
+            ),
+        ];
+
+        for (internal_key, output_key, address) in vectors {
+            let mut public_key = vec![0x02];
+            public_key.extend(hex::decode(internal_key).unwrap());
+            let key = taproot_output_key(&public_key);
+            assert_eq!(hex::encode(key), output_key);
+            assert_eq!(
+                BitcoinAddress::P2trV1(key).display(Network::Mainnet),
+                address
+            );
+
+            // BIP-340 keys are x-only, the parity of the public key does not matter.
+            public_key[0] = 0x03;
+            assert_eq!(taproot_output_key(&public_key), key);
+        }
+    }
 }
@@ -360,6 +360,9 @@ pub fn build_submitted_transactions() -> String {
                         .unwrap();
 
                         write!(buf, "<td rowspan='{}'>", rowspan).unwrap();
+                        if tx.is_utxo_consolidation() {
+                            write!(buf, "UTXO consolidation").unwrap();
+                        }
                         for req in &tx.requests {
                             write!(
                                 buf,
@@ -7,6 +7,7 @@ use crate::tasks::schedule_after;
 use candid::{CandidType, Deserialize};
 use ic_btc_interface::{MillisatoshiPerByte, Network, OutPoint, Satoshi, Txid, Utxo};
 use ic_canister_log::log;
+pub use ic_ckbtc_kyt::blocklist;
 use ic_ic00_types::DerivationPath;
 use icrc_ledger_types::icrc1::account::Account;
 use icrc_ledger_types::icrc1::transfer::{Memo, TransferError};
@@ -18,7 +19,6 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::time::Duration;
 
 pub mod address;
-pub mod blocklist;
 pub mod dashboard;
 pub mod guard;
 pub mod lifecycle;
@@ -68,6 +68,40 @@ pub const CKBTC_LEDGER_MEMO_SIZE: u16 = 80;
 /// when building transactions.
 pub const UTXOS_COUNT_THRESHOLD: usize = 1_000;
 
+/// The minimum number of available UTXOs before the minter starts
+/// consolidating them.
+pub const UTXO_CONSOLIDATION_THRESHOLD: usize = UTXOS_COUNT_THRESHOLD;
+
+/// The maximum number of UTXOs the minter consolidates in a single
+/// transaction. This bound keeps consolidation transactions well below the
+/// standard transaction size limit of 100k vbytes.
+pub const MAX_UTXOS_PER_CONSOLIDATION: usize = 500;
+
+/// The minter consolidates UTXOs only if the median fee (in millisatoshi per
+/// vbyte) does not exceed this value.
+pub const MAX_CONSOLIDATION_FEE_PER_VBYTE: MillisatoshiPerByte = 10_000;
+
+/// The subaccount of the minter holding the ckBTC that the minter burns to pay
+/// for the fees of UTXO consolidation transactions. Burning the fee keeps the
+/// ckBTC supply backed by the minter's UTXOs.
+pub const CONSOLIDATION_FEE_SUBACCOUNT: [u8; 32] = *b"ckBTC UTXO consolidation fees\0\0\0";
+
+/// The maximum total amount (in satoshi) of fees that the minter spends on
+/// UTXO consolidation transactions.
+pub const MAX_TOTAL_CONSOLIDATION_FEES: Satoshi = 10_000_000;
+
+/// Having a sequence number lower than (0xffffffff - 1) signals the use of replacement by fee.
+/// It allows us to increase the fee of a transaction already sent to the mempool.
+/// The rbf option is used in `resubmit_retrieve_btc`.
+/// https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki
+const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
+
+/// The default dustRelayFee is 3 sat/vB,
+/// which translates to a dust threshold of 546 satoshi for P2PKH outputs.
+/// The threshold for other types is lower,
+/// so we simply use 546 satoshi as the minimum amount per output.
+const MIN_OUTPUT_AMOUNT: u64 = 546;
+
 #[derive(Clone, serde::Serialize, Deserialize, Debug)]
 pub enum Priority {
     P0,
@@ -106,9 +140,13 @@ struct SignTxRequest {
     key_name: String,
     network: Network,
     ecdsa_public_key: ECDSAPublicKey,
+    /// The Schnorr public key, required if the transaction spends Taproot outputs.
+    schnorr_public_key: Option<ECDSAPublicKey>,
     unsigned_tx: tx::UnsignedTransaction,
     change_output: state::ChangeOutput,
     outpoint_account: BTreeMap<OutPoint, Account>,
+    /// The transaction inputs spending Taproot outputs.
+    taproot_outpoints: BTreeSet<OutPoint>,
     /// The original requests that we keep around to place back to the queue
     /// if the signature fails.
     requests: Vec<state::RetrieveBtcRequest>,
@@ -236,6 +274,7 @@ async fn submit_pending_requests() {
     };
 
     let ecdsa_public_key = updates::get_btc_address::init_ecdsa_public_key().await;
+    init_schnorr_public_key_if_needed().await;
     let main_address = address::account_to_bitcoin_address(&ecdsa_public_key, &main_account);
 
     let fee_millisatoshi_per_vbyte = match estimate_fee_per_vbyte().await {
@@ -260,6 +299,7 @@ async fn submit_pending_requests() {
             outputs,
             main_address,
             fee_millisatoshi_per_vbyte,
+            &s.taproot_outpoints,
         ) {
             Ok((unsigned_tx, change_output, utxos)) => {
                 for req in batch.iter() {
@@ -269,8 +309,10 @@ async fn submit_pending_requests() {
                 Some(SignTxRequest {
                     key_name: s.ecdsa_key_name.clone(),
                     ecdsa_public_key,
+                    schnorr_public_key: s.schnorr_public_key.clone(),
                     change_output,
                     outpoint_account: filter_output_accounts(s, &unsigned_tx),
+                    taproot_outpoints: filter_taproot_outpoints(s, &unsigned_tx),
                     network: s.btc_network,
                     unsigned_tx,
                     requests: batch,
@@ -343,7 +385,10 @@ async fn submit_pending_requests() {
         match sign_transaction(
             req.key_name,
             &req.ecdsa_public_key,
+            req.schnorr_public_key.as_ref(),
+            &management::IcSchnorrSigner,
             &req.outpoint_account,
+            &req.taproot_outpoints,
             req.unsigned_tx,
         )
         .await
@@ -410,6 +455,204 @@ async fn submit_pending_requests() {
     }
 }
 
+/// Consolidates the smallest UTXOs of the minter into a single UTXO to the
+/// minter's main address, so that large retrieve_btc requests do not need
+/// transactions with too many inputs.
+///
+/// The minter tracks consolidation transactions as submitted transactions
+/// without retrieve_btc requests, so they are finalized and resubmitted like
+/// any other transaction.
+async fn consolidate_utxos() {
+    if !state::read_state(|s| s.should_consolidate_utxos(UTXO_CONSOLIDATION_THRESHOLD)) {
+        return;
+    }
+
+    let fee_millisatoshi_per_vbyte = match estimate_fee_per_vbyte().await {
+        Some(fee) => fee,
+        None => return,
+    };
+
+    if fee_millisatoshi_per_vbyte > MAX_CONSOLIDATION_FEE_PER_VBYTE {
+        log!(
+            P1,
+            "[consolidate_utxos]: postponing UTXO consolidation, the fee rate {} millisatoshi/vbyte exceeds {}",
+            fee_millisatoshi_per_vbyte,
+            MAX_CONSOLIDATION_FEE_PER_VBYTE
+        );
+        return;
+    }
+
+    let main_account = Account {
+        owner: ic_cdk::id(),
+        subaccount: None,
+    };
+
+    let ecdsa_public_key = updates::get_btc_address::init_ecdsa_public_key().await;
+    init_schnorr_public_key_if_needed().await;
+    let main_address = address::account_to_bitcoin_address(&ecdsa_public_key, &main_account);
+
+    let maybe_sign_request = state::mutate_state(|s| {
+        // The state might have changed while we were waiting for the fee estimate.
+        if !s.should_consolidate_utxos(UTXO_CONSOLIDATION_THRESHOLD) {
+            return None;
+        }
+
+        let utxos = select_utxos_to_consolidate(
+            &mut s.available_utxos,
+            MAX_UTXOS_PER_CONSOLIDATION,
+            fee_millisatoshi_per_vbyte,
+        );
+
+        if utxos.len() < 2 {
+            // Consolidating fewer than two UTXOs does not reduce their number.
+            for utxo in utxos {
+                assert!(s.available_utxos.insert(utxo));
+            }
+            return None;
+        }
+
+        match build_consolidation_transaction(
+            &utxos,
+            main_address,
+            fee_millisatoshi_per_vbyte,
+            &s.taproot_outpoints,
+        ) {
+            Ok((_, change_output))
+                if s.total_consolidation_fees + consolidation_fee(&utxos, &change_output)
+                    > MAX_TOTAL_CONSOLIDATION_FEES =>
+            {
+                log!(
+                    P0,
+                    "[consolidate_utxos]: skipping UTXO consolidation, the minter already spent {} on consolidation fees",
+                    tx::DisplayAmount(s.total_consolidation_fees),
+                );
+                for utxo in utxos {
+                    assert!(s.available_utxos.insert(utxo));
+                }
+                None
+            }
+            Ok((unsigned_tx, change_output)) => Some(SignTxRequest {
+                key_name: s.ecdsa_key_name.clone(),
+                ecdsa_public_key,
+                schnorr_public_key: s.schnorr_public_key.clone(),
+                change_output,
+                outpoint_account: filter_output_accounts(s, &unsigned_tx),
+                taproot_outpoints: filter_taproot_outpoints(s, &unsigned_tx),
+                network: s.btc_network,
+                unsigned_tx,
+                requests: vec![],
+                utxos,
+            }),
+            Err(err) => {
+                log!(
+                    P0,
+                    "[consolidate_utxos]: failed to build a consolidation transaction for {} UTXOs: {:?}",
+                    utxos.len(),
+                    err
+                );
+                for utxo in utxos {
+                    assert!(s.available_utxos.insert(utxo));
+                }
+                None
+            }
+        }
+    });
+
+    if let Some(req) = maybe_sign_request {
+        log!(
+            P1,
+            "[consolidate_utxos]: signing a new consolidation transaction: {}",
+            hex::encode(tx::encode_into(&req.unsigned_tx, Vec::new()))
+        );
+
+        // This guard ensures that we return the UTXOs back to the state if
+        // signing or sending the transaction fails or panics.
+        let utxos_guard = guard(req.utxos, |utxos| {
+            undo_sign_request(vec![], utxos);
+        });
+
+        let txid = req.unsigned_tx.txid();
+
+        let signed_tx = match sign_transaction(
+            req.key_name,
+            &req.ecdsa_public_key,
+            req.schnorr_public_key.as_ref(),
+            &management::IcSchnorrSigner,
+            &req.outpoint_account,
+            &req.taproot_outpoints,
+            req.unsigned_tx,
+        )
+        .await
+        {
+            Ok(signed_tx) => signed_tx,
+            Err(err) => {
+                log!(
+                    P0,
+                    "[consolidate_utxos]: failed to sign a BTC transaction: {}",
+                    err
+                );
+                return;
+            }
+        };
+
+        let fee = consolidation_fee(&utxos_guard, &req.change_output);
+        let fee_burn = match burn_consolidation_fee(fee, &txid).await {
+            Ok(block_index) => state::ConsolidationFeeBurn {
+                amount: fee,
+                block_index,
+            },
+            Err(err) => {
+                log!(
+                    P0,
+                    "[consolidate_utxos]: failed to burn {} to pay for the consolidation fee: {:?}",
+                    tx::DisplayAmount(fee),
+                    err
+                );
+                return;
+            }
+        };
+
+        match management::send_transaction(&signed_tx, req.network).await {
+            Ok(()) => {
+                log!(
+                    P0,
+                    "[consolidate_utxos]: sent transaction {} consolidating {} UTXOs into {}",
+                    &txid,
+                    utxos_guard.len(),
+                    tx::DisplayAmount(req.change_output.value),
+                );
+
+                // Defuse the guard because we sent the transaction
+                // successfully.
+                let used_utxos = ScopeGuard::into_inner(utxos_guard);
+
+                state::mutate_state(|s| {
+                    state::audit::sent_consolidation_transaction(
+                        s,
+                        state::SubmittedBtcTransaction {
+                            requests: vec![],
+                            txid,
+                            used_utxos,
+                            change_output: Some(req.change_output),
+                            submitted_at: ic_cdk::api::time(),
+                            fee_per_vbyte: Some(fee_millisatoshi_per_vbyte),
+                        },
+                        fee_burn,
+                    );
+                });
+            }
+            Err(err) => {
+                log!(
+                    P0,
+                    "[consolidate_utxos]: failed to send a bitcoin transaction: {}. The fee burned in block {} was not spent",
+                    err,
+                    fee_burn.block_index
+                );
+            }
+        }
+    }
+}
+
 fn finalization_time_estimate(min_confirmations: u32, network: Network) -> Duration {
     Duration::from_nanos(
         min_confirmations as u64
@@ -472,6 +715,7 @@ async fn finalize_requests() {
     }
 
     let ecdsa_public_key = updates::get_btc_address::init_ecdsa_public_key().await;
+    init_schnorr_public_key_if_needed().await;
     let now = ic_cdk::api::time();
 
     // The list of transactions that are likely to be finalized, indexed by the transaction id.
@@ -510,7 +754,13 @@ async fn finalize_requests() {
 
     state::mutate_state(|s| {
         if !new_utxos.is_empty() {
-            state::audit::add_utxos(s, None, main_account, new_utxos);
+            state::audit::add_utxos(
+                s,
+                None,
+                main_account,
+                new_utxos,
+                address::DepositAddressType::P2wpkh,
+            );
         }
         for txid in &confirmed_transactions {
             state::audit::confirm_transaction(s, txid);
@@ -606,11 +856,15 @@ async fn finalize_requests() {
         None => return,
     };
 
-    let key_name = state::read_state(|s| s.ecdsa_key_name.clone());
+    let (key_name, schnorr_public_key, taproot_outpoints) = state::read_state(|s| {
+        (
+            s.ecdsa_key_name.clone(),
+            s.schnorr_public_key.clone(),
+            s.taproot_outpoints.clone(),
+        )
+    });
 
     for (old_txid, submitted_tx) in maybe_finalized_transactions {
-        let mut utxos: BTreeSet<_> = submitted_tx.used_utxos.iter().cloned().collect();
-
         let tx_fee_per_vbyte = match submitted_tx.fee_per_vbyte {
             Some(prev_fee) => {
                 // Ensure that the fee is at least min relay fee higher than the previous
@@ -620,18 +874,40 @@ async fn finalize_requests() {
             None => fee_per_vbyte,
         };
 
-        let outputs = submitted_tx
-            .requests
-            .iter()
-            .map(|req| (req.address.clone(), req.amount))
-            .collect();
+        let rebuilt_tx = if submitted_tx.is_utxo_consolidation() {
+            build_consolidation_transaction(
+                &submitted_tx.used_utxos,
+                main_address.clone(),
+                tx_fee_per_vbyte,
+                &taproot_outpoints,
+            )
+            .map(|(unsigned_tx, change_output)| {
+                (unsigned_tx, change_output, submitted_tx.used_utxos.clone())
+            })
+        } else {
+            let mut utxos: BTreeSet<_> = submitted_tx.used_utxos.iter().cloned().collect();
+            let outputs = submitted_tx
+                .requests
+                .iter()
+                .map(|req| (req.address.clone(), req.amount))
+                .collect();
+            let result = build_unsigned_transaction(
+                &mut utxos,
+                outputs,
+                main_address.clone(),
+                tx_fee_per_vbyte,
+                &taproot_outpoints,
+            );
+            if result.is_ok() {
+                assert!(
+                    utxos.is_empty(),
+                    "build_unsigned_transaction didn't use all inputs"
+                );
+            }
+            result
+        };
 
-        let (unsigned_tx, change_output, used_utxos) = match build_unsigned_transaction(
-            &mut utxos,
-            outputs,
-            main_address.clone(),
-            tx_fee_per_vbyte,
-        ) {
+        let (unsigned_tx, change_output, used_utxos) = match rebuilt_tx {
             Ok(tx) => tx,
             // If it's impossible to build a new transaction, the fees probably became too high.
             // Let's ignore this transaction and wait for fees to go down.
@@ -646,20 +922,51 @@ async fn finalize_requests() {
             }
         };
 
-        let outpoint_account = state::read_state(|s| filter_output_accounts(s, &unsigned_tx));
+        let (outpoint_account, tx_taproot_outpoints) = state::read_state(|s| {
+            (
+                filter_output_accounts(s, &unsigned_tx),
+                filter_taproot_outpoints(s, &unsigned_tx),
+            )
+        });
 
-        assert!(
-            utxos.is_empty(),
-            "build_unsigned_transaction didn't use all inputs"
-        );
         assert_eq!(used_utxos.len(), submitted_tx.used_utxos.len());
 
         let new_txid = unsigned_tx.txid();
 
+        // The minter burns the additional fee of a replacement consolidation
+        // transaction, the fee of other transactions is paid by the withdrawn amounts.
+        let consolidation_fee_increase = if submitted_tx.is_utxo_consolidation() {
+            let old_fee = consolidation_fee(
+                &submitted_tx.used_utxos,
+                submitted_tx
+                    .change_output
+                    .as_ref()
+                    .expect("bug: all consolidation transactions must have the change output"),
+            );
+            let fee_increase =
+                consolidation_fee(&used_utxos, &change_output).saturating_sub(old_fee);
+            let total_consolidation_fees = state::read_state(|s| s.total_consolidation_fees);
+            if total_consolidation_fees + fee_increase > MAX_TOTAL_CONSOLIDATION_FEES {
+                log!(
+                    P0,
+                    "[finalize_requests]: not replacing stuck consolidation transaction {}, the minter already spent {} on consolidation fees",
+                    &submitted_tx.txid,
+                    tx::DisplayAmount(total_consolidation_fees),
+                );
+                continue;
+            }
+            Some(fee_increase)
+        } else {
+            None
+        };
+
         let maybe_signed_tx = sign_transaction(
             key_name.clone(),
             &ecdsa_public_key,
+            schnorr_public_key.as_ref(),
+            &management::IcSchnorrSigner,
             &outpoint_account,
+            &tx_taproot_outpoints,
             unsigned_tx,
         )
         .await;
@@ -676,6 +983,26 @@ async fn finalize_requests() {
             }
         };
 
+        let consolidation_fee_burn = match consolidation_fee_increase {
+            Some(fee_increase) => match burn_consolidation_fee(fee_increase, &new_txid).await {
+                Ok(block_index) => Some(state::ConsolidationFeeBurn {
+                    amount: fee_increase,
+                    block_index,
+                }),
+                Err(err) => {
+                    log!(
+                        P0,
+                        "[finalize_requests]: failed to burn {} to pay for the fee of replacement transaction {}: {:?}",
+                        tx::DisplayAmount(fee_increase),
+                        &new_txid,
+                        err
+                    );
+                    continue;
+                }
+            },
+            None => None,
+        };
+
         match management::send_transaction(&signed_tx, btc_network).await {
             Ok(()) => {
                 if old_txid == new_txid {
@@ -706,7 +1033,7 @@ async fn finalize_requests() {
                 };
 
                 state::mutate_state(|s| {
-                    state::audit::replace_transaction(s, old_txid, new_tx);
+                    state::audit::replace_transaction(s, old_txid, new_tx, consolidation_fee_burn);
                 });
             }
             Err(err) => {
@@ -746,6 +1073,32 @@ fn filter_output_accounts(
         .collect()
 }
 
+/// Returns the output points of the transaction inputs that spend Taproot
+/// outputs.
+fn filter_taproot_outpoints(
+    state: &state::CkBtcMinterState,
+    unsigned_tx: &tx::UnsignedTransaction,
+) -> BTreeSet<OutPoint> {
+    unsigned_tx
+        .inputs
+        .iter()
+        .filter(|input| state.taproot_outpoints.contains(&input.previous_output))
+        .map(|input| input.previous_output.clone())
+        .collect()
+}
+
+/// Initializes the Schnorr public key if the minter owns UTXOs deposited to
+/// Taproot addresses, which is the case after an upgrade.
+///
+/// Callers must initialize the key before they take UTXOs out of the state:
+/// fetching the key might trap, which would skip the guards putting the UTXOs
+/// back.
+async fn init_schnorr_public_key_if_needed() {
+    if state::read_state(|s| !s.taproot_outpoints.is_empty()) {
+        updates::get_btc_address::init_schnorr_public_key().await;
+    }
+}
+
 /// The algorithm greedily selects the smallest UTXO(s) with a value that is at least the given `target` in a first step.
 ///
 /// If the minter manages more than [UTXOS_COUNT_THRESHOLD], it will then try to match the number of inputs with the
@@ -819,45 +1172,103 @@ fn greedy(target: u64, available_utxos: &mut BTreeSet<Utxo>) -> Vec<Utxo> {
     solution
 }
 
-/// Gathers ECDSA signatures for all the inputs in the specified unsigned
-/// transaction.
+/// Gathers ECDSA signatures for the inputs spending P2WPKH outputs and
+/// BIP-340 signatures for the inputs spending Taproot outputs in the specified
+/// unsigned transaction.
 ///
 /// # Panics
 ///
-/// This function panics if the `output_account` map does not have an entry for
-/// at least one of the transaction previous output points.
+/// This function panics if:
+/// * The `output_account` map does not have an entry for at least one of the
+///   transaction previous output points.
+/// * The transaction spends Taproot outputs, but the `schnorr_public_key` is
+///   not set.
 pub async fn sign_transaction(
     key_name: String,
     ecdsa_public_key: &ECDSAPublicKey,
+    schnorr_public_key: Option<&ECDSAPublicKey>,
+    schnorr_signer: &impl management::SchnorrSigner,
     output_account: &BTreeMap<tx::OutPoint, Account>,
+    taproot_outpoints: &BTreeSet<tx::OutPoint>,
     unsigned_tx: tx::UnsignedTransaction,
 ) -> Result<tx::SignedTransaction, management::CallError> {
-    use crate::address::{derivation_path, derive_public_key};
+    use crate::address::{
+        account_to_bitcoin_address, account_to_taproot_address, derivation_path, derive_public_key,
+    };
+
+    let account_of = |outpoint: &tx::OutPoint| {
+        output_account
+            .get(outpoint)
+            .unwrap_or_else(|| panic!("bug: no account for outpoint {:?}", outpoint))
+    };
+    let is_taproot = |input: &tx::UnsignedInput| taproot_outpoints.contains(&input.previous_output);
+
+    // Taproot signatures commit to the scriptPubKeys of all outputs that the
+    // transaction spends, so we need the addresses of all spent outputs.
+    let taproot_sighasher = if unsigned_tx.inputs.iter().any(is_taproot) {
+        let schnorr_public_key = schnorr_public_key
+            .expect("bug: the Schnorr public key is required to spend Taproot outputs");
+        let spent_addresses: Vec<_> = unsigned_tx
+            .inputs
+            .iter()
+            .map(|input| {
+                let account = account_of(&input.previous_output);
+                if is_taproot(input) {
+                    account_to_taproot_address(schnorr_public_key, account)
+                } else {
+                    account_to_bitcoin_address(ecdsa_public_key, account)
+                }
+            })
+            .collect();
+        Some(tx::TaprootSigHasher::new(&unsigned_tx, &spent_addresses))
+    } else {
+        None
+    };
 
     let mut signed_inputs = Vec::with_capacity(unsigned_tx.inputs.len());
     let sighasher = tx::TxSigHasher::new(&unsigned_tx);
-    for input in &unsigned_tx.inputs {
+    for (index, input) in unsigned_tx.inputs.iter().enumerate() {
         let outpoint = &input.previous_output;
+        let account = account_of(outpoint);
+        let path = derivation_path(account);
 
-        let account = output_account
-            .get(outpoint)
-            .unwrap_or_else(|| panic!("bug: no account for outpoint {:?}", outpoint));
+        let witness = match taproot_sighasher.as_ref() {
+            Some(taproot_sighasher) if is_taproot(input) => {
+                let sighash = taproot_sighasher.sighash(index);
 
-        let path = derivation_path(account);
-        let pubkey = ByteBuf::from(derive_public_key(ecdsa_public_key, account).public_key);
-        let pkhash = tx::hash160(&pubkey);
+                let signature = schnorr_signer
+                    .sign_taproot_key_spend(key_name.clone(), path, sighash)
+                    .await?;
 
-        let sighash = sighasher.sighash(input, &pkhash);
+                tx::Witness::P2trKeyPath {
+                    signature: signature::SchnorrSignature::try_from_slice(&signature)
+                        .unwrap_or_else(|e| panic!("bug: invalid BIP-340 signature: {e}")),
+                }
+            }
+            _ => {
+                let pubkey = ByteBuf::from(derive_public_key(ecdsa_public_key, account).public_key);
+                let pkhash = tx::hash160(&pubkey);
 
-        let sec1_signature =
-            management::sign_with_ecdsa(key_name.clone(), DerivationPath::new(path), sighash)
+                let sighash = sighasher.sighash(input, &pkhash);
+
+                let sec1_signature = management::sign_with_ecdsa(
+                    key_name.clone(),
+                    DerivationPath::new(path),
+                    sighash,
+                )
                 .await?;
 
+                tx::Witness::P2wpkh {
+                    signature: signature::EncodedSignature::from_sec1(&sec1_signature),
+                    pubkey,
+                }
+            }
+        };
+
         signed_inputs.push(tx::SignedInput {
-            signature: signature::EncodedSignature::from_sec1(&sec1_signature),
-            pubkey,
             previous_output: outpoint.clone(),
             sequence: input.sequence,
+            witness,
         });
     }
     Ok(tx::SignedTransaction {
@@ -867,7 +1278,13 @@ pub async fn sign_transaction(
     })
 }
 
-pub fn fake_sign(unsigned_tx: &tx::UnsignedTransaction) -> tx::SignedTransaction {
+/// Signs the transaction with fake signatures of the maximum length to estimate
+/// its size. The inputs spending `taproot_outpoints` get a Taproot key path
+/// witness, all other inputs get a P2WPKH witness.
+pub fn fake_sign(
+    unsigned_tx: &tx::UnsignedTransaction,
+    taproot_outpoints: &BTreeSet<OutPoint>,
+) -> tx::SignedTransaction {
     tx::SignedTransaction {
         inputs: unsigned_tx
             .inputs
@@ -875,8 +1292,16 @@ pub fn fake_sign(unsigned_tx: &tx::UnsignedTransaction) -> tx::SignedTransaction
             .map(|unsigned_input| tx::SignedInput {
                 previous_output: unsigned_input.previous_output.clone(),
                 sequence: unsigned_input.sequence,
-                signature: signature::EncodedSignature::fake(),
-                pubkey: ByteBuf::from(vec![0u8; tx::PUBKEY_LEN]),
+                witness: if taproot_outpoints.contains(&unsigned_input.previous_output) {
+                    tx::Witness::P2trKeyPath {
+                        signature: signature::SchnorrSignature::fake(),
+                    }
+                } else {
+                    tx::Witness::P2wpkh {
+                        signature: signature::EncodedSignature::fake(),
+                        pubkey: ByteBuf::from(vec![0u8; tx::PUBKEY_LEN]),
+                    }
+                },
             })
             .collect(),
         outputs: unsigned_tx.outputs.clone(),
@@ -911,6 +1336,8 @@ pub enum BuildTxError {
 /// * `outputs` - The destination BTC addresses and respective amounts.
 /// * `main_address` - The BTC address of the minter's main account do absorb the change.
 /// * `fee_per_vbyte` - The current 50th percentile of BTC fees, in millisatoshi/byte
+/// * `taproot_outpoints` - The minter UTXOs deposited to Taproot addresses,
+///   which are cheaper to spend
 ///
 /// # Panics
 ///
@@ -951,15 +1378,10 @@ pub fn build_unsigned_transaction(
     outputs: Vec<(BitcoinAddress, Satoshi)>,
     main_address: BitcoinAddress,
     fee_per_vbyte: u64,
+    taproot_outpoints: &BTreeSet<OutPoint>,
 ) -> Result<(tx::UnsignedTransaction, state::ChangeOutput, Vec<Utxo>), BuildTxError> {
     assert!(!outputs.is_empty());
 
-    /// Having a sequence number lower than (0xffffffff - 1) signals the use of replacement by fee.
-    /// It allows us to increase the fee of a transaction already sent to the mempool.
-    /// The rbf option is used in `resubmit_retrieve_btc`.
-    /// https://github.com/bitcoin/bips/blob/master/bip-0125.mediawiki
-    const SEQUENCE_RBF_ENABLED: u32 = 0xfffffffd;
-
     let amount = outputs.iter().map(|(_, amount)| amount).sum::<u64>();
 
     let input_utxos = utxos_selection(amount, minter_utxos, outputs.len());
@@ -1020,7 +1442,7 @@ pub fn build_unsigned_transaction(
         lock_time: 0,
     };
 
-    let tx_vsize = fake_sign(&unsigned_tx).vsize();
+    let tx_vsize = fake_sign(&unsigned_tx, taproot_outpoints).vsize();
     let fee = (tx_vsize as u64 * fee_per_vbyte) / 1000;
 
     if fee + minter_fee > amount {
@@ -1028,11 +1450,6 @@ pub fn build_unsigned_transaction(
     }
 
     let fee_shares = distribute(fee + minter_fee, outputs.len() as u64);
-    // The default dustRelayFee is 3 sat/vB,
-    // which translates to a dust threshold of 546 satoshi for P2PKH outputs.
-    // The threshold for other types is lower,
-    // so we simply use 546 satoshi as the minimum amount per output.
-    const MIN_OUTPUT_AMOUNT: u64 = 546;
 
     for (output, fee_share) in unsigned_tx.outputs.iter_mut().zip(fee_shares.iter()) {
         if output.address != main_address {
@@ -1058,6 +1475,140 @@ pub fn build_unsigned_transaction(
     ))
 }
 
+/// Selects at most `max_count` of the smallest available UTXOs worth
+/// consolidating and removes them from the available set.
+///
+/// A UTXO is worth consolidating only if its value exceeds the fee required to
+/// spend it at the given fee rate (in millisatoshi per vbyte).
+fn select_utxos_to_consolidate(
+    available_utxos: &mut BTreeSet<Utxo>,
+    max_count: usize,
+    fee_per_vbyte: u64,
+) -> Vec<Utxo> {
+    const INPUT_SIZE_VBYTES: u64 = 68;
+    let input_fee = INPUT_SIZE_VBYTES * fee_per_vbyte / 1000;
+
+    let mut candidates: Vec<_> = available_utxos
+        .iter()
+        .filter(|u| u.value > input_fee)
+        .cloned()
+        .collect();
+    candidates.sort_by_key(|u| u.value);
+    candidates.truncate(max_count);
+
+    for utxo in candidates.iter() {
+        assert!(available_utxos.remove(utxo));
+    }
+    candidates
+}
+
+/// Builds a transaction that moves the value of the given minter UTXOs to a
+/// single output to the minter's main address. The minter pays the fee.
+///
+/// # Panics
+///
+/// This function panics if the `inputs` vector is empty as it indicates a bug
+/// in the caller's code.
+///
+/// # Success case properties
+///
+/// * The transaction has exactly one output, which is the change output.
+/// ```text
+/// tx.outputs == { value = inputs_value(tx) - fee(tx); address = main_address }
+/// ```
+pub fn build_consolidation_transaction(
+    inputs: &[Utxo],
+    main_address: BitcoinAddress,
+    fee_per_vbyte: u64,
+    taproot_outpoints: &BTreeSet<OutPoint>,
+) -> Result<(tx::UnsignedTransaction, state::ChangeOutput), BuildTxError> {
+    assert!(!inputs.is_empty());
+
+    let inputs_value = inputs.iter().map(|u| u.value).sum::<u64>();
+
+    let mut unsigned_tx = tx::UnsignedTransaction {
+        inputs: inputs
+            .iter()
+            .map(|utxo| tx::UnsignedInput {
+                previous_output: utxo.outpoint.clone(),
+                value: utxo.value,
+                sequence: SEQUENCE_RBF_ENABLED,
+            })
+            .collect(),
+        outputs: vec![tx::TxOut {
+            address: main_address,
+            value: inputs_value,
+        }],
+        lock_time: 0,
+    };
+
+    let tx_vsize = fake_sign(&unsigned_tx, taproot_outpoints).vsize();
+    let fee = (tx_vsize as u64 * fee_per_vbyte) / 1000;
+
+    if fee + MIN_OUTPUT_AMOUNT > inputs_value {
+        return Err(BuildTxError::AmountTooLow);
+    }
+
+    unsigned_tx.outputs[0].value = inputs_value - fee;
+    let change_output = state::ChangeOutput {
+        vout: 0,
+        value: inputs_value - fee,
+    };
+
+    Ok((unsigned_tx, change_output))
+}
+
+/// Returns the fee (in satoshi) of a consolidation transaction spending the
+/// given UTXOs into the given change output.
+fn consolidation_fee(utxos: &[Utxo], change_output: &state::ChangeOutput) -> Satoshi {
+    utxos
+        .iter()
+        .map(|u| u.value)
+        .sum::<u64>()
+        .checked_sub(change_output.value)
+        .expect("bug: the change output cannot exceed the consolidated value")
+}
+
+#[derive(Debug)]
+enum BurnConsolidationFeeError {
+    TransferError(TransferError),
+    CallError(i32, String),
+}
+
+/// Burns the given amount from the minter's consolidation fee subaccount.
+async fn burn_consolidation_fee(
+    amount: Satoshi,
+    txid: &Txid,
+) -> Result<u64, BurnConsolidationFeeError> {
+    use icrc_ledger_client_cdk::CdkRuntime;
+    use icrc_ledger_client_cdk::ICRC1Client;
+    use icrc_ledger_types::icrc1::transfer::TransferArg;
+
+    let memo = crate::memo::BurnMemo::Consolidate {
+        txid: Some(txid.as_ref()),
+    };
+    let client = ICRC1Client {
+        runtime: CdkRuntime,
+        ledger_canister_id: state::read_state(|s| s.ledger_id.get().into()),
+    };
+    client
+        .transfer(TransferArg {
+            from_subaccount: Some(CONSOLIDATION_FEE_SUBACCOUNT),
+            to: Account {
+                owner: ic_cdk::id(),
+                subaccount: None,
+            },
+            fee: None,
+            created_at_time: None,
+            memo: Some(crate::memo::encode(&memo).into()),
+            amount: candid::Nat::from(amount),
+        })
+        .await
+        .map_err(|(code, msg)| BurnConsolidationFeeError::CallError(code, msg))?
+        .map_err(BurnConsolidationFeeError::TransferError)
+        .map(|n| n.0.to_u64().expect("nat does not fit into u64"))
+}
+
 /// Distributes an amount across the specified number of shares as fairly as
 /// possible.
 ///
@@ -1184,6 +1735,22 @@ pub fn timer() {
                 reimburse_failed_kyt().await;
             });
         }
+        TaskType::ConsolidateUtxos => {
+            ic_cdk::spawn(async {
+                let _guard = match crate::guard::ConsolidateUtxosGuard::new() {
+                    Some(guard) => guard,
+                    None => return,
+                };
+
+                const UTXO_CONSOLIDATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+                let _enqueue_followup_guard = guard((), |_| {
+                    schedule_after(UTXO_CONSOLIDATION_INTERVAL, TaskType::ConsolidateUtxos)
+                });
+
+                consolidate_utxos().await;
+            });
+        }
         TaskType::RefreshFeePercentiles => {
             ic_cdk::spawn(async {
                 const FEE_ESTIMATE_DELAY: Duration = Duration::from_secs(60 * 60);
@@ -36,6 +36,7 @@ fn init(args: MinterArg) {
             schedule_now(TaskType::ProcessLogic);
             schedule_now(TaskType::RefreshFeePercentiles);
             schedule_now(TaskType::DistributeKytFee);
+            schedule_now(TaskType::ConsolidateUtxos);
 
             #[cfg(feature = "self_check")]
             ok_or_die(check_invariants())
@@ -126,6 +127,7 @@ fn post_upgrade(minter_arg: Option<MinterArg>) {
     schedule_now(TaskType::ProcessLogic);
     schedule_now(TaskType::RefreshFeePercentiles);
     schedule_now(TaskType::DistributeKytFee);
+    schedule_now(TaskType::ConsolidateUtxos);
 }
 
 #[update]
@@ -3,7 +3,8 @@
 use crate::logs::P0;
 use crate::tx;
 use crate::ECDSAPublicKey;
-use candid::{CandidType, Principal};
+use async_trait::async_trait;
+use candid::{CandidType, Deserialize, Principal};
 use ic_btc_interface::{
     Address, GetCurrentFeePercentilesRequest, GetUtxosRequest, GetUtxosResponse,
     MillisatoshiPerByte, Network, Utxo, UtxosFilterInRequest,
@@ -16,6 +17,7 @@ use ic_ic00_types::{
     SignWithECDSAArgs, SignWithECDSAReply,
 };
 use serde::de::DeserializeOwned;
+use serde_bytes::ByteBuf;
 use std::fmt;
 
 /// Represents an error from a management canister call, such as
@@ -293,6 +295,130 @@ pub async fn sign_with_ecdsa(
     Ok(reply.signature)
 }
 
+#[derive(CandidType, Clone, Debug, Deserialize)]
+enum SchnorrAlgorithm {
+    #[serde(rename = "bip340secp256k1")]
+    Bip340Secp256k1,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize)]
+struct SchnorrKeyId {
+    algorithm: SchnorrAlgorithm,
+    name: String,
+}
+
+impl SchnorrKeyId {
+    fn bip340(name: String) -> Self {
+        Self {
+            algorithm: SchnorrAlgorithm::Bip340Secp256k1,
+            name,
+        }
+    }
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize)]
+struct SchnorrPublicKeyArgs {
+    canister_id: Option<Principal>,
+    derivation_path: Vec<ByteBuf>,
+    key_id: SchnorrKeyId,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize)]
+struct SchnorrPublicKeyResponse {
+    public_key: ByteBuf,
+    chain_code: ByteBuf,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize)]
+struct SignWithBip341Aux {
+    merkle_root_hash: ByteBuf,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize)]
+enum SignWithSchnorrAux {
+    #[serde(rename = "bip341")]
+    Bip341(SignWithBip341Aux),
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize)]
+struct SignWithSchnorrArgs {
+    message: ByteBuf,
+    derivation_path: Vec<ByteBuf>,
+    key_id: SchnorrKeyId,
+    aux: Option<SignWithSchnorrAux>,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize)]
+struct SignWithSchnorrReply {
+    signature: ByteBuf,
+}
+
+/// Fetches the BIP-340 Schnorr public key of the canister.
+pub async fn schnorr_public_key(key_name: String) -> Result<ECDSAPublicKey, CallError> {
+    call(
+        "schnorr_public_key",
+        /*payment=*/ 0,
+        &SchnorrPublicKeyArgs {
+            canister_id: None,
+            derivation_path: vec![],
+            key_id: SchnorrKeyId::bip340(key_name),
+        },
+    )
+    .await
+    .map(|response: SchnorrPublicKeyResponse| ECDSAPublicKey {
+        public_key: response.public_key.into_vec(),
+        chain_code: response.chain_code.into_vec(),
+    })
+}
+
+/// Produces BIP-340 signatures for spending Taproot outputs of the minter.
+///
+/// The minter signs Taproot inputs through this trait so that tests can
+/// replace the threshold Schnorr API with a mock signer.
+#[async_trait(?Send)]
+pub trait SchnorrSigner {
+    /// Signs the message with the key derived at the specified path and
+    /// tweaked for a Taproot output without a script path (see BIP-86).
+    async fn sign_taproot_key_spend(
+        &self,
+        key_name: String,
+        derivation_path: Vec<ByteBuf>,
+        message: [u8; 32],
+    ) -> Result<Vec<u8>, CallError>;
+}
+
+/// Signs messages using the threshold Schnorr API of the management canister.
+pub struct IcSchnorrSigner;
+
+#[async_trait(?Send)]
+impl SchnorrSigner for IcSchnorrSigner {
+    async fn sign_taproot_key_spend(
+        &self,
+        key_name: String,
+        derivation_path: Vec<ByteBuf>,
+        message: [u8; 32],
+    ) -> Result<Vec<u8>, CallError> {
+        const CYCLES_PER_SIGNATURE: u64 = 26_153_846_153;
+
+        let reply: SignWithSchnorrReply = call(
+            "sign_with_schnorr",
+            CYCLES_PER_SIGNATURE,
+            &SignWithSchnorrArgs {
+                message: ByteBuf::from(message.to_vec()),
+                derivation_path,
+                key_id: SchnorrKeyId::bip340(key_name),
+                // An empty Merkle root asks the management canister to tweak
+                // the key with the hash of the internal key only.
+                aux: Some(SignWithSchnorrAux::Bip341(SignWithBip341Aux {
+                    merkle_root_hash: ByteBuf::new(),
+                })),
+            },
+        )
+        .await?;
+        Ok(reply.signature.into_vec())
+    }
+}
+
 /// Requests alerts for the given UTXO.
 pub async fn fetch_utxo_alerts(
     kyt_principal: Principal,
@@ -69,4 +69,11 @@ pub enum BurnMemo<'a> {
         /// The status of the KYT check.
         status: Option<Status>,
     },
+    #[n(1)]
+    /// The minter paid the fee of a UTXO consolidation transaction.
+    Consolidate {
+        #[cbor(n(0), with = "minicbor::bytes")]
+        /// The transaction ID of the consolidation transaction.
+        txid: Option<&'a [u8]>,
+    },
 }
@@ -201,5 +201,11 @@ pub fn encode_metrics(
         "The total amount of ckBTC that minter owes to the KYT canister.",
     )?;
 
+    metrics.encode_gauge(
+        "ckbtc_minter_total_consolidation_fees",
+        state::read_state(|s| s.total_consolidation_fees) as f64,
+        "The total amount of ckBTC that the minter burned to pay for UTXO consolidation fees.",
+    )?;
+
     Ok(())
 }
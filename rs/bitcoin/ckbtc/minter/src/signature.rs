@@ -49,6 +49,43 @@ impl EncodedSignature {
     }
 }
 
+/// The length of a BIP-340 signature.
+pub const SCHNORR_SIGNATURE_LEN: usize = 64;
+
+// BIP-340 signature of a Taproot key path spend.
+// We always sign with SIGHASH_DEFAULT, so the signature has no trailing
+// sighash byte (see BIP-341).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchnorrSignature([u8; SCHNORR_SIGNATURE_LEN]);
+
+impl fmt::Display for SchnorrSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl SchnorrSignature {
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, String> {
+        let sig: [u8; SCHNORR_SIGNATURE_LEN] = bytes.try_into().map_err(|_| {
+            format!(
+                "expected a BIP-340 signature to have {} bytes, got: {}",
+                SCHNORR_SIGNATURE_LEN,
+                hex::encode(bytes)
+            )
+        })?;
+        Ok(Self(sig))
+    }
+
+    /// Returns a signature of the same length as a valid one.
+    pub fn fake() -> Self {
+        Self([0; SCHNORR_SIGNATURE_LEN])
+    }
+}
+
 /// Converts a SEC1 ECDSA signature to the DER format.
 ///
 /// # Panics
@@ -14,7 +14,10 @@ pub mod eventlog;
 use crate::lifecycle::init::InitArgs;
 use crate::lifecycle::upgrade::UpgradeArgs;
 use crate::logs::P0;
-use crate::{address::BitcoinAddress, ECDSAPublicKey};
+use crate::{
+    address::{BitcoinAddress, DepositAddressType},
+    ECDSAPublicKey,
+};
 use candid::{CandidType, Deserialize, Principal};
 use ic_base_types::CanisterId;
 pub use ic_btc_interface::Network;
@@ -67,6 +70,16 @@ pub struct ChangeOutput {
     pub value: u64,
 }
 
+/// The ckBTC that the minter burned to pay for the fee of a UTXO
+/// consolidation transaction.
+#[derive(candid::CandidType, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsolidationFeeBurn {
+    /// The burned amount in satoshi.
+    pub amount: u64,
+    /// The index of the ledger block burning the fee.
+    pub block_index: u64,
+}
+
 /// Represents a transaction sent to the Bitcoin network.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SubmittedBtcTransaction {
@@ -86,6 +99,14 @@ pub struct SubmittedBtcTransaction {
     pub fee_per_vbyte: Option<u64>,
 }
 
+impl SubmittedBtcTransaction {
+    /// Returns true if the minter sent this transaction to consolidate its own
+    /// UTXOs rather than to serve retrieve_btc requests.
+    pub fn is_utxo_consolidation(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
 /// Pairs a retrieve_btc request with its outcome.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FinalizedBtcRetrieval {
@@ -276,6 +297,12 @@ pub struct CkBtcMinterState {
     /// The Minter ECDSA public key
     pub ecdsa_public_key: Option<ECDSAPublicKey>,
 
+    /// The Minter BIP-340 Schnorr public key. The minter uses the threshold
+    /// Schnorr key with the same name as its ECDSA key to derive Taproot
+    /// deposit addresses.
+    #[serde(default)]
+    pub schnorr_public_key: Option<ECDSAPublicKey>,
+
     /// The minimum number of confirmations on the Bitcoin chain.
     pub min_confirmations: u32,
 
@@ -345,6 +372,11 @@ pub struct CkBtcMinterState {
     /// The map of known addresses to their utxos.
     pub utxos_state_addresses: BTreeMap<Account, BTreeSet<Utxo>>,
 
+    /// The output points of UTXOs deposited to Taproot addresses. The minter
+    /// spends all other UTXOs as P2WPKH outputs.
+    #[serde(default)]
+    pub taproot_outpoints: BTreeSet<OutPoint>,
+
     /// This map contains the UTXOs we removed due to a transaction finalization
     /// while there was a concurrent update_balance call for the principal whose
     /// UTXOs participated in the transaction. The UTXOs can belong to any
@@ -362,6 +394,10 @@ pub struct CkBtcMinterState {
     #[serde(skip)]
     pub is_distributing_fee: bool,
 
+    /// Consolidate UTXOs in one task at a time.
+    #[serde(skip)]
+    pub is_consolidating_utxos: bool,
+
     /// The mode in which the minter runs.
     pub mode: Mode,
 
@@ -373,6 +409,10 @@ pub struct CkBtcMinterState {
     /// The total amount of fees we owe to the KYT provider.
     pub owed_kyt_amount: BTreeMap<Principal, u64>,
 
+    /// The total amount of ckBTC (in satoshi) burned to pay for the fees of
+    /// UTXO consolidation transactions.
+    pub total_consolidation_fees: u64,
+
     /// A cache of UTXO KYT check statuses.
     pub checked_utxos: BTreeMap<Utxo, (String, UtxoCheckStatus, Principal)>,
 
@@ -513,6 +553,14 @@ impl CkBtcMinterState {
             );
         }
 
+        for outpoint in self.taproot_outpoints.iter() {
+            ensure!(
+                self.outpoint_account.contains_key(outpoint),
+                "the output_account map is missing an entry for Taproot outpoint {:?}",
+                outpoint
+            );
+        }
+
         for (addr, utxos) in self.utxos_state_addresses.iter() {
             for utxo in utxos.iter() {
                 ensure_eq!(
@@ -581,7 +629,12 @@ impl CkBtcMinterState {
     }
 
     // public for only for tests
-    pub(crate) fn add_utxos(&mut self, account: Account, utxos: Vec<Utxo>) {
+    pub(crate) fn add_utxos(
+        &mut self,
+        account: Account,
+        utxos: Vec<Utxo>,
+        address_type: DepositAddressType,
+    ) {
         if utxos.is_empty() {
             return;
         }
@@ -592,6 +645,9 @@ impl CkBtcMinterState {
 
         for utxo in utxos {
             self.outpoint_account.insert(utxo.outpoint.clone(), account);
+            if address_type == DepositAddressType::P2tr {
+                self.taproot_outpoints.insert(utxo.outpoint.clone());
+            }
             self.available_utxos.insert(utxo.clone());
             self.checked_utxos.remove(&utxo);
             account_bucket.insert(utxo);
@@ -726,6 +782,24 @@ impl CkBtcMinterState {
         batch
     }
 
+    /// Returns true if the minter manages at least `threshold` available UTXOs
+    /// and there is no pending UTXO consolidation transaction.
+    pub fn should_consolidate_utxos(&self, threshold: usize) -> bool {
+        self.available_utxos.len() >= threshold
+            && !self
+                .submitted_transactions
+                .iter()
+                .any(SubmittedBtcTransaction::is_utxo_consolidation)
+    }
+
+    /// Records the ckBTC burned to pay for a UTXO consolidation fee.
+    pub fn record_consolidation_fee_burn(&mut self, burn: &ConsolidationFeeBurn) {
+        self.total_consolidation_fees = self
+            .total_consolidation_fees
+            .checked_add(burn.amount)
+            .expect("bug: total consolidation fees overflow");
+    }
+
     /// Returns the total number of all retrieve_btc requests that we haven't
     /// finalized yet.
     pub fn count_incomplete_retrieve_btc_requests(&self) -> usize {
@@ -747,6 +821,7 @@ impl CkBtcMinterState {
     }
 
     fn forget_utxo(&mut self, utxo: &Utxo) {
+        self.taproot_outpoints.remove(&utxo.outpoint);
         if let Some(account) = self.outpoint_account.remove(&utxo.outpoint) {
             if self.update_balance_principals.contains(&account.owner) {
                 self.finalized_utxos
@@ -1125,6 +1200,11 @@ impl CkBtcMinterState {
             other.utxos_state_addresses,
             "utxos_state_addresses do not match"
         );
+        ensure_eq!(
+            self.taproot_outpoints,
+            other.taproot_outpoints,
+            "taproot_outpoints do not match"
+        );
         ensure_eq!(
             self.quarantined_utxos,
             other.quarantined_utxos,
@@ -1151,6 +1231,12 @@ impl CkBtcMinterState {
             "owed_kyt_amount does not match"
         );
 
+        ensure_eq!(
+            self.total_consolidation_fees,
+            other.total_consolidation_fees,
+            "total_consolidation_fees does not match"
+        );
+
         ensure_eq!(
             self.kyt_principal,
             other.kyt_principal,
@@ -1214,6 +1300,7 @@ impl From<InitArgs> for CkBtcMinterState {
             btc_network: args.btc_network.into(),
             ecdsa_key_name: args.ecdsa_key_name,
             ecdsa_public_key: None,
+            schnorr_public_key: None,
             min_confirmations: args
                 .min_confirmations
                 .unwrap_or(crate::lifecycle::init::DEFAULT_MIN_CONFIRMATIONS),
@@ -1238,15 +1325,18 @@ impl From<InitArgs> for CkBtcMinterState {
             available_utxos: Default::default(),
             outpoint_account: Default::default(),
             utxos_state_addresses: Default::default(),
+            taproot_outpoints: Default::default(),
             finalized_utxos: Default::default(),
             is_timer_running: false,
             is_distributing_fee: false,
+            is_consolidating_utxos: false,
             mode: args.mode,
             last_fee_per_vbyte: vec![1; 100],
             kyt_fee: args
                 .kyt_fee
                 .unwrap_or(crate::lifecycle::init::DEFAULT_KYT_FEE),
             owed_kyt_amount: Default::default(),
+            total_consolidation_fees: 0,
             checked_utxos: Default::default(),
             ignored_utxos: Default::default(),
             quarantined_utxos: Default::default(),
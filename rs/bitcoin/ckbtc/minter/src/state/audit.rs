@@ -1,9 +1,10 @@
 //! State modifications that should end up in the event log.
 
 use super::{
-    eventlog::Event, CkBtcMinterState, FinalizedBtcRetrieval, FinalizedStatus, RetrieveBtcRequest,
-    SubmittedBtcTransaction, UtxoCheckStatus,
+    eventlog::Event, CkBtcMinterState, ConsolidationFeeBurn, FinalizedBtcRetrieval,
+    FinalizedStatus, RetrieveBtcRequest, SubmittedBtcTransaction, UtxoCheckStatus,
 };
+use crate::address::DepositAddressType;
 use crate::state::{ReimburseDepositTask, ReimbursedDeposit};
 use crate::storage::record_event;
 use crate::ReimbursementReason;
@@ -31,14 +32,18 @@ pub fn add_utxos(
     mint_txid: Option<u64>,
     account: Account,
     utxos: Vec<Utxo>,
+    address_type: DepositAddressType,
 ) {
     record_event(&Event::ReceivedUtxos {
         mint_txid,
         to_account: account,
         utxos: utxos.clone(),
+        // We omit the default address type to keep the encoding of P2WPKH
+        // deposits unchanged.
+        address_type: (address_type != DepositAddressType::P2wpkh).then_some(address_type),
     });
 
-    state.add_utxos(account, utxos);
+    state.add_utxos(account, utxos, address_type);
 }
 
 pub fn remove_retrieve_btc_request(state: &mut CkBtcMinterState, request: RetrieveBtcRequest) {
@@ -65,6 +70,33 @@ pub fn sent_transaction(state: &mut CkBtcMinterState, tx: SubmittedBtcTransactio
     state.push_submitted_transaction(tx);
 }
 
+pub fn sent_consolidation_transaction(
+    state: &mut CkBtcMinterState,
+    tx: SubmittedBtcTransaction,
+    fee_burn: ConsolidationFeeBurn,
+) {
+    assert!(
+        tx.is_utxo_consolidation(),
+        "bug: consolidation transactions cannot serve retrieve_btc requests"
+    );
+    record_event(&Event::SentConsolidationTransaction {
+        txid: tx.txid,
+        utxos: tx.used_utxos.clone(),
+        change_output: tx
+            .change_output
+            .clone()
+            .expect("bug: all consolidation transactions must have the change output"),
+        submitted_at: tx.submitted_at,
+        fee_per_vbyte: tx
+            .fee_per_vbyte
+            .expect("bug: all consolidation transactions must have the fee"),
+        fee_burn: fee_burn.clone(),
+    });
+
+    state.record_consolidation_fee_burn(&fee_burn);
+    state.push_submitted_transaction(tx);
+}
+
 pub fn confirm_transaction(state: &mut CkBtcMinterState, txid: &Txid) {
     record_event(&Event::ConfirmedBtcTransaction { txid: *txid });
     state.finalize_transaction(txid);
@@ -95,6 +127,7 @@ pub fn replace_transaction(
     state: &mut CkBtcMinterState,
     old_txid: Txid,
     new_tx: SubmittedBtcTransaction,
+    consolidation_fee_burn: Option<ConsolidationFeeBurn>,
 ) {
     record_event(&Event::ReplacedBtcTransaction {
         old_txid,
@@ -107,7 +140,11 @@ pub fn replace_transaction(
         fee_per_vbyte: new_tx
             .fee_per_vbyte
             .expect("bug: all replacement transactions must have the fee"),
+        consolidation_fee_burn: consolidation_fee_burn.clone(),
     });
+    if let Some(burn) = consolidation_fee_burn {
+        state.record_consolidation_fee_burn(&burn);
+    }
     state.replace_transaction(&old_txid, new_tx);
 }
 
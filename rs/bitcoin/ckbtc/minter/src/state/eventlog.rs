@@ -1,8 +1,9 @@
+use crate::address::DepositAddressType;
 use crate::lifecycle::init::InitArgs;
 use crate::lifecycle::upgrade::UpgradeArgs;
 use crate::state::{
-    ChangeOutput, CkBtcMinterState, FinalizedBtcRetrieval, FinalizedStatus, Overdraft,
-    RetrieveBtcRequest, SubmittedBtcTransaction, UtxoCheckStatus,
+    ChangeOutput, CkBtcMinterState, ConsolidationFeeBurn, FinalizedBtcRetrieval, FinalizedStatus,
+    Overdraft, RetrieveBtcRequest, SubmittedBtcTransaction, UtxoCheckStatus,
 };
 use crate::state::{ReimburseDepositTask, ReimbursedDeposit, ReimbursementReason};
 use candid::Principal;
@@ -41,6 +42,11 @@ pub enum Event {
         to_account: Account,
         #[serde(rename = "utxos")]
         utxos: Vec<Utxo>,
+        /// The type of the deposit address that received the UTXOs.
+        /// None stands for P2WPKH addresses.
+        #[serde(rename = "address_type")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        address_type: Option<DepositAddressType>,
     },
 
     /// Indicates that the minter accepted a new retrieve_btc request.
@@ -102,6 +108,35 @@ pub enum Event {
         /// The fee per vbyte (in millisatoshi) that we used for the transaction.
         #[serde(rename = "fee")]
         fee_per_vbyte: u64,
+        /// The ckBTC burned to pay for the additional fee of a replacement
+        /// UTXO consolidation transaction.
+        #[serde(rename = "consolidation_fee_burn")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        consolidation_fee_burn: Option<ConsolidationFeeBurn>,
+    },
+
+    /// Indicates that the minter sent out a new transaction consolidating
+    /// some of its UTXOs into a single output to its main address.
+    #[serde(rename = "sent_consolidation_transaction")]
+    SentConsolidationTransaction {
+        /// The Txid of the Bitcoin transaction.
+        #[serde(rename = "txid")]
+        txid: Txid,
+        /// UTXOs consolidated by the transaction.
+        #[serde(rename = "utxos")]
+        utxos: Vec<Utxo>,
+        /// The output holding the consolidated value.
+        #[serde(rename = "change_output")]
+        change_output: ChangeOutput,
+        /// The IC time at which the minter submitted the transaction.
+        #[serde(rename = "submitted_at")]
+        submitted_at: u64,
+        /// The fee per vbyte (in millisatoshi) that we used for the transaction.
+        #[serde(rename = "fee")]
+        fee_per_vbyte: u64,
+        /// The ckBTC burned to pay for the transaction fee.
+        #[serde(rename = "fee_burn")]
+        fee_burn: ConsolidationFeeBurn,
     },
 
     /// Indicates that the minter received enough confirmations for a bitcoin
@@ -207,8 +242,11 @@ pub fn replay(mut events: impl Iterator<Item = Event>) -> Result<CkBtcMinterStat
             }
             Event::Upgrade(args) => state.upgrade(args),
             Event::ReceivedUtxos {
-                to_account, utxos, ..
-            } => state.add_utxos(to_account, utxos),
+                to_account,
+                utxos,
+                address_type,
+                ..
+            } => state.add_utxos(to_account, utxos, address_type.unwrap_or_default()),
             Event::AcceptedRetrieveBtcRequest(req) => {
                 if let Some(account) = req.reimbursement_account {
                     state
@@ -262,13 +300,43 @@ pub fn replay(mut events: impl Iterator<Item = Event>) -> Result<CkBtcMinterStat
                     submitted_at,
                 });
             }
+            Event::SentConsolidationTransaction {
+                txid,
+                utxos,
+                change_output,
+                submitted_at,
+                fee_per_vbyte,
+                fee_burn,
+            } => {
+                state.record_consolidation_fee_burn(&fee_burn);
+                for utxo in utxos.iter() {
+                    if !state.available_utxos.remove(utxo) {
+                        return Err(ReplayLogError::InconsistentLog(format!(
+                            "Attempted to consolidate an unavailable UTXO {:?}",
+                            utxo
+                        )));
+                    }
+                }
+                state.push_submitted_transaction(SubmittedBtcTransaction {
+                    requests: vec![],
+                    txid,
+                    used_utxos: utxos,
+                    fee_per_vbyte: Some(fee_per_vbyte),
+                    change_output: Some(change_output),
+                    submitted_at,
+                });
+            }
             Event::ReplacedBtcTransaction {
                 old_txid,
                 new_txid,
                 change_output,
                 submitted_at,
                 fee_per_vbyte,
+                consolidation_fee_burn,
             } => {
+                if let Some(burn) = consolidation_fee_burn {
+                    state.record_consolidation_fee_burn(&burn);
+                }
                 let (requests, used_utxos) = match state
                     .submitted_transactions
                     .iter()
@@ -13,6 +13,7 @@ pub enum TaskType {
     ProcessLogic,
     RefreshFeePercentiles,
     DistributeKytFee,
+    ConsolidateUtxos,
 }
 
 #[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
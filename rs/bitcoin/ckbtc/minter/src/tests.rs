@@ -1,13 +1,16 @@
 use crate::MINTER_FEE_CONSTANT;
 use crate::{
-    address::BitcoinAddress, build_unsigned_transaction, estimate_fee, fake_sign, greedy,
-    signature::EncodedSignature, tx, BuildTxError,
+    address::{BitcoinAddress, DepositAddressType},
+    build_consolidation_transaction, build_unsigned_transaction, consolidation_fee, estimate_fee,
+    fake_sign, greedy, select_utxos_to_consolidate,
+    signature::{EncodedSignature, SchnorrSignature},
+    tx, BuildTxError,
 };
 use crate::{
     lifecycle::init::InitArgs,
     state::{
-        ChangeOutput, CkBtcMinterState, Mode, RetrieveBtcRequest, RetrieveBtcStatus,
-        SubmittedBtcTransaction,
+        ChangeOutput, CkBtcMinterState, ConsolidationFeeBurn, Mode, RetrieveBtcRequest,
+        RetrieveBtcStatus, SubmittedBtcTransaction,
     },
 };
 use bitcoin::network::constants::Network as BtcNetwork;
@@ -153,10 +156,15 @@ fn signed_tx_to_bitcoin_tx(tx: &tx::SignedTransaction) -> bitcoin::Transaction {
                 },
                 sequence: txin.sequence,
                 script_sig: bitcoin::Script::default(),
-                witness: bitcoin::Witness::from_vec(vec![
-                    txin.signature.as_slice().to_vec(),
-                    txin.pubkey.to_vec(),
-                ]),
+                witness: match &txin.witness {
+                    tx::Witness::P2wpkh { signature, pubkey } => bitcoin::Witness::from_vec(vec![
+                        signature.as_slice().to_vec(),
+                        pubkey.to_vec(),
+                    ]),
+                    tx::Witness::P2trKeyPath { signature } => {
+                        bitcoin::Witness::from_vec(vec![signature.as_slice().to_vec()])
+                    }
+                },
             })
             .collect(),
         output: tx
@@ -240,6 +248,7 @@ fn should_have_same_input_and_output_count() {
         vec![(out1_addr.clone(), 100_000), (out2_addr.clone(), 99_999)],
         minter_addr.clone(),
         fee_per_vbyte,
+        &BTreeSet::new(),
     )
     .expect("failed to build a transaction");
 
@@ -288,10 +297,11 @@ fn test_min_change_amount() {
         vec![(out1_addr.clone(), 100_000), (out2_addr.clone(), 99_999)],
         minter_addr.clone(),
         fee_per_vbyte,
+        &BTreeSet::new(),
     )
     .expect("failed to build a transaction");
 
-    let fee = fake_sign(&tx).vsize() as u64 * fee_per_vbyte / 1000;
+    let fee = fake_sign(&tx, &BTreeSet::new()).vsize() as u64 * fee_per_vbyte / 1000;
     let minter_fee = crate::MINTER_FEE_PER_INPUT * tx.inputs.len() as u64
         + crate::MINTER_FEE_PER_OUTPUT * tx.outputs.len() as u64
         + crate::MINTER_FEE_CONSTANT;
@@ -348,6 +358,7 @@ fn test_no_dust_outputs() {
             vec![(out1_addr.clone(), 99_900), (out2_addr.clone(), 100)],
             minter_addr.clone(),
             fee_per_vbyte,
+            &BTreeSet::new(),
         ),
         Err(BuildTxError::DustOutput {
             address: out2_addr.clone(),
@@ -363,6 +374,7 @@ fn test_no_dust_outputs() {
             vec![(out1_addr, 99_000), (out2_addr.clone(), 1000)],
             minter_addr,
             fee_per_vbyte,
+            &BTreeSet::new(),
         ),
         Err(BuildTxError::DustOutput {
             address: out2_addr,
@@ -412,21 +424,28 @@ fn arb_unsigned_input(
     })
 }
 
-fn arb_signed_input() -> impl Strategy<Value = tx::SignedInput> {
-    (
-        arb_out_point(),
-        any::<u32>(),
-        pvec(1u8..0xff, 64),
-        pvec(any::<u8>(), 32),
-    )
-        .prop_map(
-            |(previous_output, sequence, sec1, pubkey)| tx::SignedInput {
-                previous_output,
-                sequence,
+fn arb_witness() -> impl Strategy<Value = tx::Witness> {
+    prop_oneof![
+        (pvec(1u8..0xff, 64), pvec(any::<u8>(), 32)).prop_map(|(sec1, pubkey)| {
+            tx::Witness::P2wpkh {
                 signature: EncodedSignature::from_sec1(&sec1),
                 pubkey: ByteBuf::from(pubkey),
-            },
-        )
+            }
+        }),
+        pvec(any::<u8>(), 64).prop_map(|sig| tx::Witness::P2trKeyPath {
+            signature: SchnorrSignature::try_from_slice(&sig).unwrap(),
+        }),
+    ]
+}
+
+fn arb_signed_input() -> impl Strategy<Value = tx::SignedInput> {
+    (arb_out_point(), any::<u32>(), arb_witness()).prop_map(
+        |(previous_output, sequence, witness)| tx::SignedInput {
+            previous_output,
+            sequence,
+            witness,
+        },
+    )
 }
 
 fn arb_address() -> impl Strategy<Value = BitcoinAddress> {
@@ -650,6 +669,57 @@ proptest! {
         }
     }
 
+    #[test]
+    fn unsigned_tx_taproot_sighash_model(
+        inputs_data in pvec(
+            (
+                arb_utxo(5_000u64..1_000_000_000),
+                any::<u32>(),
+                arb_address(),
+            ),
+            1..20
+        ),
+        outputs in pvec(arb_tx_out(), 1..20),
+        lock_time in any::<u32>(),
+    ) {
+        let inputs: Vec<tx::UnsignedInput> = inputs_data
+            .iter()
+            .map(|(utxo, seq, _)| tx::UnsignedInput {
+                previous_output: utxo.outpoint.clone(),
+                value: utxo.value,
+                sequence: *seq,
+            })
+            .collect();
+        let spent_addresses: Vec<BitcoinAddress> = inputs_data
+            .iter()
+            .map(|(_, _, address)| address.clone())
+            .collect();
+        let prevouts: Vec<bitcoin::TxOut> = inputs_data
+            .iter()
+            .map(|(utxo, _, address)| bitcoin::TxOut {
+                value: utxo.value,
+                script_pubkey: address_to_script_pubkey(address),
+            })
+            .collect();
+        let arb_tx = tx::UnsignedTransaction { inputs, outputs, lock_time };
+        let btc_tx = unsigned_tx_to_bitcoin_tx(&arb_tx);
+
+        let sighasher = tx::TaprootSigHasher::new(&arb_tx, &spent_addresses);
+        let mut btc_sighasher = bitcoin::util::sighash::SighashCache::new(&btc_tx);
+
+        for i in 0..arb_tx.inputs.len() {
+            let sighash = sighasher.sighash(i);
+            let btc_sighash = btc_sighasher
+                .taproot_key_spend_signature_hash(
+                    i,
+                    &bitcoin::util::sighash::Prevouts::All(&prevouts),
+                    bitcoin::SchnorrSighashType::Default,
+                )
+                .unwrap();
+            prop_assert_eq!(hex::encode(sighash), hex::encode(btc_sighash));
+        }
+    }
+
     #[test]
     fn signed_tx_encoding_model(
         inputs in pvec(arb_signed_input(), 1..20),
@@ -692,11 +762,12 @@ proptest! {
             &mut utxos,
             vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), target)],
             BitcoinAddress::P2wpkhV0(main_pkhash),
-            fee_per_vbyte
+            fee_per_vbyte,
+            &BTreeSet::new(),
         )
         .expect("failed to build transaction");
 
-        let vsize = fake_sign(&unsigned_tx).vsize() as u64;
+        let vsize = fake_sign(&unsigned_tx, &BTreeSet::new()).vsize() as u64;
 
         prop_assert_eq!(
             vsize,
@@ -731,7 +802,8 @@ proptest! {
             &mut utxos,
             vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), target)],
             BitcoinAddress::P2wpkhV0(main_pkhash),
-            fee_per_vbyte
+            fee_per_vbyte,
+            &BTreeSet::new(),
         )
         .expect("failed to build transaction");
 
@@ -758,11 +830,12 @@ proptest! {
             &mut utxos,
             vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), target)],
             BitcoinAddress::P2wpkhV0(main_pkhash),
-            fee_per_vbyte
+            fee_per_vbyte,
+            &BTreeSet::new(),
         )
         .expect("failed to build transaction");
 
-        let fee = fake_sign(&unsigned_tx).vsize() as u64 * fee_per_vbyte / 1000;
+        let fee = fake_sign(&unsigned_tx, &BTreeSet::new()).vsize() as u64 * fee_per_vbyte / 1000;
         let minter_fee =
             crate::MINTER_FEE_PER_INPUT * unsigned_tx.inputs.len() as u64 +
             crate::MINTER_FEE_PER_OUTPUT * unsigned_tx.outputs.len() as u64 +
@@ -806,7 +879,8 @@ proptest! {
                 &mut utxos,
                 vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), total_value * 2)],
                 BitcoinAddress::P2wpkhV0(main_pkhash),
-                fee_per_vbyte
+                fee_per_vbyte,
+                &BTreeSet::new(),
             ).expect_err("build transaction should fail because the amount is too high"),
             BuildTxError::NotEnoughFunds
         );
@@ -817,7 +891,8 @@ proptest! {
                 &mut utxos,
                 vec![(BitcoinAddress::P2wpkhV0(dst_pkhash), 1)],
                 BitcoinAddress::P2wpkhV0(main_pkhash),
-                fee_per_vbyte
+                fee_per_vbyte,
+                &BTreeSet::new(),
             ).expect_err("build transaction should fail because the amount is too low to pay the fee"),
             BuildTxError::AmountTooLow
         );
@@ -841,7 +916,7 @@ proptest! {
             kyt_principal: None
         });
         for (utxo, acc_idx) in utxos_acc_idx {
-            state.add_utxos(accounts[acc_idx], vec![utxo]);
+            state.add_utxos(accounts[acc_idx], vec![utxo], DepositAddressType::P2wpkh);
             state.check_invariants().expect("invariant check failed");
         }
     }
@@ -868,7 +943,7 @@ proptest! {
         let mut available_amount = 0;
         for (utxo, acc_idx) in utxos_acc_idx {
             available_amount += utxo.value;
-            state.add_utxos(accounts[acc_idx], vec![utxo]);
+            state.add_utxos(accounts[acc_idx], vec![utxo], DepositAddressType::P2wpkh);
         }
         for req in requests {
             let block_index = req.block_index;
@@ -909,7 +984,7 @@ proptest! {
         });
 
         for (utxo, acc_idx) in utxos_acc_idx {
-            state.add_utxos(accounts[acc_idx], vec![utxo]);
+            state.add_utxos(accounts[acc_idx], vec![utxo], DepositAddressType::P2wpkh);
         }
         let fee_per_vbyte = 100_000u64;
 
@@ -917,7 +992,8 @@ proptest! {
             &mut state.available_utxos,
             requests.iter().map(|r| (r.address.clone(), r.amount)).collect(),
             BitcoinAddress::P2wpkhV0(main_pkhash),
-            fee_per_vbyte
+            fee_per_vbyte,
+            &BTreeSet::new(),
         )
         .expect("failed to build transaction");
         let mut txids = vec![tx.txid()];
@@ -942,6 +1018,7 @@ proptest! {
                 requests.iter().map(|r| (r.address.clone(), r.amount)).collect(),
                 BitcoinAddress::P2wpkhV0(main_pkhash),
                 fee_per_vbyte + 1000 * i as u64,
+                &BTreeSet::new(),
             )
             .expect("failed to build transaction");
 
@@ -1121,6 +1198,168 @@ proptest! {
     }
 }
 
+#[test]
+fn should_select_smallest_utxos_worth_consolidating() {
+    let mut utxos: BTreeSet<Utxo> = [100, 1_000, 2_000, 3_000, 4_000, 50_000]
+        .into_iter()
+        .map(dummy_utxo_from_value)
+        .collect();
+    // Spending an input costs 68 vbytes * 10 sat/vbyte = 680 satoshi.
+    let fee_per_vbyte = 10_000;
+
+    let selected = select_utxos_to_consolidate(&mut utxos, 3, fee_per_vbyte);
+
+    assert_eq!(
+        selected.iter().map(|u| u.value).collect::<Vec<_>>(),
+        vec![1_000, 2_000, 3_000]
+    );
+    assert_eq!(
+        utxos.iter().map(|u| u.value).collect::<BTreeSet<_>>(),
+        BTreeSet::from([100, 4_000, 50_000])
+    );
+}
+
+#[test]
+fn should_build_consolidation_transaction() {
+    let utxos: Vec<Utxo> = (1..=10u64)
+        .map(|i| dummy_utxo_from_value(i * 10_000))
+        .collect();
+    let inputs_value = utxos.iter().map(|u| u.value).sum::<u64>();
+    let minter_addr = BitcoinAddress::P2wpkhV0([0; 20]);
+    let fee_per_vbyte = 10_000;
+
+    let (tx, change_output) = build_consolidation_transaction(
+        &utxos,
+        minter_addr.clone(),
+        fee_per_vbyte,
+        &BTreeSet::new(),
+    )
+    .expect("failed to build a consolidation transaction");
+
+    let fee = fake_sign(&tx, &BTreeSet::new()).vsize() as u64 * fee_per_vbyte / 1000;
+    assert_eq!(tx.inputs.len(), utxos.len());
+    assert_eq!(
+        tx.outputs,
+        vec![tx::TxOut {
+            address: minter_addr,
+            value: inputs_value - fee,
+        }]
+    );
+    assert_eq!(
+        change_output,
+        ChangeOutput {
+            vout: 0,
+            value: inputs_value - fee
+        }
+    );
+}
+
+#[test]
+fn should_not_build_consolidation_transaction_with_dust_output() {
+    let utxos: Vec<Utxo> = (1..=10u64).map(dummy_utxo_from_value).collect();
+
+    assert_eq!(
+        build_consolidation_transaction(
+            &utxos,
+            BitcoinAddress::P2wpkhV0([0; 20]),
+            10_000,
+            &BTreeSet::new()
+        ),
+        Err(BuildTxError::AmountTooLow)
+    );
+}
+
+#[test]
+fn should_consolidate_utxos_once_at_a_time() {
+    let mut state = CkBtcMinterState::from(InitArgs {
+        btc_network: Network::Regtest.into(),
+        ecdsa_key_name: "".to_string(),
+        retrieve_btc_min_amount: 0,
+        ledger_id: CanisterId::from_u64(42),
+        max_time_in_queue_nanos: 0,
+        min_confirmations: None,
+        mode: Mode::GeneralAvailability,
+        kyt_fee: None,
+        kyt_principal: None,
+    });
+    let account = Account {
+        owner: Principal::anonymous(),
+        subaccount: None,
+    };
+    state.add_utxos(
+        account,
+        (1..=10u64)
+            .map(|i| dummy_utxo_from_value(i * 10_000))
+            .collect(),
+        DepositAddressType::P2wpkh,
+    );
+    assert!(!state.should_consolidate_utxos(11));
+    assert!(state.should_consolidate_utxos(10));
+
+    let used_utxos = select_utxos_to_consolidate(&mut state.available_utxos, 5, 10_000);
+    let (tx, change_output) = build_consolidation_transaction(
+        &used_utxos,
+        BitcoinAddress::P2wpkhV0([0; 20]),
+        10_000,
+        &BTreeSet::new(),
+    )
+    .unwrap();
+    let txid = tx.txid();
+    state.push_submitted_transaction(SubmittedBtcTransaction {
+        requests: vec![],
+        txid,
+        used_utxos,
+        submitted_at: 0,
+        change_output: Some(change_output),
+        fee_per_vbyte: Some(10_000),
+    });
+    assert!(state.submitted_transactions[0].is_utxo_consolidation());
+    assert!(!state.should_consolidate_utxos(1));
+    state.check_invariants().expect("violated invariants");
+
+    state.finalize_transaction(&txid);
+    assert_eq!(state.finalized_requests_count, 0);
+    assert!(state.should_consolidate_utxos(1));
+    state.check_invariants().expect("violated invariants");
+}
+
+#[test]
+fn should_compute_and_record_consolidation_fees() {
+    let mut state = CkBtcMinterState::from(InitArgs {
+        btc_network: Network::Regtest.into(),
+        ecdsa_key_name: "".to_string(),
+        retrieve_btc_min_amount: 0,
+        ledger_id: CanisterId::from_u64(42),
+        max_time_in_queue_nanos: 0,
+        min_confirmations: None,
+        mode: Mode::GeneralAvailability,
+        kyt_fee: None,
+        kyt_principal: None,
+    });
+    let utxos: Vec<Utxo> = (1..=10u64)
+        .map(|i| dummy_utxo_from_value(i * 10_000))
+        .collect();
+    let (_tx, change_output) = build_consolidation_transaction(
+        &utxos,
+        BitcoinAddress::P2wpkhV0([0; 20]),
+        10_000,
+        &BTreeSet::new(),
+    )
+    .unwrap();
+    let fee = consolidation_fee(&utxos, &change_output);
+    assert_eq!(fee, 550_000 - change_output.value);
+
+    state.record_consolidation_fee_burn(&ConsolidationFeeBurn {
+        amount: fee,
+        block_index: 1,
+    });
+    state.record_consolidation_fee_burn(&ConsolidationFeeBurn {
+        amount: 100,
+        block_index: 2,
+    });
+    assert_eq!(state.total_consolidation_fees, fee + 100);
+}
+
 #[test]
 fn can_form_a_batch_conditions() {
     let mut state = CkBtcMinterState::from(InitArgs {
@@ -1175,3 +1414,212 @@ fn can_form_a_batch_conditions() {
     // Two request, long enough since last_transaction_submission_time, pass.
     assert!(state.can_form_a_batch(10, 10600));
 }
+
+#[test]
+fn should_estimate_lower_fee_for_taproot_inputs() {
+    let utxos: Vec<Utxo> = (1..=8u64)
+        .map(|i| dummy_utxo_from_value(i * 100_000))
+        .collect();
+    let taproot_outpoints: BTreeSet<OutPoint> = utxos
+        .iter()
+        .step_by(2)
+        .map(|u| u.outpoint.clone())
+        .collect();
+    let minter_addr = BitcoinAddress::P2wpkhV0([0; 20]);
+    let fee_per_vbyte = 10_000;
+
+    let (p2wpkh_tx, p2wpkh_change) = build_consolidation_transaction(
+        &utxos,
+        minter_addr.clone(),
+        fee_per_vbyte,
+        &BTreeSet::new(),
+    )
+    .unwrap();
+    let (mixed_tx, mixed_change) =
+        build_consolidation_transaction(&utxos, minter_addr, fee_per_vbyte, &taproot_outpoints)
+            .unwrap();
+
+    // The transactions are the same except for the estimated fee.
+    assert_eq!(p2wpkh_tx.inputs, mixed_tx.inputs);
+
+    let p2wpkh_vsize = fake_sign(&p2wpkh_tx, &BTreeSet::new()).vsize();
+    let mixed_vsize = fake_sign(&mixed_tx, &taproot_outpoints).vsize();
+    // A P2WPKH input weighs 272 units at most, a Taproot key path input 230.
+    assert_eq!(
+        (p2wpkh_vsize - mixed_vsize) * 4,
+        taproot_outpoints.len() * (272 - 230)
+    );
+    assert_eq!(
+        mixed_change.value - p2wpkh_change.value,
+        (p2wpkh_vsize - mixed_vsize) as u64 * fee_per_vbyte / 1000
+    );
+}
+
+#[test]
+fn should_track_taproot_outpoints() {
+    let mut state = CkBtcMinterState::from(InitArgs {
+        btc_network: Network::Regtest.into(),
+        ecdsa_key_name: "".to_string(),
+        retrieve_btc_min_amount: 0,
+        ledger_id: CanisterId::from_u64(42),
+        max_time_in_queue_nanos: 0,
+        min_confirmations: None,
+        mode: Mode::GeneralAvailability,
+        kyt_fee: None,
+        kyt_principal: None,
+    });
+    let account = Account {
+        owner: Principal::anonymous(),
+        subaccount: None,
+    };
+    let p2wpkh_utxo = dummy_utxo_from_value(100_000);
+    let p2tr_utxo = dummy_utxo_from_value(200_000);
+    state.add_utxos(
+        account,
+        vec![p2wpkh_utxo.clone()],
+        DepositAddressType::P2wpkh,
+    );
+    state.add_utxos(account, vec![p2tr_utxo.clone()], DepositAddressType::P2tr);
+    assert_eq!(
+        state.taproot_outpoints,
+        BTreeSet::from([p2tr_utxo.outpoint.clone()])
+    );
+    state.check_invariants().expect("violated invariants");
+
+    let used_utxos = vec![p2wpkh_utxo, p2tr_utxo];
+    let (tx, change_output) = build_consolidation_transaction(
+        &used_utxos,
+        BitcoinAddress::P2wpkhV0([0; 20]),
+        10_000,
+        &state.taproot_outpoints,
+    )
+    .unwrap();
+    for utxo in used_utxos.iter() {
+        assert!(state.available_utxos.remove(utxo));
+    }
+    let txid = tx.txid();
+    state.push_submitted_transaction(SubmittedBtcTransaction {
+        requests: vec![],
+        txid,
+        used_utxos,
+        submitted_at: 0,
+        change_output: Some(change_output),
+        fee_per_vbyte: Some(10_000),
+    });
+
+    state.finalize_transaction(&txid);
+    assert!(state.taproot_outpoints.is_empty());
+    state.check_invariants().expect("violated invariants");
+}
+
+#[tokio::test]
+async fn should_sign_taproot_inputs_with_schnorr_signer() {
+    use crate::address::{account_to_taproot_address, derivation_path};
+    use crate::management::{CallError, SchnorrSigner};
+    use crate::sign_transaction;
+    use crate::ECDSAPublicKey;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct MockSchnorrSigner {
+        requests: RefCell<Vec<(Vec<ByteBuf>, [u8; 32])>>,
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl SchnorrSigner for MockSchnorrSigner {
+        async fn sign_taproot_key_spend(
+            &self,
+            _key_name: String,
+            derivation_path: Vec<ByteBuf>,
+            message: [u8; 32],
+        ) -> Result<Vec<u8>, CallError> {
+            let mut requests = self.requests.borrow_mut();
+            requests.push((derivation_path, message));
+            Ok(vec![requests.len() as u8; 64])
+        }
+    }
+
+    // The secp256k1 generator point.
+    let master_key = ECDSAPublicKey {
+        public_key: hex::decode(
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        )
+        .unwrap(),
+        chain_code: vec![0; 32],
+    };
+    let accounts = [
+        Account {
+            owner: Principal::anonymous(),
+            subaccount: None,
+        },
+        Account {
+            owner: Principal::anonymous(),
+            subaccount: Some([1; 32]),
+        },
+    ];
+    let utxos = [
+        dummy_utxo_from_value(100_000),
+        dummy_utxo_from_value(200_000),
+    ];
+    let output_account: BTreeMap<OutPoint, Account> = utxos
+        .iter()
+        .zip(accounts.iter())
+        .map(|(utxo, account)| (utxo.outpoint.clone(), *account))
+        .collect();
+    let taproot_outpoints: BTreeSet<OutPoint> = utxos.iter().map(|u| u.outpoint.clone()).collect();
+    let (unsigned_tx, _) = build_consolidation_transaction(
+        &utxos,
+        BitcoinAddress::P2wpkhV0([0; 20]),
+        10_000,
+        &taproot_outpoints,
+    )
+    .unwrap();
+
+    let signer = MockSchnorrSigner::default();
+    let signed_tx = sign_transaction(
+        "key".to_string(),
+        &master_key,
+        Some(&master_key),
+        &signer,
+        &output_account,
+        &taproot_outpoints,
+        unsigned_tx.clone(),
+    )
+    .await
+    .expect("failed to sign the transaction");
+
+    let spent_addresses: Vec<_> = unsigned_tx
+        .inputs
+        .iter()
+        .map(|input| {
+            account_to_taproot_address(&master_key, &output_account[&input.previous_output])
+        })
+        .collect();
+    let sighasher = tx::TaprootSigHasher::new(&unsigned_tx, &spent_addresses);
+    let requests = signer.requests.borrow();
+    assert_eq!(requests.len(), unsigned_tx.inputs.len());
+    for (i, input) in unsigned_tx.inputs.iter().enumerate() {
+        let account = &output_account[&input.previous_output];
+        assert_eq!(
+            requests[i],
+            (derivation_path(account), sighasher.sighash(i))
+        );
+        assert_eq!(
+            signed_tx.inputs[i].witness,
+            tx::Witness::P2trKeyPath {
+                signature: SchnorrSignature::try_from_slice(&[i as u8 + 1; 64]).unwrap()
+            }
+        );
+    }
+
+    let decoded_btc_tx = bitcoin::Transaction::deserialize(&signed_tx.serialize())
+        .expect("failed to deserialize a signed transaction");
+    assert!(decoded_btc_tx
+        .input
+        .iter()
+        .all(|txin| txin.witness.len() == 1));
+    assert_eq!(
+        signed_tx.vsize(),
+        fake_sign(&unsigned_tx, &taproot_outpoints).vsize()
+    );
+}
@@ -1,8 +1,8 @@
-//! This module contains definitions of Bitcoin P2PKWH transactions and rules to
-//! encode them into a byte stream.
+//! This module contains definitions of Bitcoin transactions spending P2WPKH
+//! and P2TR (key path) outputs and rules to encode them into a byte stream.
 
 use crate::address::BitcoinAddress;
-use crate::signature::EncodedSignature;
+use crate::signature::{EncodedSignature, SchnorrSignature};
 use ic_crypto_sha2::Sha256;
 use serde_bytes::{ByteBuf, Bytes};
 use std::fmt;
@@ -22,6 +22,9 @@ const MARKER: u8 = 0;
 const FLAGS: u8 = 1;
 // The signature applies to all inputs and outputs.
 pub const SIGHASH_ALL: u32 = 1;
+// The Taproot signature applies to all inputs and outputs.
+// See https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#common-signature-message.
+pub const SIGHASH_DEFAULT: u8 = 0;
 
 /// Bitcoin script opcodes.
 mod ops {
@@ -166,6 +169,16 @@ impl Buffer for CountBytes {
     }
 }
 
+/// Returns a hasher initialized with the BIP-340 tag prefix, so that the
+/// final digest is `SHA256(SHA256(tag) || SHA256(tag) || data)`.
+pub fn tagged_hasher(tag: &str) -> Sha256 {
+    let tag_hash = Sha256::hash(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.write(&tag_hash[..]);
+    hasher.write(&tag_hash[..]);
+    hasher
+}
+
 /// SHA-256 followed by Ripemd160, also known as HASH160.
 pub fn hash160(bytes: &[u8]) -> [u8; 20] {
     use ripemd::{Digest, Ripemd160};
@@ -200,10 +213,23 @@ pub fn write_compact_size(n: usize, buf: &mut impl Buffer) {
 pub struct SignedInput {
     pub previous_output: OutPoint,
     pub sequence: u32,
-    pub signature: EncodedSignature,
-    // The public key bytes.
-    // Must be PUBKEY_LEN bytes long.
-    pub pubkey: ByteBuf,
+    pub witness: Witness,
+}
+
+/// The witness data unlocking a transaction input.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Witness {
+    /// Spends a P2WPKH output: `<signature> <pubkey>`.
+    /// See BIP-141.
+    P2wpkh {
+        signature: EncodedSignature,
+        // The public key bytes.
+        // Must be PUBKEY_LEN bytes long.
+        pubkey: ByteBuf,
+    },
+    /// Spends a P2TR output using the key path: `<signature>`.
+    /// See BIP-341.
+    P2trKeyPath { signature: SchnorrSignature },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -340,6 +366,131 @@ impl<'a> TxSigHasher<'a> {
     }
 }
 
+/// Computes signature hashes for Taproot key path spends.
+///
+/// Unlike the SegWit v0 signature hash, the Taproot signature hash commits to
+/// the amounts and the scriptPubKeys of all outputs that the transaction spends.
+pub struct TaprootSigHasher<'a> {
+    tx: &'a UnsignedTransaction,
+    sha_prevouts: [u8; 32],
+    sha_amounts: [u8; 32],
+    sha_scriptpubkeys: [u8; 32],
+    sha_sequences: [u8; 32],
+    sha_outputs: [u8; 32],
+}
+
+impl<'a> TaprootSigHasher<'a> {
+    /// Creates a new hasher for the specified transaction. The i-th element of
+    /// `spent_addresses` must be the address of the output that the i-th
+    /// transaction input spends.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of addresses does not match the
+    /// number of transaction inputs.
+    pub fn new(tx: &'a UnsignedTransaction, spent_addresses: &[BitcoinAddress]) -> Self {
+        assert_eq!(
+            tx.inputs.len(),
+            spent_addresses.len(),
+            "bug: every transaction input must have a spent address"
+        );
+
+        let sha_prevouts = {
+            let mut hasher = Sha256::new();
+            for input in tx.inputs.iter() {
+                input.previous_output.encode(&mut hasher);
+            }
+            hasher.finish()
+        };
+
+        let sha_amounts = {
+            let mut hasher = Sha256::new();
+            for input in tx.inputs.iter() {
+                input.value.encode(&mut hasher);
+            }
+            hasher.finish()
+        };
+
+        let sha_scriptpubkeys = {
+            let mut hasher = Sha256::new();
+            for address in spent_addresses.iter() {
+                encode_address_script_pubkey(address, &mut hasher);
+            }
+            hasher.finish()
+        };
+
+        let sha_sequences = {
+            let mut hasher = Sha256::new();
+            for input in tx.inputs.iter() {
+                input.sequence.encode(&mut hasher);
+            }
+            hasher.finish()
+        };
+
+        let sha_outputs = {
+            let mut hasher = Sha256::new();
+            for output in tx.outputs.iter() {
+                output.encode(&mut hasher);
+            }
+            hasher.finish()
+        };
+
+        Self {
+            tx,
+            sha_prevouts,
+            sha_amounts,
+            sha_scriptpubkeys,
+            sha_sequences,
+            sha_outputs,
+        }
+    }
+
+    pub fn encode_sighash_data(&self, input_index: usize, buf: &mut impl Buffer) {
+        assert!(
+            input_index < self.tx.inputs.len(),
+            "bug: invalid input index {}",
+            input_index
+        );
+
+        //  0. The sighash epoch (1 byte)
+        buf.write(&[0]);
+        //  1. hash_type (1 byte)
+        buf.write(&[SIGHASH_DEFAULT]);
+        //  2. nVersion of the transaction (4-byte little endian)
+        TX_VERSION.encode(buf);
+        //  3. nLockTime of the transaction (4-byte little endian)
+        self.tx.lock_time.encode(buf);
+        //  4. sha_prevouts (32-byte hash)
+        buf.write(&self.sha_prevouts[..]);
+        //  5. sha_amounts (32-byte hash)
+        buf.write(&self.sha_amounts[..]);
+        //  6. sha_scriptpubkeys (32-byte hash)
+        buf.write(&self.sha_scriptpubkeys[..]);
+        //  7. sha_sequences (32-byte hash)
+        buf.write(&self.sha_sequences[..]);
+        //  8. sha_outputs (32-byte hash)
+        buf.write(&self.sha_outputs[..]);
+        //  9. spend_type (1 byte): key path spend without an annex
+        buf.write(&[0]);
+        // 10. input_index (4-byte little endian)
+        (input_index as u32).encode(buf);
+    }
+
+    /// Returns the bytes that the input with the specified index needs to sign
+    /// for a Taproot key path spend.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the `input_index` is invalid transaction input index.
+    pub fn sighash(&self, input_index: usize) -> [u8; 32] {
+        // Spec:
+        // https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#common-signature-message
+        let mut hasher = tagged_hasher("TapSighash");
+        self.encode_sighash_data(input_index, &mut hasher);
+        hasher.finish()
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct UnsignedTransaction {
     pub inputs: Vec<UnsignedInput>,
@@ -538,11 +689,14 @@ impl Encode for SignedTransaction {
         self.inputs.encode(buf);
         self.outputs.encode(buf);
         for txin in self.inputs.iter() {
-            [
-                Bytes::new(txin.signature.as_slice()),
-                Bytes::new(&txin.pubkey),
-            ][..]
-                .encode(buf);
+            match &txin.witness {
+                Witness::P2wpkh { signature, pubkey } => {
+                    [Bytes::new(signature.as_slice()), Bytes::new(pubkey)][..].encode(buf)
+                }
+                Witness::P2trKeyPath { signature } => {
+                    [Bytes::new(signature.as_slice())][..].encode(buf)
+                }
+            }
         }
         self.lock_time.encode(buf)
     }
@@ -1,4 +1,5 @@
 use crate::{
+    address::DepositAddressType,
     logs::P1,
     state::{mutate_state, read_state, CkBtcMinterState},
     ECDSAPublicKey,
@@ -13,6 +14,8 @@ use serde::Serialize;
 pub struct GetBtcAddressArgs {
     pub owner: Option<Principal>,
     pub subaccount: Option<Subaccount>,
+    /// The type of the deposit address, P2WPKH if not set.
+    pub address_type: Option<DepositAddressType>,
 }
 
 /// PRECONDITION: s.ecdsa_public_key.is_some()
@@ -26,18 +29,58 @@ pub fn account_to_p2wpkh_address_from_state(s: &CkBtcMinterState, account: &Acco
     )
 }
 
+/// PRECONDITION: s.schnorr_public_key.is_some()
+pub fn account_to_p2tr_address_from_state(s: &CkBtcMinterState, account: &Account) -> String {
+    crate::address::account_to_p2tr_address(
+        s.btc_network,
+        s.schnorr_public_key
+            .as_ref()
+            .expect("bug: the Schnorr public key must be initialized"),
+        account,
+    )
+}
+
+/// Returns the deposit address of the specified type for the account.
+///
+/// PRECONDITION: the public key corresponding to the address type is initialized.
+pub fn deposit_address_from_state(
+    s: &CkBtcMinterState,
+    account: &Account,
+    address_type: DepositAddressType,
+) -> String {
+    match address_type {
+        DepositAddressType::P2wpkh => account_to_p2wpkh_address_from_state(s, account),
+        DepositAddressType::P2tr => account_to_p2tr_address_from_state(s, account),
+    }
+}
+
+/// Initializes the public key that the minter needs to derive deposit
+/// addresses of the specified type.
+pub async fn init_deposit_public_key(address_type: DepositAddressType) {
+    match address_type {
+        DepositAddressType::P2wpkh => {
+            init_ecdsa_public_key().await;
+        }
+        DepositAddressType::P2tr => {
+            init_schnorr_public_key().await;
+        }
+    }
+}
+
 pub async fn get_btc_address(args: GetBtcAddressArgs) -> String {
     let owner = args.owner.unwrap_or_else(ic_cdk::caller);
+    let address_type = args.address_type.unwrap_or_default();
 
-    init_ecdsa_public_key().await;
+    init_deposit_public_key(address_type).await;
 
     read_state(|s| {
-        account_to_p2wpkh_address_from_state(
+        deposit_address_from_state(
             s,
             &Account {
                 owner,
                 subaccount: args.subaccount,
             },
+            address_type,
         )
     })
 }
@@ -66,6 +109,29 @@ pub async fn init_ecdsa_public_key() -> ECDSAPublicKey {
     ecdsa_public_key
 }
 
+/// Initializes the Minter Schnorr public key. This function must be called
+/// before the minter derives Taproot addresses or spends Taproot outputs.
+pub async fn init_schnorr_public_key() -> ECDSAPublicKey {
+    if let Some(key) = read_state(|s| s.schnorr_public_key.clone()) {
+        return key;
+    };
+    let key_name = read_state(|s| s.ecdsa_key_name.clone());
+    log!(P1, "Fetching the Schnorr public key {}", &key_name);
+    let schnorr_public_key = crate::management::schnorr_public_key(key_name)
+        .await
+        .unwrap_or_else(|e| ic_cdk::trap(&format!("failed to retrieve Schnorr public key: {e}")));
+    log!(
+        P1,
+        "Schnorr public key set to {}, chain code to {}",
+        hex::encode(&schnorr_public_key.public_key),
+        hex::encode(&schnorr_public_key.chain_code)
+    );
+    mutate_state(|s| {
+        s.schnorr_public_key = Some(schnorr_public_key.clone());
+    });
+    schnorr_public_key
+}
+
 #[cfg(test)]
 mod tests {
     use ic_btc_interface::Network;
@@ -13,9 +13,10 @@ use icrc_ledger_types::icrc1::transfer::{TransferArg, TransferError};
 use num_traits::ToPrimitive;
 use serde::Serialize;
 
-use super::get_btc_address::init_ecdsa_public_key;
+use super::get_btc_address::init_deposit_public_key;
 
 use crate::{
+    address::DepositAddressType,
     guard::{balance_update_guard, GuardError},
     management::{fetch_utxo_alerts, get_utxos, CallError, CallSource},
     state,
@@ -31,6 +32,8 @@ pub struct UpdateBalanceArgs {
     pub owner: Option<Principal>,
     /// The desired subaccount on the ledger, if any.
     pub subaccount: Option<Subaccount>,
+    /// The type of the deposit address to check for new UTXOs, P2WPKH if not set.
+    pub address_type: Option<DepositAddressType>,
 }
 
 /// The outcome of UTXO processing.
@@ -135,7 +138,8 @@ pub async fn update_balance(
     state::read_state(|s| s.mode.is_deposit_available_for(&caller))
         .map_err(UpdateBalanceError::TemporarilyUnavailable)?;
 
-    init_ecdsa_public_key().await;
+    let address_type = args.address_type.unwrap_or_default();
+    init_deposit_public_key(address_type).await;
     let _guard = balance_update_guard(args.owner.unwrap_or(caller))?;
 
     let caller_account = Account {
@@ -144,7 +148,7 @@ pub async fn update_balance(
     };
 
     let address = state::read_state(|s| {
-        get_btc_address::account_to_p2wpkh_address_from_state(s, &caller_account)
+        get_btc_address::deposit_address_from_state(s, &caller_account, address_type)
     });
 
     let (btc_network, min_confirmations) =
@@ -256,6 +260,7 @@ pub async fn update_balance(
                         Some(block_index),
                         caller_account,
                         vec![utxo.clone()],
+                        address_type,
                     )
                 });
                 utxo_statuses.push(UtxoStatus::Minted {
@@ -249,6 +249,7 @@ fn test_upgrade_read_only() {
     let update_balance_args = UpdateBalanceArgs {
         owner: None,
         subaccount: None,
+        address_type: None,
     };
     let res = env
         .execute_ingress_as(
@@ -319,6 +320,7 @@ fn test_upgrade_restricted() {
     let update_balance_args = UpdateBalanceArgs {
         owner: None,
         subaccount: None,
+        address_type: None,
     };
     let res = env
         .execute_ingress_as(
@@ -370,6 +372,7 @@ fn test_upgrade_restricted() {
     let update_balance_args = UpdateBalanceArgs {
         owner: None,
         subaccount: None,
+        address_type: None,
     };
 
     let res = env
@@ -413,6 +416,7 @@ fn test_no_new_utxos() {
     let update_balance_args = UpdateBalanceArgs {
         owner: None,
         subaccount: None,
+        address_type: None,
     };
     let res = ckbtc
         .env
@@ -478,6 +482,7 @@ fn update_balance_should_return_correct_confirmations() {
     let update_balance_args = UpdateBalanceArgs {
         owner: None,
         subaccount: None,
+        address_type: None,
     };
 
     let res = ckbtc
@@ -514,6 +519,7 @@ fn test_illegal_caller() {
     let update_balance_args = UpdateBalanceArgs {
         owner: Some(Principal::from_str(&minter_id.get().to_string()).unwrap()),
         subaccount: None,
+        address_type: None,
     };
     // This call should panick
     let res = env.execute_ingress_as(
@@ -576,6 +582,7 @@ fn test_minter() {
         &GetBtcAddressArgs {
             owner: None,
             subaccount: None,
+            address_type: None,
         },
     );
     let address_1 = Address::from_str(&btc_address_1).expect("invalid bitcoin address");
@@ -585,6 +592,7 @@ fn test_minter() {
         &GetBtcAddressArgs {
             owner: None,
             subaccount: Some([1; 32]),
+            address_type: None,
         },
     );
     let address_2 = Address::from_str(&btc_address_2).expect("invalid bitcoin address");
@@ -675,6 +683,8 @@ impl CkBtcSetup {
                 minter_id: minter_id.into(),
                 maintainers: vec![kyt_provider.into()],
                 mode: KytMode::AcceptAll,
+                quorum: None,
+                blocked_addresses: None,
             }))
             .unwrap(),
         )
@@ -693,6 +703,7 @@ impl CkBtcSetup {
             "set_api_key",
             Encode!(&SetApiKeyArg {
                 api_key: "api key".to_string(),
+                provider_kind: None,
             })
             .unwrap(),
         )
@@ -753,6 +764,7 @@ impl CkBtcSetup {
                         Encode!(&GetBtcAddressArgs {
                             owner: Some(account.owner),
                             subaccount: account.subaccount,
+                            address_type: None,
                         })
                         .unwrap(),
                     )
@@ -844,6 +856,7 @@ impl CkBtcSetup {
                         Encode!(&UpdateBalanceArgs {
                             owner: Some(account.owner),
                             subaccount: account.subaccount,
+                            address_type: None,
                         })
                         .unwrap()
                     )
@@ -2015,6 +2028,8 @@ fn test_retrieve_btc_with_approval_fail() {
                 minter_id: None,
                 maintainers: None,
                 mode: Some(KytMode::RejectAll),
+                quorum: None,
+                blocked_addresses: None,
             }))
             .unwrap(),
         )
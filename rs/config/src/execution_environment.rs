@@ -65,6 +65,11 @@ pub(crate) const SUBNET_HEAP_DELTA_CAPACITY: NumBytes = NumBytes::new(140 * GIB)
 const MAX_INSTRUCTIONS_FOR_MESSAGE_ACCEPTANCE_CALLS: NumInstructions =
     NumInstructions::new(200_000_000);
 
+/// The maximum number of instructions for dry runs of update calls. This is
+/// deliberately a fraction of the limit for replicated update calls, since dry
+/// runs are free for the caller and compete with queries for the same threads.
+const MAX_INSTRUCTIONS_PER_DRY_RUN: NumInstructions = NumInstructions::new(1_000_000_000);
+
 /// The maximum depth of call graphs allowed for composite query calls
 pub(crate) const MAX_QUERY_CALL_DEPTH: usize = 6;
 /// Equivalent to MAX_INSTRUCTIONS_PER_MESSAGE_WITHOUT_DTS for now
@@ -145,6 +150,10 @@ pub struct Config {
     /// check message acceptance can run for.
     pub max_instructions_for_message_acceptance_calls: NumInstructions,
 
+    /// The maximum number of instructions that a dry run of an update call
+    /// can run for. A dry run that exceeds it is reported as failed.
+    pub max_instructions_per_dry_run: NumInstructions,
+
     /// This specifies the threshold in bytes at which the subnet memory usage is
     /// considered to be high. If this value is greater or equal to the subnet
     /// capacity, then the subnet is never considered to have high usage.
@@ -283,6 +292,7 @@ impl Default for Config {
             create_funds_whitelist: String::default(),
             max_instructions_for_message_acceptance_calls:
                 MAX_INSTRUCTIONS_FOR_MESSAGE_ACCEPTANCE_CALLS,
+            max_instructions_per_dry_run: MAX_INSTRUCTIONS_PER_DRY_RUN,
             subnet_memory_threshold: SUBNET_MEMORY_THRESHOLD,
             subnet_memory_capacity: SUBNET_MEMORY_CAPACITY,
             subnet_message_memory_capacity: SUBNET_MESSAGE_MEMORY_CAPACITY,
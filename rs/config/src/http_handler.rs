@@ -62,8 +62,36 @@ pub struct Config {
     /// Serving at most `max_call_concurrent_requests` requests concurrently for endpoint `/api/v2/query`.
     pub max_query_concurrent_requests: usize,
 
+    /// Serving at most `max_dry_run_concurrent_requests` requests concurrently for endpoint `/api/v2/canister/.../dry_run`.
+    /// Dry runs compete with queries for the query execution threads, so this is kept below the
+    /// number of query execution threads.
+    pub max_dry_run_concurrent_requests: usize,
+
+    /// Each sender can make at most `max_dry_run_requests_per_sender` requests to endpoint
+    /// `/api/v2/canister/.../dry_run` within each window of `dry_run_rate_limit_window_seconds`.
+    /// Further requests are rejected with
+    /// [`429 Too Many Requests`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/429).
+    pub max_dry_run_requests_per_sender: u64,
+
+    /// See `max_dry_run_requests_per_sender`.
+    pub dry_run_rate_limit_window_seconds: u64,
+
     /// Serving at most `max_pprof_concurrent_requests` requessts concurrently for all endpoints under `/_/pprof`.
     pub max_pprof_concurrent_requests: usize,
+
+    /// How long a request to `/api/v3/canister/.../call` waits for the submitted ingress message
+    /// to reach a terminal status in the certified state. If it has not by then,
+    /// [`202 Accepted`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/202) is returned
+    /// and the client has to poll `read_state`, as with `/api/v2/canister/.../call`.
+    pub ingress_message_certificate_timeout_seconds: u64,
+
+    /// Serving at most `max_sync_call_concurrent_requests` requests concurrently for endpoint
+    /// `/api/v3/canister/.../call`. Unlike a request to `/api/v2/call`, each request holds its
+    /// slot until its ingress message is certified, i.e. for up to
+    /// `ingress_message_certificate_timeout_seconds`. The endpoint therefore serves at least
+    /// `max_sync_call_concurrent_requests / ingress_message_certificate_timeout_seconds` requests
+    /// per second, and this limit should be raised along with the timeout to keep that rate.
+    pub max_sync_call_concurrent_requests: usize,
 }
 
 impl Default for Config {
@@ -86,7 +114,12 @@ impl Default for Config {
             max_status_concurrent_requests: 100,
             max_call_concurrent_requests: 50,
             max_query_concurrent_requests: QUERY_EXECUTION_THREADS_TOTAL * 100,
+            max_dry_run_concurrent_requests: QUERY_EXECUTION_THREADS_TOTAL / 2,
+            max_dry_run_requests_per_sender: 10,
+            dry_run_rate_limit_window_seconds: 10,
             max_pprof_concurrent_requests: 5,
+            ingress_message_certificate_timeout_seconds: 10,
+            max_sync_call_concurrent_requests: 1_000,
         }
     }
 }
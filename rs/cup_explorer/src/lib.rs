@@ -1,7 +1,23 @@
 use ic_canister_client::{Agent, Sender};
+use ic_consensus::dkg::make_registry_cup_from_cup_contents;
+use ic_crypto_utils_threshold_sig::verify_combined;
+use ic_interfaces_registry::RegistryClient;
+use ic_logger::replica_logger::no_op_logger;
+use ic_protobuf::types::v1 as pb;
 use ic_protobuf::types::v1::CatchUpContent;
+use ic_registry_client_helpers::{crypto::CryptoRegistry, subnet::SubnetRegistry};
+use ic_types::{
+    consensus::{
+        catchup::{CatchUpContentProtobufBytes, CatchUpPackage},
+        HasHeight,
+    },
+    crypto::{crypto_hash, CombinedThresholdSig, CombinedThresholdSigOf},
+    Height, NodeId, RegistryVersion, SubnetId,
+};
 use prost::Message;
 use reqwest::Url;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
 /// Fetches the contents of a CatchUp package, if it's present.
 pub async fn get_catchup_content(url: &Url) -> Result<Option<CatchUpContent>, String> {
@@ -20,3 +36,293 @@ pub async fn get_catchup_content(url: &Url) -> Result<Option<CatchUpContent>, St
         None => Ok(None),
     }
 }
+
+/// How the authenticity of a CUP was established.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CupVerification {
+    /// The threshold signature is valid under the subnet's public key at the
+    /// registry version referenced by the CUP's block.
+    Signed,
+    /// The CUP is unsigned, i.e. it is a genesis or recovery CUP, and it
+    /// matches the CUP contents in the registry at the given version.
+    Registry(RegistryVersion),
+}
+
+/// Verifies the given CUP of `subnet_id` against the registry.
+///
+/// Signed CUPs are checked against the subnet's threshold signing public key,
+/// at the registry version of the validation context of the CUP's block. This
+/// is the same check the orchestrator does for CUPs fetched from peers.
+/// Unsigned CUPs are checked to be identical to the CUP the orchestrator makes
+/// from the CUP contents in the registry at the CUP's registry version.
+pub fn verify_cup(
+    registry: &dyn RegistryClient,
+    subnet_id: SubnetId,
+    proto: &pb::CatchUpPackage,
+) -> Result<CupVerification, String> {
+    let cup =
+        CatchUpPackage::try_from(proto).map_err(|e| format!("failed to deserialize cup: {}", e))?;
+
+    if !cup.is_signed() {
+        let registry_version = cup.content.registry_version();
+        let record = registry
+            .get_cup_contents(subnet_id, registry_version)
+            .map_err(|e| {
+                format!(
+                    "failed to get the cup contents at registry version {}: {}",
+                    registry_version, e
+                )
+            })?;
+        let contents = record.value.ok_or_else(|| {
+            format!(
+                "no cup contents for subnet {} at registry version {}",
+                subnet_id, registry_version
+            )
+        })?;
+        if contents.initial_ni_dkg_transcript_low_threshold.is_none()
+            || contents.initial_ni_dkg_transcript_high_threshold.is_none()
+        {
+            return Err(format!(
+                "no initial DKG transcripts in the cup contents at registry version {}",
+                record.version
+            ));
+        }
+        let registry_height = contents.height;
+        let registry_cup = make_registry_cup_from_cup_contents(
+            registry,
+            subnet_id,
+            contents,
+            record.version,
+            &no_op_logger(),
+        )
+        .ok_or_else(|| {
+            format!(
+                "failed to make the registry cup at registry version {}",
+                record.version
+            )
+        })?;
+        if crypto_hash(&registry_cup.content) != crypto_hash(&cup.content) {
+            return Err(format!(
+                "unsigned cup at height {} does not match the cup contents in the registry at version {} (height {})",
+                cup.height(),
+                record.version,
+                registry_height
+            ));
+        }
+        return Ok(CupVerification::Registry(record.version));
+    }
+
+    let registry_version = cup.content.block.get_value().context.registry_version;
+    let public_key = registry
+        .get_threshold_signing_public_key_for_subnet(subnet_id, registry_version)
+        .map_err(|e| {
+            format!(
+                "failed to get the public key at registry version {}: {}",
+                registry_version, e
+            )
+        })?
+        .ok_or_else(|| {
+            format!(
+                "no public key for subnet {} at registry version {}",
+                subnet_id, registry_version
+            )
+        })?;
+    verify_combined(
+        &CatchUpContentProtobufBytes::from(proto),
+        &CombinedThresholdSigOf::new(CombinedThresholdSig(proto.signature.clone())),
+        &public_key,
+    )
+    .map_err(|e| format!("invalid cup signature: {}", e))?;
+    Ok(CupVerification::Signed)
+}
+
+/// Where a CUP in the chain of a subnet was found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CupOrigin {
+    /// CUP contents stored in the registry, i.e. a genesis or recovery CUP.
+    Registry,
+    /// A CUP saved to a file, e.g. by the orchestrator.
+    File(PathBuf),
+}
+
+impl fmt::Display for CupOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CupOrigin::Registry => write!(f, "registry"),
+            CupOrigin::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// A single CUP in the chain of a subnet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainLink {
+    pub height: Height,
+    pub state_hash: Vec<u8>,
+    pub registry_version: RegistryVersion,
+    pub origin: CupOrigin,
+    /// Whether the CUP was created by a subnet recovery, rather than at
+    /// subnet creation or by the subnet itself.
+    pub recovery: bool,
+    /// The nodes on the subnet at `registry_version`, sorted.
+    pub subnet_members: Vec<NodeId>,
+    /// The result of verifying the CUP. Always `Ok` for registry CUPs.
+    pub verification: Result<CupVerification, String>,
+}
+
+/// Returns the sorted nodes on the subnet at the given registry version, or no
+/// nodes if the registry doesn't know the subnet at that version.
+fn subnet_members(
+    registry: &dyn RegistryClient,
+    subnet_id: SubnetId,
+    version: RegistryVersion,
+) -> Vec<NodeId> {
+    let mut nodes = registry
+        .get_node_ids_on_subnet(subnet_id, version)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    nodes.sort();
+    nodes
+}
+
+/// Returns all CUP contents that were ever stored in the registry for the
+/// given subnet, as links of its chain, ordered by registry version.
+///
+/// The oldest CUP contents are the ones the subnet was created with; every
+/// later one was put there by a subnet recovery.
+pub fn registry_cups(
+    registry: &dyn RegistryClient,
+    subnet_id: SubnetId,
+) -> Result<Vec<ChainLink>, String> {
+    let mut links = Vec::new();
+    let mut version = registry.get_latest_version();
+    while version > RegistryVersion::from(0) {
+        let record = registry.get_cup_contents(subnet_id, version).map_err(|e| {
+            format!(
+                "failed to get the cup contents at registry version {}: {}",
+                version, e
+            )
+        })?;
+        let Some(contents) = record.value else {
+            break;
+        };
+        links.push(ChainLink {
+            height: Height::from(contents.height),
+            state_hash: contents.state_hash,
+            registry_version: record.version,
+            origin: CupOrigin::Registry,
+            recovery: true,
+            subnet_members: subnet_members(registry, subnet_id, record.version),
+            verification: Ok(CupVerification::Registry(record.version)),
+        });
+        version = record.version.decrement();
+    }
+    links.reverse();
+    if let Some(genesis) = links.first_mut() {
+        genesis.recovery = false;
+    }
+    Ok(links)
+}
+
+/// Reads and verifies all CUPs saved in `dir`, and merges them with the CUPs
+/// in the registry into the chain of the given subnet, ordered by height.
+///
+/// Saved CUPs that are copies of registry CUPs are not listed twice. Files
+/// that can't be decoded as CUPs are returned as errors.
+pub fn cup_chain(
+    registry: &dyn RegistryClient,
+    subnet_id: SubnetId,
+    dir: &Path,
+) -> Result<(Vec<ChainLink>, Vec<(PathBuf, String)>), String> {
+    let mut links = registry_cups(registry, subnet_id)?;
+    let mut errors = Vec::new();
+
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read directory {}: {}", dir.display(), e))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("failed to read directory {}: {}", dir.display(), e))?;
+    paths.sort();
+
+    for path in paths.into_iter().filter(|p| p.is_file()) {
+        let proto = match pb::CatchUpPackage::read_from_file(&path) {
+            Ok(proto) => proto,
+            Err(err) => {
+                errors.push((path, err));
+                continue;
+            }
+        };
+        let cup = match CatchUpPackage::try_from(&proto) {
+            Ok(cup) => cup,
+            Err(err) => {
+                errors.push((path, format!("failed to deserialize cup: {}", err)));
+                continue;
+            }
+        };
+        let verification = verify_cup(registry, subnet_id, &proto);
+        if let Ok(CupVerification::Registry(_)) = verification {
+            // A copy of a registry CUP we already have.
+            continue;
+        }
+        let registry_version = cup.content.registry_version();
+        links.push(ChainLink {
+            height: cup.height(),
+            state_hash: cup.content.state_hash.get_ref().0.clone(),
+            registry_version,
+            origin: CupOrigin::File(path),
+            recovery: !cup.is_signed() && cup.height() > Height::from(0),
+            subnet_members: subnet_members(registry, subnet_id, registry_version),
+            verification,
+        });
+    }
+
+    links.sort_by_key(|link| (link.height, link.registry_version));
+    Ok((links, errors))
+}
+
+/// Explains how the chain got from `prev` to `link`.
+pub fn explain_jump(prev: Option<&ChainLink>, link: &ChainLink) -> String {
+    let Some(prev) = prev else {
+        return if link.recovery {
+            "first known cup, created by a recovery".to_string()
+        } else {
+            "first known cup".to_string()
+        };
+    };
+    let heights = link.height.get() as i128 - prev.height.get() as i128;
+    let state = if link.state_hash == prev.state_hash {
+        "same state"
+    } else {
+        "new state"
+    };
+    let added = link
+        .subnet_members
+        .iter()
+        .filter(|node| !prev.subnet_members.contains(node))
+        .count();
+    let removed = prev
+        .subnet_members
+        .iter()
+        .filter(|node| !link.subnet_members.contains(node))
+        .count();
+    let membership = if added == 0 && removed == 0 {
+        "same nodes".to_string()
+    } else {
+        format!("membership changed: {} added, {} removed", added, removed)
+    };
+    if link.recovery {
+        format!(
+            "recovery at registry version {}: {:+} heights, {}, {}",
+            link.registry_version, heights, state, membership
+        )
+    } else {
+        format!(
+            "subnet progressed {:+} heights, {}, {}",
+            heights, state, membership
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests;
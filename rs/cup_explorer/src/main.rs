@@ -1,12 +1,16 @@
-use ic_cup_explorer::get_catchup_content;
+use ic_cup_explorer::{cup_chain, explain_jump, get_catchup_content, verify_cup, CupVerification};
 use ic_protobuf::registry::{node::v1::NodeRecord, subnet::v1::SubnetRecord};
+use ic_protobuf::types::v1 as pb;
+use ic_registry_client::client::RegistryClientImpl;
 use ic_registry_keys::{make_node_record_key, make_subnet_record_key};
+use ic_registry_local_store::LocalStoreImpl;
 use ic_registry_nns_data_provider::registry::RegistryCanister;
 use ic_types::{NodeId, PrincipalId, SubnetId};
 use prost::Message;
 use reqwest::Url;
 use std::convert::TryFrom;
 use std::net::SocketAddr;
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::task;
@@ -66,21 +70,113 @@ fn http_url(n: &NodeRecord) -> Url {
     .unwrap()
 }
 
+fn parse_subnet_id(arg: &str) -> SubnetId {
+    SubnetId::from(
+        PrincipalId::from_str(arg)
+            .unwrap_or_else(|e| panic!("failed to parse subnet id {}: {}", arg, e)),
+    )
+}
+
+/// Returns a registry client that reads from the given local store, without
+/// contacting the NNS.
+fn local_registry(local_store_path: &str) -> RegistryClientImpl {
+    let data_provider = Arc::new(LocalStoreImpl::new(Path::new(local_store_path)));
+    let registry = RegistryClientImpl::new(data_provider, None);
+    registry
+        .poll_once()
+        .unwrap_or_else(|e| panic!("failed to read local store {}: {}", local_store_path, e));
+    registry
+}
+
+/// Verifies a saved CUP against the registry in the local store.
+fn verify(local_store_path: &str, subnet_id: SubnetId, cup_path: &str) {
+    let registry = local_registry(local_store_path);
+    let proto = pb::CatchUpPackage::read_from_file(cup_path)
+        .unwrap_or_else(|e| panic!("failed to read cup {}: {}", cup_path, e));
+    match verify_cup(&registry, subnet_id, &proto) {
+        Ok(CupVerification::Signed) => {
+            println!(
+                " ✔ {}: valid threshold signature of subnet {}",
+                cup_path, subnet_id
+            )
+        }
+        Ok(CupVerification::Registry(version)) => println!(
+            " ✔ {}: unsigned, matches the registry cup at version {}",
+            cup_path, version
+        ),
+        Err(err) => {
+            println!(" ✘ {}: {}", cup_path, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the chain of CUPs of the subnet, from the registry in the local
+/// store and the CUPs saved in `cup_dir`, explaining each jump.
+fn explain(local_store_path: &str, subnet_id: SubnetId, cup_dir: &str) {
+    let registry = local_registry(local_store_path);
+    let (links, errors) = cup_chain(&registry, subnet_id, Path::new(cup_dir))
+        .unwrap_or_else(|e| panic!("failed to build the cup chain: {}", e));
+
+    for (path, err) in errors {
+        println!(" ? {}: {}", path.display(), err);
+    }
+    println!("Found {} cup(s) of subnet {}", links.len(), subnet_id);
+    let mut prev = None;
+    for link in &links {
+        let mark = if link.verification.is_ok() {
+            "✔"
+        } else {
+            "✘"
+        };
+        println!(
+            " {} height = {}, state_hash: {}, registry_version: {}, recovery: {} [{}]",
+            mark,
+            link.height,
+            hex::encode(&link.state_hash),
+            link.registry_version,
+            link.recovery,
+            link.origin
+        );
+        println!("     {}", explain_jump(prev, link));
+        if let Err(err) = &link.verification {
+            println!("     {}", err);
+        }
+        prev = Some(link);
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let args: Vec<_> = std::env::args().collect();
-    if args.len() != 3 {
-        eprintln!("Usage: {} [REGISTRY_URL] [SUBNET_ID]", args[0]);
-        std::process::exit(1);
+    match args.get(1).map(String::as_str) {
+        Some("verify") if args.len() == 5 => {
+            verify(&args[2], parse_subnet_id(&args[3]), &args[4]);
+            return;
+        }
+        Some("explain") if args.len() == 5 => {
+            explain(&args[2], parse_subnet_id(&args[3]), &args[4]);
+            return;
+        }
+        _ if args.len() == 3 => {}
+        _ => {
+            eprintln!("Usage: {} [REGISTRY_URL] [SUBNET_ID]", args[0]);
+            eprintln!(
+                "       {} verify [LOCAL_STORE] [SUBNET_ID] [CUP_FILE]",
+                args[0]
+            );
+            eprintln!(
+                "       {} explain [LOCAL_STORE] [SUBNET_ID] [CUP_DIR]",
+                args[0]
+            );
+            std::process::exit(1);
+        }
     }
 
     let registry_url = Url::parse(&args[1][..])
         .unwrap_or_else(|e| panic!("failed to parse registry url {}: {}", args[1], e));
 
-    let subnet_id = SubnetId::from(
-        PrincipalId::from_str(&args[2][..])
-            .unwrap_or_else(|e| panic!("failed to parse subnet id {}: {}", args[2], e)),
-    );
+    let subnet_id = parse_subnet_id(&args[2]);
 
     let registry_canister = Arc::new(RegistryCanister::new(vec![registry_url]));
 
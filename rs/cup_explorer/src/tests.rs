@@ -0,0 +1,308 @@
+use super::*;
+use ic_certification_test_utils::generate_root_of_trust;
+use ic_crypto_internal_threshold_sig_bls12381::{
+    api::{combine_signatures, sign_message},
+    types::SecretKeyBytes,
+};
+use ic_crypto_test_utils_ni_dkg::dummy_transcript_for_tests_with_params;
+use ic_protobuf::registry::{
+    crypto::v1::PublicKey as PublicKeyProto,
+    subnet::v1::{CatchUpPackageContents, InitialNiDkgTranscriptRecord},
+};
+use ic_registry_client_fake::FakeRegistryClient;
+use ic_registry_keys::{
+    make_catch_up_package_contents_key, make_crypto_threshold_signing_pubkey_key,
+    make_subnet_record_key,
+};
+use ic_registry_proto_data_provider::ProtoRegistryDataProvider;
+use ic_test_utilities::{
+    consensus::{fake::Fake, make_genesis},
+    types::ids::{node_test_id, subnet_test_id},
+};
+use ic_test_utilities_registry::SubnetRecordBuilder;
+use ic_types::{
+    consensus::dkg::Summary,
+    crypto::{threshold_sig::ni_dkg::NiDkgTag, CryptoHash, CryptoHashOf, Signable},
+    NumberOfNodes,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::sync::Arc;
+
+const RECOVERY_HEIGHT: u64 = 500;
+
+fn subnet_id() -> SubnetId {
+    subnet_test_id(1)
+}
+
+/// Returns CUP contents with initial DKG transcripts for the given nodes.
+fn cup_contents(
+    height: u64,
+    state_hash: Vec<u8>,
+    nodes: &[NodeId],
+    registry_version: u64,
+) -> CatchUpPackageContents {
+    let transcript = |tag: NiDkgTag| {
+        Some(InitialNiDkgTranscriptRecord::from(
+            dummy_transcript_for_tests_with_params(
+                nodes.to_vec(),
+                tag,
+                tag.threshold_for_subnet_of_size(nodes.len()) as u32,
+                registry_version,
+            ),
+        ))
+    };
+    CatchUpPackageContents {
+        height,
+        state_hash,
+        initial_ni_dkg_transcript_low_threshold: transcript(NiDkgTag::LowThreshold),
+        initial_ni_dkg_transcript_high_threshold: transcript(NiDkgTag::HighThreshold),
+        ..Default::default()
+    }
+}
+
+/// The CUP contents stored in the registry by the recovery.
+fn recovery_contents() -> CatchUpPackageContents {
+    cup_contents(
+        RECOVERY_HEIGHT,
+        vec![5; 32],
+        &[node_test_id(1), node_test_id(3)],
+        3,
+    )
+}
+
+/// Returns a registry in which the subnet is created at version 1 with nodes 1
+/// and 2, and recovered at version 3 at `RECOVERY_HEIGHT`, with node 2
+/// replaced by node 3. Also returns the secret key of the subnet.
+fn setup_registry() -> (Arc<FakeRegistryClient>, SecretKeyBytes) {
+    let data_provider = Arc::new(ProtoRegistryDataProvider::new());
+    let (public_key, secret_key) = generate_root_of_trust(&mut StdRng::seed_from_u64(42));
+    let genesis_nodes = [node_test_id(1), node_test_id(2)];
+    let memberships = [
+        (1, genesis_nodes, cup_contents(0, vec![], &genesis_nodes, 1)),
+        (3, [node_test_id(1), node_test_id(3)], recovery_contents()),
+    ];
+    for (version, nodes, contents) in memberships {
+        let version = RegistryVersion::from(version);
+        data_provider
+            .add(
+                &make_subnet_record_key(subnet_id()),
+                version,
+                Some(SubnetRecordBuilder::from(&nodes).build()),
+            )
+            .unwrap();
+        data_provider
+            .add(
+                &make_catch_up_package_contents_key(subnet_id()),
+                version,
+                Some(contents),
+            )
+            .unwrap();
+    }
+    data_provider
+        .add(
+            &make_crypto_threshold_signing_pubkey_key(subnet_id()),
+            RegistryVersion::from(1),
+            Some(PublicKeyProto::from(public_key)),
+        )
+        .unwrap();
+    let registry = Arc::new(FakeRegistryClient::new(data_provider));
+    registry.update_to_latest_version();
+    (registry, secret_key)
+}
+
+fn make_cup(height: u64, registry_version: u64, state_hash: Vec<u8>) -> CatchUpPackage {
+    let mut summary = Summary::fake();
+    summary.height = Height::from(height);
+    summary.registry_version = RegistryVersion::from(registry_version);
+    let mut cup = make_genesis(summary);
+    cup.content.state_hash = CryptoHashOf::from(CryptoHash(state_hash));
+    cup
+}
+
+/// Returns the unsigned CUP the orchestrator makes from the given CUP
+/// contents.
+fn registry_cup(
+    registry: &dyn RegistryClient,
+    contents: CatchUpPackageContents,
+    registry_version: u64,
+) -> CatchUpPackage {
+    make_registry_cup_from_cup_contents(
+        registry,
+        subnet_id(),
+        contents,
+        RegistryVersion::from(registry_version),
+        &no_op_logger(),
+    )
+    .unwrap()
+}
+
+fn sign_cup(cup: &CatchUpPackage, secret_key: &SecretKeyBytes) -> pb::CatchUpPackage {
+    let mut proto = pb::CatchUpPackage::from(cup);
+    let signature = sign_message(
+        &CatchUpContentProtobufBytes::from(&proto).as_signed_bytes(),
+        secret_key,
+    )
+    .unwrap();
+    proto.signature = combine_signatures(&[Some(signature)], NumberOfNodes::new(1))
+        .unwrap()
+        .0
+        .to_vec();
+    proto
+}
+
+#[test]
+fn verify_cup_accepts_cup_signed_with_subnet_key() {
+    let (registry, secret_key) = setup_registry();
+    let proto = sign_cup(&make_cup(100, 1, vec![1; 32]), &secret_key);
+
+    assert_eq!(
+        verify_cup(&*registry, subnet_id(), &proto),
+        Ok(CupVerification::Signed)
+    );
+}
+
+#[test]
+fn verify_cup_rejects_cup_signed_with_another_key() {
+    let (registry, _) = setup_registry();
+    let (_, other_secret_key) = generate_root_of_trust(&mut StdRng::seed_from_u64(7));
+    let proto = sign_cup(&make_cup(100, 1, vec![1; 32]), &other_secret_key);
+
+    let err = verify_cup(&*registry, subnet_id(), &proto).unwrap_err();
+    assert!(err.contains("invalid cup signature"), "{}", err);
+}
+
+#[test]
+fn verify_cup_checks_unsigned_cup_against_registry() {
+    let (registry, _) = setup_registry();
+    let recovery_cup = registry_cup(&*registry, recovery_contents(), 3);
+    let forged_cup = registry_cup(
+        &*registry,
+        CatchUpPackageContents {
+            state_hash: vec![6; 32],
+            ..recovery_contents()
+        },
+        3,
+    );
+
+    assert_eq!(
+        verify_cup(
+            &*registry,
+            subnet_id(),
+            &pb::CatchUpPackage::from(&recovery_cup)
+        ),
+        Ok(CupVerification::Registry(RegistryVersion::from(3)))
+    );
+    let err = verify_cup(
+        &*registry,
+        subnet_id(),
+        &pb::CatchUpPackage::from(&forged_cup),
+    )
+    .unwrap_err();
+    assert!(err.contains("does not match the cup contents"), "{}", err);
+}
+
+#[test]
+fn verify_cup_rejects_unsigned_cup_with_other_transcripts() {
+    let (registry, _) = setup_registry();
+    // Same height and state hash as the recovery CUP, but the initial DKG
+    // transcripts are for the nodes before the recovery.
+    let forged_cup = registry_cup(
+        &*registry,
+        cup_contents(
+            RECOVERY_HEIGHT,
+            vec![5; 32],
+            &[node_test_id(1), node_test_id(2)],
+            3,
+        ),
+        3,
+    );
+
+    let err = verify_cup(
+        &*registry,
+        subnet_id(),
+        &pb::CatchUpPackage::from(&forged_cup),
+    )
+    .unwrap_err();
+    assert!(err.contains("does not match the cup contents"), "{}", err);
+}
+
+#[test]
+fn registry_cups_lists_creation_and_recovery() {
+    let (registry, _) = setup_registry();
+
+    let links = registry_cups(&*registry, subnet_id()).unwrap();
+
+    assert_eq!(
+        links
+            .iter()
+            .map(|link| (
+                link.height.get(),
+                link.registry_version.get(),
+                link.recovery
+            ))
+            .collect::<Vec<_>>(),
+        vec![(0, 1, false), (RECOVERY_HEIGHT, 3, true)]
+    );
+    let sorted = |mut nodes: Vec<NodeId>| {
+        nodes.sort();
+        nodes
+    };
+    assert_eq!(
+        links[0].subnet_members,
+        sorted(vec![node_test_id(1), node_test_id(2)])
+    );
+    assert_eq!(
+        links[1].subnet_members,
+        sorted(vec![node_test_id(1), node_test_id(3)])
+    );
+}
+
+#[test]
+fn explain_jump_reports_recovery_with_membership_change() {
+    let (registry, _) = setup_registry();
+    let links = registry_cups(&*registry, subnet_id()).unwrap();
+
+    assert_eq!(explain_jump(None, &links[0]), "first known cup");
+    assert_eq!(
+        explain_jump(Some(&links[0]), &links[1]),
+        "recovery at registry version 3: +500 heights, new state, \
+         membership changed: 1 added, 1 removed"
+    );
+}
+
+#[test]
+fn cup_chain_merges_saved_cups_with_registry_cups() {
+    let (registry, secret_key) = setup_registry();
+    let dir = tempfile::tempdir().unwrap();
+    let signed = sign_cup(&make_cup(100, 1, vec![1; 32]), &secret_key);
+    let recovery = pb::CatchUpPackage::from(&registry_cup(&*registry, recovery_contents(), 3));
+    std::fs::write(dir.path().join("a.pb"), signed.encode_to_vec()).unwrap();
+    std::fs::write(dir.path().join("b.pb"), recovery.encode_to_vec()).unwrap();
+    std::fs::write(dir.path().join("c.pb"), b"not a cup").unwrap();
+
+    let (links, errors) = cup_chain(&*registry, subnet_id(), dir.path()).unwrap();
+
+    assert_eq!(
+        links
+            .iter()
+            .map(|link| (link.height.get(), link.origin.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (0, CupOrigin::Registry),
+            (100, CupOrigin::File(dir.path().join("a.pb"))),
+            (RECOVERY_HEIGHT, CupOrigin::Registry),
+        ]
+    );
+    assert_eq!(links[1].verification, Ok(CupVerification::Signed));
+    assert_eq!(
+        explain_jump(Some(&links[0]), &links[1]),
+        "subnet progressed +100 heights, new state, same nodes"
+    );
+    assert_eq!(
+        errors
+            .iter()
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>(),
+        vec![dir.path().join("c.pb")]
+    );
+}
@@ -9,7 +9,10 @@ use ic_cketh_minter::eth_rpc::Hash;
 use ic_cketh_minter::eth_rpc_client::responses::TransactionStatus;
 use ic_cketh_minter::lifecycle::EthereumNetwork;
 use ic_cketh_minter::numeric::{BlockNumber, LedgerBurnIndex, TransactionNonce, Wei};
-use ic_cketh_minter::state::transactions::{EthWithdrawalRequest, Reimbursed};
+use ic_cketh_minter::state::transactions::{
+    Erc20Reimbursed, Erc20ReimbursementRequest, EthWithdrawalRequest, Reimbursed,
+    ReimbursementRequest,
+};
 use ic_cketh_minter::state::{EthBalance, MintedEvent, State};
 use ic_ethereum_types::Address;
 use std::cmp::Reverse;
@@ -66,7 +69,10 @@ pub struct DashboardTemplate {
     pub withdrawal_requests: Vec<EthWithdrawalRequest>,
     pub pending_transactions: Vec<DashboardPendingTransaction>,
     pub finalized_transactions: Vec<DashboardFinalizedTransaction>,
+    pub pending_reimbursements: Vec<ReimbursementRequest>,
     pub reimbursed_transactions: Vec<Reimbursed>,
+    pub pending_erc20_reimbursements: Vec<Erc20ReimbursementRequest>,
+    pub reimbursed_erc20_transactions: Vec<Erc20Reimbursed>,
     pub eth_balance: EthBalance,
     pub skipped_blocks: BTreeSet<BlockNumber>,
 }
@@ -129,6 +135,18 @@ impl DashboardTemplate {
         reimbursed_transactions
             .sort_unstable_by_key(|reimbursed_tx| std::cmp::Reverse(reimbursed_tx.withdrawal_id));
 
+        let mut pending_reimbursements = state.eth_transactions.get_reimbursement_requests();
+        pending_reimbursements.sort_unstable_by_key(|request| Reverse(request.withdrawal_id));
+
+        let mut pending_erc20_reimbursements =
+            state.eth_transactions.get_erc20_reimbursement_requests();
+        pending_erc20_reimbursements.sort_unstable_by_key(|request| Reverse(request.withdrawal_id));
+
+        let mut reimbursed_erc20_transactions =
+            state.eth_transactions.get_erc20_reimbursed_transactions();
+        reimbursed_erc20_transactions
+            .sort_unstable_by_key(|reimbursed_tx| Reverse(reimbursed_tx.withdrawal_id));
+
         DashboardTemplate {
             ethereum_network: state.ethereum_network,
             ecdsa_key_name: state.ecdsa_key_name.clone(),
@@ -151,7 +169,10 @@ impl DashboardTemplate {
             withdrawal_requests,
             pending_transactions,
             finalized_transactions,
+            pending_reimbursements,
             reimbursed_transactions,
+            pending_erc20_reimbursements,
+            reimbursed_erc20_transactions,
             eth_balance: state.eth_balance.clone(),
             skipped_blocks: state.skipped_blocks.clone(),
         }
@@ -404,11 +404,15 @@ fn should_display_finalized_transactions_sorted_by_decreasing_ledger_burn_index(
                     transaction: signed_tx,
                 },
             );
+            let reimbursed_amount = state
+                .eth_transactions
+                .reimbursed_amount_if_failed(&id, &receipt);
             apply_state_transition(
                 &mut state,
                 &EventType::FinalizedTransaction {
                     withdrawal_id: id,
                     transaction_receipt: receipt,
+                    reimbursed_amount,
                 },
             );
         }
@@ -419,7 +423,7 @@ fn should_display_finalized_transactions_sorted_by_decreasing_ledger_burn_index(
     DashboardAssert::assert_that(dashboard)
         .has_eth_balance("8_900_000_000_000_000")
         .has_total_effective_tx_fees("42_000_000_000_000")
-        .has_total_unspent_tx_fees("42_000_000_000_000")
+        .has_total_unspent_tx_fees("21_000_000_000_000")
         .has_finalized_transactions(
             1,
             &vec![
@@ -522,11 +526,15 @@ fn should_display_reimbursed_requests() {
                     transaction: signed_tx,
                 },
             );
+            let reimbursed_amount = state
+                .eth_transactions
+                .reimbursed_amount_if_failed(&id, &receipt);
             apply_state_transition(
                 &mut state,
                 &EventType::FinalizedTransaction {
                     withdrawal_id: id,
                     transaction_receipt: receipt.clone(),
+                    reimbursed_amount,
                 },
             );
             if receipt.status == TransactionStatus::Failure {
@@ -587,7 +595,7 @@ fn should_display_reimbursed_requests() {
             &vec![
                 "17",
                 "123",
-                "1_058_000_000_000_000",
+                "1_079_000_000_000_000",
                 "0xada056f5d3942fac34371527524b5ee8a45833eb5edc41a06ac7a742a6a59762",
             ],
         )
@@ -596,12 +604,77 @@ fn should_display_reimbursed_requests() {
             &vec![
                 "16",
                 "123",
-                "1_058_000_000_000_000",
+                "1_079_000_000_000_000",
                 "0x9a4793ece4b3a487679a43dd465d8a4855fa2a23adc128a59eaaa9eb5837105e",
             ],
         );
 }
 
+#[test]
+fn should_display_pending_reimbursements() {
+    DashboardAssert::assert_that(initial_dashboard())
+        .has_no_elements_matching("#pending-reimbursements");
+
+    let dashboard = {
+        let mut state = initial_state();
+        let deposit = received_eth_event();
+        apply_state_transition(&mut state, &EventType::AcceptedDeposit(deposit.clone()));
+        apply_state_transition(
+            &mut state,
+            &EventType::MintedCkEth {
+                event_source: deposit.source(),
+                mint_block_index: LedgerMintIndex::new(42),
+            },
+        );
+
+        let (req, tx, signed_tx, receipt) = withdrawal_flow(
+            LedgerBurnIndex::new(16),
+            TransactionNonce::from(0_u8),
+            TransactionStatus::Failure,
+        );
+        let id = req.ledger_burn_index;
+        apply_state_transition(&mut state, &EventType::AcceptedEthWithdrawalRequest(req));
+        apply_state_transition(
+            &mut state,
+            &EventType::CreatedTransaction {
+                withdrawal_id: id,
+                transaction: tx,
+            },
+        );
+        apply_state_transition(
+            &mut state,
+            &EventType::SignedTransaction {
+                withdrawal_id: id,
+                transaction: signed_tx,
+            },
+        );
+        let reimbursed_amount = state
+            .eth_transactions
+            .reimbursed_amount_if_failed(&id, &receipt);
+        apply_state_transition(
+            &mut state,
+            &EventType::FinalizedTransaction {
+                withdrawal_id: id,
+                transaction_receipt: receipt,
+                reimbursed_amount,
+            },
+        );
+        DashboardTemplate::from_state(&state)
+    };
+
+    DashboardAssert::assert_that(dashboard)
+        .has_no_elements_matching("#reimbursed-transactions")
+        .has_pending_reimbursements(
+            1,
+            &vec![
+                "16",
+                "k2t6j-2nvnp-4zjm3-25dtz-6xhaa-c7boj-5gayf-oj3xs-i43lp-teztq-6ae",
+                "1_079_000_000_000_000",
+                "0xdea6b45f0978fea7f38fe6957db7ee11dd0e351a6f24fe54598d8aec9c8a1527",
+            ],
+        );
+}
+
 fn initial_dashboard() -> DashboardTemplate {
     DashboardTemplate::from_state(&initial_state())
 }
@@ -618,6 +691,7 @@ fn initial_state() -> State {
         minimum_withdrawal_amount: Wei::TWO.into(),
         next_transaction_nonce: TransactionNonce::ZERO.into(),
         last_scraped_block_number: candid::Nat::from(3_956_206_u32),
+        rpc_config: None,
     })
     .expect("valid init args")
 }
@@ -918,6 +992,18 @@ mod assertions {
             )
         }
 
+        pub fn has_pending_reimbursements(
+            &self,
+            row_index: u8,
+            expected_value: &Vec<&str>,
+        ) -> &Self {
+            self.has_table_row_string_value(
+                &format!("#pending-reimbursements + table > tbody > tr:nth-child({row_index})"),
+                expected_value,
+                "pending-reimbursements",
+            )
+        }
+
         pub fn has_reimbursed_transactions(
             &self,
             row_index: u8,
@@ -1,4 +1,6 @@
-use crate::eth_logs::{report_transaction_error, ReceivedEthEventError};
+use crate::eth_logs::{
+    report_transaction_error, ReceivedEthEventError, ReceivedEvent, ReceivedEventKind,
+};
 use crate::eth_rpc::{BlockSpec, HttpOutcallError};
 use crate::eth_rpc_client::EthRpcClient;
 use crate::guard::TimerGuard;
@@ -13,11 +15,11 @@ use num_traits::ToPrimitive;
 use std::cmp::{min, Ordering};
 use std::time::Duration;
 
-async fn mint_cketh() {
+async fn mint() {
     use icrc_ledger_client_cdk::{CdkRuntime, ICRC1Client};
     use icrc_ledger_types::icrc1::transfer::TransferArg;
 
-    let _guard = match TimerGuard::new(TaskType::MintCkEth) {
+    let _guard = match TimerGuard::new(TaskType::Mint) {
         Ok(guard) => guard,
         Err(_) => return,
     };
@@ -74,12 +76,82 @@ async fn mint_cketh() {
         );
     }
 
+    let erc20_events = read_state(|s| {
+        s.erc20_events_to_mint
+            .iter()
+            .map(|(source, event)| {
+                let token = s
+                    .ckerc20_tokens
+                    .get(&event.erc20_contract_address)
+                    .expect("BUG: accepted ERC-20 deposit for an unsupported token")
+                    .clone();
+                (*source, event.clone(), token)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for (event_source, event, token) in erc20_events {
+        let client = ICRC1Client {
+            runtime: CdkRuntime,
+            ledger_canister_id: token.ckerc20_ledger_id,
+        };
+        let block_index = match client
+            .transfer(TransferArg {
+                from_subaccount: None,
+                to: event.principal.into(),
+                fee: None,
+                created_at_time: None,
+                memo: Some(event.clone().into()),
+                amount: candid::Nat::from(event.value),
+            })
+            .await
+        {
+            Ok(Ok(block_index)) => block_index.0.to_u64().expect("nat does not fit into u64"),
+            Ok(Err(err)) => {
+                log!(
+                    INFO,
+                    "Failed to mint {}: {event:?} {err}",
+                    token.ckerc20_token_symbol
+                );
+                error_count += 1;
+                continue;
+            }
+            Err(err) => {
+                log!(
+                    INFO,
+                    "Failed to send a message to the ledger ({}): {err:?}",
+                    token.ckerc20_ledger_id
+                );
+                error_count += 1;
+                continue;
+            }
+        };
+        mutate_state(|s| {
+            process_event(
+                s,
+                EventType::MintedCkErc20 {
+                    event_source,
+                    mint_block_index: LedgerMintIndex::new(block_index),
+                    ckerc20_token_symbol: token.ckerc20_token_symbol.clone(),
+                    erc20_contract_address: token.erc20_contract_address,
+                },
+            )
+        });
+        log!(
+            INFO,
+            "Minted {} {} to {} in block {block_index}",
+            event.value,
+            token.ckerc20_token_symbol,
+            event.principal
+        );
+    }
+
     if error_count > 0 {
         log!(
             INFO,
             "Failed to mint {error_count} events, rescheduling the minting"
         );
-        ic_cdk_timers::set_timer(crate::MINT_RETRY_DELAY, || ic_cdk::spawn(mint_cketh()));
+        ic_cdk_timers::set_timer(crate::MINT_RETRY_DELAY, || ic_cdk::spawn(mint()));
     }
 }
 
@@ -88,6 +160,7 @@ async fn mint_cketh() {
 /// Returns the last block number that was scraped (which is `min(from + MAX_BLOCK_SPREAD, to)`) if there
 /// was no error when querying the providers, otherwise returns `None`.
 async fn scrap_eth_logs_range_inclusive(
+    kind: ReceivedEventKind,
     contract_address: Address,
     from: BlockNumber,
     to: BlockNumber,
@@ -103,13 +176,14 @@ async fn scrap_eth_logs_range_inclusive(
             let mut last_block_number = min(max_to, to);
             log!(
                 DEBUG,
-                "Scrapping ETH logs from block {:?} to block {:?}...",
+                "Scrapping {kind:?} logs from block {:?} to block {:?}...",
                 from,
                 last_block_number
             );
 
             let (transaction_events, errors) = loop {
-                match crate::eth_logs::last_received_eth_events(
+                match crate::eth_logs::last_received_events(
+                    kind,
                     contract_address,
                     from,
                     last_block_number,
@@ -120,15 +194,23 @@ async fn scrap_eth_logs_range_inclusive(
                     Err(e) => {
                         log!(
                         INFO,
-                        "Failed to get ETH logs from block {from} to block {last_block_number}: {e:?}",
+                        "Failed to get {kind:?} logs from block {from} to block {last_block_number}: {e:?}",
                     );
                         if e.has_http_outcall_error_matching(
                             HttpOutcallError::is_response_too_large,
                         ) {
                             if from == last_block_number {
                                 mutate_state(|s| {
-                                    process_event(s, EventType::SkippedBlock(last_block_number));
-                                    s.last_scraped_block_number = last_block_number;
+                                    let skipped_block_event = match kind {
+                                        ReceivedEventKind::Eth => {
+                                            EventType::SkippedBlock(last_block_number)
+                                        }
+                                        ReceivedEventKind::Erc20 => {
+                                            EventType::SkippedErc20Block(last_block_number)
+                                        }
+                                    };
+                                    process_event(s, skipped_block_event);
+                                    set_last_scraped_block_number(s, kind, last_block_number);
                                 });
                                 return Some(last_block_number);
                             } else {
@@ -151,35 +233,56 @@ async fn scrap_eth_logs_range_inclusive(
             for event in transaction_events {
                 log!(
                     INFO,
-                    "Received event {event:?}; will mint {} wei to {}",
-                    event.value,
-                    event.principal
+                    "Received event {event:?}; will mint to {}",
+                    event.principal()
                 );
-                if crate::blocklist::is_blocked(event.from_address) {
+                let from_address = event.from_address();
+                if crate::blocklist::is_blocked(from_address) {
                     log!(
                         INFO,
-                        "Received event from a blocked address: {} for {} WEI",
-                        event.from_address,
-                        event.value,
+                        "Received event from a blocked address: {from_address}: {event:?}",
                     );
                     mutate_state(|s| {
                         process_event(
                             s,
                             EventType::InvalidDeposit {
-                                event_source: crate::eth_logs::EventSource {
-                                    transaction_hash: event.transaction_hash,
-                                    log_index: event.log_index,
-                                },
-                                reason: format!("blocked address {}", event.from_address),
+                                event_source: event.source(),
+                                reason: format!("blocked address {from_address}"),
                             },
                         )
                     });
-                } else {
-                    mutate_state(|s| process_event(s, EventType::AcceptedDeposit(event)));
+                    continue;
+                }
+                match event {
+                    ReceivedEvent::Eth(event) => {
+                        mutate_state(|s| process_event(s, EventType::AcceptedDeposit(event)));
+                    }
+                    ReceivedEvent::Erc20(event) => {
+                        if read_state(|s| {
+                            s.ckerc20_tokens.contains_key(&event.erc20_contract_address)
+                        }) {
+                            mutate_state(|s| {
+                                process_event(s, EventType::AcceptedErc20Deposit(event))
+                            });
+                        } else {
+                            mutate_state(|s| {
+                                process_event(
+                                    s,
+                                    EventType::InvalidDeposit {
+                                        event_source: event.source(),
+                                        reason: format!(
+                                            "unsupported ERC-20 contract address {}",
+                                            event.erc20_contract_address
+                                        ),
+                                    },
+                                )
+                            });
+                        }
+                    }
                 }
             }
             if read_state(State::has_events_to_mint) {
-                ic_cdk_timers::set_timer(Duration::from_secs(0), || ic_cdk::spawn(mint_cketh()));
+                ic_cdk_timers::set_timer(Duration::from_secs(0), || ic_cdk::spawn(mint()));
             }
             for error in errors {
                 if let ReceivedEthEventError::InvalidEventSource { source, error } = &error {
@@ -195,7 +298,7 @@ async fn scrap_eth_logs_range_inclusive(
                 }
                 report_transaction_error(error);
             }
-            mutate_state(|s| s.last_scraped_block_number = last_block_number);
+            mutate_state(|s| set_last_scraped_block_number(s, kind, last_block_number));
             Some(last_block_number)
         }
         Ordering::Greater => {
@@ -207,21 +310,45 @@ async fn scrap_eth_logs_range_inclusive(
     }
 }
 
+fn last_scraped_block_number(state: &State, kind: ReceivedEventKind) -> BlockNumber {
+    match kind {
+        ReceivedEventKind::Eth => state.last_scraped_block_number,
+        ReceivedEventKind::Erc20 => state.last_erc20_scraped_block_number,
+    }
+}
+
+fn set_last_scraped_block_number(
+    state: &mut State,
+    kind: ReceivedEventKind,
+    block_number: BlockNumber,
+) {
+    match kind {
+        ReceivedEventKind::Eth => state.last_scraped_block_number = block_number,
+        ReceivedEventKind::Erc20 => state.last_erc20_scraped_block_number = block_number,
+    }
+}
+
 pub async fn scrap_eth_logs() {
     let _guard = match TimerGuard::new(TaskType::ScrapEthLogs) {
         Ok(guard) => guard,
         Err(_) => return,
     };
-    let contract_address = match read_state(|s| s.ethereum_contract_address) {
-        Some(address) => address,
-        None => {
-            log!(
-                DEBUG,
-                "[scrap_eth_logs]: skipping scrapping ETH logs: no contract address"
-            );
-            return;
-        }
-    };
+    let contracts: Vec<_> = read_state(|s| {
+        [
+            (ReceivedEventKind::Eth, s.ethereum_contract_address),
+            (ReceivedEventKind::Erc20, s.erc20_helper_contract_address),
+        ]
+        .into_iter()
+        .filter_map(|(kind, address)| address.map(|address| (kind, address)))
+        .collect()
+    });
+    if contracts.is_empty() {
+        log!(
+            DEBUG,
+            "[scrap_eth_logs]: skipping scrapping ETH logs: no contract address"
+        );
+        return;
+    }
     let last_block_number = match update_last_observed_block_number().await {
         Some(block_number) => block_number,
         None => {
@@ -232,24 +359,28 @@ pub async fn scrap_eth_logs() {
             return;
         }
     };
-    let mut last_scraped_block_number = read_state(|s| s.last_scraped_block_number);
 
-    while last_scraped_block_number < last_block_number {
-        let next_block_to_query = last_scraped_block_number
-            .checked_increment()
-            .unwrap_or(BlockNumber::MAX);
-        last_scraped_block_number = match scrap_eth_logs_range_inclusive(
-            contract_address,
-            next_block_to_query,
-            last_block_number,
-        )
-        .await
-        {
-            Some(last_scraped_block_number) => last_scraped_block_number,
-            None => {
-                return;
-            }
-        };
+    for (kind, contract_address) in contracts {
+        let mut last_scraped_block_number = read_state(|s| last_scraped_block_number(s, kind));
+
+        while last_scraped_block_number < last_block_number {
+            let next_block_to_query = last_scraped_block_number
+                .checked_increment()
+                .unwrap_or(BlockNumber::MAX);
+            last_scraped_block_number = match scrap_eth_logs_range_inclusive(
+                kind,
+                contract_address,
+                next_block_to_query,
+                last_block_number,
+            )
+            .await
+            {
+                Some(last_scraped_block_number) => last_scraped_block_number,
+                None => {
+                    return;
+                }
+            };
+        }
     }
 }
 
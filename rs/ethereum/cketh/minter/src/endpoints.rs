@@ -1,6 +1,6 @@
 use crate::state::transactions::EthWithdrawalRequest;
 use crate::tx::{SignedEip1559TransactionRequest, TransactionPrice};
-use candid::{CandidType, Deserialize, Nat};
+use candid::{CandidType, Deserialize, Nat, Principal};
 use icrc_ledger_types::icrc2::transfer_from::TransferFromError;
 use minicbor::{Decode, Encode};
 use std::fmt::{Display, Formatter};
@@ -60,6 +60,98 @@ pub enum CandidBlockTag {
     Finalized,
 }
 
+/// JSON-RPC providers queried by the minter, replacing the built-in providers
+/// of the configured Ethereum network.
+#[derive(CandidType, Deserialize, Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub struct RpcConfig {
+    /// Providers queried in parallel for each request. Must be non-empty.
+    #[n(0)]
+    pub providers: Vec<RpcProvider>,
+    /// Minimum number of providers that must return the same result for a given method.
+    /// Methods without an explicit quorum keep their default aggregation strategy.
+    #[n(1)]
+    pub quorum: Vec<RpcMethodQuorum>,
+}
+
+impl RpcConfig {
+    /// Replaces the value of all API key headers, e.g., before exposing the config publicly.
+    pub fn redact_api_keys(mut self) -> Self {
+        for provider in self.providers.iter_mut() {
+            if let Some(header) = provider.api_key_header.as_mut() {
+                header.value = RpcApiKeyHeader::REDACTED.to_string();
+            }
+        }
+        self
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub struct RpcProvider {
+    /// HTTPS endpoint of the JSON-RPC provider.
+    #[n(0)]
+    pub url: String,
+    /// Optional HTTP header authenticating the minter with the provider.
+    #[n(1)]
+    pub api_key_header: Option<RpcApiKeyHeader>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Encode, Decode, PartialEq, Eq)]
+pub struct RpcApiKeyHeader {
+    #[n(0)]
+    pub name: String,
+    #[n(1)]
+    pub value: String,
+}
+
+impl RpcApiKeyHeader {
+    pub const REDACTED: &'static str = "<redacted>";
+}
+
+impl std::fmt::Debug for RpcApiKeyHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcApiKeyHeader")
+            .field("name", &self.name)
+            .field("value", &Self::REDACTED)
+            .finish()
+    }
+}
+
+#[derive(
+    CandidType, Deserialize, Clone, Copy, Debug, Encode, Decode, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[cbor(index_only)]
+pub enum RpcMethod {
+    #[n(0)]
+    EthGetLogs,
+    #[n(1)]
+    EthGetBlockByNumber,
+    #[n(2)]
+    EthGetTransactionReceipt,
+    #[n(3)]
+    EthFeeHistory,
+}
+
+impl RpcMethod {
+    /// Name of the method in the Ethereum JSON-RPC API.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            RpcMethod::EthGetLogs => "eth_getLogs",
+            RpcMethod::EthGetBlockByNumber => "eth_getBlockByNumber",
+            RpcMethod::EthGetTransactionReceipt => "eth_getTransactionReceipt",
+            RpcMethod::EthFeeHistory => "eth_feeHistory",
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub struct RpcMethodQuorum {
+    #[n(0)]
+    pub method: RpcMethod,
+    /// E.g., a value of 3 with 4 providers requires 3 of them to agree.
+    #[n(1)]
+    pub min_agreeing_providers: u8,
+}
+
 impl From<EthWithdrawalRequest> for RetrieveEthRequest {
     fn from(value: EthWithdrawalRequest) -> Self {
         Self {
@@ -165,7 +257,123 @@ impl From<TransferFromError> for WithdrawalError {
     }
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AddCkErc20Token {
+    pub chain_id: Nat,
+    pub address: String,
+    pub ckerc20_token_symbol: String,
+    pub ckerc20_ledger_id: Principal,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CkErc20Token {
+    pub ckerc20_token_symbol: String,
+    pub erc20_contract_address: String,
+    pub ledger_canister_id: Principal,
+}
+
+impl From<crate::erc20::CkErc20Token> for CkErc20Token {
+    fn from(value: crate::erc20::CkErc20Token) -> Self {
+        Self {
+            ckerc20_token_symbol: value.ckerc20_token_symbol,
+            erc20_contract_address: value.erc20_contract_address.to_string(),
+            ledger_canister_id: value.ckerc20_ledger_id,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct WithdrawErc20Arg {
+    pub amount: Nat,
+    pub ckerc20_ledger_id: Principal,
+    pub recipient: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RetrieveErc20Request {
+    pub cketh_block_index: Nat,
+    pub ckerc20_block_index: Nat,
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq)]
+pub enum WithdrawErc20Error {
+    AmountTooLow {
+        min_withdrawal_amount: Nat,
+    },
+    AmountTooHigh {
+        max_withdrawal_amount: Nat,
+    },
+    TokenNotSupported {
+        supported_tokens: Vec<CkErc20Token>,
+    },
+    RecipientAddressBlocked {
+        address: String,
+    },
+    CkEthLedgerError {
+        error: LedgerError,
+    },
+    /// The minter burned ckETH to pay for the transaction fee but failed to burn the ckERC20 tokens.
+    /// The burned ckETH will be reimbursed.
+    CkErc20LedgerError {
+        cketh_block_index: Nat,
+        error: LedgerError,
+    },
+    TemporarilyUnavailable(String),
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq)]
+pub enum LedgerError {
+    InsufficientFunds {
+        balance: Nat,
+        failed_burn_amount: Nat,
+        token_symbol: String,
+        ledger_id: Principal,
+    },
+    InsufficientAllowance {
+        allowance: Nat,
+        failed_burn_amount: Nat,
+        token_symbol: String,
+        ledger_id: Principal,
+    },
+    TemporarilyUnavailable(String),
+}
+
+impl LedgerError {
+    pub fn from_transfer_from_error(
+        transfer_from_error: TransferFromError,
+        failed_burn_amount: Nat,
+        token_symbol: String,
+        ledger_id: Principal,
+    ) -> Self {
+        match transfer_from_error {
+            TransferFromError::InsufficientFunds { balance } => Self::InsufficientFunds {
+                balance,
+                failed_burn_amount,
+                token_symbol,
+                ledger_id,
+            },
+            TransferFromError::InsufficientAllowance { allowance } => Self::InsufficientAllowance {
+                allowance,
+                failed_burn_amount,
+                token_symbol,
+                ledger_id,
+            },
+            TransferFromError::TemporarilyUnavailable => Self::TemporarilyUnavailable(format!(
+                "{token_symbol} ledger temporarily unavailable, try again"
+            )),
+            TransferFromError::GenericError {
+                error_code,
+                message,
+            } => Self::TemporarilyUnavailable(format!(
+                "{token_symbol} ledger unreachable, error code: {error_code}, with message: {message}"
+            )),
+            error => panic!("bug: unexpected ledger error {error:?}"),
+        }
+    }
+}
+
 pub mod events {
+    use crate::endpoints::RpcMethod;
     use crate::lifecycle::init::InitArg;
     use crate::lifecycle::upgrade::UpgradeArg;
     use candid::{CandidType, Deserialize, Nat, Principal};
@@ -276,6 +484,7 @@ pub mod events {
         FinalizedTransaction {
             withdrawal_id: Nat,
             transaction_receipt: TransactionReceipt,
+            reimbursed_amount: Option<Nat>,
         },
         ReimbursedEthWithdrawal {
             reimbursed_in_block: Nat,
@@ -286,5 +495,61 @@ pub mod events {
         SkippedBlock {
             block_number: Nat,
         },
+        AddedCkErc20Token {
+            chain_id: Nat,
+            address: String,
+            ckerc20_token_symbol: String,
+            ckerc20_ledger_id: Principal,
+        },
+        AcceptedErc20Deposit {
+            transaction_hash: String,
+            block_number: Nat,
+            log_index: Nat,
+            from_address: String,
+            value: Nat,
+            principal: Principal,
+            erc20_contract_address: String,
+        },
+        MintedCkErc20 {
+            event_source: EventSource,
+            mint_block_index: Nat,
+            ckerc20_token_symbol: String,
+            erc20_contract_address: String,
+        },
+        AcceptedErc20WithdrawalRequest {
+            max_transaction_fee: Nat,
+            withdrawal_amount: Nat,
+            erc20_contract_address: String,
+            destination: String,
+            cketh_ledger_burn_index: Nat,
+            ckerc20_ledger_id: Principal,
+            ckerc20_ledger_burn_index: Nat,
+            from: Principal,
+            from_subaccount: Option<[u8; 32]>,
+            created_at: u64,
+        },
+        FailedErc20WithdrawalRequest {
+            withdrawal_id: Nat,
+            reimbursed_amount: Nat,
+            to: Principal,
+            to_subaccount: Option<[u8; 32]>,
+        },
+        SyncedErc20ToBlock {
+            block_number: Nat,
+        },
+        SkippedErc20Block {
+            block_number: Nat,
+        },
+        ReimbursedErc20Withdrawal {
+            withdrawal_id: Nat,
+            reimbursed_in_block: Nat,
+            reimbursed_amount: Nat,
+            ckerc20_ledger_id: Principal,
+            transaction_hash: String,
+        },
+        InconsistentRpcResults {
+            method: RpcMethod,
+            providers: Vec<String>,
+        },
     }
 }
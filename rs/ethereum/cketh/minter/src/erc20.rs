@@ -0,0 +1,71 @@
+//! Module dealing with the ERC-20 tokens supported by the minter (ckERC20).
+use crate::endpoints::AddCkErc20Token;
+use crate::lifecycle::EthereumNetwork;
+use crate::numeric::Erc20Value;
+use candid::Principal;
+use ic_ethereum_types::Address;
+use minicbor::{Decode, Encode};
+use num_traits::ToPrimitive;
+use std::str::FromStr;
+
+/// Maximum length in bytes of a ckERC20 token symbol, e.g., `ckUSDC`.
+pub const MAX_CKERC20_TOKEN_SYMBOL_LENGTH: usize = 20;
+
+/// Minimum amount (in the smallest unit of the token) of a ckERC20 withdrawal.
+/// Withdrawing nothing would still burn ckETH to pay for the transaction fee.
+pub const CKERC20_MINIMUM_WITHDRAWAL_AMOUNT: Erc20Value = Erc20Value::ONE;
+
+/// An ERC-20 token on the Ethereum network together with the ledger of its
+/// twin token on the IC.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct CkErc20Token {
+    /// The address of the ERC-20 smart contract on the Ethereum network.
+    #[n(0)]
+    pub erc20_contract_address: Address,
+    /// The symbol of the ckERC20 token, e.g., `ckUSDC`.
+    #[n(1)]
+    pub ckerc20_token_symbol: String,
+    /// The ledger canister holding the ckERC20 token.
+    #[cbor(n(2), with = "crate::cbor::principal")]
+    pub ckerc20_ledger_id: Principal,
+}
+
+impl CkErc20Token {
+    /// Validates the request to support a new ckERC20 token for the given Ethereum network.
+    pub fn try_from_request(
+        request: AddCkErc20Token,
+        ethereum_network: EthereumNetwork,
+    ) -> Result<Self, String> {
+        let chain_id =
+            request.chain_id.0.to_u64().ok_or_else(|| {
+                format!("ERROR: chain ID {} does not fit in u64", request.chain_id)
+            })?;
+        if chain_id != ethereum_network.chain_id() {
+            return Err(format!(
+                "ERROR: expected chain ID {}, got {chain_id}",
+                ethereum_network.chain_id()
+            ));
+        }
+        let erc20_contract_address = Address::from_str(&request.address)
+            .map_err(|e| format!("ERROR: invalid ERC-20 contract address: {e}"))?;
+        if erc20_contract_address == Address::ZERO {
+            return Err("ERROR: ERC-20 contract address cannot be the zero address".to_string());
+        }
+        let ckerc20_token_symbol = request.ckerc20_token_symbol.trim().to_string();
+        if ckerc20_token_symbol.is_empty()
+            || ckerc20_token_symbol.len() > MAX_CKERC20_TOKEN_SYMBOL_LENGTH
+        {
+            return Err(format!(
+                "ERROR: ckERC20 token symbol must be non-empty and at most {MAX_CKERC20_TOKEN_SYMBOL_LENGTH} bytes long"
+            ));
+        }
+        if request.ckerc20_ledger_id == Principal::anonymous() {
+            return Err("ERROR: ckERC20 ledger ID cannot be the anonymous principal".to_string());
+        }
+        Ok(Self {
+            erc20_contract_address,
+            ckerc20_token_symbol,
+            ckerc20_ledger_id: request.ckerc20_ledger_id,
+        })
+    }
+}
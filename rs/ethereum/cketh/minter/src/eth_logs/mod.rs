@@ -4,7 +4,7 @@ mod tests;
 use crate::eth_rpc::{FixedSizeData, Hash, LogEntry};
 use crate::eth_rpc_client::{EthRpcClient, MultiCallError};
 use crate::logs::{DEBUG, INFO};
-use crate::numeric::{BlockNumber, LogIndex, Wei};
+use crate::numeric::{BlockNumber, Erc20Value, LogIndex, Wei};
 use crate::state::read_state;
 use candid::Principal;
 use hex_literal::hex;
@@ -17,6 +17,10 @@ use thiserror::Error;
 pub(crate) const RECEIVED_ETH_EVENT_TOPIC: [u8; 32] =
     hex!("257e057bb61920d8d0ed2cb7b720ac7f9c513cd1110bc9fa543079154f45f435");
 
+// Keccak256("ReceivedErc20(address,address,uint256,bytes32)")
+pub(crate) const RECEIVED_ERC20_EVENT_TOPIC: [u8; 32] =
+    hex!("4d69d0bd4287b7f66c548f90154dc81bc98f65a1b362775df5ae171a2ccd262b");
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
 pub struct ReceivedEthEvent {
     #[n(0)]
@@ -46,6 +50,107 @@ impl fmt::Debug for ReceivedEthEvent {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
+pub struct ReceivedErc20Event {
+    #[n(0)]
+    pub transaction_hash: Hash,
+    #[n(1)]
+    pub block_number: BlockNumber,
+    #[cbor(n(2))]
+    pub log_index: LogIndex,
+    #[n(3)]
+    pub from_address: Address,
+    #[n(4)]
+    pub value: Erc20Value,
+    #[cbor(n(5), with = "crate::cbor::principal")]
+    pub principal: Principal,
+    #[n(6)]
+    pub erc20_contract_address: Address,
+}
+
+impl fmt::Debug for ReceivedErc20Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReceivedErc20Event")
+            .field("transaction_hash", &self.transaction_hash)
+            .field("block_number", &self.block_number)
+            .field("log_index", &self.log_index)
+            .field("from_address", &self.from_address)
+            .field("value", &self.value)
+            .field("principal", &format_args!("{}", self.principal))
+            .field("erc20_contract_address", &self.erc20_contract_address)
+            .finish()
+    }
+}
+
+/// A deposit discovered in the logs of one of the helper smart contracts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReceivedEvent {
+    Eth(ReceivedEthEvent),
+    Erc20(ReceivedErc20Event),
+}
+
+impl ReceivedEvent {
+    pub fn source(&self) -> EventSource {
+        match self {
+            ReceivedEvent::Eth(event) => event.source(),
+            ReceivedEvent::Erc20(event) => event.source(),
+        }
+    }
+
+    pub fn from_address(&self) -> Address {
+        match self {
+            ReceivedEvent::Eth(event) => event.from_address,
+            ReceivedEvent::Erc20(event) => event.from_address,
+        }
+    }
+
+    pub fn principal(&self) -> Principal {
+        match self {
+            ReceivedEvent::Eth(event) => event.principal,
+            ReceivedEvent::Erc20(event) => event.principal,
+        }
+    }
+}
+
+impl From<ReceivedEthEvent> for ReceivedEvent {
+    fn from(event: ReceivedEthEvent) -> Self {
+        ReceivedEvent::Eth(event)
+    }
+}
+
+impl From<ReceivedErc20Event> for ReceivedEvent {
+    fn from(event: ReceivedErc20Event) -> Self {
+        ReceivedEvent::Erc20(event)
+    }
+}
+
+/// The kind of deposit logs emitted by a helper smart contract.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReceivedEventKind {
+    /// `ReceivedEth` logs emitted by the ETH helper smart contract.
+    Eth,
+    /// `ReceivedErc20` logs emitted by the ERC-20 helper smart contract.
+    Erc20,
+}
+
+impl ReceivedEventKind {
+    pub fn topic(&self) -> [u8; 32] {
+        match self {
+            ReceivedEventKind::Eth => RECEIVED_ETH_EVENT_TOPIC,
+            ReceivedEventKind::Erc20 => RECEIVED_ERC20_EVENT_TOPIC,
+        }
+    }
+
+    pub fn parse_log(&self, entry: LogEntry) -> Result<ReceivedEvent, ReceivedEthEventError> {
+        match self {
+            ReceivedEventKind::Eth => ReceivedEthEvent::try_from(entry).map(ReceivedEvent::from),
+            ReceivedEventKind::Erc20 => {
+                ReceivedErc20Event::try_from(entry).map(ReceivedEvent::from)
+            }
+        }
+    }
+}
+
 /// A unique identifier of the event source: the source transaction hash and the log
 /// entry index.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
@@ -71,11 +176,21 @@ impl ReceivedEthEvent {
     }
 }
 
-pub async fn last_received_eth_events(
+impl ReceivedErc20Event {
+    pub fn source(&self) -> EventSource {
+        EventSource {
+            transaction_hash: self.transaction_hash,
+            log_index: self.log_index,
+        }
+    }
+}
+
+pub async fn last_received_events(
+    kind: ReceivedEventKind,
     contract_address: Address,
     from: BlockNumber,
     to: BlockNumber,
-) -> Result<(Vec<ReceivedEthEvent>, Vec<ReceivedEthEventError>), MultiCallError<Vec<LogEntry>>> {
+) -> Result<(Vec<ReceivedEvent>, Vec<ReceivedEthEventError>), MultiCallError<Vec<LogEntry>>> {
     use crate::eth_rpc::GetLogsParam;
 
     if from > to {
@@ -90,15 +205,15 @@ pub async fn last_received_eth_events(
             from_block: from.into(),
             to_block: to.into(),
             address: vec![contract_address],
-            topics: vec![FixedSizeData(RECEIVED_ETH_EVENT_TOPIC)],
+            topics: vec![FixedSizeData(kind.topic())],
         })
         .await?;
 
     let (ok, not_ok): (Vec<_>, Vec<_>) = result
         .into_iter()
-        .map(ReceivedEthEvent::try_from)
+        .map(|entry| kind.parse_log(entry))
         .partition(Result::is_ok);
-    let valid_transactions: Vec<ReceivedEthEvent> = ok.into_iter().map(Result::unwrap).collect();
+    let valid_transactions: Vec<ReceivedEvent> = ok.into_iter().map(Result::unwrap).collect();
     let errors: Vec<ReceivedEthEventError> = not_ok.into_iter().map(Result::unwrap_err).collect();
     Ok((valid_transactions, errors))
 }
@@ -137,10 +252,16 @@ pub enum EventSourceError {
     InvalidEvent(String),
 }
 
-impl TryFrom<LogEntry> for ReceivedEthEvent {
-    type Error = ReceivedEthEventError;
+/// Metadata common to all log entries emitted by the helper smart contracts.
+struct LogEntryMetadata {
+    transaction_hash: Hash,
+    block_number: BlockNumber,
+    log_index: LogIndex,
+    event_source: EventSource,
+}
 
-    fn try_from(entry: LogEntry) -> Result<Self, Self::Error> {
+impl LogEntryMetadata {
+    fn parse(entry: &LogEntry, expected_topics: usize) -> Result<Self, ReceivedEthEventError> {
         let _block_hash = entry
             .block_hash
             .ok_or(ReceivedEthEventError::PendingLogEntry)?;
@@ -170,53 +291,94 @@ impl TryFrom<LogEntry> for ReceivedEthEvent {
             });
         }
 
-        if entry.topics.len() != 3 {
+        if entry.topics.len() != expected_topics {
             return Err(ReceivedEthEventError::InvalidEventSource {
                 source: event_source,
                 error: EventSourceError::InvalidEvent(format!(
-                    "Expected exactly 3 topics, got {}",
+                    "Expected exactly {expected_topics} topics, got {}",
                     entry.topics.len()
                 )),
             });
         }
-        let from_address = Address::try_from(&entry.topics[1].0).map_err(|err| {
-            ReceivedEthEventError::InvalidEventSource {
-                source: event_source,
-                error: EventSourceError::InvalidEvent(format!(
-                    "Invalid address in log entry: {}",
-                    err
-                )),
-            }
-        })?;
-        let principal = parse_principal_from_slice(entry.topics[2].as_ref()).map_err(|_err| {
+
+        Ok(Self {
+            transaction_hash,
+            block_number,
+            log_index,
+            event_source,
+        })
+    }
+
+    fn parse_address(&self, topic: &FixedSizeData) -> Result<Address, ReceivedEthEventError> {
+        Address::try_from(&topic.0).map_err(|err| ReceivedEthEventError::InvalidEventSource {
+            source: self.event_source,
+            error: EventSourceError::InvalidEvent(format!("Invalid address in log entry: {}", err)),
+        })
+    }
+
+    fn parse_principal(&self, topic: &FixedSizeData) -> Result<Principal, ReceivedEthEventError> {
+        parse_principal_from_slice(topic.as_ref()).map_err(|_err| {
             ReceivedEthEventError::InvalidEventSource {
-                source: event_source,
+                source: self.event_source,
                 error: EventSourceError::InvalidPrincipal {
-                    invalid_principal: entry.topics[2].clone(),
+                    invalid_principal: topic.clone(),
                 },
             }
-        })?;
-        let value_bytes: [u8; 32] =
-            entry
-                .data
-                .0
-                .try_into()
-                .map_err(|data| ReceivedEthEventError::InvalidEventSource {
-                    source: event_source,
-                    error: EventSourceError::InvalidEvent(format!(
-                        "Invalid data length; expected 32-byte value, got {}",
-                        hex::encode(data)
-                    )),
-                })?;
-        let value = Wei::from_be_bytes(value_bytes);
+        })
+    }
+
+    fn parse_value(&self, data: Vec<u8>) -> Result<[u8; 32], ReceivedEthEventError> {
+        data.try_into()
+            .map_err(|data: Vec<u8>| ReceivedEthEventError::InvalidEventSource {
+                source: self.event_source,
+                error: EventSourceError::InvalidEvent(format!(
+                    "Invalid data length; expected 32-byte value, got {}",
+                    hex::encode(data)
+                )),
+            })
+    }
+}
+
+impl TryFrom<LogEntry> for ReceivedEthEvent {
+    type Error = ReceivedEthEventError;
+
+    fn try_from(entry: LogEntry) -> Result<Self, Self::Error> {
+        // event ReceivedEth(address indexed from, uint256 value, bytes32 indexed principal);
+        let metadata = LogEntryMetadata::parse(&entry, 3)?;
+        let from_address = metadata.parse_address(&entry.topics[1])?;
+        let principal = metadata.parse_principal(&entry.topics[2])?;
+        let value = Wei::from_be_bytes(metadata.parse_value(entry.data.0)?);
 
         Ok(ReceivedEthEvent {
-            transaction_hash,
-            block_number,
-            log_index,
+            transaction_hash: metadata.transaction_hash,
+            block_number: metadata.block_number,
+            log_index: metadata.log_index,
+            from_address,
+            value,
+            principal,
+        })
+    }
+}
+
+impl TryFrom<LogEntry> for ReceivedErc20Event {
+    type Error = ReceivedEthEventError;
+
+    fn try_from(entry: LogEntry) -> Result<Self, Self::Error> {
+        // event ReceivedErc20(address indexed erc20_contract_address, address indexed owner, uint256 amount, bytes32 indexed principal);
+        let metadata = LogEntryMetadata::parse(&entry, 4)?;
+        let erc20_contract_address = metadata.parse_address(&entry.topics[1])?;
+        let from_address = metadata.parse_address(&entry.topics[2])?;
+        let principal = metadata.parse_principal(&entry.topics[3])?;
+        let value = Erc20Value::from_be_bytes(metadata.parse_value(entry.data.0)?);
+
+        Ok(ReceivedErc20Event {
+            transaction_hash: metadata.transaction_hash,
+            block_number: metadata.block_number,
+            log_index: metadata.log_index,
             from_address,
             value,
             principal,
+            erc20_contract_address,
         })
     }
 }
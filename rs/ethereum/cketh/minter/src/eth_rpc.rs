@@ -594,6 +594,7 @@ impl HttpResponsePayload for TransactionCount {}
 /// Calls a JSON-RPC method on an Ethereum node at the specified URL.
 pub async fn call<I, O>(
     url: impl Into<String>,
+    headers: Vec<HttpHeader>,
     method: impl Into<String>,
     params: I,
     mut response_size_estimate: ResponseSizeEstimate,
@@ -635,10 +636,12 @@ where
             url: url.clone(),
             max_response_bytes: Some(effective_size_estimate),
             method: HttpMethod::POST,
-            headers: vec![HttpHeader {
+            headers: std::iter::once(HttpHeader {
                 name: "Content-Type".to_string(),
                 value: "application/json".to_string(),
-            }],
+            })
+            .chain(headers.iter().cloned())
+            .collect(),
             body: Some(payload.as_bytes().to_vec()),
             transform: Some(TransformContext::from_name(
                 "cleanup_response".to_owned(),
@@ -1,15 +1,19 @@
+use crate::endpoints::RpcMethod;
 use crate::eth_rpc::{
     self, are_errors_consistent, Block, BlockSpec, FeeHistory, FeeHistoryParams, GetLogsParam,
     Hash, HttpOutcallError, HttpOutcallResult, HttpResponsePayload, JsonRpcResult, LogEntry,
     ResponseSizeEstimate, SendRawTransactionResult,
 };
-use crate::eth_rpc_client::providers::{RpcNodeProvider, MAINNET_PROVIDERS, SEPOLIA_PROVIDERS};
+use crate::eth_rpc_client::providers::{
+    CustomProvider, RpcNodeProvider, MAINNET_PROVIDERS, SEPOLIA_PROVIDERS,
+};
 use crate::eth_rpc_client::requests::GetTransactionCountParams;
 use crate::eth_rpc_client::responses::TransactionReceipt;
 use crate::lifecycle::EthereumNetwork;
 use crate::logs::{DEBUG, INFO};
 use crate::numeric::TransactionCount;
-use crate::state::State;
+use crate::state::audit::{process_event, EventType};
+use crate::state::{mutate_state, State};
 use ic_canister_log::log;
 use serde::{de::DeserializeOwned, Serialize};
 use std::collections::BTreeMap;
@@ -25,24 +29,75 @@ mod tests;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EthRpcClient {
     chain: EthereumNetwork,
+    /// Providers replacing the built-in providers of `chain`, if any.
+    custom_providers: Option<Vec<RpcNodeProvider>>,
+    /// Minimum number of agreeing providers for methods that require a quorum.
+    quorum: BTreeMap<RpcMethod, usize>,
 }
 
 impl EthRpcClient {
     const fn new(chain: EthereumNetwork) -> Self {
-        Self { chain }
+        Self {
+            chain,
+            custom_providers: None,
+            quorum: BTreeMap::new(),
+        }
     }
 
-    pub const fn from_state(state: &State) -> Self {
-        Self::new(state.ethereum_network())
+    pub fn from_state(state: &State) -> Self {
+        let mut client = Self::new(state.ethereum_network());
+        if let Some(rpc_config) = &state.rpc_config {
+            client.custom_providers = Some(
+                rpc_config
+                    .providers
+                    .iter()
+                    .map(|provider| RpcNodeProvider::Custom(CustomProvider::from(provider)))
+                    .collect(),
+            );
+            client.quorum = rpc_config
+                .quorum
+                .iter()
+                .map(|quorum| (quorum.method, quorum.min_agreeing_providers as usize))
+                .collect();
+        }
+        client
     }
 
     fn providers(&self) -> &[RpcNodeProvider] {
+        if let Some(providers) = &self.custom_providers {
+            return providers;
+        }
         match self.chain {
             EthereumNetwork::Mainnet => &MAINNET_PROVIDERS,
             EthereumNetwork::Sepolia => &SEPOLIA_PROVIDERS,
         }
     }
 
+    /// Reduces the results with the quorum configured for the given method, if any,
+    /// or with the default strategy otherwise. Inconsistent results are recorded as an event.
+    fn reduce<T: Clone + Debug + PartialEq>(
+        &self,
+        method: RpcMethod,
+        results: MultiCallResults<T>,
+        default_strategy: impl FnOnce(MultiCallResults<T>) -> Result<T, MultiCallError<T>>,
+    ) -> Result<T, MultiCallError<T>> {
+        let reduced = match self.quorum.get(&method) {
+            Some(min_agreeing_providers) => results.reduce_with_quorum(*min_agreeing_providers),
+            None => default_strategy(results),
+        };
+        if let Err(MultiCallError::InconsistentResults(inconsistent_results)) = &reduced {
+            let providers = inconsistent_results
+                .results
+                .keys()
+                .map(|provider| provider.host().to_string())
+                .collect();
+            mutate_state(|s| {
+                process_event(s, EventType::InconsistentRpcResults { method, providers })
+            });
+        }
+        reduced
+    }
+
     /// Query all providers in sequence until one returns an ok result
     /// (which could still be a JsonRpcResult::Error).
     /// If none of the providers return an ok result, return the last error.
@@ -67,6 +122,7 @@ impl EthRpcClient {
             );
             let result = eth_rpc::call(
                 provider.url().to_string(),
+                provider.headers(),
                 method.clone(),
                 params.clone(),
                 response_size_estimate,
@@ -112,6 +168,7 @@ impl EthRpcClient {
                 log!(DEBUG, "[parallel_call]: will call provider: {:?}", provider);
                 fut.push(eth_rpc::call(
                     provider.url().to_string(),
+                    provider.headers(),
                     method.clone(),
                     params.clone(),
                     response_size_estimate,
@@ -128,9 +185,15 @@ impl EthRpcClient {
     ) -> Result<Vec<LogEntry>, MultiCallError<Vec<LogEntry>>> {
         // We expect most of the calls to contain zero events.
         let results: MultiCallResults<Vec<LogEntry>> = self
-            .parallel_call("eth_getLogs", vec![params], ResponseSizeEstimate::new(100))
+            .parallel_call(
+                RpcMethod::EthGetLogs.as_str(),
+                vec![params],
+                ResponseSizeEstimate::new(100),
+            )
             .await;
-        results.reduce_with_equality()
+        self.reduce(RpcMethod::EthGetLogs, results, |results| {
+            results.reduce_with_equality()
+        })
     }
 
     pub async fn eth_get_block_by_number(
@@ -146,7 +209,7 @@ impl EthRpcClient {
 
         let results: MultiCallResults<Block> = self
             .parallel_call(
-                "eth_getBlockByNumber",
+                RpcMethod::EthGetBlockByNumber.as_str(),
                 GetBlockByNumberParams {
                     block,
                     include_full_transactions: false,
@@ -154,7 +217,9 @@ impl EthRpcClient {
                 ResponseSizeEstimate::new(expected_block_size),
             )
             .await;
-        results.reduce_with_equality()
+        self.reduce(RpcMethod::EthGetBlockByNumber, results, |results| {
+            results.reduce_with_equality()
+        })
     }
 
     pub async fn eth_get_transaction_receipt(
@@ -163,12 +228,14 @@ impl EthRpcClient {
     ) -> Result<Option<TransactionReceipt>, MultiCallError<Option<TransactionReceipt>>> {
         let results: MultiCallResults<Option<TransactionReceipt>> = self
             .parallel_call(
-                "eth_getTransactionReceipt",
+                RpcMethod::EthGetTransactionReceipt.as_str(),
                 vec![tx_hash],
                 ResponseSizeEstimate::new(700),
             )
             .await;
-        results.reduce_with_equality()
+        self.reduce(RpcMethod::EthGetTransactionReceipt, results, |results| {
+            results.reduce_with_equality()
+        })
     }
 
     pub async fn eth_fee_history(
@@ -177,9 +244,15 @@ impl EthRpcClient {
     ) -> Result<FeeHistory, MultiCallError<FeeHistory>> {
         // A typical response is slightly above 300 bytes.
         let results: MultiCallResults<FeeHistory> = self
-            .parallel_call("eth_feeHistory", params, ResponseSizeEstimate::new(512))
+            .parallel_call(
+                RpcMethod::EthFeeHistory.as_str(),
+                params,
+                ResponseSizeEstimate::new(512),
+            )
             .await;
-        results.reduce_with_strict_majority_by_key(|fee_history| fee_history.oldest_block)
+        self.reduce(RpcMethod::EthFeeHistory, results, |results| {
+            results.reduce_with_strict_majority_by_key(|fee_history| fee_history.oldest_block)
+        })
     }
 
     pub async fn eth_send_raw_transaction(
@@ -330,6 +403,41 @@ impl<T: Debug + PartialEq> MultiCallResults<T> {
         Ok(base_result)
     }
 
+    /// Returns the result on which at least `min_agreeing_providers` providers agree.
+    /// If no provider returned an ok result, the errors are aggregated as in [`Self::all_ok`].
+    pub fn reduce_with_quorum(self, min_agreeing_providers: usize) -> Result<T, MultiCallError<T>>
+    where
+        T: Clone,
+    {
+        let mut tally: Vec<(&T, usize)> = Vec::new();
+        for result in self.results.values() {
+            if let Ok(JsonRpcResult::Result(value)) = result {
+                match tally.iter_mut().find(|(other, _votes)| *other == value) {
+                    Some((_value, votes)) => *votes += 1,
+                    None => tally.push((value, 1)),
+                }
+            }
+        }
+        if tally.is_empty() {
+            return match self.all_ok() {
+                Ok(_) => panic!("BUG: all_ok should fail when there is no ok result"),
+                Err(error) => Err(error),
+            };
+        }
+        if let Some((value, _votes)) = tally
+            .into_iter()
+            .find(|(_value, votes)| *votes >= min_agreeing_providers)
+        {
+            return Ok(value.clone());
+        }
+        let error = MultiCallError::InconsistentResults(self);
+        log!(
+            INFO,
+            "[reduce_with_quorum]: no quorum of {min_agreeing_providers} providers {error:?}"
+        );
+        Err(error)
+    }
+
     pub fn reduce_with_min_by_key<F: FnMut(&T) -> K, K: Ord>(
         self,
         extractor: F,
@@ -1,3 +1,7 @@
+use crate::endpoints::RpcProvider;
+use ic_cdk::api::management_canister::http_request::HttpHeader;
+use std::fmt;
+
 pub(crate) const MAINNET_PROVIDERS: [RpcNodeProvider; 3] = [
     RpcNodeProvider::Ethereum(EthereumProvider::Ankr),
     RpcNodeProvider::Ethereum(EthereumProvider::PublicNode),
@@ -9,10 +13,11 @@ pub(crate) const SEPOLIA_PROVIDERS: [RpcNodeProvider; 2] = [
     RpcNodeProvider::Sepolia(SepoliaProvider::PublicNode),
 ];
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub(crate) enum RpcNodeProvider {
     Ethereum(EthereumProvider),
     Sepolia(SepoliaProvider),
+    Custom(CustomProvider),
 }
 
 impl RpcNodeProvider {
@@ -20,8 +25,69 @@ impl RpcNodeProvider {
         match self {
             Self::Ethereum(provider) => provider.ethereum_mainnet_endpoint_url(),
             Self::Sepolia(provider) => provider.ethereum_sepolia_endpoint_url(),
+            Self::Custom(provider) => &provider.url,
+        }
+    }
+
+    /// Additional HTTP headers to send to the provider.
+    pub(crate) fn headers(&self) -> Vec<HttpHeader> {
+        match self {
+            Self::Ethereum(_) | Self::Sepolia(_) => vec![],
+            Self::Custom(provider) => provider
+                .api_key_header
+                .iter()
+                .map(|(name, value)| HttpHeader {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
         }
     }
+
+    /// The host of the provider URL, which is safe to expose publicly
+    /// since it cannot contain an API key.
+    pub(crate) fn host(&self) -> &str {
+        url_host(self.url())
+    }
+}
+
+fn url_host(url: &str) -> &str {
+    let without_scheme = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or(without_scheme)
+}
+
+/// A provider configured by the minter's init or upgrade arguments.
+#[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub(crate) struct CustomProvider {
+    pub(crate) url: String,
+    pub(crate) api_key_header: Option<(String, String)>,
+}
+
+impl From<&RpcProvider> for CustomProvider {
+    fn from(provider: &RpcProvider) -> Self {
+        Self {
+            url: provider.url.clone(),
+            api_key_header: provider
+                .api_key_header
+                .as_ref()
+                .map(|header| (header.name.clone(), header.value.clone())),
+        }
+    }
+}
+
+impl fmt::Debug for CustomProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The URL path and the header value may contain credentials.
+        f.debug_struct("CustomProvider")
+            .field("host", &url_host(&self.url))
+            .finish()
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
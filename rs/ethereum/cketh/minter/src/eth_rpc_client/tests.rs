@@ -33,6 +33,85 @@ mod eth_rpc_client {
             ]
         );
     }
+
+    #[test]
+    fn should_use_providers_and_quorum_from_rpc_config() {
+        use crate::endpoints::{
+            RpcApiKeyHeader, RpcConfig, RpcMethod, RpcMethodQuorum, RpcProvider,
+        };
+        use crate::eth_rpc_client::providers::CustomProvider;
+        use crate::lifecycle::init::InitArg;
+        use crate::state::State;
+        use candid::Principal;
+
+        let state = State::try_from(InitArg {
+            ethereum_network: EthereumNetwork::Mainnet,
+            ecdsa_key_name: "test_key_1".to_string(),
+            ethereum_contract_address: None,
+            ledger_id: Principal::from_text("apia6-jaaaa-aaaar-qabma-cai").unwrap(),
+            ethereum_block_height: Default::default(),
+            minimum_withdrawal_amount: 10_000_000_000_000_000_u64.into(),
+            next_transaction_nonce: Default::default(),
+            last_scraped_block_number: Default::default(),
+            rpc_config: Some(RpcConfig {
+                providers: vec![
+                    RpcProvider {
+                        url: "http://localhost:8545".to_string(),
+                        api_key_header: None,
+                    },
+                    RpcProvider {
+                        url: "https://eth-mainnet.example.com/v2".to_string(),
+                        api_key_header: Some(RpcApiKeyHeader {
+                            name: "X-API-Key".to_string(),
+                            value: "secret".to_string(),
+                        }),
+                    },
+                ],
+                quorum: vec![RpcMethodQuorum {
+                    method: RpcMethod::EthGetLogs,
+                    min_agreeing_providers: 2,
+                }],
+            }),
+        })
+        .expect("valid init args");
+
+        let client = EthRpcClient::from_state(&state);
+
+        assert_eq!(
+            client.providers(),
+            &[
+                RpcNodeProvider::Custom(CustomProvider {
+                    url: "http://localhost:8545".to_string(),
+                    api_key_header: None,
+                }),
+                RpcNodeProvider::Custom(CustomProvider {
+                    url: "https://eth-mainnet.example.com/v2".to_string(),
+                    api_key_header: Some(("X-API-Key".to_string(), "secret".to_string())),
+                })
+            ]
+        );
+        assert_eq!(client.providers()[0].headers(), vec![]);
+        assert_eq!(client.providers()[1].headers().len(), 1);
+        assert_eq!(client.providers()[1].host(), "eth-mainnet.example.com");
+        assert_eq!(client.quorum.get(&RpcMethod::EthGetLogs), Some(&2));
+        assert_eq!(client.quorum.get(&RpcMethod::EthFeeHistory), None);
+    }
+
+    #[test]
+    fn should_not_leak_api_key_of_custom_provider() {
+        use crate::eth_rpc_client::providers::CustomProvider;
+
+        let provider = RpcNodeProvider::Custom(CustomProvider {
+            url: "https://eth-mainnet.example.com/v2/path-api-key".to_string(),
+            api_key_header: Some(("X-API-Key".to_string(), "header-api-key".to_string())),
+        });
+
+        let debug = format!("{:?}", provider);
+
+        assert!(debug.contains("eth-mainnet.example.com"), "{debug}");
+        assert!(!debug.contains("path-api-key"), "{debug}");
+        assert!(!debug.contains("header-api-key"), "{debug}");
+    }
 }
 
 mod multi_call_results {
@@ -226,6 +305,73 @@ mod multi_call_results {
         }
     }
 
+    mod reduce_with_quorum {
+        use crate::eth_rpc::{HttpOutcallError, JsonRpcResult};
+        use crate::eth_rpc_client::tests::multi_call_results::{ANKR, CLOUDFLARE, PUBLIC_NODE};
+        use crate::eth_rpc_client::{MultiCallError, MultiCallResults};
+        use ic_cdk::api::call::RejectionCode;
+
+        #[test]
+        fn should_get_result_with_2_out_of_3() {
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result("hello".to_string()))),
+                (PUBLIC_NODE, Ok(JsonRpcResult::Result("world".to_string()))),
+                (CLOUDFLARE, Ok(JsonRpcResult::Result("hello".to_string()))),
+            ]);
+
+            assert_eq!(results.reduce_with_quorum(2), Ok("hello".to_string()));
+        }
+
+        #[test]
+        fn should_tolerate_error_when_quorum_reached() {
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result("hello".to_string()))),
+                (
+                    PUBLIC_NODE,
+                    Err(HttpOutcallError::IcError {
+                        code: RejectionCode::SysTransient,
+                        message: "transient".to_string(),
+                    }),
+                ),
+                (CLOUDFLARE, Ok(JsonRpcResult::Result("hello".to_string()))),
+            ]);
+
+            assert_eq!(results.reduce_with_quorum(2), Ok("hello".to_string()));
+        }
+
+        #[test]
+        fn should_be_inconsistent_when_quorum_not_reached() {
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Ok(JsonRpcResult::Result("hello".to_string()))),
+                (PUBLIC_NODE, Ok(JsonRpcResult::Result("world".to_string()))),
+                (CLOUDFLARE, Ok(JsonRpcResult::Result("hello".to_string()))),
+            ]);
+
+            assert_eq!(
+                results.clone().reduce_with_quorum(3),
+                Err(MultiCallError::InconsistentResults(results))
+            );
+        }
+
+        #[test]
+        fn should_be_consistent_http_outcall_error_when_no_ok_result() {
+            let error = HttpOutcallError::IcError {
+                code: RejectionCode::SysTransient,
+                message: "transient".to_string(),
+            };
+            let results: MultiCallResults<String> = MultiCallResults::from_non_empty_iter(vec![
+                (ANKR, Err(error.clone())),
+                (PUBLIC_NODE, Err(error.clone())),
+                (CLOUDFLARE, Err(error.clone())),
+            ]);
+
+            assert_eq!(
+                results.reduce_with_quorum(2),
+                Err(MultiCallError::ConsistentHttpOutcallError(error))
+            );
+        }
+    }
+
     mod reduce_with_stable_majority_by_key {
         use crate::eth_rpc::{FeeHistory, JsonRpcResult};
         use crate::eth_rpc_client::tests::multi_call_results::{ANKR, CLOUDFLARE, PUBLIC_NODE};
@@ -4,6 +4,7 @@ mod cbor;
 pub mod checked_amount;
 pub mod deposit;
 pub mod endpoints;
+pub mod erc20;
 pub mod eth_logs;
 pub mod eth_rpc;
 pub mod eth_rpc_client;
@@ -1,4 +1,4 @@
-use crate::endpoints::CandidBlockTag;
+use crate::endpoints::{CandidBlockTag, RpcConfig};
 use crate::eth_rpc::BlockTag;
 use crate::lifecycle::EthereumNetwork;
 use crate::numeric::{BlockNumber, TransactionNonce, Wei};
@@ -28,6 +28,9 @@ pub struct InitArg {
     pub next_transaction_nonce: Nat,
     #[cbor(n(8), with = "crate::cbor::nat")]
     pub last_scraped_block_number: Nat,
+    /// JSON-RPC providers to use instead of the built-in ones.
+    #[n(9)]
+    pub rpc_config: Option<RpcConfig>,
 }
 
 impl TryFrom<InitArg> for State {
@@ -42,6 +45,7 @@ impl TryFrom<InitArg> for State {
             minimum_withdrawal_amount,
             next_transaction_nonce,
             last_scraped_block_number,
+            rpc_config,
         }: InitArg,
     ) -> Result<Self, Self::Error> {
         use std::str::FromStr;
@@ -89,6 +93,15 @@ impl TryFrom<InitArg> for State {
             skipped_blocks: Default::default(),
             active_tasks: Default::default(),
             http_request_counter: 0,
+            ledger_suite_orchestrator_id: None,
+            erc20_helper_contract_address: None,
+            last_erc20_scraped_block_number: last_scraped_block_number,
+            ckerc20_tokens: Default::default(),
+            erc20_events_to_mint: Default::default(),
+            minted_erc20_events: Default::default(),
+            skipped_erc20_blocks: Default::default(),
+            rpc_config,
+            inconsistent_rpc_results: Default::default(),
         };
         state.validate_config()?;
         Ok(state)
@@ -99,6 +99,7 @@ mod init {
             minimum_withdrawal_amount: Wei::TWO.into(),
             next_transaction_nonce: TransactionNonce::ZERO.into(),
             last_scraped_block_number: Default::default(),
+            rpc_config: None,
         }
     }
 }
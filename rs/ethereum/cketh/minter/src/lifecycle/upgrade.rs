@@ -1,10 +1,10 @@
-use crate::endpoints::CandidBlockTag;
+use crate::endpoints::{CandidBlockTag, RpcConfig};
 use crate::logs::INFO;
 use crate::state::audit::{process_event, replay_events, EventType};
 use crate::state::mutate_state;
 use crate::state::STATE;
 use crate::storage::total_event_count;
-use candid::{CandidType, Deserialize, Nat};
+use candid::{CandidType, Deserialize, Nat, Principal};
 use ic_canister_log::log;
 use minicbor::{Decode, Encode};
 
@@ -18,6 +18,19 @@ pub struct UpgradeArg {
     pub ethereum_contract_address: Option<String>,
     #[n(3)]
     pub ethereum_block_height: Option<CandidBlockTag>,
+    /// The canister allowed to add new ckERC20 tokens to the minter.
+    #[cbor(n(4), with = "crate::cbor::principal::option")]
+    pub ledger_suite_orchestrator_id: Option<Principal>,
+    /// The address of the helper smart contract emitting `ReceivedErc20` logs.
+    #[n(5)]
+    pub erc20_helper_contract_address: Option<String>,
+    /// The last block of the ERC-20 helper smart contract logs that does not need to be scrapped.
+    /// Defaults to the last scrapped block of the ETH helper smart contract logs.
+    #[cbor(n(6), with = "crate::cbor::nat::option")]
+    pub last_erc20_scraped_block_number: Option<Nat>,
+    /// JSON-RPC providers to use instead of the currently configured ones.
+    #[n(7)]
+    pub rpc_config: Option<RpcConfig>,
 }
 
 pub fn post_upgrade(upgrade_args: Option<UpgradeArg>) {
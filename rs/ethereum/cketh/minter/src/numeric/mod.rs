@@ -9,6 +9,10 @@ use phantom_newtype::Id;
 pub enum WeiTag {}
 pub type Wei = CheckedAmountOf<WeiTag>;
 
+pub enum Erc20Tag {}
+/// Amount of an ERC-20 token, in the smallest unit of that token.
+pub type Erc20Value = CheckedAmountOf<Erc20Tag>;
+
 pub enum WeiPerGasUnit {}
 pub type WeiPerGas = CheckedAmountOf<WeiPerGasUnit>;
 
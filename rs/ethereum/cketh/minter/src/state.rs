@@ -1,5 +1,7 @@
 use crate::address::ecdsa_public_key_to_address;
-use crate::eth_logs::{EventSource, ReceivedEthEvent};
+use crate::endpoints::{RpcConfig, RpcMethod};
+use crate::erc20::CkErc20Token;
+use crate::eth_logs::{EventSource, ReceivedErc20Event, ReceivedEthEvent};
 use crate::eth_rpc::BlockTag;
 use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
 use crate::lifecycle::upgrade::UpgradeArg;
@@ -39,6 +41,19 @@ impl MintedEvent {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MintedErc20Event {
+    pub deposit_event: ReceivedErc20Event,
+    pub mint_block_index: LedgerMintIndex,
+    pub ckerc20_token_symbol: String,
+}
+
+impl MintedErc20Event {
+    pub fn source(&self) -> EventSource {
+        self.deposit_event.source()
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct State {
     pub ethereum_network: EthereumNetwork,
@@ -57,6 +72,22 @@ pub struct State {
     pub eth_transactions: EthTransactions,
     pub skipped_blocks: BTreeSet<BlockNumber>,
 
+    /// The canister allowed to add new ckERC20 tokens.
+    pub ledger_suite_orchestrator_id: Option<Principal>,
+    /// The helper smart contract emitting `ReceivedErc20` logs.
+    pub erc20_helper_contract_address: Option<Address>,
+    pub last_erc20_scraped_block_number: BlockNumber,
+    /// Supported ckERC20 tokens, indexed by the address of the ERC-20 smart contract.
+    pub ckerc20_tokens: BTreeMap<Address, CkErc20Token>,
+    pub erc20_events_to_mint: BTreeMap<EventSource, ReceivedErc20Event>,
+    pub minted_erc20_events: BTreeMap<EventSource, MintedErc20Event>,
+    pub skipped_erc20_blocks: BTreeSet<BlockNumber>,
+
+    /// JSON-RPC providers replacing the built-in providers of `ethereum_network`.
+    pub rpc_config: Option<RpcConfig>,
+    /// Number of times the JSON-RPC providers returned inconsistent results, per method.
+    pub inconsistent_rpc_results: BTreeMap<RpcMethod, u64>,
+
     /// Current balance of ETH held by minter.
     /// Computed based on audit events.
     pub eth_balance: EthBalance,
@@ -79,6 +110,8 @@ pub enum InvalidStateError {
     InvalidEthereumContractAddress(String),
     InvalidMinimumWithdrawalAmount(String),
     InvalidLastScrapedBlockNumber(String),
+    InvalidErc20HelperContractAddress(String),
+    InvalidRpcConfig(String),
 }
 
 impl State {
@@ -102,11 +135,23 @@ impl State {
                 "ethereum_contract_address cannot be the zero address".to_string(),
             ));
         }
+        if self
+            .erc20_helper_contract_address
+            .iter()
+            .any(|address| address == &Address::ZERO)
+        {
+            return Err(InvalidStateError::InvalidErc20HelperContractAddress(
+                "erc20_helper_contract_address cannot be the zero address".to_string(),
+            ));
+        }
         if self.minimum_withdrawal_amount == Wei::ZERO {
             return Err(InvalidStateError::InvalidMinimumWithdrawalAmount(
                 "minimum_withdrawal_amount must be positive".to_string(),
             ));
         }
+        if let Some(rpc_config) = &self.rpc_config {
+            validate_rpc_config(rpc_config).map_err(InvalidStateError::InvalidRpcConfig)?;
+        }
         Ok(())
     }
 
@@ -120,29 +165,75 @@ impl State {
 
     fn record_event_to_mint(&mut self, event: &ReceivedEthEvent) {
         let event_source = event.source();
-        assert!(
-            !self.events_to_mint.contains_key(&event_source),
-            "there must be no two different events with the same source"
-        );
-        assert!(!self.minted_events.contains_key(&event_source));
-        assert!(!self.invalid_events.contains_key(&event_source));
+        self.assert_new_event_source(&event_source);
 
         self.events_to_mint.insert(event_source, event.clone());
 
         self.update_eth_balance_upon_deposit(event)
     }
 
+    fn record_erc20_event_to_mint(&mut self, event: &ReceivedErc20Event) {
+        let event_source = event.source();
+        self.assert_new_event_source(&event_source);
+        assert!(
+            self.ckerc20_tokens
+                .contains_key(&event.erc20_contract_address),
+            "BUG: unsupported ERC-20 contract address {}",
+            event.erc20_contract_address
+        );
+
+        self.erc20_events_to_mint
+            .insert(event_source, event.clone());
+    }
+
+    fn assert_new_event_source(&self, event_source: &EventSource) {
+        assert!(
+            !self.events_to_mint.contains_key(event_source)
+                && !self.erc20_events_to_mint.contains_key(event_source),
+            "there must be no two different events with the same source"
+        );
+        assert!(!self.minted_events.contains_key(event_source));
+        assert!(!self.minted_erc20_events.contains_key(event_source));
+        assert!(!self.invalid_events.contains_key(event_source));
+    }
+
     pub fn has_events_to_mint(&self) -> bool {
-        !self.events_to_mint.is_empty()
+        !self.events_to_mint.is_empty() || !self.erc20_events_to_mint.is_empty()
+    }
+
+    fn record_add_ckerc20_token(&mut self, token: CkErc20Token) {
+        assert!(
+            !self
+                .ckerc20_tokens
+                .contains_key(&token.erc20_contract_address),
+            "BUG: ERC-20 contract address {} is already supported",
+            token.erc20_contract_address
+        );
+        assert!(
+            self.find_ckerc20_token_by_ledger_id(&token.ckerc20_ledger_id)
+                .is_none(),
+            "BUG: ckERC20 ledger {} is already used",
+            token.ckerc20_ledger_id
+        );
+        self.ckerc20_tokens
+            .insert(token.erc20_contract_address, token);
+    }
+
+    pub fn find_ckerc20_token_by_ledger_id(&self, ledger_id: &Principal) -> Option<&CkErc20Token> {
+        self.ckerc20_tokens
+            .values()
+            .find(|token| &token.ckerc20_ledger_id == ledger_id)
     }
 
     fn record_invalid_deposit(&mut self, source: EventSource, error: String) -> bool {
         assert!(
-            !self.events_to_mint.contains_key(&source),
+            !self.events_to_mint.contains_key(&source)
+                && !self.erc20_events_to_mint.contains_key(&source),
             "attempted to mark an accepted event as invalid"
         );
         assert!(
-            !self.minted_events.contains_key(&source),
+            !self.minted_events.contains_key(&source)
+                && !self.minted_erc20_events.contains_key(&source),
             "attempted to mark a minted event {source:?} as invalid"
         );
 
@@ -178,14 +269,54 @@ impl State {
         );
     }
 
+    fn record_successful_erc20_mint(
+        &mut self,
+        source: EventSource,
+        ckerc20_token_symbol: &str,
+        mint_block_index: LedgerMintIndex,
+    ) {
+        assert!(
+            !self.invalid_events.contains_key(&source),
+            "attempted to mint an event previously marked as invalid {source:?}"
+        );
+        let deposit_event = match self.erc20_events_to_mint.remove(&source) {
+            Some(event) => event,
+            None => panic!("attempted to mint ckERC20 for an unknown event {source:?}"),
+        };
+        assert_eq!(
+            self.ckerc20_tokens
+                .get(&deposit_event.erc20_contract_address)
+                .map(|token| token.ckerc20_token_symbol.as_str()),
+            Some(ckerc20_token_symbol),
+            "BUG: ckERC20 token symbol mismatch for event {source:?}"
+        );
+
+        assert_eq!(
+            self.minted_erc20_events.insert(
+                source,
+                MintedErc20Event {
+                    deposit_event,
+                    mint_block_index,
+                    ckerc20_token_symbol: ckerc20_token_symbol.to_string(),
+                }
+            ),
+            None,
+            "attempted to mint ckERC20 twice for the same event {source:?}"
+        );
+    }
+
     pub fn record_finalized_transaction(
         &mut self,
         withdrawal_id: &LedgerBurnIndex,
         receipt: &TransactionReceipt,
+        reimbursed_amount: &Option<Wei>,
     ) {
-        self.eth_transactions
-            .record_finalized_transaction(*withdrawal_id, receipt.clone());
-        self.update_eth_balance_upon_withdrawal(withdrawal_id, receipt);
+        self.eth_transactions.record_finalized_transaction(
+            *withdrawal_id,
+            receipt.clone(),
+            *reimbursed_amount,
+        );
+        self.update_eth_balance_upon_withdrawal(withdrawal_id, receipt, reimbursed_amount);
     }
 
     pub fn next_request_id(&mut self) -> u64 {
@@ -204,6 +335,7 @@ impl State {
         &mut self,
         withdrawal_id: &LedgerBurnIndex,
         receipt: &TransactionReceipt,
+        reimbursed_amount: &Option<Wei>,
     ) {
         let tx_fee = receipt.effective_transaction_fee();
         let tx = self
@@ -212,9 +344,14 @@ impl State {
             .get_alt(withdrawal_id)
             .expect("BUG: missing finalized transaction");
         let charged_tx_fee = tx.transaction_price().max_transaction_fee();
-        let unspent_tx_fee = charged_tx_fee.checked_sub(tx_fee).expect(
-            "BUG: charged transaction fee MUST always be at least the effective transaction fee",
-        );
+        // The unspent transaction fee of a failed ETH withdrawal is part of the reimbursed amount.
+        let unspent_tx_fee = if reimbursed_amount.is_some() {
+            Wei::ZERO
+        } else {
+            charged_tx_fee.checked_sub(tx_fee).expect(
+                "BUG: charged transaction fee MUST always be at least the effective transaction fee",
+            )
+        };
         let debited_amount = match receipt.status {
             TransactionStatus::Success => tx
                 .transaction()
@@ -236,6 +373,14 @@ impl State {
         );
     }
 
+    pub fn record_skipped_erc20_block(&mut self, block_number: BlockNumber) {
+        assert!(
+            self.skipped_erc20_blocks.insert(block_number),
+            "BUG: block {} was already skipped for the ERC-20 helper contract",
+            block_number
+        );
+    }
+
     pub const fn ethereum_network(&self) -> EthereumNetwork {
         self.ethereum_network
     }
@@ -252,6 +397,10 @@ impl State {
             minimum_withdrawal_amount,
             ethereum_contract_address,
             ethereum_block_height,
+            ledger_suite_orchestrator_id,
+            erc20_helper_contract_address,
+            last_erc20_scraped_block_number,
+            rpc_config,
         } = upgrade_args;
         if let Some(nonce) = next_transaction_nonce {
             let nonce = TransactionNonce::try_from(nonce)
@@ -273,9 +422,34 @@ impl State {
         if let Some(block_height) = ethereum_block_height {
             self.ethereum_block_height = block_height.into();
         }
+        if let Some(orchestrator_id) = ledger_suite_orchestrator_id {
+            self.ledger_suite_orchestrator_id = Some(orchestrator_id);
+        }
+        if let Some(address) = erc20_helper_contract_address {
+            let erc20_helper_contract_address = Address::from_str(&address).map_err(|e| {
+                InvalidStateError::InvalidErc20HelperContractAddress(format!("ERROR: {}", e))
+            })?;
+            if self.erc20_helper_contract_address.is_none() {
+                self.last_erc20_scraped_block_number = self.last_scraped_block_number;
+            }
+            self.erc20_helper_contract_address = Some(erc20_helper_contract_address);
+        }
+        if let Some(block_number) = last_erc20_scraped_block_number {
+            self.last_erc20_scraped_block_number =
+                BlockNumber::try_from(block_number).map_err(|e| {
+                    InvalidStateError::InvalidLastScrapedBlockNumber(format!("ERROR: {}", e))
+                })?;
+        }
+        if let Some(rpc_config) = rpc_config {
+            self.rpc_config = Some(rpc_config);
+        }
         self.validate_config()
     }
 
+    fn record_inconsistent_rpc_results(&mut self, method: RpcMethod) {
+        *self.inconsistent_rpc_results.entry(method).or_default() += 1;
+    }
+
     /// Checks whether two states are equivalent.
     pub fn is_equivalent_to(&self, other: &Self) -> Result<(), String> {
         // We define the equivalence using the upgrade procedure.
@@ -310,6 +484,26 @@ impl State {
         ensure_eq!(self.events_to_mint, other.events_to_mint);
         ensure_eq!(self.minted_events, other.minted_events);
         ensure_eq!(self.invalid_events, other.invalid_events);
+        ensure_eq!(
+            self.ledger_suite_orchestrator_id,
+            other.ledger_suite_orchestrator_id
+        );
+        ensure_eq!(
+            self.erc20_helper_contract_address,
+            other.erc20_helper_contract_address
+        );
+        ensure_eq!(
+            self.last_erc20_scraped_block_number,
+            other.last_erc20_scraped_block_number
+        );
+        ensure_eq!(self.ckerc20_tokens, other.ckerc20_tokens);
+        ensure_eq!(self.erc20_events_to_mint, other.erc20_events_to_mint);
+        ensure_eq!(self.minted_erc20_events, other.minted_erc20_events);
+        ensure_eq!(self.rpc_config, other.rpc_config);
+        ensure_eq!(
+            self.inconsistent_rpc_results,
+            other.inconsistent_rpc_results
+        );
 
         self.eth_transactions
             .is_equivalent_to(&other.eth_transactions)
@@ -320,6 +514,48 @@ impl State {
     }
 }
 
+fn validate_rpc_config(rpc_config: &RpcConfig) -> Result<(), String> {
+    if rpc_config.providers.is_empty() {
+        return Err("rpc_config must contain at least one provider".to_string());
+    }
+    let mut urls = BTreeSet::new();
+    for provider in &rpc_config.providers {
+        if !provider.url.starts_with("https://") && !provider.url.starts_with("http://") {
+            return Err(format!(
+                "provider URL {} must use the http or https scheme",
+                provider.url
+            ));
+        }
+        if !urls.insert(provider.url.as_str()) {
+            return Err(format!("duplicate provider URL {}", provider.url));
+        }
+        if let Some(header) = &provider.api_key_header {
+            if header.name.trim().is_empty() {
+                return Err(format!(
+                    "API key header name of provider {} cannot be blank",
+                    provider.url
+                ));
+            }
+        }
+    }
+    let num_providers = rpc_config.providers.len();
+    let mut methods = BTreeSet::new();
+    for quorum in &rpc_config.quorum {
+        if !methods.insert(quorum.method) {
+            return Err(format!("duplicate quorum for {}", quorum.method.as_str()));
+        }
+        let min_agreeing_providers = quorum.min_agreeing_providers as usize;
+        // A strict majority guarantees that at most one result can reach the quorum.
+        if min_agreeing_providers > num_providers || 2 * min_agreeing_providers <= num_providers {
+            return Err(format!(
+                "quorum for {} must be a strict majority of the {num_providers} providers, got {min_agreeing_providers}",
+                quorum.method.as_str()
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub fn read_state<R>(f: impl FnOnce(&State) -> R) -> R {
     STATE.with(|s| f(s.borrow().as_ref().expect("BUG: state is not initialized")))
 }
@@ -463,7 +699,7 @@ impl EthBalance {
 
 #[derive(Debug, Hash, Copy, Clone, PartialEq, Eq, EnumIter)]
 pub enum TaskType {
-    MintCkEth,
+    Mint,
     RetrieveEth,
     ScrapEthLogs,
     Reimbursement,
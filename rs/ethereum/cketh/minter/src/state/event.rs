@@ -1,9 +1,14 @@
-use crate::eth_logs::{EventSource, ReceivedEthEvent};
+use crate::endpoints::RpcMethod;
+use crate::erc20::CkErc20Token;
+use crate::eth_logs::{EventSource, ReceivedErc20Event, ReceivedEthEvent};
 use crate::eth_rpc_client::responses::TransactionReceipt;
 use crate::lifecycle::{init::InitArg, upgrade::UpgradeArg};
-use crate::numeric::{BlockNumber, LedgerBurnIndex, LedgerMintIndex};
-use crate::state::transactions::{EthWithdrawalRequest, Reimbursed};
+use crate::numeric::{BlockNumber, LedgerBurnIndex, LedgerMintIndex, Wei};
+use crate::state::transactions::{
+    Erc20Reimbursed, Erc20WithdrawalRequest, EthWithdrawalRequest, Reimbursed, ReimbursementRequest,
+};
 use crate::tx::{Eip1559TransactionRequest, SignedEip1559TransactionRequest};
+use ic_ethereum_types::Address;
 use minicbor::{Decode, Encode};
 
 /// The event describing the ckETH minter state transition.
@@ -86,6 +91,11 @@ pub enum EventType {
         /// The receipt for the finalized transaction.
         #[n(1)]
         transaction_receipt: TransactionReceipt,
+        /// The amount to reimburse if the transaction of an ETH withdrawal failed.
+        /// Absent from events recorded before the effective transaction fee was deducted
+        /// from the reimbursed amount, in which case the transaction amount is reimbursed.
+        #[n(2)]
+        reimbursed_amount: Option<Wei>,
     },
     /// The minter successfully reimbursed a failed withdrawal.
     #[n(12)]
@@ -93,6 +103,57 @@ pub enum EventType {
     /// The minter could not scrap the logs for that block.
     #[n(13)]
     SkippedBlock(#[n(0)] BlockNumber),
+    /// The minter supports a new ckERC20 token.
+    #[n(14)]
+    AddedCkErc20Token(#[n(0)] CkErc20Token),
+    /// The minter discovered a ckERC20 deposit in the ERC-20 helper contract logs.
+    #[n(15)]
+    AcceptedErc20Deposit(#[n(0)] ReceivedErc20Event),
+    /// The minter minted ckERC20 in response to a deposit.
+    #[n(16)]
+    MintedCkErc20 {
+        /// The unique identifier of the deposit on the Ethereum network.
+        #[n(0)]
+        event_source: EventSource,
+        /// The transaction index on the ckERC20 ledger.
+        #[cbor(n(1), with = "crate::cbor::id")]
+        mint_block_index: LedgerMintIndex,
+        /// The symbol of the minted ckERC20 token.
+        #[n(2)]
+        ckerc20_token_symbol: String,
+        /// The address of the ERC-20 smart contract.
+        #[n(3)]
+        erc20_contract_address: Address,
+    },
+    /// The minter accepted a new ERC-20 withdrawal request.
+    #[n(17)]
+    AcceptedErc20WithdrawalRequest(#[n(0)] Erc20WithdrawalRequest),
+    /// The minter burned ckETH to pay for the transaction fees of an ERC-20 withdrawal
+    /// but failed to burn the ckERC20 tokens. The burned ckETH will be reimbursed.
+    #[n(18)]
+    FailedErc20WithdrawalRequest(#[n(0)] ReimbursementRequest),
+    /// The minter processed the ERC-20 helper smart contract logs up to the specified height.
+    #[n(19)]
+    SyncedErc20ToBlock {
+        /// The last processed block number (inclusive).
+        #[n(0)]
+        block_number: BlockNumber,
+    },
+    /// The minter could not scrap the ERC-20 helper smart contract logs for that block.
+    #[n(20)]
+    SkippedErc20Block(#[n(0)] BlockNumber),
+    /// The minter reimbursed the ckERC20 tokens burned for a failed ERC-20 withdrawal.
+    #[n(21)]
+    ReimbursedErc20Withdrawal(#[n(0)] Erc20Reimbursed),
+    /// The JSON-RPC providers returned inconsistent results for the given method.
+    #[n(22)]
+    InconsistentRpcResults {
+        #[n(0)]
+        method: RpcMethod,
+        /// Hosts of the providers involved, without paths that may contain credentials.
+        #[n(1)]
+        providers: Vec<String>,
+    },
 }
 
 #[derive(Encode, Decode, Debug, PartialEq, Eq)]
@@ -1,6 +1,9 @@
 use crate::checked_amount::CheckedAmountOf;
-use crate::endpoints::CandidBlockTag;
-use crate::eth_logs::{EventSource, ReceivedEthEvent};
+use crate::endpoints::{
+    CandidBlockTag, RpcApiKeyHeader, RpcConfig, RpcMethod, RpcMethodQuorum, RpcProvider,
+};
+use crate::erc20::CkErc20Token;
+use crate::eth_logs::{EventSource, ReceivedErc20Event, ReceivedEthEvent};
 use crate::eth_rpc::{BlockTag, Hash};
 use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
 use crate::lifecycle::init::InitArg;
@@ -57,6 +60,7 @@ fn a_state() -> State {
         minimum_withdrawal_amount: wei_from_milli_ether(10).into(),
         next_transaction_nonce: Default::default(),
         last_scraped_block_number: Default::default(),
+        rpc_config: None,
     })
     .expect("init args should be valid")
 }
@@ -184,6 +188,7 @@ mod mint_transaction {
             minimum_withdrawal_amount: wei_from_milli_ether(10).into(),
             next_transaction_nonce: Default::default(),
             last_scraped_block_number: Default::default(),
+            rpc_config: None,
         })
         .expect("init args should be valid")
     }
@@ -212,7 +217,7 @@ mod upgrade {
     use crate::numeric::{wei_from_milli_ether, TransactionNonce, Wei};
     use crate::state::{InvalidStateError, State};
     use assert_matches::assert_matches;
-    use candid::Nat;
+    use candid::{Nat, Principal};
     use ic_ethereum_types::Address;
     use num_bigint::BigUint;
     use std::str::FromStr;
@@ -271,6 +276,14 @@ mod upgrade {
                 "0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34".to_string(),
             ),
             ethereum_block_height: Some(CandidBlockTag::Safe),
+            ledger_suite_orchestrator_id: Some(
+                Principal::from_text("vxkom-oyaaa-aaaar-qafda-cai").unwrap(),
+            ),
+            erc20_helper_contract_address: Some(
+                "0xE1788E4834c896F1932188645cc36c54d1b80AC1".to_string(),
+            ),
+            last_erc20_scraped_block_number: None,
+            rpc_config: Some(valid_rpc_config()),
         };
 
         state.upgrade(upgrade_arg).expect("valid upgrade args");
@@ -285,11 +298,125 @@ mod upgrade {
             Some(Address::from_str("0xb44B5e756A894775FC32EDdf3314Bb1B1944dC34").unwrap())
         );
         assert_eq!(state.ethereum_block_height, BlockTag::Safe);
+        assert_eq!(
+            state.ledger_suite_orchestrator_id,
+            Some(Principal::from_text("vxkom-oyaaa-aaaar-qafda-cai").unwrap())
+        );
+        assert_eq!(
+            state.erc20_helper_contract_address,
+            Some(Address::from_str("0xE1788E4834c896F1932188645cc36c54d1b80AC1").unwrap())
+        );
+        assert_eq!(
+            state.last_erc20_scraped_block_number,
+            state.last_scraped_block_number
+        );
+        assert_eq!(state.rpc_config, Some(valid_rpc_config()));
+    }
+
+    #[test]
+    fn should_fail_when_rpc_config_is_invalid() {
+        use crate::endpoints::{RpcConfig, RpcMethod, RpcMethodQuorum};
+
+        for invalid_rpc_config in [
+            RpcConfig {
+                providers: vec![],
+                quorum: vec![],
+            },
+            RpcConfig {
+                providers: vec![rpc_provider("ws://localhost:8545")],
+                ..valid_rpc_config()
+            },
+            RpcConfig {
+                providers: vec![
+                    rpc_provider("http://localhost:8545"),
+                    rpc_provider("http://localhost:8545"),
+                ],
+                quorum: vec![],
+            },
+            RpcConfig {
+                quorum: vec![RpcMethodQuorum {
+                    method: RpcMethod::EthGetLogs,
+                    min_agreeing_providers: 2,
+                }],
+                ..valid_rpc_config()
+            },
+            RpcConfig {
+                quorum: vec![RpcMethodQuorum {
+                    method: RpcMethod::EthGetLogs,
+                    min_agreeing_providers: 5,
+                }],
+                ..valid_rpc_config()
+            },
+            RpcConfig {
+                quorum: vec![
+                    RpcMethodQuorum {
+                        method: RpcMethod::EthFeeHistory,
+                        min_agreeing_providers: 3,
+                    },
+                    RpcMethodQuorum {
+                        method: RpcMethod::EthFeeHistory,
+                        min_agreeing_providers: 4,
+                    },
+                ],
+                ..valid_rpc_config()
+            },
+        ] {
+            let mut state = initial_state();
+            assert_matches!(
+                state.upgrade(UpgradeArg {
+                    rpc_config: Some(invalid_rpc_config),
+                    ..Default::default()
+                }),
+                Err(InvalidStateError::InvalidRpcConfig(_))
+            );
+        }
+    }
+
+    fn valid_rpc_config() -> crate::endpoints::RpcConfig {
+        use crate::endpoints::{RpcApiKeyHeader, RpcConfig, RpcMethod, RpcMethodQuorum};
+        RpcConfig {
+            providers: vec![
+                rpc_provider("http://localhost:8545"),
+                rpc_provider("http://localhost:8546"),
+                rpc_provider("http://localhost:8547"),
+                crate::endpoints::RpcProvider {
+                    url: "https://eth-mainnet.example.com/v2".to_string(),
+                    api_key_header: Some(RpcApiKeyHeader {
+                        name: "X-API-Key".to_string(),
+                        value: "secret".to_string(),
+                    }),
+                },
+            ],
+            quorum: vec![RpcMethodQuorum {
+                method: RpcMethod::EthGetLogs,
+                min_agreeing_providers: 3,
+            }],
+        }
+    }
+
+    fn rpc_provider(url: &str) -> crate::endpoints::RpcProvider {
+        crate::endpoints::RpcProvider {
+            url: url.to_string(),
+            api_key_header: None,
+        }
+    }
+
+    #[test]
+    fn should_fail_when_erc20_helper_contract_address_is_zero() {
+        let mut state = initial_state();
+        assert_matches!(
+            state.upgrade(UpgradeArg {
+                erc20_helper_contract_address: Some(
+                    "0x0000000000000000000000000000000000000000".to_string(),
+                ),
+                ..Default::default()
+            }),
+            Err(InvalidStateError::InvalidErc20HelperContractAddress(_))
+        );
     }
 
     fn initial_state() -> State {
         use crate::lifecycle::init::InitArg;
-        use candid::Principal;
         State::try_from(InitArg {
             ethereum_network: Default::default(),
             ecdsa_key_name: "test_key_1".to_string(),
@@ -300,6 +427,7 @@ mod upgrade {
             minimum_withdrawal_amount: wei_from_milli_ether(10).into(),
             next_transaction_nonce: Default::default(),
             last_scraped_block_number: Default::default(),
+            rpc_config: None,
         })
         .expect("valid init args")
     }
@@ -368,6 +496,7 @@ prop_compose! {
         ledger_id in arb_principal(),
         ecdsa_key_name in "[a-z_]*",
         last_scraped_block_number in arb_nat(),
+        rpc_config in proptest::option::of(arb_rpc_config()),
     ) -> InitArg {
         InitArg {
             ethereum_network: EthereumNetwork::Sepolia,
@@ -377,7 +506,8 @@ prop_compose! {
             ethereum_block_height,
             minimum_withdrawal_amount,
             next_transaction_nonce,
-            last_scraped_block_number
+            last_scraped_block_number,
+            rpc_config,
         }
     }
 }
@@ -388,12 +518,95 @@ prop_compose! {
         ethereum_block_height in proptest::option::of(arb_block_tag()),
         minimum_withdrawal_amount in proptest::option::of(arb_nat()),
         next_transaction_nonce in proptest::option::of(arb_nat()),
+        ledger_suite_orchestrator_id in proptest::option::of(arb_principal()),
+        erc20_helper_contract_address in proptest::option::of(arb_address()),
+        last_erc20_scraped_block_number in proptest::option::of(arb_nat()),
+        rpc_config in proptest::option::of(arb_rpc_config()),
     ) -> UpgradeArg {
         UpgradeArg {
             ethereum_contract_address: contract_address.map(|addr| addr.to_string()),
             ethereum_block_height,
             minimum_withdrawal_amount,
             next_transaction_nonce,
+            ledger_suite_orchestrator_id,
+            erc20_helper_contract_address: erc20_helper_contract_address.map(|addr| addr.to_string()),
+            last_erc20_scraped_block_number,
+            rpc_config,
+        }
+    }
+}
+
+fn arb_rpc_method() -> impl Strategy<Value = RpcMethod> {
+    prop_oneof![
+        Just(RpcMethod::EthGetLogs),
+        Just(RpcMethod::EthGetBlockByNumber),
+        Just(RpcMethod::EthGetTransactionReceipt),
+        Just(RpcMethod::EthFeeHistory),
+    ]
+}
+
+prop_compose! {
+    fn arb_rpc_provider()(
+        url in "https://[a-z]{1,10}\\.com/[a-z0-9]{0,10}",
+        api_key_header in proptest::option::of(("[A-Za-z-]{1,10}", "[a-z0-9]{0,20}")),
+    ) -> RpcProvider {
+        RpcProvider {
+            url,
+            api_key_header: api_key_header.map(|(name, value)| RpcApiKeyHeader { name, value }),
+        }
+    }
+}
+
+prop_compose! {
+    fn arb_rpc_config()(
+        providers in pvec(arb_rpc_provider(), 1..5),
+        quorum in pvec((arb_rpc_method(), any::<u8>()), 0..4),
+    ) -> RpcConfig {
+        RpcConfig {
+            providers,
+            quorum: quorum
+                .into_iter()
+                .map(|(method, min_agreeing_providers)| RpcMethodQuorum {
+                    method,
+                    min_agreeing_providers,
+                })
+                .collect(),
+        }
+    }
+}
+
+prop_compose! {
+    fn arb_ckerc20_token()(
+        erc20_contract_address in arb_address(),
+        ckerc20_token_symbol in "ck[A-Z]{1,10}",
+        ckerc20_ledger_id in arb_principal(),
+    ) -> CkErc20Token {
+        CkErc20Token {
+            erc20_contract_address,
+            ckerc20_token_symbol,
+            ckerc20_ledger_id,
+        }
+    }
+}
+
+prop_compose! {
+    fn arb_received_erc20_event()(
+        transaction_hash in arb_hash(),
+        block_number in arb_checked_amount_of(),
+        log_index in arb_checked_amount_of(),
+        from_address in arb_address(),
+        value in arb_checked_amount_of(),
+        principal in arb_principal(),
+        erc20_contract_address in arb_address(),
+    ) -> ReceivedErc20Event {
+        ReceivedErc20Event {
+            transaction_hash,
+            block_number,
+            log_index,
+            from_address,
+            value,
+            principal,
+            erc20_contract_address,
         }
     }
 }
@@ -523,11 +736,33 @@ fn arb_event_type() -> impl Strategy<Value = EventType> {
                 transaction,
             }
         }),
-        (any::<u64>(), arb_tx_receipt()).prop_map(|(withdrawal_id, transaction_receipt)| {
-            EventType::FinalizedTransaction {
-                withdrawal_id: withdrawal_id.into(),
-                transaction_receipt,
+        (
+            any::<u64>(),
+            arb_tx_receipt(),
+            proptest::option::of(arb_checked_amount_of())
+        )
+            .prop_map(|(withdrawal_id, transaction_receipt, reimbursed_amount)| {
+                EventType::FinalizedTransaction {
+                    withdrawal_id: withdrawal_id.into(),
+                    transaction_receipt,
+                    reimbursed_amount,
+                }
+            }),
+        arb_ckerc20_token().prop_map(EventType::AddedCkErc20Token),
+        arb_received_erc20_event().prop_map(EventType::AcceptedErc20Deposit),
+        (arb_event_source(), any::<u64>(), arb_address()).prop_map(
+            |(event_source, index, erc20_contract_address)| EventType::MintedCkErc20 {
+                event_source,
+                mint_block_index: index.into(),
+                ckerc20_token_symbol: "ckUSDC".to_string(),
+                erc20_contract_address,
             }
+        ),
+        arb_checked_amount_of()
+            .prop_map(|block_number| EventType::SyncedErc20ToBlock { block_number }),
+        arb_checked_amount_of().prop_map(EventType::SkippedErc20Block),
+        (arb_rpc_method(), pvec("[a-z]{1,10}\\.com", 1..5)).prop_map(|(method, providers)| {
+            EventType::InconsistentRpcResults { method, providers }
         }),
     ]
 }
@@ -597,6 +832,7 @@ fn state_equivalence() {
         withdrawal_requests: vec![withdrawal_request1.clone(), withdrawal_request2.clone()]
             .into_iter()
             .collect(),
+        erc20_withdrawal_requests: Default::default(),
         created_tx: singleton_map(
             2,
             4,
@@ -689,6 +925,7 @@ fn state_equivalence() {
                 created_at: Some(1699527697000000000),
             }
         },
+        maybe_reimburse_erc20: Default::default(),
         reimbursement_requests: btreemap! {
             LedgerBurnIndex::new(3) => ReimbursementRequest {
                 transaction_hash: Some("0x06afc3c693dc2ba2c19b5c287c4dddce040d766bea5fd13c8a7268b04aa94f2d"
@@ -759,6 +996,15 @@ fn state_equivalence() {
         http_request_counter: 100,
         eth_balance: Default::default(),
         skipped_blocks: Default::default(),
+        ledger_suite_orchestrator_id: None,
+        erc20_helper_contract_address: None,
+        last_erc20_scraped_block_number: BlockNumber::new(1_000_000),
+        ckerc20_tokens: Default::default(),
+        erc20_events_to_mint: Default::default(),
+        minted_erc20_events: Default::default(),
+        skipped_erc20_blocks: Default::default(),
+        rpc_config: None,
+        inconsistent_rpc_results: Default::default(),
     };
 
     assert_eq!(
@@ -1084,8 +1330,9 @@ mod eth_balance {
             balance_after_failed_withdrawal.total_effective_tx_fees
         );
         assert_eq!(
-            balance_after_successful_withdrawal.total_unspent_tx_fees,
-            balance_after_failed_withdrawal.total_unspent_tx_fees()
+            balance_before_withdrawal.total_unspent_tx_fees,
+            balance_after_failed_withdrawal.total_unspent_tx_fees(),
+            "the unspent transaction fee of a failed withdrawal is reimbursed"
         );
     }
 
@@ -1183,11 +1430,15 @@ mod eth_balance {
                 status: self.tx_status,
                 transaction_hash: signed_tx.hash(),
             };
+            let reimbursed_amount = state
+                .eth_transactions
+                .reimbursed_amount_if_failed(&self.ledger_burn_index, &tx_receipt);
             apply_state_transition(
                 state,
                 &EventType::FinalizedTransaction {
                     withdrawal_id: self.ledger_burn_index,
                     transaction_receipt: tx_receipt.clone(),
+                    reimbursed_amount,
                 },
             );
             tx_receipt
@@ -7,10 +7,12 @@ use crate::eth_rpc_client::responses::TransactionReceipt;
 use crate::eth_rpc_client::responses::TransactionStatus;
 use crate::lifecycle::EthereumNetwork;
 use crate::map::MultiKeyMap;
-use crate::numeric::{LedgerBurnIndex, LedgerMintIndex, TransactionCount, TransactionNonce, Wei};
+use crate::numeric::{
+    Erc20Value, LedgerBurnIndex, LedgerMintIndex, TransactionCount, TransactionNonce, Wei,
+};
 use crate::tx::{
-    Eip1559TransactionRequest, FinalizedEip1559Transaction, SignedEip1559TransactionRequest,
-    TransactionPrice,
+    erc20_transfer_data, Eip1559TransactionRequest, FinalizedEip1559Transaction,
+    SignedEip1559TransactionRequest, TransactionPrice, CKERC20_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
 };
 use candid::Principal;
 use ic_ethereum_types::Address;
@@ -42,6 +44,44 @@ pub struct EthWithdrawalRequest {
     pub created_at: Option<u64>,
 }
 
+/// ERC-20 withdrawal request issued by the user.
+///
+/// The withdrawal is identified by the index of the ckETH burn transaction
+/// paying for the Ethereum transaction fees.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+pub struct Erc20WithdrawalRequest {
+    /// Amount of burned ckETH that can be used to pay for the Ethereum transaction fees.
+    #[n(0)]
+    pub max_transaction_fee: Wei,
+    /// The amount of ERC-20 tokens that the receiver will get.
+    #[n(1)]
+    pub withdrawal_amount: Erc20Value,
+    /// The address to which the minter will send the ERC-20 tokens.
+    #[n(2)]
+    pub destination: Address,
+    /// The transaction ID of the ckETH burn operation on the ckETH ledger.
+    #[cbor(n(3), with = "crate::cbor::id")]
+    pub cketh_ledger_burn_index: LedgerBurnIndex,
+    /// The address of the ERC-20 smart contract.
+    #[n(4)]
+    pub erc20_contract_address: Address,
+    /// The ckERC20 ledger on which the minter burned the ckERC20 tokens.
+    #[cbor(n(5), with = "crate::cbor::principal")]
+    pub ckerc20_ledger_id: Principal,
+    /// The transaction ID of the ckERC20 burn operation on the ckERC20 ledger.
+    #[cbor(n(6), with = "crate::cbor::id")]
+    pub ckerc20_ledger_burn_index: LedgerBurnIndex,
+    /// The owner of the account from which the minter burned ckETH and ckERC20.
+    #[cbor(n(7), with = "crate::cbor::principal")]
+    pub from: Principal,
+    /// The subaccount from which the minter burned ckETH and ckERC20.
+    #[n(8)]
+    pub from_subaccount: Option<Subaccount>,
+    /// The IC time at which the withdrawal request arrived.
+    #[n(9)]
+    pub created_at: u64,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Encode, Decode)]
 pub struct ReimbursementRequest {
     #[cbor(n(0), with = "crate::cbor::id")]
@@ -71,6 +111,40 @@ pub struct Reimbursed {
     pub transaction_hash: Option<Hash>,
 }
 
+/// Request to reimburse the ckERC20 tokens burned for an ERC-20 withdrawal
+/// whose Ethereum transaction failed.
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode)]
+pub struct Erc20ReimbursementRequest {
+    /// The ckETH ledger burn index identifying the withdrawal.
+    #[cbor(n(0), with = "crate::cbor::id")]
+    pub withdrawal_id: LedgerBurnIndex,
+    #[n(1)]
+    pub reimbursed_amount: Erc20Value,
+    #[cbor(n(2), with = "crate::cbor::principal")]
+    pub ckerc20_ledger_id: Principal,
+    #[cbor(n(3), with = "crate::cbor::principal")]
+    pub to: Principal,
+    #[n(4)]
+    pub to_subaccount: Option<Subaccount>,
+    /// Transaction hash of the failed ERC-20 transaction.
+    #[n(5)]
+    pub transaction_hash: Hash,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode)]
+pub struct Erc20Reimbursed {
+    #[cbor(n(0), with = "crate::cbor::id")]
+    pub withdrawal_id: LedgerBurnIndex,
+    #[cbor(n(1), with = "crate::cbor::id")]
+    pub reimbursed_in_block: LedgerMintIndex,
+    #[n(2)]
+    pub reimbursed_amount: Erc20Value,
+    #[cbor(n(3), with = "crate::cbor::principal")]
+    pub ckerc20_ledger_id: Principal,
+    #[n(4)]
+    pub transaction_hash: Hash,
+}
+
 #[derive(Clone, Eq, PartialEq, Encode, Decode)]
 #[cbor(transparent)]
 pub struct Subaccount(#[cbor(n(0), with = "minicbor::bytes")] pub [u8; 32]);
@@ -101,9 +175,31 @@ impl fmt::Debug for EthWithdrawalRequest {
     }
 }
 
+impl fmt::Debug for Erc20WithdrawalRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Erc20WithdrawalRequest")
+            .field("max_transaction_fee", &self.max_transaction_fee)
+            .field("withdrawal_amount", &self.withdrawal_amount)
+            .field("destination", &self.destination)
+            .field("cketh_ledger_burn_index", &self.cketh_ledger_burn_index)
+            .field("erc20_contract_address", &self.erc20_contract_address)
+            .field(
+                "ckerc20_ledger_id",
+                &DebugPrincipal(&self.ckerc20_ledger_id),
+            )
+            .field("ckerc20_ledger_burn_index", &self.ckerc20_ledger_burn_index)
+            .field("from", &DebugPrincipal(&self.from))
+            .field("from_subaccount", &self.from_subaccount)
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
 /// State machine holding Ethereum transactions issued by the minter.
 /// Overall the transaction lifecycle is as follows:
 /// 1. The user's withdrawal request is enqueued and processed in a FIFO order.
+///    ETH and ERC-20 withdrawal requests are held in separate queues but share
+///    the same transaction nonces and are identified by the index of the ckETH burn transaction.
 /// 2. A transaction is created by either consuming a withdrawal request
 ///    (the first time a transaction is created for that nonce and burn index)
 ///    or re-submitting an already sent transaction for that nonce and burn index.
@@ -114,10 +210,13 @@ impl fmt::Debug for EthWithdrawalRequest {
 /// 5. For a given nonce (and burn index), at most one sent transaction is finalized.
 ///    The others sent transactions for that nonce were never mined and can be discarded.
 /// 6. If a given transaction fails the minter will reimburse the user who requested the
-///    withdrawal with the corresponding amount minus fees.
+///    withdrawal with the withdrawn amount minus the fees actually spent by the failed transaction.
+///    For ERC-20 withdrawals, the burned ckERC20 tokens are reimbursed in full,
+///    since the transaction fees were paid in ckETH.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct EthTransactions {
     pub(in crate::state) withdrawal_requests: VecDeque<EthWithdrawalRequest>,
+    pub(in crate::state) erc20_withdrawal_requests: VecDeque<Erc20WithdrawalRequest>,
     pub(in crate::state) created_tx:
         MultiKeyMap<TransactionNonce, LedgerBurnIndex, Eip1559TransactionRequest>,
     pub(in crate::state) sent_tx:
@@ -127,8 +226,12 @@ pub struct EthTransactions {
     pub(in crate::state) next_nonce: TransactionNonce,
 
     pub(in crate::state) maybe_reimburse: BTreeMap<LedgerBurnIndex, EthWithdrawalRequest>,
+    pub(in crate::state) maybe_reimburse_erc20: BTreeMap<LedgerBurnIndex, Erc20WithdrawalRequest>,
     pub(in crate::state) reimbursement_requests: BTreeMap<LedgerBurnIndex, ReimbursementRequest>,
     pub(in crate::state) reimbursed: BTreeMap<LedgerBurnIndex, Reimbursed>,
+    pub(in crate::state) erc20_reimbursement_requests:
+        BTreeMap<LedgerBurnIndex, Erc20ReimbursementRequest>,
+    pub(in crate::state) erc20_reimbursed: BTreeMap<LedgerBurnIndex, Erc20Reimbursed>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -138,6 +241,11 @@ pub enum CreateTransactionError {
         withdrawal_amount: Wei,
         max_transaction_fee: Wei,
     },
+    InsufficientTransactionFee {
+        ledger_burn_index: LedgerBurnIndex,
+        allowed_max_transaction_fee: Wei,
+        actual_max_transaction_fee: Wei,
+    },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -148,19 +256,29 @@ pub enum ResubmitTransactionError {
         transaction_amount: Wei,
         max_transaction_fee: Wei,
     },
+    InsufficientTransactionFee {
+        ledger_burn_index: LedgerBurnIndex,
+        transaction_nonce: TransactionNonce,
+        allowed_max_transaction_fee: Wei,
+        max_transaction_fee: Wei,
+    },
 }
 
 impl EthTransactions {
     pub fn new(next_nonce: TransactionNonce) -> Self {
         Self {
             withdrawal_requests: VecDeque::new(),
+            erc20_withdrawal_requests: VecDeque::new(),
             created_tx: MultiKeyMap::default(),
             sent_tx: MultiKeyMap::default(),
             finalized_tx: MultiKeyMap::default(),
             next_nonce,
             maybe_reimburse: Default::default(),
+            maybe_reimburse_erc20: Default::default(),
             reimbursement_requests: Default::default(),
             reimbursed: Default::default(),
+            erc20_reimbursement_requests: Default::default(),
+            erc20_reimbursed: Default::default(),
         }
     }
 
@@ -180,21 +298,46 @@ impl EthTransactions {
         self.reimbursed.values().cloned().collect()
     }
 
+    pub fn get_erc20_reimbursement_requests(&self) -> Vec<Erc20ReimbursementRequest> {
+        self.erc20_reimbursement_requests
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_erc20_reimbursed_transactions(&self) -> Vec<Erc20Reimbursed> {
+        self.erc20_reimbursed.values().cloned().collect()
+    }
+
     pub fn record_withdrawal_request(&mut self, request: EthWithdrawalRequest) {
         let burn_index = request.ledger_burn_index;
-        if self
-            .withdrawal_requests
-            .iter()
-            .any(|r| r.ledger_burn_index == burn_index)
-            || self.created_tx.contains_alt(&burn_index)
-            || self.sent_tx.contains_alt(&burn_index)
-            || self.finalized_tx.contains_alt(&burn_index)
-        {
+        if self.contains_withdrawal_id(&burn_index) {
             panic!("BUG: duplicate ledger burn index {burn_index}");
         }
         self.withdrawal_requests.push_back(request);
     }
 
+    pub fn record_erc20_withdrawal_request(&mut self, request: Erc20WithdrawalRequest) {
+        let burn_index = request.cketh_ledger_burn_index;
+        if self.contains_withdrawal_id(&burn_index) {
+            panic!("BUG: duplicate ckETH ledger burn index {burn_index}");
+        }
+        self.erc20_withdrawal_requests.push_back(request);
+    }
+
+    fn contains_withdrawal_id(&self, withdrawal_id: &LedgerBurnIndex) -> bool {
+        self.withdrawal_requests
+            .iter()
+            .any(|r| &r.ledger_burn_index == withdrawal_id)
+            || self
+                .erc20_withdrawal_requests
+                .iter()
+                .any(|r| &r.cketh_ledger_burn_index == withdrawal_id)
+            || self.created_tx.contains_alt(withdrawal_id)
+            || self.sent_tx.contains_alt(withdrawal_id)
+            || self.finalized_tx.contains_alt(withdrawal_id)
+    }
+
     /// Move an existing withdrawal request to the back of the queue.
     pub fn reschedule_withdrawal_request(&mut self, request: EthWithdrawalRequest) {
         assert_eq!(
@@ -210,43 +353,80 @@ impl EthTransactions {
         self.record_withdrawal_request(request);
     }
 
+    /// Move an existing ERC-20 withdrawal request to the back of the queue.
+    pub fn reschedule_erc20_withdrawal_request(&mut self, request: Erc20WithdrawalRequest) {
+        assert_eq!(
+            self.erc20_withdrawal_requests
+                .iter()
+                .filter(|r| r.cketh_ledger_burn_index == request.cketh_ledger_burn_index)
+                .count(),
+            1,
+            "BUG: expected exactly one ERC-20 withdrawal request with ckETH ledger burn index {}",
+            request.cketh_ledger_burn_index
+        );
+        self.erc20_withdrawal_requests.retain(|r| r != &request);
+        self.record_erc20_withdrawal_request(request);
+    }
+
     pub fn record_created_transaction(
         &mut self,
         withdrawal_id: LedgerBurnIndex,
         transaction: Eip1559TransactionRequest,
     ) {
-        let withdrawal_request = self
+        let nonce = self.next_nonce;
+        assert_eq!(transaction.nonce, nonce, "BUG: transaction nonce mismatch");
+        if let Some(withdrawal_request) = self
             .withdrawal_requests
             .iter()
             .find(|req| req.ledger_burn_index == withdrawal_id)
             .cloned()
-            .unwrap_or_else(|| panic!("BUG: withdrawal request {withdrawal_id} not found"));
-        assert!(
-            self.withdrawal_requests.contains(&withdrawal_request),
-            "BUG: withdrawal request not found"
-        );
-        assert_eq!(
-            withdrawal_request.destination, transaction.destination,
-            "BUG: withdrawal request and transaction destination mismatch"
-        );
-        assert!(
-            withdrawal_request.withdrawal_amount > transaction.amount,
-            "BUG: transaction amount should be the withdrawal amount deducted from transaction fees"
-        );
-        let nonce = self.next_nonce;
-        assert_eq!(transaction.nonce, nonce, "BUG: transaction nonce mismatch");
+        {
+            assert_eq!(
+                withdrawal_request.destination, transaction.destination,
+                "BUG: withdrawal request and transaction destination mismatch"
+            );
+            assert!(
+                withdrawal_request.withdrawal_amount > transaction.amount,
+                "BUG: transaction amount should be the withdrawal amount deducted from transaction fees"
+            );
+            self.remove_withdrawal_request(&withdrawal_request);
+            self.maybe_reimburse
+                .insert(withdrawal_id, withdrawal_request);
+        } else {
+            let withdrawal_request = self
+                .erc20_withdrawal_requests
+                .iter()
+                .find(|req| req.cketh_ledger_burn_index == withdrawal_id)
+                .cloned()
+                .unwrap_or_else(|| panic!("BUG: withdrawal request {withdrawal_id} not found"));
+            assert_eq!(
+                withdrawal_request.erc20_contract_address, transaction.destination,
+                "BUG: ERC-20 withdrawal request and transaction destination mismatch"
+            );
+            assert_eq!(
+                transaction.amount,
+                Wei::ZERO,
+                "BUG: ERC-20 transfer transaction should not transfer any ETH"
+            );
+            assert!(
+                transaction.transaction_price().max_transaction_fee()
+                    <= withdrawal_request.max_transaction_fee,
+                "BUG: transaction fee should be covered by the ckETH burned for the ERC-20 withdrawal"
+            );
+            self.erc20_withdrawal_requests
+                .retain(|r| r != &withdrawal_request);
+            self.maybe_reimburse_erc20
+                .insert(withdrawal_id, withdrawal_request);
+        }
         self.next_nonce = self
             .next_nonce
             .checked_increment()
             .expect("Transaction nonce overflow");
-        self.remove_withdrawal_request(&withdrawal_request);
         assert_eq!(
             self.created_tx
-                .try_insert(nonce, withdrawal_request.ledger_burn_index, transaction),
+                .try_insert(nonce, withdrawal_id, transaction),
             Ok(())
         );
-        self.maybe_reimburse
-            .insert(withdrawal_id, withdrawal_request);
     }
 
     pub fn record_signed_transaction(
@@ -308,6 +488,32 @@ impl EthTransactions {
                 let new_tx_price = last_tx_price
                     .increase_by_10_percent()
                     .max(current_transaction_price.clone());
+                if let Some(erc20_request) = self.maybe_reimburse_erc20.get(burn_index) {
+                    // The transaction fee of an ERC-20 withdrawal is paid with the ckETH burned
+                    // for that purpose and cannot be deducted from the transferred ERC-20 tokens.
+                    let new_max_transaction_fee = new_tx_price.max_transaction_fee();
+                    if new_max_transaction_fee > erc20_request.max_transaction_fee {
+                        transactions_to_resubmit.push(Err(
+                            ResubmitTransactionError::InsufficientTransactionFee {
+                                ledger_burn_index: *burn_index,
+                                transaction_nonce: *nonce,
+                                allowed_max_transaction_fee: erc20_request.max_transaction_fee,
+                                max_transaction_fee: new_max_transaction_fee,
+                            },
+                        ));
+                        return transactions_to_resubmit;
+                    }
+                    transactions_to_resubmit.push(Ok((
+                        *burn_index,
+                        Eip1559TransactionRequest {
+                            max_priority_fee_per_gas: new_tx_price.max_priority_fee_per_gas,
+                            max_fee_per_gas: new_tx_price.max_fee_per_gas,
+                            gas_limit: new_tx_price.gas_limit,
+                            ..last_tx
+                        },
+                    )));
+                    continue;
+                }
                 let new_amount = match last_tx.amount.checked_sub(
                     new_tx_price
                         .max_transaction_fee()
@@ -379,10 +585,29 @@ impl EthTransactions {
         transactions
     }
 
+    /// Returns the amount to reimburse if the given receipt is for a failed transaction
+    /// of an ETH withdrawal, i.e., the withdrawal amount minus the effective transaction fee.
+    pub fn reimbursed_amount_if_failed(
+        &self,
+        ledger_burn_index: &LedgerBurnIndex,
+        receipt: &TransactionReceipt,
+    ) -> Option<Wei> {
+        if receipt.status != TransactionStatus::Failure {
+            return None;
+        }
+        self.maybe_reimburse.get(ledger_burn_index).map(|request| {
+            request
+                .withdrawal_amount
+                .checked_sub(receipt.effective_transaction_fee())
+                .expect("BUG: withdrawal amount MUST always cover the effective transaction fee")
+        })
+    }
+
     pub fn record_finalized_transaction(
         &mut self,
         ledger_burn_index: LedgerBurnIndex,
         receipt: TransactionReceipt,
+        reimbursed_amount: Option<Wei>,
     ) {
         let sent_tx = self
             .sent_tx
@@ -408,23 +633,58 @@ impl EthTransactions {
             Ok(())
         );
 
+        if let Some(maybe_reimburse) = self.maybe_reimburse_erc20.remove(&ledger_burn_index) {
+            if receipt.status == TransactionStatus::Failure {
+                self.erc20_reimbursement_requests.insert(
+                    ledger_burn_index,
+                    Erc20ReimbursementRequest {
+                        withdrawal_id: ledger_burn_index,
+                        reimbursed_amount: maybe_reimburse.withdrawal_amount,
+                        ckerc20_ledger_id: maybe_reimburse.ckerc20_ledger_id,
+                        to: maybe_reimburse.from,
+                        to_subaccount: maybe_reimburse.from_subaccount,
+                        transaction_hash: receipt.transaction_hash,
+                    },
+                );
+            }
+            return;
+        }
         let maybe_reimburse = self.maybe_reimburse.remove(&ledger_burn_index).expect(
             "failed to remove entry from maybe_reimburse map with block index: {ledger_burn_index}",
         );
         if receipt.status == TransactionStatus::Failure {
+            // Events recorded before the effective transaction fee was deducted
+            // from the reimbursed amount do not carry it.
+            let reimbursed_amount = reimbursed_amount.unwrap_or(*finalized_tx.transaction_amount());
             self.reimbursement_requests.insert(
                 ledger_burn_index,
                 ReimbursementRequest {
                     withdrawal_id: ledger_burn_index,
                     to: maybe_reimburse.from,
                     to_subaccount: maybe_reimburse.from_subaccount,
-                    reimbursed_amount: *finalized_tx.transaction_amount(),
+                    reimbursed_amount,
                     transaction_hash: Some(receipt.transaction_hash),
                 },
             );
         }
     }
 
+    /// Records a request to reimburse ckETH that was burned for a withdrawal
+    /// that will never result in an Ethereum transaction.
+    pub fn record_reimbursement_request(&mut self, request: ReimbursementRequest) {
+        let withdrawal_id = request.withdrawal_id;
+        assert!(
+            !self.contains_withdrawal_id(&withdrawal_id)
+                && !self.reimbursed.contains_key(&withdrawal_id),
+            "BUG: reimbursement request for an already processed withdrawal {withdrawal_id}"
+        );
+        assert_eq!(
+            self.reimbursement_requests.insert(withdrawal_id, request),
+            None,
+            "BUG: duplicate reimbursement request for withdrawal {withdrawal_id}"
+        );
+    }
+
     pub fn record_finalized_reimbursement(
         &mut self,
         withdrawal_id: LedgerBurnIndex,
@@ -448,11 +708,39 @@ impl EthTransactions {
         );
     }
 
+    pub fn record_finalized_erc20_reimbursement(
+        &mut self,
+        withdrawal_id: LedgerBurnIndex,
+        reimbursed_in_block: LedgerMintIndex,
+    ) {
+        let reimbursement_request = self
+            .erc20_reimbursement_requests
+            .remove(&withdrawal_id)
+            .expect("failed to remove ckERC20 reimbursement request");
+        assert_eq!(
+            self.erc20_reimbursed.insert(
+                withdrawal_id,
+                Erc20Reimbursed {
+                    withdrawal_id,
+                    reimbursed_in_block,
+                    reimbursed_amount: reimbursement_request.reimbursed_amount,
+                    ckerc20_ledger_id: reimbursement_request.ckerc20_ledger_id,
+                    transaction_hash: reimbursement_request.transaction_hash,
+                },
+            ),
+            None
+        );
+    }
+
     pub fn transaction_status(&self, burn_index: &LedgerBurnIndex) -> RetrieveEthStatus {
         if self
             .withdrawal_requests
             .iter()
             .any(|r| &r.ledger_burn_index == burn_index)
+            || self
+                .erc20_withdrawal_requests
+                .iter()
+                .any(|r| &r.cketh_ledger_burn_index == burn_index)
         {
             return RetrieveEthStatus::Pending;
         }
@@ -473,6 +761,13 @@ impl EthTransactions {
                     reimbursed_amount: reimbursed.reimbursed_amount.into(),
                 });
             }
+            if let Some(reimbursed) = self.erc20_reimbursed.get(burn_index) {
+                return RetrieveEthStatus::TxFinalized(TxFinalizedStatus::Reimbursed {
+                    reimbursed_in_block: reimbursed.reimbursed_in_block.get().into(),
+                    transaction_hash: tx.transaction_hash().to_string(),
+                    reimbursed_amount: reimbursed.reimbursed_amount.into(),
+                });
+            }
             if tx.transaction_status() == &TransactionStatus::Failure {
                 return RetrieveEthStatus::TxFinalized(TxFinalizedStatus::PendingReimbursement(
                     EthTransaction {
@@ -493,6 +788,23 @@ impl EthTransactions {
         &self,
         requested_batch_size: usize,
     ) -> Vec<EthWithdrawalRequest> {
+        self.withdrawal_requests_iter()
+            .take(self.actual_batch_size(requested_batch_size))
+            .cloned()
+            .collect()
+    }
+
+    pub fn erc20_withdrawal_requests_batch(
+        &self,
+        requested_batch_size: usize,
+    ) -> Vec<Erc20WithdrawalRequest> {
+        self.erc20_withdrawal_requests_iter()
+            .take(self.actual_batch_size(requested_batch_size))
+            .cloned()
+            .collect()
+    }
+
+    fn actual_batch_size(&self, requested_batch_size: usize) -> usize {
         // The number of pending transaction nonces is counted and not the number of pending transactions
         // because a nonce may be associated with several distinct transactions (due to re-submission and dynamic fees).
         // However, once a nonce is chosen for a withdrawal request, it's in our interest that the corresponding transaction be finalized asap.
@@ -500,15 +812,11 @@ impl EthTransactions {
         const MAX_NUM_PENDING_TRANSACTION_NONCES: usize = 1000;
         let unique_pending_transaction_nonces: BTreeSet<_> =
             self.created_tx.keys().chain(self.sent_tx.keys()).collect();
-        let actual_batch_size = min(
+        min(
             MAX_NUM_PENDING_TRANSACTION_NONCES
                 .saturating_sub(unique_pending_transaction_nonces.len()),
             requested_batch_size,
-        );
-        self.withdrawal_requests_iter()
-            .take(actual_batch_size)
-            .cloned()
-            .collect()
+        )
     }
 
     pub fn withdrawal_requests_iter(&self) -> impl Iterator<Item = &EthWithdrawalRequest> {
@@ -519,6 +827,14 @@ impl EthTransactions {
         self.withdrawal_requests.len()
     }
 
+    pub fn erc20_withdrawal_requests_iter(&self) -> impl Iterator<Item = &Erc20WithdrawalRequest> {
+        self.erc20_withdrawal_requests.iter()
+    }
+
+    pub fn erc20_withdrawal_requests_len(&self) -> usize {
+        self.erc20_withdrawal_requests.len()
+    }
+
     pub fn transactions_to_sign_iter(
         &self,
     ) -> impl Iterator<
@@ -590,6 +906,7 @@ impl EthTransactions {
 
     pub fn has_pending_requests(&self) -> bool {
         !self.withdrawal_requests.is_empty()
+            || !self.erc20_withdrawal_requests.is_empty()
             || !self.created_tx.is_empty()
             || !self.sent_tx.is_empty()
     }
@@ -641,24 +958,40 @@ impl EthTransactions {
             sorted_requests(&self.withdrawal_requests),
             sorted_requests(&other.withdrawal_requests)
         );
+        ensure_eq!(
+            self.erc20_withdrawal_requests,
+            other.erc20_withdrawal_requests
+        );
         ensure_eq!(self.created_tx, other.created_tx);
         ensure_eq!(self.sent_tx, other.sent_tx);
         ensure_eq!(self.finalized_tx, other.finalized_tx);
         ensure_eq!(self.next_nonce, other.next_nonce);
 
         ensure_eq!(self.maybe_reimburse, other.maybe_reimburse);
+        ensure_eq!(self.maybe_reimburse_erc20, other.maybe_reimburse_erc20);
         ensure_eq!(self.reimbursement_requests, other.reimbursement_requests);
         ensure_eq!(self.reimbursed, other.reimbursed);
+        ensure_eq!(
+            self.erc20_reimbursement_requests,
+            other.erc20_reimbursement_requests
+        );
+        ensure_eq!(self.erc20_reimbursed, other.erc20_reimbursed);
 
         Ok(())
     }
 
     pub fn oldest_incomplete_withdrawal_timestamp(&self) -> Option<u64> {
-        self.withdrawal_requests
+        let eth_requests = self
+            .withdrawal_requests
             .iter()
             .chain(self.maybe_reimburse.values())
-            .flat_map(|req| req.created_at.into_iter())
-            .min()
+            .flat_map(|req| req.created_at.into_iter());
+        let erc20_requests = self
+            .erc20_withdrawal_requests
+            .iter()
+            .chain(self.maybe_reimburse_erc20.values())
+            .map(|req| req.created_at);
+        eth_requests.chain(erc20_requests).min()
     }
 }
 
@@ -701,6 +1034,47 @@ pub fn create_transaction(
     })
 }
 
+/// Creates an EIP-1559 transaction calling `transfer` on the ERC-20 smart contract
+/// for the given ERC-20 withdrawal request.
+/// The transaction fees are paid with the ckETH burned by the user when the request was made,
+/// meaning that the beneficiary receives exactly the withdrawn amount of ERC-20 tokens.
+///
+/// # Errors
+/// * `CreateTransactionError::InsufficientTransactionFee` if the burned ckETH does not cover the transaction fee.
+pub fn create_erc20_transaction(
+    withdrawal_request: &Erc20WithdrawalRequest,
+    nonce: TransactionNonce,
+    transaction_price: TransactionPrice,
+    ethereum_network: EthereumNetwork,
+) -> Result<Eip1559TransactionRequest, CreateTransactionError> {
+    let transaction_price = TransactionPrice {
+        gas_limit: CKERC20_WITHDRAWAL_TRANSACTION_GAS_LIMIT,
+        ..transaction_price
+    };
+    let actual_max_transaction_fee = transaction_price.max_transaction_fee();
+    if actual_max_transaction_fee > withdrawal_request.max_transaction_fee {
+        return Err(CreateTransactionError::InsufficientTransactionFee {
+            ledger_burn_index: withdrawal_request.cketh_ledger_burn_index,
+            allowed_max_transaction_fee: withdrawal_request.max_transaction_fee,
+            actual_max_transaction_fee,
+        });
+    }
+    Ok(Eip1559TransactionRequest {
+        chain_id: ethereum_network.chain_id(),
+        nonce,
+        max_priority_fee_per_gas: transaction_price.max_priority_fee_per_gas,
+        max_fee_per_gas: transaction_price.max_fee_per_gas,
+        gas_limit: transaction_price.gas_limit,
+        destination: withdrawal_request.erc20_contract_address,
+        amount: Wei::ZERO,
+        data: erc20_transfer_data(
+            &withdrawal_request.destination,
+            withdrawal_request.withdrawal_amount,
+        ),
+        access_list: Default::default(),
+    })
+}
+
 /// Returns true if the two transactions are equal ignoring the transaction fee and amount.
 /// The following fields are ignored:
 /// * `max_fee_per_gas`
@@ -1,9 +1,11 @@
 use crate::eth_rpc::Hash;
 use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
 use crate::lifecycle::EthereumNetwork;
-use crate::numeric::{BlockNumber, GasAmount, LedgerBurnIndex, TransactionNonce, Wei, WeiPerGas};
+use crate::numeric::{
+    BlockNumber, Erc20Value, GasAmount, LedgerBurnIndex, TransactionNonce, Wei, WeiPerGas,
+};
 use crate::state::transactions::{
-    create_transaction, EthTransactions, EthWithdrawalRequest, Subaccount,
+    create_transaction, Erc20WithdrawalRequest, EthTransactions, EthWithdrawalRequest, Subaccount,
 };
 use crate::tx::{
     AccessList, Eip1559Signature, Eip1559TransactionRequest, SignedEip1559TransactionRequest,
@@ -74,6 +76,7 @@ mod eth_transactions {
             transactions.record_finalized_transaction(
                 index,
                 transaction_receipt(&signed_tx, TransactionStatus::Success),
+                None,
             );
             expect_panic_with_message(
                 || transactions.record_withdrawal_request(withdrawal_request.clone()),
@@ -1178,6 +1181,7 @@ mod eth_transactions {
                     transactions.record_finalized_transaction(
                         LedgerBurnIndex::new(16),
                         transaction_receipt(&signed_tx, TransactionStatus::Success),
+                        None,
                     )
                 },
                 "missing sent transaction",
@@ -1194,8 +1198,11 @@ mod eth_transactions {
 
             expect_panic_with_message(
                 || {
-                    transactions
-                        .record_finalized_transaction(ledger_burn_index, receipt_with_wrong_hash)
+                    transactions.record_finalized_transaction(
+                        ledger_burn_index,
+                        receipt_with_wrong_hash,
+                        None,
+                    )
                 },
                 "no transaction matching receipt",
             );
@@ -1236,7 +1243,7 @@ mod eth_transactions {
             assert!(!transactions.maybe_reimburse.is_empty());
 
             let receipt = transaction_receipt(&signed_tx, TransactionStatus::Success);
-            transactions.record_finalized_transaction(ledger_burn_index, receipt.clone());
+            transactions.record_finalized_transaction(ledger_burn_index, receipt.clone(), None);
 
             assert!(transactions.maybe_reimburse.is_empty());
             assert!(transactions.reimbursement_requests.is_empty());
@@ -1274,7 +1281,13 @@ mod eth_transactions {
             );
 
             let receipt = transaction_receipt(&signed_tx, TransactionStatus::Failure);
-            transactions.record_finalized_transaction(ledger_burn_index, receipt.clone());
+            let reimbursed_amount =
+                transactions.reimbursed_amount_if_failed(&ledger_burn_index, &receipt);
+            transactions.record_finalized_transaction(
+                ledger_burn_index,
+                receipt.clone(),
+                reimbursed_amount,
+            );
 
             let finalized_transaction = transactions
                 .finalized_tx
@@ -1293,11 +1306,109 @@ mod eth_transactions {
                     withdrawal_id: ledger_burn_index,
                     to: candid::Principal::from_str(DEFAULT_PRINCIPAL,).unwrap(),
                     to_subaccount: Some(Subaccount(DEFAULT_SUBACCOUNT)),
-                    reimbursed_amount: *finalized_transaction.transaction_amount(),
+                    reimbursed_amount: Wei::new(DEFAULT_WITHDRAWAL_AMOUNT)
+                        .checked_sub(finalized_transaction.effective_transaction_fee())
+                        .unwrap(),
                 }
             );
         }
 
+        #[test]
+        fn should_reimburse_transaction_amount_when_reimbursed_amount_is_not_recorded() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let ledger_burn_index = LedgerBurnIndex::new(15);
+            let withdrawal_request =
+                create_and_record_withdrawal_request(&mut transactions, ledger_burn_index);
+            let created_tx = create_and_record_transaction(
+                &mut transactions,
+                withdrawal_request,
+                transaction_price(),
+            );
+            let signed_tx = create_and_record_signed_transaction(&mut transactions, created_tx);
+
+            let receipt = transaction_receipt(&signed_tx, TransactionStatus::Failure);
+            transactions.record_finalized_transaction(ledger_burn_index, receipt, None);
+
+            let finalized_transaction = transactions
+                .finalized_tx
+                .get_alt(&ledger_burn_index)
+                .expect("finalized tx not found");
+            assert_eq!(
+                transactions
+                    .reimbursement_requests
+                    .get(&ledger_burn_index)
+                    .map(|request| request.reimbursed_amount),
+                Some(*finalized_transaction.transaction_amount())
+            );
+        }
+
+        #[test]
+        fn should_record_finalized_erc20_transaction_and_reimburse_ckerc20() {
+            use crate::endpoints::{RetrieveEthStatus, TxFinalizedStatus};
+            use crate::lifecycle::EthereumNetwork;
+            use crate::numeric::LedgerMintIndex;
+            use crate::state::transactions::tests::erc20_withdrawal_request_with_index;
+            use crate::state::transactions::{
+                create_erc20_transaction, Erc20Reimbursed, Erc20ReimbursementRequest,
+            };
+
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let ledger_burn_index = LedgerBurnIndex::new(15);
+            let withdrawal_request = erc20_withdrawal_request_with_index(ledger_burn_index);
+            transactions.record_erc20_withdrawal_request(withdrawal_request.clone());
+            let created_tx = create_erc20_transaction(
+                &withdrawal_request,
+                transactions.next_transaction_nonce(),
+                transaction_price(),
+                EthereumNetwork::Sepolia,
+            )
+            .expect("failed to create ERC-20 transaction");
+            transactions.record_created_transaction(ledger_burn_index, created_tx.clone());
+            let signed_tx = create_and_record_signed_transaction(&mut transactions, created_tx);
+
+            let receipt = transaction_receipt(&signed_tx, TransactionStatus::Failure);
+            transactions.record_finalized_transaction(ledger_burn_index, receipt.clone(), None);
+
+            assert!(transactions.maybe_reimburse_erc20.is_empty());
+            assert!(transactions.reimbursement_requests.is_empty());
+            assert_eq!(
+                transactions
+                    .erc20_reimbursement_requests
+                    .get(&ledger_burn_index),
+                Some(&Erc20ReimbursementRequest {
+                    withdrawal_id: ledger_burn_index,
+                    reimbursed_amount: withdrawal_request.withdrawal_amount,
+                    ckerc20_ledger_id: withdrawal_request.ckerc20_ledger_id,
+                    to: withdrawal_request.from,
+                    to_subaccount: withdrawal_request.from_subaccount.clone(),
+                    transaction_hash: receipt.transaction_hash,
+                })
+            );
+
+            transactions
+                .record_finalized_erc20_reimbursement(ledger_burn_index, LedgerMintIndex::new(16));
+
+            assert!(transactions.erc20_reimbursement_requests.is_empty());
+            assert_eq!(
+                transactions.get_erc20_reimbursed_transactions(),
+                vec![Erc20Reimbursed {
+                    withdrawal_id: ledger_burn_index,
+                    reimbursed_in_block: LedgerMintIndex::new(16),
+                    reimbursed_amount: withdrawal_request.withdrawal_amount,
+                    ckerc20_ledger_id: withdrawal_request.ckerc20_ledger_id,
+                    transaction_hash: receipt.transaction_hash,
+                }]
+            );
+            assert_eq!(
+                transactions.transaction_status(&ledger_burn_index),
+                RetrieveEthStatus::TxFinalized(TxFinalizedStatus::Reimbursed {
+                    reimbursed_in_block: candid::Nat::from(16_u8),
+                    transaction_hash: signed_tx.hash().to_string(),
+                    reimbursed_amount: withdrawal_request.withdrawal_amount.into(),
+                })
+            );
+        }
+
         #[test]
         fn should_record_finalized_transaction() {
             let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
@@ -1312,7 +1423,7 @@ mod eth_transactions {
             let signed_tx = create_and_record_signed_transaction(&mut transactions, created_tx);
 
             let receipt = transaction_receipt(&signed_tx, TransactionStatus::Success);
-            transactions.record_finalized_transaction(ledger_burn_index, receipt.clone());
+            transactions.record_finalized_transaction(ledger_burn_index, receipt.clone(), None);
 
             assert_eq!(
                 transactions
@@ -1345,7 +1456,7 @@ mod eth_transactions {
             assert!(transactions.created_tx.contains_alt(&ledger_burn_index));
 
             let receipt = transaction_receipt(&signed_tx, TransactionStatus::Success);
-            transactions.record_finalized_transaction(ledger_burn_index, receipt.clone());
+            transactions.record_finalized_transaction(ledger_burn_index, receipt.clone(), None);
 
             assert_eq!(
                 transactions.finalized_tx,
@@ -1409,6 +1520,7 @@ mod eth_transactions {
             transactions.record_finalized_transaction(
                 ledger_burn_index,
                 transaction_receipt(&signed_tx, TransactionStatus::Success),
+                None,
             );
             assert_eq!(
                 transactions.transaction_status(&ledger_burn_index),
@@ -1436,9 +1548,13 @@ mod eth_transactions {
             };
             transactions.record_signed_transaction(signed_tx.clone());
 
+            let receipt = transaction_receipt(&signed_tx, TransactionStatus::Failure);
+            let reimbursed_amount =
+                transactions.reimbursed_amount_if_failed(&ledger_burn_index, &receipt);
             transactions.record_finalized_transaction(
                 ledger_burn_index,
-                transaction_receipt(&signed_tx, TransactionStatus::Failure),
+                receipt,
+                reimbursed_amount,
             );
             assert_eq!(
                 transactions.transaction_status(&ledger_burn_index),
@@ -1559,6 +1675,7 @@ mod oldest_incomplete_withdrawal_timestamp {
         transactions.record_finalized_transaction(
             index,
             transaction_receipt(&signed_tx, TransactionStatus::Success),
+            None,
         );
 
         assert_eq!(transactions.oldest_incomplete_withdrawal_timestamp(), None);
@@ -1888,6 +2005,25 @@ fn withdrawal_request_with_index(ledger_burn_index: LedgerBurnIndex) -> EthWithd
     }
 }
 
+fn erc20_withdrawal_request_with_index(
+    cketh_ledger_burn_index: LedgerBurnIndex,
+) -> Erc20WithdrawalRequest {
+    use std::str::FromStr;
+    Erc20WithdrawalRequest {
+        max_transaction_fee: Wei::new(DEFAULT_WITHDRAWAL_AMOUNT),
+        withdrawal_amount: Erc20Value::new(1_000_000),
+        destination: Address::from_str(DEFAULT_RECIPIENT_ADDRESS).unwrap(),
+        cketh_ledger_burn_index,
+        erc20_contract_address: Address::from_str("0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238")
+            .unwrap(),
+        ckerc20_ledger_id: candid::Principal::from_str("mxzaz-hqaaa-aaaar-qaada-cai").unwrap(),
+        ckerc20_ledger_burn_index: LedgerBurnIndex::new(7),
+        from: candid::Principal::from_str(DEFAULT_PRINCIPAL).unwrap(),
+        from_subaccount: Some(Subaccount(DEFAULT_SUBACCOUNT)),
+        created_at: DEFAULT_CREATED_AT,
+    }
+}
+
 fn signed_transaction_with_nonce(nonce: TransactionNonce) -> SignedEip1559TransactionRequest {
     SignedEip1559TransactionRequest::from((
         eip_1559_transaction_request_with_nonce(nonce),
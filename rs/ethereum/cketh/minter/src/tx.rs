@@ -3,9 +3,10 @@ mod tests;
 
 use crate::eth_rpc::{FeeHistory, Hash};
 use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
-use crate::numeric::{BlockNumber, GasAmount, TransactionNonce, Wei, WeiPerGas};
+use crate::numeric::{BlockNumber, Erc20Value, GasAmount, TransactionNonce, Wei, WeiPerGas};
 use crate::state::{lazy_call_ecdsa_public_key, read_state};
 use ethnum::u256;
+use hex_literal::hex;
 use ic_crypto_ecdsa_secp256k1::RecoveryId;
 use ic_ethereum_types::Address;
 use ic_ic00_types::DerivationPath;
@@ -14,6 +15,24 @@ use rlp::RlpStream;
 
 const EIP1559_TX_ID: u8 = 2;
 
+/// Gas limit attached to the transaction calling `transfer` on an ERC-20 smart contract.
+/// Transferring ERC-20 tokens is more expensive than transferring ETH
+/// (21_000 gas) and depends on the token implementation.
+pub const CKERC20_WITHDRAWAL_TRANSACTION_GAS_LIMIT: GasAmount = GasAmount::new(65_000);
+
+// First 4 bytes of Keccak256("transfer(address,uint256)")
+const ERC20_TRANSFER_FUNCTION_SELECTOR: [u8; 4] = hex!("a9059cbb");
+
+/// ABI-encodes a call to `transfer(address to, uint256 value)` on an ERC-20 smart contract.
+pub fn erc20_transfer_data(to: &Address, value: Erc20Value) -> Vec<u8> {
+    let mut data = Vec::with_capacity(4 + 32 + 32);
+    data.extend(ERC20_TRANSFER_FUNCTION_SELECTOR);
+    data.extend([0_u8; 12]);
+    data.extend(to.as_ref());
+    data.extend(value.to_be_bytes());
+    data
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Encode, Decode)]
 #[cbor(transparent)]
 pub struct AccessList(#[n(0)] pub Vec<AccessListItem>);
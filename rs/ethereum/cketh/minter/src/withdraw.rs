@@ -11,7 +11,8 @@ use crate::logs::{DEBUG, INFO};
 use crate::numeric::{LedgerBurnIndex, LedgerMintIndex, TransactionCount};
 use crate::state::audit::{process_event, EventType};
 use crate::state::transactions::{
-    create_transaction, CreateTransactionError, Reimbursed, ReimbursementRequest,
+    create_erc20_transaction, create_transaction, CreateTransactionError, Erc20Reimbursed,
+    Erc20ReimbursementRequest, Reimbursed, ReimbursementRequest,
 };
 use crate::state::{mutate_state, read_state, State, TaskType};
 use crate::tx::{estimate_transaction_price, TransactionPrice};
@@ -36,7 +37,11 @@ pub async fn process_reimbursement() {
             return;
         }
     };
+    reimburse_cketh_withdrawals().await;
+    reimburse_ckerc20_withdrawals().await;
+}
 
+async fn reimburse_cketh_withdrawals() {
     let reimbursement_requests: Vec<ReimbursementRequest> =
         read_state(|s| s.eth_transactions.get_reimbursement_requests());
     if reimbursement_requests.is_empty() {
@@ -104,6 +109,74 @@ pub async fn process_reimbursement() {
     }
 }
 
+async fn reimburse_ckerc20_withdrawals() {
+    let reimbursement_requests: Vec<Erc20ReimbursementRequest> =
+        read_state(|s| s.eth_transactions.get_erc20_reimbursement_requests());
+    let mut error_count = 0;
+
+    for reimbursement_request in reimbursement_requests {
+        let ledger_canister_id = reimbursement_request.ckerc20_ledger_id;
+        let client = ICRC1Client {
+            runtime: CdkRuntime,
+            ledger_canister_id,
+        };
+        let args = TransferArg {
+            from_subaccount: None,
+            to: Account {
+                owner: reimbursement_request.to,
+                subaccount: reimbursement_request
+                    .to_subaccount
+                    .as_ref()
+                    .map(|subaccount| subaccount.0),
+            },
+            fee: None,
+            created_at_time: None,
+            memo: Some(reimbursement_request.clone().into()),
+            amount: Nat::from(reimbursement_request.reimbursed_amount),
+        };
+        let block_index = match client.transfer(args).await {
+            Ok(Ok(block_index)) => block_index
+                .0
+                .to_u64()
+                .expect("block index should fit into u64"),
+            Ok(Err(err)) => {
+                log!(
+                    INFO,
+                    "[process_reimbursement] Failed to mint ckERC20 on ledger {ledger_canister_id}: {err}"
+                );
+                error_count += 1;
+                continue;
+            }
+            Err(err) => {
+                log!(
+                    INFO,
+                    "[process_reimbursement] Failed to send a message to the ledger ({ledger_canister_id}): {err:?}"
+                );
+                error_count += 1;
+                continue;
+            }
+        };
+        mutate_state(|s| {
+            process_event(
+                s,
+                EventType::ReimbursedErc20Withdrawal(Erc20Reimbursed {
+                    withdrawal_id: reimbursement_request.withdrawal_id,
+                    reimbursed_in_block: LedgerMintIndex::new(block_index),
+                    reimbursed_amount: reimbursement_request.reimbursed_amount,
+                    ckerc20_ledger_id: ledger_canister_id,
+                    transaction_hash: reimbursement_request.transaction_hash,
+                }),
+            )
+        });
+    }
+    if error_count > 0 {
+        log!(
+            INFO,
+            "[process_reimbursement] Failed to reimburse {error_count} ckERC20 withdrawals, retrying later."
+        );
+    }
+}
+
 pub async fn process_retrieve_eth_requests() {
     let _guard = match TimerGuard::new(TaskType::RetrieveEth) {
         Ok(guard) => guard,
@@ -148,7 +221,8 @@ pub async fn process_retrieve_eth_requests() {
     );
     let latest_transaction_count = latest_transaction_count().await;
     resubmit_transactions_batch(latest_transaction_count, &transaction_price).await;
-    create_transactions_batch(transaction_price);
+    create_transactions_batch(transaction_price.clone());
+    create_erc20_transactions_batch(transaction_price);
     sign_transactions_batch().await;
     send_transactions_batch(latest_transaction_count).await;
     finalize_transactions_batch().await;
@@ -256,6 +330,52 @@ fn create_transactions_batch(transaction_price: TransactionPrice) {
                 );
                 mutate_state(|s| s.eth_transactions.reschedule_withdrawal_request(request));
             }
+            Err(e @ CreateTransactionError::InsufficientTransactionFee { .. }) => {
+                panic!("BUG: unexpected error for an ETH withdrawal request: {e:?}")
+            }
+        };
+    }
+}
+
+fn create_erc20_transactions_batch(transaction_price: TransactionPrice) {
+    for request in read_state(|s| {
+        s.eth_transactions
+            .erc20_withdrawal_requests_batch(WITHDRAWAL_REQUESTS_BATCH_SIZE)
+    }) {
+        log!(
+            DEBUG,
+            "[create_erc20_transactions_batch]: processing {request:?}",
+        );
+        let ethereum_network = read_state(State::ethereum_network);
+        let nonce = read_state(|s| s.eth_transactions.next_transaction_nonce());
+        match create_erc20_transaction(&request, nonce, transaction_price.clone(), ethereum_network)
+        {
+            Ok(transaction) => {
+                log!(
+                    DEBUG,
+                    "[create_erc20_transactions_batch]: created transaction {transaction:?}",
+                );
+
+                mutate_state(|s| {
+                    process_event(
+                        s,
+                        EventType::CreatedTransaction {
+                            withdrawal_id: request.cketh_ledger_burn_index,
+                            transaction,
+                        },
+                    );
+                });
+            }
+            Err(e) => {
+                log!(
+                    INFO,
+                    "[create_erc20_transactions_batch]: Failed to create transaction for ERC-20 withdrawal request {request:?}: {e:?}. Request moved back to end of queue."
+                );
+                mutate_state(|s| {
+                    s.eth_transactions
+                        .reschedule_erc20_withdrawal_request(request)
+                });
+            }
         };
     }
 }
@@ -396,11 +516,15 @@ async fn finalize_transactions_batch() {
             );
             for (withdrawal_id, transaction_receipt) in receipts {
                 mutate_state(|s| {
+                    let reimbursed_amount = s
+                        .eth_transactions
+                        .reimbursed_amount_if_failed(&withdrawal_id, &transaction_receipt);
                     process_event(
                         s,
                         EventType::FinalizedTransaction {
                             withdrawal_id,
                             transaction_receipt,
+                            reimbursed_amount,
                         },
                     );
                 });
@@ -165,6 +165,7 @@ fn should_deposit_and_withdraw() {
                 transaction_hash:
                 "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string(),
             },
+            reimbursed_amount: None,
         },
     ]);
 }
@@ -412,7 +413,6 @@ fn should_reimburse() {
         .expect_withdrawal_request_accepted();
 
     let withdrawal_id = cketh.withdrawal_id().clone();
-    let (tx, _sig) = default_signed_eip_1559_transaction();
     let cketh = cketh
         .wait_and_validate_withdrawal(
             ProcessWithdrawalParams::default().with_mock_eth_get_transaction_receipt(move |mock| {
@@ -448,13 +448,9 @@ fn should_reimburse() {
     cketh.env.advance_time(PROCESS_REIMBURSEMENT);
     cketh.env.tick();
 
-    let cost_of_failed_transaction = withdrawal_amount
-        .0
-        .to_u128()
-        .unwrap()
-        .checked_sub(tx.value.unwrap().as_u128())
-        .unwrap();
-    assert_eq!(cost_of_failed_transaction, 693_077_873_418_000);
+    let effective_gas_price = 4277923390_u128;
+    let cost_of_failed_transaction = effective_gas_price * 21_000;
+    assert_eq!(cost_of_failed_transaction, 89_836_391_190_000);
 
     let balance_after_withdrawal = cketh.balance_of(caller);
     assert_eq!(
@@ -462,7 +458,7 @@ fn should_reimburse() {
         balance_before_withdrawal.clone() - cost_of_failed_transaction
     );
 
-    let reimbursed_amount = Nat::from(tx.value.unwrap().as_u128());
+    let reimbursed_amount = withdrawal_amount.clone() - cost_of_failed_transaction;
     let reimbursed_in_block = withdrawal_id.clone() + Nat::from(1_u8);
     let failed_tx_hash =
         "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string();
@@ -529,7 +525,9 @@ fn should_reimburse() {
                 status: TransactionStatus::Failure,
                 transaction_hash:
                 "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string(),
-            }},
+            },
+            reimbursed_amount: Some(reimbursed_amount.clone()),
+        },
         EventPayload::ReimbursedEthWithdrawal {
             transaction_hash: Some("0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string()),
             reimbursed_amount,
@@ -711,6 +709,7 @@ fn should_resubmit_new_transaction_when_price_increased() {
                 status: TransactionStatus::Success,
                 transaction_hash: format!("{:?}", resubmitted_tx_hash),
             },
+            reimbursed_amount: None,
         },
     ]);
 }
@@ -1121,6 +1120,7 @@ fn install_minter(env: &StateMachine, ledger_id: CanisterId, minter_id: Canister
         ethereum_contract_address: Some(HELPER_SMART_CONTRACT_ADDRESS.to_string()),
         minimum_withdrawal_amount: CKETH_TRANSFER_FEE.into(),
         last_scraped_block_number: LAST_SCRAPED_BLOCK_NUMBER_AT_INSTALL.into(),
+        rpc_config: None,
     };
     let minter_arg = MinterArg::InitArg(args);
     env.install_existing_canister(minter_id, minter_wasm(), Encode!(&minter_arg).unwrap())
@@ -15,8 +15,23 @@ pub enum OrchestratorArg {
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct InitArg {}
 
-#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
-pub struct UpgradeArg {}
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UpgradeArg {
+    /// Upgrade all managed ledgers to the embedded ledger wasm with this hash.
+    pub ledger_compressed_wasm_hash: Option<String>,
+    /// Upgrade all managed indexes to the embedded index wasm with this hash.
+    pub index_compressed_wasm_hash: Option<String>,
+    /// Upgrade all managed archives to the embedded archive wasm with this hash.
+    pub archive_compressed_wasm_hash: Option<String>,
+    /// Change how the cycles of the managed canisters are topped up.
+    pub cycles_management: Option<UpdateCyclesManagement>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UpdateCyclesManagement {
+    pub cycles_top_up_threshold: Option<Nat>,
+    pub cycles_top_up_increment: Option<Nat>,
+}
 
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct AddErc20Arg {
@@ -2,6 +2,7 @@
 mod tests;
 
 pub use askama::Template;
+use candid::Principal;
 use ic_ledger_suite_orchestrator::scheduler::Erc20Token;
 use ic_ledger_suite_orchestrator::state::{Canisters, IndexCanister, LedgerCanister, State};
 use std::collections::BTreeMap;
@@ -10,6 +11,9 @@ use std::collections::BTreeMap;
 #[template(path = "dashboard.html")]
 pub struct DashboardTemplate {
     managed_canisters: BTreeMap<Erc20Token, CanistersDashboardData>,
+    cycles_top_up_threshold: u128,
+    cycles_top_up_increment: u128,
+    cycles_top_ups: BTreeMap<Principal, u128>,
 }
 
 #[derive(Default, Debug, PartialEq, Clone)]
@@ -43,7 +47,17 @@ impl CanisterDashboardData {
         if let Some(index) = &canisters.index {
             result.push(Self::from(index));
         }
-        //TODO add archive canisters
+        for archive in &canisters.archives {
+            result.push(Self {
+                canister_type: "Archive".to_string(),
+                canister_id: archive.to_string(),
+                installed_from: canisters
+                    .archive_wasm_hashes
+                    .get(archive)
+                    .map(|hash| hash.to_string())
+                    .unwrap_or("installed by ledger".to_string()),
+            });
+        }
         result
     }
 }
@@ -89,6 +103,9 @@ impl DashboardTemplate {
                     )
                 })
                 .collect(),
+            cycles_top_up_threshold: state.cycles_management().cycles_top_up_threshold,
+            cycles_top_up_increment: state.cycles_management().cycles_top_up_increment,
+            cycles_top_ups: state.cycles_top_ups().clone(),
         }
     }
 }
@@ -143,6 +143,56 @@ fn should_display_etherscan_links_according_to_chain_id() {
     DashboardAssert::assert_that_dashboard_from_state(&state).has_no_elements_matching("a");
 }
 
+#[test]
+fn should_display_archives() {
+    const USDC_LEDGER_ID: &str = "apia6-jaaaa-aaaar-qabma-cai";
+    const FIRST_ARCHIVE_ID: &str = "xob7s-iqaaa-aaaar-qacra-cai";
+    const SECOND_ARCHIVE_ID: &str = "n5wcd-faaaa-aaaar-qaaea-cai";
+    const ARCHIVE_WASM_HASH: &str =
+        "3148f7a9f1b0ee39262c8abe3b08813480cf78551eee5a60ab1cf38433b5d9b0";
+
+    let mut state = initial_state();
+    state.record_new_erc20_token(usdc(), usdc_metadata());
+    state.record_created_canister::<Ledger>(&usdc(), Principal::from_str(USDC_LEDGER_ID).unwrap());
+    state.record_archives(
+        &usdc(),
+        vec![
+            Principal::from_str(FIRST_ARCHIVE_ID).unwrap(),
+            Principal::from_str(SECOND_ARCHIVE_ID).unwrap(),
+        ],
+    );
+    DashboardAssert::assert_that_dashboard_from_state(&state)
+        .has_erc20("ckUSDC", 1, USDC_ADDRESS)
+        .has_ledger(USDC_LEDGER_ID, "not installed")
+        .has_archive(FIRST_ARCHIVE_ID, "installed by ledger")
+        .has_archive(SECOND_ARCHIVE_ID, "installed by ledger");
+
+    state.record_upgraded_archive(
+        &usdc(),
+        Principal::from_str(FIRST_ARCHIVE_ID).unwrap(),
+        WasmHash::from_str(ARCHIVE_WASM_HASH).unwrap(),
+    );
+    DashboardAssert::assert_that_dashboard_from_state(&state)
+        .has_erc20("ckUSDC", 1, USDC_ADDRESS)
+        .has_archive(FIRST_ARCHIVE_ID, ARCHIVE_WASM_HASH)
+        .has_archive(SECOND_ARCHIVE_ID, "installed by ledger");
+}
+
+#[test]
+fn should_display_cycles_management() {
+    const USDC_LEDGER_ID: &str = "apia6-jaaaa-aaaar-qabma-cai";
+
+    DashboardAssert::assert_that(initial_dashboard())
+        .has_cycles_management("500000000000", "500000000000")
+        .has_no_elements_matching("#cycles-top-ups");
+
+    let mut state = initial_state();
+    state.record_cycles_top_up(Principal::from_str(USDC_LEDGER_ID).unwrap(), 1_000);
+    state.record_cycles_top_up(Principal::from_str(USDC_LEDGER_ID).unwrap(), 2_000);
+    DashboardAssert::assert_that_dashboard_from_state(&state)
+        .has_cycles_top_up(USDC_LEDGER_ID, "3000");
+}
+
 fn initial_dashboard() -> DashboardTemplate {
     DashboardTemplate::from_state(&initial_state())
 }
@@ -203,6 +253,40 @@ mod assertions {
             }
         }
 
+        pub fn has_cycles_management(self, threshold: &str, increment: &str) -> Self {
+            self.has_string_value(
+                "#cycles-top-up-threshold",
+                threshold,
+                "wrong top-up threshold",
+            );
+            self.has_string_value(
+                "#cycles-top-up-increment",
+                increment,
+                "wrong top-up increment",
+            );
+            self
+        }
+
+        pub fn has_cycles_top_up(self, canister_id: &str, expected_cycles: &str) -> Self {
+            let row_selector = Selector::parse("#cycles-top-ups + table > tbody > tr").unwrap();
+            let cell_selector = Selector::parse("td").unwrap();
+            for row in self.actual.select(&row_selector) {
+                let cells: Vec<_> = row
+                    .select(&cell_selector)
+                    .map(|c| c.text().collect::<String>())
+                    .collect();
+                if cells[0] == canister_id {
+                    assert_eq!(
+                        cells[1], expected_cycles,
+                        "Unexpected cycles top-up. Rendered html: {}",
+                        self.rendered_html
+                    );
+                    return self;
+                }
+            }
+            panic!("BUG: top-up of canister {} not found!", canister_id);
+        }
+
         fn has_string_value(&self, selector: &str, expected_value: &str, error_msg: &str) -> &Self {
             let selector = Selector::parse(selector).unwrap();
             let actual_value = only_one(&mut self.actual.select(&selector));
@@ -260,11 +344,32 @@ mod assertions {
             self.has_erc20_table_row_string_value("Index", expected_canister_id, expected_version)
         }
 
+        pub fn has_archive(self, expected_canister_id: &str, expected_version: &str) -> Self {
+            self.has_erc20_table_row_matching(
+                |cells| cells[1] == "Archive" && cells[0] == expected_canister_id,
+                expected_canister_id,
+                expected_version,
+            )
+        }
+
         fn has_erc20_table_row_string_value(
             self,
             canister_type: &str,
             expected_canister_id: &str,
             expected_version: &str,
+        ) -> Self {
+            self.has_erc20_table_row_matching(
+                |cells| cells[1] == canister_type,
+                expected_canister_id,
+                expected_version,
+            )
+        }
+
+        fn has_erc20_table_row_matching<F: Fn(&[String]) -> bool>(
+            self,
+            row_filter: F,
+            expected_canister_id: &str,
+            expected_version: &str,
         ) -> Self {
             let row_selector = Selector::parse(&format!(
                 "#managed-canisters-{}-{} + table > tbody > tr",
@@ -284,7 +389,7 @@ mod assertions {
                     cells,
                     self.assert.rendered_html
                 );
-                if cells[1] == canister_type {
+                if row_filter(&cells) {
                     assert_eq!(
                         cells[0], expected_canister_id,
                         "Unexpected canister ID. Rendered html: {}",
@@ -299,8 +404,8 @@ mod assertions {
                 }
             }
             panic!(
-                "BUG: row matching canister type {} not found!",
-                canister_type
+                "BUG: row matching canister {} not found!",
+                expected_canister_id
             );
         }
     }
@@ -2,12 +2,14 @@ use crate::candid::{AddErc20Arg, InitArg, UpgradeArg};
 use crate::guard::TimerGuard;
 use crate::logs::INFO;
 use crate::management::IcCanisterRuntime;
-use crate::scheduler::{InstallLedgerSuiteArgs, Task};
+use crate::scheduler::{InstallLedgerSuiteArgs, Task, UpgradeLedgerSuiteArgs};
 use crate::state::{init_state, mutate_state, read_state, State};
 use ic_canister_log::log;
 use std::time::Duration;
 
 const IC_CANISTER_RUNTIME: IcCanisterRuntime = IcCanisterRuntime {};
+const MAYBE_TOP_UP_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const DISCOVER_ARCHIVES_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 pub fn init(init_arg: InitArg) {
     log!(
@@ -19,12 +21,40 @@ pub fn init(init_arg: InitArg) {
     setup_timers()
 }
 
-pub fn post_upgrade(_upgrade_arg: Option<UpgradeArg>) {
-    //TODO: in case UpgradeArg is present, 1) refresh wasms binaries in stable memory and plan upgrade of managed canisters
+pub fn post_upgrade(upgrade_arg: Option<UpgradeArg>) {
+    mutate_state(State::refresh_embedded_wasms);
+    if let Some(arg) = upgrade_arg {
+        log!(
+            INFO,
+            "[post_upgrade]: upgrading orchestrator with arg: {:?}",
+            arg
+        );
+        if let Some(update) = arg.cycles_management.clone() {
+            mutate_state(|s| s.cycles_management_mut().apply(update)).unwrap_or_else(|e| {
+                ic_cdk::trap(&format!(
+                    "[post_upgrade]: ERROR: invalid cycles management: {}",
+                    e
+                ))
+            });
+        }
+        match read_state(|s| UpgradeLedgerSuiteArgs::validate_upgrade(s, &arg)) {
+            Ok(Some(args)) => {
+                mutate_state(|s| s.add_task(Task::UpgradeLedgerSuite(args)));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                ic_cdk::trap(&format!(
+                    "[post_upgrade]: ERROR: invalid arguments to upgrade ledger suites {:?}: {:?}",
+                    arg, e
+                ));
+            }
+        }
+    }
     setup_timers()
 }
 
 pub fn add_erc20(token: AddErc20Arg) {
+    mutate_state(State::refresh_embedded_wasms);
     match read_state(|s| InstallLedgerSuiteArgs::validate_add_erc20(s, token.clone())) {
         Ok(args) => {
             mutate_state(|s| s.add_task(Task::InstallLedgerSuite(args)));
@@ -42,6 +72,12 @@ pub fn add_erc20(token: AddErc20Arg) {
 
 pub fn setup_timers() {
     ic_cdk_timers::set_timer_interval(Duration::from_secs(1), || ic_cdk::spawn(execute_tasks()));
+    ic_cdk_timers::set_timer_interval(MAYBE_TOP_UP_INTERVAL, || {
+        mutate_state(|s| s.add_task_once(Task::MaybeTopUp))
+    });
+    ic_cdk_timers::set_timer_interval(DISCOVER_ARCHIVES_INTERVAL, || {
+        mutate_state(|s| s.add_task_once(Task::DiscoverArchives))
+    });
 }
 
 async fn execute_tasks() {
@@ -107,6 +107,23 @@ pub trait CanisterRuntime {
         wasm_module: Wasm,
         arg: Vec<u8>,
     ) -> Result<(), CallError>;
+
+    /// Upgrades the given canister to the given wasm module with the upgrade arguments.
+    async fn upgrade_canister(
+        &self,
+        canister_id: Principal,
+        wasm_module: Wasm,
+        arg: Vec<u8>,
+    ) -> Result<(), CallError>;
+
+    /// Returns the cycles balance of the given canister, which must be controlled by the current canister.
+    async fn canister_cycles(&self, canister_id: Principal) -> Result<u128, CallError>;
+
+    /// Sends the given amount of cycles to the given canister.
+    async fn send_cycles(&self, canister_id: Principal, cycles: u128) -> Result<(), CallError>;
+
+    /// Returns the archives spawned by the given ICRC-1 ledger.
+    async fn ledger_archives(&self, ledger_id: Principal) -> Result<Vec<Principal>, CallError>;
 }
 
 pub struct IcCanisterRuntime {}
@@ -193,4 +210,82 @@ impl CanisterRuntime for IcCanisterRuntime {
 
         Ok(())
     }
+
+    async fn upgrade_canister(
+        &self,
+        canister_id: Principal,
+        wasm_module: Wasm,
+        arg: Vec<u8>,
+    ) -> Result<(), CallError> {
+        let install_code = InstallCodeArgs {
+            mode: CanisterInstallMode::Upgrade,
+            canister_id: PrincipalId::from(canister_id),
+            wasm_module: wasm_module.to_bytes(),
+            arg,
+            compute_allocation: None,
+            memory_allocation: None,
+            query_allocation: None,
+            sender_canister_version: None,
+        };
+
+        self.call("install_code", 0, &install_code).await?;
+
+        Ok(())
+    }
+
+    async fn canister_cycles(&self, canister_id: Principal) -> Result<u128, CallError> {
+        use num_traits::ToPrimitive;
+
+        let (status,) = ic_cdk::api::management_canister::main::canister_status(
+            ic_cdk::api::management_canister::main::CanisterIdRecord { canister_id },
+        )
+        .await
+        .map_err(|(code, msg)| CallError {
+            method: "canister_status".to_string(),
+            reason: Reason::from_reject(code, msg),
+        })?;
+
+        status.cycles.0.to_u128().ok_or_else(|| CallError {
+            method: "canister_status".to_string(),
+            reason: Reason::InternalError(format!(
+                "cycles balance {} does not fit in u128",
+                status.cycles
+            )),
+        })
+    }
+
+    async fn send_cycles(&self, canister_id: Principal, cycles: u128) -> Result<(), CallError> {
+        if ic_cdk::api::canister_balance128() < cycles {
+            return Err(CallError {
+                method: "deposit_cycles".to_string(),
+                reason: Reason::OutOfCycles,
+            });
+        }
+
+        ic_cdk::api::management_canister::main::deposit_cycles(
+            ic_cdk::api::management_canister::main::CanisterIdRecord { canister_id },
+            cycles,
+        )
+        .await
+        .map_err(|(code, msg)| CallError {
+            method: "deposit_cycles".to_string(),
+            reason: Reason::from_reject(code, msg),
+        })
+    }
+
+    async fn ledger_archives(&self, ledger_id: Principal) -> Result<Vec<Principal>, CallError> {
+        use icrc_ledger_types::icrc3::archive::ArchiveInfo;
+
+        let (archives,): (Vec<ArchiveInfo>,) = ic_cdk::api::call::call(ledger_id, "archives", ())
+            .await
+            .map_err(|(code, msg)| CallError {
+                method: "archives".to_string(),
+                reason: Reason::from_reject(code, msg),
+            })?;
+
+        Ok(archives
+            .into_iter()
+            .map(|archive| archive.canister_id)
+            .collect())
+    }
 }
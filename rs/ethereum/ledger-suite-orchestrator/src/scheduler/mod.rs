@@ -3,12 +3,12 @@ pub mod test_fixtures;
 #[cfg(test)]
 mod tests;
 
-use crate::candid::{AddErc20Arg, LedgerInitArg};
+use crate::candid::{AddErc20Arg, LedgerInitArg, UpgradeArg};
 use crate::logs::INFO;
 use crate::management::{CallError, CanisterRuntime};
 use crate::state::{
-    mutate_state, read_state, Canisters, CanistersMetadata, Index, Ledger, ManageSingleCanister,
-    ManagedCanisterStatus, RetrieveCanisterWasm, State, WasmHash,
+    mutate_state, read_state, Archive, Canisters, CanistersMetadata, Index, Ledger,
+    ManageSingleCanister, ManagedCanisterStatus, RetrieveCanisterWasm, State, WasmHash,
 };
 use candid::{CandidType, Encode, Principal};
 use ic_base_types::PrincipalId;
@@ -36,6 +36,14 @@ impl Tasks {
     pub fn add_task(&mut self, task: Task) {
         self.0.push_back(task);
     }
+
+    pub fn contains(&self, task: &Task) -> bool {
+        self.0.contains(task)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Task> {
+        self.0.iter()
+    }
 }
 
 impl Tasks {
@@ -67,6 +75,12 @@ impl Tasks {
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum Task {
     InstallLedgerSuite(InstallLedgerSuiteArgs),
+    /// Upgrade the ledger suites of all managed ERC-20 tokens.
+    UpgradeLedgerSuite(UpgradeLedgerSuiteArgs),
+    /// Top up the managed canisters whose cycles balance is below the threshold.
+    MaybeTopUp,
+    /// Discover and manage the archives spawned by the managed ledgers.
+    DiscoverArchives,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
@@ -140,11 +154,78 @@ impl InstallLedgerSuiteArgs {
     }
 }
 
+/// Target versions of the managed canisters.
+/// Canisters of a type without target version are not upgraded.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct UpgradeLedgerSuiteArgs {
+    ledger_compressed_wasm_hash: Option<WasmHash>,
+    index_compressed_wasm_hash: Option<WasmHash>,
+    archive_compressed_wasm_hash: Option<WasmHash>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum InvalidUpgradeArgError {
+    InvalidWasmHash(String),
+    WasmHashNotFound(WasmHash),
+}
+
+impl UpgradeLedgerSuiteArgs {
+    /// Validates the wasm hashes of the upgrade argument against the wasms embedded in the orchestrator.
+    /// Returns `None` if no managed canister should be upgraded.
+    pub fn validate_upgrade(
+        state: &State,
+        args: &UpgradeArg,
+    ) -> Result<Option<UpgradeLedgerSuiteArgs>, InvalidUpgradeArgError> {
+        fn parse_wasm_hash<C>(
+            state: &State,
+            name: &str,
+            hash: &Option<String>,
+        ) -> Result<Option<WasmHash>, InvalidUpgradeArgError>
+        where
+            State: RetrieveCanisterWasm<C>,
+        {
+            let hash = match hash {
+                Some(hash) => WasmHash::from_str(hash).map_err(|e| {
+                    InvalidUpgradeArgError::InvalidWasmHash(format!(
+                        "Invalid {} compressed wasm hash: {}",
+                        name, e
+                    ))
+                })?,
+                None => return Ok(None),
+            };
+            if RetrieveCanisterWasm::<C>::retrieve_wasm(state, &hash).is_none() {
+                return Err(InvalidUpgradeArgError::WasmHashNotFound(hash));
+            }
+            Ok(Some(hash))
+        }
+
+        let ledger_compressed_wasm_hash =
+            parse_wasm_hash::<Ledger>(state, "ledger", &args.ledger_compressed_wasm_hash)?;
+        let index_compressed_wasm_hash =
+            parse_wasm_hash::<Index>(state, "index", &args.index_compressed_wasm_hash)?;
+        let archive_compressed_wasm_hash =
+            parse_wasm_hash::<Archive>(state, "archive", &args.archive_compressed_wasm_hash)?;
+        if ledger_compressed_wasm_hash.is_none()
+            && index_compressed_wasm_hash.is_none()
+            && archive_compressed_wasm_hash.is_none()
+        {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            ledger_compressed_wasm_hash,
+            index_compressed_wasm_hash,
+            archive_compressed_wasm_hash,
+        }))
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum TaskError {
     CanisterCreationError(CallError),
     InstallCodeError(CallError),
+    UpgradeCodeError(CallError),
     WasmHashNotFound(WasmHash),
+    InterCanisterCallError(CallError),
 }
 
 impl TaskError {
@@ -154,7 +235,10 @@ impl TaskError {
         match self {
             TaskError::CanisterCreationError(_) => true,
             TaskError::InstallCodeError(_) => true,
+            TaskError::UpgradeCodeError(_) => true,
             TaskError::WasmHashNotFound(_) => false,
+            // Only periodic tasks query the managed canisters and they will be scheduled again.
+            TaskError::InterCanisterCallError(_) => false,
         }
     }
 }
@@ -163,6 +247,9 @@ impl Task {
     pub async fn execute<R: CanisterRuntime>(&self, runtime: &R) -> Result<(), TaskError> {
         match self {
             Task::InstallLedgerSuite(args) => install_ledger_suite(args, runtime).await,
+            Task::UpgradeLedgerSuite(args) => upgrade_ledger_suite(args, runtime).await,
+            Task::MaybeTopUp => maybe_top_up(runtime).await,
+            Task::DiscoverArchives => discover_archives(runtime).await,
         }
     }
 }
@@ -203,6 +290,268 @@ async fn install_ledger_suite<R: CanisterRuntime>(
     Ok(())
 }
 
+async fn upgrade_ledger_suite<R: CanisterRuntime>(
+    args: &UpgradeLedgerSuiteArgs,
+    runtime: &R,
+) -> Result<(), TaskError> {
+    let contracts: Vec<Erc20Token> = read_state(|s| {
+        s.managed_canisters_iter()
+            .map(|(contract, _canisters)| contract.clone())
+            .collect()
+    });
+    // The index is upgraded first since it only reads from the ledger,
+    // and the archives last since they are only accessed through the ledger.
+    for contract in contracts {
+        if let Some(wasm_hash) = &args.index_compressed_wasm_hash {
+            upgrade_canister_once::<Index, _, _>(
+                &contract,
+                wasm_hash,
+                &Option::<IndexArg>::None,
+                runtime,
+            )
+            .await?;
+        }
+        if let Some(wasm_hash) = &args.ledger_compressed_wasm_hash {
+            upgrade_canister_once::<Ledger, _, _>(
+                &contract,
+                wasm_hash,
+                &LedgerArgument::Upgrade(None),
+                runtime,
+            )
+            .await?;
+        }
+        if let Some(wasm_hash) = &args.archive_compressed_wasm_hash {
+            upgrade_archives_once(&contract, wasm_hash, runtime).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn upgrade_canister_once<C, R, U>(
+    contract: &Erc20Token,
+    wasm_hash: &WasmHash,
+    upgrade_arg: &U,
+    runtime: &R,
+) -> Result<(), TaskError>
+where
+    C: Debug,
+    Canisters: ManageSingleCanister<C>,
+    State: RetrieveCanisterWasm<C>,
+    R: CanisterRuntime,
+    U: Debug + CandidType,
+{
+    let canister_id = match read_state(|s| s.managed_status::<C>(contract).cloned()) {
+        None | Some(ManagedCanisterStatus::Created { .. }) => {
+            log!(
+                INFO,
+                "skipping upgrade of {} canister for {:?}: canister is not installed",
+                Canisters::display_name(),
+                contract
+            );
+            return Ok(());
+        }
+        Some(ManagedCanisterStatus::Installed {
+            installed_wasm_hash,
+            ..
+        }) if &installed_wasm_hash == wasm_hash => return Ok(()),
+        Some(ManagedCanisterStatus::Installed { canister_id, .. }) => canister_id,
+    };
+
+    let wasm = read_state(|s| RetrieveCanisterWasm::<C>::retrieve_wasm(s, wasm_hash).cloned())
+        .ok_or_else(|| TaskError::WasmHashNotFound(wasm_hash.clone()))?;
+
+    match runtime
+        .upgrade_canister(
+            canister_id,
+            wasm,
+            Encode!(upgrade_arg).expect("BUG: failed to encode upgrade arg"),
+        )
+        .await
+    {
+        Ok(_) => {
+            log!(
+                INFO,
+                "successfully upgraded {} canister for {:?} at '{}' to wasm hash {}",
+                Canisters::display_name(),
+                contract,
+                canister_id,
+                wasm_hash
+            );
+        }
+        Err(e) => {
+            log!(
+                INFO,
+                "failed to upgrade {} canister for {:?} at '{}' to wasm hash {}: {}",
+                Canisters::display_name(),
+                contract,
+                canister_id,
+                wasm_hash,
+                e
+            );
+            return Err(TaskError::UpgradeCodeError(e));
+        }
+    }
+
+    mutate_state(|s| s.record_installed_canister::<C>(contract, wasm_hash.clone()));
+
+    Ok(())
+}
+
+async fn upgrade_archives_once<R: CanisterRuntime>(
+    contract: &Erc20Token,
+    wasm_hash: &WasmHash,
+    runtime: &R,
+) -> Result<(), TaskError> {
+    let archives: Vec<Principal> = read_state(|s| {
+        s.managed_canisters(contract)
+            .map(|canisters| {
+                canisters
+                    .archives
+                    .iter()
+                    .filter(|archive| canisters.archive_wasm_hashes.get(archive) != Some(wasm_hash))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    });
+    if archives.is_empty() {
+        return Ok(());
+    }
+    let wasm =
+        read_state(|s| RetrieveCanisterWasm::<Archive>::retrieve_wasm(s, wasm_hash).cloned())
+            .ok_or_else(|| TaskError::WasmHashNotFound(wasm_hash.clone()))?;
+
+    for archive in archives {
+        match runtime
+            .upgrade_canister(
+                archive,
+                wasm.clone(),
+                Encode!().expect("BUG: failed to encode upgrade arg"),
+            )
+            .await
+        {
+            Ok(_) => {
+                log!(
+                    INFO,
+                    "successfully upgraded archive canister for {:?} at '{}' to wasm hash {}",
+                    contract,
+                    archive,
+                    wasm_hash
+                );
+            }
+            Err(e) => {
+                log!(
+                    INFO,
+                    "failed to upgrade archive canister for {:?} at '{}' to wasm hash {}: {}",
+                    contract,
+                    archive,
+                    wasm_hash,
+                    e
+                );
+                return Err(TaskError::UpgradeCodeError(e));
+            }
+        }
+        mutate_state(|s| s.record_upgraded_archive(contract, archive, wasm_hash.clone()));
+    }
+    Ok(())
+}
+
+async fn maybe_top_up<R: CanisterRuntime>(runtime: &R) -> Result<(), TaskError> {
+    let (canister_ids, cycles_management) = read_state(|s| {
+        (
+            s.managed_canisters_iter()
+                .flat_map(|(_contract, canisters)| canisters.canister_ids().cloned())
+                .collect::<Vec<_>>(),
+            s.cycles_management().clone(),
+        )
+    });
+    let mut first_error = None;
+    // Errors are not propagated right away so that one failing canister does not prevent
+    // the other canisters from being topped up.
+    for canister_id in canister_ids {
+        let balance = match runtime.canister_cycles(canister_id).await {
+            Ok(balance) => balance,
+            Err(e) => {
+                log!(
+                    INFO,
+                    "failed to retrieve cycles balance of canister '{}': {}",
+                    canister_id,
+                    e
+                );
+                first_error.get_or_insert(TaskError::InterCanisterCallError(e));
+                continue;
+            }
+        };
+        if balance >= cycles_management.cycles_top_up_threshold {
+            continue;
+        }
+        match runtime
+            .send_cycles(canister_id, cycles_management.cycles_top_up_increment)
+            .await
+        {
+            Ok(()) => {
+                log!(
+                    INFO,
+                    "topped up canister '{}' with {} cycles (balance was {})",
+                    canister_id,
+                    cycles_management.cycles_top_up_increment,
+                    balance
+                );
+                mutate_state(|s| {
+                    s.record_cycles_top_up(canister_id, cycles_management.cycles_top_up_increment)
+                });
+            }
+            Err(e) => {
+                log!(INFO, "failed to top up canister '{}': {}", canister_id, e);
+                first_error.get_or_insert(TaskError::InterCanisterCallError(e));
+            }
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+async fn discover_archives<R: CanisterRuntime>(runtime: &R) -> Result<(), TaskError> {
+    let ledgers: Vec<(Erc20Token, Principal)> = read_state(|s| {
+        s.managed_canisters_iter()
+            .filter_map(
+                |(contract, _canisters)| match s.managed_status::<Ledger>(contract) {
+                    Some(ManagedCanisterStatus::Installed { canister_id, .. }) => {
+                        Some((contract.clone(), *canister_id))
+                    }
+                    _ => None,
+                },
+            )
+            .collect()
+    });
+    let mut first_error = None;
+    for (contract, ledger_id) in ledgers {
+        match runtime.ledger_archives(ledger_id).await {
+            Ok(archives) => {
+                let new_archives = mutate_state(|s| s.record_archives(&contract, archives));
+                if !new_archives.is_empty() {
+                    log!(
+                        INFO,
+                        "discovered new archives {:?} for {:?}",
+                        new_archives,
+                        contract
+                    );
+                }
+            }
+            Err(e) => {
+                log!(
+                    INFO,
+                    "failed to retrieve archives of ledger '{}' for {:?}: {}",
+                    ledger_id,
+                    contract,
+                    e
+                );
+                first_error.get_or_insert(TaskError::InterCanisterCallError(e));
+            }
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
 fn record_new_erc20_token_once(contract: Erc20Token, metadata: CanistersMetadata) {
     mutate_state(|s| {
         if s.managed_canisters(&contract).is_some() {
@@ -1,16 +1,23 @@
-use crate::candid::{InitArg, LedgerInitArg};
+use crate::candid::{InitArg, LedgerInitArg, UpgradeArg};
 use crate::management::{CallError, Reason};
 use crate::scheduler::test_fixtures::usdc_metadata;
 use crate::scheduler::tests::mock::MockCanisterRuntime;
-use crate::scheduler::{Erc20Token, InstallLedgerSuiteArgs, Task, TaskError, Tasks};
+use crate::scheduler::{
+    Erc20Token, InstallLedgerSuiteArgs, InvalidUpgradeArgError, Task, TaskError, Tasks,
+    UpgradeLedgerSuiteArgs,
+};
 use crate::state::{
-    read_state, Canisters, IndexCanister, LedgerCanister, ManagedCanisterStatus, State, WasmHash,
+    mutate_state, read_state, Canisters, Index, IndexCanister, Ledger, LedgerCanister,
+    ManagedCanisterStatus, State, WasmHash, DEFAULT_CYCLES_TOP_UP_INCREMENT,
+    DEFAULT_CYCLES_TOP_UP_THRESHOLD,
 };
 use candid::Principal;
+use mockall::Sequence;
 
 const ORCHESTRATOR_PRINCIPAL: Principal = Principal::from_slice(&[0_u8; 29]);
 const LEDGER_PRINCIPAL: Principal = Principal::from_slice(&[1_u8; 29]);
 const INDEX_PRINCIPAL: Principal = Principal::from_slice(&[2_u8; 29]);
+const ARCHIVE_PRINCIPAL: Principal = Principal::from_slice(&[3_u8; 29]);
 
 #[tokio::test]
 async fn should_install_ledger_suite() {
@@ -43,6 +50,7 @@ async fn should_install_ledger_suite() {
                 installed_wasm_hash: read_index_wasm_hash(),
             })),
             archives: vec![],
+            archive_wasm_hashes: Default::default(),
             metadata: usdc_metadata(),
         })
     );
@@ -81,6 +89,7 @@ async fn should_not_retry_successful_operation_after_failing_one() {
             })),
             index: None,
             archives: vec![],
+            archive_wasm_hashes: Default::default(),
             metadata: usdc_metadata(),
         })
     );
@@ -110,6 +119,7 @@ async fn should_not_retry_successful_operation_after_failing_one() {
             })),
             index: None,
             archives: vec![],
+            archive_wasm_hashes: Default::default(),
             metadata: usdc_metadata(),
         })
     );
@@ -144,6 +154,7 @@ async fn should_not_retry_successful_operation_after_failing_one() {
                 canister_id: INDEX_PRINCIPAL
             })),
             archives: vec![],
+            archive_wasm_hashes: Default::default(),
             metadata: usdc_metadata(),
         })
     );
@@ -167,6 +178,7 @@ async fn should_not_retry_successful_operation_after_failing_one() {
                 installed_wasm_hash: read_index_wasm_hash(),
             })),
             archives: vec![],
+            archive_wasm_hashes: Default::default(),
             metadata: usdc_metadata(),
         })
     );
@@ -204,6 +216,7 @@ async fn should_discard_add_erc20_task_when_ledger_wasm_not_found() {
             })),
             index: None,
             archives: vec![],
+            archive_wasm_hashes: Default::default(),
             metadata: usdc_metadata(),
         })
     );
@@ -248,15 +261,285 @@ async fn should_discard_add_erc20_task_when_index_wasm_not_found() {
                 canister_id: INDEX_PRINCIPAL
             })),
             archives: vec![],
+            archive_wasm_hashes: Default::default(),
             metadata: usdc_metadata(),
         })
     );
 }
 
+#[tokio::test]
+async fn should_upgrade_ledger_suite() {
+    init_state();
+    let outdated_wasm_hash = WasmHash::from([0_u8; 32]);
+    init_installed_usdc_ledger_suite(outdated_wasm_hash.clone());
+    mutate_state(|s| s.record_archives(&usdc(), vec![ARCHIVE_PRINCIPAL]));
+    let mut tasks = Tasks::default();
+    tasks.add_task(Task::UpgradeLedgerSuite(upgrade_to_embedded_wasms()));
+    let mut runtime = MockCanisterRuntime::new();
+
+    let mut seq = Sequence::new();
+    for canister_id in [INDEX_PRINCIPAL, LEDGER_PRINCIPAL, ARCHIVE_PRINCIPAL] {
+        runtime
+            .expect_upgrade_canister()
+            .withf(move |id, _wasm, _arg| *id == canister_id)
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(Ok(()));
+    }
+
+    assert_eq!(tasks.execute(&runtime).await, Ok(()));
+    assert_eq!(
+        read_state(|s| s.managed_canisters(&usdc()).cloned()),
+        Some(Canisters {
+            ledger: Some(LedgerCanister::new(ManagedCanisterStatus::Installed {
+                canister_id: LEDGER_PRINCIPAL,
+                installed_wasm_hash: read_ledger_wasm_hash(),
+            })),
+            index: Some(IndexCanister::new(ManagedCanisterStatus::Installed {
+                canister_id: INDEX_PRINCIPAL,
+                installed_wasm_hash: read_index_wasm_hash(),
+            })),
+            archives: vec![ARCHIVE_PRINCIPAL],
+            archive_wasm_hashes: [(ARCHIVE_PRINCIPAL, read_archive_wasm_hash())]
+                .into_iter()
+                .collect(),
+            metadata: usdc_metadata(),
+        })
+    );
+
+    runtime.checkpoint();
+    tasks.add_task(Task::UpgradeLedgerSuite(upgrade_to_embedded_wasms()));
+    runtime.expect_upgrade_canister().never();
+    assert_eq!(tasks.execute(&runtime).await, Ok(()));
+}
+
+#[tokio::test]
+async fn should_retry_failed_upgrade_without_upgrading_twice() {
+    init_state();
+    init_installed_usdc_ledger_suite(WasmHash::from([0_u8; 32]));
+    let mut tasks = Tasks::default();
+    tasks.add_task(Task::UpgradeLedgerSuite(upgrade_to_embedded_wasms()));
+    let mut runtime = MockCanisterRuntime::new();
+
+    let expected_error = CallError {
+        method: "install_code".to_string(),
+        reason: Reason::OutOfCycles,
+    };
+    runtime
+        .expect_upgrade_canister()
+        .withf(|id, _wasm, _arg| *id == INDEX_PRINCIPAL)
+        .times(1)
+        .return_const(Ok(()));
+    runtime
+        .expect_upgrade_canister()
+        .withf(|id, _wasm, _arg| *id == LEDGER_PRINCIPAL)
+        .times(1)
+        .return_const(Err(expected_error.clone()));
+    assert_eq!(
+        tasks.execute(&runtime).await,
+        Err(TaskError::UpgradeCodeError(expected_error))
+    );
+
+    runtime.checkpoint();
+    runtime
+        .expect_upgrade_canister()
+        .withf(|id, _wasm, _arg| *id == LEDGER_PRINCIPAL)
+        .times(1)
+        .return_const(Ok(()));
+    assert_eq!(tasks.execute(&runtime).await, Ok(()));
+    assert_eq!(tasks.0.len(), 0);
+}
+
+#[test]
+fn should_validate_upgrade_arg() {
+    let state = State::from(InitArg {});
+
+    assert_eq!(
+        UpgradeLedgerSuiteArgs::validate_upgrade(&state, &UpgradeArg::default()),
+        Ok(None)
+    );
+
+    let unknown_wasm_hash = WasmHash::from([0_u8; 32]);
+    assert_eq!(
+        UpgradeLedgerSuiteArgs::validate_upgrade(
+            &state,
+            &UpgradeArg {
+                ledger_compressed_wasm_hash: Some(unknown_wasm_hash.to_string()),
+                ..Default::default()
+            }
+        ),
+        Err(InvalidUpgradeArgError::WasmHashNotFound(unknown_wasm_hash))
+    );
+
+    // The ledger wasm cannot be used to upgrade the index
+    assert_eq!(
+        UpgradeLedgerSuiteArgs::validate_upgrade(
+            &state,
+            &UpgradeArg {
+                index_compressed_wasm_hash: Some(state.ledger_wasm().hash().to_string()),
+                ..Default::default()
+            }
+        ),
+        Err(InvalidUpgradeArgError::WasmHashNotFound(
+            state.ledger_wasm().hash().clone()
+        ))
+    );
+
+    assert!(matches!(
+        UpgradeLedgerSuiteArgs::validate_upgrade(
+            &state,
+            &UpgradeArg {
+                archive_compressed_wasm_hash: Some("0xinvalid".to_string()),
+                ..Default::default()
+            }
+        ),
+        Err(InvalidUpgradeArgError::InvalidWasmHash(_))
+    ));
+
+    assert_eq!(
+        UpgradeLedgerSuiteArgs::validate_upgrade(
+            &state,
+            &UpgradeArg {
+                archive_compressed_wasm_hash: Some(state.archive_wasm().hash().to_string()),
+                ..Default::default()
+            }
+        ),
+        Ok(Some(UpgradeLedgerSuiteArgs {
+            ledger_compressed_wasm_hash: None,
+            index_compressed_wasm_hash: None,
+            archive_compressed_wasm_hash: Some(state.archive_wasm().hash().clone()),
+        }))
+    );
+}
+
+#[tokio::test]
+async fn should_top_up_canisters_below_threshold() {
+    init_state();
+    init_installed_usdc_ledger_suite(read_state(|s| s.ledger_wasm().hash().clone()));
+    let mut tasks = Tasks::default();
+    tasks.add_task(Task::MaybeTopUp);
+    let mut runtime = MockCanisterRuntime::new();
+
+    runtime
+        .expect_canister_cycles()
+        .withf(|id| *id == LEDGER_PRINCIPAL)
+        .times(1)
+        .return_const(Ok(DEFAULT_CYCLES_TOP_UP_THRESHOLD - 1));
+    runtime
+        .expect_canister_cycles()
+        .withf(|id| *id == INDEX_PRINCIPAL)
+        .times(1)
+        .return_const(Ok(DEFAULT_CYCLES_TOP_UP_THRESHOLD));
+    runtime
+        .expect_send_cycles()
+        .withf(|id, cycles| *id == LEDGER_PRINCIPAL && *cycles == DEFAULT_CYCLES_TOP_UP_INCREMENT)
+        .times(1)
+        .return_const(Ok(()));
+
+    assert_eq!(tasks.execute(&runtime).await, Ok(()));
+    assert_eq!(
+        read_state(|s| s.cycles_top_ups().clone()),
+        [(LEDGER_PRINCIPAL, DEFAULT_CYCLES_TOP_UP_INCREMENT)]
+            .into_iter()
+            .collect()
+    );
+}
+
+#[tokio::test]
+async fn should_top_up_remaining_canisters_when_one_fails() {
+    init_state();
+    init_installed_usdc_ledger_suite(read_state(|s| s.ledger_wasm().hash().clone()));
+    let mut tasks = Tasks::default();
+    tasks.add_task(Task::MaybeTopUp);
+    let mut runtime = MockCanisterRuntime::new();
+
+    let expected_error = CallError {
+        method: "canister_status".to_string(),
+        reason: Reason::OutOfCycles,
+    };
+    runtime
+        .expect_canister_cycles()
+        .withf(|id| *id == LEDGER_PRINCIPAL)
+        .times(1)
+        .return_const(Err(expected_error.clone()));
+    runtime
+        .expect_canister_cycles()
+        .withf(|id| *id == INDEX_PRINCIPAL)
+        .times(1)
+        .return_const(Ok(0));
+    runtime
+        .expect_send_cycles()
+        .withf(|id, _cycles| *id == INDEX_PRINCIPAL)
+        .times(1)
+        .return_const(Ok(()));
+
+    assert_eq!(
+        tasks.execute(&runtime).await,
+        Err(TaskError::InterCanisterCallError(expected_error))
+    );
+    assert_eq!(tasks.0.len(), 0);
+    assert_eq!(
+        read_state(|s| s.cycles_top_ups().clone()),
+        [(INDEX_PRINCIPAL, DEFAULT_CYCLES_TOP_UP_INCREMENT)]
+            .into_iter()
+            .collect()
+    );
+}
+
+#[tokio::test]
+async fn should_discover_archives() {
+    init_state();
+    init_installed_usdc_ledger_suite(read_state(|s| s.ledger_wasm().hash().clone()));
+    let mut tasks = Tasks::default();
+    tasks.add_task(Task::DiscoverArchives);
+    let mut runtime = MockCanisterRuntime::new();
+
+    runtime
+        .expect_ledger_archives()
+        .withf(|id| *id == LEDGER_PRINCIPAL)
+        .times(1)
+        .return_const(Ok(vec![ARCHIVE_PRINCIPAL]));
+    assert_eq!(tasks.execute(&runtime).await, Ok(()));
+    assert_eq!(
+        read_state(|s| s.managed_canisters(&usdc()).unwrap().archives.clone()),
+        vec![ARCHIVE_PRINCIPAL]
+    );
+
+    runtime.checkpoint();
+    tasks.add_task(Task::DiscoverArchives);
+    runtime
+        .expect_ledger_archives()
+        .times(1)
+        .return_const(Ok(vec![ARCHIVE_PRINCIPAL]));
+    assert_eq!(tasks.execute(&runtime).await, Ok(()));
+    assert_eq!(
+        read_state(|s| s.managed_canisters(&usdc()).unwrap().archives.clone()),
+        vec![ARCHIVE_PRINCIPAL]
+    );
+}
+
 fn init_state() {
     crate::state::init_state(State::from(InitArg {}));
 }
 
+fn init_installed_usdc_ledger_suite(installed_wasm_hash: WasmHash) {
+    mutate_state(|s| {
+        s.record_new_erc20_token(usdc(), usdc_metadata());
+        s.record_created_canister::<Ledger>(&usdc(), LEDGER_PRINCIPAL);
+        s.record_installed_canister::<Ledger>(&usdc(), installed_wasm_hash.clone());
+        s.record_created_canister::<Index>(&usdc(), INDEX_PRINCIPAL);
+        s.record_installed_canister::<Index>(&usdc(), installed_wasm_hash);
+    });
+}
+
+fn upgrade_to_embedded_wasms() -> UpgradeLedgerSuiteArgs {
+    read_state(|s| UpgradeLedgerSuiteArgs {
+        ledger_compressed_wasm_hash: Some(s.ledger_wasm().hash().clone()),
+        index_compressed_wasm_hash: Some(s.index_wasm().hash().clone()),
+        archive_compressed_wasm_hash: Some(s.archive_wasm().hash().clone()),
+    })
+}
+
 fn usdc_install_args() -> InstallLedgerSuiteArgs {
     InstallLedgerSuiteArgs {
         contract: usdc(),
@@ -305,6 +588,10 @@ fn read_ledger_wasm_hash() -> WasmHash {
     read_state(|s| s.ledger_wasm().hash().clone())
 }
 
+fn read_archive_wasm_hash() -> WasmHash {
+    read_state(|s| s.archive_wasm().hash().clone())
+}
+
 fn expect_create_canister_returning(
     runtime: &mut MockCanisterRuntime,
     results: Vec<Result<Principal, CallError>>,
@@ -350,6 +637,19 @@ mod mock {
                 wasm_module: Wasm,
                 arg: Vec<u8>,
             ) -> Result<(), CallError>;
+
+            async fn upgrade_canister(
+                &self,
+                canister_id: Principal,
+                wasm_module: Wasm,
+                arg: Vec<u8>,
+            ) -> Result<(), CallError>;
+
+            async fn canister_cycles(&self, canister_id: Principal) -> Result<u128, CallError>;
+
+            async fn send_cycles(&self, canister_id: Principal, cycles: u128) -> Result<(), CallError>;
+
+            async fn ledger_archives(&self, ledger_id: Principal) -> Result<Vec<Principal>, CallError>;
         }
     }
 }
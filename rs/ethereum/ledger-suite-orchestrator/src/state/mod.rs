@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests;
 
-use crate::candid::InitArg;
+use crate::candid::{InitArg, UpdateCyclesManagement};
 use crate::scheduler::{Erc20Token, Task, Tasks};
 use candid::Principal;
 use ic_cdk::trap;
@@ -18,7 +18,8 @@ use std::str::FromStr;
 
 pub(crate) const LEDGER_BYTECODE: &[u8] = include_bytes!(env!("LEDGER_CANISTER_WASM_PATH"));
 pub(crate) const INDEX_BYTECODE: &[u8] = include_bytes!(env!("INDEX_CANISTER_WASM_PATH"));
-const ARCHIVE_NODE_BYTECODE: &[u8] = include_bytes!(env!("LEDGER_ARCHIVE_NODE_CANISTER_WASM_PATH"));
+pub(crate) const ARCHIVE_NODE_BYTECODE: &[u8] =
+    include_bytes!(env!("LEDGER_ARCHIVE_NODE_CANISTER_WASM_PATH"));
 
 const STATE_MEMORY_ID: MemoryId = MemoryId::new(0);
 const WASM_HASH_LENGTH: usize = 32;
@@ -133,7 +134,12 @@ pub struct ManagedCanisters {
 pub struct Canisters {
     pub ledger: Option<LedgerCanister>,
     pub index: Option<IndexCanister>,
+    /// Archives spawned by the ledger, in the order they were discovered.
     pub archives: Vec<Principal>,
+    /// Wasm hash of the archives the orchestrator upgraded.
+    /// Archives are installed by the ledger, so their initial wasm hash is unknown.
+    #[serde(default)]
+    pub archive_wasm_hashes: BTreeMap<Principal, WasmHash>,
     pub metadata: CanistersMetadata,
 }
 
@@ -148,6 +154,7 @@ impl Canisters {
             ledger: None,
             index: None,
             archives: vec![],
+            archive_wasm_hashes: BTreeMap::new(),
             metadata,
         }
     }
@@ -163,6 +170,14 @@ impl Canisters {
     pub fn archive_canister_ids(&self) -> &[Principal] {
         &self.archives
     }
+
+    /// All canisters managed for this ERC-20 token.
+    pub fn canister_ids(&self) -> impl Iterator<Item = &Principal> {
+        self.ledger_canister_id()
+            .into_iter()
+            .chain(self.index_canister_id())
+            .chain(self.archives.iter())
+    }
 }
 
 #[derive(Debug)]
@@ -220,6 +235,9 @@ pub type LedgerCanister = Canister<Ledger>;
 pub enum Index {}
 pub type IndexCanister = Canister<Index>;
 
+#[derive(Debug)]
+pub enum Archive {}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum ManagedCanisterStatus {
     /// Canister created with the given principal
@@ -297,6 +315,51 @@ impl Storable for ConfigState {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+/// Default minimum amount of cycles a managed canister should hold.
+pub const DEFAULT_CYCLES_TOP_UP_THRESHOLD: u128 = 500_000_000_000;
+/// Default amount of cycles sent to a managed canister below the threshold.
+pub const DEFAULT_CYCLES_TOP_UP_INCREMENT: u128 = 500_000_000_000;
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct CyclesManagement {
+    pub cycles_top_up_threshold: u128,
+    pub cycles_top_up_increment: u128,
+}
+
+impl Default for CyclesManagement {
+    fn default() -> Self {
+        Self {
+            cycles_top_up_threshold: DEFAULT_CYCLES_TOP_UP_THRESHOLD,
+            cycles_top_up_increment: DEFAULT_CYCLES_TOP_UP_INCREMENT,
+        }
+    }
+}
+
+impl CyclesManagement {
+    pub fn apply(&mut self, update: UpdateCyclesManagement) -> Result<(), String> {
+        use num_traits::ToPrimitive;
+
+        let mut updated = self.clone();
+        if let Some(threshold) = update.cycles_top_up_threshold {
+            updated.cycles_top_up_threshold = threshold
+                .0
+                .to_u128()
+                .ok_or("cycles_top_up_threshold does not fit in u128")?;
+        }
+        if let Some(increment) = update.cycles_top_up_increment {
+            updated.cycles_top_up_increment = increment
+                .0
+                .to_u128()
+                .ok_or("cycles_top_up_increment does not fit in u128")?;
+        }
+        if updated.cycles_top_up_increment == 0 {
+            return Err("cycles_top_up_increment must be positive".to_string());
+        }
+        *self = updated;
+        Ok(())
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct State {
     ledger_wasm: Wasm,
@@ -305,6 +368,11 @@ pub struct State {
     managed_canisters: ManagedCanisters,
     tasks: Tasks,
     processing_tasks_guard: bool,
+    #[serde(default)]
+    cycles_management: CyclesManagement,
+    /// Total amount of cycles sent to each managed canister.
+    #[serde(default)]
+    cycles_top_ups: BTreeMap<Principal, u128>,
 }
 
 impl State {
@@ -320,10 +388,45 @@ impl State {
         &self.index_wasm
     }
 
+    pub fn archive_wasm(&self) -> &Wasm {
+        &self.archive_wasm
+    }
+
+    /// Replaces the stored wasms with the ones embedded in the current orchestrator wasm.
+    pub fn refresh_embedded_wasms(&mut self) {
+        self.ledger_wasm = Wasm::from(LEDGER_BYTECODE);
+        self.index_wasm = Wasm::from(INDEX_BYTECODE);
+        self.archive_wasm = Wasm::from(ARCHIVE_NODE_BYTECODE);
+    }
+
+    pub fn cycles_management(&self) -> &CyclesManagement {
+        &self.cycles_management
+    }
+
+    pub fn cycles_management_mut(&mut self) -> &mut CyclesManagement {
+        &mut self.cycles_management
+    }
+
+    pub fn cycles_top_ups(&self) -> &BTreeMap<Principal, u128> {
+        &self.cycles_top_ups
+    }
+
+    pub fn record_cycles_top_up(&mut self, canister_id: Principal, cycles: u128) {
+        let total = self.cycles_top_ups.entry(canister_id).or_default();
+        *total = total.saturating_add(cycles);
+    }
+
     pub fn add_task(&mut self, task: Task) {
         self.tasks.add_task(task);
     }
 
+    /// Adds the task unless an identical task is already scheduled.
+    pub fn add_task_once(&mut self, task: Task) {
+        if !self.tasks.contains(&task) {
+            self.tasks.add_task(task);
+        }
+    }
+
     pub fn set_tasks(&mut self, tasks: Tasks) {
         self.tasks = tasks;
     }
@@ -421,6 +524,44 @@ impl State {
             installed_wasm_hash: wasm_hash,
         };
     }
+
+    /// Records the archives spawned by the ledger that are not yet managed.
+    /// Returns the newly managed archives.
+    pub fn record_archives(
+        &mut self,
+        contract: &Erc20Token,
+        archives: Vec<Principal>,
+    ) -> Vec<Principal> {
+        let canisters = self
+            .managed_canisters_mut(contract)
+            .unwrap_or_else(|| panic!("BUG: token {:?} is not managed", contract));
+        let mut new_archives = vec![];
+        for archive in archives {
+            if !canisters.archives.contains(&archive) {
+                canisters.archives.push(archive);
+                new_archives.push(archive);
+            }
+        }
+        new_archives
+    }
+
+    pub fn record_upgraded_archive(
+        &mut self,
+        contract: &Erc20Token,
+        archive: Principal,
+        wasm_hash: WasmHash,
+    ) {
+        let canisters = self
+            .managed_canisters_mut(contract)
+            .unwrap_or_else(|| panic!("BUG: token {:?} is not managed", contract));
+        assert!(
+            canisters.archives.contains(&archive),
+            "BUG: archive {} of token {:?} is not managed",
+            archive,
+            contract
+        );
+        canisters.archive_wasm_hashes.insert(archive, wasm_hash);
+    }
 }
 
 pub trait ManageSingleCanister<T> {
@@ -515,6 +656,15 @@ impl RetrieveCanisterWasm<Index> for State {
     }
 }
 
+impl RetrieveCanisterWasm<Archive> for State {
+    fn retrieve_wasm(&self, compressed_wasm_hash: &WasmHash) -> Option<&Wasm> {
+        if self.archive_wasm.hash() == compressed_wasm_hash {
+            return Some(&self.archive_wasm);
+        }
+        None
+    }
+}
+
 impl From<InitArg> for State {
     fn from(InitArg {}: InitArg) -> Self {
         Self {
@@ -524,6 +674,8 @@ impl From<InitArg> for State {
             managed_canisters: Default::default(),
             tasks: Default::default(),
             processing_tasks_guard: false,
+            cycles_management: Default::default(),
+            cycles_top_ups: Default::default(),
         }
     }
 }
@@ -0,0 +1,114 @@
+use crate::{query_handler::QueryScheduler, ExecutionEnvironment};
+use ic_error_types::{ErrorCode, RejectCode};
+use ic_interfaces::execution_environment::{
+    DryRunExecutionResponse, DryRunExecutionService, QueryExecutionError,
+};
+use ic_interfaces_state_manager::StateReader;
+use ic_replicated_state::ReplicatedState;
+use ic_types::{
+    ingress::WasmResult,
+    messages::{
+        Blob, HttpDryRunResponse, HttpQueryResponse, HttpQueryResponseReply, SignedIngressContent,
+    },
+    NumInstructions,
+};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::oneshot;
+use tower::{util::BoxCloneService, Service};
+
+#[derive(Clone)]
+// Struct that is responsible for handling dry runs of update calls sent by users.
+pub(crate) struct DryRunHandler {
+    exec_env: Arc<ExecutionEnvironment>,
+    state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+    query_scheduler: QueryScheduler,
+    max_instructions_per_dry_run: NumInstructions,
+}
+
+impl DryRunHandler {
+    pub(crate) fn new_service(
+        query_scheduler: QueryScheduler,
+        state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+        exec_env: Arc<ExecutionEnvironment>,
+        max_instructions_per_dry_run: NumInstructions,
+    ) -> DryRunExecutionService {
+        BoxCloneService::new(Self {
+            exec_env,
+            state_reader,
+            query_scheduler,
+            max_instructions_per_dry_run,
+        })
+    }
+}
+
+impl Service<SignedIngressContent> for DryRunHandler {
+    type Response = DryRunExecutionResponse;
+    type Error = Infallible;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, ingress: SignedIngressContent) -> Self::Future {
+        let max_instructions_per_dry_run = self.max_instructions_per_dry_run;
+        let exec_env = Arc::clone(&self.exec_env);
+        let state_reader = Arc::clone(&self.state_reader);
+        let (tx, rx) = oneshot::channel();
+        let canister_id = ingress.canister_id();
+        // Dry runs share the query threads, so that they are scheduled fairly
+        // with the queries to the same canister.
+        self.query_scheduler.push(canister_id, move || {
+            let start = std::time::Instant::now();
+            if !tx.is_closed() {
+                // As for queries, the state is retrieved right before it is used,
+                // so that queued up dry runs do not keep old states alive.
+                let result = match state_reader.get_state_at(state_reader.latest_certified_height())
+                {
+                    Ok(state) => {
+                        let time = state.get_ref().metadata.batch_time;
+                        let (result, instructions_used, estimated_cycles_cost) = exec_env
+                            .execute_dry_run(&ingress, state.take(), max_instructions_per_dry_run);
+
+                        let outcome = match result {
+                            Ok(WasmResult::Reply(vec)) => HttpQueryResponse::Replied {
+                                reply: HttpQueryResponseReply { arg: Blob(vec) },
+                            },
+                            Ok(WasmResult::Reject(message)) => HttpQueryResponse::Rejected {
+                                error_code: ErrorCode::CanisterRejectedMessage.to_string(),
+                                reject_code: RejectCode::CanisterReject as u64,
+                                reject_message: message,
+                            },
+                            Err(user_error) => HttpQueryResponse::Rejected {
+                                error_code: user_error.code().to_string(),
+                                reject_code: user_error.reject_code() as u64,
+                                reject_message: user_error.to_string(),
+                            },
+                        };
+                        let response = HttpDryRunResponse {
+                            outcome,
+                            instructions_used: instructions_used.get(),
+                            estimated_cycles_cost: u64::try_from(estimated_cycles_cost.get())
+                                .unwrap_or(u64::MAX),
+                        };
+
+                        Ok((response, time))
+                    }
+                    Err(_) => Err(QueryExecutionError::CertifiedStateUnavailable),
+                };
+
+                let _ = tx.send(Ok(result));
+            }
+            start.elapsed()
+        });
+        Box::pin(async move {
+            rx.await
+                .expect("The sender was dropped before sending the message.")
+        })
+    }
+}
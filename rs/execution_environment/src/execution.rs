@@ -13,4 +13,5 @@ pub mod upgrade;
 
 // Common helpers.
 pub(crate) mod common;
+pub mod dry_run;
 pub mod inspect_message;
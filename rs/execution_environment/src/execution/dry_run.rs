@@ -0,0 +1,103 @@
+use crate::execution::common::validate_message;
+use crate::Hypervisor;
+use crate::{
+    execution_environment::{as_round_instructions, RoundLimits},
+    metrics::CallTreeMetricsNoOp,
+};
+use ic_error_types::{ErrorCode, UserError};
+use ic_interfaces::execution_environment::SubnetAvailableMemory;
+use ic_replicated_state::{CallOrigin, CanisterState, NetworkTopology};
+use ic_system_api::{ApiType, ExecutionParameters};
+use ic_types::ingress::WasmResult;
+use ic_types::messages::{HttpRequestContent, RequestMetadata, SignedIngressContent};
+use ic_types::methods::{FuncRef, WasmMethod};
+use ic_types::{Cycles, NumInstructions, Time};
+use prometheus::IntCounter;
+
+/// Executes the update method called by `ingress` on a copy of `canister`,
+/// without committing any of the effects of the execution.
+///
+/// This method is called pre-consensus to let users find out what an update
+/// call would return, and what it would cost, before they submit it. Outgoing
+/// calls made by the method end up in the output queues of the copy, so they
+/// are never sent. If the method only replies after such a call returns, the
+/// dry run fails with `CanisterDidNotReply`.
+///
+/// Returns the number of instructions left, along with the result.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_dry_run(
+    time: Time,
+    canister: CanisterState,
+    ingress: &SignedIngressContent,
+    execution_parameters: ExecutionParameters,
+    subnet_available_memory: SubnetAvailableMemory,
+    hypervisor: &Hypervisor,
+    network_topology: &NetworkTopology,
+    state_changes_error: &IntCounter,
+) -> (NumInstructions, Result<WasmResult, UserError>) {
+    let message_instruction_limit = execution_parameters.instruction_limits.message();
+    let canister_id = canister.canister_id();
+    let method = WasmMethod::Update(ingress.method_name().to_string());
+    if let Err(err) = validate_message(&canister, &method) {
+        return (message_instruction_limit, Err(err));
+    }
+
+    let memory_usage = canister.memory_usage();
+    let message_memory_usage = canister.message_memory_usage();
+    let (execution_state, mut system_state, _) = canister.into_parts();
+    // `validate_message()` only succeeds if the Wasm module exports the method.
+    let execution_state = execution_state.unwrap();
+
+    let call_context_id = system_state
+        .call_context_manager_mut()
+        .unwrap()
+        .new_call_context(
+            CallOrigin::Ingress(ingress.sender(), ingress.id()),
+            Cycles::zero(),
+            time,
+            RequestMetadata::for_new_call_tree(time),
+        );
+    let system_api = ApiType::update(
+        time,
+        ingress.arg().to_vec(),
+        Cycles::zero(),
+        ingress.sender().get(),
+        call_context_id,
+    );
+    let mut round_limits = RoundLimits {
+        instructions: as_round_instructions(message_instruction_limit),
+        subnet_available_memory,
+        // Ignore compute allocation
+        compute_allocation_used: 0,
+    };
+    // The resulting execution and system states are dropped: nothing the
+    // method did is ever visible outside of this function.
+    let (output, _execution_state, _system_state) = hypervisor.execute(
+        system_api,
+        time,
+        system_state,
+        memory_usage,
+        message_memory_usage,
+        execution_parameters,
+        FuncRef::Method(method),
+        execution_state,
+        network_topology,
+        &mut round_limits,
+        state_changes_error,
+        &CallTreeMetricsNoOp,
+        time,
+    );
+
+    let result = match output.wasm_result {
+        Ok(Some(wasm_result)) => Ok(wasm_result),
+        Ok(None) => Err(UserError::new(
+            ErrorCode::CanisterDidNotReply,
+            format!(
+                "Canister {} did not reply to the call in the dry run. Outgoing calls are not executed in dry runs, so methods that reply after awaiting them cannot be dry run.",
+                canister_id
+            ),
+        )),
+        Err(err) => Err(err.into_user_error(&canister_id)),
+    };
+    (output.num_instructions_left, result)
+}
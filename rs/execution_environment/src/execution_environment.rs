@@ -5,7 +5,7 @@ use crate::{
     },
     canister_settings::CanisterSettings,
     execution::{
-        inspect_message, install_code::validate_controller,
+        dry_run, inspect_message, install_code::validate_controller,
         nonreplicated_query::execute_non_replicated_query,
         replicated_query::execute_replicated_query, response::execute_response,
         update::execute_update,
@@ -2004,6 +2004,94 @@ impl ExecutionEnvironment {
         }
     }
 
+    /// Executes the update call in `ingress` against `state` without
+    /// committing any of its effects, see `dry_run::execute_dry_run()`.
+    ///
+    /// Returns the result of the call, the number of instructions it used, and
+    /// the cycles that inducting and executing it would have cost the canister.
+    pub fn execute_dry_run(
+        &self,
+        ingress: &SignedIngressContent,
+        state: Arc<ReplicatedState>,
+        max_instructions_per_dry_run: NumInstructions,
+    ) -> (Result<WasmResult, UserError>, NumInstructions, Cycles) {
+        if ingress.is_addressed_to_subnet(self.own_subnet_id) {
+            return (
+                Err(UserError::new(
+                    ErrorCode::CanisterRejectedMessage,
+                    "Calls to the management canister cannot be dry run",
+                )),
+                NumInstructions::from(0),
+                Cycles::zero(),
+            );
+        }
+        let canister = match state.canister_state(&ingress.canister_id()) {
+            Some(canister) => canister,
+            None => {
+                return (
+                    Err(UserError::new(
+                        ErrorCode::CanisterNotFound,
+                        format!("Canister {} not found", ingress.canister_id()),
+                    )),
+                    NumInstructions::from(0),
+                    Cycles::zero(),
+                )
+            }
+        };
+
+        // A dry run is expected to finish quickly, so DTS is not supported
+        // for it.
+        let instruction_limits = InstructionLimits::new(
+            FlagStatus::Disabled,
+            max_instructions_per_dry_run,
+            max_instructions_per_dry_run,
+        );
+        // Letting the canister grow arbitrarily when executing the dry run is
+        // fine as we do not persist state modifications.
+        let subnet_available_memory = subnet_memory_capacity(&self.config);
+        let execution_parameters = self.execution_parameters(
+            canister,
+            instruction_limits,
+            // The call is executed exactly as it would be in a round, so that
+            // the dry run is representative.
+            ExecutionMode::Replicated,
+            // Effectively disable subnet memory resource reservation for dry runs.
+            ResourceSaturation::default(),
+        );
+
+        let (instructions_left, result) = dry_run::execute_dry_run(
+            state.time(),
+            canister.clone(),
+            ingress,
+            execution_parameters,
+            subnet_available_memory,
+            &self.hypervisor,
+            &state.metadata.network_topology,
+            &self.metrics.state_changes_error,
+        );
+        let instructions_used = max_instructions_per_dry_run - instructions_left;
+        let subnet_size = state
+            .metadata
+            .network_topology
+            .get_subnet_size(&state.metadata.own_subnet_id)
+            .unwrap_or(SMALL_APP_SUBNET_MAX_SIZE);
+        // Calls to the management canister are rejected above, so the
+        // effective canister ID is not needed.
+        let ingress_induction_cost =
+            match self
+                .cycles_account_manager
+                .ingress_induction_cost(ingress, None, subnet_size)
+            {
+                IngressInductionCost::Fee { payer: _, cost } => cost,
+                IngressInductionCost::Free => Cycles::zero(),
+            };
+        let estimated_cycles_cost = ingress_induction_cost
+            + self
+                .cycles_account_manager
+                .execution_cost(instructions_used, subnet_size);
+        (result, instructions_used, estimated_cycles_cost)
+    }
+
     // Output the response of a subnet message depending on its type.
     //
     // Canister requests are responded to by adding a response to the subnet's
@@ -1728,6 +1728,76 @@ fn should_accept_ingress_filters_correctly_on_method_type() {
     assert_eq!(ErrorCode::CompositeQueryCalledInReplicatedMode, err.code());
 }
 
+#[test]
+fn dry_run_returns_reply_without_changing_state() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister = test.universal_canister().unwrap();
+    let balance_before = test.canister_state(canister).system_state.balance();
+
+    let run = wasm()
+        .set_global_data(b"dry run")
+        .get_global_data()
+        .append_and_reply()
+        .build();
+    let ingress_bytes = NumBytes::from((run.len() + "update".len()) as u64);
+    let (result, instructions_used, estimated_cycles_cost) = test.dry_run(canister, "update", run);
+    assert_eq!(result, Ok(WasmResult::Reply(b"dry run".to_vec())));
+    assert!(instructions_used.get() > 0);
+    // The estimate covers both the induction and the execution of the call.
+    assert_eq!(
+        estimated_cycles_cost,
+        test.cycles_account_manager()
+            .ingress_induction_cost_from_bytes(ingress_bytes, test.subnet_size())
+            + test
+                .cycles_account_manager()
+                .execution_cost(instructions_used, test.subnet_size())
+    );
+
+    // Neither the balance nor the call contexts nor the global data changed.
+    assert_eq!(
+        test.canister_state(canister).system_state.balance(),
+        balance_before
+    );
+    assert!(test
+        .canister_state(canister)
+        .system_state
+        .call_context_manager()
+        .unwrap()
+        .call_contexts()
+        .is_empty());
+    let result = test.ingress(
+        canister,
+        "update",
+        wasm().get_global_data().append_and_reply().build(),
+    );
+    assert_eq!(get_reply(result), Vec::<u8>::new());
+}
+
+#[test]
+fn dry_run_does_not_send_outgoing_calls() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let caller = test.universal_canister().unwrap();
+    let callee = test.universal_canister().unwrap();
+
+    let run = wasm()
+        .inter_update(callee, call_args().other_side(wasm().reply()))
+        .build();
+    let (result, _, _) = test.dry_run(caller, "update", run);
+    assert_eq!(result.unwrap_err().code(), ErrorCode::CanisterDidNotReply);
+    assert!(!test.canister_state(caller).has_output());
+}
+
+#[test]
+fn dry_run_of_management_canister_call_is_rejected() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let (result, instructions_used, _) = test.dry_run(CanisterId::ic_00(), "raw_rand", vec![]);
+    assert_eq!(
+        result.unwrap_err().code(),
+        ErrorCode::CanisterRejectedMessage
+    );
+    assert_eq!(instructions_used.get(), 0);
+}
+
 #[test]
 fn message_to_canister_with_enough_balance_is_accepted() {
     let mut test = ExecutionTestBuilder::new().build();
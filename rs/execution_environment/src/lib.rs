@@ -2,6 +2,7 @@ mod anonymous_query_handler;
 mod bitcoin;
 mod canister_manager;
 mod canister_settings;
+mod dry_run_handler;
 pub mod execution;
 mod execution_environment;
 mod execution_environment_metrics;
@@ -16,6 +17,7 @@ mod types;
 pub mod util;
 
 use crate::anonymous_query_handler::AnonymousQueryHandler;
+use crate::dry_run_handler::DryRunHandler;
 use crate::ingress_filter::IngressFilterServiceImpl;
 pub use execution_environment::{
     as_num_instructions, as_round_instructions, execute_canister, CompilationCostHandling,
@@ -26,7 +28,7 @@ pub use hypervisor::{Hypervisor, HypervisorMetrics};
 use ic_base_types::PrincipalId;
 use ic_config::{execution_environment::Config, subnet_config::SchedulerConfig};
 use ic_cycles_account_manager::CyclesAccountManager;
-use ic_interfaces::execution_environment::AnonymousQueryService;
+use ic_interfaces::execution_environment::{AnonymousQueryService, DryRunExecutionService};
 use ic_interfaces::execution_environment::{
     IngressFilter, IngressFilterService, IngressHistoryReader, IngressHistoryWriter,
     QueryExecutionService, QueryHandler, Scheduler,
@@ -87,6 +89,7 @@ pub struct ExecutionServices {
     pub sync_query_handler: Arc<dyn QueryHandler<State = ReplicatedState>>,
     pub async_query_handler: QueryExecutionService,
     pub anonymous_query_handler: AnonymousQueryService,
+    pub dry_run_handler: DryRunExecutionService,
     pub scheduler: Box<dyn Scheduler<State = ReplicatedState>>,
     pub query_stats_payload_builder: QueryStatsPayloadBuilderParams,
 }
@@ -180,6 +183,12 @@ impl ExecutionServices {
             Arc::clone(&exec_env),
             ingress_filter_metrics.clone(),
         );
+        let dry_run_handler = DryRunHandler::new_service(
+            query_scheduler.clone(),
+            Arc::clone(&state_reader),
+            Arc::clone(&exec_env),
+            config.max_instructions_per_dry_run,
+        );
         let anonymous_query_handler = AnonymousQueryHandler::new_service(
             query_scheduler,
             Arc::clone(&state_reader),
@@ -209,6 +218,7 @@ impl ExecutionServices {
             sync_query_handler,
             async_query_handler,
             anonymous_query_handler,
+            dry_run_handler,
             scheduler,
             query_stats_payload_builder,
         }
@@ -27,7 +27,7 @@ use ic_types::{
     artifact::UnvalidatedArtifactMutation,
     artifact_kind::IngressArtifact,
     malicious_flags::MaliciousFlags,
-    messages::{SignedIngress, SignedIngressContent, SignedRequestBytes},
+    messages::{MessageId, SignedIngress, SignedIngressContent, SignedRequestBytes},
     CanisterId, CountBytes, NodeId, RegistryVersion, SubnetId,
 };
 use std::convert::{Infallible, TryInto};
@@ -174,24 +174,18 @@ fn get_registry_data(
     Ok((settings, provisional_whitelist))
 }
 
-/// Handles a call to /api/v2/canister/../call
-impl Service<Request<Bytes>> for CallService {
-    type Response = Response<Body>;
-    type Error = Infallible;
+impl CallService {
+    /// Validates the ingress message in `request`, including its signature and
+    /// whether the canister accepts it. Returns the message, or the response
+    /// for the client if the message was rejected.
+    ///
+    /// Shared by all endpoints that take ingress messages, so that all of them
+    /// apply exactly the same validation.
     #[allow(clippy::type_complexity)]
-    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
-
-    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Poll::Ready(Ok(()))
-    }
-
-    fn call(&mut self, request: Request<Bytes>) -> Self::Future {
-        // Actual parsing.
-        self.metrics
-            .request_body_size_bytes
-            .with_label_values(&[ApiReqType::Call.into(), LABEL_UNKNOWN])
-            .observe(request.body().len() as f64);
-
+    pub(crate) fn validate(
+        &self,
+        request: Request<Bytes>,
+    ) -> Pin<Box<dyn Future<Output = Result<SignedIngress, Response<Body>>> + Send>> {
         let (mut parts, body) = request.into_parts();
         let msg: SignedIngress = match SignedRequestBytes::from(body.to_vec()).try_into() {
             Ok(msg) => msg,
@@ -200,7 +194,7 @@ impl Service<Request<Bytes>> for CallService {
                     StatusCode::BAD_REQUEST,
                     format!("Could not parse body as call message: {}", e),
                 );
-                return Box::pin(async move { Ok(res) });
+                return Box::pin(async move { Err(res) });
             }
         };
 
@@ -211,7 +205,7 @@ impl Service<Request<Bytes>> for CallService {
                     self.log,
                     "Effective canister ID is not attached to call request. This is a bug."
                 );
-                return Box::pin(async move { Ok(res) });
+                return Box::pin(async move { Err(res) });
             }
         };
 
@@ -231,7 +225,7 @@ impl Service<Request<Bytes>> for CallService {
                     effective_canister_id
                 ),
             );
-            return Box::pin(async move { Ok(res) });
+            return Box::pin(async move { Err(res) });
         }
 
         let message_id = msg.id();
@@ -244,7 +238,7 @@ impl Service<Request<Bytes>> for CallService {
         ) {
             Ok((s, p)) => (s, p),
             Err(HttpError { status, message }) => {
-                return Box::pin(async move { Ok(make_plaintext_response(status, message)) });
+                return Box::pin(async move { Err(make_plaintext_response(status, message)) });
             }
         };
         if msg.count_bytes() > ingress_registry_settings.max_ingress_bytes_per_message {
@@ -257,22 +251,18 @@ impl Service<Request<Bytes>> for CallService {
                     ingress_registry_settings.max_ingress_bytes_per_message
                 ),
             );
-            return Box::pin(async move { Ok(res) });
+            return Box::pin(async move { Err(res) });
         }
 
-        let ingress_tx = self.ingress_tx.clone();
         let ingress_filter = self.ingress_filter.clone();
-        let log = self.log.clone();
         let validator_executor = self.validator_executor.clone();
-        let node_id = self.node_id;
-        let ingress_throttler = self.ingress_throttler.clone();
         Box::pin(async move {
             if let Err(http_err) = validator_executor
                 .validate_request(msg.as_ref().clone(), registry_version)
                 .await
             {
                 let res = make_plaintext_response(http_err.status, http_err.message);
-                return Ok(res);
+                return Err(res);
             }
 
             match ingress_filter
@@ -281,11 +271,40 @@ impl Service<Request<Bytes>> for CallService {
             {
                 Err(_) => panic!("Can't panic on Infallible"),
                 Ok(Err(err)) => {
-                    return Ok(make_response(err));
+                    return Err(make_response(err));
                 }
                 Ok(Ok(())) => (),
             }
 
+            Ok(msg)
+        })
+    }
+
+    /// Validates the ingress message in `request` and submits it to the ingress
+    /// pool. Returns the ID of the submitted message, or the response for the
+    /// client if the message was rejected.
+    ///
+    /// Shared by the asynchronous (`/api/v2`) and the synchronous (`/api/v3`)
+    /// call endpoints.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn validate_and_submit(
+        &self,
+        request: Request<Bytes>,
+    ) -> Pin<Box<dyn Future<Output = Result<MessageId, Response<Body>>> + Send>> {
+        // Actual parsing.
+        self.metrics
+            .request_body_size_bytes
+            .with_label_values(&[ApiReqType::Call.into(), LABEL_UNKNOWN])
+            .observe(request.body().len() as f64);
+
+        let validation = self.validate(request);
+        let ingress_tx = self.ingress_tx.clone();
+        let log = self.log.clone();
+        let node_id = self.node_id;
+        let ingress_throttler = self.ingress_throttler.clone();
+        Box::pin(async move {
+            let msg = validation.await?;
+            let message_id = msg.id();
             let ingress_log_entry = msg.log_entry();
 
             let is_overloaded = ingress_throttler.read().unwrap().exceeds_threshold()
@@ -293,28 +312,49 @@ impl Service<Request<Bytes>> for CallService {
                     .try_send(UnvalidatedArtifactMutation::Insert((msg, node_id)))
                     .is_err();
 
-            let response = if is_overloaded {
-                make_plaintext_response(
+            if is_overloaded {
+                return Err(make_plaintext_response(
                     StatusCode::TOO_MANY_REQUESTS,
                     "Service is overloaded, try again later.".to_string(),
-                )
-            } else {
-                // We're pretty much done, just need to send the message to ingress and
-                // make_response to the client
-                info_sample!(
-                    "message_id" => &message_id,
-                    log,
-                    "ingress_message_submit";
-                    ingress_message => ingress_log_entry
-                );
-                make_accepted_response()
-            };
-            Ok(response)
+                ));
+            }
+
+            // We're pretty much done, just need to send the message to ingress and
+            // make_response to the client
+            info_sample!(
+                "message_id" => &message_id,
+                log,
+                "ingress_message_submit";
+                ingress_message => ingress_log_entry
+            );
+            Ok(message_id)
+        })
+    }
+}
+
+/// Handles a call to /api/v2/canister/../call
+impl Service<Request<Bytes>> for CallService {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Bytes>) -> Self::Future {
+        let submission = self.validate_and_submit(request);
+        Box::pin(async move {
+            Ok(match submission.await {
+                Ok(_message_id) => make_accepted_response(),
+                Err(response) => response,
+            })
         })
     }
 }
 
-fn make_accepted_response() -> Response<Body> {
+pub(crate) fn make_accepted_response() -> Response<Body> {
     let mut response = Response::new(Body::from(""));
     *response.status_mut() = StatusCode::ACCEPTED;
     *response.headers_mut() = get_cors_headers();
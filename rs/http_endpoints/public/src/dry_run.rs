@@ -0,0 +1,194 @@
+//! Module that deals with requests to /api/v2/canister/.../dry_run
+
+use crate::{
+    call::CallService,
+    common::{cbor_response, make_plaintext_response},
+    metrics::LABEL_UNKNOWN,
+    types::ApiReqType,
+    EndpointService, HttpHandlerMetrics,
+};
+use bytes::Bytes;
+use http::Request;
+use hyper::{Body, Response, StatusCode};
+use ic_config::http_handler::Config;
+use ic_interfaces::execution_environment::{DryRunExecutionService, QueryExecutionError};
+use ic_types::UserId;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{
+    limit::concurrency::GlobalConcurrencyLimitLayer, util::BoxCloneService, Service,
+    ServiceBuilder, ServiceExt,
+};
+
+#[derive(Clone)]
+pub(crate) struct DryRunService {
+    metrics: HttpHandlerMetrics,
+    call_service: CallService,
+    dry_run_execution_service: DryRunExecutionService,
+    rate_limiter: Arc<Mutex<SenderRateLimiter>>,
+}
+
+/// Limits the number of dry runs per sender within fixed windows of time.
+///
+/// The counters of all senders are dropped at the start of each window, so
+/// only the senders of the current window are kept track of. Anonymous
+/// requests all count towards the limit of the anonymous sender.
+struct SenderRateLimiter {
+    max_requests_per_sender: u64,
+    window: Duration,
+    window_start: Instant,
+    requests_per_sender: HashMap<UserId, u64>,
+}
+
+impl SenderRateLimiter {
+    fn new(max_requests_per_sender: u64, window: Duration) -> Self {
+        Self {
+            max_requests_per_sender,
+            window,
+            window_start: Instant::now(),
+            requests_per_sender: HashMap::new(),
+        }
+    }
+
+    /// Counts a request of `sender` at `now`. Returns false if the sender has
+    /// already used up its requests in the current window.
+    fn try_acquire(&mut self, sender: UserId, now: Instant) -> bool {
+        if now.saturating_duration_since(self.window_start) >= self.window {
+            self.window_start = now;
+            self.requests_per_sender.clear();
+        }
+        let requests = self.requests_per_sender.entry(sender).or_default();
+        if *requests >= self.max_requests_per_sender {
+            return false;
+        }
+        *requests += 1;
+        true
+    }
+}
+
+impl DryRunService {
+    pub(crate) fn new_service(
+        config: Config,
+        metrics: HttpHandlerMetrics,
+        call_service: CallService,
+        dry_run_execution_service: DryRunExecutionService,
+    ) -> EndpointService {
+        let base_service = Self {
+            metrics,
+            call_service,
+            dry_run_execution_service,
+            rate_limiter: Arc::new(Mutex::new(SenderRateLimiter::new(
+                config.max_dry_run_requests_per_sender,
+                Duration::from_secs(config.dry_run_rate_limit_window_seconds),
+            ))),
+        };
+        BoxCloneService::new(
+            ServiceBuilder::new()
+                .layer(GlobalConcurrencyLimitLayer::new(
+                    config.max_dry_run_concurrent_requests,
+                ))
+                .service(base_service),
+        )
+    }
+}
+
+/// Handles a call to /api/v2/canister/../dry_run
+///
+/// The request body is a signed ingress message, exactly as for
+/// /api/v2/canister/../call, and it is validated the same way. Instead of
+/// being submitted, the update call is executed against the latest certified
+/// state in a sandbox whose state is thrown away afterwards. The reply or the
+/// reject is returned along with the instructions used and an estimate of the
+/// cycles that the call would cost.
+///
+/// Each sender can only make a limited number of dry runs per window of time,
+/// see `Config::max_dry_run_requests_per_sender`.
+impl Service<Request<Bytes>> for DryRunService {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Bytes>) -> Self::Future {
+        self.metrics
+            .request_body_size_bytes
+            .with_label_values(&[ApiReqType::DryRun.into(), LABEL_UNKNOWN])
+            .observe(request.body().len() as f64);
+
+        let validation = self.call_service.validate(request);
+        let metrics = self.metrics.clone();
+        let dry_run_execution_service = self.dry_run_execution_service.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        Box::pin(async move {
+            let msg = match validation.await {
+                Ok(msg) => msg,
+                Err(response) => return Ok(response),
+            };
+
+            // The sender is only known to be genuine once the request is
+            // validated, so the limit is checked afterwards.
+            let within_limit = rate_limiter
+                .lock()
+                .unwrap()
+                .try_acquire(msg.sender(), Instant::now());
+            if !within_limit {
+                return Ok(make_plaintext_response(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "Too many dry runs from this sender, try again later.".to_string(),
+                ));
+            }
+
+            let dry_run_response = match dry_run_execution_service
+                .oneshot(msg.content().clone())
+                .await?
+            {
+                Ok((response, _time)) => response,
+                Err(QueryExecutionError::CertifiedStateUnavailable) => {
+                    return Ok(make_plaintext_response(
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        "Certified state unavailable. Please try again.".to_string(),
+                    ))
+                }
+            };
+
+            let (resp, body_size) = cbor_response(&dry_run_response);
+            metrics
+                .response_body_size_bytes
+                .with_label_values(&[ApiReqType::DryRun.into()])
+                .observe(body_size as f64);
+            Ok(resp)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_test_utilities::types::ids::user_test_id;
+
+    #[test]
+    fn test_sender_rate_limiter_limits_each_sender_per_window() {
+        let start = Instant::now();
+        let window = Duration::from_secs(10);
+        let mut rate_limiter = SenderRateLimiter::new(2, window);
+
+        assert!(rate_limiter.try_acquire(user_test_id(1), start));
+        assert!(rate_limiter.try_acquire(user_test_id(1), start));
+        assert!(!rate_limiter.try_acquire(user_test_id(1), start));
+        // Other senders have their own limit.
+        assert!(rate_limiter.try_acquire(user_test_id(2), start));
+
+        // The limit is reset in the next window.
+        assert!(!rate_limiter.try_acquire(user_test_id(1), start + window / 2));
+        assert!(rate_limiter.try_acquire(user_test_id(1), start + window));
+    }
+}
@@ -9,12 +9,14 @@ mod body;
 mod catch_up_package;
 mod common;
 mod dashboard;
+mod dry_run;
 mod health_status_refresher;
 mod pprof;
 mod query;
 mod read_state;
 mod state_reader_executor;
 mod status;
+mod sync_call;
 mod threads;
 mod types;
 
@@ -42,6 +44,7 @@ use crate::{
         map_box_error_to_response,
     },
     dashboard::DashboardService,
+    dry_run::DryRunService,
     health_status_refresher::HealthStatusRefreshLayer,
     metrics::{
         LABEL_REQUEST_TYPE, LABEL_STATUS, REQUESTS_LABEL_NAMES, REQUESTS_NUM_LABELS, STATUS_ERROR,
@@ -51,6 +54,7 @@ use crate::{
     read_state::subnet::SubnetReadStateService,
     state_reader_executor::StateReaderExecutor,
     status::StatusService,
+    sync_call::SyncCallService,
     types::*,
 };
 use byte_unit::Byte;
@@ -68,7 +72,7 @@ use ic_crypto_utils_threshold_sig_der::parse_threshold_sig_key_from_der;
 use ic_interfaces::{
     consensus_pool::ConsensusPoolCache,
     crypto::BasicSigner,
-    execution_environment::{IngressFilterService, QueryExecutionService},
+    execution_environment::{DryRunExecutionService, IngressFilterService, QueryExecutionService},
     ingress_pool::IngressPoolThrottler,
 };
 use ic_interfaces_registry::RegistryClient;
@@ -130,6 +134,8 @@ pub(crate) type EndpointService = BoxCloneService<Request<Bytes>, Response<Body>
 #[derive(Clone)]
 struct HttpHandler {
     call_service: EndpointService,
+    sync_call_service: EndpointService,
+    dry_run_service: EndpointService,
     query_service: EndpointService,
     catchup_service: EndpointService,
     dashboard_service: EndpointService,
@@ -260,6 +266,7 @@ pub fn start_server(
     config: Config,
     ingress_filter: IngressFilterService,
     query_execution_service: QueryExecutionService,
+    dry_run_execution_service: DryRunExecutionService,
     ingress_throttler: Arc<RwLock<dyn IngressPoolThrottler + Send + Sync>>,
     ingress_tx: Sender<UnvalidatedArtifactMutation<IngressArtifact>>,
     state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
@@ -297,26 +304,39 @@ pub fn start_server(
     let health_status = Arc::new(AtomicCell::new(ReplicaHealthStatus::Starting));
     let state_reader_clone = state_reader.clone();
     let state_reader_executor = StateReaderExecutor::new(state_reader);
+    let base_call_service = CallServiceBuilder::builder(
+        node_id,
+        subnet_id,
+        registry_client.clone(),
+        ingress_verifier.clone(),
+        ingress_filter,
+        ingress_throttler,
+        ingress_tx,
+    )
+    .with_logger(log.clone())
+    .with_metrics(metrics.clone())
+    .with_malicious_flags(malicious_flags.clone())
+    .build();
     let call_service = BoxCloneService::new(
         ServiceBuilder::new()
             .layer(GlobalConcurrencyLimitLayer::new(
                 config.max_call_concurrent_requests,
             ))
-            .service(
-                CallServiceBuilder::builder(
-                    node_id,
-                    subnet_id,
-                    registry_client.clone(),
-                    ingress_verifier.clone(),
-                    ingress_filter,
-                    ingress_throttler,
-                    ingress_tx,
-                )
-                .with_logger(log.clone())
-                .with_metrics(metrics.clone())
-                .with_malicious_flags(malicious_flags.clone())
-                .build(),
-            ),
+            .service(base_call_service.clone()),
+    );
+    let sync_call_service = SyncCallService::new_service(
+        config.clone(),
+        log.clone(),
+        metrics.clone(),
+        base_call_service.clone(),
+        Arc::clone(&delegation_from_nns),
+        state_reader_executor.clone(),
+    );
+    let dry_run_service = DryRunService::new_service(
+        config.clone(),
+        metrics.clone(),
+        base_call_service,
+        dry_run_execution_service,
     );
     let query_service = BoxCloneService::new(
         ServiceBuilder::new()
@@ -417,6 +437,8 @@ pub fn start_server(
 
     let http_handler = HttpHandler {
         call_service,
+        sync_call_service,
+        dry_run_service,
         query_service,
         status_service,
         catchup_service,
@@ -693,6 +715,8 @@ async fn make_router(
     (mut req, mut timer): RequestWithTimer,
 ) -> ResponseWithTimer {
     let call_service = http_handler.call_service.clone();
+    let sync_call_service = http_handler.sync_call_service.clone();
+    let dry_run_service = http_handler.dry_run_service.clone();
     let query_service = http_handler.query_service.clone();
     let status_service = http_handler.status_service.clone();
     let catch_up_package_service = http_handler.catchup_service.clone();
@@ -741,6 +765,26 @@ async fn make_router(
                             ),
                         )
                     }
+                    ["", "api", "v3", "canister", effective_canister_id, "call"] => {
+                        timer.set_label(LABEL_REQUEST_TYPE, ApiReqType::SyncCall.into());
+                        (
+                            sync_call_service,
+                            Some(
+                                PrincipalId::from_str(effective_canister_id)
+                                    .map_err(|err| (effective_canister_id, err.to_string())),
+                            ),
+                        )
+                    }
+                    ["", "api", "v2", "canister", effective_canister_id, "dry_run"] => {
+                        timer.set_label(LABEL_REQUEST_TYPE, ApiReqType::DryRun.into());
+                        (
+                            dry_run_service,
+                            Some(
+                                PrincipalId::from_str(effective_canister_id)
+                                    .map_err(|err| (effective_canister_id, err.to_string())),
+                            ),
+                        )
+                    }
                     ["", "api", "v2", "canister", effective_canister_id, "query"] => {
                         timer.set_label(LABEL_REQUEST_TYPE, ApiReqType::Query.into());
                         (
@@ -30,6 +30,7 @@ pub struct HttpHandlerMetrics {
     pub health_status_transitions_total: IntCounterVec,
     pub connection_setup_duration: HistogramVec,
     pub connection_duration: HistogramVec,
+    pub sync_call_certificate_timeouts_total: IntCounter,
 }
 
 // There is a mismatch between the labels and the public spec.
@@ -92,6 +93,10 @@ impl HttpHandlerMetrics {
                 decimal_buckets(-2, 4),
                 &[LABEL_STATUS, LABEL_PROTOCOL],
             ),
+            sync_call_certificate_timeouts_total: metrics_registry.int_counter(
+                "replica_http_sync_call_certificate_timeouts_total",
+                "Number of synchronous calls answered with `202 Accepted`, because the ingress message did not reach a terminal status in time."
+            ),
         }
     }
 }
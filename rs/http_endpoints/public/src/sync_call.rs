@@ -0,0 +1,208 @@
+//! Module that deals with requests to /api/v3/canister/.../call
+
+use crate::{
+    call::{make_accepted_response, CallService},
+    common::{cbor_response, into_cbor, make_plaintext_response},
+    state_reader_executor::StateReaderExecutor,
+    types::ApiReqType,
+    EndpointService, HttpError, HttpHandlerMetrics,
+};
+use bytes::Bytes;
+use http::Request;
+use hyper::{Body, Response, StatusCode};
+use ic_config::http_handler::Config;
+use ic_crypto_tree_hash::{
+    sparse_labeled_tree_from_paths, Label, LabeledTree, MixedHashTree, Path,
+};
+use ic_logger::{info, ReplicaLogger};
+use ic_types::{
+    consensus::certification::Certification,
+    ingress::IngressStatus,
+    messages::{Blob, Certificate, CertificateDelegation, HttpSyncCallResponse, MessageId},
+    Height,
+};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+use tower::{
+    limit::concurrency::GlobalConcurrencyLimitLayer, util::BoxCloneService, Service, ServiceBuilder,
+};
+
+/// How often the latest certified height is checked while waiting for an
+/// ingress message to reach a terminal status. Checking the height is cheap;
+/// the certified state is only read once the height has advanced.
+const CERTIFIED_HEIGHT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Clone)]
+pub(crate) struct SyncCallService {
+    log: ReplicaLogger,
+    metrics: HttpHandlerMetrics,
+    call_service: CallService,
+    delegation_from_nns: Arc<RwLock<Option<CertificateDelegation>>>,
+    state_reader_executor: StateReaderExecutor,
+    ingress_message_certificate_timeout: Duration,
+}
+
+impl SyncCallService {
+    pub(crate) fn new_service(
+        config: Config,
+        log: ReplicaLogger,
+        metrics: HttpHandlerMetrics,
+        call_service: CallService,
+        delegation_from_nns: Arc<RwLock<Option<CertificateDelegation>>>,
+        state_reader_executor: StateReaderExecutor,
+    ) -> EndpointService {
+        let base_service = Self {
+            log,
+            metrics,
+            call_service,
+            delegation_from_nns,
+            state_reader_executor,
+            ingress_message_certificate_timeout: Duration::from_secs(
+                config.ingress_message_certificate_timeout_seconds,
+            ),
+        };
+        BoxCloneService::new(
+            ServiceBuilder::new()
+                .layer(GlobalConcurrencyLimitLayer::new(
+                    config.max_sync_call_concurrent_requests,
+                ))
+                .service(base_service),
+        )
+    }
+}
+
+/// Handles a call to /api/v3/canister/../call
+///
+/// The ingress message is validated and submitted exactly like for
+/// /api/v2/canister/../call. Then, instead of answering `202 Accepted` right
+/// away, the certified state is watched until the message reaches a terminal
+/// status, and the certificate of its `request_status` subtree is returned.
+/// If that takes longer than `ingress_message_certificate_timeout_seconds`,
+/// `202 Accepted` is returned after all, and the client polls `read_state`.
+impl Service<Request<Bytes>> for SyncCallService {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Bytes>) -> Self::Future {
+        let submission = self.call_service.validate_and_submit(request);
+
+        let log = self.log.clone();
+        let metrics = self.metrics.clone();
+        let delegation_from_nns = self.delegation_from_nns.clone();
+        let state_reader_executor = self.state_reader_executor.clone();
+        let ingress_message_certificate_timeout = self.ingress_message_certificate_timeout;
+        Box::pin(async move {
+            let message_id = match submission.await {
+                Ok(message_id) => message_id,
+                Err(response) => return Ok(response),
+            };
+
+            let (tree, certification) = match timeout(
+                ingress_message_certificate_timeout,
+                wait_for_certified_terminal_status(&state_reader_executor, &message_id),
+            )
+            .await
+            {
+                Ok(Ok(certified_request_status)) => certified_request_status,
+                Ok(Err(HttpError { status, message })) => {
+                    return Ok(make_plaintext_response(status, message))
+                }
+                Err(_elapsed) => {
+                    info!(
+                        every_n_seconds => 30,
+                        log,
+                        "Ingress message {} did not reach a terminal status within {:?}, falling back to 202 Accepted.",
+                        message_id,
+                        ingress_message_certificate_timeout
+                    );
+                    metrics.sync_call_certificate_timeouts_total.inc();
+                    return Ok(make_accepted_response());
+                }
+            };
+
+            // The delegation is read only now, since it may have been loaded
+            // while waiting.
+            let delegation_from_nns = delegation_from_nns.read().unwrap().clone();
+            let signature = certification.signed.signature.signature.get().0;
+            let res = HttpSyncCallResponse::Replied {
+                certificate: Blob(into_cbor(&Certificate {
+                    tree,
+                    signature: Blob(signature),
+                    delegation: delegation_from_nns,
+                })),
+            };
+            let (resp, body_size) = cbor_response(&res);
+            metrics
+                .response_body_size_bytes
+                .with_label_values(&[ApiReqType::SyncCall.into()])
+                .observe(body_size as f64);
+            Ok(resp)
+        })
+    }
+}
+
+/// Returns the labeled tree of the paths that are certified in the response to
+/// a synchronous call: the `request_status` subtree of the message, and `time`.
+fn request_status_labeled_tree(message_id: &MessageId) -> LabeledTree<()> {
+    let paths = [
+        Path::new(vec![
+            Label::from("request_status"),
+            Label::from(message_id.as_bytes()),
+        ]),
+        Path::from(Label::from("time")),
+    ];
+    sparse_labeled_tree_from_paths(&paths).expect("Paths of depth 2 are never too long.")
+}
+
+/// Waits until the ingress message with `message_id` has a terminal status in
+/// the latest certified state. Then returns the certified `request_status`
+/// subtree of the message, along with the certification.
+///
+/// Never returns if the message does not reach a terminal status, so callers
+/// have to bound the wait.
+async fn wait_for_certified_terminal_status(
+    state_reader_executor: &StateReaderExecutor,
+    message_id: &MessageId,
+) -> Result<(MixedHashTree, Certification), HttpError> {
+    let labeled_tree = request_status_labeled_tree(message_id);
+    let mut last_checked_height: Option<Height> = None;
+
+    loop {
+        if last_checked_height != Some(state_reader_executor.latest_certified_height()) {
+            if let Some(certified_state_reader) =
+                state_reader_executor.get_certified_state_snapshot().await?
+            {
+                last_checked_height = Some(certified_state_reader.get_height());
+                let is_terminal = match certified_state_reader
+                    .get_state()
+                    .get_ingress_status(message_id)
+                {
+                    IngressStatus::Known { state, .. } => state.is_terminal(),
+                    IngressStatus::Unknown => false,
+                };
+                if is_terminal {
+                    return certified_state_reader
+                        .read_certified_state(&labeled_tree)
+                        .ok_or_else(|| HttpError {
+                            status: StatusCode::SERVICE_UNAVAILABLE,
+                            message: "Certified state is not available yet. Please try again..."
+                                .to_string(),
+                        });
+                }
+            }
+        }
+
+        sleep(CERTIFIED_HEIGHT_POLL_INTERVAL).await;
+    }
+}
@@ -10,6 +10,10 @@ use strum::IntoStaticStr;
 pub(crate) enum ApiReqType {
     /// `call`
     Call,
+    /// `call` via `/api/v3`, which waits for the certified response.
+    SyncCall,
+    /// `dry_run` of a `call`, which is executed but not committed.
+    DryRun,
     /// `query`
     Query,
     /// `read_state`
@@ -36,6 +40,8 @@ mod tests {
     fn test_label_values_do_not_change() {
         type StaticStr = &'static str;
         assert_eq!(StaticStr::from(ApiReqType::Call), "call");
+        assert_eq!(StaticStr::from(ApiReqType::SyncCall), "sync_call");
+        assert_eq!(StaticStr::from(ApiReqType::DryRun), "dry_run");
         assert_eq!(StaticStr::from(ApiReqType::Query), "query");
         assert_eq!(StaticStr::from(ApiReqType::ReadState), "read_state");
         assert_eq!(StaticStr::from(ApiReqType::Status), "status");
@@ -11,7 +11,10 @@ use ic_error_types::UserError;
 use ic_http_endpoints_public::start_server;
 use ic_interfaces::{
     consensus_pool::ConsensusPoolCache,
-    execution_environment::{IngressFilterService, QueryExecutionResponse, QueryExecutionService},
+    execution_environment::{
+        DryRunExecutionService, IngressFilterService, QueryExecutionError, QueryExecutionResponse,
+        QueryExecutionService,
+    },
     ingress_pool::IngressPoolThrottler,
 };
 use ic_interfaces_mocks::consensus_pool::MockConsensusPoolCache;
@@ -404,6 +407,7 @@ pub struct HttpEndpointBuilder {
     registry_client: Arc<dyn RegistryClient>,
     delegation_from_nns: Option<CertificateDelegation>,
     pprof_collector: Arc<dyn PprofCollector>,
+    dry_run_execution_service: DryRunExecutionService,
 }
 
 impl HttpEndpointBuilder {
@@ -416,6 +420,11 @@ impl HttpEndpointBuilder {
             registry_client: Arc::new(basic_registry_client()),
             delegation_from_nns: None,
             pprof_collector: Arc::new(Pprof),
+            dry_run_execution_service: BoxCloneService::new(tower::service_fn(
+                |_: SignedIngressContent| async {
+                    Ok::<_, Infallible>(Err(QueryExecutionError::CertifiedStateUnavailable))
+                },
+            )),
         }
     }
 
@@ -450,6 +459,14 @@ impl HttpEndpointBuilder {
         self
     }
 
+    pub fn with_dry_run_execution_service(
+        mut self,
+        dry_run_execution_service: DryRunExecutionService,
+    ) -> Self {
+        self.dry_run_execution_service = dry_run_execution_service;
+        self
+    }
+
     pub fn run(
         self,
     ) -> (
@@ -481,6 +498,7 @@ impl HttpEndpointBuilder {
             self.config,
             ingress_filter,
             query_exe,
+            self.dry_run_execution_service,
             Arc::new(RwLock::new(ingress_pool_throtller)),
             ingress_tx,
             self.state_manager,
@@ -5,7 +5,8 @@ pub mod common;
 
 use crate::common::{
     create_conn_and_send_request, default_get_latest_state, default_latest_certified_height,
-    get_free_localhost_socket_addr, wait_for_status_healthy, HttpEndpointBuilder,
+    default_read_certified_state, get_free_localhost_socket_addr, wait_for_status_healthy,
+    HttpEndpointBuilder,
 };
 use hyper::{body::to_bytes, Body, Client, Method, Request, StatusCode};
 use ic_agent::{
@@ -59,17 +60,22 @@ use ic_types::{
         },
         CombinedThresholdSig, CombinedThresholdSigOf, CryptoHash, CryptoHashOf, Signed,
     },
-    messages::{Blob, CertificateDelegation, HttpQueryResponse, HttpQueryResponseReply},
+    ingress::{IngressState, IngressStatus, WasmResult},
+    messages::{
+        Blob, CertificateDelegation, HttpDryRunResponse, HttpQueryResponse, HttpQueryResponseReply,
+        HttpSyncCallResponse, MessageId, SignedIngressContent,
+    },
     signature::ThresholdSignature,
     time::current_time,
-    CryptoHashOfPartialState, Height, PrincipalId, RegistryVersion,
+    CryptoHashOfPartialState, Height, NumBytes, PrincipalId, RegistryVersion,
 };
 use prost::Message;
 use serde_bytes::ByteBuf;
 use std::{
+    convert::Infallible,
     net::TcpStream,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
 };
@@ -77,7 +83,7 @@ use tokio::{
     runtime::Runtime,
     time::{sleep, Duration},
 };
-use tower::ServiceExt;
+use tower::{util::BoxCloneService, ServiceExt};
 
 #[test]
 fn test_healthy_behind() {
@@ -955,3 +961,313 @@ fn subnet_metrics_not_supported_via_canister_read_state() {
     let response = request(body.as_ref().to_vec());
     assert_eq!(StatusCode::NOT_FOUND, response.status());
 }
+
+/// Sends the signed update call to `/api/v3/canister/<effective_canister_id>/call`.
+async fn send_sync_call(
+    addr: std::net::SocketAddr,
+    effective_canister_id: Principal,
+    signed_update: Vec<u8>,
+) -> hyper::Response<Body> {
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(format!(
+            "http://{}/api/v3/canister/{}/call",
+            addr, effective_canister_id
+        ))
+        .header("Content-Type", "application/cbor")
+        .body(Body::from(signed_update))
+        .expect("request builder");
+    Client::new().request(req).await.unwrap()
+}
+
+/// If the ingress message does not reach a terminal status within
+/// `ingress_message_certificate_timeout_seconds`, the sync call endpoint falls
+/// back to `202 Accepted`, like the asynchronous one.
+#[test]
+fn test_sync_call_falls_back_to_accepted_on_timeout() {
+    let rt = Runtime::new().unwrap();
+    let addr = get_free_localhost_socket_addr();
+    let config = Config {
+        listen_addr: addr,
+        ingress_message_certificate_timeout_seconds: 1,
+        ..Default::default()
+    };
+
+    let (mut ingress_filter, _ingress_rx, _) =
+        HttpEndpointBuilder::new(rt.handle().clone(), config).run();
+
+    rt.spawn(async move {
+        loop {
+            let (_, resp) = ingress_filter.next_request().await.unwrap();
+            resp.send_response(Ok(()))
+        }
+    });
+
+    let agent = Agent::builder()
+        .with_identity(AnonymousIdentity)
+        .with_transport(ReqwestHttpReplicaV2Transport::create(format!("http://{}", addr)).unwrap())
+        .build()
+        .unwrap();
+    let canister = Principal::from_text("223xb-saaaa-aaaaf-arlqa-cai").unwrap();
+    let update = UpdateBuilder::new(&agent, canister, "test".to_string())
+        .with_effective_canister_id(canister)
+        .with_arg(Vec::new())
+        .sign()
+        .unwrap();
+
+    rt.block_on(async {
+        wait_for_status_healthy(&agent).await.unwrap();
+        // The default certified state never learns about the message.
+        let response = send_sync_call(addr, canister, update.signed_update).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    });
+}
+
+/// Once the ingress message has a terminal status in the certified state, the
+/// sync call endpoint returns the certificate right away.
+#[test]
+fn test_sync_call_returns_certificate_for_terminal_status() {
+    let rt = Runtime::new().unwrap();
+    let addr = get_free_localhost_socket_addr();
+    let config = Config {
+        listen_addr: addr,
+        ..Default::default()
+    };
+
+    let agent = Agent::builder()
+        .with_identity(AnonymousIdentity)
+        .with_transport(ReqwestHttpReplicaV2Transport::create(format!("http://{}", addr)).unwrap())
+        .build()
+        .unwrap();
+    let canister = Principal::from_text("223xb-saaaa-aaaaf-arlqa-cai").unwrap();
+    let update = UpdateBuilder::new(&agent, canister, "test".to_string())
+        .with_effective_canister_id(canister)
+        .with_arg(Vec::new())
+        .sign()
+        .unwrap();
+    let message_id = MessageId::from(*update.request_id);
+
+    struct CompletedMessageSnapshot(Arc<ReplicatedState>);
+
+    impl CertifiedStateSnapshot for CompletedMessageSnapshot {
+        type State = ReplicatedState;
+
+        fn get_state(&self) -> &ReplicatedState {
+            &self.0
+        }
+
+        fn get_height(&self) -> Height {
+            Height::from(1)
+        }
+
+        fn read_certified_state(
+            &self,
+            paths: &LabeledTree<()>,
+        ) -> Option<(MixedHashTree, Certification)> {
+            default_read_certified_state(paths)
+                .map(|(_, tree, certification)| (tree, certification))
+        }
+    }
+
+    let mut state = ReplicatedStateBuilder::new().build();
+    state.set_ingress_status(
+        message_id,
+        IngressStatus::Known {
+            receiver: canister_test_id(1).get(),
+            user_id: user_test_id(1),
+            time: mock_time(),
+            state: IngressState::Completed(WasmResult::Reply(b"success".to_vec())),
+        },
+        NumBytes::from(u64::MAX),
+    );
+    let state = Arc::new(state);
+
+    let mut mock_state_manager = MockStateManager::new();
+    mock_state_manager
+        .expect_get_latest_state()
+        .returning(default_get_latest_state);
+    mock_state_manager
+        .expect_read_certified_state()
+        .returning(default_read_certified_state);
+    mock_state_manager
+        .expect_latest_certified_height()
+        .returning(default_latest_certified_height);
+    mock_state_manager
+        .expect_get_certified_state_snapshot()
+        .returning(move || {
+            Some(Box::new(CompletedMessageSnapshot(state.clone()))
+                as Box<dyn CertifiedStateSnapshot<State = ReplicatedState>>)
+        });
+
+    let (mut ingress_filter, _ingress_rx, _) =
+        HttpEndpointBuilder::new(rt.handle().clone(), config)
+            .with_state_manager(mock_state_manager)
+            .run();
+
+    rt.spawn(async move {
+        loop {
+            let (_, resp) = ingress_filter.next_request().await.unwrap();
+            resp.send_response(Ok(()))
+        }
+    });
+
+    rt.block_on(async {
+        wait_for_status_healthy(&agent).await.unwrap();
+        let response = send_sync_call(addr, canister, update.signed_update).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let response: HttpSyncCallResponse = serde_cbor::from_slice(&body).unwrap();
+        assert!(matches!(response, HttpSyncCallResponse::Replied { .. }));
+    });
+}
+
+/// The dry run endpoint returns the outcome of the execution service, and
+/// never submits the ingress message.
+#[test]
+fn test_dry_run_returns_outcome_without_submitting() {
+    let rt = Runtime::new().unwrap();
+    let addr = get_free_localhost_socket_addr();
+    let config = Config {
+        listen_addr: addr,
+        ..Default::default()
+    };
+
+    let dry_run_response = HttpDryRunResponse {
+        outcome: HttpQueryResponse::Replied {
+            reply: HttpQueryResponseReply {
+                arg: Blob(b"success".to_vec()),
+            },
+        },
+        instructions_used: 1_000,
+        estimated_cycles_cost: 590_400,
+    };
+    let expected_response = dry_run_response.clone();
+    let dry_run_execution_service =
+        BoxCloneService::new(tower::service_fn(move |ingress: SignedIngressContent| {
+            assert_eq!(ingress.method_name(), "test");
+            let dry_run_response = dry_run_response.clone();
+            async move { Ok::<_, Infallible>(Ok((dry_run_response, current_time()))) }
+        }));
+
+    let (mut ingress_filter, ingress_rx, _) = HttpEndpointBuilder::new(rt.handle().clone(), config)
+        .with_dry_run_execution_service(dry_run_execution_service)
+        .run();
+
+    rt.spawn(async move {
+        loop {
+            let (_, resp) = ingress_filter.next_request().await.unwrap();
+            resp.send_response(Ok(()))
+        }
+    });
+
+    let agent = Agent::builder()
+        .with_identity(AnonymousIdentity)
+        .with_transport(ReqwestHttpReplicaV2Transport::create(format!("http://{}", addr)).unwrap())
+        .build()
+        .unwrap();
+    let canister = Principal::from_text("223xb-saaaa-aaaaf-arlqa-cai").unwrap();
+    let update = UpdateBuilder::new(&agent, canister, "test".to_string())
+        .with_effective_canister_id(canister)
+        .with_arg(Vec::new())
+        .sign()
+        .unwrap();
+
+    rt.block_on(async {
+        wait_for_status_healthy(&agent).await.unwrap();
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri(format!(
+                "http://{}/api/v2/canister/{}/dry_run",
+                addr, canister
+            ))
+            .header("Content-Type", "application/cbor")
+            .body(Body::from(update.signed_update))
+            .expect("request builder");
+        let response = Client::new().request(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let response: HttpDryRunResponse = serde_cbor::from_slice(&body).unwrap();
+        assert_eq!(response, expected_response);
+    });
+    assert!(ingress_rx.try_recv().is_err());
+}
+
+/// Dry runs beyond the limit per sender are rejected with 429 Too Many
+/// Requests, without reaching the execution service.
+#[test]
+fn test_dry_run_is_rate_limited_per_sender() {
+    let rt = Runtime::new().unwrap();
+    let addr = get_free_localhost_socket_addr();
+    let config = Config {
+        listen_addr: addr,
+        max_dry_run_requests_per_sender: 1,
+        dry_run_rate_limit_window_seconds: 3_600,
+        ..Default::default()
+    };
+
+    let num_dry_runs = Arc::new(AtomicUsize::new(0));
+    let num_dry_runs_clone = num_dry_runs.clone();
+    let dry_run_execution_service =
+        BoxCloneService::new(tower::service_fn(move |_: SignedIngressContent| {
+            num_dry_runs_clone.fetch_add(1, Ordering::SeqCst);
+            let dry_run_response = HttpDryRunResponse {
+                outcome: HttpQueryResponse::Replied {
+                    reply: HttpQueryResponseReply {
+                        arg: Blob(b"success".to_vec()),
+                    },
+                },
+                instructions_used: 1_000,
+                estimated_cycles_cost: 590_400,
+            };
+            async move { Ok::<_, Infallible>(Ok((dry_run_response, current_time()))) }
+        }));
+
+    let (mut ingress_filter, _ingress_rx, _) =
+        HttpEndpointBuilder::new(rt.handle().clone(), config)
+            .with_dry_run_execution_service(dry_run_execution_service)
+            .run();
+
+    rt.spawn(async move {
+        loop {
+            let (_, resp) = ingress_filter.next_request().await.unwrap();
+            resp.send_response(Ok(()))
+        }
+    });
+
+    let agent = Agent::builder()
+        .with_identity(AnonymousIdentity)
+        .with_transport(ReqwestHttpReplicaV2Transport::create(format!("http://{}", addr)).unwrap())
+        .build()
+        .unwrap();
+    let canister = Principal::from_text("223xb-saaaa-aaaaf-arlqa-cai").unwrap();
+    let update = UpdateBuilder::new(&agent, canister, "test".to_string())
+        .with_effective_canister_id(canister)
+        .with_arg(Vec::new())
+        .sign()
+        .unwrap();
+
+    rt.block_on(async {
+        wait_for_status_healthy(&agent).await.unwrap();
+        let dry_run = || {
+            let req = Request::builder()
+                .method(Method::POST)
+                .uri(format!(
+                    "http://{}/api/v2/canister/{}/dry_run",
+                    addr, canister
+                ))
+                .header("Content-Type", "application/cbor")
+                .body(Body::from(update.signed_update.clone()))
+                .expect("request builder");
+            Client::new().request(req)
+        };
+
+        assert_eq!(dry_run().await.unwrap().status(), StatusCode::OK);
+        assert_eq!(
+            dry_run().await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    });
+    assert_eq!(num_dry_runs.load(Ordering::SeqCst), 1);
+}
@@ -14,8 +14,8 @@ use ic_types::{
     crypto::canister_threshold_sig::MasterEcdsaPublicKey,
     ingress::{IngressStatus, WasmResult},
     messages::{
-        AnonymousQuery, AnonymousQueryResponse, CertificateDelegation, HttpQueryResponse,
-        MessageId, SignedIngressContent, UserQuery,
+        AnonymousQuery, AnonymousQueryResponse, CertificateDelegation, HttpDryRunResponse,
+        HttpQueryResponse, MessageId, SignedIngressContent, UserQuery,
     },
     Cycles, ExecutionRound, Height, NumInstructions, NumPages, Randomness, Time,
 };
@@ -438,6 +438,15 @@ pub type QueryExecutionResponse = Result<(HttpQueryResponse, Time), QueryExecuti
 pub type QueryExecutionService =
     BoxCloneService<(UserQuery, Option<CertificateDelegation>), QueryExecutionResponse, Infallible>;
 
+/// The response type to a `call()` request in [`DryRunExecutionService`].
+/// An Ok response contains the outcome of the dry run and the batch time of the state it ran against.
+pub type DryRunExecutionResponse = Result<(HttpDryRunResponse, Time), QueryExecutionError>;
+
+/// Interface for the component to execute update calls in a sandbox, against
+/// the latest certified state, without committing any of their effects.
+pub type DryRunExecutionService =
+    BoxCloneService<SignedIngressContent, DryRunExecutionResponse, Infallible>;
+
 /// Interface for the component to execute queries on canisters.  It can be used
 /// by the HttpHandler and other system components to execute queries.
 pub trait QueryHandler: Send + Sync {
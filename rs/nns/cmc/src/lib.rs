@@ -134,6 +134,9 @@ pub enum NotifyErrorCode {
     RefundFailed = 3,
     /// The subnet selection parameters are set in an invalid way.
     BadSubnetSelection = 4,
+    /// The cycles minting canister failed to pull the payment from the caller's
+    /// account with ICRC-2 `transfer_from`.
+    TransferFromFailed = 5,
 }
 
 impl NotifyError {
@@ -196,6 +199,55 @@ pub struct NotifyMintCyclesSuccess {
     pub balance: Nat,
 }
 
+/// Argument taken by the `create_canister_with_icp` endpoint
+#[derive(Deserialize, CandidType, Clone, Debug, PartialEq, Eq)]
+pub struct CreateCanisterWithIcp {
+    /// The amount of ICP to convert into cycles. The caller must approve the
+    /// cycles minting canister to spend this amount plus the ledger fee.
+    pub amount: Tokens,
+    pub from_subaccount: Option<icrc_ledger_types::icrc1::account::Subaccount>,
+    /// The ledger deduplicates payments with the same `created_at_time`, so
+    /// retrying the call with the same arguments does not pay twice and
+    /// resumes the processing of the original payment. It is required, as
+    /// the payment could not be processed again otherwise.
+    pub created_at_time: u64,
+    /// The controller of the new canister. Defaults to the caller.
+    pub controller: Option<PrincipalId>,
+    pub subnet_selection: Option<SubnetSelection>,
+    pub settings: Option<CanisterSettingsArgs>,
+}
+
+/// Argument taken by the `top_up_with_icp` endpoint
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, PartialEq, Eq)]
+pub struct TopUpWithIcp {
+    /// The amount of ICP to convert into cycles. The caller must approve the
+    /// cycles minting canister to spend this amount plus the ledger fee.
+    pub amount: Tokens,
+    pub from_subaccount: Option<icrc_ledger_types::icrc1::account::Subaccount>,
+    /// The ledger deduplicates payments with the same `created_at_time`, so
+    /// retrying the call with the same arguments does not pay twice and
+    /// resumes the processing of the original payment. It is required, as
+    /// the payment could not be processed again otherwise.
+    pub created_at_time: u64,
+    pub canister_id: CanisterId,
+}
+
+/// Argument taken by the `mint_cycles_with_icp` endpoint
+#[derive(Serialize, Deserialize, CandidType, Clone, Debug, PartialEq, Eq)]
+pub struct MintCyclesWithIcp {
+    /// The amount of ICP to convert into cycles. The caller must approve the
+    /// cycles minting canister to spend this amount plus the ledger fee.
+    pub amount: Tokens,
+    pub from_subaccount: Option<icrc_ledger_types::icrc1::account::Subaccount>,
+    /// The ledger deduplicates payments with the same `created_at_time`, so
+    /// retrying the call with the same arguments does not pay twice and
+    /// resumes the processing of the original payment. It is required, as
+    /// the payment could not be processed again otherwise.
+    pub created_at_time: u64,
+    pub to_subaccount: Option<icrc_ledger_types::icrc1::account::Subaccount>,
+    pub deposit_memo: Option<Vec<u8>>,
+}
+
 /// Argument taken by the cycles ledger's `deposit` endpoint
 #[derive(Serialize, Deserialize, CandidType, Clone, Hash, Debug, PartialEq, Eq)]
 pub struct CyclesLedgerDepositArgs {
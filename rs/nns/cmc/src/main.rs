@@ -1,4 +1,4 @@
-use candid::{candid_method, CandidType, Decode, Encode};
+use candid::{candid_method, CandidType, Decode, Encode, Nat};
 use core::cmp::Ordering;
 use cycles_minting_canister::*;
 use dfn_candid::{candid_one, CandidOne};
@@ -32,6 +32,7 @@ use icp_ledger::{
     Subaccount, Tokens, TransactionNotification, DEFAULT_TRANSFER_FEE,
 };
 use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
 use on_wire::{FromWire, IntoWire, NewType};
 use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use serde::{Deserialize, Serialize};
@@ -1195,6 +1196,21 @@ fn notify_mint_cycles_() {
     over_async(candid_one, notify_mint_cycles)
 }
 
+#[export_name = "canister_update create_canister_with_icp"]
+fn create_canister_with_icp_() {
+    over_async(candid_one, create_canister_with_icp)
+}
+
+#[export_name = "canister_update top_up_with_icp"]
+fn top_up_with_icp_() {
+    over_async(candid_one, top_up_with_icp)
+}
+
+#[export_name = "canister_update mint_cycles_with_icp"]
+fn mint_cycles_with_icp_() {
+    over_async(candid_one, mint_cycles_with_icp)
+}
+
 fn is_transient_error<T>(result: &Result<T, NotifyError>) -> bool {
     if let Err(e) = result {
         return e.is_retriable();
@@ -1227,6 +1243,17 @@ async fn notify_top_up(
     )
     .await?;
 
+    process_top_up_once(block_index, canister_id, from, amount).await
+}
+
+/// Tops up the canister with the payment in the specified block, unless the
+/// payment was already processed.
+async fn process_top_up_once(
+    block_index: BlockIndex,
+    canister_id: CanisterId,
+    from: AccountIdentifier,
+    amount: Tokens,
+) -> Result<Cycles, NotifyError> {
     let maybe_early_result = with_state_mut(|state| {
         state.purge_old_notifications(MAX_NOTIFY_HISTORY);
 
@@ -1304,6 +1331,27 @@ async fn notify_mint_cycles(
     let (amount, from) =
         fetch_transaction(block_index, expected_destination_account, MEMO_MINT_CYCLES).await?;
 
+    process_mint_cycles_once(
+        block_index,
+        to_account,
+        amount,
+        deposit_memo,
+        from,
+        subaccount,
+    )
+    .await
+}
+
+/// Mints cycles for the payment in the specified block, unless the payment was
+/// already processed.
+async fn process_mint_cycles_once(
+    block_index: BlockIndex,
+    to_account: Account,
+    amount: Tokens,
+    deposit_memo: Option<Vec<u8>>,
+    from: AccountIdentifier,
+    subaccount: Subaccount,
+) -> NotifyMintCyclesResult {
     let maybe_early_result = with_state_mut(|state| {
         state.purge_old_notifications(MAX_NOTIFY_HISTORY);
 
@@ -1391,6 +1439,27 @@ async fn notify_create_canister(
     )
     .await?;
 
+    process_create_canister_once(
+        block_index,
+        controller,
+        from,
+        amount,
+        subnet_selection,
+        settings,
+    )
+    .await
+}
+
+/// Creates a canister for the payment in the specified block, unless the
+/// payment was already processed.
+async fn process_create_canister_once(
+    block_index: BlockIndex,
+    controller: PrincipalId,
+    from: AccountIdentifier,
+    amount: Tokens,
+    subnet_selection: Option<SubnetSelection>,
+    settings: Option<CanisterSettingsArgs>,
+) -> Result<CanisterId, NotifyError> {
     let maybe_early_result = with_state_mut(|state| {
         state.purge_old_notifications(MAX_NOTIFY_HISTORY);
 
@@ -1485,6 +1554,184 @@ async fn create_canister(
     }
 }
 
+/// Tops up a canister with the ICP that the cycles minting canister pulls from
+/// the caller's account with ICRC-2 `transfer_from`.
+///
+/// The caller must first approve the cycles minting canister to spend the
+/// `amount` plus the ledger fee.
+#[candid_method(update, rename = "top_up_with_icp")]
+async fn top_up_with_icp(
+    TopUpWithIcp {
+        amount,
+        from_subaccount,
+        created_at_time,
+        canister_id,
+    }: TopUpWithIcp,
+) -> Result<Cycles, NotifyError> {
+    let from = Account {
+        owner: caller().into(),
+        subaccount: from_subaccount,
+    };
+
+    let block_index = transfer_from_caller(
+        from,
+        Subaccount::from(&canister_id),
+        amount,
+        MEMO_TOP_UP_CANISTER,
+        created_at_time,
+    )
+    .await?;
+
+    process_top_up_once(
+        block_index,
+        canister_id,
+        AccountIdentifier::from(from),
+        amount,
+    )
+    .await
+}
+
+/// Mints cycles to the cycles ledger with the ICP that the cycles minting
+/// canister pulls from the caller's account with ICRC-2 `transfer_from`.
+///
+/// The caller must first approve the cycles minting canister to spend the
+/// `amount` plus the ledger fee.
+#[candid_method(update, rename = "mint_cycles_with_icp")]
+async fn mint_cycles_with_icp(
+    MintCyclesWithIcp {
+        amount,
+        from_subaccount,
+        created_at_time,
+        to_subaccount,
+        deposit_memo,
+    }: MintCyclesWithIcp,
+) -> NotifyMintCyclesResult {
+    let from = Account {
+        owner: caller().into(),
+        subaccount: from_subaccount,
+    };
+    let subaccount = Subaccount::from(&caller());
+    let to_account = Account {
+        owner: caller().into(),
+        subaccount: to_subaccount,
+    };
+
+    let block_index =
+        transfer_from_caller(from, subaccount, amount, MEMO_MINT_CYCLES, created_at_time).await?;
+
+    process_mint_cycles_once(
+        block_index,
+        to_account,
+        amount,
+        deposit_memo,
+        AccountIdentifier::from(from),
+        subaccount,
+    )
+    .await
+}
+
+/// Creates a canister with the ICP that the cycles minting canister pulls from
+/// the caller's account with ICRC-2 `transfer_from`.
+///
+/// The caller must first approve the cycles minting canister to spend the
+/// `amount` plus the ledger fee.
+#[candid_method(update, rename = "create_canister_with_icp")]
+async fn create_canister_with_icp(
+    CreateCanisterWithIcp {
+        amount,
+        from_subaccount,
+        created_at_time,
+        controller,
+        subnet_selection,
+        settings,
+    }: CreateCanisterWithIcp,
+) -> Result<CanisterId, NotifyError> {
+    let controller = controller.unwrap_or_else(caller);
+    let from = Account {
+        owner: caller().into(),
+        subaccount: from_subaccount,
+    };
+
+    let block_index = transfer_from_caller(
+        from,
+        Subaccount::from(&controller),
+        amount,
+        MEMO_CREATE_CANISTER,
+        created_at_time,
+    )
+    .await?;
+
+    process_create_canister_once(
+        block_index,
+        controller,
+        AccountIdentifier::from(from),
+        amount,
+        subnet_selection,
+        settings,
+    )
+    .await
+}
+
+/// Pulls the `amount` from the `from` account into the `to_subaccount` of the
+/// cycles minting canister with ICRC-2 `transfer_from`, and returns the index
+/// of the ledger block containing the payment. If the ledger deduplicates the
+/// transfer, returns the index of the block of the original transfer.
+async fn transfer_from_caller(
+    from: Account,
+    to_subaccount: Subaccount,
+    amount: Tokens,
+    memo: Memo,
+    created_at_time: u64,
+) -> Result<BlockIndex, NotifyError> {
+    fn transfer_from_failed(error_message: String) -> NotifyError {
+        NotifyError::Other {
+            error_code: NotifyErrorCode::TransferFromFailed as u64,
+            error_message,
+        }
+    }
+
+    let ledger_canister_id = with_state(|state| state.ledger_canister_id);
+    let arg = TransferFromArgs {
+        spender_subaccount: None,
+        from,
+        to: Account {
+            owner: dfn_core::api::id().get().into(),
+            subaccount: Some(to_subaccount.0),
+        },
+        amount: Nat::from(amount.get_e8s()),
+        fee: None,
+        // The ledger keeps ICRC-1 memos apart from the legacy memo, so the
+        // notify endpoints reject this block and cannot process it again.
+        // Retries have to go through the ledger deduplication instead, which
+        // is why `created_at_time` is required.
+        memo: Some(memo.0.to_le_bytes().to_vec().into()),
+        created_at_time: Some(created_at_time),
+    };
+
+    let result: Result<Result<Nat, TransferFromError>, (Option<i32>, String)> =
+        call_with_cleanup(ledger_canister_id, "icrc2_transfer_from", candid_one, arg).await;
+
+    let block_index = match result.map_err(|(code, err)| {
+        transfer_from_failed(format!(
+            "Ledger rejected transfer_from with code {}: {}",
+            code.unwrap_or_default(),
+            err
+        ))
+    })? {
+        Ok(block_index) => block_index,
+        Err(TransferFromError::Duplicate { duplicate_of }) => duplicate_of,
+        Err(err) => {
+            return Err(transfer_from_failed(format!(
+                "Failed to transfer {} from {}: {:?}",
+                amount, from, err
+            )))
+        }
+    };
+
+    u64::try_from(block_index.0)
+        .map_err(|_| transfer_from_failed("The ledger returned an invalid block index".to_string()))
+}
+
 async fn query_block(block_index: BlockIndex, ledger_id: CanisterId) -> Result<Block, NotifyError> {
     fn failed_to_fetch_block(error_message: String) -> NotifyError {
         NotifyError::Other {
@@ -13,7 +13,10 @@ use async_trait::async_trait;
 use candid::{candid_method, Decode, Encode};
 use dfn_candid::{candid, candid_one};
 use dfn_core::{
-    api::{arg_data, call_with_callbacks, caller, now, reject_message},
+    api::{
+        arg_data, call_with_callbacks, caller, data_certificate, now, reject_message,
+        set_certified_data,
+    },
     over, over_async, println,
 };
 use dfn_protobuf::protobuf;
@@ -48,10 +51,11 @@ use ic_nns_governance::{
         manage_neuron_response, ClaimOrRefreshNeuronFromAccount,
         ClaimOrRefreshNeuronFromAccountResponse, ExecuteNnsFunction,
         GetNeuronsFundAuditInfoRequest, GetNeuronsFundAuditInfoResponse,
-        Governance as GovernanceProto, GovernanceError, ListKnownNeuronsResponse, ListNeurons,
-        ListNeuronsResponse, ListNodeProvidersResponse, ListProposalInfo, ListProposalInfoResponse,
-        ManageNeuron, ManageNeuronResponse, MostRecentMonthlyNodeProviderRewards, NetworkEconomics,
-        Neuron, NeuronInfo, NnsFunction, NodeProvider, Proposal, ProposalInfo, RewardEvent,
+        Governance as GovernanceProto, GovernanceError, ListArchivedProposals,
+        ListArchivedProposalsResponse, ListKnownNeuronsResponse, ListNeurons, ListNeuronsResponse,
+        ListNodeProvidersResponse, ListProposalInfo, ListProposalInfoResponse, ManageNeuron,
+        ManageNeuronResponse, MostRecentMonthlyNodeProviderRewards, NetworkEconomics, Neuron,
+        NeuronInfo, NnsFunction, NodeProvider, Proposal, ProposalInfo, RewardEvent,
         RewardNodeProviders, SettleCommunityFundParticipation,
         SettleNeuronsFundParticipationRequest, SettleNeuronsFundParticipationResponse,
         UpdateNodeProvider, Vote,
@@ -200,6 +204,10 @@ impl Environment for CanisterEnv {
         self.time_warp = new_time_warp;
     }
 
+    fn set_certified_data(&self, data: &[u8]) {
+        set_certified_data(data);
+    }
+
     fn random_u64(&mut self) -> u64 {
         self.rng.next_u64()
     }
@@ -330,6 +338,7 @@ fn canister_init_(init_payload: GovernanceProto) {
         Box::new(IcpLedgerCanister::new(LEDGER_CANISTER_ID)),
         Box::new(CMCCanister::<DfnRuntime>::new()),
     ));
+    governance().recertify_proposal_archive();
 }
 
 #[export_name = "canister_pre_upgrade"]
@@ -374,6 +383,7 @@ fn canister_post_upgrade() {
     ));
 
     validate_stable_storage();
+    governance().recertify_proposal_archive();
 }
 
 #[cfg(feature = "test")]
@@ -646,6 +656,20 @@ fn list_proposals_(req: ListProposalInfo) -> ListProposalInfoResponse {
     governance().list_proposals(&caller(), &req)
 }
 
+#[export_name = "canister_query list_archived_proposals"]
+fn list_archived_proposals() {
+    debug_log("list_archived_proposals");
+    over(candid_one, list_archived_proposals_)
+}
+
+#[candid_method(query, rename = "list_archived_proposals")]
+fn list_archived_proposals_(req: ListArchivedProposals) -> ListArchivedProposalsResponse {
+    ListArchivedProposalsResponse {
+        certificate: data_certificate().unwrap_or_default(),
+        ..governance().list_archived_proposals(&req)
+    }
+}
+
 #[export_name = "canister_query list_neurons"]
 fn list_neurons() {
     debug_log("list_neurons");
@@ -64,10 +64,15 @@ impl Governance {
             if props.len() > max_proposals {
                 for prop_id in props.iter().take(props.len() - max_proposals) {
                     // Check that this proposal can be purged.
-                    if let Some(prop) = self.heap_data.proposals.get(prop_id) {
-                        if prop.can_be_purged(now_seconds, voting_period_seconds) {
-                            self.heap_data.proposals.remove(prop_id);
-                        }
+                    let can_be_purged =
+                        self.heap_data.proposals.get(prop_id).map_or(false, |prop| {
+                            prop.can_be_purged(now_seconds, voting_period_seconds)
+                        });
+                    if can_be_purged {
+                        // Make sure that nothing is lost, in case the proposal
+                        // was not fully archived yet.
+                        self.archive_proposal(*prop_id, usize::MAX);
+                        self.heap_data.proposals.remove(prop_id);
                     }
                 }
             }
@@ -243,6 +243,11 @@ pub struct Neuron {
     /// of the different states.
     #[prost(enumeration = "NeuronType", optional, tag = "22")]
     pub neuron_type: ::core::option::Option<i32>,
+    /// The maturity disbursements that have been initiated but not yet
+    /// finalized, in the order in which they were initiated. See
+    /// \[ManageNeuron::DisburseMaturity\].
+    #[prost(message, repeated, tag = "23")]
+    pub maturity_disbursements_in_progress: ::prost::alloc::vec::Vec<MaturityDisbursement>,
     /// At any time, at most one of `when_dissolved` and
     /// `dissolve_delay` are specified.
     ///
@@ -311,6 +316,29 @@ pub mod neuron {
         DissolveDelaySeconds(u64),
     }
 }
+/// A disbursement of maturity that is waiting for the maturity modulation
+/// window to pass before the corresponding ICP is minted.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MaturityDisbursement {
+    /// The amount of maturity to be disbursed, in "e8s equivalent". The amount
+    /// of ICP that is minted is this amount with the maturity modulation of
+    /// the day of finalization applied.
+    #[prost(uint64, tag = "1")]
+    pub amount_e8s: u64,
+    /// The timestamp, in seconds from the Unix epoch, at which the disbursement
+    /// was initiated.
+    #[prost(uint64, tag = "2")]
+    pub timestamp_of_disbursement_seconds: u64,
+    /// The account to which the ICP will be minted.
+    #[prost(message, optional, tag = "3")]
+    pub account_to_disburse_to: ::core::option::Option<::icp_ledger::protobuf::AccountIdentifier>,
+    /// The timestamp, in seconds from the Unix epoch, after which the
+    /// disbursement can be finalized.
+    #[prost(uint64, tag = "4")]
+    pub finalize_disbursement_timestamp_seconds: u64,
+}
 /// Subset of Neuron that has no collections or big fields that might not exist in most neurons, and
 /// the goal is to keep the size of the struct consistent and can be easily stored in a
 /// StableBTreeMap. For the meaning of each field, see the Neuron struct.
@@ -550,7 +578,7 @@ pub struct Proposal {
     /// take.
     #[prost(
         oneof = "proposal::Action",
-        tags = "10, 12, 13, 14, 15, 16, 17, 18, 19, 21, 22, 23, 24"
+        tags = "10, 12, 13, 14, 15, 16, 17, 18, 19, 21, 22, 23, 24, 25"
     )]
     pub action: ::core::option::Option<proposal::Action>,
 }
@@ -635,6 +663,92 @@ pub mod proposal {
         /// Create a new SNS.
         #[prost(message, tag = "24")]
         CreateServiceNervousSystem(super::CreateServiceNervousSystem),
+        /// Install, reinstall or upgrade the code of an NNS canister.
+        #[prost(message, tag = "25")]
+        InstallCode(super::InstallCode),
+    }
+}
+/// Installs, reinstalls or upgrades the code of an NNS canister controlled by
+/// the NNS root canister. The proposal is executed by the root canister.
+///
+/// The SHA-256 hashes of the wasm module and of the argument are computed by
+/// governance when the proposal is made, so that voters can verify them
+/// without having to download the (potentially large) wasm module. When
+/// proposals are listed, only the hashes are returned.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InstallCode {
+    /// The canister whose code is changed.
+    #[prost(message, optional, tag = "1")]
+    pub canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
+    /// The install mode.
+    #[prost(enumeration = "install_code::CanisterInstallMode", optional, tag = "2")]
+    pub install_mode: ::core::option::Option<i32>,
+    /// The new wasm module.
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub wasm_module: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// The argument passed to the canister's init or post_upgrade method.
+    #[prost(bytes = "vec", optional, tag = "4")]
+    pub arg: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// Whether to skip stopping the canister before installing the code. This
+    /// should only be set for canisters that do not make inter-canister calls.
+    #[prost(bool, optional, tag = "5")]
+    pub skip_stopping_before_installing: ::core::option::Option<bool>,
+    /// The SHA-256 hash of `wasm_module`. Set by governance.
+    #[prost(bytes = "vec", optional, tag = "6")]
+    pub wasm_module_hash: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// The SHA-256 hash of `arg`. Set by governance.
+    #[prost(bytes = "vec", optional, tag = "7")]
+    pub arg_hash: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+}
+/// Nested message and enum types in `InstallCode`.
+pub mod install_code {
+    #[derive(
+        candid::CandidType,
+        candid::Deserialize,
+        serde::Serialize,
+        comparable::Comparable,
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration,
+    )]
+    #[repr(i32)]
+    pub enum CanisterInstallMode {
+        Unspecified = 0,
+        Install = 1,
+        Reinstall = 2,
+        Upgrade = 3,
+    }
+    impl CanisterInstallMode {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                CanisterInstallMode::Unspecified => "CANISTER_INSTALL_MODE_UNSPECIFIED",
+                CanisterInstallMode::Install => "CANISTER_INSTALL_MODE_INSTALL",
+                CanisterInstallMode::Reinstall => "CANISTER_INSTALL_MODE_REINSTALL",
+                CanisterInstallMode::Upgrade => "CANISTER_INSTALL_MODE_UPGRADE",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "CANISTER_INSTALL_MODE_UNSPECIFIED" => Some(Self::Unspecified),
+                "CANISTER_INSTALL_MODE_INSTALL" => Some(Self::Install),
+                "CANISTER_INSTALL_MODE_REINSTALL" => Some(Self::Reinstall),
+                "CANISTER_INSTALL_MODE_UPGRADE" => Some(Self::Upgrade),
+                _ => None,
+            }
+        }
     }
 }
 /// Empty message to use in oneof fields that represent empty
@@ -864,6 +978,22 @@ pub mod manage_neuron {
         #[prost(uint32, optional, tag = "1")]
         pub percentage_to_stake: ::core::option::Option<u32>,
     }
+    /// Disburse the maturity of a neuron to an account as ICP. The maturity
+    /// is deducted from the neuron immediately, and the ICP is minted once
+    /// the maturity modulation window (7 days) has passed, with the maturity
+    /// modulation of that day applied.
+    #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct DisburseMaturity {
+        /// The percentage of maturity to disburse, from 1 to 100 (inclusive).
+        #[prost(uint32, tag = "1")]
+        pub percentage_to_disburse: u32,
+        /// The account to which the ICP will be minted. If not specified, the
+        /// default account of the caller is used.
+        #[prost(message, optional, tag = "2")]
+        pub to_account: ::core::option::Option<::icp_ledger::protobuf::AccountIdentifier>,
+    }
     /// Disburse a portion of this neuron's stake into another neuron.
     /// This allows to split a neuron but with a new dissolve delay
     /// and owned by someone else.
@@ -1011,6 +1141,8 @@ pub mod manage_neuron {
         Merge(Merge),
         #[prost(message, tag = "15")]
         StakeMaturity(StakeMaturity),
+        #[prost(message, tag = "16")]
+        DisburseMaturity(DisburseMaturity),
     }
 }
 /// The response of the ManageNeuron command
@@ -1069,6 +1201,16 @@ pub mod manage_neuron_response {
     #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct DisburseMaturityResponse {
+        /// The amount of maturity that was deducted from the neuron, in "e8s
+        /// equivalent". The amount of ICP that is eventually minted depends on
+        /// the maturity modulation at the time of finalization.
+        #[prost(uint64, tag = "1")]
+        pub amount_disbursed_e8s: u64,
+    }
+    #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct FollowResponse {}
     #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
     #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1155,6 +1297,8 @@ pub mod manage_neuron_response {
         Merge(MergeResponse),
         #[prost(message, tag = "13")]
         StakeMaturity(StakeMaturityResponse),
+        #[prost(message, tag = "14")]
+        DisburseMaturity(DisburseMaturityResponse),
     }
 }
 #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
@@ -2341,7 +2485,7 @@ pub mod governance {
         pub timestamp: u64,
         #[prost(
             oneof = "neuron_in_flight_command::Command",
-            tags = "2, 3, 5, 7, 8, 9, 10, 20, 21"
+            tags = "2, 3, 5, 7, 8, 9, 10, 20, 21, 22"
         )]
         pub command: ::core::option::Option<neuron_in_flight_command::Command>,
     }
@@ -2360,6 +2504,18 @@ pub mod governance {
         #[allow(clippy::derive_partial_eq_without_eq)]
         #[derive(Clone, PartialEq, ::prost::Message)]
         pub struct SyncCommand {}
+        /// The finalization of the oldest maturity disbursement of a neuron.
+        #[derive(
+            candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable,
+        )]
+        #[allow(clippy::derive_partial_eq_without_eq)]
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct FinalizeDisburseMaturity {
+            #[prost(uint64, tag = "1")]
+            pub amount_to_be_disbursed_e8s: u64,
+            #[prost(message, optional, tag = "2")]
+            pub to_account: ::core::option::Option<::icp_ledger::protobuf::AccountIdentifier>,
+        }
         #[derive(
             candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable,
         )]
@@ -2384,6 +2540,8 @@ pub mod governance {
             Spawn(::ic_nns_common::pb::v1::NeuronId),
             #[prost(message, tag = "21")]
             SyncCommand(SyncCommand),
+            #[prost(message, tag = "22")]
+            FinalizeDisburseMaturity(FinalizeDisburseMaturity),
         }
     }
     /// Stores metrics that are too costly to compute each time metrics are
@@ -2663,6 +2821,131 @@ pub struct ListProposalInfoResponse {
     #[prost(message, repeated, tag = "1")]
     pub proposal_info: ::prost::alloc::vec::Vec<ProposalInfo>,
 }
+/// A settled proposal, as kept in the proposal archive after it has become
+/// eligible for garbage collection. Archived proposals are never modified.
+///
+/// Large fields of the proposal are omitted in the same way as in the
+/// response to `list_proposals` (e.g. only the hashes of the wasm module and
+/// the argument of an InstallCode proposal are kept).
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ArchivedProposal {
+    #[prost(message, optional, tag = "1")]
+    pub id: ::core::option::Option<::ic_nns_common::pb::v1::ProposalId>,
+    /// See \[ProposalData::proposer\].
+    #[prost(message, optional, tag = "2")]
+    pub proposer: ::core::option::Option<::ic_nns_common::pb::v1::NeuronId>,
+    /// The proposal, with large fields omitted.
+    #[prost(message, optional, tag = "3")]
+    pub proposal: ::core::option::Option<Proposal>,
+    /// See \[ProposalData::proposal_timestamp_seconds\].
+    #[prost(uint64, tag = "4")]
+    pub proposal_timestamp_seconds: u64,
+    /// The final tally of the proposal.
+    #[prost(message, optional, tag = "5")]
+    pub latest_tally: ::core::option::Option<Tally>,
+    /// See \[ProposalData::decided_timestamp_seconds\].
+    #[prost(uint64, tag = "6")]
+    pub decided_timestamp_seconds: u64,
+    /// See \[ProposalData::executed_timestamp_seconds\].
+    #[prost(uint64, tag = "7")]
+    pub executed_timestamp_seconds: u64,
+    /// See \[ProposalData::failed_timestamp_seconds\].
+    #[prost(uint64, tag = "8")]
+    pub failed_timestamp_seconds: u64,
+    /// See \[ProposalData::failure_reason\].
+    #[prost(message, optional, tag = "9")]
+    pub failure_reason: ::core::option::Option<GovernanceError>,
+    /// See \[ProposalData::reward_event_round\].
+    #[prost(uint64, tag = "10")]
+    pub reward_event_round: u64,
+    #[prost(enumeration = "Topic", tag = "11")]
+    pub topic: i32,
+    #[prost(enumeration = "ProposalStatus", tag = "12")]
+    pub status: i32,
+    #[prost(enumeration = "ProposalRewardStatus", tag = "13")]
+    pub reward_status: i32,
+    /// The number of ballots of the proposal. The ballots themselves are
+    /// archived separately, and can be listed per neuron.
+    #[prost(uint64, tag = "14")]
+    pub num_ballots: u64,
+}
+/// The ballot of a neuron on an archived proposal.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ArchivedBallot {
+    #[prost(message, optional, tag = "1")]
+    pub proposal_id: ::core::option::Option<::ic_nns_common::pb::v1::ProposalId>,
+    #[prost(message, optional, tag = "2")]
+    pub neuron_id: ::core::option::Option<::ic_nns_common::pb::v1::NeuronId>,
+    #[prost(message, optional, tag = "3")]
+    pub ballot: ::core::option::Option<Ballot>,
+}
+/// A request to list archived proposals in ascending order of proposal ID.
+///
+/// Without filters, all archived proposals in the ID range are returned. If
+/// `topic` is set, only proposals of that topic are returned. If `neuron_id`
+/// is set, only proposals on which the neuron had a ballot are returned,
+/// together with the ballots of the neuron.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListArchivedProposals {
+    /// Only return proposals with an ID greater than or equal to this one.
+    #[prost(message, optional, tag = "1")]
+    pub start_proposal_id: ::core::option::Option<::ic_nns_common::pb::v1::ProposalId>,
+    /// Only return proposals with an ID less than or equal to this one.
+    #[prost(message, optional, tag = "2")]
+    pub end_proposal_id: ::core::option::Option<::ic_nns_common::pb::v1::ProposalId>,
+    /// If set, only return proposals of this topic.
+    #[prost(enumeration = "Topic", optional, tag = "3")]
+    pub topic: ::core::option::Option<i32>,
+    /// If set, only return proposals on which this neuron had a ballot.
+    #[prost(message, optional, tag = "4")]
+    pub neuron_id: ::core::option::Option<::ic_nns_common::pb::v1::NeuronId>,
+    /// Limit on the number of proposals to return. If no value is specified,
+    /// or if a value greater than 100 is specified, 100 will be used.
+    #[prost(uint32, tag = "5")]
+    pub limit: u32,
+}
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListArchivedProposalsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub proposals: ::prost::alloc::vec::Vec<ArchivedProposal>,
+    /// The ballots of `ListArchivedProposals::neuron_id` on the returned
+    /// proposals. Empty if no neuron was specified.
+    #[prost(message, repeated, tag = "2")]
+    pub ballots: ::prost::alloc::vec::Vec<ArchivedBallot>,
+    /// If set, there may be more matching proposals. To fetch the next page,
+    /// repeat the request with this as `start_proposal_id`.
+    #[prost(message, optional, tag = "3")]
+    pub next_start_proposal_id: ::core::option::Option<::ic_nns_common::pb::v1::ProposalId>,
+    /// A CBOR-encoded hash tree witnessing the returned proposals and ballots.
+    /// Its root hash is the certified data of the governance canister. The
+    /// tree has the following structure:
+    ///
+    /// archived_ballots_by_neuron -- \[neuron ID\] \[proposal ID\] -- \[ballot hash\]
+    /// archived_proposals -- \[proposal ID\] -- \[proposal hash\]
+    /// archived_proposals_by_topic -- \[topic\] \[proposal ID\] -- \[proposal hash\]
+    ///
+    /// where IDs are big-endian u64s, topics big-endian u32s, and hashes the
+    /// SHA-256 of the encoded proposal or ballot. The tree reveals every entry
+    /// of the subtree matching the filters, i.e. `archived_ballots_by_neuron`
+    /// if a neuron is specified, else `archived_proposals_by_topic` if a topic
+    /// is specified, else `archived_proposals`, from `start_proposal_id` up to
+    /// the end of the page, so that it also proves that no matching proposal
+    /// was left out. When filtering by both neuron and topic, it additionally
+    /// proves for each ballot whether its proposal has the topic.
+    #[prost(bytes = "vec", tag = "4")]
+    pub hash_tree: ::prost::alloc::vec::Vec<u8>,
+    /// The certificate of the certified data.
+    #[prost(bytes = "vec", tag = "5")]
+    pub certificate: ::prost::alloc::vec::Vec<u8>,
+}
 /// A request to list neurons. The "requested list", i.e., the list of
 /// neuron IDs to retrieve information about, is the union of the list
 /// of neurons listed in `neuron_ids` and, if `caller_neurons` is true,
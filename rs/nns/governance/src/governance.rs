@@ -17,7 +17,9 @@ use crate::{
         create_service_nervous_system::LedgerParameters,
         get_neurons_fund_audit_info_response,
         governance::{
-            neuron_in_flight_command::{Command as InFlightCommand, SyncCommand},
+            neuron_in_flight_command::{
+                Command as InFlightCommand, FinalizeDisburseMaturity, SyncCommand,
+            },
             GovernanceCachedMetrics, NeuronInFlightCommand,
         },
         governance_error::ErrorType,
@@ -27,7 +29,9 @@ use crate::{
             ClaimOrRefresh, Command, NeuronIdOrSubaccount,
         },
         manage_neuron_response,
-        manage_neuron_response::{MergeMaturityResponse, StakeMaturityResponse},
+        manage_neuron_response::{
+            DisburseMaturityResponse, MergeMaturityResponse, StakeMaturityResponse,
+        },
         neuron::{DissolveState, Followees},
         neurons_fund_snapshot::NeuronsFundNeuronPortion as NeuronsFundNeuronPortionPb,
         proposal,
@@ -37,10 +41,11 @@ use crate::{
         settle_neurons_fund_participation_response::NeuronsFundNeuron as NeuronsFundNeuronPb,
         swap_background_information, Ballot, CreateServiceNervousSystem, ExecuteNnsFunction,
         GetNeuronsFundAuditInfoRequest, GetNeuronsFundAuditInfoResponse,
-        Governance as GovernanceProto, GovernanceError, KnownNeuron, ListKnownNeuronsResponse,
-        ListNeurons, ListNeuronsResponse, ListProposalInfo, ListProposalInfoResponse, ManageNeuron,
-        ManageNeuronResponse, MostRecentMonthlyNodeProviderRewards, Motion, NetworkEconomics,
-        Neuron, NeuronInfo, NeuronState, NeuronsFundAuditInfo, NeuronsFundData,
+        Governance as GovernanceProto, GovernanceError, InstallCode, KnownNeuron,
+        ListKnownNeuronsResponse, ListNeurons, ListNeuronsResponse, ListProposalInfo,
+        ListProposalInfoResponse, ManageNeuron, ManageNeuronResponse, MaturityDisbursement,
+        MostRecentMonthlyNodeProviderRewards, Motion, NetworkEconomics, Neuron, NeuronInfo,
+        NeuronState, NeuronsFundAuditInfo, NeuronsFundData,
         NeuronsFundParticipation as NeuronsFundParticipationPb,
         NeuronsFundSnapshot as NeuronsFundSnapshotPb, NnsFunction, NodeProvider, Proposal,
         ProposalData, ProposalInfo, ProposalRewardStatus, ProposalStatus, RewardEvent,
@@ -176,7 +181,7 @@ pub const MAX_LIST_PROPOSAL_RESULTS: u32 = 100;
 const E8S_PER_ICP: u64 = TOKEN_SUBDIVIDABLE_BY;
 
 /// The max number of unsettled proposals -- that is proposals for which ballots
-/// are still stored.
+/// are still stored, and are not about to be moved to the proposal archive.
 pub const MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS: usize = 200;
 
 /// The max number of open manage neuron proposals.
@@ -185,6 +190,13 @@ pub const MAX_NUMBER_OF_OPEN_MANAGE_NEURON_PROPOSALS: usize = 100000;
 /// Max number of hot key for each neuron.
 pub const MAX_NUM_HOT_KEYS_PER_NEURON: usize = 10;
 
+/// The delay between initiating a maturity disbursement and minting the
+/// corresponding ICP, during which the maturity modulation can change.
+pub const MATURITY_DISBURSEMENT_DELAY_SECONDS: u64 = 7 * ONE_DAY_SECONDS;
+
+/// Max number of maturity disbursements in progress for each neuron.
+pub const MAX_NUM_MATURITY_DISBURSEMENTS_IN_PROGRESS: usize = 10;
+
 const MAX_HEAP_SIZE_IN_KIB: usize = 4 * 1024 * 1024;
 const WASM32_PAGE_SIZE_IN_KIB: usize = 64;
 
@@ -459,6 +471,12 @@ impl ManageNeuronResponse {
         }
     }
 
+    pub fn disburse_maturity_response(response: DisburseMaturityResponse) -> Self {
+        ManageNeuronResponse {
+            command: Some(manage_neuron_response::Command::DisburseMaturity(response)),
+        }
+    }
+
     pub fn follow_response() -> Self {
         ManageNeuronResponse {
             command: Some(manage_neuron_response::Command::Follow(
@@ -771,6 +789,7 @@ impl Proposal {
                     Topic::SnsAndCommunityFund
                 }
                 Action::CreateServiceNervousSystem(_) => Topic::SnsAndCommunityFund,
+                Action::InstallCode(_) => Topic::NetworkCanisterManagement,
             }
         } else {
             println!("{}ERROR: No action -> no topic.", LOG_PREFIX);
@@ -786,7 +805,7 @@ impl Proposal {
             .map_or(false, |a| a.allowed_when_resources_are_low())
     }
 
-    fn omit_large_fields(self) -> Self {
+    pub(crate) fn omit_large_fields(self) -> Self {
         Proposal {
             action: self.action.map(|action| action.omit_large_fields()),
             ..self
@@ -805,6 +824,7 @@ impl Action {
                     None => false,
                 }
             }
+            Action::InstallCode(install_code) => install_code.allowed_when_resources_are_low(),
             _ => false,
         }
     }
@@ -831,6 +851,9 @@ impl Action {
                 }
                 Action::ExecuteNnsFunction(execute_nns_function)
             }
+            Action::InstallCode(install_code) => {
+                Action::InstallCode(install_code.omit_large_fields())
+            }
             action => action,
         }
     }
@@ -1294,6 +1317,10 @@ pub trait Environment: Send + Sync {
         panic!("Not implemented.");
     }
 
+    /// Sets the certified data of the canister. Environments that do not run
+    /// in a canister have nothing to certify.
+    fn set_certified_data(&self, _data: &[u8]) {}
+
     /// Returns a random number.
     ///
     /// This number is the same in all replicas.
@@ -1407,6 +1434,9 @@ pub struct Governance {
 
     /// Scope guard for minting node provider rewards.
     minting_node_provider_rewards: bool,
+
+    /// Scope guard for finalizing maturity disbursements.
+    finalizing_disburse_maturity: bool,
 }
 
 pub fn governance_minting_account() -> AccountIdentifier {
@@ -1580,6 +1610,7 @@ impl Governance {
             latest_gc_num_proposals: 0,
             neuron_data_validator: NeuronDataValidator::new(),
             minting_node_provider_rewards: false,
+            finalizing_disburse_maturity: false,
         }
     }
 
@@ -1604,6 +1635,7 @@ impl Governance {
             latest_gc_num_proposals: 0,
             neuron_data_validator: NeuronDataValidator::new(),
             minting_node_provider_rewards: false,
+            finalizing_disburse_maturity: false,
         }
     }
 
@@ -2431,6 +2463,7 @@ impl Governance {
             known_neuron_data: None,
             spawn_at_timestamp_seconds: None,
             neuron_type: parent_neuron.neuron_type,
+            maturity_disbursements_in_progress: vec![],
         };
 
         // Add the child neuron to the set of neurons undergoing ledger updates.
@@ -2717,6 +2750,7 @@ impl Governance {
             joined_community_fund_timestamp_seconds: None,
             known_neuron_data: None,
             neuron_type: None,
+            maturity_disbursements_in_progress: vec![],
         };
 
         // `add_neuron` will verify that `child_neuron.controller` `is_self_authenticating()`, so we don't need to check it here.
@@ -2839,6 +2873,140 @@ impl Governance {
         Ok(responses)
     }
 
+    /// Disburses the maturity of a neuron to an account.
+    ///
+    /// The maturity is deducted from the neuron right away, and a
+    /// `MaturityDisbursement` is recorded on the neuron. Once
+    /// `MATURITY_DISBURSEMENT_DELAY_SECONDS` have passed, the disbursement is
+    /// finalized by `maybe_finalize_disburse_maturity`, which mints the
+    /// maturity, modulated by the maturity modulation of that day, as ICP to
+    /// the target account.
+    ///
+    /// Pre-conditions:
+    /// - The neuron is controlled by `caller`
+    /// - The neuron is not in spawning state.
+    /// - The percentage to disburse is between 1 and 100 (inclusive).
+    /// - The disbursed maturity, with the worst case maturity modulation
+    ///   applied, is at least the transaction fee.
+    /// - The neuron has fewer than `MAX_NUM_MATURITY_DISBURSEMENTS_IN_PROGRESS`
+    ///   disbursements in progress.
+    pub fn disburse_maturity(
+        &mut self,
+        id: &NeuronId,
+        caller: &PrincipalId,
+        disburse_maturity: &manage_neuron::DisburseMaturity,
+    ) -> Result<DisburseMaturityResponse, GovernanceError> {
+        let now = self.env.now();
+        let (
+            neuron_state,
+            is_neuron_controlled_by_caller,
+            neuron_maturity_e8s_equivalent,
+            num_disbursements_in_progress,
+        ) = self.with_neuron(id, |neuron| {
+            (
+                neuron.state(now),
+                neuron.is_controlled_by(caller),
+                neuron.maturity_e8s_equivalent,
+                neuron.maturity_disbursements_in_progress.len(),
+            )
+        })?;
+
+        if neuron_state == NeuronState::Spawning {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                "Can't perform operation on neuron: Neuron is spawning.",
+            ));
+        }
+
+        if !is_neuron_controlled_by_caller {
+            return Err(GovernanceError::new(ErrorType::NotAuthorized));
+        }
+
+        let percentage_to_disburse = disburse_maturity.percentage_to_disburse;
+        if percentage_to_disburse > 100 || percentage_to_disburse == 0 {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                "The percentage of maturity to disburse must be a value between 1 and 100 (inclusive).",
+            ));
+        }
+
+        if num_disbursements_in_progress >= MAX_NUM_MATURITY_DISBURSEMENTS_IN_PROGRESS {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!(
+                    "The neuron already has {} maturity disbursements in progress, which is the \
+                     maximum.",
+                    MAX_NUM_MATURITY_DISBURSEMENTS_IN_PROGRESS
+                ),
+            ));
+        }
+
+        // If no account was provided, disburse to the caller's account.
+        let to_account: AccountIdentifier = match disburse_maturity.to_account.as_ref() {
+            None => AccountIdentifier::new(*caller, None),
+            Some(ai_pb) => AccountIdentifier::try_from(ai_pb).map_err(|e| {
+                GovernanceError::new_with_message(
+                    ErrorType::InvalidCommand,
+                    format!("The recipient's subaccount is invalid due to: {}", e),
+                )
+            })?,
+        };
+
+        let maturity_to_disburse =
+            neuron_maturity_e8s_equivalent.saturating_mul(percentage_to_disburse as u64) / 100;
+
+        // Make sure that the amount minted is worth a transaction even with the
+        // worst case maturity modulation.
+        let worst_case_amount_e8s = apply_maturity_modulation(
+            maturity_to_disburse,
+            *VALID_MATURITY_MODULATION_BASIS_POINTS_RANGE.start(),
+        )
+        .map_err(|e| {
+            GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!("Could not apply maturity modulation: {}", e),
+            )
+        })?;
+        let transaction_fee_e8s = self.transaction_fee();
+        if worst_case_amount_e8s < transaction_fee_e8s {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!(
+                    "There isn't enough maturity to disburse due to worst case maturity \
+                     modulation: {} e8s would be disbursed, but the transaction fee is {} e8s.",
+                    worst_case_amount_e8s, transaction_fee_e8s
+                ),
+            ));
+        }
+
+        let in_flight_command = NeuronInFlightCommand {
+            timestamp: now,
+            command: Some(InFlightCommand::SyncCommand(SyncCommand {})),
+        };
+        // Lock the neuron so that we're sure that we are not disbursing the maturity in the middle of another ongoing operation.
+        let _neuron_lock = self.lock_neuron_for_command(id.id, in_flight_command)?;
+
+        self.with_neuron_mut(id, |neuron| {
+            neuron.maturity_e8s_equivalent = neuron
+                .maturity_e8s_equivalent
+                .saturating_sub(maturity_to_disburse);
+            neuron
+                .maturity_disbursements_in_progress
+                .push(MaturityDisbursement {
+                    amount_e8s: maturity_to_disburse,
+                    timestamp_of_disbursement_seconds: now,
+                    account_to_disburse_to: Some(to_account.into()),
+                    finalize_disbursement_timestamp_seconds: now
+                        + MATURITY_DISBURSEMENT_DELAY_SECONDS,
+                });
+        })
+        .expect("Expected the neuron to exist");
+
+        Ok(DisburseMaturityResponse {
+            amount_disbursed_e8s: maturity_to_disburse,
+        })
+    }
+
     /// Disburse part of the stake of a neuron into a new neuron, possibly
     /// owned by someone else and with a different dissolve delay.
     ///
@@ -3031,6 +3199,7 @@ impl Governance {
             known_neuron_data: None,
             spawn_at_timestamp_seconds: None,
             neuron_type: None,
+            maturity_disbursements_in_progress: vec![],
         };
 
         self.add_neuron(child_nid.id, child_neuron.clone())?;
@@ -3369,11 +3538,17 @@ impl Governance {
 
         // If this is part of a "multi" query and an ExecuteNnsFunction
         // proposal then remove the payload if the payload is larger
-        // than EXECUTE_NNS_FUNCTION_PAYLOAD_LISTING_BYTES_MAX.
+        // than EXECUTE_NNS_FUNCTION_PAYLOAD_LISTING_BYTES_MAX. For
+        // InstallCode proposals, only the hashes of the wasm module and
+        // the argument are listed.
         let proposal = if multi_query {
             if let Some(
                 proposal @ Proposal {
-                    action: Some(proposal::Action::ExecuteNnsFunction(_)),
+                    action:
+                        Some(
+                            proposal::Action::ExecuteNnsFunction(_)
+                            | proposal::Action::InstallCode(_),
+                        ),
                     ..
                 },
             ) = data.proposal.clone()
@@ -3813,6 +3988,7 @@ impl Governance {
                     known_neuron_data: None,
                     spawn_at_timestamp_seconds: None,
                     neuron_type: None,
+                    maturity_disbursements_in_progress: vec![],
                 };
                 self.add_neuron(nid.id, neuron)
             }
@@ -4208,6 +4384,10 @@ impl Governance {
                 self.create_service_nervous_system(pid, create_service_nervous_system)
                     .await;
             }
+            Action::InstallCode(ref install_code) => {
+                let result = self.perform_install_code(install_code).await;
+                self.set_proposal_execution_status(pid, result);
+            }
 
             Action::SetSnsTokenSwapOpenTimeWindow(obsolete_action) => {
                 self.perform_obsolete_action(pid, obsolete_action);
@@ -4218,6 +4398,40 @@ impl Governance {
         }
     }
 
+    /// Asks the NNS root canister to install the code of an InstallCode
+    /// proposal. Root replies once the installation has been started, so
+    /// success means that the request was accepted.
+    async fn perform_install_code(
+        &mut self,
+        install_code: &InstallCode,
+    ) -> Result<(), GovernanceError> {
+        let request = install_code.change_canister_request()?;
+        let request = Encode!(&request).map_err(|err| {
+            GovernanceError::new_with_message(
+                ErrorType::External,
+                format!(
+                    "Failed to encode request for change_nns_canister Candid method call: {}",
+                    err
+                ),
+            )
+        })?;
+
+        self.env
+            .call_canister_method(ROOT_CANISTER_ID, "change_nns_canister", request)
+            .await
+            .map(|_| ())
+            .map_err(|(code, message)| {
+                GovernanceError::new_with_message(
+                    ErrorType::External,
+                    format!(
+                        "Failed to send change_nns_canister request to the root canister: \
+                         code {:?}, message: {}",
+                        code, message,
+                    ),
+                )
+            })
+    }
+
     /// Fails immediately, because this type of proposal is obsolete.
     fn perform_obsolete_action<T>(&mut self, proposal_id: u64, obsolete_action: T)
     where
@@ -4469,8 +4683,8 @@ impl Governance {
                 managed_neuron.not_for_profit
             })?;
 
-        // Only not-for-profit neurons can issue disburse/split/disburse-to-neuron
-        // commands through a proposal.
+        // Only not-for-profit neurons can issue disburse/split/disburse-to-neuron/
+        // disburse-maturity commands through a proposal.
         if !is_managed_neuron_not_for_profit {
             match command {
                 Command::Disburse(_) => {
@@ -4485,6 +4699,12 @@ impl Governance {
                         "Cannot issue a disburse to neuron command through a proposal",
                     ));
                 }
+                Command::DisburseMaturity(_) => {
+                    return Err(GovernanceError::new_with_message(
+                        ErrorType::NotAuthorized,
+                        "Cannot issue a disburse maturity command through a proposal",
+                    ));
+                }
                 _ => {}
             }
         }
@@ -4559,6 +4779,7 @@ impl Governance {
             Action::ManageNeuron(manage_neuron) => {
                 self.validate_manage_neuron_proposal(manage_neuron)
             }
+            Action::InstallCode(install_code) => install_code.validate(),
             Action::ManageNetworkEconomics(_)
             | Action::ApproveGenesisKyc(_)
             | Action::AddOrRemoveNodeProvider(_)
@@ -4839,12 +5060,20 @@ impl Governance {
             // most amount of space. (In the case of proposals with a wasm
             // module in the payload, the payload also takes a lot of
             // space). Manage neuron proposals are not counted as they have
-            // a smaller electoral roll and use their own limit.
+            // a smaller electoral roll and use their own limit. Proposals
+            // that can be purged are not counted either, as their ballots
+            // are moved to the proposal archive by maybe_archive_proposals,
+            // a bounded number per round.
+            let voting_period_seconds = self.voting_period_seconds();
             if self
                 .heap_data
                 .proposals
                 .values()
-                .filter(|info| !info.ballots.is_empty() && !info.is_manage_neuron())
+                .filter(|info| {
+                    !info.ballots.is_empty()
+                        && !info.is_manage_neuron()
+                        && !info.can_be_purged(now_seconds, voting_period_seconds(info.topic()))
+                })
                 .count()
                 >= MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS
                 && !proposal.allowed_when_resources_are_low()
@@ -4897,6 +5126,13 @@ impl Governance {
                 title,
                 ..proposal.clone()
             }
+        } else if let Action::InstallCode(ref install_code) = action {
+            // Record the hashes of the wasm module and the argument, so that
+            // they can be shown when the large fields are omitted.
+            Proposal {
+                action: Some(Action::InstallCode(install_code.with_hashes())),
+                ..proposal.clone()
+            }
         } else {
             proposal.clone()
         };
@@ -5618,6 +5854,7 @@ impl Governance {
             known_neuron_data: None,
             spawn_at_timestamp_seconds: None,
             neuron_type: None,
+            maturity_disbursements_in_progress: vec![],
         };
 
         // This also verifies that there are not too many neurons already.
@@ -5829,6 +6066,9 @@ impl Governance {
             Some(Command::StakeMaturity(s)) => self
                 .stake_maturity_of_neuron(&id, caller, s)
                 .map(|(response, _)| ManageNeuronResponse::stake_maturity_response(response)),
+            Some(Command::DisburseMaturity(d)) => self
+                .disburse_maturity(&id, caller, d)
+                .map(ManageNeuronResponse::disburse_maturity_response),
             Some(Command::Split(s)) => self
                 .split_neuron(&id, caller, s)
                 .await
@@ -5963,9 +6203,13 @@ impl Governance {
         // Try to spawn neurons (potentially multiple times per day).
         } else if self.can_spawn_neurons() {
             self.spawn_neurons().await;
+        // Try to finalize maturity disbursements (potentially multiple times per day).
+        } else if self.can_finalize_disburse_maturity() {
+            self.maybe_finalize_disburse_maturity().await;
         }
 
         self.unstake_maturity_of_dissolved_neurons();
+        self.maybe_archive_proposals();
         self.maybe_gc();
         self.maybe_run_migrations();
         self.maybe_run_validations();
@@ -6190,6 +6434,156 @@ impl Governance {
         self.heap_data.spawning_neurons = Some(false);
     }
 
+    fn can_finalize_disburse_maturity(&self) -> bool {
+        if self.finalizing_disburse_maturity {
+            return false;
+        }
+
+        let now_seconds = self.env.now();
+        !self
+            .neuron_store
+            .list_neurons_ready_to_finalize_maturity_disbursement(now_seconds)
+            .is_empty()
+    }
+
+    /// Finalizes the oldest maturity disbursement of each neuron whose
+    /// disbursement delay has passed, by minting the disbursed maturity,
+    /// modulated by the maturity modulation rate of the day, to the target
+    /// account. A disbursement is only removed from the neuron once the
+    /// transfer succeeded. If the transfer fails, the neuron stays locked, as
+    /// the ledger may still have minted the ICP and retrying could mint twice.
+    async fn maybe_finalize_disburse_maturity(&mut self) {
+        if !self.can_finalize_disburse_maturity() {
+            return;
+        }
+
+        let maturity_modulation = match self.heap_data.cached_daily_maturity_modulation_basis_points
+        {
+            None => return,
+            Some(value) => value,
+        };
+
+        // Sanity check that the maturity modulation returned is within bounds.
+        if !VALID_MATURITY_MODULATION_BASIS_POINTS_RANGE.contains(&maturity_modulation) {
+            println!(
+                "{}Maturity modulation (in basis points) out-of-bounds. Should be in range [-500, 500], actually is: {}",
+                LOG_PREFIX, maturity_modulation
+            );
+            return;
+        }
+
+        // Acquire the scope guard.
+        self.finalizing_disburse_maturity = true;
+
+        let now_seconds = self.env.now();
+        let ready_to_finalize_ids = self
+            .neuron_store
+            .list_neurons_ready_to_finalize_maturity_disbursement(now_seconds);
+
+        for neuron_id in ready_to_finalize_ids {
+            let disbursement = match self.with_neuron(&neuron_id, |neuron| {
+                neuron.maturity_disbursements_in_progress.first().cloned()
+            }) {
+                Ok(Some(disbursement)) => disbursement,
+                _ => continue,
+            };
+
+            let amount_to_be_disbursed_e8s = match apply_maturity_modulation(
+                disbursement.amount_e8s,
+                maturity_modulation,
+            ) {
+                Ok(amount_e8s) => amount_e8s,
+                Err(err) => {
+                    println!(
+                            "{}Could not apply modulation to {:?} for neuron {:?} due to {:?}, skipping",
+                            LOG_PREFIX, disbursement, neuron_id, err
+                        );
+                    continue;
+                }
+            };
+
+            let to_account = match disbursement
+                .account_to_disburse_to
+                .as_ref()
+                .map(AccountIdentifier::try_from)
+            {
+                Some(Ok(to_account)) => to_account,
+                _ => {
+                    println!(
+                        "{}Invalid account in maturity disbursement {:?} of neuron {:?}, skipping",
+                        LOG_PREFIX, disbursement, neuron_id
+                    );
+                    continue;
+                }
+            };
+
+            let in_flight_command = NeuronInFlightCommand {
+                timestamp: now_seconds,
+                command: Some(InFlightCommand::FinalizeDisburseMaturity(
+                    FinalizeDisburseMaturity {
+                        amount_to_be_disbursed_e8s,
+                        to_account: disbursement.account_to_disburse_to.clone(),
+                    },
+                )),
+            };
+            let mut neuron_lock = match self
+                .lock_neuron_for_command(neuron_id.id, in_flight_command)
+            {
+                Ok(lock) => lock,
+                Err(error) => {
+                    // If the lock was already acquired, just continue.
+                    println!(
+                        "{}Tried to finalize maturity disbursement of neuron {:?} but it was already locked. Error: {:?}",
+                        LOG_PREFIX, neuron_id, error,
+                    );
+                    continue;
+                }
+            };
+
+            // Do the transfer, this is a minting transfer, from the governance canister's
+            // (which is also the minting canister) main account into the target account.
+            match self
+                .ledger
+                .transfer_funds(
+                    amount_to_be_disbursed_e8s,
+                    0, // Minting transfer don't pay a fee.
+                    None,
+                    to_account,
+                    now_seconds,
+                )
+                .await
+            {
+                Ok(block_height) => {
+                    println!(
+                        "{}Finalized maturity disbursement {:?} of neuron {:?} at block {}.",
+                        LOG_PREFIX, disbursement, neuron_id, block_height,
+                    );
+                    let result = self.with_neuron_mut(&neuron_id, |neuron| {
+                        neuron.maturity_disbursements_in_progress.remove(0);
+                    });
+                    if let Err(error) = result {
+                        println!(
+                            "{}Failed to remove maturity disbursement {:?} of neuron {:?}: {:?}",
+                            LOG_PREFIX, disbursement, neuron_id, error,
+                        );
+                    }
+                }
+                Err(error) => {
+                    // Retain the neuron lock, so that the disbursement is not retried until
+                    // this is fixed, as the ledger may have minted the ICP anyway.
+                    neuron_lock.retain();
+                    println!(
+                        "{}Error finalizing maturity disbursement {:?} of neuron {:?}. Ledger update failed with err: {:?}.",
+                        LOG_PREFIX, disbursement, neuron_id, error,
+                    );
+                }
+            }
+        }
+
+        // Release the scope guard.
+        self.finalizing_disburse_maturity = false;
+    }
+
     /// Return `true` if rewards should be distributed, `false` otherwise
     fn should_distribute_rewards(&self) -> bool {
         let latest_distribution_nominal_end_timestamp_seconds =
@@ -6401,7 +6795,8 @@ impl Governance {
                        })
                     };
                     p.reward_event_round = day_after_genesis;
-                    p.ballots.clear();
+                    // The ballots are not needed anymore, and are moved to
+                    // the proposal archive by maybe_archive_proposals.
                 }
             };
         }
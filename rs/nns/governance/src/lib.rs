@@ -155,6 +155,7 @@ pub mod neuron_data_validation;
 mod neuron_store;
 pub mod neurons_fund;
 pub mod pb;
+mod proposal_archive;
 pub mod proposals;
 mod reward;
 pub mod storage;
@@ -741,7 +741,8 @@ impl Neuron {
     /// The exact criteria is subject to change. Currently, all of the following must hold:
     ///
     ///     1. Not seed or ect: NeuronType is not NeuronType::Seed or NeuronType::Ect
-    ///     2. Not funded: No stake, and no (unstaked) maturity.
+    ///     2. Not funded: No stake, no (unstaked) maturity, and no maturity disbursements in
+    ///        progress.
     ///     3. Dissolved sufficiently "long ago": Precisely, dissolved as of now - 2 weeks.
     ///     4. Member of the Neuron's Fund.
     ///
@@ -761,7 +762,7 @@ impl Neuron {
         }
 
         // Require condition 2.
-        if self.is_funded() {
+        if self.is_funded() || !self.maturity_disbursements_in_progress.is_empty() {
             return false;
         }
 
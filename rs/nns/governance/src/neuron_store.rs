@@ -652,6 +652,25 @@ impl NeuronStore {
             .collect()
     }
 
+    /// List all neuron ids whose oldest maturity disbursement can be finalized.
+    pub fn list_neurons_ready_to_finalize_maturity_disbursement(
+        &self,
+        now_seconds: u64,
+    ) -> Vec<NeuronId> {
+        let filter = |n: &Neuron| {
+            n.maturity_disbursements_in_progress
+                .first()
+                .map(|disbursement| {
+                    now_seconds >= disbursement.finalize_disbursement_timestamp_seconds
+                })
+                .unwrap_or(false)
+        };
+        self.map_heap_neurons_filtered(filter, |n| n.id)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
     /// Returns an iterator of all voting-eligible neurons
     pub fn voting_eligible_neurons(&self, now_seconds: u64) -> impl Iterator<Item = &Neuron> {
         // This should be safe to do without with_neuron because
@@ -0,0 +1,264 @@
+use crate::{
+    governance::{Governance, LOG_PREFIX},
+    pb::v1::{
+        ArchivedBallot, ArchivedProposal, Ballot, ListArchivedProposals,
+        ListArchivedProposalsResponse, ProposalData, Topic,
+    },
+    storage::{
+        proposal_archive::{encode_hash_tree, merge_hash_trees},
+        with_stable_proposal_archive, with_stable_proposal_archive_mut,
+    },
+};
+use ic_nns_common::pb::v1::{NeuronId, ProposalId};
+
+#[cfg(target_arch = "wasm32")]
+use dfn_core::println;
+
+/// The maximum number of ballots that are moved from the heap to the proposal
+/// archive in one call to `maybe_archive_proposals`.
+pub const MAX_BALLOTS_TO_ARCHIVE_PER_ROUND: usize = 10_000;
+
+/// The maximum number of archived proposals returned by
+/// `list_archived_proposals`.
+pub const MAX_LIST_ARCHIVED_PROPOSALS_RESULTS: u32 = 100;
+
+/// The maximum number of archive entries looked at by one call to
+/// `list_archived_proposals`, to bound the cost of queries with filters that
+/// match few proposals. Since every entry looked at is revealed by the
+/// returned witness, this also bounds the size of the response.
+const MAX_ARCHIVED_PROPOSALS_SCANNED_PER_QUERY: usize = 1_000;
+
+impl Governance {
+    /// Moves settled proposals to the proposal archive.
+    ///
+    /// A proposal is archived once it could be garbage collected: when it is
+    /// decided and its rewards are settled. Its record is archived first, and
+    /// its ballots are then moved out of the heap a bounded number at a time,
+    /// across as many calls as needed.
+    pub fn maybe_archive_proposals(&mut self) {
+        let now_seconds = self.env.now();
+        let voting_period_seconds = self.voting_period_seconds();
+        let proposal_ids = self
+            .heap_data
+            .proposals
+            .iter()
+            .filter(|(_, proposal_data)| {
+                !proposal_data.ballots.is_empty()
+                    && proposal_data
+                        .can_be_purged(now_seconds, voting_period_seconds(proposal_data.topic()))
+            })
+            .map(|(proposal_id, _)| *proposal_id)
+            .collect::<Vec<_>>();
+
+        let mut remaining_ballots = MAX_BALLOTS_TO_ARCHIVE_PER_ROUND;
+        for proposal_id in proposal_ids {
+            if remaining_ballots == 0 {
+                break;
+            }
+            remaining_ballots -= self.archive_proposal(proposal_id, remaining_ballots);
+        }
+    }
+
+    /// Archives the proposal with the given ID if it is not archived yet, and
+    /// then moves up to `max_ballots` of its ballots to the archive. Returns
+    /// the number of ballots moved.
+    ///
+    /// The caller must make sure that the proposal can be purged.
+    pub(crate) fn archive_proposal(&mut self, proposal_id: u64, max_ballots: usize) -> usize {
+        let now_seconds = self.env.now();
+        let voting_period_seconds = self.voting_period_seconds();
+        let Some(proposal_data) = self.heap_data.proposals.get_mut(&proposal_id) else {
+            return 0;
+        };
+
+        let newly_archived = !with_stable_proposal_archive(|archive| archive.contains(proposal_id));
+        if newly_archived {
+            let archived_proposal = proposal_data
+                .to_archived_proposal(now_seconds, voting_period_seconds(proposal_data.topic()));
+            if let Err(err) =
+                with_stable_proposal_archive_mut(|archive| archive.insert(archived_proposal))
+            {
+                println!(
+                    "{}Failed to archive proposal {}: {}",
+                    LOG_PREFIX, proposal_id, err
+                );
+                return 0;
+            }
+        }
+
+        let neuron_ids = proposal_data
+            .ballots
+            .keys()
+            .take(max_ballots)
+            .copied()
+            .collect::<Vec<_>>();
+        with_stable_proposal_archive_mut(|archive| {
+            for neuron_id in &neuron_ids {
+                if let Some(ballot) = proposal_data.ballots.remove(neuron_id) {
+                    archive.insert_ballot(proposal_id, NeuronId { id: *neuron_id }, ballot);
+                }
+            }
+        });
+
+        if newly_archived || !neuron_ids.is_empty() {
+            self.recertify_proposal_archive();
+        }
+        neuron_ids.len()
+    }
+
+    /// Sets the certified data of the canister to the root hash of the
+    /// proposal archive.
+    pub fn recertify_proposal_archive(&self) {
+        let root_hash = with_stable_proposal_archive(|archive| archive.root_hash());
+        self.env.set_certified_data(&root_hash);
+    }
+
+    /// Returns a page of archived proposals, in ascending order of proposal
+    /// ID, together with a witness for them. The certificate is left empty, as
+    /// it is only available to the canister.
+    ///
+    /// The witness reveals every entry of the index used to list the page
+    /// (by proposal ID, topic or neuron) over the range covered by the page,
+    /// so it proves both the returned proposals and ballots and that no other
+    /// proposal in that range matches the filters.
+    pub fn list_archived_proposals(
+        &self,
+        request: &ListArchivedProposals,
+    ) -> ListArchivedProposalsResponse {
+        let start = request.start_proposal_id.map_or(0, |id| id.id);
+        let end = request.end_proposal_id.map_or(u64::MAX, |id| id.id);
+        let limit = if request.limit == 0 || request.limit > MAX_LIST_ARCHIVED_PROPOSALS_RESULTS {
+            MAX_LIST_ARCHIVED_PROPOSALS_RESULTS
+        } else {
+            request.limit
+        } as usize;
+        let topic = request
+            .topic
+            .map(|topic| Topic::try_from(topic).unwrap_or(Topic::Unspecified));
+        if start > end {
+            return ListArchivedProposalsResponse::default();
+        }
+
+        with_stable_proposal_archive(|archive| {
+            let candidates: Box<dyn Iterator<Item = (u64, Option<Ballot>)>> =
+                match (request.neuron_id, topic) {
+                    (Some(neuron_id), _) => Box::new(
+                        archive
+                            .ballots_of_neuron(neuron_id, start..=end)
+                            .map(|(proposal_id, ballot)| (proposal_id, Some(ballot))),
+                    ),
+                    (None, Some(topic)) => Box::new(
+                        archive
+                            .proposal_ids_with_topic(topic, start..=end)
+                            .map(|proposal_id| (proposal_id, None)),
+                    ),
+                    (None, None) => Box::new(
+                        archive
+                            .proposal_ids(start..=end)
+                            .map(|proposal_id| (proposal_id, None)),
+                    ),
+                };
+
+            let mut proposals = vec![];
+            let mut ballots = vec![];
+            let mut scanned_proposal_ids = vec![];
+            let mut stopped_early = false;
+            for (scanned, (proposal_id, ballot)) in candidates.enumerate() {
+                if proposals.len() == limit || scanned == MAX_ARCHIVED_PROPOSALS_SCANNED_PER_QUERY {
+                    stopped_early = true;
+                    break;
+                }
+                scanned_proposal_ids.push(proposal_id);
+
+                let Some(archived_proposal) = archive.get(proposal_id) else {
+                    continue;
+                };
+                if let Some(topic) = topic {
+                    if archived_proposal.topic != topic as i32 {
+                        continue;
+                    }
+                }
+                proposals.push(archived_proposal);
+                if let (Some(neuron_id), Some(ballot)) = (request.neuron_id, ballot) {
+                    ballots.push(ArchivedBallot {
+                        proposal_id: Some(ProposalId { id: proposal_id }),
+                        neuron_id: Some(neuron_id),
+                        ballot: Some(ballot),
+                    });
+                }
+            }
+
+            // The page covers the range up to the last proposal looked at, or
+            // the whole requested range if all matching entries were looked at.
+            let page_end = match scanned_proposal_ids.last() {
+                Some(proposal_id) if stopped_early => *proposal_id,
+                _ => end,
+            };
+            let next_start_proposal_id = if page_end < end {
+                Some(ProposalId { id: page_end + 1 })
+            } else {
+                None
+            };
+
+            let page = start..=page_end;
+            let hash_tree = match (request.neuron_id, topic) {
+                // The ballots of the neuron in the page, and whether each of
+                // the proposals they are on has the requested topic.
+                (Some(neuron_id), Some(topic)) => merge_hash_trees(
+                    archive.ballots_range_witness(neuron_id, page),
+                    archive.topic_witness(topic, &scanned_proposal_ids),
+                ),
+                (Some(neuron_id), None) => {
+                    let proposal_ids = proposals
+                        .iter()
+                        .filter_map(|proposal| proposal.id.map(|id| id.id))
+                        .collect::<Vec<_>>();
+                    merge_hash_trees(
+                        archive.ballots_range_witness(neuron_id, page),
+                        archive.witness(&proposal_ids),
+                    )
+                }
+                (None, Some(topic)) => archive.topic_range_witness(topic, page),
+                (None, None) => archive.range_witness(page),
+            };
+            let hash_tree = encode_hash_tree(hash_tree);
+
+            ListArchivedProposalsResponse {
+                proposals,
+                ballots,
+                next_start_proposal_id,
+                hash_tree,
+                certificate: vec![],
+            }
+        })
+    }
+}
+
+impl ProposalData {
+    /// Returns the record kept in the proposal archive for this proposal.
+    fn to_archived_proposal(
+        &self,
+        now_seconds: u64,
+        voting_period_seconds: u64,
+    ) -> ArchivedProposal {
+        ArchivedProposal {
+            id: self.id,
+            proposer: self.proposer,
+            proposal: self
+                .proposal
+                .clone()
+                .map(|proposal| proposal.omit_large_fields()),
+            proposal_timestamp_seconds: self.proposal_timestamp_seconds,
+            latest_tally: self.latest_tally.clone(),
+            decided_timestamp_seconds: self.decided_timestamp_seconds,
+            executed_timestamp_seconds: self.executed_timestamp_seconds,
+            failed_timestamp_seconds: self.failed_timestamp_seconds,
+            failure_reason: self.failure_reason.clone(),
+            reward_event_round: self.reward_event_round,
+            topic: self.topic() as i32,
+            status: self.status() as i32,
+            reward_status: self.reward_status(now_seconds, voting_period_seconds) as i32,
+            num_ballots: self.ballots.len() as u64,
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::pb::v1::{
+    governance_error::ErrorType, install_code::CanisterInstallMode, GovernanceError, InstallCode,
+};
+use ic_base_types::CanisterId;
+use ic_crypto_sha2::Sha256;
+use ic_ic00_types::CanisterInstallMode as RootCanisterInstallMode;
+use ic_nervous_system_root::change_canister::ChangeCanisterRequest;
+use ic_nns_constants::{ALL_NNS_CANISTER_IDS, ROOT_CANISTER_ID};
+
+impl InstallCode {
+    /// Returns an error if the proposal is not well-formed, or if it targets
+    /// a canister whose code cannot be changed by the NNS root canister.
+    pub fn validate(&self) -> Result<(), GovernanceError> {
+        let invalid_proposal = |message: String| {
+            GovernanceError::new_with_message(ErrorType::InvalidProposal, message)
+        };
+
+        let canister_id = self.valid_canister_id().map_err(invalid_proposal)?;
+        // The root canister cannot change its own code. Root is upgraded by
+        // the lifeline canister through NnsRootUpgrade proposals instead.
+        if canister_id == ROOT_CANISTER_ID {
+            return Err(invalid_proposal(
+                "InstallCode proposals cannot target the root canister. Use an \
+                 NnsRootUpgrade proposal instead."
+                    .to_string(),
+            ));
+        }
+
+        self.valid_install_mode().map_err(invalid_proposal)?;
+
+        if self.wasm_module.as_ref().map_or(true, Vec::is_empty) {
+            return Err(invalid_proposal(
+                "InstallCode proposals must specify a wasm module.".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns a copy of this action with `wasm_module_hash` and `arg_hash`
+    /// set to the SHA-256 hashes of `wasm_module` and `arg`.
+    pub fn with_hashes(&self) -> Self {
+        let wasm_module_hash = Sha256::hash(self.wasm_module.as_deref().unwrap_or_default());
+        let arg_hash = Sha256::hash(self.arg.as_deref().unwrap_or_default());
+        Self {
+            wasm_module_hash: Some(wasm_module_hash.to_vec()),
+            arg_hash: Some(arg_hash.to_vec()),
+            ..self.clone()
+        }
+    }
+
+    /// Removes the wasm module and the argument, leaving their hashes.
+    pub fn omit_large_fields(self) -> Self {
+        Self {
+            wasm_module: None,
+            arg: None,
+            ..self
+        }
+    }
+
+    /// Upgrades are allowed when resources are low, like NnsCanisterUpgrade
+    /// proposals.
+    pub fn allowed_when_resources_are_low(&self) -> bool {
+        self.install_mode == Some(CanisterInstallMode::Upgrade as i32)
+    }
+
+    /// Builds the request sent to the `change_nns_canister` method of the NNS
+    /// root canister.
+    pub fn change_canister_request(&self) -> Result<ChangeCanisterRequest, GovernanceError> {
+        let invalid_proposal = |message: String| {
+            GovernanceError::new_with_message(ErrorType::InvalidProposal, message)
+        };
+
+        let canister_id = self.valid_canister_id().map_err(invalid_proposal)?;
+        let mode = match self.valid_install_mode().map_err(invalid_proposal)? {
+            CanisterInstallMode::Install => RootCanisterInstallMode::Install,
+            CanisterInstallMode::Reinstall => RootCanisterInstallMode::Reinstall,
+            CanisterInstallMode::Upgrade => RootCanisterInstallMode::Upgrade,
+            CanisterInstallMode::Unspecified => unreachable!("Checked by valid_install_mode."),
+        };
+        let stop_before_installing = !self.skip_stopping_before_installing.unwrap_or(false);
+
+        Ok(
+            ChangeCanisterRequest::new(stop_before_installing, mode, canister_id)
+                .with_wasm(self.wasm_module.clone().unwrap_or_default())
+                .with_arg(self.arg.clone().unwrap_or_default()),
+        )
+    }
+
+    fn valid_canister_id(&self) -> Result<CanisterId, String> {
+        let principal_id = self
+            .canister_id
+            .ok_or_else(|| "InstallCode proposals must specify a canister ID.".to_string())?;
+        let canister_id = CanisterId::try_from(principal_id)
+            .map_err(|e| format!("Invalid canister ID {}: {}", principal_id, e))?;
+        if !ALL_NNS_CANISTER_IDS.contains(&&canister_id) {
+            return Err(format!("{} is not an NNS canister.", canister_id));
+        }
+        Ok(canister_id)
+    }
+
+    fn valid_install_mode(&self) -> Result<CanisterInstallMode, String> {
+        match self.install_mode.map(CanisterInstallMode::try_from) {
+            Some(Ok(CanisterInstallMode::Unspecified)) | None => {
+                Err("InstallCode proposals must specify an install mode.".to_string())
+            }
+            Some(Err(_)) => Err(format!("Unknown install mode: {:?}", self.install_mode)),
+            Some(Ok(install_mode)) => Ok(install_mode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_nns_constants::{GOVERNANCE_CANISTER_ID, REGISTRY_CANISTER_ID};
+
+    fn basic_install_code() -> InstallCode {
+        InstallCode {
+            canister_id: Some(REGISTRY_CANISTER_ID.get()),
+            install_mode: Some(CanisterInstallMode::Upgrade as i32),
+            wasm_module: Some(vec![0, 0x61, 0x73, 0x6d, 1, 0, 0, 0]),
+            arg: Some(vec![42]),
+            skip_stopping_before_installing: None,
+            wasm_module_hash: None,
+            arg_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(basic_install_code().validate(), Ok(()));
+
+        for invalid in [
+            InstallCode {
+                canister_id: None,
+                ..basic_install_code()
+            },
+            InstallCode {
+                canister_id: Some(CanisterId::from_u64(1_000_000).get()),
+                ..basic_install_code()
+            },
+            InstallCode {
+                canister_id: Some(ROOT_CANISTER_ID.get()),
+                ..basic_install_code()
+            },
+            InstallCode {
+                install_mode: None,
+                ..basic_install_code()
+            },
+            InstallCode {
+                install_mode: Some(CanisterInstallMode::Unspecified as i32),
+                ..basic_install_code()
+            },
+            InstallCode {
+                install_mode: Some(42),
+                ..basic_install_code()
+            },
+            InstallCode {
+                wasm_module: Some(vec![]),
+                ..basic_install_code()
+            },
+        ] {
+            let error = invalid.validate().unwrap_err();
+            assert_eq!(
+                error.error_type,
+                ErrorType::InvalidProposal as i32,
+                "{:?}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_hashes_survive_omitting_large_fields() {
+        let install_code = basic_install_code().with_hashes();
+        assert_eq!(
+            install_code.wasm_module_hash,
+            Some(Sha256::hash(&[0, 0x61, 0x73, 0x6d, 1, 0, 0, 0]).to_vec())
+        );
+        assert_eq!(install_code.arg_hash, Some(Sha256::hash(&[42]).to_vec()));
+
+        let listed = install_code.clone().omit_large_fields();
+        assert_eq!(listed.wasm_module, None);
+        assert_eq!(listed.arg, None);
+        assert_eq!(listed.wasm_module_hash, install_code.wasm_module_hash);
+        assert_eq!(listed.arg_hash, install_code.arg_hash);
+    }
+
+    #[test]
+    fn test_change_canister_request() {
+        let request = InstallCode {
+            canister_id: Some(GOVERNANCE_CANISTER_ID.get()),
+            ..basic_install_code()
+        }
+        .change_canister_request()
+        .unwrap();
+        assert!(request.stop_before_installing);
+        assert_eq!(request.mode, RootCanisterInstallMode::Upgrade);
+        assert_eq!(request.canister_id, GOVERNANCE_CANISTER_ID);
+        assert_eq!(request.wasm_module, vec![0, 0x61, 0x73, 0x6d, 1, 0, 0, 0]);
+        assert_eq!(request.arg, vec![42]);
+
+        let request = InstallCode {
+            skip_stopping_before_installing: Some(true),
+            ..basic_install_code()
+        }
+        .change_canister_request()
+        .unwrap();
+        assert!(!request.stop_before_installing);
+    }
+}
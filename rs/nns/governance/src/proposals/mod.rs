@@ -1,2 +1,3 @@
 pub mod create_service_nervous_system;
+pub mod install_code;
 pub mod proposal_submission;
@@ -26,8 +26,14 @@ const NEURON_FOLLOWING_INDEX_MEMORY_ID: MemoryId = MemoryId::new(11);
 const NEURON_KNOWN_NEURON_INDEX_MEMORY_ID: MemoryId = MemoryId::new(12);
 const NEURON_ACCOUNT_ID_INDEX_MEMORY_ID: MemoryId = MemoryId::new(13);
 
+const ARCHIVED_PROPOSALS_MEMORY_ID: MemoryId = MemoryId::new(14);
+const ARCHIVED_PROPOSAL_HASHES_MEMORY_ID: MemoryId = MemoryId::new(15);
+const ARCHIVED_PROPOSAL_TOPIC_INDEX_MEMORY_ID: MemoryId = MemoryId::new(16);
+const ARCHIVED_BALLOTS_MEMORY_ID: MemoryId = MemoryId::new(17);
+
 pub mod neuron_indexes;
 pub mod neurons;
+pub mod proposal_archive;
 
 type VM = VirtualMemory<DefaultMemoryImpl>;
 
@@ -50,6 +56,9 @@ struct State {
 
     // Neuron indexes stored in stable storage.
     stable_neuron_indexes: neuron_indexes::StableNeuronIndexes<VM>,
+
+    // Settled proposals and their ballots, archived before garbage collection.
+    stable_proposal_archive: proposal_archive::StableProposalArchive<VM>,
 }
 
 impl State {
@@ -93,11 +102,23 @@ impl State {
             .build()
         });
 
+        let stable_proposal_archive = MEMORY_MANAGER.with(|memory_manager| {
+            let memory_manager = memory_manager.borrow();
+            proposal_archive::StableProposalArchiveBuilder {
+                proposals: memory_manager.get(ARCHIVED_PROPOSALS_MEMORY_ID),
+                hashes: memory_manager.get(ARCHIVED_PROPOSAL_HASHES_MEMORY_ID),
+                topic_index: memory_manager.get(ARCHIVED_PROPOSAL_TOPIC_INDEX_MEMORY_ID),
+                ballots: memory_manager.get(ARCHIVED_BALLOTS_MEMORY_ID),
+            }
+            .build()
+        });
+
         Self {
             upgrades_memory,
             audit_events_log,
             stable_neuron_store,
             stable_neuron_indexes,
+            stable_proposal_archive,
         }
     }
 
@@ -106,6 +127,7 @@ impl State {
     fn validate(&self) {
         self.stable_neuron_store.validate();
         self.stable_neuron_indexes.validate();
+        self.stable_proposal_archive.validate();
     }
 }
 
@@ -159,6 +181,24 @@ pub(crate) fn with_stable_neuron_indexes_mut<R>(
     })
 }
 
+pub(crate) fn with_stable_proposal_archive<R>(
+    f: impl FnOnce(&proposal_archive::StableProposalArchive<VM>) -> R,
+) -> R {
+    STATE.with(|state| {
+        let stable_proposal_archive = &state.borrow().stable_proposal_archive;
+        f(stable_proposal_archive)
+    })
+}
+
+pub(crate) fn with_stable_proposal_archive_mut<R>(
+    f: impl FnOnce(&mut proposal_archive::StableProposalArchive<VM>) -> R,
+) -> R {
+    STATE.with(|state| {
+        let stable_proposal_archive = &mut state.borrow_mut().stable_proposal_archive;
+        f(stable_proposal_archive)
+    })
+}
+
 /// Validates that some of the data in stable storage can be read, in order to prevent broken
 /// schema. Should only be called in post_upgrade.
 pub fn validate_stable_storage() {
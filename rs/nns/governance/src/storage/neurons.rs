@@ -641,10 +641,23 @@ impl TryFrom<Neuron> for DecomposedNeuron {
             known_neuron_data,
             neuron_type,
             dissolve_state,
+            maturity_disbursements_in_progress,
         } = source;
 
         let id = id.ok_or(NeuronStoreError::NeuronIdIsNone)?;
 
+        // Neurons with maturity disbursements in progress are active, and
+        // active neurons are not stored in stable memory.
+        if !maturity_disbursements_in_progress.is_empty() {
+            return Err(NeuronStoreError::InvalidData {
+                reason: format!(
+                    "Neuron {:?} has maturity disbursements in progress and cannot be stored \
+                     in stable memory",
+                    id
+                ),
+            });
+        }
+
         let main = AbridgedNeuron {
             id: Some(id),
             account,
@@ -735,6 +748,7 @@ impl DecomposedNeuron {
             known_neuron_data,
             neuron_type,
             dissolve_state: dissolve_state.map(NeuronDissolveState::from),
+            maturity_disbursements_in_progress: vec![],
         }
     }
 }
@@ -0,0 +1,404 @@
+use crate::{
+    pb::v1::{ArchivedProposal, Ballot, Topic},
+    storage::validate_stable_btree_map,
+};
+use ic_certified_map::{fork, labeled, labeled_hash, AsHashTree, Hash, HashTree, RbTree};
+use ic_crypto_sha2::Sha256;
+use ic_nns_common::pb::v1::NeuronId;
+use ic_stable_structures::{storable::Bound, StableBTreeMap, Storable};
+use prost::Message;
+use serde::Serialize;
+use std::{borrow::Cow, ops::RangeInclusive};
+
+/// The label under which the hashes of archived ballots are certified, keyed
+/// by neuron ID and proposal ID.
+const ARCHIVED_BALLOTS_BY_NEURON_LABEL: &[u8] = b"archived_ballots_by_neuron";
+/// The label under which the hashes of archived proposals are certified.
+const ARCHIVED_PROPOSALS_LABEL: &[u8] = b"archived_proposals";
+/// The label under which the hashes of archived proposals are certified, keyed
+/// by topic and proposal ID.
+const ARCHIVED_PROPOSALS_BY_TOPIC_LABEL: &[u8] = b"archived_proposals_by_topic";
+
+// Constructing a StableProposalArchive is done like so:
+//
+//     let stable_proposal_archive = proposal_archive::StableProposalArchiveBuilder {
+//         proposals: new_memory(...),
+//         hashes: etc,
+//         ...
+//     }
+//     .build()
+pub(crate) struct StableProposalArchiveBuilder<Memory> {
+    pub proposals: Memory,
+    pub hashes: Memory,
+    pub topic_index: Memory,
+    pub ballots: Memory,
+}
+
+impl<Memory> StableProposalArchiveBuilder<Memory>
+where
+    Memory: ic_stable_structures::Memory,
+{
+    pub fn build(self) -> StableProposalArchive<Memory> {
+        let Self {
+            proposals,
+            hashes,
+            topic_index,
+            ballots,
+        } = self;
+
+        let hashes = StableBTreeMap::init(hashes);
+        let topic_index = StableBTreeMap::init(topic_index);
+        let ballots = StableBTreeMap::init(ballots);
+        // The certified trees only live in the heap, so they are restored from
+        // stable memory.
+        let mut certified_hashes = RbTree::new();
+        for (proposal_id, hash) in hashes.iter() {
+            certified_hashes.insert(proposal_id_key(proposal_id), hash.to_vec());
+        }
+        let mut certified_topic_index = RbTree::new();
+        for ((topic, proposal_id), ()) in topic_index.iter() {
+            let hash = hashes
+                .get(&proposal_id)
+                .expect("Every proposal in the topic index must have a hash.");
+            certified_topic_index.insert(topic_key(topic, proposal_id), hash.to_vec());
+        }
+        let mut certified_ballots = RbTree::new();
+        for ((neuron_id, proposal_id), ballot) in ballots.iter() {
+            certified_ballots.insert(ballot_key(neuron_id, proposal_id), ballot_hash(&ballot));
+        }
+
+        StableProposalArchive {
+            proposals: StableBTreeMap::init(proposals),
+            hashes,
+            topic_index,
+            ballots,
+            certified_hashes,
+            certified_topic_index,
+            certified_ballots,
+        }
+    }
+}
+
+/// An append-only archive of settled proposals and the ballots cast on them.
+///
+/// Proposals are keyed by their ID. Each archived proposal and ballot is
+/// hashed when it is archived, and the hashes are kept in a certified tree
+/// (see `root_hash`) with one subtree per way of listing the archive: by
+/// proposal ID, by topic and by neuron. Queries can thus return range
+/// witnesses proving both the entries they return and that no other entry
+/// matches in the range they cover.
+pub(crate) struct StableProposalArchive<Memory>
+where
+    Memory: ic_stable_structures::Memory,
+{
+    proposals: StableBTreeMap<u64, ArchivedProposal, Memory>,
+    hashes: StableBTreeMap<u64, [u8; 32], Memory>,
+    topic_index: StableBTreeMap<(Topic, /* proposal ID */ u64), (), Memory>,
+    ballots: StableBTreeMap<(NeuronId, /* proposal ID */ u64), Ballot, Memory>,
+
+    certified_hashes: RbTree<[u8; 8], Vec<u8>>,
+    certified_topic_index: RbTree<[u8; 12], Vec<u8>>,
+    certified_ballots: RbTree<[u8; 16], Vec<u8>>,
+}
+
+impl<Memory> StableProposalArchive<Memory>
+where
+    Memory: ic_stable_structures::Memory,
+{
+    pub fn contains(&self, proposal_id: u64) -> bool {
+        self.hashes.contains_key(&proposal_id)
+    }
+
+    pub fn get(&self, proposal_id: u64) -> Option<ArchivedProposal> {
+        self.proposals.get(&proposal_id)
+    }
+
+    /// Adds a proposal to the archive. Archived proposals cannot be changed,
+    /// so this returns Err if a proposal with the same ID was archived before.
+    pub fn insert(&mut self, archived_proposal: ArchivedProposal) -> Result<(), String> {
+        let proposal_id = archived_proposal
+            .id
+            .ok_or_else(|| "Archived proposals must have an ID.".to_string())?
+            .id;
+        if self.contains(proposal_id) {
+            return Err(format!("Proposal {} is already archived.", proposal_id));
+        }
+
+        let hash = Sha256::hash(&archived_proposal.encode_to_vec());
+        let topic = Topic::try_from(archived_proposal.topic).unwrap_or(Topic::Unspecified);
+
+        self.proposals.insert(proposal_id, archived_proposal);
+        self.hashes.insert(proposal_id, hash);
+        self.topic_index.insert((topic, proposal_id), ());
+        self.certified_hashes
+            .insert(proposal_id_key(proposal_id), hash.to_vec());
+        self.certified_topic_index
+            .insert(topic_key(topic, proposal_id), hash.to_vec());
+
+        Ok(())
+    }
+
+    /// Records the ballot of a neuron on an archived proposal.
+    pub fn insert_ballot(&mut self, proposal_id: u64, neuron_id: NeuronId, ballot: Ballot) {
+        self.certified_ballots
+            .insert(ballot_key(neuron_id, proposal_id), ballot_hash(&ballot));
+        self.ballots.insert((neuron_id, proposal_id), ballot);
+    }
+
+    /// Returns the IDs of archived proposals in the given range, in ascending
+    /// order.
+    pub fn proposal_ids(&self, range: RangeInclusive<u64>) -> impl Iterator<Item = u64> + '_ {
+        self.hashes.range(range).map(|(proposal_id, _)| proposal_id)
+    }
+
+    /// Returns the IDs of archived proposals of a topic in the given range, in
+    /// ascending order.
+    pub fn proposal_ids_with_topic(
+        &self,
+        topic: Topic,
+        range: RangeInclusive<u64>,
+    ) -> impl Iterator<Item = u64> + '_ {
+        self.topic_index
+            .range((topic, *range.start())..=(topic, *range.end()))
+            .map(|((_, proposal_id), ())| proposal_id)
+    }
+
+    /// Returns the ballots of a neuron on the archived proposals in the given
+    /// range, in ascending order of proposal ID.
+    pub fn ballots_of_neuron(
+        &self,
+        neuron_id: NeuronId,
+        range: RangeInclusive<u64>,
+    ) -> impl Iterator<Item = (u64, Ballot)> + '_ {
+        self.ballots
+            .range((neuron_id, *range.start())..=(neuron_id, *range.end()))
+            .map(|((_, proposal_id), ballot)| (proposal_id, ballot))
+    }
+
+    /// The root hash of the certified tree of the archive, which has the
+    /// following structure:
+    ///
+    ///   archived_ballots_by_neuron -- [neuron ID] [proposal ID] -- [ballot hash]
+    ///   archived_proposals -- [proposal ID] -- [proposal hash]
+    ///   archived_proposals_by_topic -- [topic] [proposal ID] -- [proposal hash]
+    ///
+    /// All IDs are big-endian u64s, and topics big-endian u32s.
+    pub fn root_hash(&self) -> Hash {
+        self.tree(None, None, None).reconstruct()
+    }
+
+    /// Returns a witness for all the archived proposals in the given range.
+    /// Since all the leaves in the range are revealed, the witness also proves
+    /// that no other proposal in the range was archived.
+    pub fn range_witness(&self, range: RangeInclusive<u64>) -> HashTree<'_> {
+        let tree = self.certified_hashes.value_range(
+            &proposal_id_key(*range.start()),
+            &proposal_id_key(*range.end()),
+        );
+        self.tree(None, Some(tree), None)
+    }
+
+    /// Returns a witness for the given archived proposals.
+    pub fn witness(&self, proposal_ids: &[u64]) -> HashTree<'_> {
+        let tree = proposal_ids
+            .iter()
+            .map(|proposal_id| {
+                self.certified_hashes
+                    .witness(&proposal_id_key(*proposal_id))
+            })
+            .reduce(merge_hash_trees);
+        self.tree(None, tree, None)
+    }
+
+    /// Returns a witness for all the archived proposals of a topic in the given
+    /// range, which also proves that no other proposal of the topic in the
+    /// range was archived.
+    pub fn topic_range_witness(&self, topic: Topic, range: RangeInclusive<u64>) -> HashTree<'_> {
+        let tree = self.certified_topic_index.value_range(
+            &topic_key(topic, *range.start()),
+            &topic_key(topic, *range.end()),
+        );
+        self.tree(None, None, Some(tree))
+    }
+
+    /// Returns a witness proving, for each of the given proposals, whether it
+    /// was archived with the given topic.
+    pub fn topic_witness(&self, topic: Topic, proposal_ids: &[u64]) -> HashTree<'_> {
+        let tree = proposal_ids
+            .iter()
+            .map(|proposal_id| {
+                self.certified_topic_index
+                    .witness(&topic_key(topic, *proposal_id))
+            })
+            .reduce(merge_hash_trees);
+        self.tree(None, None, tree)
+    }
+
+    /// Returns a witness for all the ballots of a neuron on the archived
+    /// proposals in the given range, which also proves that the neuron had no
+    /// other ballot in the range.
+    pub fn ballots_range_witness(
+        &self,
+        neuron_id: NeuronId,
+        range: RangeInclusive<u64>,
+    ) -> HashTree<'_> {
+        let tree = self.certified_ballots.value_range(
+            &ballot_key(neuron_id, *range.start()),
+            &ballot_key(neuron_id, *range.end()),
+        );
+        self.tree(Some(tree), None, None)
+    }
+
+    /// Assembles the certified tree (see `root_hash`) out of witnesses of its
+    /// subtrees, pruning the subtrees for which there is no witness.
+    fn tree<'a>(
+        &'a self,
+        ballots: Option<HashTree<'a>>,
+        proposals: Option<HashTree<'a>>,
+        topic_index: Option<HashTree<'a>>,
+    ) -> HashTree<'a> {
+        fn labeled_or_pruned<'a>(
+            label: &'static [u8],
+            witness: Option<HashTree<'a>>,
+            root_hash: Hash,
+        ) -> HashTree<'a> {
+            match witness {
+                Some(witness) => labeled(label, witness),
+                None => HashTree::Pruned(labeled_hash(label, &root_hash)),
+            }
+        }
+
+        fork(
+            fork(
+                labeled_or_pruned(
+                    ARCHIVED_BALLOTS_BY_NEURON_LABEL,
+                    ballots,
+                    self.certified_ballots.root_hash(),
+                ),
+                labeled_or_pruned(
+                    ARCHIVED_PROPOSALS_LABEL,
+                    proposals,
+                    self.certified_hashes.root_hash(),
+                ),
+            ),
+            labeled_or_pruned(
+                ARCHIVED_PROPOSALS_BY_TOPIC_LABEL,
+                topic_index,
+                self.certified_topic_index.root_hash(),
+            ),
+        )
+    }
+
+    /// Validates that some of the data in stable storage can be read, in order to prevent broken
+    /// schema. Should only be called in post_upgrade.
+    pub fn validate(&self) {
+        validate_stable_btree_map(&self.proposals);
+        validate_stable_btree_map(&self.hashes);
+        validate_stable_btree_map(&self.topic_index);
+        validate_stable_btree_map(&self.ballots);
+    }
+}
+
+fn proposal_id_key(proposal_id: u64) -> [u8; 8] {
+    proposal_id.to_be_bytes()
+}
+
+fn topic_key(topic: Topic, proposal_id: u64) -> [u8; 12] {
+    let mut key = [0; 12];
+    key[..4].copy_from_slice(&(topic as i32 as u32).to_be_bytes());
+    key[4..].copy_from_slice(&proposal_id.to_be_bytes());
+    key
+}
+
+fn ballot_key(neuron_id: NeuronId, proposal_id: u64) -> [u8; 16] {
+    let mut key = [0; 16];
+    key[..8].copy_from_slice(&neuron_id.id.to_be_bytes());
+    key[8..].copy_from_slice(&proposal_id.to_be_bytes());
+    key
+}
+
+fn ballot_hash(ballot: &Ballot) -> Vec<u8> {
+    Sha256::hash(&ballot.encode_to_vec()).to_vec()
+}
+
+/// Merges two witnesses of the same tree into one witness that reveals
+/// everything revealed by either of them.
+pub(crate) fn merge_hash_trees<'a>(lhs: HashTree<'a>, rhs: HashTree<'a>) -> HashTree<'a> {
+    use HashTree::*;
+
+    match (lhs, rhs) {
+        (Pruned(lhs), Pruned(_)) => Pruned(lhs),
+        (Pruned(_), rhs) => rhs,
+        (lhs, Pruned(_)) => lhs,
+        (Fork(lhs), Fork(rhs)) => {
+            let (lhs_left, lhs_right) = *lhs;
+            let (rhs_left, rhs_right) = *rhs;
+            fork(
+                merge_hash_trees(lhs_left, rhs_left),
+                merge_hash_trees(lhs_right, rhs_right),
+            )
+        }
+        (Labeled(label, lhs), Labeled(_, rhs)) => labeled(label, merge_hash_trees(*lhs, *rhs)),
+        (lhs, _) => lhs,
+    }
+}
+
+/// Encodes a hash tree into the self-describing CBOR expected by clients.
+pub(crate) fn encode_hash_tree(tree: HashTree<'_>) -> Vec<u8> {
+    let mut serializer = serde_cbor::ser::Serializer::new(vec![]);
+    serializer.self_describe().unwrap();
+    tree.serialize(&mut serializer)
+        .expect("Failed to serialize a hash tree.");
+    serializer.into_inner()
+}
+
+// impl Storable for $ProtoMessage
+// ======================================
+
+impl Storable for ArchivedProposal {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::from(self.encode_to_vec())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Self::decode(&bytes[..]).expect("Unable to deserialize ArchivedProposal.")
+    }
+
+    // The size of an archived proposal depends on its action, and is not bounded
+    // by any known constant.
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Ballot {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::from(self.encode_to_vec())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Self::decode(&bytes[..]).expect("Unable to deserialize Ballot.")
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        // How this number was chosen: a Ballot has two varint fields of at most
+        // 10 bytes each, plus two bytes of tags.
+        max_size: 32,
+        is_fixed_size: false,
+    };
+}
+
+#[cfg(test)]
+use ic_stable_structures::VectorMemory;
+
+#[cfg(test)]
+pub(crate) fn new_heap_based() -> StableProposalArchive<VectorMemory> {
+    StableProposalArchiveBuilder {
+        proposals: VectorMemory::default(),
+        hashes: VectorMemory::default(),
+        topic_index: VectorMemory::default(),
+        ballots: VectorMemory::default(),
+    }
+    .build()
+}
+
+#[cfg(test)]
+mod tests;
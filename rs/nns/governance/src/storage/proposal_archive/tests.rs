@@ -0,0 +1,233 @@
+use super::*;
+
+use crate::pb::v1::{Tally, Vote};
+use ic_nns_common::pb::v1::ProposalId;
+use pretty_assertions::assert_eq;
+
+fn archived_proposal(id: u64, topic: Topic) -> ArchivedProposal {
+    ArchivedProposal {
+        id: Some(ProposalId { id }),
+        proposer: Some(NeuronId { id: 1 }),
+        latest_tally: Some(Tally {
+            timestamp_seconds: id,
+            yes: 2,
+            no: 1,
+            total: 3,
+        }),
+        topic: topic as i32,
+        num_ballots: 2,
+        ..Default::default()
+    }
+}
+
+fn ballot(vote: Vote) -> Ballot {
+    Ballot {
+        vote: vote as i32,
+        voting_power: 42,
+    }
+}
+
+#[test]
+fn test_insert_and_get() {
+    let mut archive = new_heap_based();
+    let proposal = archived_proposal(7, Topic::Governance);
+
+    assert!(!archive.contains(7));
+    assert_eq!(archive.insert(proposal.clone()), Ok(()));
+    assert!(archive.contains(7));
+    assert_eq!(archive.get(7), Some(proposal.clone()));
+
+    // Archived proposals cannot be changed.
+    let changed_proposal = ArchivedProposal {
+        num_ballots: 3,
+        ..proposal.clone()
+    };
+    assert!(archive.insert(changed_proposal).is_err());
+    assert_eq!(archive.get(7), Some(proposal));
+
+    // Proposals need an ID.
+    assert!(archive
+        .insert(ArchivedProposal {
+            id: None,
+            ..Default::default()
+        })
+        .is_err());
+}
+
+#[test]
+fn test_list_by_range_and_topic() {
+    let mut archive = new_heap_based();
+    for id in 1..=10 {
+        let topic = if id % 2 == 0 {
+            Topic::Governance
+        } else {
+            Topic::NodeAdmin
+        };
+        archive.insert(archived_proposal(id, topic)).unwrap();
+    }
+
+    assert_eq!(
+        archive.proposal_ids(3..=6).collect::<Vec<_>>(),
+        vec![3, 4, 5, 6]
+    );
+    assert_eq!(
+        archive
+            .proposal_ids_with_topic(Topic::Governance, 3..=u64::MAX)
+            .collect::<Vec<_>>(),
+        vec![4, 6, 8, 10]
+    );
+    assert_eq!(
+        archive
+            .proposal_ids_with_topic(Topic::ExchangeRate, 0..=u64::MAX)
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn test_ballots_of_neuron() {
+    let mut archive = new_heap_based();
+    archive.insert_ballot(1, NeuronId { id: 100 }, ballot(Vote::Yes));
+    archive.insert_ballot(2, NeuronId { id: 100 }, ballot(Vote::No));
+    archive.insert_ballot(2, NeuronId { id: 101 }, ballot(Vote::Yes));
+    archive.insert_ballot(3, NeuronId { id: 100 }, ballot(Vote::Unspecified));
+
+    assert_eq!(
+        archive
+            .ballots_of_neuron(NeuronId { id: 100 }, 2..=u64::MAX)
+            .collect::<Vec<_>>(),
+        vec![(2, ballot(Vote::No)), (3, ballot(Vote::Unspecified))]
+    );
+    assert_eq!(
+        archive
+            .ballots_of_neuron(NeuronId { id: 101 }, 0..=u64::MAX)
+            .collect::<Vec<_>>(),
+        vec![(2, ballot(Vote::Yes))]
+    );
+}
+
+#[test]
+fn test_witnesses_reconstruct_root_hash() {
+    let mut archive = new_heap_based();
+    let empty_root_hash = archive.root_hash();
+    for id in 1..=20 {
+        archive
+            .insert(archived_proposal(id, Topic::Governance))
+            .unwrap();
+    }
+    let root_hash = archive.root_hash();
+    assert_ne!(root_hash, empty_root_hash);
+
+    assert_eq!(archive.range_witness(5..=9).reconstruct(), root_hash);
+    assert_eq!(archive.witness(&[2, 11, 17]).reconstruct(), root_hash);
+    assert_eq!(archive.witness(&[]).reconstruct(), root_hash);
+
+    // The witnessed hashes are the hashes of the encoded proposals.
+    let expected_hash = Sha256::hash(&archived_proposal(11, Topic::Governance).encode_to_vec());
+    assert_eq!(
+        find_leaf(&archive.witness(&[11]), &proposal_id_key(11)),
+        Some(&expected_hash[..])
+    );
+}
+
+#[test]
+fn test_index_witnesses_reconstruct_root_hash() {
+    let mut archive = new_heap_based();
+    let empty_root_hash = archive.root_hash();
+    for id in 1..=10 {
+        let topic = if id % 2 == 0 {
+            Topic::Governance
+        } else {
+            Topic::NodeAdmin
+        };
+        archive.insert(archived_proposal(id, topic)).unwrap();
+        if id % 3 == 0 {
+            archive.insert_ballot(id, NeuronId { id: 100 }, ballot(Vote::Yes));
+        }
+    }
+    let root_hash = archive.root_hash();
+    assert_ne!(root_hash, empty_root_hash);
+
+    // Archiving a ballot changes the root hash.
+    archive.insert_ballot(10, NeuronId { id: 101 }, ballot(Vote::No));
+    assert_ne!(archive.root_hash(), root_hash);
+    let root_hash = archive.root_hash();
+
+    let topic_witness = archive.topic_range_witness(Topic::Governance, 3..=7);
+    assert_eq!(topic_witness.reconstruct(), root_hash);
+    let ballots_witness = archive.ballots_range_witness(NeuronId { id: 100 }, 0..=u64::MAX);
+    assert_eq!(ballots_witness.reconstruct(), root_hash);
+    assert_eq!(
+        merge_hash_trees(
+            archive.ballots_range_witness(NeuronId { id: 100 }, 2..=8),
+            archive.topic_witness(Topic::Governance, &[3, 6]),
+        )
+        .reconstruct(),
+        root_hash
+    );
+
+    // The topic index reveals the hashes of the proposals of the topic in the
+    // range, and only those.
+    let expected_hash = Sha256::hash(&archived_proposal(4, Topic::Governance).encode_to_vec());
+    assert_eq!(
+        find_leaf(&topic_witness, &topic_key(Topic::Governance, 4)),
+        Some(&expected_hash[..])
+    );
+    assert_eq!(
+        find_leaf(&topic_witness, &topic_key(Topic::Governance, 5)),
+        None
+    );
+
+    // The ballots index reveals the hashes of the ballots of the neuron.
+    let expected_hash = Sha256::hash(&ballot(Vote::Yes).encode_to_vec());
+    for id in [3, 6, 9] {
+        assert_eq!(
+            find_leaf(&ballots_witness, &ballot_key(NeuronId { id: 100 }, id)),
+            Some(&expected_hash[..])
+        );
+    }
+}
+
+#[test]
+fn test_certified_hashes_are_restored() {
+    let mut archive = new_heap_based();
+    for id in 1..=5 {
+        archive
+            .insert(archived_proposal(id, Topic::Governance))
+            .unwrap();
+        archive.insert_ballot(id, NeuronId { id: 100 + id }, ballot(Vote::Yes));
+    }
+    let root_hash = archive.root_hash();
+
+    let StableProposalArchive {
+        proposals,
+        hashes,
+        topic_index,
+        ballots,
+        certified_hashes: _,
+        certified_topic_index: _,
+        certified_ballots: _,
+    } = archive;
+    let restored_archive = StableProposalArchiveBuilder {
+        proposals: proposals.into_memory(),
+        hashes: hashes.into_memory(),
+        topic_index: topic_index.into_memory(),
+        ballots: ballots.into_memory(),
+    }
+    .build();
+
+    assert_eq!(restored_archive.root_hash(), root_hash);
+}
+
+/// Returns the leaf labeled with `label`, if it is revealed by `tree`.
+fn find_leaf<'a>(tree: &'a HashTree<'_>, label: &[u8]) -> Option<&'a [u8]> {
+    match tree {
+        HashTree::Labeled(l, subtree) if *l == label => match subtree.as_ref() {
+            HashTree::Leaf(value) => Some(value.as_ref()),
+            _ => None,
+        },
+        HashTree::Labeled(_, subtree) => find_leaf(subtree, label),
+        HashTree::Fork(lr) => find_leaf(&lr.0, label).or_else(|| find_leaf(&lr.1, label)),
+        _ => None,
+    }
+}
@@ -46,10 +46,11 @@ use ic_nns_governance::{
         },
         validate_proposal_title, Environment, Governance, HeapGrowthPotential, TimeWarp,
         DEPRECATED_TOPICS, EXECUTE_NNS_FUNCTION_PAYLOAD_LISTING_BYTES_MAX,
-        MAX_DISSOLVE_DELAY_SECONDS, MAX_NEURON_AGE_FOR_AGE_BONUS,
-        MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS, MIN_DISSOLVE_DELAY_FOR_VOTE_ELIGIBILITY_SECONDS,
-        ONE_DAY_SECONDS, ONE_MONTH_SECONDS, ONE_YEAR_SECONDS, PROPOSAL_MOTION_TEXT_BYTES_MAX,
-        REWARD_DISTRIBUTION_PERIOD_SECONDS, WAIT_FOR_QUIET_DEADLINE_INCREASE_SECONDS,
+        MATURITY_DISBURSEMENT_DELAY_SECONDS, MAX_DISSOLVE_DELAY_SECONDS,
+        MAX_NEURON_AGE_FOR_AGE_BONUS, MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS,
+        MIN_DISSOLVE_DELAY_FOR_VOTE_ELIGIBILITY_SECONDS, ONE_DAY_SECONDS, ONE_MONTH_SECONDS,
+        ONE_YEAR_SECONDS, PROPOSAL_MOTION_TEXT_BYTES_MAX, REWARD_DISTRIBUTION_PERIOD_SECONDS,
+        WAIT_FOR_QUIET_DEADLINE_INCREASE_SECONDS,
     },
     init::GovernanceCanisterInitPayloadBuilder,
     pb::v1::{
@@ -64,9 +65,9 @@ use ic_nns_governance::{
             configure::Operation,
             disburse::Amount,
             ChangeAutoStakeMaturity, ClaimOrRefresh, Command, Configure, Disburse,
-            DisburseToNeuron, IncreaseDissolveDelay, JoinCommunityFund, LeaveCommunityFund,
-            MergeMaturity, NeuronIdOrSubaccount, SetDissolveTimestamp, Spawn, Split,
-            StartDissolving,
+            DisburseMaturity, DisburseToNeuron, IncreaseDissolveDelay, JoinCommunityFund,
+            LeaveCommunityFund, MergeMaturity, NeuronIdOrSubaccount, SetDissolveTimestamp, Spawn,
+            Split, StartDissolving,
         },
         manage_neuron_response::{self, Command as CommandResponse},
         neuron::{self, DissolveState, Followees},
@@ -79,10 +80,10 @@ use ic_nns_governance::{
         Governance as GovernanceProto, GovernanceChange, GovernanceError,
         IdealMatchedParticipationFunction, KnownNeuron, KnownNeuronData, ListNeurons,
         ListNeuronsResponse, ListProposalInfo, ListProposalInfoResponse, ManageNeuron,
-        ManageNeuronResponse, MostRecentMonthlyNodeProviderRewards, Motion, NetworkEconomics,
-        Neuron, NeuronChange, NeuronState, NeuronType, NeuronsFundData, NeuronsFundParticipation,
-        NeuronsFundSnapshot, NnsFunction, NodeProvider, Proposal, ProposalChange, ProposalData,
-        ProposalDataChange,
+        ManageNeuronResponse, MaturityDisbursement, MostRecentMonthlyNodeProviderRewards, Motion,
+        NetworkEconomics, Neuron, NeuronChange, NeuronState, NeuronType, NeuronsFundData,
+        NeuronsFundParticipation, NeuronsFundSnapshot, NnsFunction, NodeProvider, Proposal,
+        ProposalChange, ProposalData, ProposalDataChange,
         ProposalRewardStatus::{self, AcceptVotes, ReadyToSettle},
         ProposalStatus::{self, Rejected},
         RewardEvent, RewardNodeProvider, RewardNodeProviders, SetDefaultFollowees,
@@ -5522,6 +5523,93 @@ fn test_neuron_spawn() {
     );
 }
 
+/// Checks that:
+/// * Disbursing maturity validates the percentage and the caller.
+/// * The disbursed maturity is deducted right away and shows up as a
+///   disbursement in progress in the full neuron.
+/// * The ICP is only minted after the disbursement delay, with the maturity
+///   modulation applied.
+#[test]
+fn test_disburse_maturity() {
+    let from = *TEST_NEURON_1_OWNER_PRINCIPAL;
+    let nonce = 1234u64;
+
+    let (mut driver, mut gov, id, _) = governance_with_staked_neuron(
+        MIN_DISSOLVE_DELAY_FOR_VOTE_ELIGIBILITY_SECONDS,
+        1_000_000_000,
+        543212234,
+        from,
+        nonce,
+    );
+
+    let neuron_maturity_e8s: u64 = 1_000_000_000;
+    gov.with_neuron_mut(&id, |neuron| {
+        neuron.maturity_e8s_equivalent = neuron_maturity_e8s;
+    })
+    .expect("Neuron did not exist");
+
+    let disburse_maturity = |gov: &mut Governance, caller: &PrincipalId, percentage: u32| {
+        gov.disburse_maturity(
+            &id,
+            caller,
+            &DisburseMaturity {
+                percentage_to_disburse: percentage,
+                to_account: Some(
+                    AccountIdentifier::new(*TEST_NEURON_2_OWNER_PRINCIPAL, None).into(),
+                ),
+            },
+        )
+    };
+
+    assert_matches!(
+        disburse_maturity(&mut gov, &from, 0),
+        Err(GovernanceError { error_type, .. }) if error_type == PreconditionFailed as i32
+    );
+    assert_matches!(
+        disburse_maturity(&mut gov, &from, 101),
+        Err(GovernanceError { error_type, .. }) if error_type == PreconditionFailed as i32
+    );
+    assert_matches!(
+        disburse_maturity(&mut gov, &TEST_NEURON_2_OWNER_PRINCIPAL, 50),
+        Err(GovernanceError { error_type, .. }) if error_type == NotAuthorized as i32
+    );
+
+    let response = disburse_maturity(&mut gov, &from, 50).unwrap();
+    assert_eq!(response.amount_disbursed_e8s, neuron_maturity_e8s / 2);
+
+    let now = driver.now();
+    let full_neuron = gov.get_full_neuron(&id, &from).unwrap();
+    assert_eq!(full_neuron.maturity_e8s_equivalent, neuron_maturity_e8s / 2);
+    assert_eq!(
+        full_neuron.maturity_disbursements_in_progress,
+        vec![MaturityDisbursement {
+            amount_e8s: neuron_maturity_e8s / 2,
+            timestamp_of_disbursement_seconds: now,
+            account_to_disburse_to: Some(
+                AccountIdentifier::new(*TEST_NEURON_2_OWNER_PRINCIPAL, None).into()
+            ),
+            finalize_disbursement_timestamp_seconds: now + MATURITY_DISBURSEMENT_DELAY_SECONDS,
+        }]
+    );
+
+    // Running periodic tasks shouldn't cause the ICP to be minted.
+    run_periodic_tasks_on_governance_often_enough_to_spawn(&mut gov);
+    driver.assert_num_neuron_accounts_exist(1);
+
+    // Once the disbursement delay has passed, the ICP is minted with the
+    // maturity modulation applied.
+    driver.advance_time_by(MATURITY_DISBURSEMENT_DELAY_SECONDS);
+    run_periodic_tasks_on_governance_often_enough_to_spawn(&mut gov);
+    driver.assert_account_contains(
+        &AccountIdentifier::new(*TEST_NEURON_2_OWNER_PRINCIPAL, None),
+        (neuron_maturity_e8s / 2) * 101 / 100,
+    );
+
+    let full_neuron = gov.get_full_neuron(&id, &from).unwrap();
+    assert_eq!(full_neuron.maturity_e8s_equivalent, neuron_maturity_e8s / 2);
+    assert_eq!(full_neuron.maturity_disbursements_in_progress, vec![]);
+}
+
 #[test]
 fn test_neuron_spawn_with_subaccount() {
     let from = *TEST_NEURON_1_OWNER_PRINCIPAL;
@@ -8898,6 +8986,78 @@ async fn test_max_number_of_proposals_with_ballots() {
     .unwrap();
 }
 
+/// Settled proposals whose ballots are waiting to be moved to the proposal
+/// archive do not count towards MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS, but
+/// settled proposals that cannot be archived yet still do.
+#[test]
+fn test_max_number_of_proposals_with_ballots_ignores_proposals_being_archived() {
+    let fake_driver = fake::FakeDriver::default();
+    let proposals = (1..=MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS as u64)
+        .map(|id| {
+            let proposal_data = ProposalData {
+                id: Some(ProposalId { id }),
+                proposer: Some(NeuronId { id: 1 }),
+                proposal: Some(Proposal {
+                    title: Some("A Reasonable Title".to_string()),
+                    action: Some(proposal::Action::Motion(Motion {
+                        motion_text: "dummy text".to_string(),
+                    })),
+                    ..Default::default()
+                }),
+                ballots: hashmap! {
+                    1 => Ballot { vote: Vote::No as i32, voting_power: 1 },
+                },
+                decided_timestamp_seconds: 1,
+                reward_event_round: 1,
+                ..Default::default()
+            };
+            (id, proposal_data)
+        })
+        .collect::<BTreeMap<u64, ProposalData>>();
+    let mut gov = Governance::new(
+        GovernanceProto {
+            proposals,
+            ..fixture_two_neurons_second_is_bigger()
+        },
+        fake_driver.get_fake_env(),
+        fake_driver.get_fake_ledger(),
+        fake_driver.get_fake_cmc(),
+    );
+    let make_motion_proposal = |gov: &mut Governance| {
+        gov.make_proposal(
+            &NeuronId { id: 1 },
+            // Must match neuron 1's serialized_id.
+            &principal(1),
+            &Proposal {
+                title: Some("A Reasonable Title".to_string()),
+                summary: "one more proposal".to_string(),
+                action: Some(proposal::Action::Motion(Motion {
+                    motion_text: "dummy text".to_string(),
+                })),
+                ..Default::default()
+            },
+        )
+    };
+
+    // The proposals are rejected and settled, so they can be purged.
+    let new_proposal_id = make_motion_proposal(&mut gov).unwrap();
+    gov.heap_data.proposals.remove(&new_proposal_id.id);
+
+    // Once adopted but not executed, the proposals cannot be purged.
+    for proposal_data in gov.heap_data.proposals.values_mut() {
+        proposal_data.latest_tally = Some(Tally {
+            timestamp_seconds: 1,
+            yes: 2,
+            no: 0,
+            total: 2,
+        });
+    }
+    assert_matches!(
+        make_motion_proposal(&mut gov),
+        Err(GovernanceError { error_type, .. }) if error_type == ResourceExhausted as i32
+    );
+}
+
 #[test]
 fn test_proposal_gc() {
     let props = (1..1000)
@@ -1,11 +1,13 @@
-use candid::{Decode, Encode};
+use candid::{Decode, Encode, Nat};
 use canister_test::Canister;
 use cycles_minting_canister::{
-    ChangeSubnetTypeAssignmentArgs, CreateCanister, CreateCanisterError,
-    IcpXdrConversionRateCertifiedResponse, NotifyCreateCanister, NotifyError, NotifyMintCyclesArg,
-    NotifyMintCyclesSuccess, SubnetListWithType, SubnetTypesToSubnetsResponse,
-    UpdateSubnetTypeArgs, BAD_REQUEST_CYCLES_PENALTY, CYCLES_LEDGER_CANISTER_ID,
-    MEMO_CREATE_CANISTER, MEMO_MINT_CYCLES, MEMO_TOP_UP_CANISTER,
+    ChangeSubnetTypeAssignmentArgs, CreateCanister, CreateCanisterError, CreateCanisterWithIcp,
+    IcpXdrConversionRateCertifiedResponse, MintCyclesWithIcp, NotifyCreateCanister, NotifyError,
+    NotifyErrorCode, NotifyMintCyclesArg, NotifyMintCyclesSuccess, SubnetFilter,
+    SubnetListWithType, SubnetSelection, SubnetTypesToSubnetsResponse, TopUpWithIcp,
+    UpdateSubnetTypeArgs, BAD_REQUEST_CYCLES_PENALTY, CREATE_CANISTER_REFUND_FEE,
+    CYCLES_LEDGER_CANISTER_ID, MEMO_CREATE_CANISTER, MEMO_MINT_CYCLES, MEMO_TOP_UP_CANISTER,
+    TOP_UP_CANISTER_REFUND_FEE,
 };
 use dfn_candid::candid_one;
 use dfn_protobuf::protobuf;
@@ -30,13 +32,13 @@ use ic_nns_test_utils::{
     itest_helpers::{local_test_on_nns_subnet, NnsCanisters},
     neuron_helpers::get_neuron_1,
     state_test_helpers::{
-        cmc_set_default_authorized_subnetworks, set_up_universal_canister, setup_cycles_ledger,
-        setup_nns_canisters, update_with_sender,
+        cmc_set_default_authorized_subnetworks, icrc1_balance, set_up_universal_canister,
+        setup_cycles_ledger, setup_nns_canisters, update_with_sender,
     },
 };
 use ic_state_machine_tests::{StateMachine, WasmResult};
 use ic_test_utilities::universal_canister::{call_args, wasm};
-use ic_types::{CanisterId, PrincipalId};
+use ic_types::{CanisterId, Cycles, PrincipalId};
 use ic_types_test_utils::ids::subnet_test_id;
 use icp_ledger::{
     tokens_from_proto, AccountBalanceArgs, AccountIdentifier, BlockIndex, CyclesResponse, Memo,
@@ -44,6 +46,7 @@ use icp_ledger::{
     DEFAULT_TRANSFER_FEE,
 };
 use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
 
 /// Test that the CMC's `icp_xdr_conversion_rate` can be updated via Governance
 /// proposal.
@@ -1098,3 +1101,296 @@ fn cmc_notify_mint_cycles() {
     assert_eq!(minted, minted_duplicate);
     assert_eq!(balance, balance_duplicate);
 }
+
+/// Approves the CMC to spend `amount` from `TEST_USER1_PRINCIPAL`s ledger account.
+fn approve_cmc(state_machine: &StateMachine, amount: Tokens) {
+    let approve_args = ApproveArgs {
+        from_subaccount: None,
+        spender: Account {
+            owner: CYCLES_MINTING_CANISTER_ID.get().into(),
+            subaccount: None,
+        },
+        amount: Nat::from(amount.get_e8s()),
+        expected_allowance: None,
+        expires_at: None,
+        fee: None,
+        memo: None,
+        created_at_time: None,
+    };
+    let WasmResult::Reply(res) = state_machine
+        .execute_ingress_as(
+            *TEST_USER1_PRINCIPAL,
+            CanisterId::from_u64(LEDGER_CANISTER_INDEX_IN_NNS_SUBNET),
+            "icrc2_approve",
+            Encode!(&approve_args).unwrap(),
+        )
+        .unwrap()
+    else {
+        panic!("icrc2_approve rejected")
+    };
+    Decode!(&res, Result<Nat, ApproveError>)
+        .unwrap()
+        .expect("failed to approve the CMC");
+}
+
+fn user1_icp_balance(state_machine: &StateMachine) -> Tokens {
+    icrc1_balance(
+        state_machine,
+        CanisterId::from_u64(LEDGER_CANISTER_INDEX_IN_NNS_SUBNET),
+        Account {
+            owner: (*TEST_USER1_PRINCIPAL).into(),
+            subaccount: None,
+        },
+    )
+}
+
+fn state_machine_time_nanos(state_machine: &StateMachine) -> u64 {
+    state_machine
+        .time()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64
+}
+
+fn mint_cycles_with_icp(
+    state_machine: &StateMachine,
+    arg: &MintCyclesWithIcp,
+) -> Result<NotifyMintCyclesSuccess, NotifyError> {
+    if let WasmResult::Reply(res) = state_machine
+        .execute_ingress_as(
+            *TEST_USER1_PRINCIPAL,
+            CYCLES_MINTING_CANISTER_ID,
+            "mint_cycles_with_icp",
+            Encode!(arg).unwrap(),
+        )
+        .unwrap()
+    {
+        Decode!(&res, Result<NotifyMintCyclesSuccess, NotifyError>).unwrap()
+    } else {
+        panic!("mint_cycles_with_icp rejected")
+    }
+}
+
+#[test]
+fn cmc_mint_cycles_with_icp() {
+    let account = AccountIdentifier::new(*TEST_USER1_PRINCIPAL, None);
+    let main_account = Account {
+        owner: (*TEST_USER1_PRINCIPAL).into(),
+        subaccount: None,
+    };
+    let icpts = Tokens::new(100, 0).unwrap();
+
+    let state_machine = StateMachine::new();
+    let nns_init_payloads = NnsInitPayloadsBuilder::new()
+        .with_test_neurons()
+        .with_ledger_account(account, icpts)
+        .build();
+    setup_nns_canisters(&state_machine, nns_init_payloads);
+    setup_cycles_ledger(&state_machine);
+
+    let created_at_time = state_machine_time_nanos(&state_machine);
+    let arg = MintCyclesWithIcp {
+        amount: Tokens::new(1, 0).unwrap(),
+        from_subaccount: None,
+        created_at_time,
+        to_subaccount: None,
+        deposit_memo: None,
+    };
+
+    // Without an approval, the CMC cannot pull the payment.
+    assert_matches::assert_matches!(
+        mint_cycles_with_icp(&state_machine, &arg),
+        Err(NotifyError::Other { error_code, .. }) if error_code == NotifyErrorCode::TransferFromFailed as u64
+    );
+
+    approve_cmc(&state_machine, Tokens::new(10, 0).unwrap());
+
+    let success = mint_cycles_with_icp(&state_machine, &arg).expect("failed to mint cycles");
+    assert_eq!(success.minted, Nat::from(100_000_000_000_000u64));
+    assert_eq!(
+        cycles_ledger_balance_of(&state_machine, main_account),
+        100_000_000_000_000
+    );
+
+    // Retrying the same call returns the same result without pulling more ICP.
+    assert_eq!(mint_cycles_with_icp(&state_machine, &arg), Ok(success));
+    assert_eq!(
+        cycles_ledger_balance_of(&state_machine, main_account),
+        100_000_000_000_000
+    );
+}
+
+fn create_canister_with_icp(
+    state_machine: &StateMachine,
+    arg: &CreateCanisterWithIcp,
+) -> Result<CanisterId, NotifyError> {
+    if let WasmResult::Reply(res) = state_machine
+        .execute_ingress_as(
+            *TEST_USER1_PRINCIPAL,
+            CYCLES_MINTING_CANISTER_ID,
+            "create_canister_with_icp",
+            Encode!(arg).unwrap(),
+        )
+        .unwrap()
+    {
+        Decode!(&res, Result<CanisterId, NotifyError>).unwrap()
+    } else {
+        panic!("create_canister_with_icp rejected")
+    }
+}
+
+#[test]
+fn cmc_create_canister_with_icp() {
+    let account = AccountIdentifier::new(*TEST_USER1_PRINCIPAL, None);
+    let icpts = Tokens::new(100, 0).unwrap();
+    let neuron = get_neuron_1();
+
+    let mut state_machine = StateMachine::new();
+    let nns_init_payloads = NnsInitPayloadsBuilder::new()
+        .with_test_neurons()
+        .with_ledger_account(account, icpts)
+        .build();
+    setup_nns_canisters(&state_machine, nns_init_payloads);
+    let subnet_id = state_machine.get_subnet_id();
+    cmc_set_default_authorized_subnetworks(
+        &mut state_machine,
+        vec![subnet_id],
+        neuron.principal_id,
+        neuron.neuron_id,
+    );
+
+    let amount = Tokens::new(10, 0).unwrap();
+    let created_at_time = state_machine_time_nanos(&state_machine);
+    let arg = CreateCanisterWithIcp {
+        amount,
+        from_subaccount: None,
+        created_at_time,
+        controller: None,
+        subnet_selection: None,
+        settings: None,
+    };
+
+    // Without an approval, the CMC cannot pull the payment.
+    assert_matches::assert_matches!(
+        create_canister_with_icp(&state_machine, &arg),
+        Err(NotifyError::Other { error_code, .. }) if error_code == NotifyErrorCode::TransferFromFailed as u64
+    );
+
+    approve_cmc(&state_machine, Tokens::new(50, 0).unwrap());
+
+    let canister_id =
+        create_canister_with_icp(&state_machine, &arg).expect("failed to create a canister");
+    let status = canister_status(&state_machine, *TEST_USER1_PRINCIPAL, canister_id).unwrap();
+    assert_eq!(status.controllers(), vec![*TEST_USER1_PRINCIPAL]);
+    let balance = user1_icp_balance(&state_machine);
+
+    // Retrying the same call returns the same canister without pulling more ICP.
+    assert_eq!(
+        create_canister_with_icp(&state_machine, &arg),
+        Ok(canister_id)
+    );
+    assert_eq!(user1_icp_balance(&state_machine), balance);
+
+    // The CMC refunds the payment minus fees if it cannot create the canister.
+    let arg = CreateCanisterWithIcp {
+        created_at_time: created_at_time + 1,
+        subnet_selection: Some(SubnetSelection::Filter(SubnetFilter {
+            subnet_type: Some("fake_subnet_type".to_string()),
+        })),
+        ..arg
+    };
+    let Err(NotifyError::Refunded {
+        reason,
+        block_index,
+    }) = create_canister_with_icp(&state_machine, &arg)
+    else {
+        panic!("expected the payment to be refunded")
+    };
+    assert!(reason.contains("fake_subnet_type does not exist"));
+    assert!(block_index.is_some());
+    assert_eq!(
+        user1_icp_balance(&state_machine),
+        balance
+            .checked_sub(&DEFAULT_TRANSFER_FEE)
+            .and_then(|b| b.checked_sub(&DEFAULT_TRANSFER_FEE))
+            .and_then(|b| b.checked_sub(&CREATE_CANISTER_REFUND_FEE))
+            .unwrap()
+    );
+}
+
+fn top_up_with_icp(
+    state_machine: &StateMachine,
+    arg: &TopUpWithIcp,
+) -> Result<Cycles, NotifyError> {
+    if let WasmResult::Reply(res) = state_machine
+        .execute_ingress_as(
+            *TEST_USER1_PRINCIPAL,
+            CYCLES_MINTING_CANISTER_ID,
+            "top_up_with_icp",
+            Encode!(arg).unwrap(),
+        )
+        .unwrap()
+    {
+        Decode!(&res, Result<Cycles, NotifyError>).unwrap()
+    } else {
+        panic!("top_up_with_icp rejected")
+    }
+}
+
+#[test]
+fn cmc_top_up_with_icp() {
+    let account = AccountIdentifier::new(*TEST_USER1_PRINCIPAL, None);
+    let icpts = Tokens::new(100, 0).unwrap();
+
+    let state_machine = StateMachine::new();
+    let nns_init_payloads = NnsInitPayloadsBuilder::new()
+        .with_test_neurons()
+        .with_ledger_account(account, icpts)
+        .build();
+    setup_nns_canisters(&state_machine, nns_init_payloads);
+
+    let amount = Tokens::new(1, 0).unwrap();
+    let created_at_time = state_machine_time_nanos(&state_machine);
+    let arg = TopUpWithIcp {
+        amount,
+        from_subaccount: None,
+        created_at_time,
+        canister_id: GOVERNANCE_CANISTER_ID,
+    };
+
+    // Without an approval, the CMC cannot pull the payment.
+    assert_matches::assert_matches!(
+        top_up_with_icp(&state_machine, &arg),
+        Err(NotifyError::Other { error_code, .. }) if error_code == NotifyErrorCode::TransferFromFailed as u64
+    );
+
+    approve_cmc(&state_machine, Tokens::new(10, 0).unwrap());
+
+    let cycles = top_up_with_icp(&state_machine, &arg).expect("failed to top up the canister");
+    assert_eq!(cycles, Cycles::new(100_000_000_000_000));
+    let balance = user1_icp_balance(&state_machine);
+
+    // Retrying the same call returns the same result without pulling more ICP.
+    assert_eq!(top_up_with_icp(&state_machine, &arg), Ok(cycles));
+    assert_eq!(user1_icp_balance(&state_machine), balance);
+
+    // The CMC refunds the payment minus fees if it cannot top up the canister.
+    let arg = TopUpWithIcp {
+        created_at_time: created_at_time + 1,
+        canister_id: CanisterId::from_u64(1_000_000),
+        ..arg
+    };
+    let Err(NotifyError::Refunded { block_index, .. }) = top_up_with_icp(&state_machine, &arg)
+    else {
+        panic!("expected the payment to be refunded")
+    };
+    assert!(block_index.is_some());
+    assert_eq!(
+        user1_icp_balance(&state_machine),
+        balance
+            .checked_sub(&DEFAULT_TRANSFER_FEE)
+            .and_then(|b| b.checked_sub(&DEFAULT_TRANSFER_FEE))
+            .and_then(|b| b.checked_sub(&TOP_UP_CANISTER_REFUND_FEE))
+            .unwrap()
+    );
+}
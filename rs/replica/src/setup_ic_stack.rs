@@ -314,6 +314,7 @@ pub fn construct_ic_stack(
         config.http_handler.clone(),
         execution_services.ingress_filter,
         execution_services.async_query_handler,
+        execution_services.dry_run_handler,
         ingress_throttler,
         ingress_tx.clone(),
         Arc::clone(&state_manager) as Arc<_>,
@@ -51,6 +51,101 @@ pub type GetAccountIdentifierTransactionsResult =
     Result<GetAccountIdentifierTransactionsResponse, GetAccountIdentifierTransactionsError>;
 pub type GetAccountTransactionsResult = GetAccountIdentifierTransactionsResult;
 
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct GetAccountIdentifierBalanceAtArgs {
+    pub account_identifier: AccountIdentifier,
+    // The index of the block after which the balance is computed.
+    pub block_index: BlockIndex,
+    // The cursor returned by a previous call for the same account_identifier
+    // and block_index that ran out of its budget.
+    pub resume: Option<BalanceAtCursor>,
+}
+
+/// The progress of a balance computation that did not fit into a single call.
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct BalanceAtCursor {
+    // The balance of the account_identifier right before the block at reverted_from.
+    pub balance: u64,
+    // The blocks from this index onwards have been reverted.
+    pub reverted_from: BlockIndex,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum BalanceAt {
+    // The balance of the account_identifier after block_index.
+    Balance(u64),
+    // The call reverted the maximum number of blocks per call without
+    // reaching block_index. Pass the cursor as resume to continue.
+    Incomplete(BalanceAtCursor),
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct ListBalancesAtCursor {
+    // All account_identifiers up to and including this account_identifier
+    // have been listed, unless pending is set.
+    pub account_identifier: AccountIdentifier,
+    // The progress of the balance of account_identifier if it did not fit
+    // into the call.
+    pub pending: Option<BalanceAtCursor>,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct ListBalancesAtArgs {
+    // The index of the block after which the balances are computed.
+    pub block_index: BlockIndex,
+    // The cursor returned by the previous call.
+    // If None then the results will start from the first
+    // account_identifier in natural order.
+    pub cursor: Option<ListBalancesAtCursor>,
+    // Maximum number of balances to fetch.
+    pub max_results: u64,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct AccountIdentifierBalance {
+    pub account_identifier: AccountIdentifier,
+    pub balance: u64,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct ListBalancesAtResponse {
+    pub block_index: BlockIndex,
+    // The hash of the block at block_index. It lets clients check that all
+    // the pages of a snapshot refer to the same block.
+    pub block_hash: Vec<u8>,
+    // The non-zero balances after block_index, ordered by account_identifier.
+    pub balances: Vec<AccountIdentifierBalance>,
+    // The cursor to pass to the next call, None once all the
+    // account_identifiers have been listed.
+    pub next: Option<ListBalancesAtCursor>,
+    // The certificate of the certified data of the index. Only set if
+    // block_index is the last indexed block and the call is a query.
+    pub certificate: Option<Vec<u8>>,
+    // A CBOR-encoded hash tree whose root hash is the certified data of the
+    // index. Only set if block_index is the last indexed block. The tree has
+    // the following structure:
+    //
+    //   balances -- [account_identifier hash] -- [big-endian balance]
+    //   last_block_hash -- [hash of the last indexed block]
+    //   last_block_index -- [big-endian index of the last indexed block]
+    //
+    // where the account_identifier hash is the account_identifier without
+    // its checksum. The tree reveals all the account_identifiers from the
+    // cursor account_identifier up to the next cursor account_identifier, so
+    // that it also proves that no non-zero balance was left out. Snapshots at
+    // older blocks can be checked by reverting the blocks up to the last
+    // indexed block, whose hash chain ends at last_block_hash.
+    pub hash_tree: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct BalanceAtError {
+    pub message: String,
+}
+
+pub type GetAccountIdentifierBalanceAtResult = Result<BalanceAt, BalanceAtError>;
+pub type ListBalancesAtResult = Result<ListBalancesAtResponse, BalanceAtError>;
+
 #[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
 pub struct Status {
     pub num_blocks_synced: BlockIndex,
@@ -3,11 +3,15 @@ use ic_canister_log::{export as export_logs, log};
 use ic_canisters_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
 use ic_cdk_macros::{init, post_upgrade, query};
 use ic_cdk_timers::TimerId;
+use ic_certified_map::{fork, labeled, labeled_hash, AsHashTree, HashTree, RbTree};
 use ic_icp_index::logs::{P0, P1};
 use ic_icp_index::{
+    AccountIdentifierBalance, BalanceAt, BalanceAtCursor, BalanceAtError,
+    GetAccountIdentifierBalanceAtArgs, GetAccountIdentifierBalanceAtResult,
     GetAccountIdentifierTransactionsArgs, GetAccountIdentifierTransactionsResponse,
-    GetAccountIdentifierTransactionsResult, GetAccountTransactionsResult, InitArg, Log, LogEntry,
-    Priority, Status, TransactionWithId,
+    GetAccountIdentifierTransactionsResult, GetAccountTransactionsResult, InitArg,
+    ListBalancesAtArgs, ListBalancesAtCursor, ListBalancesAtResponse, ListBalancesAtResult, Log,
+    LogEntry, Priority, Status, TransactionWithId,
 };
 use ic_icrc1_index_ng::GetAccountTransactionsArgs;
 use ic_ledger_core::block::{BlockType, EncodedBlock};
@@ -28,11 +32,21 @@ use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::Reverse;
+use std::ops::Bound::{Excluded, Unbounded};
 use std::time::Duration;
 
 /// The maximum number of blocks to return in a single [get_blocks] request.
 const DEFAULT_MAX_BLOCKS_PER_RESPONSE: usize = MAX_BLOCKS_PER_REQUEST;
 
+/// The maximum number of balances to return in a single [list_balances_at] request.
+const MAX_BALANCES_PER_RESPONSE: u64 = 500;
+
+/// The maximum number of blocks that a single [get_account_identifier_balance_at]
+/// or [list_balances_at] request reverts to compute historical balances.
+/// [list_balances_at] also counts every account_identifier it reads against
+/// this budget. Requests that need more return a cursor to continue from.
+const MAX_BLOCKS_REVERTED_PER_CALL: u64 = 10_000;
+
 /// Memory ids for stable structures used in the icp index canister
 const STATE_MEMORY_ID: MemoryId = MemoryId::new(0);
 const BLOCK_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(1);
@@ -43,6 +57,11 @@ const ACCOUNTIDENTIFIER_DATA_MEMORY_ID: MemoryId = MemoryId::new(4);
 const DEFAULT_MAX_WAIT_TIME: Duration = Duration::from_secs(2);
 const DEFAULT_RETRY_WAIT_TIME: Duration = Duration::from_secs(1);
 
+/// The labels of the certified tree of the index, see [certified_tree].
+const BALANCES_LABEL: &[u8] = b"balances";
+const LAST_BLOCK_HASH_LABEL: &[u8] = b"last_block_hash";
+const LAST_BLOCK_INDEX_LABEL: &[u8] = b"last_block_index";
+
 type VM = VirtualMemory<DefaultMemoryImpl>;
 type StateCell = StableCell<State, VM>;
 type BlockLog = StableLog<Vec<u8>, VM, VM>;
@@ -57,6 +76,9 @@ type AccountIdentifierBlockIdsMap = StableBTreeMap<AccountIdentifierBlockIdsMapK
 type AccountIdentifierDataMapKey = (AccountIdentifierDataType, [u8; 28]);
 type AccountIdentifierDataMap = StableBTreeMap<AccountIdentifierDataMapKey, u64, VM>;
 
+// The non-zero balances of the account_identifiers keyed by their hash.
+type CertifiedBalances = RbTree<[u8; 28], Vec<u8>>;
+
 thread_local! {
     /// Static memory manager to manage the memory available for stable structures.
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
@@ -82,6 +104,11 @@ thread_local! {
     static ACCOUNTIDENTIFIER_DATA: RefCell<AccountIdentifierDataMap> = with_memory_manager(|memory_manager| {
         RefCell::new(AccountIdentifierDataMap::init(memory_manager.get(ACCOUNTIDENTIFIER_DATA_MEMORY_ID)))
     });
+
+    /// The balances of [ACCOUNTIDENTIFIER_DATA] after the last indexed block,
+    /// as a certified tree. It only lives in the heap and is rebuilt after
+    /// upgrades.
+    static CERTIFIED_BALANCES: RefCell<CertifiedBalances> = RefCell::new(RbTree::new());
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -214,6 +241,14 @@ fn get_balance(account_identifier: AccountIdentifier) -> u64 {
 fn change_balance(account_identifier: AccountIdentifier, f: impl FnOnce(u64) -> u64) {
     let key = balance_key(account_identifier);
     let new_balance = f(get_balance(account_identifier));
+    CERTIFIED_BALANCES.with(|certified_balances| {
+        let mut certified_balances = certified_balances.borrow_mut();
+        if new_balance == 0 {
+            certified_balances.delete(&account_identifier.hash);
+        } else {
+            certified_balances.insert(account_identifier.hash, new_balance.to_be_bytes().to_vec());
+        }
+    });
     if new_balance == 0 {
         with_account_identifier_data(|account_identifier_data| {
             account_identifier_data.remove(&key)
@@ -229,6 +264,80 @@ fn balance_key(account_identifier: AccountIdentifier) -> (AccountIdentifierDataT
     (AccountIdentifierDataType::Balance, account_identifier.hash)
 }
 
+fn rebuild_certified_balances() {
+    let certified_balances = with_account_identifier_data(|account_identifier_data| {
+        let mut certified_balances = RbTree::new();
+        for ((_, hash), balance) in account_identifier_data.iter() {
+            certified_balances.insert(hash, balance.to_be_bytes().to_vec());
+        }
+        certified_balances
+    });
+    CERTIFIED_BALANCES.with(|cell| *cell.borrow_mut() = certified_balances);
+}
+
+/// Returns the index and the hash of the last indexed block, if any.
+fn last_block() -> Option<(BlockIndex, Vec<u8>)> {
+    let block_index = with_blocks(|blocks| blocks.len()).checked_sub(1)?;
+    let block = with_blocks(|blocks| blocks.get(block_index))?;
+    let block_hash = Block::block_hash(&EncodedBlock::from_vec(block));
+    Some((block_index, block_hash.as_slice().to_vec()))
+}
+
+/// Returns the certified tree of the index, which has the following
+/// structure:
+///
+///   balances -- [account_identifier hash] -- [big-endian balance]
+///   last_block_hash -- [hash of the last indexed block]
+///   last_block_index -- [big-endian index of the last indexed block]
+///
+/// The balances are revealed by `balances_witness`, or pruned if it is None.
+fn certified_tree<'a>(
+    certified_balances: &'a CertifiedBalances,
+    balances_witness: Option<HashTree<'a>>,
+    (last_block_index, last_block_hash): (BlockIndex, Vec<u8>),
+) -> HashTree<'a> {
+    let balances = match balances_witness {
+        Some(witness) => labeled(BALANCES_LABEL, witness),
+        None => HashTree::Pruned(labeled_hash(
+            BALANCES_LABEL,
+            &certified_balances.root_hash(),
+        )),
+    };
+    fork(
+        balances,
+        fork(
+            labeled(
+                LAST_BLOCK_HASH_LABEL,
+                HashTree::Leaf(Cow::Owned(last_block_hash)),
+            ),
+            labeled(
+                LAST_BLOCK_INDEX_LABEL,
+                HashTree::Leaf(Cow::Owned(last_block_index.to_be_bytes().to_vec())),
+            ),
+        ),
+    )
+}
+
+/// Sets the certified data of the index to the root hash of [certified_tree].
+fn update_certified_data() {
+    let Some(last_block) = last_block() else {
+        return;
+    };
+    let root_hash = CERTIFIED_BALANCES.with(|certified_balances| {
+        certified_tree(&certified_balances.borrow(), None, last_block).reconstruct()
+    });
+    ic_cdk::api::set_certified_data(&root_hash);
+}
+
+/// Encodes a hash tree into the self-describing CBOR expected by clients.
+fn encode_hash_tree(tree: HashTree<'_>) -> Vec<u8> {
+    let mut serializer = serde_cbor::ser::Serializer::new(vec![]);
+    serializer.self_describe().unwrap();
+    tree.serialize(&mut serializer)
+        .expect("failed to serialize a hash tree");
+    serializer.into_inner()
+}
+
 #[init]
 #[candid_method(init)]
 fn init(init_arg: InitArg) {
@@ -243,6 +352,8 @@ fn init(init_arg: InitArg) {
 
 #[post_upgrade]
 fn post_upgrade() {
+    rebuild_certified_balances();
+    update_certified_data();
     set_build_index_timer(Duration::from_secs(1));
 }
 
@@ -388,6 +499,9 @@ fn append_blocks(new_blocks: Vec<EncodedBlock>) -> Result<(), String> {
     // the index of the next block that we
     // are going to append
     let mut block_index = with_blocks(|blocks| blocks.len());
+    // the blocks appended so far are certified even if a block fails
+    let _update_certified_data_guard =
+        (!new_blocks.is_empty()).then(|| guard((), |_| update_certified_data()));
     for block in new_blocks {
         // append the encoded block to the block log
         with_blocks(|blocks| {
@@ -435,6 +549,135 @@ fn process_balance_changes(block_index: BlockIndex, block: &Block) -> Result<(),
     Ok(())
 }
 
+/// Returns `balance` as it was before `block` changed the balance of `account_identifier`.
+fn revert_balance_changes(
+    block_index: BlockIndex,
+    account_identifier: AccountIdentifier,
+    block: &Block,
+    balance: u64,
+) -> u64 {
+    let add = |balance: u64, amount: u64| {
+        balance.checked_add(amount).unwrap_or_else(|| {
+            ic_cdk::trap(&format!(
+                "Reverting block {} caused an overflow for account_identifier {}",
+                block_index, account_identifier
+            ))
+        })
+    };
+    let sub = |balance: u64, amount: u64| {
+        balance.checked_sub(amount).unwrap_or_else(|| {
+            ic_cdk::trap(&format!(
+                "Reverting block {} caused an underflow for account_identifier {}",
+                block_index, account_identifier
+            ))
+        })
+    };
+    match block.transaction.operation {
+        Operation::Burn { from, amount, .. } if from == account_identifier => {
+            add(balance, amount.get_e8s())
+        }
+        Operation::Mint { to, amount } if to == account_identifier => {
+            sub(balance, amount.get_e8s())
+        }
+        Operation::Transfer {
+            from,
+            to,
+            amount,
+            fee,
+            ..
+        } => {
+            let mut balance = balance;
+            if to == account_identifier {
+                balance = sub(balance, amount.get_e8s());
+            }
+            if from == account_identifier {
+                balance = add(balance, add(amount.get_e8s(), fee.get_e8s()));
+            }
+            balance
+        }
+        Operation::Approve { from, fee, .. } if from == account_identifier => {
+            add(balance, fee.get_e8s())
+        }
+        _ => balance,
+    }
+}
+
+/// The outcome of [get_balance_at].
+enum BalanceAtProgress {
+    Complete(u64),
+    /// The budget ran out. `balance` is the balance of the account_identifier
+    /// right before the block at `reverted_from` was applied.
+    Incomplete {
+        balance: u64,
+        reverted_from: BlockIndex,
+    },
+}
+
+impl From<BalanceAtProgress> for BalanceAt {
+    fn from(progress: BalanceAtProgress) -> Self {
+        match progress {
+            BalanceAtProgress::Complete(balance) => BalanceAt::Balance(balance),
+            BalanceAtProgress::Incomplete {
+                balance,
+                reverted_from,
+            } => BalanceAt::Incomplete(BalanceAtCursor {
+                balance,
+                reverted_from,
+            }),
+        }
+    }
+}
+
+/// Returns the balance of `account_identifier` right after the block at
+/// `block_index` was applied. The balance is computed by reverting, from the
+/// most recent, the blocks of the account_identifier after `block_index`,
+/// starting from the current balance or from the `(balance, reverted_from)`
+/// progress of a previous call. At most `budget` blocks are reverted and
+/// `budget` is reduced by the number of reverted blocks.
+fn get_balance_at(
+    account_identifier: AccountIdentifier,
+    block_index: BlockIndex,
+    resume: Option<(u64, BlockIndex)>,
+    budget: &mut u64,
+) -> BalanceAtProgress {
+    let limit = *budget as usize;
+    let (mut balance, before) =
+        resume.unwrap_or_else(|| (get_balance(account_identifier), u64::MAX));
+    let first_key = account_identifier_block_ids_key(account_identifier, before);
+    let last_key = account_identifier_block_ids_key(account_identifier, block_index);
+    let newer_block_ids = with_account_identifier_block_ids(|account_identifier_block_ids| {
+        account_identifier_block_ids
+            .range((Excluded(first_key), Excluded(last_key)))
+            .take(limit + 1)
+            .map(|(k, _)| k.1 .0)
+            .collect::<Vec<BlockIndex>>()
+    });
+    for &id in newer_block_ids.iter().take(limit) {
+        let block = with_blocks(|blocks| blocks.get(id)).unwrap_or_else(|| {
+            ic_cdk::api::trap(&format!(
+                "Block {} not found in the block log, account_identifier blocks map is corrupted!",
+                id
+            ))
+        });
+        let block = decode_encoded_block(id, EncodedBlock::from_vec(block))
+            .unwrap_or_else(|err| ic_cdk::api::trap(&err));
+        balance = revert_balance_changes(id, account_identifier, &block, balance);
+    }
+
+    if newer_block_ids.len() <= limit {
+        *budget -= newer_block_ids.len() as u64;
+        BalanceAtProgress::Complete(balance)
+    } else {
+        *budget = 0;
+        BalanceAtProgress::Incomplete {
+            balance,
+            reverted_from: limit
+                .checked_sub(1)
+                .map_or(before, |last| newer_block_ids[last]),
+        }
+    }
+}
+
 fn debit(block_index: BlockIndex, account_identifier: AccountIdentifier, amount: u64) {
     change_balance(account_identifier, |balance| {
         if balance < amount {
@@ -632,6 +875,12 @@ fn get_account_identifier_transactions(
     })
 }
 
+// NB: unlike the ICRC-1 index-ng, this index doesn't offer `list_subaccounts`.
+// ICP blocks only store `AccountIdentifier`s, also for transactions created via
+// `icrc1_transfer` and `icrc2_*`, and an `AccountIdentifier` is a one-way hash of
+// the owner and the subaccount. The subaccounts of a principal therefore cannot
+// be recovered from the blocks. Transactions of a known `Account` are looked up
+// via its `AccountIdentifier`.
 #[query]
 #[candid_method(query)]
 fn get_account_transactions(arg: GetAccountTransactionsArgs) -> GetAccountTransactionsResult {
@@ -649,6 +898,183 @@ fn get_account_transactions(arg: GetAccountTransactionsArgs) -> GetAccountTransa
     })
 }
 
+/// Checks that the block at `block_index` has been indexed.
+fn check_block_index_is_indexed(block_index: BlockIndex) -> Result<(), BalanceAtError> {
+    let num_blocks_synced = with_blocks(|blocks| blocks.len());
+    if block_index >= num_blocks_synced {
+        return Err(BalanceAtError {
+            message: format!(
+                "Block {} has not been indexed yet, the index has {} blocks",
+                block_index, num_blocks_synced
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Checks that `cursor` can continue the computation of a balance at `block_index`.
+fn resume_balance_at(
+    block_index: BlockIndex,
+    cursor: BalanceAtCursor,
+) -> Result<(u64, BlockIndex), BalanceAtError> {
+    if cursor.reverted_from <= block_index {
+        return Err(BalanceAtError {
+            message: format!("Invalid cursor {:?} for block {}", cursor, block_index),
+        });
+    }
+    Ok((cursor.balance, cursor.reverted_from))
+}
+
+/// Returns the first account_identifier after `after` that took part in a block.
+fn next_account_identifier(after: Option<AccountIdentifier>) -> Option<AccountIdentifier> {
+    with_account_identifier_block_ids(|account_identifier_block_ids| {
+        let range = match after {
+            // (account_identifier, Reverse(0)) is the last possible key of the account_identifier.
+            Some(after) => (
+                Excluded(account_identifier_block_ids_key(after, 0)),
+                Unbounded,
+            ),
+            None => (Unbounded, Unbounded),
+        };
+        account_identifier_block_ids
+            .range(range)
+            .next()
+            .map(|(k, _)| AccountIdentifier { hash: k.0 })
+    })
+}
+
+#[query]
+#[candid_method(query)]
+fn get_account_identifier_balance_at(
+    arg: GetAccountIdentifierBalanceAtArgs,
+) -> GetAccountIdentifierBalanceAtResult {
+    check_block_index_is_indexed(arg.block_index)?;
+    let resume = arg
+        .resume
+        .map(|cursor| resume_balance_at(arg.block_index, cursor))
+        .transpose()?;
+    let mut budget = MAX_BLOCKS_REVERTED_PER_CALL;
+    Ok(get_balance_at(arg.account_identifier, arg.block_index, resume, &mut budget).into())
+}
+
+#[query]
+#[candid_method(query)]
+fn list_balances_at(arg: ListBalancesAtArgs) -> ListBalancesAtResult {
+    check_block_index_is_indexed(arg.block_index)?;
+    let block_hash = with_blocks(|blocks| blocks.get(arg.block_index))
+        .map(|block| Block::block_hash(&EncodedBlock::from_vec(block)))
+        .unwrap_or_else(|| {
+            ic_cdk::api::trap(&format!(
+                "Block {} not found in the block log",
+                arg.block_index
+            ))
+        });
+    let first_account_identifier = arg.cursor.as_ref().map(|cursor| cursor.account_identifier);
+    let length = arg.max_results.min(MAX_BALANCES_PER_RESPONSE) as usize;
+
+    // The last account_identifier whose balance has been computed and the
+    // account_identifier whose balance computation has to be resumed, if any.
+    let (mut after, mut pending) = match arg.cursor {
+        Some(ListBalancesAtCursor {
+            account_identifier,
+            pending: Some(cursor),
+        }) => (
+            None,
+            Some((
+                account_identifier,
+                resume_balance_at(arg.block_index, cursor)?,
+            )),
+        ),
+        Some(ListBalancesAtCursor {
+            account_identifier,
+            pending: None,
+        }) => (Some(account_identifier), None),
+        None => (None, None),
+    };
+
+    // The balances of the account_identifiers that are zero are not stored,
+    // so the account_identifiers are read from the account_identifier block
+    // ids map, one account_identifier at a time, until enough balances are
+    // found or the budget of reverted blocks runs out. Reading an
+    // account_identifier costs one unit of budget on top of the blocks
+    // reverted for it, so that a call cannot read an unbounded number of
+    // account_identifiers that were drained before block_index.
+    let mut budget = MAX_BLOCKS_REVERTED_PER_CALL;
+    let mut balances = vec![];
+    let next = loop {
+        let (account_identifier, resume) = match pending.take() {
+            Some((account_identifier, resume)) => (account_identifier, Some(resume)),
+            None => {
+                if balances.len() >= length || budget == 0 {
+                    break after.map(|account_identifier| ListBalancesAtCursor {
+                        account_identifier,
+                        pending: None,
+                    });
+                }
+                match next_account_identifier(after) {
+                    Some(account_identifier) => (account_identifier, None),
+                    None => break None,
+                }
+            }
+        };
+        match get_balance_at(account_identifier, arg.block_index, resume, &mut budget) {
+            BalanceAtProgress::Complete(balance) => {
+                budget = budget.saturating_sub(1);
+                if balance > 0 {
+                    balances.push(AccountIdentifierBalance {
+                        account_identifier,
+                        balance,
+                    });
+                }
+                after = Some(account_identifier);
+            }
+            BalanceAtProgress::Incomplete {
+                balance,
+                reverted_from,
+            } => {
+                break Some(ListBalancesAtCursor {
+                    account_identifier,
+                    pending: Some(BalanceAtCursor {
+                        balance,
+                        reverted_from,
+                    }),
+                })
+            }
+        }
+    };
+
+    // The balances after the last indexed block are the certified ones, so
+    // the page is certified by revealing all the account_identifiers it
+    // covers.
+    let (certificate, hash_tree) = match last_block() {
+        Some(last_block) if last_block.0 == arg.block_index => {
+            let first_key = first_account_identifier.map_or([0; 28], |a| a.hash);
+            let last_key = next
+                .as_ref()
+                .map_or([u8::MAX; 28], |cursor| cursor.account_identifier.hash);
+            let hash_tree = CERTIFIED_BALANCES.with(|certified_balances| {
+                let certified_balances = certified_balances.borrow();
+                let witness = certified_balances.value_range(&first_key, &last_key);
+                encode_hash_tree(certified_tree(
+                    &certified_balances,
+                    Some(witness),
+                    last_block,
+                ))
+            });
+            (ic_cdk::api::data_certificate(), Some(hash_tree))
+        }
+        _ => (None, None),
+    };
+    Ok(ListBalancesAtResponse {
+        block_index: arg.block_index,
+        block_hash: block_hash.as_slice().to_vec(),
+        balances,
+        next,
+        certificate,
+        hash_tree,
+    })
+}
+
 #[candid_method(query)]
 #[query]
 fn http_request(req: HttpRequest) -> HttpResponse {
@@ -1,8 +1,10 @@
 use candid::{Decode, Encode, Nat};
 use ic_base_types::{CanisterId, PrincipalId};
 use ic_icp_index::{
-    GetAccountIdentifierTransactionsArgs, GetAccountIdentifierTransactionsResponse,
-    GetAccountIdentifierTransactionsResult, Status, TransactionWithId,
+    AccountIdentifierBalance, BalanceAt, BalanceAtCursor, GetAccountIdentifierBalanceAtArgs,
+    GetAccountIdentifierBalanceAtResult, GetAccountIdentifierTransactionsArgs,
+    GetAccountIdentifierTransactionsResponse, GetAccountIdentifierTransactionsResult,
+    ListBalancesAtArgs, ListBalancesAtCursor, ListBalancesAtResult, Status, TransactionWithId,
 };
 use ic_icrc1_index_ng::GetAccountTransactionsArgs;
 use ic_ledger_canister_core::archive::ArchiveOptions;
@@ -283,6 +285,47 @@ fn approve(
         .expect("Failed to create an approval")
 }
 
+fn get_account_identifier_balance_at(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+    block_index: u64,
+    resume: Option<BalanceAtCursor>,
+) -> GetAccountIdentifierBalanceAtResult {
+    let req = Encode!(&GetAccountIdentifierBalanceAtArgs {
+        account_identifier: account.into(),
+        block_index,
+        resume,
+    })
+    .expect("Failed to encode GetAccountIdentifierBalanceAtArgs");
+    let res = env
+        .execute_ingress(index_id, "get_account_identifier_balance_at", req)
+        .expect("Failed to get_account_identifier_balance_at")
+        .bytes();
+    Decode!(&res, GetAccountIdentifierBalanceAtResult)
+        .expect("Failed to decode GetAccountIdentifierBalanceAtResult")
+}
+
+fn list_balances_at(
+    env: &StateMachine,
+    index_id: CanisterId,
+    block_index: u64,
+    cursor: Option<ListBalancesAtCursor>,
+    max_results: u64,
+) -> ListBalancesAtResult {
+    let req = Encode!(&ListBalancesAtArgs {
+        block_index,
+        cursor,
+        max_results,
+    })
+    .expect("Failed to encode ListBalancesAtArgs");
+    let res = env
+        .query(index_id, "list_balances_at", req)
+        .expect("Failed to list_balances_at")
+        .bytes();
+    Decode!(&res, ListBalancesAtResult).expect("Failed to decode ListBalancesAtResult")
+}
+
 fn get_account_identifier_transactions(
     env: &StateMachine,
     index_id: CanisterId,
@@ -1014,3 +1057,115 @@ fn test_post_upgrade_start_timer() {
     transfer(env, ledger_id, account(1, 0), account(2, 0), 2_000_000);
     wait_until_sync_is_completed(env, index_id, ledger_id);
 }
+
+#[test]
+fn test_balances_at() {
+    let env = &StateMachine::new();
+    let ledger_id = install_ledger(env, HashMap::new(), default_archive_options());
+    let index_id = install_index(env, ledger_id);
+    let minter = Account {
+        owner: MINTER_PRINCIPAL.into(),
+        subaccount: None,
+    };
+
+    transfer(env, ledger_id, minter, account(1, 0), 1_000_000);
+    transfer(env, ledger_id, minter, account(2, 0), 500_000);
+    transfer(env, ledger_id, account(1, 0), account(2, 0), 100_000);
+    transfer(env, ledger_id, account(2, 0), account(3, 0), 200_000);
+    transfer(env, ledger_id, account(1, 0), minter, 50_000);
+    approve(
+        env,
+        ledger_id,
+        account(1, 0),
+        ApproveTestArgs::new(account(1, 0), account(2, 0), 100_000),
+    );
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+
+    let expected_balances = vec![
+        vec![(account(1, 0), 1_000_000)],
+        vec![(account(1, 0), 1_000_000), (account(2, 0), 500_000)],
+        vec![(account(1, 0), 900_000 - FEE), (account(2, 0), 600_000)],
+        vec![
+            (account(1, 0), 900_000 - FEE),
+            (account(2, 0), 400_000 - FEE),
+            (account(3, 0), 200_000),
+        ],
+        vec![
+            (account(1, 0), 850_000 - FEE),
+            (account(2, 0), 400_000 - FEE),
+            (account(3, 0), 200_000),
+        ],
+        vec![
+            (account(1, 0), 850_000 - 2 * FEE),
+            (account(2, 0), 400_000 - FEE),
+            (account(3, 0), 200_000),
+        ],
+    ];
+
+    for (block_index, expected_balances) in expected_balances.into_iter().enumerate() {
+        let block_index = block_index as u64;
+        for account in [account(1, 0), account(2, 0), account(3, 0)] {
+            let expected_balance = expected_balances
+                .iter()
+                .find(|(a, _)| a == &account)
+                .map_or(0, |(_, balance)| *balance);
+            assert_eq!(
+                Ok(BalanceAt::Balance(expected_balance)),
+                get_account_identifier_balance_at(env, index_id, account, block_index, None),
+                "account: {} block_index: {}",
+                account,
+                block_index
+            );
+        }
+
+        // Read the snapshot one balance at a time to exercise the paging.
+        let mut balances = vec![];
+        let mut cursor = None;
+        loop {
+            let response = list_balances_at(env, index_id, block_index, cursor, 1).unwrap();
+            assert_eq!(block_index, response.block_index);
+            assert!(response.balances.len() <= 1);
+            // Only the balances after the last indexed block are certified.
+            let is_last_block = block_index == 5;
+            assert_eq!(is_last_block, response.certificate.is_some());
+            assert_eq!(is_last_block, response.hash_tree.is_some());
+            balances.extend(response.balances);
+            match response.next {
+                Some(next) => {
+                    // The blocks of this test fit into the budget of a single call.
+                    assert_eq!(None, next.pending);
+                    cursor = Some(next);
+                }
+                None => break,
+            }
+        }
+        let mut expected_balances: Vec<_> = expected_balances
+            .into_iter()
+            .map(|(account, balance)| AccountIdentifierBalance {
+                account_identifier: account.into(),
+                balance,
+            })
+            .collect();
+        expected_balances.sort_by_key(|balance| balance.account_identifier);
+        assert_eq!(expected_balances, balances, "block_index: {}", block_index);
+    }
+
+    // A computation can be resumed from a cursor: the balance of account 1
+    // right before block 4 is its balance after block 3.
+    let cursor = BalanceAtCursor {
+        balance: 900_000 - FEE,
+        reverted_from: 4,
+    };
+    assert_eq!(
+        Ok(BalanceAt::Balance(1_000_000)),
+        get_account_identifier_balance_at(env, index_id, account(1, 0), 1, Some(cursor.clone()))
+    );
+    // The cursor must not start at or before the requested block.
+    assert!(
+        get_account_identifier_balance_at(env, index_id, account(1, 0), 4, Some(cursor)).is_err()
+    );
+
+    // Blocks that have not been indexed yet are rejected.
+    assert!(get_account_identifier_balance_at(env, index_id, account(1, 0), 6, None).is_err());
+    assert!(list_balances_at(env, index_id, 6, None, 10).is_err());
+}
@@ -37,6 +37,10 @@ use icp_ledger::{
 use icrc_ledger_types::icrc1::transfer::TransferArg;
 use icrc_ledger_types::icrc2::allowance::{Allowance, AllowanceArgs};
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
+use icrc_ledger_types::icrc21::{
+    errors::Icrc21Error, lib::build_icrc21_consent_info, requests::ConsentMessageRequest,
+    responses::ConsentInfo,
+};
 use icrc_ledger_types::{
     icrc::generic_metadata_value::MetadataValue as Value, icrc3::archive::QueryArchiveFn,
 };
@@ -592,6 +596,10 @@ fn icrc1_supported_standards() -> Vec<StandardRecord> {
             url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-2".to_string(),
         });
     }
+    standards.push(StandardRecord {
+        name: "ICRC-21".to_string(),
+        url: "https://github.com/dfinity/wg-identity-authentication/blob/main/topics/icrc_21_consent_msg.md".to_string(),
+    });
     standards
 }
 
@@ -1499,6 +1507,33 @@ fn icrc2_allowance_candid() {
     over(candid_one, icrc2_allowance)
 }
 
+#[candid_method(update, rename = "icrc21_canister_call_consent_message")]
+fn icrc21_canister_call_consent_message(
+    consent_msg_request: ConsentMessageRequest,
+) -> Result<ConsentInfo, Icrc21Error> {
+    let caller_principal = caller().0;
+    let (ledger_fee, token_symbol) = {
+        let ledger = LEDGER.read().unwrap();
+        (
+            Nat::from(ledger.transfer_fee.get_e8s()),
+            ledger.token_symbol.clone(),
+        )
+    };
+
+    build_icrc21_consent_info(
+        consent_msg_request,
+        caller_principal,
+        ledger_fee,
+        token_symbol,
+        DECIMAL_PLACES as u8,
+    )
+}
+
+#[export_name = "canister_update icrc21_canister_call_consent_message"]
+fn icrc21_canister_call_consent_message_candid() {
+    over(candid_one, icrc21_canister_call_consent_message)
+}
+
 candid::export_service!();
 
 #[export_name = "canister_query __get_candid_interface_tmp_hack"]
@@ -7,6 +7,15 @@ use icrc_ledger_types::icrc3::transactions::Transaction;
 /// The maximum number of blocks to return in a single [get_blocks] request.
 pub const DEFAULT_MAX_BLOCKS_PER_RESPONSE: u64 = 2000;
 
+/// The maximum number of balances to return in a single [list_balances_at] request.
+pub const MAX_BALANCES_PER_RESPONSE: u64 = 500;
+
+/// The maximum number of blocks that a single [get_account_balance_at] or
+/// [list_balances_at] request reverts to compute historical balances.
+/// [list_balances_at] also counts every account it reads against this budget.
+/// Requests that need more return a cursor to continue from.
+pub const MAX_BLOCKS_REVERTED_PER_CALL: u64 = 10_000;
+
 #[derive(CandidType, Debug, Deserialize)]
 pub enum IndexArg {
     Init(InitArg),
@@ -76,6 +85,100 @@ pub struct ListSubaccountsArgs {
     pub start: Option<Subaccount>,
 }
 
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct GetAccountBalanceAtArgs {
+    pub account: Account,
+    // The index of the block after which the balance is computed.
+    pub block_index: BlockIndex,
+    // The cursor returned by a previous call for the same account and
+    // block_index that ran out of its budget.
+    pub resume: Option<BalanceAtCursor>,
+}
+
+/// The progress of a balance computation that did not fit into a single call.
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct BalanceAtCursor {
+    // The balance of the account right before the block at reverted_from.
+    pub balance: Nat,
+    // The blocks from this index onwards have been reverted.
+    pub reverted_from: BlockIndex,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum BalanceAt {
+    // The balance of the account after block_index.
+    Balance(Nat),
+    // The call reverted MAX_BLOCKS_REVERTED_PER_CALL blocks without reaching
+    // block_index. Pass the cursor as resume to continue.
+    Incomplete(BalanceAtCursor),
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct ListBalancesAtCursor {
+    // All accounts up to and including this account have been listed, unless
+    // pending is set.
+    pub account: Account,
+    // The progress of the balance of account if it did not fit into the call.
+    pub pending: Option<BalanceAtCursor>,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct ListBalancesAtArgs {
+    // The index of the block after which the balances are computed.
+    pub block_index: BlockIndex,
+    // The cursor returned by the previous call.
+    // If None then the results will start from the first
+    // account in natural order.
+    pub cursor: Option<ListBalancesAtCursor>,
+    // Maximum number of balances to fetch.
+    pub max_results: Nat,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct AccountBalance {
+    pub account: Account,
+    pub balance: Nat,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct ListBalancesAtResponse {
+    pub block_index: BlockIndex,
+    // The hash of the block at block_index. It lets clients check that all
+    // the pages of a snapshot refer to the same block.
+    pub block_hash: Vec<u8>,
+    // The non-zero balances after block_index, ordered by account.
+    pub balances: Vec<AccountBalance>,
+    // The cursor to pass to the next call, None once all the accounts
+    // have been listed.
+    pub next: Option<ListBalancesAtCursor>,
+    // The certificate of the certified data of the index. Only set if
+    // block_index is the last indexed block and the call is a query.
+    pub certificate: Option<Vec<u8>>,
+    // A CBOR-encoded hash tree whose root hash is the certified data of the
+    // index. Only set if block_index is the last indexed block. The tree has
+    // the following structure:
+    //
+    //   balances -- [account key] -- [big-endian balance]
+    //   last_block_hash -- [hash of the last indexed block]
+    //   last_block_index -- [big-endian index of the last indexed block]
+    //
+    // where the account key is the owner padded with zeros to 29 bytes, the
+    // length of the owner and the effective subaccount. The tree reveals all
+    // the accounts from the cursor account up to the next cursor account, so
+    // that it also proves that no non-zero balance was left out. Snapshots at
+    // older blocks can be checked by reverting the blocks up to the last
+    // indexed block, whose hash chain ends at last_block_hash.
+    pub hash_tree: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct BalanceAtError {
+    pub message: String,
+}
+
+pub type GetAccountBalanceAtResult = Result<BalanceAt, BalanceAtError>;
+pub type ListBalancesAtResult = Result<ListBalancesAtResponse, BalanceAtError>;
+
 #[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
 pub struct Status {
     pub num_blocks_synced: BlockIndex,
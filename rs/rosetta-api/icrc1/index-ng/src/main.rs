@@ -5,13 +5,17 @@ use ic_canisters_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
 use ic_cdk::trap;
 use ic_cdk_macros::{init, post_upgrade, query};
 use ic_cdk_timers::TimerId;
+use ic_certified_map::{fork, labeled, labeled_hash, AsHashTree, HashTree, RbTree};
 use ic_crypto_sha2::Sha256;
 use ic_icrc1::blocks::{encoded_block_to_generic_block, generic_block_to_encoded_block};
 use ic_icrc1::{Block, Operation};
 use ic_icrc1_index_ng::{
-    FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
-    GetAccountTransactionsResult, IndexArg, ListSubaccountsArgs, Log, LogEntry, Status,
-    TransactionWithId, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
+    AccountBalance, BalanceAt, BalanceAtCursor, BalanceAtError, FeeCollectorRanges,
+    GetAccountBalanceAtArgs, GetAccountBalanceAtResult, GetAccountTransactionsArgs,
+    GetAccountTransactionsResponse, GetAccountTransactionsResult, IndexArg, ListBalancesAtArgs,
+    ListBalancesAtCursor, ListBalancesAtResponse, ListBalancesAtResult, ListSubaccountsArgs, Log,
+    LogEntry, Status, TransactionWithId, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
+    MAX_BALANCES_PER_RESPONSE, MAX_BLOCKS_REVERTED_PER_CALL,
 };
 use ic_ledger_core::block::{BlockIndex as BlockIndex64, BlockType, EncodedBlock};
 use ic_ledger_core::tokens::{CheckedAdd, CheckedSub, Zero};
@@ -21,7 +25,7 @@ use ic_stable_structures::{
     memory_manager::MemoryManager, DefaultMemoryImpl, StableBTreeMap, StableCell, StableLog,
     Storable,
 };
-use icrc_ledger_types::icrc1::account::{Account, Subaccount};
+use icrc_ledger_types::icrc1::account::{Account, Subaccount, DEFAULT_SUBACCOUNT};
 use icrc_ledger_types::icrc3::archive::{ArchivedRange, QueryBlockArchiveFn};
 use icrc_ledger_types::icrc3::blocks::{
     BlockRange, GenericBlock, GetBlocksRequest, GetBlocksResponse,
@@ -38,7 +42,7 @@ use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::io::Read;
-use std::ops::Bound::{Excluded, Included};
+use std::ops::Bound::{Excluded, Included, Unbounded};
 use std::ops::Range;
 use std::time::Duration;
 
@@ -54,6 +58,11 @@ const ACCOUNT_DATA_MEMORY_ID: MemoryId = MemoryId::new(4);
 
 const DEFAULT_MAX_WAIT_TIME: Duration = Duration::from_secs(1);
 
+/// The labels of the certified tree of the index, see [certified_tree].
+const BALANCES_LABEL: &[u8] = b"balances";
+const LAST_BLOCK_HASH_LABEL: &[u8] = b"last_block_hash";
+const LAST_BLOCK_INDEX_LABEL: &[u8] = b"last_block_index";
+
 #[cfg(not(feature = "u256-tokens"))]
 type Tokens = ic_icrc1_tokens_u64::U64;
 
@@ -73,6 +82,10 @@ type AccountBlockIdsMap = StableBTreeMap<AccountBlockIdsMapKey, (), VM>;
 type AccountDataMapKey = (AccountDataType, (Blob<29>, [u8; 32]));
 type AccountDataMap = StableBTreeMap<AccountDataMapKey, Tokens, VM>;
 
+// The balances of the accounts keyed by [certified_balance_key], which sorts
+// the accounts like [AccountDataMapKey].
+type CertifiedBalances = RbTree<[u8; 62], Vec<u8>>;
+
 thread_local! {
     /// Static memory manager to manage the memory available for stable structures.
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
@@ -100,6 +113,10 @@ thread_local! {
         RefCell::new(AccountDataMap::init(memory_manager.get(ACCOUNT_DATA_MEMORY_ID)))
     });
 
+    /// The balances of [ACCOUNT_DATA] after the last indexed block, as a
+    /// certified tree. It only lives in the heap and is rebuilt after upgrades.
+    static CERTIFIED_BALANCES: RefCell<CertifiedBalances> = RefCell::new(RbTree::new());
+
     /// Profiling data to understand cycles usage
     static PROFILING_DATA: RefCell<SpanStats> = RefCell::new(SpanStats::default());
 }
@@ -257,6 +274,12 @@ fn get_balance(account: Account) -> Tokens {
 fn change_balance(account: Account, f: impl FnOnce(Tokens) -> Tokens) {
     let key = balance_key(account);
     let new_balance = f(get_balance(account));
+    CERTIFIED_BALANCES.with(|certified_balances| {
+        certified_balances.borrow_mut().insert(
+            certified_balance_key(account),
+            certified_balance(new_balance),
+        )
+    });
     with_account_data(|account_data| account_data.insert(key, new_balance));
 }
 
@@ -268,6 +291,99 @@ fn balance_key(account: Account) -> (AccountDataType, (Blob<29>, [u8; 32])) {
     )
 }
 
+/// The key of an account in [CERTIFIED_BALANCES]: the owner padded with zeros
+/// to 29 bytes, the length of the owner, and the effective subaccount.
+fn certified_balance_key(account: Account) -> [u8; 62] {
+    let owner = account.owner.as_slice();
+    let mut key = [0; 62];
+    key[..owner.len()].copy_from_slice(owner);
+    key[29] = owner.len() as u8;
+    key[30..].copy_from_slice(account.effective_subaccount());
+    key
+}
+
+/// The value of a balance in [CERTIFIED_BALANCES]: its big-endian encoding.
+fn certified_balance(balance: Tokens) -> Vec<u8> {
+    Nat::from(balance).0.to_bytes_be()
+}
+
+fn rebuild_certified_balances() {
+    let certified_balances = with_account_data(|account_data| {
+        let mut certified_balances = RbTree::new();
+        for (key, balance) in account_data.iter() {
+            certified_balances.insert(
+                certified_balance_key(balance_key_to_account(&key)),
+                certified_balance(balance),
+            );
+        }
+        certified_balances
+    });
+    CERTIFIED_BALANCES.with(|cell| *cell.borrow_mut() = certified_balances);
+}
+
+/// Returns the index and the hash of the last indexed block, if any.
+fn last_block() -> Option<(BlockIndex64, Vec<u8>)> {
+    let block_index = with_blocks(|blocks| blocks.len()).checked_sub(1)?;
+    let block = with_blocks(|blocks| blocks.get(block_index))?;
+    let block_hash = Block::<Tokens>::block_hash(&EncodedBlock::from(block));
+    Some((block_index, block_hash.as_slice().to_vec()))
+}
+
+/// Returns the certified tree of the index, which has the following
+/// structure:
+///
+///   balances -- [certified_balance_key] -- [certified_balance]
+///   last_block_hash -- [hash of the last indexed block]
+///   last_block_index -- [big-endian index of the last indexed block]
+///
+/// The balances are revealed by `balances_witness`, or pruned if it is None.
+fn certified_tree<'a>(
+    certified_balances: &'a CertifiedBalances,
+    balances_witness: Option<HashTree<'a>>,
+    (last_block_index, last_block_hash): (BlockIndex64, Vec<u8>),
+) -> HashTree<'a> {
+    let balances = match balances_witness {
+        Some(witness) => labeled(BALANCES_LABEL, witness),
+        None => HashTree::Pruned(labeled_hash(
+            BALANCES_LABEL,
+            &certified_balances.root_hash(),
+        )),
+    };
+    fork(
+        balances,
+        fork(
+            labeled(
+                LAST_BLOCK_HASH_LABEL,
+                HashTree::Leaf(Cow::Owned(last_block_hash)),
+            ),
+            labeled(
+                LAST_BLOCK_INDEX_LABEL,
+                HashTree::Leaf(Cow::Owned(last_block_index.to_be_bytes().to_vec())),
+            ),
+        ),
+    )
+}
+
+/// Sets the certified data of the index to the root hash of [certified_tree].
+fn update_certified_data() {
+    let Some(last_block) = last_block() else {
+        return;
+    };
+    let root_hash = CERTIFIED_BALANCES.with(|certified_balances| {
+        certified_tree(&certified_balances.borrow(), None, last_block).reconstruct()
+    });
+    ic_cdk::api::set_certified_data(&root_hash);
+}
+
+/// Encodes a hash tree into the self-describing CBOR expected by clients.
+fn encode_hash_tree(tree: HashTree<'_>) -> Vec<u8> {
+    let mut serializer = serde_cbor::ser::Serializer::new(vec![]);
+    serializer.self_describe().unwrap();
+    tree.serialize(&mut serializer)
+        .expect("failed to serialize a hash tree");
+    serializer.into_inner()
+}
+
 #[init]
 #[candid_method(init)]
 fn init(index_arg: Option<IndexArg>) {
@@ -331,6 +447,9 @@ fn post_upgrade(index_arg: Option<IndexArg>) {
         _ => (),
     };
 
+    rebuild_certified_balances();
+    update_certified_data();
+
     // set the first build_index to be called after init
     set_build_index_timer(DEFAULT_MAX_WAIT_TIME);
 }
@@ -489,10 +608,14 @@ fn append_blocks(new_blocks: Vec<GenericBlock>) {
     // the index of the next block that we
     // are going to append
     let mut block_index = with_blocks(|blocks| blocks.len());
+    let has_new_blocks = !new_blocks.is_empty();
     for block in new_blocks {
         append_block(block_index, block);
         block_index += 1;
     }
+    if has_new_blocks {
+        update_certified_data();
+    }
 }
 
 fn index_fee_collector(block_index: BlockIndex64, block: &Block<Tokens>) {
@@ -539,12 +662,7 @@ fn process_balance_changes(block_index: BlockIndex64, block: &Block<Tokens>) {
                 fee,
                 ..
             } => {
-                let fee = block.effective_fee.or(fee).unwrap_or_else(|| {
-                    ic_cdk::trap(&format!(
-                        "Block {} is of type Transfer but has no fee or effective fee!",
-                        block_index
-                    ))
-                });
+                let fee = get_transfer_fee(block_index, block, fee);
                 mutate_state(|s| s.last_fee = Some(fee));
                 debit(
                     block_index,
@@ -563,23 +681,7 @@ fn process_balance_changes(block_index: BlockIndex64, block: &Block<Tokens>) {
             Operation::Approve {
                 from, fee, spender, ..
             } => {
-                let fee = match fee.or(block.effective_fee) {
-                    Some(fee) => fee,
-                    // NB. There was a bug in the ledger which would create
-                    // approve blocks with the fee fields unset. The bug was
-                    // quickly fixed, but there are a few blocks on the mainnet
-                    // that don't have their fee fields populated.
-                    None => match with_state(|state| state.last_fee) {
-                        Some(last_fee) => {
-                            log!(
-                                P1,
-                                "fee and effective_fee aren't set in block {block_index}, using last transfer fee {last_fee}"
-                            );
-                            last_fee
-                        }
-                        None => ic_cdk::trap(&format!("bug: index is stuck because block with index {block_index} doesn't contain a fee and no fee has been recorded before")),
-                    }
-                };
+                let fee = get_approve_fee(block_index, block, fee);
 
                 // It is possible that the spender account has not existed prior to this approve transaction.
                 // Until a transfer_from transaction occurs such account would not show up in a `list_subaccounts` query as the spender is not involved in any credit or debit calls at this point.
@@ -593,6 +695,223 @@ fn process_balance_changes(block_index: BlockIndex64, block: &Block<Tokens>) {
     );
 }
 
+fn get_transfer_fee(
+    block_index: BlockIndex64,
+    block: &Block<Tokens>,
+    fee: Option<Tokens>,
+) -> Tokens {
+    block.effective_fee.or(fee).unwrap_or_else(|| {
+        ic_cdk::trap(&format!(
+            "Block {} is of type Transfer but has no fee or effective fee!",
+            block_index
+        ))
+    })
+}
+
+fn get_approve_fee(
+    block_index: BlockIndex64,
+    block: &Block<Tokens>,
+    fee: Option<Tokens>,
+) -> Tokens {
+    match fee.or(block.effective_fee) {
+        Some(fee) => fee,
+        // NB. There was a bug in the ledger which would create
+        // approve blocks with the fee fields unset. The bug was
+        // quickly fixed, but there are a few blocks on the mainnet
+        // that don't have their fee fields populated.
+        None => match with_state(|state| state.last_fee) {
+            Some(last_fee) => {
+                log!(
+                    P1,
+                    "fee and effective_fee aren't set in block {block_index}, using last transfer fee {last_fee}"
+                );
+                last_fee
+            }
+            None => ic_cdk::trap(&format!("bug: index is stuck because block with index {block_index} doesn't contain a fee and no fee has been recorded before")),
+        },
+    }
+}
+
+/// Returns `balance` as it was before `block` changed the balance of `account`.
+/// The fee credited to the fee collector is not reverted, see [get_balance_at].
+fn revert_balance_changes(
+    block_index: BlockIndex64,
+    account: Account,
+    block: &Block<Tokens>,
+    balance: Tokens,
+) -> Tokens {
+    let add = |balance: Tokens, amount: Tokens| {
+        balance.checked_add(&amount).unwrap_or_else(|| {
+            ic_cdk::trap(&format!(
+                "token amount overflow while reverting block {block_index}"
+            ))
+        })
+    };
+    let sub = |balance: Tokens, amount: Tokens| {
+        balance.checked_sub(&amount).unwrap_or_else(|| {
+            ic_cdk::trap(&format!(
+                "token amount underflow while reverting block {block_index}"
+            ))
+        })
+    };
+    match block.transaction.operation {
+        Operation::Burn { from, amount, .. } if from == account => add(balance, amount),
+        Operation::Mint { to, amount } if to == account => sub(balance, amount),
+        Operation::Transfer {
+            from,
+            to,
+            amount,
+            fee,
+            ..
+        } => {
+            let mut balance = balance;
+            if to == account {
+                balance = sub(balance, amount);
+            }
+            if from == account {
+                let fee = get_transfer_fee(block_index, block, fee);
+                balance = add(balance, add(amount, fee));
+            }
+            balance
+        }
+        Operation::Approve { from, fee, .. } if from == account => {
+            add(balance, get_approve_fee(block_index, block, fee))
+        }
+        _ => balance,
+    }
+}
+
+/// Returns, most recent first, the ids of the blocks between `after` and
+/// `before` (both excluded) that changed the balance of an account: the
+/// blocks of the account, sorted from the most recent, and the blocks in
+/// which the account collected the fee. At most `limit + 1` ids are returned
+/// so that callers can tell whether `limit` blocks are enough.
+fn blocks_to_revert(
+    account_block_ids: &[BlockIndex64],
+    fee_collector_ranges: &[Range<BlockIndex64>],
+    after: BlockIndex64,
+    before: BlockIndex64,
+    limit: usize,
+) -> Vec<BlockIndex64> {
+    let fee_collector_block_ids = fee_collector_ranges
+        .iter()
+        .rev()
+        .flat_map(|range| (range.start.max(after.saturating_add(1))..range.end.min(before)).rev());
+    let mut ids: Vec<BlockIndex64> = account_block_ids
+        .iter()
+        .copied()
+        .filter(|id| after < *id && *id < before)
+        .take(limit + 1)
+        .chain(fee_collector_block_ids.take(limit + 1))
+        .collect();
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    ids.dedup();
+    ids.truncate(limit + 1);
+    ids
+}
+
+/// The outcome of [get_balance_at].
+enum BalanceAtProgress {
+    Complete(Tokens),
+    /// The budget ran out. `balance` is the balance of the account right
+    /// before the block at `reverted_from` was applied.
+    Incomplete {
+        balance: Tokens,
+        reverted_from: BlockIndex64,
+    },
+}
+
+impl From<BalanceAtProgress> for BalanceAt {
+    fn from(progress: BalanceAtProgress) -> Self {
+        match progress {
+            BalanceAtProgress::Complete(balance) => BalanceAt::Balance(balance.into()),
+            BalanceAtProgress::Incomplete {
+                balance,
+                reverted_from,
+            } => BalanceAt::Incomplete(BalanceAtCursor {
+                balance: balance.into(),
+                reverted_from: reverted_from.into(),
+            }),
+        }
+    }
+}
+
+/// Returns the balance of `account` right after the block at `block_index`
+/// was applied. The balance is computed by reverting, from the most recent,
+/// the blocks after `block_index` that changed the balance of the account,
+/// starting from the current balance or from the `(balance, reverted_from)`
+/// progress of a previous call. At most `budget` blocks are reverted and
+/// `budget` is reduced by the number of reverted blocks.
+fn get_balance_at(
+    account: Account,
+    block_index: BlockIndex64,
+    resume: Option<(Tokens, BlockIndex64)>,
+    budget: &mut u64,
+) -> BalanceAtProgress {
+    let limit = *budget as usize;
+    let (mut balance, before) = resume.unwrap_or_else(|| (get_balance(account), u64::MAX));
+    let first_key = account_block_ids_key(account, before);
+    let last_key = account_block_ids_key(account, block_index);
+    let account_block_ids = with_account_block_ids(|account_block_ids| {
+        account_block_ids
+            .range((Excluded(first_key), Excluded(last_key)))
+            .take(limit + 1)
+            .map(|(k, _)| k.1 .0)
+            .collect::<Vec<BlockIndex64>>()
+    });
+    // The blocks where the account collected the fee are not in the blocks
+    // of the account, so their fees are reverted separately.
+    let fee_collector_ranges =
+        with_state(|state| state.fee_collectors.get(&account).cloned()).unwrap_or_default();
+    let ids = blocks_to_revert(
+        &account_block_ids,
+        &fee_collector_ranges,
+        block_index,
+        before,
+        limit,
+    );
+
+    for &id in ids.iter().take(limit) {
+        let block = get_decoded_block(id).unwrap_or_else(|| {
+            trap(&format!(
+                "Block {} not found in the block log, account blocks map is corrupted!",
+                id
+            ))
+        });
+        if account_block_ids
+            .binary_search_by(|other| id.cmp(other))
+            .is_ok()
+        {
+            balance = revert_balance_changes(id, account, &block, balance);
+        }
+        let range_index = fee_collector_ranges.partition_point(|range| range.end <= id);
+        let collected_fee = fee_collector_ranges
+            .get(range_index)
+            .map_or(false, |range| range.contains(&id));
+        if collected_fee {
+            if let Operation::Transfer { fee, .. } = block.transaction.operation {
+                let fee = get_transfer_fee(id, &block, fee);
+                balance = balance.checked_sub(&fee).unwrap_or_else(|| {
+                    trap(&format!(
+                        "token amount underflow while reverting block {id}"
+                    ))
+                });
+            }
+        }
+    }
+
+    if ids.len() <= limit {
+        *budget -= ids.len() as u64;
+        BalanceAtProgress::Complete(balance)
+    } else {
+        *budget = 0;
+        BalanceAtProgress::Incomplete {
+            balance,
+            reverted_from: limit.checked_sub(1).map_or(before, |last| ids[last]),
+        }
+    }
+}
+
 fn debit(block_index: BlockIndex64, account: Account, amount: Tokens) {
     change_balance(account, |balance| {
         balance.checked_sub(&amount).unwrap_or_else(|| {
@@ -826,6 +1145,185 @@ fn list_subaccounts(args: ListSubaccountsArgs) -> Vec<Subaccount> {
     })
 }
 
+/// Checks that the block at `block_index` has been indexed.
+fn indexed_block_index(block_index: &Nat) -> Result<BlockIndex64, BalanceAtError> {
+    let num_blocks_synced = with_blocks(|blocks| blocks.len());
+    match block_index.0.to_u64() {
+        Some(block_index) if block_index < num_blocks_synced => Ok(block_index),
+        _ => Err(BalanceAtError {
+            message: format!(
+                "Block {} has not been indexed yet, the index has {} blocks",
+                block_index, num_blocks_synced
+            ),
+        }),
+    }
+}
+
+fn balance_key_to_account(key: &AccountDataMapKey) -> Account {
+    let (_, (owner, subaccount)) = key;
+    Account {
+        owner: Principal::from_slice(owner.as_slice()),
+        subaccount: (subaccount != DEFAULT_SUBACCOUNT).then_some(*subaccount),
+    }
+}
+
+/// Checks that `cursor` can continue the computation of a balance at `block_index`.
+fn resume_balance_at(
+    block_index: BlockIndex64,
+    cursor: BalanceAtCursor,
+) -> Result<(Tokens, BlockIndex64), BalanceAtError> {
+    let invalid_cursor = || BalanceAtError {
+        message: format!("Invalid cursor {:?} for block {}", cursor, block_index),
+    };
+    let reverted_from = cursor
+        .reverted_from
+        .0
+        .to_u64()
+        .filter(|reverted_from| *reverted_from > block_index)
+        .ok_or_else(invalid_cursor)?;
+    let balance = Tokens::try_from(cursor.balance.clone()).map_err(|_| invalid_cursor())?;
+    Ok((balance, reverted_from))
+}
+
+/// Returns the first account after `after` that has a balance entry.
+fn next_balance_account(after: Option<Account>) -> Option<Account> {
+    with_account_data(|account_data| {
+        let range = match after {
+            Some(account) => (Excluded(balance_key(account)), Unbounded),
+            None => (Unbounded, Unbounded),
+        };
+        account_data
+            .range(range)
+            .next()
+            .filter(|((data_type, _), _)| *data_type == AccountDataType::Balance)
+            .map(|(key, _)| balance_key_to_account(&key))
+    })
+}
+
+#[query]
+#[candid_method(query)]
+fn get_account_balance_at(arg: GetAccountBalanceAtArgs) -> GetAccountBalanceAtResult {
+    let block_index = indexed_block_index(&arg.block_index)?;
+    let resume = arg
+        .resume
+        .map(|cursor| resume_balance_at(block_index, cursor))
+        .transpose()?;
+    let mut budget = MAX_BLOCKS_REVERTED_PER_CALL;
+    Ok(get_balance_at(arg.account, block_index, resume, &mut budget).into())
+}
+
+#[query]
+#[candid_method(query)]
+fn list_balances_at(arg: ListBalancesAtArgs) -> ListBalancesAtResult {
+    let block_index = indexed_block_index(&arg.block_index)?;
+    let block_hash = with_blocks(|blocks| blocks.get(block_index))
+        .map(|block| Block::<Tokens>::block_hash(&EncodedBlock::from(block)))
+        .unwrap_or_else(|| trap(&format!("Block {} not found in the block log", block_index)));
+    let first_account = arg.cursor.as_ref().map(|cursor| cursor.account);
+    let length = arg
+        .max_results
+        .0
+        .to_u64()
+        .unwrap_or(MAX_BALANCES_PER_RESPONSE)
+        .min(MAX_BALANCES_PER_RESPONSE) as usize;
+
+    // The last account whose balance has been computed and the account
+    // whose balance computation has to be resumed, if any.
+    let (mut after, mut pending) = match arg.cursor {
+        Some(ListBalancesAtCursor {
+            account,
+            pending: Some(cursor),
+        }) => (
+            None,
+            Some((account, resume_balance_at(block_index, cursor)?)),
+        ),
+        Some(ListBalancesAtCursor {
+            account,
+            pending: None,
+        }) => (Some(account), None),
+        None => (None, None),
+    };
+
+    // Accounts whose balance was zero at block_index are skipped, so the
+    // accounts are read until enough balances are found or the budget of
+    // reverted blocks runs out. Reading an account costs one unit of budget
+    // on top of the blocks reverted for it, so that a call cannot read an
+    // unbounded number of accounts that were drained before block_index.
+    let mut budget = MAX_BLOCKS_REVERTED_PER_CALL;
+    let mut balances = vec![];
+    let next = loop {
+        let (account, resume) = match pending.take() {
+            Some((account, resume)) => (account, Some(resume)),
+            None => {
+                if balances.len() >= length || budget == 0 {
+                    break after.map(|account| ListBalancesAtCursor {
+                        account,
+                        pending: None,
+                    });
+                }
+                match next_balance_account(after) {
+                    Some(account) => (account, None),
+                    None => break None,
+                }
+            }
+        };
+        match get_balance_at(account, block_index, resume, &mut budget) {
+            BalanceAtProgress::Complete(balance) => {
+                budget = budget.saturating_sub(1);
+                if !balance.is_zero() {
+                    balances.push(AccountBalance {
+                        account,
+                        balance: balance.into(),
+                    });
+                }
+                after = Some(account);
+            }
+            BalanceAtProgress::Incomplete {
+                balance,
+                reverted_from,
+            } => {
+                break Some(ListBalancesAtCursor {
+                    account,
+                    pending: Some(BalanceAtCursor {
+                        balance: balance.into(),
+                        reverted_from: reverted_from.into(),
+                    }),
+                })
+            }
+        }
+    };
+
+    // The balances after the last indexed block are the certified ones, so
+    // the page is certified by revealing all the accounts it covers.
+    let (certificate, hash_tree) = match last_block() {
+        Some(last_block) if last_block.0 == block_index => {
+            let first_key = first_account.map_or([0; 62], certified_balance_key);
+            let last_key = next.as_ref().map_or([u8::MAX; 62], |cursor| {
+                certified_balance_key(cursor.account)
+            });
+            let hash_tree = CERTIFIED_BALANCES.with(|certified_balances| {
+                let certified_balances = certified_balances.borrow();
+                let witness = certified_balances.value_range(&first_key, &last_key);
+                encode_hash_tree(certified_tree(
+                    &certified_balances,
+                    Some(witness),
+                    last_block,
+                ))
+            });
+            (ic_cdk::api::data_certificate(), Some(hash_tree))
+        }
+        _ => (None, None),
+    };
+    Ok(ListBalancesAtResponse {
+        block_index: block_index.into(),
+        block_hash: block_hash.as_slice().to_vec(),
+        balances,
+        next,
+        certificate,
+        hash_tree,
+    })
+}
+
 #[query(hidden = true)]
 fn http_request(req: HttpRequest) -> HttpResponse {
     if req.path() == "/metrics" {
@@ -947,3 +1445,33 @@ fn check_candid_interface() {
         )
     });
 }
+
+#[test]
+fn test_blocks_to_revert() {
+    // The account took part in blocks 9, 7, 4 and 2 and collected the
+    // fees of the blocks 3..6 and 8..10.
+    let account_block_ids = [9, 7, 4, 2];
+    let fee_collector_ranges = [3..6, 8..10];
+
+    assert_eq!(
+        vec![9, 8, 7, 5, 4, 3, 2],
+        blocks_to_revert(&account_block_ids, &fee_collector_ranges, 1, u64::MAX, 100)
+    );
+    // Only the blocks strictly between after and before are reverted.
+    assert_eq!(
+        vec![7, 5, 4],
+        blocks_to_revert(&account_block_ids, &fee_collector_ranges, 3, 8, 100)
+    );
+    // At most limit + 1 blocks are returned, the most recent first.
+    assert_eq!(
+        vec![9, 8, 7],
+        blocks_to_revert(&account_block_ids, &fee_collector_ranges, 1, u64::MAX, 2)
+    );
+    assert_eq!(
+        vec![9],
+        blocks_to_revert(&account_block_ids, &fee_collector_ranges, 1, u64::MAX, 0)
+    );
+    assert!(
+        blocks_to_revert(&account_block_ids, &fee_collector_ranges, 9, u64::MAX, 10).is_empty()
+    );
+}
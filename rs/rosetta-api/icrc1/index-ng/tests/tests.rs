@@ -5,8 +5,10 @@ use ic_canisters_http_types::{HttpRequest, HttpResponse};
 use ic_icrc1::blocks::generic_block_to_encoded_block;
 use ic_icrc1::Block;
 use ic_icrc1_index_ng::{
-    FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
+    AccountBalance, BalanceAt, BalanceAtCursor, FeeCollectorRanges, GetAccountBalanceAtArgs,
+    GetAccountBalanceAtResult, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
     GetAccountTransactionsResult, GetBlocksResponse, IndexArg, InitArg as IndexInitArg,
+    ListBalancesAtArgs, ListBalancesAtCursor, ListBalancesAtResponse, ListBalancesAtResult,
     ListSubaccountsArgs, Log, Status, TransactionWithId, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
 };
 use ic_icrc1_ledger::{
@@ -509,6 +511,56 @@ fn list_subaccounts(
     .expect("failed to decode list_subaccounts response")
 }
 
+fn get_account_balance_at(
+    env: &StateMachine,
+    index: CanisterId,
+    account: Account,
+    block_index: u64,
+    resume: Option<BalanceAtCursor>,
+) -> GetAccountBalanceAtResult {
+    Decode!(
+        &env.execute_ingress(
+            index,
+            "get_account_balance_at",
+            Encode!(&GetAccountBalanceAtArgs {
+                account,
+                block_index: block_index.into(),
+                resume,
+            })
+            .unwrap()
+        )
+        .expect("failed to get_account_balance_at")
+        .bytes(),
+        GetAccountBalanceAtResult
+    )
+    .expect("failed to decode get_account_balance_at response")
+}
+
+fn list_balances_at(
+    env: &StateMachine,
+    index: CanisterId,
+    block_index: u64,
+    cursor: Option<ListBalancesAtCursor>,
+    max_results: u64,
+) -> ListBalancesAtResult {
+    Decode!(
+        &env.query(
+            index,
+            "list_balances_at",
+            Encode!(&ListBalancesAtArgs {
+                block_index: block_index.into(),
+                cursor,
+                max_results: max_results.into(),
+            })
+            .unwrap()
+        )
+        .expect("failed to list_balances_at")
+        .bytes(),
+        ListBalancesAtResult
+    )
+    .expect("failed to decode list_balances_at response")
+}
+
 fn get_fee_collectors_ranges(env: &StateMachine, index: CanisterId) -> FeeCollectorRanges {
     Decode!(
         &env.execute_ingress(index, "get_fee_collectors_ranges", Encode!(&()).unwrap())
@@ -1481,3 +1533,129 @@ fn test_principal_subaccounts() {
     // The subaccount 1 should show up in a `list_subaccount` query although it has only been involved in an Approve transaction
     assert!(subaccounts.contains(&account(2, 1).subaccount.unwrap()));
 }
+
+#[test]
+fn test_balances_at() {
+    let account_1 = account(1, 0);
+    let account_2 = account(2, 0);
+    let account_3 = account(3, 0);
+    let fee_collector = account(42, 0);
+
+    let env = &StateMachine::new();
+    let minter = minter_identity();
+    let ledger_id = install_ledger(
+        env,
+        vec![(account_1, 1_000_000), (account_2, 500_000)],
+        default_archive_options(),
+        Some(fee_collector),
+        minter.sender().unwrap(),
+    );
+    let index_id = install_index_ng(env, ledger_id);
+
+    // Blocks 0 and 1 are the mints of the initial balances.
+    assert_eq!(
+        Nat::from(2u64),
+        transfer(env, ledger_id, account_1, account_2, 100_000)
+    );
+    assert_eq!(
+        Nat::from(3u64),
+        transfer(env, ledger_id, account_2, account_3, 200_000)
+    );
+    assert_eq!(
+        Nat::from(4u64),
+        transfer(env, ledger_id, account_1, account_3, 50_000)
+    );
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+
+    let expected_balances = vec![
+        vec![(account_1, 1_000_000)],
+        vec![(account_1, 1_000_000), (account_2, 500_000)],
+        vec![
+            (account_1, 900_000 - FEE),
+            (account_2, 600_000),
+            (fee_collector, FEE),
+        ],
+        vec![
+            (account_1, 900_000 - FEE),
+            (account_2, 400_000 - FEE),
+            (account_3, 200_000),
+            (fee_collector, 2 * FEE),
+        ],
+        vec![
+            (account_1, 850_000 - 2 * FEE),
+            (account_2, 400_000 - FEE),
+            (account_3, 250_000),
+            (fee_collector, 3 * FEE),
+        ],
+    ];
+
+    for (block_index, expected_balances) in expected_balances.into_iter().enumerate() {
+        let block_index = block_index as u64;
+        for account in [account_1, account_2, account_3, fee_collector] {
+            let expected_balance = expected_balances
+                .iter()
+                .find(|(a, _)| a == &account)
+                .map_or(0, |(_, balance)| *balance);
+            assert_eq!(
+                Ok(BalanceAt::Balance(Nat::from(expected_balance))),
+                get_account_balance_at(env, index_id, account, block_index, None),
+                "account: {} block_index: {}",
+                account,
+                block_index
+            );
+        }
+
+        // Read the snapshot one balance at a time to exercise the paging.
+        let mut balances = vec![];
+        let mut cursor = None;
+        loop {
+            let ListBalancesAtResponse {
+                block_index: response_block_index,
+                block_hash,
+                balances: page,
+                next,
+                certificate,
+                hash_tree,
+            } = list_balances_at(env, index_id, block_index, cursor, 1).unwrap();
+            assert_eq!(Nat::from(block_index), response_block_index);
+            assert_eq!(32, block_hash.len());
+            // Only the balances after the last indexed block are certified.
+            let is_last_block = block_index == 4;
+            assert_eq!(is_last_block, certificate.is_some());
+            assert_eq!(is_last_block, hash_tree.is_some());
+            assert!(page.len() <= 1);
+            balances.extend(page);
+            match next {
+                Some(next) => {
+                    // The blocks of this test fit into the budget of a single call.
+                    assert_eq!(None, next.pending);
+                    cursor = Some(next);
+                }
+                None => break,
+            }
+        }
+        let balances: HashSet<_> = balances
+            .into_iter()
+            .map(|AccountBalance { account, balance }| (account, balance.0.to_u64().unwrap()))
+            .collect();
+        let expected_balances: HashSet<_> = expected_balances.into_iter().collect();
+        assert_eq!(expected_balances, balances, "block_index: {}", block_index);
+    }
+
+    // A computation can be resumed from a cursor: the balance of account_1
+    // right before block 4 is its balance after block 3.
+    let cursor = BalanceAtCursor {
+        balance: Nat::from(900_000 - FEE),
+        reverted_from: Nat::from(4u64),
+    };
+    assert_eq!(
+        Ok(BalanceAt::Balance(Nat::from(1_000_000u64))),
+        get_account_balance_at(env, index_id, account_1, 1, Some(cursor.clone()))
+    );
+    // The cursor must not start at or before the requested block.
+    assert!(get_account_balance_at(env, index_id, account_1, 4, Some(cursor)).is_err());
+
+    // Blocks that have not been indexed yet are rejected.
+    assert!(get_account_balance_at(env, index_id, account_1, 5, None).is_err());
+    assert!(list_balances_at(env, index_id, 5, None, 10).is_err());
+}
@@ -18,6 +18,11 @@ use icrc_ledger_types::icrc1::transfer::{Memo, TransferArg, TransferError};
 use icrc_ledger_types::icrc2::allowance::{Allowance, AllowanceArgs};
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
 use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
+use icrc_ledger_types::icrc21::errors::Icrc21Error;
+use icrc_ledger_types::icrc21::requests::{
+    ConsentMessageMetadata, ConsentMessageRequest, ConsentMessageSpec, DisplayMessageType,
+};
+use icrc_ledger_types::icrc21::responses::{ConsentInfo, ConsentMessage};
 use icrc_ledger_types::icrc3;
 use icrc_ledger_types::icrc3::archive::ArchiveInfo;
 use icrc_ledger_types::icrc3::blocks::BlockRange;
@@ -454,6 +459,27 @@ pub fn send_approval(
     .map(|n| n.0.to_u64().unwrap())
 }
 
+pub fn icrc21_consent_message(
+    env: &StateMachine,
+    ledger: CanisterId,
+    caller: Principal,
+    consent_msg_request: ConsentMessageRequest,
+) -> Result<ConsentInfo, Icrc21Error> {
+    Decode!(
+        &env.execute_ingress_as(
+            PrincipalId(caller),
+            ledger,
+            "icrc21_canister_call_consent_message",
+            Encode!(&consent_msg_request)
+            .unwrap()
+        )
+        .expect("failed to call icrc21_canister_call_consent_message")
+        .bytes(),
+        Result<ConsentInfo, Icrc21Error>
+    )
+    .expect("failed to decode icrc21_canister_call_consent_message response")
+}
+
 pub fn send_transfer_from(
     env: &StateMachine,
     ledger: CanisterId,
@@ -751,7 +777,7 @@ where
         standards.push(standard.name);
     }
     standards.sort();
-    assert_eq!(standards, vec!["ICRC-1", "ICRC-2"]);
+    assert_eq!(standards, vec!["ICRC-1", "ICRC-2", "ICRC-21"]);
 }
 pub fn test_metadata<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
 where
@@ -819,7 +845,7 @@ where
         standards.push(standard.name);
     }
     standards.sort();
-    assert_eq!(standards, vec!["ICRC-1", "ICRC-2"]);
+    assert_eq!(standards, vec!["ICRC-1", "ICRC-2", "ICRC-21"]);
 }
 
 pub fn test_total_supply<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
@@ -873,6 +899,133 @@ where
     assert_eq!(6_000_000u64, balance_of(&env, canister_id, p2.0));
 }
 
+pub fn test_icrc21_standard<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
+where
+    T: CandidType,
+{
+    let p1 = PrincipalId::new_user_test_id(1);
+    let p2 = PrincipalId::new_user_test_id(2);
+    let (env, canister_id) = setup(ledger_wasm, encode_init_args, vec![]);
+
+    let transfer_arg = TransferArg {
+        from_subaccount: None,
+        to: Account::from(p2.0),
+        fee: None,
+        created_at_time: None,
+        memo: Some(Memo::from(b"test".to_vec())),
+        amount: Nat::from(150_000_000u64),
+    };
+    let request = |method: &str, arg: Vec<u8>, device_spec| ConsentMessageRequest {
+        method: method.to_string(),
+        arg: serde_bytes::ByteBuf::from(arg),
+        user_preferences: ConsentMessageSpec {
+            metadata: ConsentMessageMetadata {
+                language: "en".to_string(),
+                utc_offset_minutes: None,
+            },
+            device_spec,
+        },
+    };
+
+    let consent_info = icrc21_consent_message(
+        &env,
+        canister_id,
+        p1.0,
+        request("icrc1_transfer", Encode!(&transfer_arg).unwrap(), None),
+    )
+    .expect("failed to get the consent message of a transfer");
+    assert_eq!(consent_info.metadata.language, "en");
+    let message = match consent_info.consent_message {
+        ConsentMessage::GenericDisplayMessage(message) => message,
+        message => panic!("expected a generic display message, got {:?}", message),
+    };
+    assert!(message.contains(&format!("Transfer {}", TOKEN_SYMBOL)));
+    assert!(message.contains(&format!("1.5 {}", TOKEN_SYMBOL)));
+    assert!(message.contains(&Account::from(p1.0).to_string()));
+    assert!(message.contains(&Account::from(p2.0).to_string()));
+    assert!(message.contains(&format!("0.0001 {}", TOKEN_SYMBOL)));
+    assert!(message.contains("test"));
+
+    let approve_args = ApproveArgs {
+        from_subaccount: None,
+        spender: Account::from(p2.0),
+        amount: Nat::from(100_000_000u64),
+        expected_allowance: None,
+        expires_at: None,
+        fee: None,
+        memo: None,
+        created_at_time: None,
+    };
+    let consent_info = icrc21_consent_message(
+        &env,
+        canister_id,
+        p1.0,
+        request(
+            "icrc2_approve",
+            Encode!(&approve_args).unwrap(),
+            Some(DisplayMessageType::LineDisplay {
+                characters_per_line: 20,
+                lines_per_page: 3,
+            }),
+        ),
+    )
+    .expect("failed to get the consent message of an approval");
+    match consent_info.consent_message {
+        ConsentMessage::LineDisplayMessage { pages } => {
+            assert!(!pages.is_empty());
+            for page in pages {
+                assert!(page.lines.len() <= 3);
+                assert!(page.lines.iter().all(|line| line.chars().count() <= 20));
+            }
+        }
+        message => panic!("expected a line display message, got {:?}", message),
+    }
+
+    let transfer_from_args = TransferFromArgs {
+        spender_subaccount: None,
+        from: Account::from(p1.0),
+        to: Account::from(p2.0),
+        amount: Nat::from(1u64),
+        fee: None,
+        memo: None,
+        created_at_time: None,
+    };
+    assert!(icrc21_consent_message(
+        &env,
+        canister_id,
+        p2.0,
+        request(
+            "icrc2_transfer_from",
+            Encode!(&transfer_from_args).unwrap(),
+            None
+        ),
+    )
+    .is_ok());
+
+    assert!(matches!(
+        icrc21_consent_message(
+            &env,
+            canister_id,
+            p1.0,
+            request(
+                "icrc1_balance_of",
+                Encode!(&Account::from(p1.0)).unwrap(),
+                None
+            ),
+        ),
+        Err(Icrc21Error::UnsupportedCanisterCall(_))
+    ));
+    assert!(matches!(
+        icrc21_consent_message(
+            &env,
+            canister_id,
+            p1.0,
+            request("icrc2_approve", Encode!(&transfer_arg).unwrap(), None),
+        ),
+        Err(Icrc21Error::UnsupportedCanisterCall(_))
+    ));
+}
+
 pub fn test_tx_deduplication<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
 where
     T: CandidType,
@@ -2655,9 +2808,12 @@ pub fn expect_icrc2_disabled(
         "Expected ICRC-2 disabled error, got: {}",
         err.description()
     );
-    let standards = supported_standards(env, canister_id);
-    assert_eq!(standards.len(), 1);
-    assert_eq!(standards[0].name, "ICRC-1");
+    let mut standards = vec![];
+    for standard in supported_standards(env, canister_id) {
+        standards.push(standard.name);
+    }
+    standards.sort();
+    assert_eq!(standards, vec!["ICRC-1", "ICRC-21"]);
 }
 
 pub fn test_feature_flags<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
@@ -2729,7 +2885,7 @@ where
         standards.push(standard.name);
     }
     standards.sort();
-    assert_eq!(standards, vec!["ICRC-1", "ICRC-2"]);
+    assert_eq!(standards, vec!["ICRC-1", "ICRC-2", "ICRC-21"]);
 
     let block_index =
         send_approval(&env, canister_id, from.0, &approve_args).expect("approval failed");
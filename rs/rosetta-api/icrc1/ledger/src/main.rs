@@ -5,7 +5,7 @@ use ic_canisters_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
 use ic_cdk::api::stable::{StableReader, StableWriter};
 use ic_cdk_macros::{init, post_upgrade, pre_upgrade, query, update};
 use ic_icrc1::{
-    endpoints::{convert_transfer_error, StandardRecord},
+    endpoints::{convert_transfer_error, BatchTransferArgs, StandardRecord},
     Operation, Transaction,
 };
 use ic_icrc1_ledger::{Ledger, LedgerArgument};
@@ -17,6 +17,10 @@ use ic_ledger_core::tokens::Zero;
 use ic_ledger_core::{approvals::Approvals, timestamp::TimeStamp};
 use icrc_ledger_types::icrc1::transfer::Memo;
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
+use icrc_ledger_types::icrc21::{
+    errors::Icrc21Error, lib::build_icrc21_consent_info, requests::ConsentMessageRequest,
+    responses::ConsentInfo,
+};
 use icrc_ledger_types::icrc3::blocks::DataCertificate;
 use icrc_ledger_types::{
     icrc::generic_metadata_value::MetadataValue as Value,
@@ -40,6 +44,18 @@ use std::cell::RefCell;
 
 const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
 
+/// The maximum number of transfers in a single `batch_transfer` call.
+const MAX_TRANSFERS_PER_BATCH: usize = 1_000;
+
+/// The error code of the `GenericError` that `batch_transfer` returns for a
+/// transfer whose memo exceeds the maximum memo length of the ledger.
+const MEMO_TOO_LONG_ERROR_CODE: u64 = 1;
+
+/// The number of instructions after which `batch_transfer` stops
+/// applying transfers. This is well below the instruction limit of an update
+/// call so that the message never runs out of instructions mid-batch.
+const MAX_BATCH_TRANSFER_INSTRUCTIONS: u64 = 10_000_000_000;
+
 #[cfg(not(feature = "u256-tokens"))]
 type Tokens = ic_icrc1_tokens_u64::U64;
 
@@ -275,7 +291,10 @@ fn icrc1_total_supply() -> Nat {
     Access::with_ledger(|ledger| ledger.balances().total_supply().into())
 }
 
-async fn execute_transfer(
+#[allow(clippy::too_many_arguments)]
+fn execute_transfer_not_async(
+    ledger: &mut Ledger<Tokens>,
+    now: TimeStamp,
     from_account: Account,
     to: Account,
     spender: Option<Account>,
@@ -283,98 +302,118 @@ async fn execute_transfer(
     amount: Nat,
     memo: Option<Memo>,
     created_at_time: Option<u64>,
-) -> Result<Nat, CoreTransferError<Tokens>> {
-    let block_idx = Access::with_ledger_mut(|ledger| {
-        let now = TimeStamp::from_nanos_since_unix_epoch(ic_cdk::api::time());
-        let created_at_time = created_at_time.map(TimeStamp::from_nanos_since_unix_epoch);
-
-        match memo.as_ref() {
-            Some(memo) if memo.0.len() > ledger.max_memo_length() as usize => {
-                ic_cdk::trap(&format!(
-                    "the memo field size of {} bytes is above the allowed limit of {} bytes",
-                    memo.0.len(),
-                    ledger.max_memo_length()
-                ))
-            }
-            _ => {}
-        };
-        let amount = match Tokens::try_from(amount.clone()) {
-            Ok(n) => n,
-            Err(_) => {
-                // No one can have so many tokens
-                let balance_tokens = ledger.balances().account_balance(&from_account);
-                let balance = Nat::from(balance_tokens);
-                assert!(balance < amount);
-                return Err(CoreTransferError::InsufficientFunds {
-                    balance: balance_tokens,
-                });
-            }
-        };
+) -> Result<u64, CoreTransferError<Tokens>> {
+    let created_at_time = created_at_time.map(TimeStamp::from_nanos_since_unix_epoch);
+
+    match memo.as_ref() {
+        Some(memo) if memo.0.len() > ledger.max_memo_length() as usize => ic_cdk::trap(&format!(
+            "the memo field size of {} bytes is above the allowed limit of {} bytes",
+            memo.0.len(),
+            ledger.max_memo_length()
+        )),
+        _ => {}
+    };
+    let amount = match Tokens::try_from(amount.clone()) {
+        Ok(n) => n,
+        Err(_) => {
+            // No one can have so many tokens
+            let balance_tokens = ledger.balances().account_balance(&from_account);
+            let balance = Nat::from(balance_tokens);
+            assert!(balance < amount);
+            return Err(CoreTransferError::InsufficientFunds {
+                balance: balance_tokens,
+            });
+        }
+    };
 
-        let (tx, effective_fee) = if &to == ledger.minting_account() {
-            let expected_fee = Tokens::zero();
-            if fee.is_some() && fee.as_ref() != Some(&expected_fee.into()) {
-                return Err(CoreTransferError::BadFee { expected_fee });
-            }
+    let (tx, effective_fee) = if &to == ledger.minting_account() {
+        let expected_fee = Tokens::zero();
+        if fee.is_some() && fee.as_ref() != Some(&expected_fee.into()) {
+            return Err(CoreTransferError::BadFee { expected_fee });
+        }
 
-            let balance = ledger.balances().account_balance(&from_account);
-            let min_burn_amount = ledger.transfer_fee().min(balance);
-            if amount < min_burn_amount {
-                return Err(CoreTransferError::BadBurn { min_burn_amount });
-            }
-            if Tokens::is_zero(&amount) {
-                return Err(CoreTransferError::BadBurn {
-                    min_burn_amount: ledger.transfer_fee(),
-                });
-            }
+        let balance = ledger.balances().account_balance(&from_account);
+        let min_burn_amount = ledger.transfer_fee().min(balance);
+        if amount < min_burn_amount {
+            return Err(CoreTransferError::BadBurn { min_burn_amount });
+        }
+        if Tokens::is_zero(&amount) {
+            return Err(CoreTransferError::BadBurn {
+                min_burn_amount: ledger.transfer_fee(),
+            });
+        }
 
-            (
-                Transaction {
-                    operation: Operation::Burn {
-                        from: from_account,
-                        spender,
-                        amount,
-                    },
-                    created_at_time: created_at_time.map(|t| t.as_nanos_since_unix_epoch()),
-                    memo,
-                },
-                Tokens::zero(),
-            )
-        } else if &from_account == ledger.minting_account() {
-            if spender.is_some() {
-                ic_cdk::trap("the minter account cannot delegate mints")
-            }
-            let expected_fee = Tokens::zero();
-            if fee.is_some() && fee.as_ref() != Some(&expected_fee.into()) {
-                return Err(CoreTransferError::BadFee { expected_fee });
-            }
-            (
-                Transaction::mint(to, amount, created_at_time, memo),
-                Tokens::zero(),
-            )
-        } else {
-            let expected_fee_tokens = ledger.transfer_fee();
-            if fee.is_some() && fee.as_ref() != Some(&expected_fee_tokens.into()) {
-                return Err(CoreTransferError::BadFee {
-                    expected_fee: expected_fee_tokens,
-                });
-            }
-            (
-                Transaction::transfer(
-                    from_account,
-                    to,
+        (
+            Transaction {
+                operation: Operation::Burn {
+                    from: from_account,
                     spender,
                     amount,
-                    fee.map(|_| expected_fee_tokens),
-                    created_at_time,
-                    memo,
-                ),
-                expected_fee_tokens,
-            )
-        };
+                },
+                created_at_time: created_at_time.map(|t| t.as_nanos_since_unix_epoch()),
+                memo,
+            },
+            Tokens::zero(),
+        )
+    } else if &from_account == ledger.minting_account() {
+        if spender.is_some() {
+            ic_cdk::trap("the minter account cannot delegate mints")
+        }
+        let expected_fee = Tokens::zero();
+        if fee.is_some() && fee.as_ref() != Some(&expected_fee.into()) {
+            return Err(CoreTransferError::BadFee { expected_fee });
+        }
+        (
+            Transaction::mint(to, amount, created_at_time, memo),
+            Tokens::zero(),
+        )
+    } else {
+        let expected_fee_tokens = ledger.transfer_fee();
+        if fee.is_some() && fee.as_ref() != Some(&expected_fee_tokens.into()) {
+            return Err(CoreTransferError::BadFee {
+                expected_fee: expected_fee_tokens,
+            });
+        }
+        (
+            Transaction::transfer(
+                from_account,
+                to,
+                spender,
+                amount,
+                fee.map(|_| expected_fee_tokens),
+                created_at_time,
+                memo,
+            ),
+            expected_fee_tokens,
+        )
+    };
 
-        let (block_idx, _) = apply_transaction(ledger, tx, now, effective_fee)?;
-        Ok(block_idx)
+    let (block_idx, _) = apply_transaction(ledger, tx, now, effective_fee)?;
+    Ok(block_idx)
+}
+
+async fn execute_transfer(
+    from_account: Account,
+    to: Account,
+    spender: Option<Account>,
+    fee: Option<Nat>,
+    amount: Nat,
+    memo: Option<Memo>,
+    created_at_time: Option<u64>,
+) -> Result<Nat, CoreTransferError<Tokens>> {
+    let block_idx = Access::with_ledger_mut(|ledger| {
+        let now = TimeStamp::from_nanos_since_unix_epoch(ic_cdk::api::time());
+        execute_transfer_not_async(
+            ledger,
+            now,
+            from_account,
+            to,
+            spender,
+            fee,
+            amount,
+            memo,
+            created_at_time,
+        )
     })?;
 
     // NB. we need to set the certified data before the first async call to make sure that the
@@ -439,6 +478,93 @@ async fn icrc2_transfer_from(arg: TransferFromArgs) -> Result<Nat, TransferFromE
     })
 }
 
+#[update]
+#[candid_method(update)]
+async fn batch_transfer(arg: BatchTransferArgs) -> Vec<Result<Nat, TransferError>> {
+    if arg.transfers.len() > MAX_TRANSFERS_PER_BATCH {
+        ic_cdk::trap(&format!(
+            "the batch contains {} transfers, the maximum number of transfers in a batch is {}",
+            arg.transfers.len(),
+            MAX_TRANSFERS_PER_BATCH
+        ))
+    }
+    let caller = ic_cdk::api::caller();
+    let results = Access::with_ledger_mut(|ledger| {
+        let now = TimeStamp::from_nanos_since_unix_epoch(ic_cdk::api::time());
+        let mut results = Vec::with_capacity(arg.transfers.len());
+        for (index, transfer) in arg.transfers.into_iter().enumerate() {
+            // Every transfer must leave enough instructions for the following ones
+            // and for the certification of the new blocks.
+            if ic_cdk::api::instruction_counter() > MAX_BATCH_TRANSFER_INSTRUCTIONS {
+                if arg.atomic {
+                    ic_cdk::trap(&format!(
+                        "the batch reached the instruction limit at transfer {}, no transfer was applied",
+                        index
+                    ))
+                }
+                results.push(Err(TransferError::TemporarilyUnavailable));
+                continue;
+            }
+            let from_account = Account {
+                owner: caller,
+                subaccount: transfer.from_subaccount,
+            };
+            let result = match transfer.memo.as_ref() {
+                // A single transfer must not trap as that would revert the whole batch.
+                Some(memo) if memo.0.len() > ledger.max_memo_length() as usize => {
+                    Err(TransferError::GenericError {
+                        error_code: Nat::from(MEMO_TOO_LONG_ERROR_CODE),
+                        message: format!(
+                            "the memo field size of {} bytes is above the allowed limit of {} bytes",
+                            memo.0.len(),
+                            ledger.max_memo_length()
+                        ),
+                    })
+                }
+                _ => execute_transfer_not_async(
+                    ledger,
+                    now,
+                    from_account,
+                    transfer.to,
+                    None,
+                    transfer.fee,
+                    transfer.amount,
+                    transfer.memo,
+                    transfer.created_at_time,
+                )
+                .map(Nat::from)
+                .map_err(convert_transfer_error)
+                .map_err(|err| {
+                    let err: TransferError = match err.try_into() {
+                        Ok(err) => err,
+                        Err(err) => ic_cdk::trap(&err),
+                    };
+                    err
+                }),
+            };
+            if let Err(err) = &result {
+                // Trapping reverts all the changes made by this message, including
+                // the blocks of the transfers that were already applied.
+                if arg.atomic {
+                    ic_cdk::trap(&format!(
+                        "transfer {} of the batch failed with {:?}, no transfer was applied",
+                        index, err
+                    ))
+                }
+            }
+            results.push(result);
+        }
+        results
+    });
+
+    // NB. we need to set the certified data before the first async call to make sure that the
+    // blockchain state agrees with the certificate while archiving is in progress.
+    ic_cdk::api::set_certified_data(&Access::with_ledger(Ledger::root_hash));
+
+    archive_blocks::<Access>(&LOG, MAX_MESSAGE_SIZE).await;
+    results
+}
+
 #[query]
 fn archives() -> Vec<ArchiveInfo> {
     Access::with_ledger(|ledger| {
@@ -475,6 +601,10 @@ fn supported_standards() -> Vec<StandardRecord> {
             name: "ICRC-2".to_string(),
             url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-2".to_string(),
         },
+        StandardRecord {
+            name: "ICRC-21".to_string(),
+            url: "https://github.com/dfinity/wg-identity-authentication/blob/main/topics/icrc_21_consent_msg.md".to_string(),
+        },
     ];
     standards
 }
@@ -602,6 +732,29 @@ fn icrc2_allowance(arg: AllowanceArgs) -> Allowance {
     })
 }
 
+#[update]
+#[candid_method(update)]
+fn icrc21_canister_call_consent_message(
+    consent_msg_request: ConsentMessageRequest,
+) -> Result<ConsentInfo, Icrc21Error> {
+    let caller_principal = ic_cdk::api::caller();
+    let (ledger_fee, token_symbol, decimals) = Access::with_ledger(|ledger| {
+        (
+            Nat::from(ledger.transfer_fee()),
+            ledger.token_symbol().to_string(),
+            ledger.decimals(),
+        )
+    });
+
+    build_icrc21_consent_info(
+        consent_msg_request,
+        caller_principal,
+        ledger_fee,
+        token_symbol,
+        decimals,
+    )
+}
+
 candid::export_service!();
 
 #[query]
@@ -1,18 +1,19 @@
 use candid::{CandidType, Decode, Encode, Nat};
 use ic_base_types::{CanisterId, PrincipalId};
+use ic_icrc1::endpoints::BatchTransferArgs;
 use ic_icrc1_ledger::{ChangeFeeCollector, FeatureFlags, InitArgs, LedgerArgument};
 use ic_icrc1_ledger_sm_tests::{
-    get_allowance, send_approval, send_transfer_from, ARCHIVE_TRIGGER_THRESHOLD, BLOB_META_KEY,
-    BLOB_META_VALUE, DECIMAL_PLACES, FEE, INT_META_KEY, INT_META_VALUE, MINTER, NAT_META_KEY,
-    NAT_META_VALUE, NUM_BLOCKS_TO_ARCHIVE, TEXT_META_KEY, TEXT_META_VALUE, TOKEN_NAME,
-    TOKEN_SYMBOL,
+    get_allowance, send_approval, send_transfer_from, system_time_to_nanos,
+    ARCHIVE_TRIGGER_THRESHOLD, BLOB_META_KEY, BLOB_META_VALUE, DECIMAL_PLACES, FEE, INT_META_KEY,
+    INT_META_VALUE, MINTER, NAT_META_KEY, NAT_META_VALUE, NUM_BLOCKS_TO_ARCHIVE, TEXT_META_KEY,
+    TEXT_META_VALUE, TOKEN_NAME, TOKEN_SYMBOL,
 };
 use ic_ledger_canister_core::archive::ArchiveOptions;
 use ic_ledger_core::block::BlockIndex;
 use ic_state_machine_tests::StateMachine;
 use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue as Value;
 use icrc_ledger_types::icrc1::account::Account;
-use icrc_ledger_types::icrc1::transfer::{TransferArg, TransferError};
+use icrc_ledger_types::icrc1::transfer::{Memo, TransferArg, TransferError};
 use icrc_ledger_types::icrc2::allowance::Allowance;
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
 use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
@@ -122,6 +123,11 @@ fn test_single_transfer() {
     ic_icrc1_ledger_sm_tests::test_single_transfer(ledger_wasm(), encode_init_args);
 }
 
+#[test]
+fn test_icrc21_standard() {
+    ic_icrc1_ledger_sm_tests::test_icrc21_standard(ledger_wasm(), encode_init_args);
+}
+
 #[test]
 fn test_account_canonicalization() {
     ic_icrc1_ledger_sm_tests::test_account_canonicalization(ledger_wasm(), encode_init_args);
@@ -424,6 +430,157 @@ fn test_upgrade_from_first_version() {
     transfer(&env, ledger_id, account(1), account(3), 1_000_000);
 }
 
+fn batch_transfer(
+    env: &StateMachine,
+    ledger_id: CanisterId,
+    from: Account,
+    arg: &BatchTransferArgs,
+) -> Result<Vec<Result<Nat, TransferError>>, String> {
+    env.execute_ingress_as(
+        from.owner.into(),
+        ledger_id,
+        "batch_transfer",
+        Encode!(arg).unwrap(),
+    )
+    .map(|res| Decode!(&res.bytes(), Vec<Result<Nat, TransferError>>).unwrap())
+    .map_err(|err| err.to_string())
+}
+
+fn transfer_arg(to: Account, amount: u64, created_at_time: Option<u64>) -> TransferArg {
+    TransferArg {
+        from_subaccount: None,
+        to,
+        amount: amount.into(),
+        fee: None,
+        created_at_time,
+        memo: None,
+    }
+}
+
+#[test]
+fn test_batch_transfer() {
+    let (env, ledger_id) = ic_icrc1_ledger_sm_tests::setup(
+        ledger_wasm(),
+        encode_init_args,
+        vec![(account(1), 10_000_000)],
+    );
+    let now = system_time_to_nanos(env.time());
+
+    // Non-atomic batches report the result of every transfer and
+    // deduplicate transfers within the batch.
+    let results = batch_transfer(
+        &env,
+        ledger_id,
+        account(1),
+        &BatchTransferArgs {
+            transfers: vec![
+                transfer_arg(account(2), 1_000_000, None),
+                transfer_arg(account(3), 100_000_000, None),
+                transfer_arg(account(3), 2_000_000, Some(now)),
+                transfer_arg(account(3), 2_000_000, Some(now)),
+            ],
+            atomic: false,
+        },
+    )
+    .expect("batch transfer failed");
+    assert_eq!(
+        results,
+        vec![
+            Ok(Nat::from(1u64)),
+            Err(TransferError::InsufficientFunds {
+                balance: Nat::from(9_000_000u64 - FEE)
+            }),
+            Ok(Nat::from(2u64)),
+            Err(TransferError::Duplicate {
+                duplicate_of: Nat::from(2u64)
+            }),
+        ]
+    );
+    assert_eq!(
+        10_000_000 - 3_000_000 - 2 * FEE,
+        balance_of(&env, ledger_id, account(1))
+    );
+    assert_eq!(1_000_000, balance_of(&env, ledger_id, account(2)));
+    assert_eq!(2_000_000, balance_of(&env, ledger_id, account(3)));
+
+    // A failing transfer rejects an atomic batch and none of its transfers is applied.
+    let balance_1 = balance_of(&env, ledger_id, account(1));
+    let result = batch_transfer(
+        &env,
+        ledger_id,
+        account(1),
+        &BatchTransferArgs {
+            transfers: vec![
+                transfer_arg(account(2), 1_000_000, None),
+                transfer_arg(account(3), 100_000_000, None),
+            ],
+            atomic: true,
+        },
+    );
+    assert!(result.is_err(), "expected the batch to be rejected");
+    assert_eq!(balance_1, balance_of(&env, ledger_id, account(1)));
+    assert_eq!(1_000_000, balance_of(&env, ledger_id, account(2)));
+    assert_eq!(2_000_000, balance_of(&env, ledger_id, account(3)));
+
+    // Every transfer of a successful atomic batch produces its own block.
+    let results = batch_transfer(
+        &env,
+        ledger_id,
+        account(1),
+        &BatchTransferArgs {
+            transfers: vec![
+                transfer_arg(account(2), 1_000_000, None),
+                transfer_arg(account(3), 1_000_000, None),
+            ],
+            atomic: true,
+        },
+    )
+    .expect("batch transfer failed");
+    assert_eq!(results, vec![Ok(Nat::from(3u64)), Ok(Nat::from(4u64))]);
+    assert_eq!(
+        balance_1 - 2_000_000 - 2 * FEE,
+        balance_of(&env, ledger_id, account(1))
+    );
+    assert_eq!(2_000_000, balance_of(&env, ledger_id, account(2)));
+    assert_eq!(3_000_000, balance_of(&env, ledger_id, account(3)));
+
+    // A memo that is too long fails only its own transfer in a non-atomic batch.
+    let balance_1 = balance_of(&env, ledger_id, account(1));
+    let results = batch_transfer(
+        &env,
+        ledger_id,
+        account(1),
+        &BatchTransferArgs {
+            transfers: vec![
+                TransferArg {
+                    memo: Some(Memo::from(vec![0u8; 33])),
+                    ..transfer_arg(account(2), 1_000_000, None)
+                },
+                transfer_arg(account(3), 1_000_000, None),
+            ],
+            atomic: false,
+        },
+    )
+    .expect("batch transfer failed");
+    assert_eq!(
+        results,
+        vec![
+            Err(TransferError::GenericError {
+                error_code: Nat::from(1u64),
+                message: "the memo field size of 33 bytes is above the allowed limit of 32 bytes"
+                    .to_string(),
+            }),
+            Ok(Nat::from(5u64)),
+        ]
+    );
+    assert_eq!(
+        balance_1 - 1_000_000 - FEE,
+        balance_of(&env, ledger_id, account(1))
+    );
+    assert_eq!(2_000_000, balance_of(&env, ledger_id, account(2)));
+    assert_eq!(4_000_000, balance_of(&env, ledger_id, account(3)));
+}
+
 #[test]
 fn test_icrc2_feature_flag_doesnt_disable_icrc2_endpoints() {
     // Disable ICRC-2 and check the endpoints still work
@@ -1,8 +1,10 @@
 use ic_icrc_rosetta::common::types::Error;
-use ic_icrc_rosetta::construction_api::types::ConstructionMetadataRequestOptions;
+use ic_icrc_rosetta::construction_api::types::{
+    ConstructionMetadataRequestOptions, ConstructionPayloadsRequestMetadata,
+};
 use reqwest::{Client, Url};
 use rosetta_core::identifiers::*;
-use rosetta_core::objects::Operation;
+use rosetta_core::objects::{Operation, PublicKey, Signature};
 use rosetta_core::request_types::*;
 use rosetta_core::response_types::*;
 use serde::{Deserialize, Serialize};
@@ -173,4 +175,106 @@ impl RosettaClient {
         )
         .await
     }
+
+    pub async fn construction_payloads(
+        &self,
+        operations: Vec<Operation>,
+        metadata: Option<ConstructionPayloadsRequestMetadata>,
+        public_keys: Option<Vec<PublicKey>>,
+        network_identifier: NetworkIdentifier,
+    ) -> Result<ConstructionPayloadsResponse, Error> {
+        self.call_endpoint(
+            "/construction/payloads",
+            &ConstructionPayloadsRequest {
+                network_identifier,
+                operations,
+                metadata: metadata.map(|metadata| metadata.into()),
+                public_keys,
+            },
+        )
+        .await
+    }
+
+    pub async fn construction_combine(
+        &self,
+        unsigned_transaction: String,
+        signatures: Vec<Signature>,
+        network_identifier: NetworkIdentifier,
+    ) -> Result<ConstructionCombineResponse, Error> {
+        self.call_endpoint(
+            "/construction/combine",
+            &ConstructionCombineRequest {
+                network_identifier,
+                unsigned_transaction,
+                signatures,
+            },
+        )
+        .await
+    }
+
+    pub async fn construction_parse(
+        &self,
+        signed: bool,
+        transaction: String,
+        network_identifier: NetworkIdentifier,
+    ) -> Result<ConstructionParseResponse, Error> {
+        self.call_endpoint(
+            "/construction/parse",
+            &ConstructionParseRequest::new(network_identifier, signed, transaction),
+        )
+        .await
+    }
+
+    pub async fn construction_hash(
+        &self,
+        signed_transaction: String,
+        network_identifier: NetworkIdentifier,
+    ) -> Result<ConstructionHashResponse, Error> {
+        self.call_endpoint(
+            "/construction/hash",
+            &ConstructionHashRequest {
+                network_identifier,
+                signed_transaction,
+            },
+        )
+        .await
+    }
+
+    pub async fn construction_submit(
+        &self,
+        signed_transaction: String,
+        network_identifier: NetworkIdentifier,
+    ) -> Result<ConstructionSubmitResponse, Error> {
+        self.call_endpoint(
+            "/construction/submit",
+            &ConstructionSubmitRequest::new(network_identifier, signed_transaction),
+        )
+        .await
+    }
+
+    pub async fn account_balance(
+        &self,
+        account_identifier: AccountIdentifier,
+        block_identifier: Option<PartialBlockIdentifier>,
+        network_identifier: NetworkIdentifier,
+    ) -> Result<AccountBalanceResponse, Error> {
+        self.call_endpoint(
+            "/account/balance",
+            &AccountBalanceRequest {
+                network_identifier,
+                account_identifier,
+                block_identifier,
+                metadata: None,
+            },
+        )
+        .await
+    }
+
+    pub async fn search_transactions(
+        &self,
+        search_transactions_request: SearchTransactionsRequest,
+    ) -> Result<SearchTransactionsResponse, Error> {
+        self.call_endpoint("/search/transactions", &search_transactions_request)
+            .await
+    }
 }
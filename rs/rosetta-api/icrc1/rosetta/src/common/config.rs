@@ -0,0 +1,167 @@
+use anyhow::{bail, Context};
+use ic_base_types::CanisterId;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// The configuration of a single ICRC-1 ledger served by Rosetta.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenConfig {
+    #[serde_as(as = "DisplayFromStr")]
+    pub ledger_id: CanisterId,
+
+    /// The symbol of the token. If not set, it is fetched from the
+    /// `icrc1_metadata` endpoint of the ledger.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icrc1_symbol: Option<String>,
+
+    /// The decimals of the token. If not set, they are fetched from the
+    /// `icrc1_metadata` endpoint of the ledger.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icrc1_decimals: Option<u8>,
+
+    /// The file to use for the store of this ledger.
+    /// If not set then it will be `<ledger_id>.sqlite` in the store directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store_file: Option<PathBuf>,
+}
+
+impl TokenConfig {
+    pub fn new(ledger_id: CanisterId) -> Self {
+        Self {
+            ledger_id,
+            icrc1_symbol: None,
+            icrc1_decimals: None,
+            store_file: None,
+        }
+    }
+
+    pub fn are_metadata_args_set(&self) -> bool {
+        self.icrc1_symbol.is_some() && self.icrc1_decimals.is_some()
+    }
+
+    /// Returns the file of the store of this ledger.
+    /// Relative paths are resolved against `store_directory`.
+    pub fn effective_store_file(&self, store_directory: &Path) -> PathBuf {
+        match &self.store_file {
+            Some(store_file) => store_directory.join(store_file),
+            None => store_directory.join(format!("{}.sqlite", self.ledger_id)),
+        }
+    }
+}
+
+/// The configuration of a Rosetta node that serves several ICRC-1 ledgers.
+/// Every ledger is exposed under its own `NetworkIdentifier`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiTokenConfig {
+    pub tokens: Vec<TokenConfig>,
+}
+
+impl MultiTokenConfig {
+    /// Reads the configuration from a JSON file.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Unable to read config file {}", path.display()))?;
+        let config: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Unable to parse config file {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.tokens.is_empty() {
+            bail!("At least one token has to be configured");
+        }
+        let mut ledger_ids = BTreeSet::new();
+        let mut store_files = BTreeSet::new();
+        for token in &self.tokens {
+            if !ledger_ids.insert(token.ledger_id) {
+                bail!("Ledger {} is configured more than once", token.ledger_id);
+            }
+            if let Some(store_file) = &token.store_file {
+                if !store_files.insert(store_file.clone()) {
+                    bail!(
+                        "Store file {} is used by more than one ledger",
+                        store_file.display()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_base_types::PrincipalId;
+
+    fn ledger_id(id: u64) -> CanisterId {
+        CanisterId::try_from(PrincipalId::new_user_test_id(id)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let ledger_1 = ledger_id(1);
+        let ledger_2 = ledger_id(2);
+        let config: MultiTokenConfig = serde_json::from_str(&format!(
+            r#"{{
+                "tokens": [
+                    {{ "ledger_id": "{}" }},
+                    {{ "ledger_id": "{}", "icrc1_symbol": "XTST", "icrc1_decimals": 6, "store_file": "xtst.sqlite" }}
+                ]
+            }}"#,
+            ledger_1, ledger_2
+        ))
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.tokens,
+            vec![
+                TokenConfig::new(ledger_1),
+                TokenConfig {
+                    ledger_id: ledger_2,
+                    icrc1_symbol: Some("XTST".to_string()),
+                    icrc1_decimals: Some(6),
+                    store_file: Some(PathBuf::from("xtst.sqlite")),
+                }
+            ]
+        );
+        assert!(!config.tokens[0].are_metadata_args_set());
+        assert!(config.tokens[1].are_metadata_args_set());
+
+        let store_directory = Path::new("/data");
+        assert_eq!(
+            config.tokens[0].effective_store_file(store_directory),
+            store_directory.join(format!("{}.sqlite", ledger_1))
+        );
+        assert_eq!(
+            config.tokens[1].effective_store_file(store_directory),
+            store_directory.join("xtst.sqlite")
+        );
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(MultiTokenConfig { tokens: vec![] }.validate().is_err());
+
+        let token = TokenConfig::new(ledger_id(1));
+        assert!(MultiTokenConfig {
+            tokens: vec![token.clone(), token]
+        }
+        .validate()
+        .is_err());
+
+        let with_store_file = |id| TokenConfig {
+            store_file: Some(PathBuf::from("db.sqlite")),
+            ..TokenConfig::new(ledger_id(id))
+        };
+        assert!(MultiTokenConfig {
+            tokens: vec![with_store_file(1), with_store_file(2)]
+        }
+        .validate()
+        .is_err());
+    }
+}
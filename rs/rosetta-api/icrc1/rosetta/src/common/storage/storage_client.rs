@@ -104,6 +104,44 @@ impl StorageClient {
         storage_operations::get_transaction_at_idx(&open_connection, block_idx)
     }
 
+    // Gets the blocks that contain a transaction with a certain hash. Returns [] if no such block exists in the database.
+    pub fn get_blocks_by_transaction_hash(
+        &self,
+        hash: ByteBuf,
+    ) -> anyhow::Result<Vec<RosettaBlock>> {
+        let open_connection = self.storage_connection.lock().unwrap();
+        storage_operations::get_blocks_by_transaction_hash(&open_connection, hash)
+    }
+
+    // Gets at most `limit` blocks with an index of at most `max_block_idx` that involve a certain account, skipping the first `offset` matches.
+    // The blocks are sorted from the highest to the lowest index.
+    pub fn get_blocks_by_account(
+        &self,
+        account: &Account,
+        max_block_idx: u64,
+        offset: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<RosettaBlock>> {
+        let open_connection = self.storage_connection.lock().unwrap();
+        storage_operations::get_blocks_by_account(
+            &open_connection,
+            account,
+            max_block_idx,
+            offset,
+            limit,
+        )
+    }
+
+    // Counts the transactions with an index of at most `max_block_idx` that involve a certain account.
+    pub fn get_account_transactions_count(
+        &self,
+        account: &Account,
+        max_block_idx: u64,
+    ) -> anyhow::Result<u64> {
+        let open_connection = self.storage_connection.lock().unwrap();
+        storage_operations::get_account_transactions_count(&open_connection, account, max_block_idx)
+    }
+
     pub fn read_metadata(&self) -> anyhow::Result<Vec<MetadataEntry>> {
         let open_connection = self.storage_connection.lock().unwrap();
         storage_operations::get_metadata(&open_connection)
@@ -180,6 +218,28 @@ impl StorageClient {
             "#,
             [],
         )?;
+        // Indices used to look up the transactions of an account, see `get_blocks_by_account`.
+        open_connection.execute(
+            r#"
+            CREATE INDEX IF NOT EXISTS from_principal_transactions
+            ON transactions(from_principal, block_idx)
+            "#,
+            [],
+        )?;
+        open_connection.execute(
+            r#"
+            CREATE INDEX IF NOT EXISTS to_principal_transactions
+            ON transactions(to_principal, block_idx)
+            "#,
+            [],
+        )?;
+        open_connection.execute(
+            r#"
+            CREATE INDEX IF NOT EXISTS spender_principal_transactions
+            ON transactions(spender_principal, block_idx)
+            "#,
+            [],
+        )?;
 
         Ok(())
     }
@@ -333,6 +393,204 @@ mod tests {
         assert!(storage_client_persistent.is_ok());
     }
 
+    fn test_account(owner: u8, subaccount: Option<[u8; 32]>) -> Account {
+        Account {
+            owner: candid::Principal::from_slice(&[owner]),
+            subaccount,
+        }
+    }
+
+    fn tokens(amount: u64) -> Tokens {
+        Tokens::from(U64::new(amount))
+    }
+
+    // Stores a small blockchain in which every account balance is known in advance and returns the accounts involved.
+    // The account `b_sub` is a subaccount of `b`, which must not be confused with `b` itself.
+    fn store_blocks_with_known_balances(
+        storage_client: &StorageClient,
+    ) -> (Account, Account, Account, Account, Account) {
+        let a = test_account(1, None);
+        let b = test_account(2, None);
+        let c = test_account(3, None);
+        let d = test_account(4, None);
+        let b_sub = test_account(2, Some([1; 32]));
+        let operations = vec![
+            Operation::Mint {
+                to: a,
+                amount: tokens(1000),
+            },
+            Operation::Transfer {
+                from: a,
+                to: b,
+                spender: None,
+                amount: tokens(100),
+                fee: Some(tokens(10)),
+            },
+            Operation::Approve {
+                from: b,
+                spender: c,
+                amount: tokens(50),
+                expected_allowance: None,
+                expires_at: None,
+                fee: Some(tokens(10)),
+            },
+            Operation::Transfer {
+                from: a,
+                to: d,
+                spender: Some(c),
+                amount: tokens(20),
+                fee: Some(tokens(10)),
+            },
+            Operation::Mint {
+                to: b_sub,
+                amount: tokens(5),
+            },
+            Operation::Burn {
+                from: a,
+                spender: None,
+                amount: tokens(30),
+            },
+        ];
+        let mut parent_hash = None;
+        let mut rosetta_blocks = vec![];
+        for (index, operation) in operations.into_iter().enumerate() {
+            let block = Block::<Tokens> {
+                transaction: Transaction {
+                    operation,
+                    created_at_time: None,
+                    memo: None,
+                },
+                parent_hash,
+                effective_fee: None,
+                timestamp: index as u64,
+                fee_collector: None,
+                fee_collector_block_index: None,
+            };
+            parent_hash = Some(Block::<Tokens>::block_hash(&block.clone().encode()));
+            rosetta_blocks.push(RosettaBlock::from_icrc_ledger_block(block, index as u64).unwrap());
+        }
+        storage_client.store_blocks(rosetta_blocks).unwrap();
+        storage_client.update_account_balances().unwrap();
+        (a, b, c, d, b_sub)
+    }
+
+    #[test]
+    fn test_account_balances() {
+        let storage_client_memory = StorageClient::new_in_memory().unwrap();
+        let (a, b, c, d, b_sub) = store_blocks_with_known_balances(&storage_client_memory);
+
+        let balance_at = |account: &Account, block_idx: u64| {
+            storage_client_memory
+                .get_account_balance_at_block_idx(account, block_idx)
+                .unwrap()
+        };
+        assert_eq!(balance_at(&a, 0), Some(tokens(1000)));
+        assert_eq!(balance_at(&a, 1), Some(tokens(890)));
+        assert_eq!(balance_at(&a, 2), Some(tokens(890)));
+        assert_eq!(balance_at(&a, 3), Some(tokens(860)));
+        assert_eq!(balance_at(&b, 0), None);
+        assert_eq!(balance_at(&b, 1), Some(tokens(100)));
+        assert_eq!(balance_at(&b, 2), Some(tokens(90)));
+        assert_eq!(balance_at(&d, 3), Some(tokens(20)));
+        assert_eq!(balance_at(&b_sub, 3), None);
+        assert_eq!(balance_at(&b_sub, 4), Some(tokens(5)));
+
+        // The spender of an approval or a transfer_from never holds any tokens.
+        assert_eq!(storage_client_memory.get_account_balance(&c).unwrap(), None);
+        assert_eq!(
+            storage_client_memory.get_account_balance(&a).unwrap(),
+            Some(tokens(830))
+        );
+        // An explicit default subaccount refers to the same account as no subaccount.
+        assert_eq!(
+            storage_client_memory
+                .get_account_balance(&test_account(1, Some([0; 32])))
+                .unwrap(),
+            Some(tokens(830))
+        );
+        assert_eq!(
+            storage_client_memory.get_account_balance(&b).unwrap(),
+            Some(tokens(90))
+        );
+    }
+
+    #[test]
+    fn test_get_blocks_by_account() {
+        let storage_client_memory = StorageClient::new_in_memory().unwrap();
+        let (a, b, c, d, b_sub) = store_blocks_with_known_balances(&storage_client_memory);
+
+        let block_indices = |account: &Account, max_block_idx: u64, offset: u64, limit: u64| {
+            storage_client_memory
+                .get_blocks_by_account(account, max_block_idx, offset, limit)
+                .unwrap()
+                .into_iter()
+                .map(|block| block.index)
+                .collect::<Vec<u64>>()
+        };
+        let count = |account: &Account, max_block_idx: u64| {
+            storage_client_memory
+                .get_account_transactions_count(account, max_block_idx)
+                .unwrap()
+        };
+
+        // Blocks are returned from the highest to the lowest index.
+        assert_eq!(block_indices(&a, 5, 0, 10), vec![5, 3, 1, 0]);
+        assert_eq!(count(&a, 5), 4);
+        assert_eq!(
+            block_indices(&test_account(1, Some([0; 32])), 5, 0, 10),
+            vec![5, 3, 1, 0]
+        );
+        assert_eq!(block_indices(&b, 5, 0, 10), vec![2, 1]);
+        assert_eq!(count(&b, 5), 2);
+        // The spender takes part in both the approval and the transfer_from.
+        assert_eq!(block_indices(&c, 5, 0, 10), vec![3, 2]);
+        assert_eq!(block_indices(&d, 5, 0, 10), vec![3]);
+        assert_eq!(block_indices(&b_sub, 5, 0, 10), vec![4]);
+        assert_eq!(count(&test_account(5, None), 5), 0);
+
+        // Blocks above the maximum block index are ignored.
+        assert_eq!(block_indices(&a, 2, 0, 10), vec![1, 0]);
+        assert_eq!(count(&a, 2), 2);
+        assert_eq!(count(&b_sub, 3), 0);
+
+        // Pagination skips `offset` matches and returns at most `limit` blocks.
+        assert_eq!(block_indices(&a, 5, 1, 2), vec![3, 1]);
+        assert_eq!(block_indices(&a, 5, 3, 2), vec![0]);
+        assert!(block_indices(&a, 5, 4, 2).is_empty());
+    }
+
+    #[test]
+    fn test_account_lookups_use_indices() {
+        let storage_client_memory = StorageClient::new_in_memory().unwrap();
+        let connection = storage_client_memory.storage_connection.lock().unwrap();
+        let plan: Vec<String> = connection
+            .prepare(&format!(
+                "EXPLAIN QUERY PLAN {} SELECT COUNT(*) FROM account_transactions",
+                storage_operations::ACCOUNT_TRANSACTIONS
+            ))
+            .unwrap()
+            .query_map(
+                rusqlite::named_params! {
+                    ":max_block_idx": 0,
+                    ":principal": [1u8].as_slice(),
+                    ":subaccount": [0u8; 32].as_slice(),
+                    ":default_subaccount": [0u8; 32].as_slice(),
+                },
+                |row| row.get(3),
+            )
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        // None of the lookups may fall back to a scan of the whole transactions table.
+        assert!(
+            plan.iter()
+                .all(|step| !step.starts_with("SCAN transactions")
+                    && !step.starts_with("SCAN TABLE transactions")),
+            "{:?}",
+            plan
+        );
+    }
+
     proptest! {
           #[test]
           fn test_read_and_write_blocks_u64(blockchain in prop::collection::vec(blocks_strategy::<U64>(arb_amount()),0..5)){
@@ -493,6 +493,92 @@ pub fn get_transactions_by_hash(
     read_transactions(&mut stmt, params![hash.as_slice().to_vec()])
 }
 
+// Returns the RosettaBlocks that contain a transaction with the given transaction hash.
+pub fn get_blocks_by_transaction_hash(
+    connection: &Connection,
+    hash: ByteBuf,
+) -> anyhow::Result<Vec<RosettaBlock>> {
+    let mut stmt = connection.prepare(
+        "SELECT blocks.idx,blocks.serialized_block \
+         FROM blocks JOIN transactions ON blocks.idx = transactions.block_idx \
+         WHERE transactions.tx_hash = ?1 \
+         ORDER BY blocks.idx DESC",
+    )?;
+    read_blocks(&mut stmt, params![hash.as_slice().to_vec()])
+}
+
+// The indices of all transactions in which the given account takes part.
+// Each of the three lookups is served by the index on the respective principal column, which
+// is why they are combined with a UNION instead of a single OR over all three columns.
+// Subaccounts are stored as NULL if the transaction did not set them, which means the default subaccount.
+pub(crate) const ACCOUNT_TRANSACTIONS: &str = "WITH account_transactions(block_idx) AS ( \
+     SELECT block_idx FROM transactions \
+     WHERE from_principal = :principal AND block_idx <= :max_block_idx \
+     AND COALESCE(from_subaccount, :default_subaccount) = :subaccount \
+     UNION \
+     SELECT block_idx FROM transactions \
+     WHERE to_principal = :principal AND block_idx <= :max_block_idx \
+     AND COALESCE(to_subaccount, :default_subaccount) = :subaccount \
+     UNION \
+     SELECT block_idx FROM transactions \
+     WHERE spender_principal = :principal AND block_idx <= :max_block_idx \
+     AND COALESCE(spender_subaccount, :default_subaccount) = :subaccount)";
+
+// Returns the RosettaBlocks with an index of at most max_block_idx that contain a transaction involving the given account.
+// The blocks are sorted from the highest to the lowest index.
+pub fn get_blocks_by_account(
+    connection: &Connection,
+    account: &Account,
+    max_block_idx: u64,
+    offset: u64,
+    limit: u64,
+) -> anyhow::Result<Vec<RosettaBlock>> {
+    let command = format!(
+        "{} \
+         SELECT blocks.idx,blocks.serialized_block \
+         FROM blocks JOIN account_transactions ON blocks.idx = account_transactions.block_idx \
+         ORDER BY blocks.idx DESC \
+         LIMIT :limit OFFSET :offset",
+        ACCOUNT_TRANSACTIONS
+    );
+    let mut stmt = connection.prepare_cached(&command)?;
+    read_blocks(
+        &mut stmt,
+        named_params! {
+            ":max_block_idx": max_block_idx,
+            ":principal": account.owner.as_slice(),
+            ":subaccount": account.effective_subaccount().as_slice(),
+            ":default_subaccount": [0u8; 32].as_slice(),
+            ":limit": limit,
+            ":offset": offset,
+        },
+    )
+}
+
+// Returns the number of transactions with an index of at most max_block_idx that involve the given account.
+pub fn get_account_transactions_count(
+    connection: &Connection,
+    account: &Account,
+    max_block_idx: u64,
+) -> anyhow::Result<u64> {
+    let command = format!(
+        "{} SELECT COUNT(*) FROM account_transactions",
+        ACCOUNT_TRANSACTIONS
+    );
+    connection
+        .prepare_cached(&command)?
+        .query_row(
+            named_params! {
+                ":max_block_idx": max_block_idx,
+                ":principal": account.owner.as_slice(),
+                ":subaccount": account.effective_subaccount().as_slice(),
+                ":default_subaccount": [0u8; 32].as_slice(),
+            },
+            |row| row.get(0),
+        )
+        .with_context(|| format!("Unable to count the transactions of account {}", account))
+}
+
 pub fn get_account_balance_at_highest_block_idx(
     connection: &Connection,
     account: &Account,
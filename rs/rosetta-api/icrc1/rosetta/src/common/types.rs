@@ -26,6 +26,7 @@ const ERROR_CODE_PARSING_ERROR: u32 = 7;
 const ERROR_CODE_UNSUPPORTED_OPERATION: u32 = 8;
 const ERROR_CODE_LEDGER_COMMUNICATION: u32 = 9;
 const ERROR_CODE_REQUEST_PROCESSING_ERROR: u32 = 10;
+const ERROR_CODE_PROCESSING_CONSTRUCTION_FAILED: u32 = 11;
 
 impl IntoResponse for Error {
     fn into_response(self) -> axum::response::Response {
@@ -158,6 +159,16 @@ impl Error {
             details: None,
         })
     }
+
+    pub fn processing_construction_failed<T: std::fmt::Debug>(description: &T) -> Self {
+        Self(rosetta_core::miscellaneous::Error {
+            code: ERROR_CODE_PROCESSING_CONSTRUCTION_FAILED,
+            message: "Failed to process construction request.".to_owned(),
+            description: Some(format!("{:?}", description)),
+            retriable: false,
+            details: None,
+        })
+    }
 }
 
 #[derive(Display, Debug, Clone, PartialEq, Eq, EnumIter, EnumString, EnumVariantNames)]
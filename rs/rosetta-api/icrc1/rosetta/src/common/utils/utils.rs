@@ -10,7 +10,7 @@ use crate::{
             TransferMetadata,
         },
     },
-    AppState,
+    AppState, MultiTokenAppState,
 };
 use anyhow::{bail, Context};
 use ic_ledger_core::block::EncodedBlock;
@@ -29,23 +29,29 @@ const BURN_OPERATION_IDENTIFIER: u64 = 0;
 const TRANSFER_OPERATION_IDENTIFIER: u64 = 0;
 const APPROVE_OPERATION_IDENTIFIER: u64 = 0;
 
-pub fn verify_network_id(
+/// Returns the state of the ledger that is identified by the network identifier.
+pub fn get_state_from_network_id(
     network_identifier: &NetworkIdentifier,
-    state: &AppState,
-) -> anyhow::Result<()> {
-    let expected = &NetworkIdentifier::new(
-        DEFAULT_BLOCKCHAIN.to_owned(),
-        state.icrc1_agent.ledger_canister_id.to_string(),
-    );
-
-    if network_identifier != expected {
+    state: &MultiTokenAppState,
+) -> anyhow::Result<Arc<AppState>> {
+    if network_identifier.blockchain != DEFAULT_BLOCKCHAIN {
         bail!(
-            "Network Identifiers did not match: Expected {:?} | Actual {:?}",
-            expected,
+            "Network Identifiers did not match: Expected blockchain {} | Actual {:?}",
+            DEFAULT_BLOCKCHAIN,
             network_identifier
         )
     }
-    Ok(())
+    state
+        .token_states
+        .get(&network_identifier.network)
+        .cloned()
+        .with_context(|| {
+            format!(
+                "Network Identifiers did not match: Expected one of {:?} | Actual {:?}",
+                state.network_identifiers(),
+                network_identifier
+            )
+        })
 }
 
 pub fn convert_timestamp_to_millis(timestamp_nanos: u64) -> anyhow::Result<u64> {
@@ -252,7 +258,18 @@ pub fn icrc1_rosetta_block_to_rosetta_core_operation(
     currency: Currency,
 ) -> anyhow::Result<rosetta_core::objects::Operation> {
     let icrc1_transaction = rosetta_block.get_transaction()?;
-    Ok(match icrc1_transaction.operation {
+    Ok(icrc1_operation_to_rosetta_core_operation(
+        icrc1_transaction.operation,
+        currency,
+    ))
+}
+
+// Converts an icrc1 Operation into an Operation from the rosetta_core crate
+pub fn icrc1_operation_to_rosetta_core_operation(
+    operation: ic_icrc1::Operation<RosettaToken>,
+    currency: Currency,
+) -> rosetta_core::objects::Operation {
+    match operation {
         ic_icrc1::Operation::Mint { to, amount } => {
             // A Mint operation only has one OperationIdentifier and thus no related Operations
             rosetta_core::objects::Operation::new(
@@ -337,7 +354,7 @@ pub fn icrc1_rosetta_block_to_rosetta_core_operation(
                 .into(),
             ),
         ),
-    })
+    }
 }
 
 // Takes in a rosetta_core operation that fully defines an icrc1 Operation
@@ -1,17 +1,18 @@
 use super::services;
+use super::types::ConstructionPayloadsRequestMetadata;
 use crate::{
-    common::{types::Error, utils::utils::verify_network_id},
-    AppState,
+    common::{types::Error, utils::utils::get_state_from_network_id},
+    MultiTokenAppState,
 };
 use axum::{extract::State, response::Result, Json};
 use rosetta_core::{request_types::*, response_types::*};
-use std::sync::Arc;
+use std::{sync::Arc, time::SystemTime};
 
 pub async fn construction_derive(
-    State(state): State<Arc<AppState>>,
+    State(state): State<Arc<MultiTokenAppState>>,
     request: Json<ConstructionDeriveRequest>,
 ) -> Result<Json<ConstructionDeriveResponse>> {
-    verify_network_id(&request.network_identifier, &state)
+    get_state_from_network_id(&request.network_identifier, &state)
         .map_err(|err| Error::invalid_network_id(&err))?;
     Ok(Json(services::construction_derive(
         request.public_key.clone(),
@@ -19,19 +20,19 @@ pub async fn construction_derive(
 }
 
 pub async fn construction_preprocess(
-    State(state): State<Arc<AppState>>,
+    State(state): State<Arc<MultiTokenAppState>>,
     request: Json<ConstructionPreprocessRequest>,
 ) -> Result<Json<ConstructionPreprocessResponse>> {
-    verify_network_id(&request.network_identifier, &state)
+    get_state_from_network_id(&request.network_identifier, &state)
         .map_err(|err| Error::invalid_network_id(&err))?;
     Ok(Json(services::construction_preprocess()))
 }
 
 pub async fn construction_metadata(
-    State(state): State<Arc<AppState>>,
+    State(state): State<Arc<MultiTokenAppState>>,
     request: Json<ConstructionMetadataRequest>,
 ) -> Result<Json<ConstructionMetadataResponse>> {
-    verify_network_id(&request.network_identifier, &state)
+    let state = get_state_from_network_id(&request.network_identifier, &state)
         .map_err(|err| Error::invalid_network_id(&err))?;
     Ok(Json(
         services::construction_metadata(
@@ -46,3 +47,76 @@ pub async fn construction_metadata(
         .await?,
     ))
 }
+
+pub async fn construction_payloads(
+    State(state): State<Arc<MultiTokenAppState>>,
+    request: Json<ConstructionPayloadsRequest>,
+) -> Result<Json<ConstructionPayloadsResponse>> {
+    let state = get_state_from_network_id(&request.network_identifier, &state)
+        .map_err(|err| Error::invalid_network_id(&err))?;
+    Ok(Json(services::construction_payloads(
+        request.operations.clone(),
+        request
+            .metadata
+            .clone()
+            .map(|metadata| {
+                ConstructionPayloadsRequestMetadata::try_from(Some(metadata))
+                    .map_err(|err| Error::parsing_unsuccessful(&err))
+            })
+            .transpose()?,
+        &state.icrc1_agent.ledger_canister_id,
+        request.public_keys.clone().unwrap_or_default(),
+        SystemTime::now(),
+    )?))
+}
+
+pub async fn construction_combine(
+    State(state): State<Arc<MultiTokenAppState>>,
+    request: Json<ConstructionCombineRequest>,
+) -> Result<Json<ConstructionCombineResponse>> {
+    get_state_from_network_id(&request.network_identifier, &state)
+        .map_err(|err| Error::invalid_network_id(&err))?;
+    Ok(Json(services::construction_combine(
+        request.unsigned_transaction.clone(),
+        request.signatures.clone(),
+    )?))
+}
+
+pub async fn construction_parse(
+    State(state): State<Arc<MultiTokenAppState>>,
+    request: Json<ConstructionParseRequest>,
+) -> Result<Json<ConstructionParseResponse>> {
+    let state = get_state_from_network_id(&request.network_identifier, &state)
+        .map_err(|err| Error::invalid_network_id(&err))?;
+    Ok(Json(services::construction_parse(
+        request.transaction.clone(),
+        request.signed,
+        state.metadata.clone().into(),
+    )?))
+}
+
+pub async fn construction_hash(
+    State(state): State<Arc<MultiTokenAppState>>,
+    request: Json<ConstructionHashRequest>,
+) -> Result<Json<ConstructionHashResponse>> {
+    get_state_from_network_id(&request.network_identifier, &state)
+        .map_err(|err| Error::invalid_network_id(&err))?;
+    Ok(Json(services::construction_hash(
+        request.signed_transaction.clone(),
+    )?))
+}
+
+pub async fn construction_submit(
+    State(state): State<Arc<MultiTokenAppState>>,
+    request: Json<ConstructionSubmitRequest>,
+) -> Result<Json<ConstructionSubmitResponse>> {
+    let state = get_state_from_network_id(&request.network_identifier, &state)
+        .map_err(|err| Error::invalid_network_id(&err))?;
+    Ok(Json(
+        services::construction_submit(
+            request.signed_transaction.clone(),
+            state.icrc1_agent.clone(),
+        )
+        .await?,
+    ))
+}
@@ -1,3 +1,4 @@
 pub mod endpoints;
 pub mod services;
 pub mod types;
+pub mod utils;
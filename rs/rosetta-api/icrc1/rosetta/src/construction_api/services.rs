@@ -1,15 +1,57 @@
-use super::types::ConstructionMetadataRequestOptions;
+use super::types::{
+    CanisterMethodName, ConstructionMetadataRequestOptions, ConstructionPayloadsRequestMetadata,
+    SignedTransaction, UnsignedTransaction,
+};
+use super::utils::{
+    decode_update, der_encoded_public_key, parse_metadata, signature_type_from_curve_type,
+    LedgerCallArgs,
+};
 use crate::common::types::Error;
+use crate::common::utils::utils::{
+    icrc1_operation_to_rosetta_core_operation, rosetta_core_operation_to_icrc1_operation,
+};
+use candid::{Decode, Nat, Principal};
+use ic_agent::agent::RequestStatusResponse;
 use ic_base_types::PrincipalId;
+use ic_ledger_canister_core::ledger::LedgerTransaction;
+use ic_rosetta_api::convert::make_read_state_from_update;
+use ic_rosetta_api::models::EnvelopePair;
+use ic_rosetta_api::request_handler::make_sig_data;
+use ic_types::messages::{
+    Blob, HttpCallContent, HttpCanisterUpdate, HttpReadStateContent, HttpRequestEnvelope,
+    MessageId, SignedRequestBytes,
+};
 use icrc_ledger_agent::{CallMode, Icrc1Agent};
 use icrc_ledger_types::icrc1::account::Account;
-use rosetta_core::objects::{Amount, Currency};
+use icrc_ledger_types::icrc1::transfer::{Memo, TransferError};
+use icrc_ledger_types::icrc2::approve::ApproveError;
+use icrc_ledger_types::icrc2::transfer_from::TransferFromError;
+use rosetta_core::identifiers::TransactionIdentifier;
+use rosetta_core::objects::{Amount, Currency, Operation, Signature, SigningPayload};
 use rosetta_core::response_types::*;
 use rosetta_core::{
     convert::principal_id_from_public_key, objects::PublicKey,
     response_types::ConstructionDeriveResponse,
 };
+use serde_bytes::ByteBuf;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// The time during which an update can be submitted after it has been signed.
+// Every ingress window results in a separate envelope.
+const INGRESS_INTERVAL: Duration = Duration::from_secs(
+    ic_constants::MAX_INGRESS_TTL.as_secs() - ic_constants::PERMITTED_DRIFT.as_secs() - 120,
+);
+
+// The time between the start of an ingress window and the expiry of the envelopes for that window.
+const INGRESS_EXPIRY_OFFSET: Duration = Duration::from_secs(
+    ic_constants::MAX_INGRESS_TTL.as_secs() - ic_constants::PERMITTED_DRIFT.as_secs(),
+);
+
+const SUBMIT_TIMEOUT: Duration = Duration::from_secs(20);
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 pub fn construction_derive(public_key: PublicKey) -> Result<ConstructionDeriveResponse, Error> {
     let principal_id: PrincipalId = principal_id_from_public_key(&public_key)
@@ -51,10 +93,361 @@ pub async fn construction_metadata(
     })
 }
 
+pub fn construction_payloads(
+    operations: Vec<Operation>,
+    metadata: Option<ConstructionPayloadsRequestMetadata>,
+    ledger_id: &Principal,
+    public_keys: Vec<PublicKey>,
+    now: SystemTime,
+) -> Result<ConstructionPayloadsResponse, Error> {
+    let operation = match <[Operation; 1]>::try_from(operations) {
+        Ok([operation]) => rosetta_core_operation_to_icrc1_operation(operation)
+            .map_err(|err| Error::parsing_unsuccessful(&err))?,
+        Err(operations) => {
+            return Err(Error::processing_construction_failed(&format!(
+                "Expected exactly one operation but got {}",
+                operations.len()
+            )))
+        }
+    };
+
+    let metadata = metadata.unwrap_or_default();
+    let now = now
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| Error::processing_construction_failed(&err))?
+        .as_nanos() as u64;
+    let ingress_start = metadata.ingress_start.unwrap_or(now);
+    let ingress_end = metadata
+        .ingress_end
+        .unwrap_or_else(|| ingress_start + INGRESS_INTERVAL.as_nanos() as u64);
+    if ingress_end <= ingress_start {
+        return Err(Error::processing_construction_failed(
+            &"The ingress end has to be after the ingress start",
+        ));
+    }
+    let created_at_time = metadata.created_at_time.unwrap_or(now);
+
+    let (caller, args) = LedgerCallArgs::from_icrc1_operation(
+        operation,
+        metadata.memo.map(Memo),
+        Some(created_at_time),
+    )
+    .map_err(|err| Error::processing_construction_failed(&err))?;
+
+    let public_key = public_keys
+        .into_iter()
+        .find(|pk| {
+            principal_id_from_public_key(pk)
+                .map(|principal_id| principal_id.0 == caller)
+                .unwrap_or(false)
+        })
+        .ok_or_else(|| {
+            Error::processing_construction_failed(&format!(
+                "No public key was provided for the principal {}",
+                caller
+            ))
+        })?;
+    let signature_type = signature_type_from_curve_type(public_key.curve_type)
+        .map_err(|err| Error::processing_construction_failed(&err))?;
+
+    let mut ingress_expiries = vec![];
+    let mut window_start = ingress_start;
+    while window_start < ingress_end {
+        ingress_expiries.push(window_start + INGRESS_EXPIRY_OFFSET.as_nanos() as u64);
+        window_start += INGRESS_INTERVAL.as_nanos() as u64;
+    }
+
+    let update = HttpCanisterUpdate {
+        canister_id: Blob(ledger_id.as_slice().to_vec()),
+        method_name: args.method_name().to_string(),
+        arg: Blob(
+            args.encode()
+                .map_err(|err| Error::processing_construction_failed(&err))?,
+        ),
+        sender: Blob(caller.as_slice().to_vec()),
+        ingress_expiry: ingress_expiries[0],
+        // The ledger deduplicates transactions through the created_at_time, so no nonce is needed.
+        nonce: None,
+    };
+
+    let account_identifier = Account::from(caller).into();
+    let mut payloads = vec![];
+    for ingress_expiry in &ingress_expiries {
+        let mut update = update.clone();
+        update.ingress_expiry = *ingress_expiry;
+        let read_state = make_read_state_from_update(&update);
+        for message_id in [
+            update.id(),
+            MessageId::from(read_state.representation_independent_hash()),
+        ] {
+            payloads.push(SigningPayload {
+                address: None,
+                account_identifier: Some(account_identifier.clone()),
+                hex_bytes: hex::encode(make_sig_data(&message_id)),
+                signature_type: Some(signature_type),
+            });
+        }
+    }
+
+    Ok(ConstructionPayloadsResponse::new(
+        UnsignedTransaction {
+            update,
+            ingress_expiries,
+        }
+        .to_string(),
+        payloads,
+    ))
+}
+
+pub fn construction_combine(
+    unsigned_transaction: String,
+    signatures: Vec<Signature>,
+) -> Result<ConstructionCombineResponse, Error> {
+    let unsigned_transaction: UnsignedTransaction = unsigned_transaction
+        .parse()
+        .map_err(|err| Error::parsing_unsuccessful(&err))?;
+
+    let signatures_by_sig_data: HashMap<String, Signature> = signatures
+        .into_iter()
+        .map(|signature| (signature.signing_payload.hex_bytes.clone(), signature))
+        .collect();
+    let find_signature = |message_id: &MessageId| {
+        signatures_by_sig_data
+            .get(&hex::encode(make_sig_data(message_id)))
+            .ok_or_else(|| {
+                Error::processing_construction_failed(&format!(
+                    "Could not find signature for message {}",
+                    message_id
+                ))
+            })
+    };
+    let envelope_sender = |signature: &Signature| {
+        let sender_pubkey = der_encoded_public_key(&signature.public_key, signature.signature_type)
+            .map_err(|err| Error::processing_construction_failed(&err))?;
+        let sender_sig =
+            hex::decode(&signature.hex_bytes).map_err(|err| Error::parsing_unsuccessful(&err))?;
+        Ok::<_, Error>((Some(sender_pubkey), Some(Blob(sender_sig))))
+    };
+
+    let mut envelope_pairs = vec![];
+    for ingress_expiry in unsigned_transaction.ingress_expiries {
+        let mut update = unsigned_transaction.update.clone();
+        update.ingress_expiry = ingress_expiry;
+        let read_state = make_read_state_from_update(&update);
+
+        let (sender_pubkey, sender_sig) = envelope_sender(find_signature(&update.id())?)?;
+        let update_envelope = HttpRequestEnvelope::<HttpCallContent> {
+            content: HttpCallContent::Call { update },
+            sender_pubkey,
+            sender_sig,
+            sender_delegation: None,
+        };
+
+        let (sender_pubkey, sender_sig) = envelope_sender(find_signature(&MessageId::from(
+            read_state.representation_independent_hash(),
+        ))?)?;
+        let read_state_envelope = HttpRequestEnvelope::<HttpReadStateContent> {
+            content: HttpReadStateContent::ReadState { read_state },
+            sender_pubkey,
+            sender_sig,
+            sender_delegation: None,
+        };
+
+        envelope_pairs.push(EnvelopePair {
+            update: update_envelope,
+            read_state: read_state_envelope,
+        });
+    }
+
+    Ok(ConstructionCombineResponse {
+        signed_transaction: SignedTransaction { envelope_pairs }.to_string(),
+    })
+}
+
+pub fn construction_parse(
+    transaction: String,
+    signed: bool,
+    currency: Currency,
+) -> Result<ConstructionParseResponse, Error> {
+    let (update, ingress_expiries) = if signed {
+        let signed_transaction: SignedTransaction = transaction
+            .parse()
+            .map_err(|err| Error::parsing_unsuccessful(&err))?;
+        let ingress_expiries = signed_transaction
+            .envelope_pairs
+            .iter()
+            .map(|pair| pair.update_content().ingress_expiry)
+            .collect::<Vec<_>>();
+        let update = signed_transaction
+            .envelope_pairs
+            .first()
+            .map(|pair| pair.update_content().clone())
+            .ok_or_else(|| Error::parsing_unsuccessful(&"The signed transaction is empty"))?;
+        (update, ingress_expiries)
+    } else {
+        let unsigned_transaction: UnsignedTransaction = transaction
+            .parse()
+            .map_err(|err| Error::parsing_unsuccessful(&err))?;
+        (
+            unsigned_transaction.update,
+            unsigned_transaction.ingress_expiries,
+        )
+    };
+
+    let (caller, args) = decode_update(&update).map_err(|err| Error::parsing_unsuccessful(&err))?;
+    let transaction = args
+        .to_icrc1_transaction(caller)
+        .map_err(|err| Error::parsing_unsuccessful(&err))?;
+
+    Ok(ConstructionParseResponse {
+        operations: vec![icrc1_operation_to_rosetta_core_operation(
+            transaction.operation,
+            currency,
+        )],
+        account_identifier_signers: signed.then(|| vec![Account::from(caller).into()]),
+        metadata: Some(parse_metadata(&args, &ingress_expiries, INGRESS_EXPIRY_OFFSET).into()),
+    })
+}
+
+pub fn construction_hash(signed_transaction: String) -> Result<ConstructionHashResponse, Error> {
+    let signed_transaction: SignedTransaction = signed_transaction
+        .parse()
+        .map_err(|err| Error::parsing_unsuccessful(&err))?;
+    Ok(ConstructionHashResponse {
+        transaction_identifier: transaction_identifier(&signed_transaction)?,
+        metadata: serde_json::map::Map::new(),
+    })
+}
+
+fn transaction_identifier(
+    signed_transaction: &SignedTransaction,
+) -> Result<TransactionIdentifier, Error> {
+    let update = signed_transaction
+        .envelope_pairs
+        .first()
+        .map(|pair| pair.update_content())
+        .ok_or_else(|| Error::parsing_unsuccessful(&"The signed transaction is empty"))?;
+    let (caller, args) = decode_update(update).map_err(|err| Error::parsing_unsuccessful(&err))?;
+    let transaction = args
+        .to_icrc1_transaction(caller)
+        .map_err(|err| Error::parsing_unsuccessful(&err))?;
+    Ok(TransactionIdentifier::from_bytes(&ByteBuf::from(
+        transaction.hash().as_slice().to_vec(),
+    )))
+}
+
+pub async fn construction_submit(
+    signed_transaction: String,
+    icrc1_agent: Arc<Icrc1Agent>,
+) -> Result<ConstructionSubmitResponse, Error> {
+    let signed_transaction: SignedTransaction = signed_transaction
+        .parse()
+        .map_err(|err| Error::parsing_unsuccessful(&err))?;
+    let transaction_identifier = transaction_identifier(&signed_transaction)?;
+
+    // Pick the envelope pair whose ingress window contains the current time.
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| Error::processing_construction_failed(&err))?
+        .as_nanos() as u64;
+    let EnvelopePair { update, read_state } = signed_transaction
+        .envelope_pairs
+        .into_iter()
+        .find(|pair| {
+            let ingress_expiry = pair.update_content().ingress_expiry;
+            ingress_expiry.saturating_sub(INGRESS_EXPIRY_OFFSET.as_nanos() as u64) <= now
+                && now < ingress_expiry
+        })
+        .ok_or_else(|| {
+            Error::processing_construction_failed(
+                &"The signed transaction is not valid at the current time",
+            )
+        })?;
+    let method_name = match &update.content {
+        HttpCallContent::Call { update } => update.method_name.clone(),
+    };
+
+    let ledger_id = icrc1_agent.ledger_canister_id;
+    let update_bytes = SignedRequestBytes::try_from(update)
+        .map_err(|err| Error::processing_construction_failed(&err))?;
+    let read_state_bytes = SignedRequestBytes::try_from(read_state)
+        .map_err(|err| Error::processing_construction_failed(&err))?;
+
+    let request_id = icrc1_agent
+        .agent
+        .update_signed(ledger_id, update_bytes.into())
+        .await
+        .map_err(|err| Error::ledger_communication_unsuccessful(&err))?;
+
+    let deadline = Instant::now() + SUBMIT_TIMEOUT;
+    let mut poll_interval = MIN_POLL_INTERVAL;
+    let reply = loop {
+        if Instant::now() + poll_interval > deadline {
+            return Err(Error::ledger_communication_unsuccessful(&format!(
+                "Transaction {} did not complete within {:?}",
+                transaction_identifier.hash, SUBMIT_TIMEOUT
+            )));
+        }
+        tokio::time::sleep(poll_interval).await;
+        poll_interval = (poll_interval * 2).min(MAX_POLL_INTERVAL);
+
+        match icrc1_agent
+            .agent
+            .request_status_signed(&request_id, ledger_id, read_state_bytes.clone().into())
+            .await
+            .map_err(|err| Error::ledger_communication_unsuccessful(&err))?
+        {
+            RequestStatusResponse::Replied(reply) => break reply.arg,
+            RequestStatusResponse::Rejected(reject) => {
+                return Err(Error::ledger_communication_unsuccessful(&reject))
+            }
+            RequestStatusResponse::Done => {
+                return Err(Error::ledger_communication_unsuccessful(
+                    &"The call has completed but the reply has been pruned",
+                ))
+            }
+            RequestStatusResponse::Unknown
+            | RequestStatusResponse::Received
+            | RequestStatusResponse::Processing => continue,
+        }
+    };
+
+    let block_index = decode_ledger_reply(&method_name, &reply)?;
+    let mut metadata = serde_json::map::Map::new();
+    metadata.insert(
+        "block_index".to_owned(),
+        serde_json::Value::String(block_index.to_string()),
+    );
+    Ok(ConstructionSubmitResponse {
+        transaction_identifier,
+        metadata: Some(metadata),
+    })
+}
+
+// Decodes the reply of the ledger and returns the index of the created block.
+fn decode_ledger_reply(method_name: &str, reply: &[u8]) -> Result<Nat, Error> {
+    let result = match method_name
+        .parse::<CanisterMethodName>()
+        .map_err(|err| Error::parsing_unsuccessful(&err))?
+    {
+        CanisterMethodName::Icrc1Transfer => Decode!(reply, Result<Nat, TransferError>)
+            .map(|res| res.map_err(|e| format!("{:?}", e))),
+        CanisterMethodName::Icrc2Approve => {
+            Decode!(reply, Result<Nat, ApproveError>).map(|res| res.map_err(|e| format!("{:?}", e)))
+        }
+        CanisterMethodName::Icrc2TransferFrom => Decode!(reply, Result<Nat, TransferFromError>)
+            .map(|res| res.map_err(|e| format!("{:?}", e))),
+    }
+    .map_err(|err| Error::parsing_unsuccessful(&err))?;
+    result.map_err(|err| Error::ledger_communication_unsuccessful(&err))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::storage::types::RosettaToken;
     use ic_canister_client_sender::{Ed25519KeyPair, Secp256k1KeyPair};
+    use ic_icrc1_test_utils::account_strategy;
     use proptest::prelude::any;
     use proptest::proptest;
     use rosetta_core::models::RosettaSupportedKeyPair;
@@ -78,7 +471,113 @@ mod tests {
         );
     }
 
+    fn call_construction_roundtrip<T: RosettaSupportedKeyPair>(
+        key_pair: &T,
+        to: Account,
+        amount: u64,
+    ) {
+        let currency = Currency::new("XTST".to_owned(), 8);
+        let ledger_id = Principal::from_slice(&[1, 2, 3]);
+        let from = Account {
+            owner: key_pair.generate_principal_id().unwrap().into(),
+            subaccount: None,
+        };
+        let operation = icrc1_operation_to_rosetta_core_operation(
+            ic_icrc1::Operation::Transfer {
+                from,
+                to,
+                spender: None,
+                amount: RosettaToken::from(ic_icrc1_tokens_u64::U64::new(amount)),
+                fee: None,
+            },
+            currency.clone(),
+        );
+        let metadata = ConstructionPayloadsRequestMetadata {
+            memo: Some(ByteBuf::from(vec![1, 2, 3])),
+            created_at_time: Some(1_000),
+            ingress_start: None,
+            ingress_end: None,
+        };
+        let now = SystemTime::now();
+
+        let payloads = construction_payloads(
+            vec![operation.clone()],
+            Some(metadata.clone()),
+            &ledger_id,
+            vec![ic_rosetta_test_utils::to_public_key(key_pair)],
+            now,
+        )
+        .unwrap();
+        // One update and one read-state payload per ingress window.
+        assert_eq!(payloads.payloads.len(), 2);
+
+        let unsigned_parse = construction_parse(
+            payloads.unsigned_transaction.clone(),
+            false,
+            currency.clone(),
+        )
+        .unwrap();
+        assert_eq!(unsigned_parse.operations, vec![operation.clone()]);
+        assert_eq!(unsigned_parse.account_identifier_signers, None);
+
+        let signatures = payloads
+            .payloads
+            .iter()
+            .map(|payload| Signature {
+                signing_payload: payload.clone(),
+                public_key: ic_rosetta_test_utils::to_public_key(key_pair),
+                signature_type: payload.signature_type.unwrap(),
+                hex_bytes: hex::encode(key_pair.sign(&hex::decode(&payload.hex_bytes).unwrap())),
+            })
+            .collect();
+        let combined = construction_combine(payloads.unsigned_transaction, signatures).unwrap();
+
+        let signed_parse =
+            construction_parse(combined.signed_transaction.clone(), true, currency).unwrap();
+        assert_eq!(signed_parse.operations, vec![operation]);
+        assert_eq!(
+            signed_parse.account_identifier_signers,
+            Some(vec![from.into()])
+        );
+        let parsed_metadata =
+            ConstructionPayloadsRequestMetadata::try_from(signed_parse.metadata).unwrap();
+        assert_eq!(parsed_metadata.memo, metadata.memo);
+        assert_eq!(parsed_metadata.created_at_time, metadata.created_at_time);
+
+        let expected_transaction = ic_icrc1::Transaction::<RosettaToken> {
+            operation: ic_icrc1::Operation::Transfer {
+                from,
+                to,
+                spender: None,
+                amount: RosettaToken::from(ic_icrc1_tokens_u64::U64::new(amount)),
+                fee: None,
+            },
+            created_at_time: metadata.created_at_time,
+            memo: metadata.memo.map(Memo),
+        };
+        assert_eq!(
+            construction_hash(combined.signed_transaction)
+                .unwrap()
+                .transaction_identifier,
+            TransactionIdentifier::from_bytes(&ByteBuf::from(
+                expected_transaction.hash().as_slice().to_vec()
+            ))
+        );
+    }
+
     proptest! {
+        #[test]
+        fn test_construction_roundtrip_ed(seed in any::<u64>(), to in account_strategy(), amount in any::<u64>()) {
+            let key_pair = Ed25519KeyPair::generate_from_u64(seed);
+            call_construction_roundtrip(&key_pair, to, amount);
+        }
+
+        #[test]
+        fn test_construction_roundtrip_secp(seed in any::<u64>(), to in account_strategy(), amount in any::<u64>()) {
+            let key_pair = Secp256k1KeyPair::generate_from_u64(seed);
+            call_construction_roundtrip(&key_pair, to, amount);
+        }
+
         #[test]
         fn test_construction_derive_ed(seed in any::<u64>()) {
             let key_pair = Ed25519KeyPair::generate_from_u64(seed);
@@ -1,6 +1,12 @@
+use anyhow::Context;
+use ic_rosetta_api::models::EnvelopePair;
+use ic_types::messages::HttpCanisterUpdate;
 use rosetta_core::objects::*;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_bytes::ByteBuf;
+use std::str::FromStr;
+use strum_macros::{Display, EnumString};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ConstructionMetadataRequestOptions {
@@ -23,3 +29,103 @@ impl TryFrom<Option<ObjectMap>> for ConstructionMetadataRequestOptions {
             .map_err(|e| format!("Could not parse MetadataOptions from JSON object: {}", e))
     }
 }
+
+/// The metadata that can be passed to `/construction/payloads`.
+/// All the timestamps are in nanoseconds since the UNIX epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct ConstructionPayloadsRequestMetadata {
+    /// The memo that is attached to the ledger transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<ByteBuf>,
+
+    /// The time at which the ledger transaction was created. It is used
+    /// by the ledger for deduplication.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at_time: Option<u64>,
+
+    /// The earliest time at which the transaction can be submitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingress_start: Option<u64>,
+
+    /// The latest time at which the transaction can be submitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingress_end: Option<u64>,
+}
+
+impl From<ConstructionPayloadsRequestMetadata> for ObjectMap {
+    fn from(m: ConstructionPayloadsRequestMetadata) -> Self {
+        match serde_json::to_value(m) {
+            Ok(serde_json::Value::Object(o)) => o,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl TryFrom<Option<ObjectMap>> for ConstructionPayloadsRequestMetadata {
+    type Error = anyhow::Error;
+    fn try_from(o: Option<ObjectMap>) -> anyhow::Result<Self> {
+        serde_json::from_value(serde_json::Value::Object(o.unwrap_or_default()))
+            .context("Could not parse ConstructionPayloadsRequestMetadata from JSON object")
+    }
+}
+
+/// The ledger endpoints that can be called through the construction API.
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+pub enum CanisterMethodName {
+    Icrc1Transfer,
+    Icrc2Approve,
+    Icrc2TransferFrom,
+}
+
+/// An unsigned ledger call.
+/// The `update` is signed once for every entry in `ingress_expiries` so that
+/// the signed transaction can be submitted at any point in the ingress window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub update: HttpCanisterUpdate,
+    pub ingress_expiries: Vec<u64>,
+}
+
+impl std::fmt::Display for UnsignedTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(serde_cbor::to_vec(self).unwrap()))
+    }
+}
+
+impl FromStr for UnsignedTransaction {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_cbor::from_slice(
+            hex::decode(s)
+                .context("Could not hex decode the unsigned transaction")?
+                .as_slice(),
+        )
+        .context("Could not CBOR decode the unsigned transaction")
+    }
+}
+
+/// A signed ledger call.
+/// There is one update/read-state envelope pair for every ingress window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    pub envelope_pairs: Vec<EnvelopePair>,
+}
+
+impl std::fmt::Display for SignedTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(serde_cbor::to_vec(self).unwrap()))
+    }
+}
+
+impl FromStr for SignedTransaction {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_cbor::from_slice(
+            hex::decode(s)
+                .context("Could not hex decode the signed transaction")?
+                .as_slice(),
+        )
+        .context("Could not CBOR decode the signed transaction")
+    }
+}
@@ -0,0 +1,299 @@
+use super::types::{CanisterMethodName, ConstructionPayloadsRequestMetadata};
+use crate::common::storage::types::RosettaToken;
+use anyhow::{bail, Context};
+use candid::{Decode, Encode, Nat, Principal};
+use ic_canister_client_sender::{Ed25519KeyPair as EdKeypair, Secp256k1KeyPair};
+use ic_icrc1::{Operation, Transaction};
+use ic_types::messages::{Blob, HttpCanisterUpdate};
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::{Memo, TransferArg};
+use icrc_ledger_types::icrc2::approve::ApproveArgs;
+use icrc_ledger_types::icrc2::transfer_from::TransferFromArgs;
+use rosetta_core::models::RosettaSupportedKeyPair;
+use rosetta_core::objects::{CurveType, PublicKey, SignatureType};
+
+/// The arguments of a call to one of the ledger endpoints supported by the construction API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerCallArgs {
+    Icrc1Transfer(TransferArg),
+    Icrc2Approve(ApproveArgs),
+    Icrc2TransferFrom(TransferFromArgs),
+}
+
+impl LedgerCallArgs {
+    pub fn method_name(&self) -> CanisterMethodName {
+        match self {
+            Self::Icrc1Transfer(_) => CanisterMethodName::Icrc1Transfer,
+            Self::Icrc2Approve(_) => CanisterMethodName::Icrc2Approve,
+            Self::Icrc2TransferFrom(_) => CanisterMethodName::Icrc2TransferFrom,
+        }
+    }
+
+    pub fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(match self {
+            Self::Icrc1Transfer(arg) => Encode!(arg)?,
+            Self::Icrc2Approve(arg) => Encode!(arg)?,
+            Self::Icrc2TransferFrom(arg) => Encode!(arg)?,
+        })
+    }
+
+    pub fn decode(method_name: &str, arg: &[u8]) -> anyhow::Result<Self> {
+        let method_name = method_name
+            .parse::<CanisterMethodName>()
+            .with_context(|| format!("Method {} is not supported", method_name))?;
+        Ok(match method_name {
+            CanisterMethodName::Icrc1Transfer => Self::Icrc1Transfer(Decode!(arg, TransferArg)?),
+            CanisterMethodName::Icrc2Approve => Self::Icrc2Approve(Decode!(arg, ApproveArgs)?),
+            CanisterMethodName::Icrc2TransferFrom => {
+                Self::Icrc2TransferFrom(Decode!(arg, TransferFromArgs)?)
+            }
+        })
+    }
+
+    /// Builds the ledger call for an icrc1 Operation.
+    /// Returns the principal that has to sign the call together with the call arguments.
+    pub fn from_icrc1_operation(
+        operation: Operation<RosettaToken>,
+        memo: Option<Memo>,
+        created_at_time: Option<u64>,
+    ) -> anyhow::Result<(Principal, Self)> {
+        Ok(match operation {
+            Operation::Transfer {
+                from,
+                to,
+                spender: None,
+                amount,
+                fee,
+            } => (
+                from.owner,
+                Self::Icrc1Transfer(TransferArg {
+                    from_subaccount: from.subaccount,
+                    to,
+                    fee: fee.map(Nat::from),
+                    created_at_time,
+                    memo,
+                    amount: amount.into(),
+                }),
+            ),
+            Operation::Transfer {
+                from,
+                to,
+                spender: Some(spender),
+                amount,
+                fee,
+            } => (
+                spender.owner,
+                Self::Icrc2TransferFrom(TransferFromArgs {
+                    spender_subaccount: spender.subaccount,
+                    from,
+                    to,
+                    amount: amount.into(),
+                    fee: fee.map(Nat::from),
+                    memo,
+                    created_at_time,
+                }),
+            ),
+            Operation::Approve {
+                from,
+                spender,
+                amount,
+                expected_allowance,
+                expires_at,
+                fee,
+            } => (
+                from.owner,
+                Self::Icrc2Approve(ApproveArgs {
+                    from_subaccount: from.subaccount,
+                    spender,
+                    amount: amount.into(),
+                    expected_allowance: expected_allowance.map(Nat::from),
+                    expires_at,
+                    fee: fee.map(Nat::from),
+                    memo,
+                    created_at_time,
+                }),
+            ),
+            Operation::Mint { .. } => bail!("Mint operations cannot be constructed"),
+            Operation::Burn { .. } => bail!("Burn operations cannot be constructed"),
+        })
+    }
+
+    /// Reconstructs the icrc1 Transaction that the ledger will create when it executes the call.
+    pub fn to_icrc1_transaction(
+        &self,
+        caller: Principal,
+    ) -> anyhow::Result<Transaction<RosettaToken>> {
+        let to_tokens = |n: Nat| RosettaToken::try_from(n).map_err(anyhow::Error::msg);
+        Ok(match self.clone() {
+            Self::Icrc1Transfer(arg) => Transaction {
+                operation: Operation::Transfer {
+                    from: Account {
+                        owner: caller,
+                        subaccount: arg.from_subaccount,
+                    },
+                    to: arg.to,
+                    spender: None,
+                    amount: to_tokens(arg.amount)?,
+                    fee: arg.fee.map(to_tokens).transpose()?,
+                },
+                created_at_time: arg.created_at_time,
+                memo: arg.memo,
+            },
+            Self::Icrc2Approve(arg) => Transaction {
+                operation: Operation::Approve {
+                    from: Account {
+                        owner: caller,
+                        subaccount: arg.from_subaccount,
+                    },
+                    spender: arg.spender,
+                    amount: to_tokens(arg.amount)?,
+                    expected_allowance: arg.expected_allowance.map(to_tokens).transpose()?,
+                    expires_at: arg.expires_at,
+                    fee: arg.fee.map(to_tokens).transpose()?,
+                },
+                created_at_time: arg.created_at_time,
+                memo: arg.memo,
+            },
+            Self::Icrc2TransferFrom(arg) => Transaction {
+                operation: Operation::Transfer {
+                    from: arg.from,
+                    to: arg.to,
+                    spender: Some(Account {
+                        owner: caller,
+                        subaccount: arg.spender_subaccount,
+                    }),
+                    amount: to_tokens(arg.amount)?,
+                    fee: arg.fee.map(to_tokens).transpose()?,
+                },
+                created_at_time: arg.created_at_time,
+                memo: arg.memo,
+            },
+        })
+    }
+
+    pub fn memo(&self) -> Option<Memo> {
+        match self {
+            Self::Icrc1Transfer(arg) => arg.memo.clone(),
+            Self::Icrc2Approve(arg) => arg.memo.clone(),
+            Self::Icrc2TransferFrom(arg) => arg.memo.clone(),
+        }
+    }
+
+    pub fn created_at_time(&self) -> Option<u64> {
+        match self {
+            Self::Icrc1Transfer(arg) => arg.created_at_time,
+            Self::Icrc2Approve(arg) => arg.created_at_time,
+            Self::Icrc2TransferFrom(arg) => arg.created_at_time,
+        }
+    }
+}
+
+/// Decodes the caller and the ledger call of an update.
+pub fn decode_update(update: &HttpCanisterUpdate) -> anyhow::Result<(Principal, LedgerCallArgs)> {
+    let caller = Principal::try_from_slice(update.sender.0.as_slice())
+        .context("Could not decode the sender of the update")?;
+    let args = LedgerCallArgs::decode(&update.method_name, update.arg.0.as_slice())?;
+    Ok((caller, args))
+}
+
+/// Returns the metadata that `/construction/parse` reports for an update in
+/// the given ingress window.
+pub fn parse_metadata(
+    args: &LedgerCallArgs,
+    ingress_expiries: &[u64],
+    ingress_window: std::time::Duration,
+) -> ConstructionPayloadsRequestMetadata {
+    ConstructionPayloadsRequestMetadata {
+        memo: args.memo().map(|memo| memo.0),
+        created_at_time: args.created_at_time(),
+        ingress_start: ingress_expiries
+            .first()
+            .map(|expiry| expiry.saturating_sub(ingress_window.as_nanos() as u64)),
+        ingress_end: ingress_expiries.last().copied(),
+    }
+}
+
+pub fn signature_type_from_curve_type(curve_type: CurveType) -> anyhow::Result<SignatureType> {
+    match curve_type {
+        CurveType::Edwards25519 => Ok(SignatureType::Ed25519),
+        CurveType::Secp256K1 => Ok(SignatureType::Ecdsa),
+        _ => bail!("Curve Type {} is not supported", curve_type),
+    }
+}
+
+/// Returns the DER encoded public key that the IC expects as the sender of an envelope.
+pub fn der_encoded_public_key(
+    public_key: &PublicKey,
+    signature_type: SignatureType,
+) -> anyhow::Result<Blob> {
+    Ok(Blob(match signature_type {
+        SignatureType::Ed25519 => {
+            EdKeypair::der_encode_pk(EdKeypair::hex_decode_pk(&public_key.hex_bytes)?)?
+        }
+        SignatureType::Ecdsa => Secp256k1KeyPair::der_encode_pk(Secp256k1KeyPair::hex_decode_pk(
+            &public_key.hex_bytes,
+        )?)?,
+        _ => bail!("Signature Type {} is not supported", signature_type),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_icrc1_test_utils::{account_strategy, arb_amount};
+    use ic_ledger_canister_core::ledger::LedgerTransaction;
+    use proptest::prelude::*;
+    use serde_bytes::ByteBuf;
+
+    fn arb_memo() -> impl Strategy<Value = Option<Memo>> {
+        proptest::option::of(
+            proptest::collection::vec(any::<u8>(), 0..32).prop_map(|m| Memo(ByteBuf::from(m))),
+        )
+    }
+
+    fn arb_operation() -> impl Strategy<Value = Operation<RosettaToken>> {
+        (
+            account_strategy(),
+            account_strategy(),
+            proptest::option::of(account_strategy()),
+            arb_amount::<RosettaToken>(),
+            proptest::option::of(arb_amount::<RosettaToken>()),
+            proptest::option::of(any::<u64>()),
+            any::<bool>(),
+        )
+            .prop_map(|(from, to, spender, amount, fee, expires_at, approve)| {
+                if approve {
+                    Operation::Approve {
+                        from,
+                        spender: to,
+                        amount,
+                        expected_allowance: None,
+                        expires_at,
+                        fee,
+                    }
+                } else {
+                    Operation::Transfer {
+                        from,
+                        to,
+                        spender,
+                        amount,
+                        fee,
+                    }
+                }
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_ledger_call_roundtrip(operation in arb_operation(), memo in arb_memo(), created_at_time in proptest::option::of(any::<u64>())) {
+            let (caller, args) = LedgerCallArgs::from_icrc1_operation(operation.clone(), memo.clone(), created_at_time).unwrap();
+            let decoded = LedgerCallArgs::decode(&args.method_name().to_string(), &args.encode().unwrap()).unwrap();
+            prop_assert_eq!(&decoded, &args);
+
+            let transaction = decoded.to_icrc1_transaction(caller).unwrap();
+            let expected = Transaction { operation, created_at_time, memo };
+            prop_assert_eq!(transaction.hash(), expected.hash());
+            prop_assert_eq!(transaction, expected);
+        }
+    }
+}
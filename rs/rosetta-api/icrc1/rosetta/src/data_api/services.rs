@@ -1,6 +1,6 @@
 use crate::{
     common::{
-        constants::{NODE_VERSION, ROSETTA_VERSION},
+        constants::{DEFAULT_BLOCKCHAIN, NODE_VERSION, ROSETTA_VERSION},
         storage::storage_client::StorageClient,
         types::Error,
         utils::utils::{
@@ -13,16 +13,21 @@ use crate::{
     Metadata,
 };
 use candid::Principal;
-use ic_rosetta_api::DEFAULT_BLOCKCHAIN;
-use rosetta_core::{identifiers::*, miscellaneous::Version, objects::*, response_types::*};
+use ic_icrc1::Operation;
+use icrc_ledger_types::icrc1::account::Account;
+use rosetta_core::{
+    identifiers::*, miscellaneous::Version, objects::*, request_types::SearchTransactionsRequest,
+    response_types::*,
+};
+use serde_bytes::ByteBuf;
 use std::sync::Arc;
 
-pub fn network_list(ledger_id: &Principal) -> NetworkListResponse {
+// The maximum number of transactions returned by a single call to /search/transactions.
+const MAX_TRANSACTIONS_PER_SEARCH_TRANSACTIONS_REQUEST: u64 = 10000;
+
+pub fn network_list(network_identifiers: Vec<NetworkIdentifier>) -> NetworkListResponse {
     NetworkListResponse {
-        network_identifiers: vec![NetworkIdentifier::new(
-            DEFAULT_BLOCKCHAIN.to_owned(),
-            ledger_id.to_string(),
-        )],
+        network_identifiers,
     }
 }
 
@@ -128,6 +133,180 @@ pub fn block(
     )))
 }
 
+// Returns true if the account is the sender, the receiver or the spender of the operation.
+fn operation_involves_account<Tokens>(operation: &Operation<Tokens>, account: &Account) -> bool {
+    match operation {
+        Operation::Mint { to, .. } => to == account,
+        Operation::Burn { from, spender, .. } => {
+            from == account || spender.as_ref() == Some(account)
+        }
+        Operation::Transfer {
+            from, to, spender, ..
+        } => from == account || to == account || spender.as_ref() == Some(account),
+        Operation::Approve { from, spender, .. } => from == account || spender == account,
+    }
+}
+
+pub fn account_balance(
+    storage_client: Arc<StorageClient>,
+    account_identifier: AccountIdentifier,
+    partial_block_identifier: Option<PartialBlockIdentifier>,
+    metadata: Metadata,
+) -> Result<AccountBalanceResponse, Error> {
+    let account: Account = account_identifier
+        .try_into()
+        .map_err(|err| Error::parsing_unsuccessful(&err))?;
+
+    let rosetta_block = match partial_block_identifier {
+        Some(partial_block_identifier) => get_rosetta_block_from_partial_block_identifier(
+            partial_block_identifier,
+            storage_client.clone(),
+        )
+        .map_err(|err| Error::invalid_block_identifier(&err))?,
+        None => storage_client
+            .get_block_with_highest_block_idx()
+            .map_err(|e| Error::unable_to_find_block(&e))?
+            .ok_or_else(|| Error::unable_to_find_block(&"Current block not found".to_owned()))?,
+    };
+
+    let balance = storage_client
+        .get_account_balance_at_block_idx(&account, rosetta_block.index)
+        .map_err(|e| Error::request_processing_error(&e))?
+        .unwrap_or_default();
+
+    Ok(AccountBalanceResponse::new(
+        rosetta_block.get_block_identifier(),
+        vec![Amount::new(
+            balance.to_string(),
+            Currency {
+                symbol: metadata.symbol,
+                decimals: metadata.decimals.into(),
+                ..Default::default()
+            },
+        )],
+    ))
+}
+
+pub fn search_transactions(
+    storage_client: Arc<StorageClient>,
+    request: SearchTransactionsRequest,
+    metadata: Metadata,
+) -> Result<SearchTransactionsResponse, Error> {
+    if request.coin_identifier.is_some() {
+        return Err(Error::request_processing_error(
+            &"Searching by coin identifier is not supported",
+        ));
+    }
+    if request.transaction_identifier.is_none() && request.account_identifier.is_none() {
+        return Err(Error::request_processing_error(
+            &"Either a transaction identifier or an account identifier has to be provided",
+        ));
+    }
+    if request.operator.is_some() && request.operator != Some(Operator::And) {
+        return Err(Error::request_processing_error(
+            &"Only the operator 'and' is supported",
+        ));
+    }
+
+    let max_block = match request.max_block {
+        Some(max_block) => u64::try_from(max_block).map_err(|_| {
+            Error::request_processing_error(&"Max block has to be a positive number")
+        })?,
+        None => {
+            storage_client
+                .get_block_with_highest_block_idx()
+                .map_err(|e| Error::unable_to_find_block(&e))?
+                .ok_or_else(|| Error::unable_to_find_block(&"Current block not found".to_owned()))?
+                .index
+        }
+    };
+    let offset = u64::try_from(request.offset.unwrap_or(0))
+        .map_err(|_| Error::request_processing_error(&"Offset has to be a positive number"))?;
+    let limit = u64::try_from(
+        request
+            .limit
+            .unwrap_or(MAX_TRANSACTIONS_PER_SEARCH_TRANSACTIONS_REQUEST as i64),
+    )
+    .map_err(|_| Error::request_processing_error(&"Limit has to be a positive number"))?
+    .min(MAX_TRANSACTIONS_PER_SEARCH_TRANSACTIONS_REQUEST);
+
+    let account: Option<Account> = request
+        .account_identifier
+        .map(|account_identifier| account_identifier.try_into())
+        .transpose()
+        .map_err(|err| Error::parsing_unsuccessful(&err))?;
+
+    let (rosetta_blocks, total_count) = match request.transaction_identifier {
+        Some(transaction_identifier) => {
+            let hash = hex::decode(&transaction_identifier.hash).map_err(|err| {
+                Error::request_processing_error(&format!(
+                    "Invalid transaction hash {}: {}",
+                    transaction_identifier.hash, err
+                ))
+            })?;
+            let rosetta_blocks: Vec<_> = storage_client
+                .get_blocks_by_transaction_hash(ByteBuf::from(hash))
+                .map_err(|e| Error::unable_to_find_block(&e))?
+                .into_iter()
+                .filter(|rosetta_block| rosetta_block.index <= max_block)
+                .filter(|rosetta_block| match &account {
+                    Some(account) => rosetta_block
+                        .get_transaction()
+                        .map(|transaction| {
+                            operation_involves_account(&transaction.operation, account)
+                        })
+                        .unwrap_or(false),
+                    None => true,
+                })
+                .collect();
+            let total_count = rosetta_blocks.len() as u64;
+            (
+                rosetta_blocks
+                    .into_iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .collect(),
+                total_count,
+            )
+        }
+        None => {
+            // The check above guarantees that the account is set if there is no transaction identifier.
+            let account = account.unwrap();
+            (
+                storage_client
+                    .get_blocks_by_account(&account, max_block, offset, limit)
+                    .map_err(|e| Error::unable_to_find_block(&e))?,
+                storage_client
+                    .get_account_transactions_count(&account, max_block)
+                    .map_err(|e| Error::unable_to_find_block(&e))?,
+            )
+        }
+    };
+
+    let currency = Currency {
+        symbol: metadata.symbol,
+        decimals: metadata.decimals.into(),
+        ..Default::default()
+    };
+    let next_offset = offset + rosetta_blocks.len() as u64;
+    let transactions = rosetta_blocks
+        .into_iter()
+        .map(|rosetta_block| {
+            Ok(BlockTransaction::new(
+                rosetta_block.get_block_identifier(),
+                icrc1_rosetta_block_to_rosetta_core_transaction(rosetta_block, currency.clone())?,
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| Error::failed_to_build_block_response(&e))?;
+
+    Ok(SearchTransactionsResponse::new(
+        transactions,
+        total_count as i64,
+        (next_offset < total_count).then_some(next_offset as i64),
+    ))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1,11 +1,16 @@
 use anyhow::{bail, Context};
+use common::constants::DEFAULT_BLOCKCHAIN;
 use common::storage::{storage_client::StorageClient, types::MetadataEntry};
 use ic_base_types::CanisterId;
 use icrc_ledger_agent::Icrc1Agent;
 use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue;
 use num_traits::ToPrimitive;
+use rosetta_core::identifiers::NetworkIdentifier;
 use rosetta_core::objects::Currency;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 pub mod common;
 pub mod construction_api;
@@ -19,6 +24,37 @@ pub struct AppState {
     pub metadata: Metadata,
 }
 
+impl AppState {
+    pub fn network_identifier(&self) -> NetworkIdentifier {
+        NetworkIdentifier::new(DEFAULT_BLOCKCHAIN.to_owned(), self.ledger_id.to_string())
+    }
+}
+
+/// The state of a Rosetta node that serves several ledgers.
+/// The states of the single ledgers are indexed by the textual representation of their ledger id,
+/// which is also the `network` of their `NetworkIdentifier`.
+pub struct MultiTokenAppState {
+    pub token_states: BTreeMap<String, Arc<AppState>>,
+}
+
+impl MultiTokenAppState {
+    pub fn new(token_states: Vec<Arc<AppState>>) -> Self {
+        Self {
+            token_states: token_states
+                .into_iter()
+                .map(|state| (state.ledger_id.to_string(), state))
+                .collect(),
+        }
+    }
+
+    pub fn network_identifiers(&self) -> Vec<NetworkIdentifier> {
+        self.token_states
+            .values()
+            .map(|state| state.network_identifier())
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Metadata {
     pub symbol: String,
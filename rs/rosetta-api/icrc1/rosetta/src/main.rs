@@ -12,11 +12,12 @@ use ic_agent::{
 };
 use ic_base_types::CanisterId;
 use ic_icrc_rosetta::{
+    common::config::{MultiTokenConfig, TokenConfig},
     common::storage::{storage_client::StorageClient, types::MetadataEntry},
     construction_api::endpoints::*,
     data_api::endpoints::*,
     ledger_blocks_synchronization::blocks_synchronizer::start_synching_blocks,
-    AppState, Metadata,
+    AppState, Metadata, MultiTokenAppState,
 };
 use icrc_ledger_agent::{CallMode, Icrc1Agent};
 use lazy_static::lazy_static;
@@ -49,8 +50,14 @@ enum NetworkType {
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    /// The ledger to serve. Either this or [multi_tokens_config_file] has to be set.
     #[arg(short, long)]
-    ledger_id: CanisterId,
+    ledger_id: Option<CanisterId>,
+
+    /// A JSON file that configures the ledgers to serve.
+    /// Every ledger is served under its own network identifier and is synced into its own store.
+    #[arg(long)]
+    multi_tokens_config_file: Option<PathBuf>,
 
     #[arg(long)]
     icrc1_symbol: Option<String>,
@@ -76,6 +83,10 @@ struct Args {
     #[arg(short = 'f', long, default_value = "db.sqlite")]
     store_file: PathBuf,
 
+    /// The directory of the stores of the ledgers configured in [multi_tokens_config_file] if [store_type] is file.
+    #[arg(long, default_value = ".")]
+    multi_tokens_store_dir: PathBuf,
+
     /// The network type that rosetta connects to.
     #[arg(short = 'n', long, value_enum)]
     network_type: NetworkType,
@@ -124,8 +135,28 @@ impl Args {
         })
     }
 
-    fn are_metadata_args_set(&self) -> bool {
-        self.icrc1_symbol.is_some() && self.icrc1_decimals.is_some()
+    /// Returns the configurations of the ledgers to serve.
+    fn token_configs(&self) -> Result<Vec<TokenConfig>> {
+        match (&self.ledger_id, &self.multi_tokens_config_file) {
+            (Some(ledger_id), None) => Ok(vec![TokenConfig {
+                ledger_id: *ledger_id,
+                icrc1_symbol: self.icrc1_symbol.clone(),
+                icrc1_decimals: self.icrc1_decimals,
+                store_file: Some(self.store_file.clone()),
+            }]),
+            (None, Some(config_file)) => {
+                if self.icrc1_symbol.is_some() || self.icrc1_decimals.is_some() {
+                    bail!("The ICRC-1 metadata of the ledgers in a multi tokens config file has to be set in the config file.");
+                }
+                Ok(MultiTokenConfig::from_file(config_file)?.tokens)
+            }
+            (Some(_), Some(_)) => {
+                bail!("Only one of --ledger-id and --multi-tokens-config-file can be set.")
+            }
+            (None, None) => {
+                bail!("Either --ledger-id or --multi-tokens-config-file has to be set.")
+            }
+        }
     }
 }
 
@@ -164,30 +195,31 @@ fn add_request_span() -> FnTraceLayer {
 }
 
 async fn load_metadata(
-    args: &Args,
+    token_config: &TokenConfig,
+    offline: bool,
     icrc1_agent: &Icrc1Agent,
     storage: &StorageClient,
 ) -> anyhow::Result<Metadata> {
-    if args.offline {
+    if offline {
         let db_metadata_entries = storage.read_metadata()?;
         // If metadata is empty and the args are not set, bail out.
-        if db_metadata_entries.is_empty() && !args.are_metadata_args_set() {
+        if db_metadata_entries.is_empty() && !token_config.are_metadata_args_set() {
             bail!("Metadata must be initialized by starting Rosetta in online mode first or by providing ICRC-1 metadata arguments.");
         }
 
         // If metadata is set in args and not entries are found in the database,
         // return the metadata from the args.
-        if args.are_metadata_args_set() && db_metadata_entries.is_empty() {
+        if token_config.are_metadata_args_set() && db_metadata_entries.is_empty() {
             return Ok(Metadata::from_args(
-                args.icrc1_symbol.clone().unwrap(),
-                args.icrc1_decimals.unwrap(),
+                token_config.icrc1_symbol.clone().unwrap(),
+                token_config.icrc1_decimals.unwrap(),
             ));
         }
 
         // Populate a metadata object with the database entries.
         let db_metadata = Metadata::from_metadata_entries(&db_metadata_entries)?;
         // If the metadata args are not set, return using the db metadata.
-        if !args.are_metadata_args_set() {
+        if !token_config.are_metadata_args_set() {
             return Ok(db_metadata);
         }
 
@@ -247,10 +279,7 @@ async fn main() -> Result<()> {
 
     init_logs(args.log_level);
 
-    let storage = Arc::new(match args.store_type {
-        StoreType::InMemory => StorageClient::new_in_memory()?,
-        StoreType::File => StorageClient::new_persistent(&args.store_file)?,
-    });
+    let token_configs = args.token_configs()?;
 
     let network_url = args.effective_network_url();
 
@@ -275,37 +304,77 @@ async fn main() -> Result<()> {
         ic_agent.status().await?.replica_health_status
     );
 
-    let icrc1_agent = Arc::new(Icrc1Agent {
-        agent: ic_agent,
-        ledger_canister_id: args.ledger_id.into(),
-    });
-
-    if !args.offline {
-        info!("Starting to sync blocks");
-        start_synching_blocks(
-            icrc1_agent.clone(),
-            storage.clone(),
-            *MAXIMUM_BLOCKS_PER_REQUEST,
-        )
-        .await?;
+    let mut tokens = vec![];
+    for token_config in token_configs {
+        let storage = Arc::new(match args.store_type {
+            StoreType::InMemory => StorageClient::new_in_memory()?,
+            StoreType::File => StorageClient::new_persistent(
+                &token_config.effective_store_file(&args.multi_tokens_store_dir),
+            )?,
+        });
+        let icrc1_agent = Arc::new(Icrc1Agent {
+            agent: ic_agent.clone(),
+            ledger_canister_id: token_config.ledger_id.into(),
+        });
+        tokens.push((token_config, icrc1_agent, storage));
     }
 
-    info!("Starting to update account balances");
-    // Once the entire blockchain has been synched and no gaps remain, the account_balance table can be updated
-    storage.update_account_balances()?;
+    // Every ledger is synced by its own task.
+    let sync_tasks: Vec<_> = tokens
+        .iter()
+        .map(|(token_config, icrc1_agent, storage)| {
+            let ledger_id = token_config.ledger_id;
+            let icrc1_agent = icrc1_agent.clone();
+            let storage = storage.clone();
+            let offline = args.offline;
+            tokio::spawn(async move {
+                if !offline {
+                    info!("Starting to sync blocks of ledger {}", ledger_id);
+                    start_synching_blocks(
+                        icrc1_agent,
+                        storage.clone(),
+                        *MAXIMUM_BLOCKS_PER_REQUEST,
+                    )
+                    .await
+                    .with_context(|| format!("Failed to sync blocks of ledger {}", ledger_id))?;
+                }
+
+                info!(
+                    "Starting to update account balances of ledger {}",
+                    ledger_id
+                );
+                // Once the entire blockchain has been synched and no gaps remain, the account_balance table can be updated
+                storage.update_account_balances()
+            })
+        })
+        .collect();
+    for sync_task in sync_tasks {
+        sync_task.await??;
+    }
 
     // If the option of exiting after the synchronization is completed is set we can exit rosetta
     if args.exit_on_sync {
         process::exit(0);
     }
 
-    let metadata = load_metadata(&args, &icrc1_agent, &storage).await?;
-    let shared_state = Arc::new(AppState {
-        icrc1_agent,
-        ledger_id: args.ledger_id,
-        storage: storage.clone(),
-        metadata,
-    });
+    let mut token_states = vec![];
+    for (token_config, icrc1_agent, storage) in tokens {
+        let metadata = load_metadata(&token_config, args.offline, &icrc1_agent, &storage)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to load the metadata of ledger {}",
+                    token_config.ledger_id
+                )
+            })?;
+        token_states.push(Arc::new(AppState {
+            icrc1_agent,
+            ledger_id: token_config.ledger_id,
+            storage,
+            metadata,
+        }));
+    }
+    let shared_state = Arc::new(MultiTokenAppState::new(token_states));
 
     let app = Router::new()
         .route("/health", get(health))
@@ -316,9 +385,16 @@ async fn main() -> Result<()> {
         .route("/block/transaction", post(block_transaction))
         .route("/mempool", post(mempool))
         .route("/mempool/transaction", post(mempool_transaction))
+        .route("/account/balance", post(account_balance))
+        .route("/search/transactions", post(search_transactions))
         .route("/construction/derive", post(construction_derive))
         .route("/construction/preprocess", post(construction_preprocess))
         .route("/construction/metadata", post(construction_metadata))
+        .route("/construction/payloads", post(construction_payloads))
+        .route("/construction/parse", post(construction_parse))
+        .route("/construction/combine", post(construction_combine))
+        .route("/construction/hash", post(construction_hash))
+        .route("/construction/submit", post(construction_submit))
         // This layer creates a span for each http request and attaches
         // the request_id, HTTP Method and path to it.
         .layer(add_request_span())
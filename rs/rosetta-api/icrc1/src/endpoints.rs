@@ -3,7 +3,7 @@ use candid::types::number::Nat;
 use candid::CandidType;
 use ic_ledger_canister_core::ledger::TransferError as CoreTransferError;
 use ic_ledger_core::tokens::TokensType;
-use icrc_ledger_types::icrc1::transfer::TransferError;
+use icrc_ledger_types::icrc1::transfer::{TransferArg, TransferError};
 use icrc_ledger_types::icrc2::approve::ApproveError;
 use icrc_ledger_types::icrc2::transfer_from::TransferFromError;
 use icrc_ledger_types::icrc3::transactions::{Approve, Burn, Mint, Transaction, Transfer};
@@ -147,6 +147,17 @@ pub struct StandardRecord {
     pub url: String,
 }
 
+/// The argument of the non-standard `batch_transfer` endpoint.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchTransferArgs {
+    /// The transfers to execute. Every transfer is sent from an account of the caller.
+    pub transfers: Vec<TransferArg>,
+    /// If true then either all the transfers are applied or the call is rejected and
+    /// none of them is applied. If false then every transfer is applied independently
+    /// and the result of each transfer is reported separately.
+    pub atomic: bool,
+}
+
 // Non-standard queries
 
 impl<Tokens: TokensType> From<Block<Tokens>> for Transaction {
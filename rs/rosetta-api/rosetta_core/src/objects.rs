@@ -286,6 +286,50 @@ impl Transaction {
     }
 }
 
+/// BlockTransaction contains a populated Transaction and the BlockIdentifier
+/// that contains it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "conversion", derive(LabelledGeneric))]
+pub struct BlockTransaction {
+    /// The block_identifier uniquely identifies a block in a particular network.
+    pub block_identifier: BlockIdentifier,
+
+    /// Transactions contain an array of Operations that are attributable to the same TransactionIdentifier.
+    pub transaction: Transaction,
+}
+
+impl BlockTransaction {
+    pub fn new(block_identifier: BlockIdentifier, transaction: Transaction) -> BlockTransaction {
+        BlockTransaction {
+            block_identifier,
+            transaction,
+        }
+    }
+}
+
+/// Operator is used by query-related endpoints to determine how to apply
+/// conditions. If this field is not populated, the default and value will be
+/// used.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "conversion", derive(LabelledGenericEnum))]
+pub enum Operator {
+    #[serde(rename = "or")]
+    Or,
+    #[serde(rename = "and")]
+    And,
+}
+
+impl ::std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match *self {
+            Operator::Or => write!(f, "or"),
+            Operator::And => write!(f, "and"),
+        }
+    }
+}
+
 // Amount is some Value of a Currency. It is considered invalid to specify a
 /// Value without a Currency.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
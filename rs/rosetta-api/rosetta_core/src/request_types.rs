@@ -36,6 +36,40 @@ impl NetworkRequest {
     }
 }
 
+/// An AccountBalanceRequest is utilized to make a balance request on the
+/// /account/balance endpoint. If the block_identifier is populated, a
+/// historical balance query should be performed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "conversion", derive(LabelledGeneric))]
+pub struct AccountBalanceRequest {
+    /// The network_identifier specifies which network a particular object is associated with.
+    pub network_identifier: NetworkIdentifier,
+
+    /// The account_identifier uniquely identifies an account within a network.
+    pub account_identifier: AccountIdentifier,
+
+    /// When fetching data by BlockIdentifier, it may be possible to only specify the index or hash. If neither property is specified, it is assumed that the client is making a request at the current block.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_identifier: Option<PartialBlockIdentifier>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ObjectMap>,
+}
+
+impl AccountBalanceRequest {
+    pub fn new(
+        network_identifier: NetworkIdentifier,
+        account_identifier: AccountIdentifier,
+    ) -> AccountBalanceRequest {
+        AccountBalanceRequest {
+            network_identifier,
+            account_identifier,
+            block_identifier: None,
+            metadata: None,
+        }
+    }
+}
+
 /// A BlockRequest is utilized to make a block request on the /block endpoint.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "conversion", derive(LabelledGeneric))]
@@ -307,3 +341,85 @@ pub struct ConstructionHashRequest {
 
     pub signed_transaction: String,
 }
+
+/// SearchTransactionsRequest is used to search for transactions matching a set
+/// of provided conditions in canonical blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "conversion", derive(LabelledGeneric))]
+pub struct SearchTransactionsRequest {
+    pub network_identifier: NetworkIdentifier,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator: Option<Operator>,
+
+    /// The max_block is the largest block index to consider when searching for
+    /// transactions. If this field is not populated, the current block is
+    /// considered the max_block.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_block: Option<i64>,
+
+    /// Offset is the offset into the query result to start returning
+    /// transactions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+
+    /// Limit is the maximum number of transactions to return in one call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_identifier: Option<TransactionIdentifier>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_identifier: Option<AccountIdentifier>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coin_identifier: Option<CoinIdentifier>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+
+    /// Status is the network-specific operation status.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    /// Type is the network-specific operation type.
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub _type: Option<String>,
+
+    /// Address is AccountIdentifier.Address. This is used to get all
+    /// transactions related to an AccountIdentifier.Address, regardless of
+    /// SubAccountIdentifier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+
+    /// Success is a synthetic condition populated by parsing network-specific
+    /// operation statuses (using the mapping provided in `/network/options`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+}
+
+impl SearchTransactionsRequest {
+    pub fn new(
+        network_identifier: NetworkIdentifier,
+        transaction_identifier: Option<TransactionIdentifier>,
+        account_identifier: Option<AccountIdentifier>,
+    ) -> SearchTransactionsRequest {
+        SearchTransactionsRequest {
+            network_identifier,
+            operator: None,
+            max_block: None,
+            offset: None,
+            limit: None,
+            transaction_identifier,
+            account_identifier,
+            coin_identifier: None,
+            currency: None,
+            status: None,
+            _type: None,
+            address: None,
+            success: None,
+        }
+    }
+}
@@ -125,6 +125,36 @@ impl BlockResponse {
     }
 }
 
+/// An AccountBalanceResponse is returned on the /account/balance endpoint. If
+/// an account has a balance for each AccountIdentifier describing it (ex: an
+/// ERC-20 token balance on a few smart contracts), an account balance request
+/// must be made with each AccountIdentifier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "conversion", derive(LabelledGeneric))]
+pub struct AccountBalanceResponse {
+    /// The block_identifier uniquely identifies a block in a particular network.
+    pub block_identifier: BlockIdentifier,
+
+    /// A single account may have a balance in multiple currencies.
+    pub balances: Vec<Amount>,
+
+    /// Account-based blockchains that utilize a nonce or sequence number should
+    /// include that number in the metadata. This number could be unique to the
+    /// identifier or global across the account address.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ObjectMap>,
+}
+
+impl AccountBalanceResponse {
+    pub fn new(block_identifier: BlockIdentifier, balances: Vec<Amount>) -> AccountBalanceResponse {
+        AccountBalanceResponse {
+            block_identifier,
+            balances,
+            metadata: None,
+        }
+    }
+}
+
 /// A BlockTransactionResponse contains information about a block transaction.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "conversion", derive(LabelledGeneric))]
@@ -327,3 +357,34 @@ pub struct ConstructionHashResponse {
     pub transaction_identifier: TransactionIdentifier,
     pub metadata: ObjectMap,
 }
+
+/// SearchTransactionsResponse contains an ordered collection of
+/// BlockTransactions that match the query in SearchTransactionsRequest. These
+/// BlockTransactions are sorted from most recent block to oldest block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "conversion", derive(LabelledGeneric))]
+pub struct SearchTransactionsResponse {
+    pub transactions: Vec<BlockTransaction>,
+
+    /// The total number of transactions that match the query.
+    pub total_count: i64,
+
+    /// Used to paginate results. If this is populated, pass this value as the
+    /// offset of the next request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<i64>,
+}
+
+impl SearchTransactionsResponse {
+    pub fn new(
+        transactions: Vec<BlockTransaction>,
+        total_count: i64,
+        next_offset: Option<i64>,
+    ) -> SearchTransactionsResponse {
+        SearchTransactionsResponse {
+            transactions,
+            total_count,
+            next_offset,
+        }
+    }
+}
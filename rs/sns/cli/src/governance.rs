@@ -0,0 +1,655 @@
+use crate::{call_or_exit, Canister, Request};
+use clap::Parser;
+use ic_base_types::PrincipalId;
+use ic_nervous_system_humanize::{format_tokens, parse_tokens};
+use ic_nervous_system_proto::pb::v1::Tokens;
+use ic_sns_governance::pb::v1::{
+    get_neuron_response, get_proposal_response,
+    manage_neuron::{self, disburse::Amount, Disburse, Follow, RegisterVote, Split},
+    manage_neuron_response::{self, MakeProposalResponse},
+    proposal::Action,
+    Account, DeregisterDappCanisters, GetNeuron, GetNeuronResponse, GetProposal,
+    GetProposalResponse, ListNeurons, ListNeuronsResponse, ListProposals, ListProposalsResponse,
+    ManageNeuron, ManageNeuronResponse, Neuron, NeuronId, Proposal, ProposalData, ProposalId,
+    RegisterDappCanisters, Vote,
+};
+use std::{
+    process::exit,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(test)]
+mod governance_tests;
+
+#[derive(Debug, Parser)]
+pub struct GovernanceArgs {
+    /// The network to deploy to. This can be "local", "ic", or the URL of an IC network.
+    #[structopt(default_value = "local", long)]
+    network: String,
+
+    /// The canister ID or name (via dfx.json) of the SNS governance canister.
+    #[clap(default_value = "sns_governance", long)]
+    governance_canister: String,
+
+    #[clap(subcommand)]
+    sub_command: SubCommand,
+}
+
+#[derive(Debug, Parser)]
+enum SubCommand {
+    /// List the neurons of the SNS.
+    ListNeurons(ListNeuronsArgs),
+    /// Show the details of a neuron.
+    GetNeuron(NeuronIdArgs),
+    /// List the most recent proposals of the SNS.
+    ListProposals(ListProposalsArgs),
+    /// Show the details of a proposal, including the rendering of its payload.
+    GetProposal(ProposalIdArgs),
+    /// Vote on a proposal with a neuron that the current dfx identity controls.
+    Vote(VoteArgs),
+    /// Make a neuron follow other neurons on proposals of a given type.
+    Follow(FollowArgs),
+    /// Split off part of a neuron's stake into a new neuron.
+    SplitNeuron(SplitNeuronArgs),
+    /// Disburse the stake of a dissolved neuron to an account.
+    Disburse(DisburseArgs),
+    /// Submit a proposal to register dapp canisters with the SNS.
+    RegisterDappCanisters(DappCanistersProposalArgs),
+    /// Submit a proposal to deregister dapp canisters from the SNS, handing
+    /// control of them to new controllers.
+    DeregisterDappCanisters(DeregisterDappCanistersArgs),
+}
+
+#[derive(Debug, Parser)]
+struct ListNeuronsArgs {
+    /// Only list neurons on which this principal has permissions.
+    #[clap(long)]
+    of_principal: Option<PrincipalId>,
+
+    /// The maximum number of neurons to list.
+    #[clap(default_value = "50", long)]
+    limit: u32,
+}
+
+#[derive(Debug, Parser)]
+struct NeuronIdArgs {
+    /// The ID of the neuron, in hex.
+    #[clap(long)]
+    neuron_id: NeuronId,
+}
+
+#[derive(Debug, Parser)]
+struct ListProposalsArgs {
+    /// Only list proposals with an ID lower than this one.
+    #[clap(long)]
+    before_proposal: Option<u64>,
+
+    /// The maximum number of proposals to list.
+    #[clap(default_value = "20", long)]
+    limit: u32,
+}
+
+#[derive(Debug, Parser)]
+struct ProposalIdArgs {
+    /// The ID of the proposal.
+    #[clap(long)]
+    proposal_id: u64,
+}
+
+#[derive(Debug, Parser)]
+struct VoteArgs {
+    /// The ID of the voting neuron, in hex.
+    #[clap(long)]
+    neuron_id: NeuronId,
+
+    /// The ID of the proposal to vote on.
+    #[clap(long)]
+    proposal_id: u64,
+
+    /// The vote to cast, either "yes" or "no".
+    #[clap(long, parse(try_from_str = parse_vote))]
+    vote: Vote,
+}
+
+#[derive(Debug, Parser)]
+struct FollowArgs {
+    /// The ID of the following neuron, in hex.
+    #[clap(long)]
+    neuron_id: NeuronId,
+
+    /// The type of proposals to follow on. This is the ID of a native or of a
+    /// generic nervous system function, as listed by the SNS governance
+    /// canister's list_nervous_system_functions method.
+    #[clap(long)]
+    function_id: u64,
+
+    /// The neurons to follow, in hex. If none are given, the neuron's followees
+    /// for this function are removed.
+    #[clap(long = "followee", multiple_occurrences(true))]
+    followees: Vec<NeuronId>,
+}
+
+#[derive(Debug, Parser)]
+struct SplitNeuronArgs {
+    /// The ID of the neuron to split, in hex.
+    #[clap(long)]
+    neuron_id: NeuronId,
+
+    /// The stake of the new neuron, e.g. "10 tokens" or "100_000 e8s".
+    #[clap(long, parse(try_from_str = parse_tokens))]
+    amount: Tokens,
+
+    /// The memo from which the ID of the new neuron is derived. It must not be
+    /// in use by another neuron of the current dfx identity.
+    #[clap(long)]
+    memo: u64,
+}
+
+#[derive(Debug, Parser)]
+struct DisburseArgs {
+    /// The ID of the neuron to disburse, in hex. The neuron must be dissolved.
+    #[clap(long)]
+    neuron_id: NeuronId,
+
+    /// The amount to disburse, e.g. "10 tokens" or "100_000 e8s". If not
+    /// given, the whole stake is disbursed.
+    #[clap(long, parse(try_from_str = parse_tokens))]
+    amount: Option<Tokens>,
+
+    /// The owner of the account to disburse to. If not given, the stake is
+    /// disbursed to the current dfx identity.
+    #[clap(long)]
+    to_principal: Option<PrincipalId>,
+}
+
+#[derive(Debug, Parser)]
+struct ProposalTextArgs {
+    /// The ID of the neuron that makes the proposal, in hex.
+    #[clap(long)]
+    neuron_id: NeuronId,
+
+    /// The title of the proposal.
+    #[clap(long)]
+    title: String,
+
+    /// The summary of the proposal, in markdown.
+    #[clap(long)]
+    summary: String,
+
+    /// A URL with more information about the proposal.
+    #[clap(default_value = "", long)]
+    url: String,
+}
+
+#[derive(Debug, Parser)]
+struct DappCanistersProposalArgs {
+    #[clap(flatten)]
+    proposal: ProposalTextArgs,
+
+    /// The dapp canisters. SNS root must already be one of their controllers.
+    #[clap(name = "CANISTER", multiple_values(true), required = true)]
+    canisters: Vec<PrincipalId>,
+}
+
+#[derive(Debug, Parser)]
+struct DeregisterDappCanistersArgs {
+    #[clap(flatten)]
+    proposal: ProposalTextArgs,
+
+    /// The principals that control the canisters once they are deregistered.
+    #[clap(long = "new-controller", multiple_occurrences(true), required = true)]
+    new_controllers: Vec<PrincipalId>,
+
+    /// The dapp canisters to deregister.
+    #[clap(name = "CANISTER", multiple_values(true), required = true)]
+    canisters: Vec<PrincipalId>,
+}
+
+impl Request for ListNeurons {
+    type Response = ListNeuronsResponse;
+    const METHOD_NAME: &'static str = "list_neurons";
+}
+
+impl Request for GetNeuron {
+    type Response = GetNeuronResponse;
+    const METHOD_NAME: &'static str = "get_neuron";
+}
+
+impl Request for ListProposals {
+    type Response = ListProposalsResponse;
+    const METHOD_NAME: &'static str = "list_proposals";
+}
+
+impl Request for GetProposal {
+    type Response = GetProposalResponse;
+    const METHOD_NAME: &'static str = "get_proposal";
+}
+
+impl Request for ManageNeuron {
+    type Response = ManageNeuronResponse;
+    const METHOD_NAME: &'static str = "manage_neuron";
+}
+
+pub fn exec(args: GovernanceArgs) {
+    let governance = Canister::new(&args.network, &args.governance_canister);
+
+    match args.sub_command {
+        SubCommand::ListNeurons(args) => list_neurons(&governance, args),
+        SubCommand::GetNeuron(args) => get_neuron(&governance, args),
+        SubCommand::ListProposals(args) => list_proposals(&governance, args),
+        SubCommand::GetProposal(args) => get_proposal(&governance, args),
+        SubCommand::Vote(args) => vote(&governance, args),
+        SubCommand::Follow(args) => follow(&governance, args),
+        SubCommand::SplitNeuron(args) => split_neuron(&governance, args),
+        SubCommand::Disburse(args) => disburse(&governance, args),
+        SubCommand::RegisterDappCanisters(args) => {
+            let action = Action::RegisterDappCanisters(RegisterDappCanisters {
+                canister_ids: args.canisters,
+            });
+            make_proposal(&governance, args.proposal, action)
+        }
+        SubCommand::DeregisterDappCanisters(args) => {
+            let action = Action::DeregisterDappCanisters(DeregisterDappCanisters {
+                canister_ids: args.canisters,
+                new_controllers: args.new_controllers,
+            });
+            make_proposal(&governance, args.proposal, action)
+        }
+    }
+}
+
+fn list_neurons(governance: &Canister, args: ListNeuronsArgs) {
+    let ListNeuronsResponse { neurons } = call_or_exit(
+        governance,
+        &ListNeurons {
+            limit: args.limit,
+            start_page_at: None,
+            of_principal: args.of_principal,
+        },
+    );
+
+    if neurons.is_empty() {
+        println!("No neurons found.");
+        return;
+    }
+
+    let now_seconds = now_seconds();
+    for neuron in &neurons {
+        println!("{}", render_neuron_summary(neuron, now_seconds));
+    }
+}
+
+fn get_neuron(governance: &Canister, args: NeuronIdArgs) {
+    let GetNeuronResponse { result } = call_or_exit(
+        governance,
+        &GetNeuron {
+            neuron_id: Some(args.neuron_id),
+        },
+    );
+
+    match result {
+        Some(get_neuron_response::Result::Neuron(neuron)) => {
+            println!("{}", render_neuron(&neuron, now_seconds()))
+        }
+        Some(get_neuron_response::Result::Error(err)) => exit_with_error(err),
+        None => exit_with_error("SNS governance returned an empty response."),
+    }
+}
+
+fn list_proposals(governance: &Canister, args: ListProposalsArgs) {
+    let ListProposalsResponse { proposals } = call_or_exit(
+        governance,
+        &ListProposals {
+            limit: args.limit,
+            before_proposal: args.before_proposal.map(|id| ProposalId { id }),
+            ..Default::default()
+        },
+    );
+
+    if proposals.is_empty() {
+        println!("No proposals found.");
+        return;
+    }
+
+    for proposal_data in &proposals {
+        println!("{}", render_proposal_summary(proposal_data));
+    }
+}
+
+fn get_proposal(governance: &Canister, args: ProposalIdArgs) {
+    let GetProposalResponse { result } = call_or_exit(
+        governance,
+        &GetProposal {
+            proposal_id: Some(ProposalId {
+                id: args.proposal_id,
+            }),
+        },
+    );
+
+    match result {
+        Some(get_proposal_response::Result::Proposal(proposal_data)) => {
+            println!("{}", render_proposal(&proposal_data))
+        }
+        Some(get_proposal_response::Result::Error(err)) => exit_with_error(err),
+        None => exit_with_error("SNS governance returned an empty response."),
+    }
+}
+
+fn vote(governance: &Canister, args: VoteArgs) {
+    manage_neuron_or_exit(
+        governance,
+        &args.neuron_id,
+        manage_neuron::Command::RegisterVote(RegisterVote {
+            proposal: Some(ProposalId {
+                id: args.proposal_id,
+            }),
+            vote: args.vote as i32,
+        }),
+    );
+
+    println!(
+        "Neuron {} voted {} on proposal {}.",
+        args.neuron_id,
+        render_vote(args.vote),
+        args.proposal_id,
+    );
+}
+
+fn follow(governance: &Canister, args: FollowArgs) {
+    manage_neuron_or_exit(
+        governance,
+        &args.neuron_id,
+        manage_neuron::Command::Follow(Follow {
+            function_id: args.function_id,
+            followees: args.followees.clone(),
+        }),
+    );
+
+    if args.followees.is_empty() {
+        println!(
+            "Neuron {} no longer follows anyone on function {}.",
+            args.neuron_id, args.function_id,
+        );
+    } else {
+        println!(
+            "Neuron {} now follows {} on function {}.",
+            args.neuron_id,
+            render_neuron_ids(&args.followees),
+            args.function_id,
+        );
+    }
+}
+
+fn split_neuron(governance: &Canister, args: SplitNeuronArgs) {
+    let response = manage_neuron_or_exit(
+        governance,
+        &args.neuron_id,
+        manage_neuron::Command::Split(Split {
+            amount_e8s: args.amount.e8s.unwrap_or_default(),
+            memo: args.memo,
+        }),
+    );
+
+    match response {
+        manage_neuron_response::Command::Split(response) => println!(
+            "Split {} off neuron {} into neuron {}.",
+            format_tokens(&args.amount),
+            args.neuron_id,
+            response
+                .created_neuron_id
+                .map_or("<unknown>".to_string(), |id| id.to_string()),
+        ),
+        response => exit_with_unexpected_response(response),
+    }
+}
+
+fn disburse(governance: &Canister, args: DisburseArgs) {
+    let to_account = args.to_principal.map(|owner| Account {
+        owner: Some(owner),
+        subaccount: None,
+    });
+    let response = manage_neuron_or_exit(
+        governance,
+        &args.neuron_id,
+        manage_neuron::Command::Disburse(Disburse {
+            amount: args.amount.map(|amount| Amount {
+                e8s: amount.e8s.unwrap_or_default(),
+            }),
+            to_account,
+        }),
+    );
+
+    match response {
+        manage_neuron_response::Command::Disburse(response) => println!(
+            "Disbursed neuron {} in ledger block {}.",
+            args.neuron_id, response.transfer_block_height,
+        ),
+        response => exit_with_unexpected_response(response),
+    }
+}
+
+fn make_proposal(governance: &Canister, args: ProposalTextArgs, action: Action) {
+    let proposal = Proposal {
+        title: args.title,
+        summary: args.summary,
+        url: args.url,
+        action: Some(action),
+    };
+    let response = manage_neuron_or_exit(
+        governance,
+        &args.neuron_id,
+        manage_neuron::Command::MakeProposal(proposal),
+    );
+
+    match response {
+        manage_neuron_response::Command::MakeProposal(MakeProposalResponse {
+            proposal_id: Some(proposal_id),
+        }) => println!("🚀 Submitted proposal {}.", proposal_id.id),
+        response => exit_with_unexpected_response(response),
+    }
+}
+
+/// Operates the neuron with the given command, as the current dfx identity.
+/// Exits if SNS governance responds with an error.
+fn manage_neuron_or_exit(
+    governance: &Canister,
+    neuron_id: &NeuronId,
+    command: manage_neuron::Command,
+) -> manage_neuron_response::Command {
+    let ManageNeuronResponse { command } = call_or_exit(
+        governance,
+        &ManageNeuron {
+            subaccount: neuron_id.id.clone(),
+            command: Some(command),
+        },
+    );
+
+    match command {
+        Some(manage_neuron_response::Command::Error(err)) => exit_with_error(err),
+        Some(command) => command,
+        None => exit_with_error("SNS governance returned an empty response."),
+    }
+}
+
+fn exit_with_error(err: impl std::fmt::Display) -> ! {
+    eprintln!("{}", err);
+    exit(1);
+}
+
+fn exit_with_unexpected_response(response: manage_neuron_response::Command) -> ! {
+    exit_with_error(format!(
+        "Unexpected response from SNS governance: {:?}",
+        response
+    ))
+}
+
+fn parse_vote(s: &str) -> Result<Vote, String> {
+    match s.to_lowercase().as_str() {
+        "yes" | "y" => Ok(Vote::Yes),
+        "no" | "n" => Ok(Vote::No),
+        _ => Err(format!("Invalid vote {:?}. Must be either yes or no.", s)),
+    }
+}
+
+fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("The system clock is before the Unix epoch.")
+        .as_secs()
+}
+
+fn render_vote(vote: Vote) -> &'static str {
+    match vote {
+        Vote::Yes => "yes",
+        Vote::No => "no",
+        Vote::Unspecified => "unspecified",
+    }
+}
+
+fn render_e8s(e8s: u64) -> String {
+    format_tokens(&Tokens { e8s: Some(e8s) })
+}
+
+fn render_neuron_ids(neuron_ids: &[NeuronId]) -> String {
+    neuron_ids
+        .iter()
+        .map(NeuronId::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn render_neuron_summary(neuron: &Neuron, now_seconds: u64) -> String {
+    format!(
+        "{}  stake: {}  {:?}",
+        neuron
+            .id
+            .as_ref()
+            .map_or("<no id>".to_string(), NeuronId::to_string),
+        render_e8s(neuron.stake_e8s()),
+        neuron.state(now_seconds),
+    )
+}
+
+fn render_neuron(neuron: &Neuron, now_seconds: u64) -> String {
+    let mut followees = neuron
+        .followees
+        .iter()
+        .map(|(function_id, followees)| {
+            format!(
+                "\n  function {}: {}",
+                function_id,
+                render_neuron_ids(&followees.followees)
+            )
+        })
+        .collect::<String>();
+    if followees.is_empty() {
+        followees = " none".to_string();
+    }
+
+    let permissions = neuron
+        .permissions
+        .iter()
+        .map(|permission| {
+            format!(
+                "\n  {}: {} permission(s)",
+                permission
+                    .principal
+                    .map_or("<no principal>".to_string(), |p| p.to_string()),
+                permission.permission_type.len(),
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        "Neuron {}\n\
+         State: {:?}\n\
+         Stake: {}\n\
+         Maturity: {}\n\
+         Staked maturity: {}\n\
+         Dissolve delay: {} seconds\n\
+         Created at: {} (seconds since the Unix epoch)\n\
+         Permissions:{}\n\
+         Followees:{}",
+        neuron
+            .id
+            .as_ref()
+            .map_or("<no id>".to_string(), NeuronId::to_string),
+        neuron.state(now_seconds),
+        render_e8s(neuron.stake_e8s()),
+        render_e8s(neuron.maturity_e8s_equivalent),
+        render_e8s(neuron.staked_maturity_e8s_equivalent.unwrap_or_default()),
+        neuron.dissolve_delay_seconds(now_seconds),
+        neuron.created_timestamp_seconds,
+        permissions,
+        followees,
+    )
+}
+
+fn render_proposal_summary(proposal_data: &ProposalData) -> String {
+    format!(
+        "{:>6}  {:<10}  {}",
+        proposal_data.id.map_or(0, |id| id.id),
+        proposal_data
+            .status()
+            .as_str_name()
+            .trim_start_matches("PROPOSAL_DECISION_STATUS_"),
+        proposal_data
+            .proposal
+            .as_ref()
+            .map_or("<no title>", |proposal| proposal.title.as_str()),
+    )
+}
+
+fn render_proposal(proposal_data: &ProposalData) -> String {
+    let (title, summary, url) = proposal_data
+        .proposal
+        .as_ref()
+        .map_or(("", "", ""), |proposal| {
+            (
+                proposal.title.as_str(),
+                proposal.summary.as_str(),
+                proposal.url.as_str(),
+            )
+        });
+    let (yes, no, total) = proposal_data
+        .latest_tally
+        .as_ref()
+        .map_or((0, 0, 0), |tally| (tally.yes, tally.no, tally.total));
+    let deadline = proposal_data
+        .wait_for_quiet_state
+        .as_ref()
+        .map_or(0, |state| state.current_deadline_timestamp_seconds);
+
+    format!(
+        "Proposal {}: {}\n\
+         Status: {}\n\
+         Proposer: {}\n\
+         Votes: {} yes, {} no, of {} total voting power\n\
+         Voting deadline: {} (seconds since the Unix epoch)\n\
+         URL: {}\n\
+         \n\
+         {}\n\
+         \n\
+         {}",
+        proposal_data.id.map_or(0, |id| id.id),
+        title,
+        proposal_data
+            .status()
+            .as_str_name()
+            .trim_start_matches("PROPOSAL_DECISION_STATUS_"),
+        proposal_data
+            .proposer
+            .as_ref()
+            .map_or("<unknown>".to_string(), NeuronId::to_string),
+        yes,
+        no,
+        total,
+        deadline,
+        url,
+        summary,
+        proposal_data
+            .payload_text_rendering
+            .as_deref()
+            .unwrap_or("<no payload rendering>"),
+    )
+}
@@ -0,0 +1,29 @@
+use crate::governance::{parse_vote, render_proposal_summary};
+use ic_sns_governance::pb::v1::{Proposal, ProposalData, ProposalId, Vote};
+
+#[test]
+fn test_parse_vote() {
+    assert_eq!(parse_vote("yes"), Ok(Vote::Yes));
+    assert_eq!(parse_vote("Y"), Ok(Vote::Yes));
+    assert_eq!(parse_vote("no"), Ok(Vote::No));
+    assert_eq!(parse_vote("N"), Ok(Vote::No));
+    assert!(parse_vote("maybe").is_err());
+    assert!(parse_vote("").is_err());
+}
+
+#[test]
+fn test_render_proposal_summary() {
+    let proposal_data = ProposalData {
+        id: Some(ProposalId { id: 42 }),
+        proposal: Some(Proposal {
+            title: "Register the frontend".to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        render_proposal_summary(&proposal_data),
+        "    42  OPEN        Register the frontend"
+    );
+}
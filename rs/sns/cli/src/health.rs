@@ -0,0 +1,145 @@
+use crate::{call_or_exit, Canister, Request};
+use clap::Parser;
+use ic_nns_constants::SNS_WASM_CANISTER_ID;
+use ic_sns_governance::pb::v1::{GetRunningSnsVersionRequest, GetRunningSnsVersionResponse};
+use ic_sns_root::{CanisterSummary, GetSnsCanistersSummaryRequest, GetSnsCanistersSummaryResponse};
+use ic_sns_wasm::pb::v1::{ListUpgradeStepsRequest, ListUpgradeStepsResponse};
+
+/// Canisters with fewer cycles than this are flagged, so that they get topped
+/// up before they are frozen.
+const LOW_CYCLES_THRESHOLD: u128 = 1_000_000_000_000;
+
+#[derive(Debug, Parser)]
+pub struct HealthArgs {
+    /// The network to deploy to. This can be "local", "ic", or the URL of an IC network.
+    #[structopt(default_value = "local", long)]
+    network: String,
+
+    /// The canister ID or name (via dfx.json) of the SNS root canister.
+    #[clap(default_value = "sns_root", long)]
+    root_canister: String,
+}
+
+impl Request for GetSnsCanistersSummaryRequest {
+    type Response = GetSnsCanistersSummaryResponse;
+    const METHOD_NAME: &'static str = "get_sns_canisters_summary";
+}
+
+impl Request for GetRunningSnsVersionRequest {
+    type Response = GetRunningSnsVersionResponse;
+    const METHOD_NAME: &'static str = "get_running_sns_version";
+}
+
+impl Request for ListUpgradeStepsRequest {
+    type Response = ListUpgradeStepsResponse;
+    const METHOD_NAME: &'static str = "list_upgrade_steps";
+}
+
+pub fn exec(args: HealthArgs) {
+    let root = Canister::new(&args.network, &args.root_canister);
+    let summary = call_or_exit(
+        &root,
+        &GetSnsCanistersSummaryRequest {
+            update_canister_list: Some(false),
+        },
+    );
+
+    let named_summaries = [
+        ("root", &summary.root),
+        ("governance", &summary.governance),
+        ("ledger", &summary.ledger),
+        ("swap", &summary.swap),
+        ("index", &summary.index),
+    ];
+    println!(
+        "{:<12} {:<29} {:<9} {:>20} {:>14}  module hash",
+        "canister", "id", "status", "cycles", "memory"
+    );
+    for (name, canister_summary) in named_summaries {
+        match canister_summary {
+            Some(canister_summary) => {
+                println!("{}", render_canister_summary(name, canister_summary))
+            }
+            None => println!("{:<12} <missing>", name),
+        }
+    }
+    for canister_summary in &summary.dapps {
+        println!("{}", render_canister_summary("dapp", canister_summary));
+    }
+    for canister_summary in &summary.archives {
+        println!("{}", render_canister_summary("archive", canister_summary));
+    }
+
+    let Some(governance_canister_id) = summary.governance.as_ref().and_then(|s| s.canister_id)
+    else {
+        eprintln!(
+            "SNS root did not report a governance canister; unable to determine the version lag."
+        );
+        std::process::exit(1);
+    };
+    let governance = Canister::new(&args.network, &governance_canister_id.to_string());
+    let GetRunningSnsVersionResponse {
+        deployed_version,
+        pending_version,
+    } = call_or_exit(&governance, &GetRunningSnsVersionRequest {});
+
+    println!();
+    let Some(deployed_version) = deployed_version else {
+        println!("Version lag: unknown (SNS governance does not know its deployed version)");
+        return;
+    };
+
+    // SNS-W is an NNS canister, so it is always addressed by ID.
+    let sns_wasm = Canister::new(&args.network, &SNS_WASM_CANISTER_ID.to_string());
+    let ListUpgradeStepsResponse { steps } = call_or_exit(
+        &sns_wasm,
+        &ListUpgradeStepsRequest {
+            starting_at: Some(deployed_version.into()),
+            sns_governance_canister_id: Some(governance_canister_id),
+            limit: 0,
+        },
+    );
+    println!("{}", render_version_lag(steps.len()));
+    if pending_version.is_some() {
+        println!("An upgrade is in progress.");
+    }
+}
+
+fn render_canister_summary(name: &str, canister_summary: &CanisterSummary) -> String {
+    let canister_id = canister_summary
+        .canister_id
+        .map_or("<unknown>".to_string(), |id| id.to_string());
+    let Some(status) = &canister_summary.status else {
+        return format!("{:<12} {:<29} <status unavailable>", name, canister_id);
+    };
+
+    let cycles = status.cycles();
+    let low_cycles_warning = if cycles < LOW_CYCLES_THRESHOLD {
+        "  ⚠️ low on cycles"
+    } else {
+        ""
+    };
+    format!(
+        "{:<12} {:<29} {:<9} {:>20} {:>14}  {}{}",
+        name,
+        canister_id,
+        status.status().to_string(),
+        cycles,
+        status.memory_size().get(),
+        status.module_hash().map_or("-".to_string(), hex::encode),
+        low_cycles_warning,
+    )
+}
+
+/// `upgrade_step_count` includes the deployed version itself, which SNS-W
+/// always returns as the first step.
+fn render_version_lag(upgrade_step_count: usize) -> String {
+    match upgrade_step_count.saturating_sub(1) {
+        0 => "Version lag: none (the SNS runs the latest blessed version)".to_string(),
+        1 => "Version lag: 1 upgrade behind the latest blessed version".to_string(),
+        lag => format!(
+            "Version lag: {} upgrades behind the latest blessed version",
+            lag
+        ),
+    }
+}
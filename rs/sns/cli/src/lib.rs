@@ -1,8 +1,11 @@
 use crate::{
     deploy::{DirectSnsDeployerForTests, SnsWasmSnsDeployer},
+    governance::GovernanceArgs,
+    health::HealthArgs,
     init_config_file::{InitConfigFileArgs, SnsCliInitConfig, SnsInitialTokenDistributionConfig},
     prepare_canisters::PrepareCanistersArgs,
     propose::ProposeArgs,
+    swap::SwapArgs,
 };
 use candid::{CandidType, Decode, Encode, IDLArgs};
 use clap::Parser;
@@ -33,9 +36,12 @@ use std::{
 use tempfile::NamedTempFile;
 
 pub mod deploy;
+pub mod governance;
+pub mod health;
 pub mod init_config_file;
 pub mod prepare_canisters;
 pub mod propose;
+pub mod swap;
 pub mod unit_helpers;
 
 #[cfg(test)]
@@ -79,6 +85,13 @@ pub enum SubCommand {
     PrepareCanisters(PrepareCanistersArgs),
     /// Submit an NNS proposal to create new SNS.
     Propose(ProposeArgs),
+    /// Inspect and operate the neurons and proposals of an SNS.
+    Governance(GovernanceArgs),
+    /// Check the status of an SNS swap and participate in it.
+    Swap(SwapArgs),
+    /// Show the health of the SNS canisters, including their cycles and how
+    /// far behind the latest SNS version they are.
+    Health(HealthArgs),
 }
 
 /// The arguments used to configure a SNS deployment
@@ -628,6 +641,24 @@ enum CanisterCallError {
     ResponseDecodeFail(String),
 }
 
+impl Display for CanisterCallError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CanisterCallError::UnableToPrepareDfxCall(reason)
+            | CanisterCallError::ResponseDecodeFail(reason) => write!(formatter, "{}", reason),
+            CanisterCallError::UnableToCallDfx(error) => {
+                write!(formatter, "Unable to run dfx: {}", error)
+            }
+            CanisterCallError::DfxBadExit(output) => write!(
+                formatter,
+                "dfx exited with {}:\n{}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr),
+            ),
+        }
+    }
+}
+
 impl Canister {
     /// Arguments are like those that are passed to `dfx canister`.
     pub fn new(network: &str, name: &str) -> Self {
@@ -717,6 +748,46 @@ impl Canister {
     }
 }
 
+/// Like `Canister::call`, except that if the call fails, the error is printed,
+/// and the process exits.
+fn call_or_exit<Req>(canister: &Canister, request: &Req) -> Req::Response
+where
+    Req: Request + CandidType,
+    <Req as Request>::Response: CandidType + for<'a> candid::Deserialize<'a>,
+{
+    canister.call(request).unwrap_or_else(|err| {
+        eprintln!(
+            "{}\n\nCalling {} on canister {} failed.",
+            err,
+            Req::METHOD_NAME,
+            canister.name,
+        );
+        exit(1);
+    })
+}
+
+/// Returns the ID of a canister that is given either by ID, or by name (via
+/// dfx.json). Exits if the canister cannot be found.
+fn canister_id_or_exit(network: &str, canister: &str) -> PrincipalId {
+    if let Ok(canister_id) = PrincipalId::from_str(canister) {
+        return canister_id;
+    }
+
+    let command = ["dfx", "canister", "--network", network, "id", canister];
+    let (stdout, _stderr) = run_command(&command).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    });
+
+    PrincipalId::from_str(stdout.trim()).unwrap_or_else(|err| {
+        eprintln!(
+            "Unable to parse the output of `dfx canister id {}` ({:?}) as a principal ID: {}",
+            canister, stdout, err,
+        );
+        exit(1);
+    })
+}
+
 struct NnsGovernanceCanister {
     canister: Canister,
 }
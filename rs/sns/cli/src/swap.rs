@@ -0,0 +1,349 @@
+use crate::{call_or_exit, canister_id_or_exit, get_identity, run_command, Canister, Request};
+use clap::Parser;
+use ic_base_types::PrincipalId;
+use ic_nervous_system_humanize::{format_tokens, parse_tokens};
+use ic_nervous_system_proto::pb::v1::Tokens;
+use ic_nns_constants::LEDGER_CANISTER_ID;
+use ic_sns_swap::pb::v1::{
+    new_sale_ticket_response, GetBuyerStateRequest, GetBuyerStateResponse, GetDerivedStateRequest,
+    GetDerivedStateResponse, GetLifecycleRequest, GetLifecycleResponse, Lifecycle,
+    NewSaleTicketRequest, NewSaleTicketResponse, RefreshBuyerTokensRequest,
+    RefreshBuyerTokensResponse,
+};
+use icp_ledger::{AccountIdentifier, BinaryAccountBalanceArgs, Subaccount};
+use std::process::exit;
+
+#[cfg(test)]
+mod swap_tests;
+
+#[derive(Debug, Parser)]
+pub struct SwapArgs {
+    /// The network to deploy to. This can be "local", "ic", or the URL of an IC network.
+    #[structopt(default_value = "local", long)]
+    network: String,
+
+    /// The canister ID or name (via dfx.json) of the SNS swap canister.
+    #[clap(default_value = "sns_swap", long)]
+    swap_canister: String,
+
+    #[clap(subcommand)]
+    sub_command: SubCommand,
+}
+
+#[derive(Debug, Parser)]
+enum SubCommand {
+    /// Show the lifecycle and the participation totals of the swap, along with
+    /// the participation of a principal.
+    Status(StatusArgs),
+    /// Participate in the swap with ICP from the account of the current dfx
+    /// identity.
+    Participate(ParticipateArgs),
+}
+
+#[derive(Debug, Parser)]
+struct StatusArgs {
+    /// The principal whose participation is shown. If not given, the
+    /// participation of the current dfx identity is shown.
+    #[clap(long)]
+    principal: Option<PrincipalId>,
+}
+
+#[derive(Debug, Parser)]
+struct ParticipateArgs {
+    /// The amount of ICP to participate with, e.g. "10 tokens" or "100_000
+    /// e8s". The ICP ledger transfer fee is paid on top of this.
+    #[clap(long, parse(try_from_str = parse_tokens))]
+    amount: Tokens,
+
+    /// The confirmation text required by the swap, if any. It must match the
+    /// text in the swap's init exactly.
+    #[clap(long)]
+    confirmation_text: Option<String>,
+}
+
+impl Request for GetLifecycleRequest {
+    type Response = GetLifecycleResponse;
+    const METHOD_NAME: &'static str = "get_lifecycle";
+}
+
+impl Request for GetDerivedStateRequest {
+    type Response = GetDerivedStateResponse;
+    const METHOD_NAME: &'static str = "get_derived_state";
+}
+
+impl Request for GetBuyerStateRequest {
+    type Response = GetBuyerStateResponse;
+    const METHOD_NAME: &'static str = "get_buyer_state";
+}
+
+impl Request for NewSaleTicketRequest {
+    type Response = NewSaleTicketResponse;
+    const METHOD_NAME: &'static str = "new_sale_ticket";
+}
+
+impl Request for RefreshBuyerTokensRequest {
+    type Response = RefreshBuyerTokensResponse;
+    const METHOD_NAME: &'static str = "refresh_buyer_tokens";
+}
+
+pub fn exec(args: SwapArgs) {
+    let swap = Canister::new(&args.network, &args.swap_canister);
+
+    match args.sub_command {
+        SubCommand::Status(sub_args) => status(&swap, &args.network, sub_args),
+        SubCommand::Participate(sub_args) => {
+            participate(&swap, &args.network, &args.swap_canister, sub_args)
+        }
+    }
+}
+
+fn status(swap: &Canister, network: &str, args: StatusArgs) {
+    let lifecycle = call_or_exit(swap, &GetLifecycleRequest {});
+    let derived_state = call_or_exit(swap, &GetDerivedStateRequest {});
+    let principal = args
+        .principal
+        .unwrap_or_else(|| get_identity("get-principal", network));
+    let GetBuyerStateResponse { buyer_state } = call_or_exit(
+        swap,
+        &GetBuyerStateRequest {
+            principal_id: Some(principal),
+        },
+    );
+    let participation_e8s = buyer_state
+        .and_then(|buyer_state| buyer_state.icp)
+        .map_or(0, |icp| icp.amount_e8s);
+
+    println!("{}", render_status(&lifecycle, &derived_state));
+    println!(
+        "Participation of {}: {}",
+        principal,
+        render_e8s(participation_e8s)
+    );
+}
+
+/// Participates in the swap the way that the swap expects participants to:
+///
+///   1. Open a sale ticket for the amount.
+///   2. Transfer the amount to the caller's subaccount of the swap canister.
+///   3. Ask the swap to pick up the transfer.
+///
+/// If step 2 or 3 fails, running the command again with the same amount picks
+/// up from the open ticket.
+fn participate(swap: &Canister, network: &str, swap_canister: &str, args: ParticipateArgs) {
+    let amount_e8s = args.amount.e8s.unwrap_or_default();
+    let caller = get_identity("get-principal", network);
+    let swap_canister_id = canister_id_or_exit(network, swap_canister);
+
+    let mut env = DfxParticipationEnv {
+        swap,
+        ledger: Canister::new(network, &LEDGER_CANISTER_ID.to_string()),
+        network,
+        caller,
+        destination: AccountIdentifier::new(swap_canister_id, Some(Subaccount::from(&caller))),
+        confirmation_text: args.confirmation_text,
+    };
+    let icp_accepted_participation_e8s =
+        participate_with(&mut env, amount_e8s).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1);
+        });
+
+    println!(
+        "{} now participates in the swap with {}.",
+        caller,
+        render_e8s(icp_accepted_participation_e8s),
+    );
+}
+
+/// The calls made to participate in the swap. They are behind a trait so that
+/// the participation flow can be tested without dfx.
+trait ParticipationEnv {
+    fn new_sale_ticket(&mut self, amount_icp_e8s: u64) -> NewSaleTicketResponse;
+
+    /// The ICP balance of the caller's subaccount of the swap canister.
+    fn swap_subaccount_balance_e8s(&mut self) -> u64;
+
+    /// The participation of the caller that the swap has already accepted.
+    fn participation_e8s(&mut self) -> u64;
+
+    fn transfer_to_swap_subaccount(&mut self, amount_e8s: u64) -> Result<(), String>;
+
+    fn refresh_buyer_tokens(&mut self) -> RefreshBuyerTokensResponse;
+}
+
+/// Runs the participation flow described in `participate`, and returns the
+/// accepted participation of the caller.
+///
+/// Before transferring, the ICP that is already in the caller's subaccount of
+/// the swap canister, but not accepted by the swap yet, is deducted from the
+/// amount. This way, resuming a ticket whose transfer went through does not
+/// transfer the amount a second time.
+fn participate_with(env: &mut impl ParticipationEnv, amount_e8s: u64) -> Result<u64, String> {
+    let NewSaleTicketResponse { result } = env.new_sale_ticket(amount_e8s);
+    match result {
+        Some(new_sale_ticket_response::Result::Ok(_)) => (),
+        Some(new_sale_ticket_response::Result::Err(err)) => {
+            let ticket_exists_with_same_amount = err
+                .existing_ticket
+                .as_ref()
+                .map_or(false, |ticket| ticket.amount_icp_e8s == amount_e8s);
+            if !ticket_exists_with_same_amount {
+                return Err(format!("Unable to open a sale ticket: {:?}", err));
+            }
+            println!("Resuming the open sale ticket.");
+        }
+        None => {
+            return Err("SNS swap returned an empty response to new_sale_ticket.".to_string());
+        }
+    }
+
+    let pending_e8s = env
+        .swap_subaccount_balance_e8s()
+        .saturating_sub(env.participation_e8s());
+    let amount_to_transfer_e8s = amount_e8s.saturating_sub(pending_e8s);
+    if amount_to_transfer_e8s == 0 {
+        println!("The ICP of the ticket was already transferred to the swap.");
+    } else {
+        env.transfer_to_swap_subaccount(amount_to_transfer_e8s)
+            .map_err(|err| format!("{}\n\nTransferring ICP to the swap failed.", err))?;
+    }
+
+    let RefreshBuyerTokensResponse {
+        icp_accepted_participation_e8s,
+        icp_ledger_account_balance_e8s: _,
+    } = env.refresh_buyer_tokens();
+    Ok(icp_accepted_participation_e8s)
+}
+
+impl Request for BinaryAccountBalanceArgs {
+    type Response = icp_ledger::Tokens;
+    const METHOD_NAME: &'static str = "account_balance";
+}
+
+/// Participates in the swap by calling the canisters via dfx, on behalf of the
+/// current dfx identity.
+struct DfxParticipationEnv<'a> {
+    swap: &'a Canister,
+    ledger: Canister,
+    network: &'a str,
+    caller: PrincipalId,
+    destination: AccountIdentifier,
+    confirmation_text: Option<String>,
+}
+
+impl ParticipationEnv for DfxParticipationEnv<'_> {
+    fn new_sale_ticket(&mut self, amount_icp_e8s: u64) -> NewSaleTicketResponse {
+        call_or_exit(
+            self.swap,
+            &NewSaleTicketRequest {
+                amount_icp_e8s,
+                subaccount: None,
+            },
+        )
+    }
+
+    fn swap_subaccount_balance_e8s(&mut self) -> u64 {
+        call_or_exit(
+            &self.ledger,
+            &BinaryAccountBalanceArgs {
+                account: self.destination.to_address(),
+            },
+        )
+        .get_e8s()
+    }
+
+    fn participation_e8s(&mut self) -> u64 {
+        let GetBuyerStateResponse { buyer_state } = call_or_exit(
+            self.swap,
+            &GetBuyerStateRequest {
+                principal_id: Some(self.caller),
+            },
+        );
+        buyer_state
+            .and_then(|buyer_state| buyer_state.icp)
+            .map_or(0, |icp| icp.amount_e8s)
+    }
+
+    fn transfer_to_swap_subaccount(&mut self, amount_e8s: u64) -> Result<(), String> {
+        let amount_icp = format!(
+            "{}.{:08}",
+            amount_e8s / 100_000_000,
+            amount_e8s % 100_000_000
+        );
+        let command = [
+            "dfx",
+            "ledger",
+            "--network",
+            self.network,
+            "transfer",
+            &self.destination.to_hex(),
+            "--amount",
+            &amount_icp,
+            "--memo",
+            "0",
+        ];
+        run_command(&command)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+
+    fn refresh_buyer_tokens(&mut self) -> RefreshBuyerTokensResponse {
+        call_or_exit(
+            self.swap,
+            &RefreshBuyerTokensRequest {
+                buyer: self.caller.to_string(),
+                confirmation_text: self.confirmation_text.clone(),
+            },
+        )
+    }
+}
+
+fn render_e8s(e8s: u64) -> String {
+    format_tokens(&Tokens { e8s: Some(e8s) })
+}
+
+fn render_status(
+    lifecycle: &GetLifecycleResponse,
+    derived_state: &GetDerivedStateResponse,
+) -> String {
+    let lifecycle_name = lifecycle
+        .lifecycle
+        .and_then(Lifecycle::from_i32)
+        .unwrap_or(Lifecycle::Unspecified)
+        .as_str_name()
+        .trim_start_matches("LIFECYCLE_");
+    let render_timestamp = |timestamp_seconds: Option<u64>| {
+        timestamp_seconds.map_or("-".to_string(), |seconds| {
+            format!("{} (seconds since the Unix epoch)", seconds)
+        })
+    };
+
+    format!(
+        "Lifecycle: {}\n\
+         Opened at: {}\n\
+         Terminated at: {}\n\
+         Total participation: {}\n\
+         Direct participation: {} from {} participant(s)\n\
+         Neurons' Fund participation: {} from {} participant(s)\n\
+         SNS tokens per ICP: {}",
+        lifecycle_name,
+        render_timestamp(lifecycle.decentralization_sale_open_timestamp_seconds),
+        render_timestamp(lifecycle.decentralization_swap_termination_timestamp_seconds),
+        render_e8s(derived_state.buyer_total_icp_e8s.unwrap_or_default()),
+        render_e8s(
+            derived_state
+                .direct_participation_icp_e8s
+                .unwrap_or_default()
+        ),
+        derived_state.direct_participant_count.unwrap_or_default(),
+        render_e8s(
+            derived_state
+                .neurons_fund_participation_icp_e8s
+                .unwrap_or_default()
+        ),
+        derived_state.cf_participant_count.unwrap_or_default(),
+        derived_state
+            .sns_tokens_per_icp
+            .map_or("-".to_string(), |rate| format!("{:.4}", rate)),
+    )
+}
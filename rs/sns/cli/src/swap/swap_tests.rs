@@ -0,0 +1,116 @@
+use crate::swap::{participate_with, ParticipationEnv};
+use ic_sns_swap::pb::v1::{
+    new_sale_ticket_response, NewSaleTicketResponse, RefreshBuyerTokensResponse, Ticket,
+};
+
+/// A swap and a ledger that only know about the caller.
+#[derive(Default)]
+struct FakeParticipationEnv {
+    open_ticket_amount_e8s: Option<u64>,
+    swap_subaccount_balance_e8s: u64,
+    participation_e8s: u64,
+    transfers_e8s: Vec<u64>,
+}
+
+impl ParticipationEnv for FakeParticipationEnv {
+    fn new_sale_ticket(&mut self, amount_icp_e8s: u64) -> NewSaleTicketResponse {
+        let result = match self.open_ticket_amount_e8s {
+            Some(open_ticket_amount_e8s) => {
+                new_sale_ticket_response::Result::Err(new_sale_ticket_response::Err {
+                    error_type: new_sale_ticket_response::err::Type::TicketExists as i32,
+                    invalid_user_amount: None,
+                    existing_ticket: Some(Ticket {
+                        amount_icp_e8s: open_ticket_amount_e8s,
+                        ..Default::default()
+                    }),
+                })
+            }
+            None => {
+                self.open_ticket_amount_e8s = Some(amount_icp_e8s);
+                new_sale_ticket_response::Result::Ok(new_sale_ticket_response::Ok {
+                    ticket: Some(Ticket {
+                        amount_icp_e8s,
+                        ..Default::default()
+                    }),
+                })
+            }
+        };
+        NewSaleTicketResponse {
+            result: Some(result),
+        }
+    }
+
+    fn swap_subaccount_balance_e8s(&mut self) -> u64 {
+        self.swap_subaccount_balance_e8s
+    }
+
+    fn participation_e8s(&mut self) -> u64 {
+        self.participation_e8s
+    }
+
+    fn transfer_to_swap_subaccount(&mut self, amount_e8s: u64) -> Result<(), String> {
+        self.transfers_e8s.push(amount_e8s);
+        self.swap_subaccount_balance_e8s += amount_e8s;
+        Ok(())
+    }
+
+    fn refresh_buyer_tokens(&mut self) -> RefreshBuyerTokensResponse {
+        self.open_ticket_amount_e8s = None;
+        self.participation_e8s = self.swap_subaccount_balance_e8s;
+        RefreshBuyerTokensResponse {
+            icp_accepted_participation_e8s: self.participation_e8s,
+            icp_ledger_account_balance_e8s: self.swap_subaccount_balance_e8s,
+        }
+    }
+}
+
+#[test]
+fn test_participate_transfers_the_amount_of_a_new_ticket() {
+    let mut env = FakeParticipationEnv {
+        swap_subaccount_balance_e8s: 100,
+        participation_e8s: 100,
+        ..Default::default()
+    };
+
+    assert_eq!(participate_with(&mut env, 500), Ok(600));
+    assert_eq!(env.transfers_e8s, vec![500]);
+}
+
+#[test]
+fn test_participate_resumes_a_ticket_without_transferring_twice() {
+    // The transfer of a previous attempt went through, but refreshing the
+    // buyer tokens did not.
+    let mut env = FakeParticipationEnv {
+        open_ticket_amount_e8s: Some(500),
+        swap_subaccount_balance_e8s: 600,
+        participation_e8s: 100,
+        ..Default::default()
+    };
+
+    assert_eq!(participate_with(&mut env, 500), Ok(600));
+    assert_eq!(env.transfers_e8s, Vec::<u64>::new());
+}
+
+#[test]
+fn test_participate_resumes_a_ticket_whose_transfer_failed() {
+    let mut env = FakeParticipationEnv {
+        open_ticket_amount_e8s: Some(500),
+        swap_subaccount_balance_e8s: 100,
+        participation_e8s: 100,
+        ..Default::default()
+    };
+
+    assert_eq!(participate_with(&mut env, 500), Ok(600));
+    assert_eq!(env.transfers_e8s, vec![500]);
+}
+
+#[test]
+fn test_participate_rejects_a_ticket_with_another_amount() {
+    let mut env = FakeParticipationEnv {
+        open_ticket_amount_e8s: Some(400),
+        ..Default::default()
+    };
+
+    assert!(participate_with(&mut env, 500).is_err());
+    assert_eq!(env.transfers_e8s, Vec::<u64>::new());
+}
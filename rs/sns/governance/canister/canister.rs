@@ -47,9 +47,11 @@ use ic_sns_governance::{
         GetModeResponse, GetNeuron, GetNeuronResponse, GetProposal, GetProposalResponse,
         GetRunningSnsVersionRequest, GetRunningSnsVersionResponse,
         GetSnsInitializationParametersRequest, GetSnsInitializationParametersResponse,
-        Governance as GovernanceProto, ListNervousSystemFunctionsResponse, ListNeurons,
-        ListNeuronsResponse, ListProposals, ListProposalsResponse, ManageNeuron,
-        ManageNeuronResponse, NervousSystemParameters, RewardEvent, SetMode, SetModeResponse,
+        GetUpgradeJournalRequest, GetUpgradeJournalResponse, Governance as GovernanceProto,
+        ListNervousSystemFunctionsResponse, ListNeurons, ListNeuronsResponse, ListProposals,
+        ListProposalsResponse, ManageNeuron, ManageNeuronResponse, NervousSystemParameters,
+        PreviewExecuteGenericNervousSystemFunctionRequest,
+        PreviewExecuteGenericNervousSystemFunctionResponse, RewardEvent, SetMode, SetModeResponse,
     },
     types::{Environment, HeapGrowthPotential},
 };
@@ -528,6 +530,42 @@ fn get_running_sns_version_(_: GetRunningSnsVersionRequest) -> GetRunningSnsVers
     }
 }
 
+/// Gets the upgrade journal of the SNS: the recent upgrades that were attempted,
+/// succeeded or failed, and the staged upgrade in progress, if any.
+#[export_name = "canister_query get_upgrade_journal"]
+fn get_upgrade_journal() {
+    log!(INFO, "get_upgrade_journal");
+    over(candid_one, get_upgrade_journal_)
+}
+
+/// Internal method for calling get_upgrade_journal.
+#[candid_method(query, rename = "get_upgrade_journal")]
+fn get_upgrade_journal_(request: GetUpgradeJournalRequest) -> GetUpgradeJournalResponse {
+    governance().get_upgrade_journal(request)
+}
+
+/// Previews what an ExecuteGenericNervousSystemFunction proposal would show
+/// voters: the validator's rendering, the payload decoded with the function's
+/// registered Candid interface, and the target canister's module hash.
+///
+/// This is an update method because it calls other canisters, but it does not
+/// change Governance's state.
+#[export_name = "canister_update preview_execute_generic_nervous_system_function"]
+fn preview_execute_generic_nervous_system_function() {
+    log!(INFO, "preview_execute_generic_nervous_system_function");
+    over_async(candid_one, preview_execute_generic_nervous_system_function_)
+}
+
+/// Internal method for calling preview_execute_generic_nervous_system_function.
+#[candid_method(update, rename = "preview_execute_generic_nervous_system_function")]
+async fn preview_execute_generic_nervous_system_function_(
+    request: PreviewExecuteGenericNervousSystemFunctionRequest,
+) -> PreviewExecuteGenericNervousSystemFunctionResponse {
+    governance()
+        .preview_execute_generic_nervous_system_function(request)
+        .await
+}
+
 /// Marks an in progress upgrade that has passed its deadline as failed.
 #[export_name = "canister_update fail_stuck_upgrade_in_progress"]
 fn fail_stuck_upgrade_in_progress() {
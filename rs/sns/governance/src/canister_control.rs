@@ -12,6 +12,7 @@ use candid::{Decode, Encode};
 use dfn_core::CanisterId;
 use ic_base_types::PrincipalId;
 use ic_canister_log::log;
+use ic_ic00_types::{CanisterInfoRequest, CanisterInfoResponse};
 use ic_nervous_system_clients::{
     canister_id_record::CanisterIdRecord,
     canister_status::{CanisterStatusResultFromManagementCanister, CanisterStatusType},
@@ -280,6 +281,42 @@ pub async fn perform_execute_generic_nervous_system_function_validate_and_render
     }
 }
 
+/// Returns the module hash of the given canister, as reported by the management
+/// canister's `canister_info` method, or None if the canister is empty.
+pub async fn get_canister_module_hash(
+    env: &dyn Environment,
+    canister_id: CanisterId,
+) -> Result<Option<Vec<u8>>, GovernanceError> {
+    let request = candid::encode_one(CanisterInfoRequest::new(canister_id, None)).map_err(|e| {
+        GovernanceError::new_with_message(
+            ErrorType::External,
+            format!("Error encoding canister_info request: {}", e),
+        )
+    })?;
+
+    let reply = env
+        .call_canister(CanisterId::ic_00(), "canister_info", request)
+        .await
+        .map_err(|err| {
+            GovernanceError::new_with_message(
+                ErrorType::External,
+                format!(
+                    "Canister method call canister_info for canister {} failed: {:?}",
+                    canister_id, err
+                ),
+            )
+        })?;
+
+    let response = candid::decode_one::<CanisterInfoResponse>(&reply).map_err(|e| {
+        GovernanceError::new_with_message(
+            ErrorType::External,
+            format!("Error decoding canister_info response: {}", e),
+        )
+    })?;
+
+    Ok(response.module_hash())
+}
+
 /// Executes a generic nervous system function (i.e., a non-native SNS proposal).
 pub async fn perform_execute_generic_nervous_system_function_call(
     env: &dyn Environment,
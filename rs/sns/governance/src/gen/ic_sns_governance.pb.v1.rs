@@ -259,6 +259,12 @@ pub mod nervous_system_function {
         /// <method_name>(proposal_data: ProposalData) -> Result<String, String>
         #[prost(string, optional, tag = "5")]
         pub validator_method_name: ::core::option::Option<::prost::alloc::string::String>,
+        /// The Candid service definition of the target canister. When set, the
+        /// payload of ExecuteGenericNervousSystemFunction proposals is decoded as
+        /// the arguments of `target_method_name` and shown to voters alongside the
+        /// validator's rendering.
+        #[prost(string, optional, tag = "6")]
+        pub target_candid_interface: ::core::option::Option<::prost::alloc::string::String>,
     }
     #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
     #[allow(clippy::derive_partial_eq_without_eq)]
@@ -450,6 +456,19 @@ pub struct ManageSnsMetadata {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpgradeSnsToNextVersion {}
+/// A proposal function to set the version that the SNS upgrades to. Once adopted, the SNS
+/// upgrades itself automatically, one step of the upgrade path published by SNS-W at a time,
+/// until the target version is deployed. The proposal is executed when the target version is
+/// reached, and fails as soon as one of the steps fails.
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AdvanceSnsTargetVersion {
+    /// The version to upgrade to. It must be on the upgrade path that starts at the currently
+    /// deployed version.
+    #[prost(message, optional, tag = "1")]
+    pub new_target: ::core::option::Option<governance::Version>,
+}
 /// A proposal to register a list of dapps in the root canister.
 #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -628,6 +647,11 @@ pub mod proposal {
         /// Id = 14.
         #[prost(message, tag = "18")]
         ManageDappCanisterSettings(super::ManageDappCanisterSettings),
+        /// Set the version that the SNS automatically upgrades to.
+        ///
+        /// Id = 15.
+        #[prost(message, tag = "19")]
+        AdvanceSnsTargetVersion(super::AdvanceSnsTargetVersion),
     }
 }
 #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
@@ -959,6 +983,13 @@ pub struct ProposalData {
     #[prost(message, optional, tag = "21")]
     pub minimum_yes_proportion_of_exercised:
         ::core::option::Option<::ic_nervous_system_proto::pb::v1::Percentage>,
+    /// For ExecuteGenericNervousSystemFunction proposals, the module hash of the
+    /// target canister at the time the proposal was submitted. Execution fails if
+    /// the target canister's module hash is different at execution time, so that
+    /// the code that runs is the code voters saw. Unset for other proposals, and
+    /// for proposals submitted before module hashes were recorded.
+    #[prost(bytes = "vec", optional, tag = "22")]
+    pub target_canister_module_hash: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
 }
 /// The nervous system's parameters, which are parameters that can be changed, via proposals,
 /// by each nervous system community.
@@ -1335,6 +1366,12 @@ pub struct Governance {
     pub is_finalizing_disburse_maturity: ::core::option::Option<bool>,
     #[prost(message, optional, tag = "26")]
     pub maturity_modulation: ::core::option::Option<governance::MaturityModulation>,
+    /// The staged upgrade that the SNS is performing, if any.
+    #[prost(message, optional, tag = "27")]
+    pub staged_upgrade: ::core::option::Option<governance::StagedUpgrade>,
+    /// The most recent events of upgrades of this SNS, oldest first.
+    #[prost(message, repeated, tag = "28")]
+    pub upgrade_journal: ::prost::alloc::vec::Vec<UpgradeJournalEntry>,
 }
 /// Nested message and enum types in `Governance`.
 pub mod governance {
@@ -1553,6 +1590,19 @@ pub mod governance {
         #[prost(uint64, optional, tag = "2")]
         pub updated_at_timestamp_seconds: ::core::option::Option<u64>,
     }
+    /// An upgrade to a target version that is performed one step at a time.
+    #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct StagedUpgrade {
+        /// The AdvanceSnsTargetVersion proposal that set the target version.
+        #[prost(uint64, tag = "1")]
+        pub proposal_id: u64,
+        /// The versions on the upgrade path, starting at the version that was deployed when the
+        /// proposal was executed and ending at the target version.
+        #[prost(message, repeated, tag = "2")]
+        pub upgrade_steps: ::prost::alloc::vec::Vec<Version>,
+    }
     #[derive(
         candid::CandidType,
         candid::Deserialize,
@@ -1651,6 +1701,160 @@ pub struct GetRunningSnsVersionResponse {
     #[prost(message, optional, tag = "2")]
     pub pending_version: ::core::option::Option<governance::UpgradeInProgress>,
 }
+/// An event in the upgrade history of an SNS.
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpgradeJournalEntry {
+    /// When the event happened, in seconds since the UNIX epoch.
+    #[prost(uint64, tag = "1")]
+    pub timestamp_seconds: u64,
+    #[prost(enumeration = "upgrade_journal_entry::Event", tag = "2")]
+    pub event: i32,
+    /// The proposal that caused the event.
+    #[prost(uint64, tag = "3")]
+    pub proposal_id: u64,
+    /// The version deployed when the event happened.
+    #[prost(message, optional, tag = "4")]
+    pub from_version: ::core::option::Option<governance::Version>,
+    /// The version being upgraded to. For TARGET_VERSION_SET and TARGET_VERSION_REACHED
+    /// events, this is the target version.
+    #[prost(message, optional, tag = "5")]
+    pub to_version: ::core::option::Option<governance::Version>,
+    /// Why the upgrade failed, for UPGRADE_FAILED events.
+    #[prost(string, optional, tag = "6")]
+    pub failure_reason: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Nested message and enum types in `UpgradeJournalEntry`.
+pub mod upgrade_journal_entry {
+    #[derive(
+        candid::CandidType,
+        candid::Deserialize,
+        comparable::Comparable,
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration,
+    )]
+    #[repr(i32)]
+    pub enum Event {
+        Unspecified = 0,
+        /// A target version was set by an AdvanceSnsTargetVersion proposal.
+        TargetVersionSet = 1,
+        /// An upgrade from one version to the next was started.
+        UpgradeAttempted = 2,
+        /// An upgrade from one version to the next completed.
+        UpgradeSucceeded = 3,
+        /// An upgrade from one version to the next failed.
+        UpgradeFailed = 4,
+        /// The target version of a staged upgrade was deployed.
+        TargetVersionReached = 5,
+    }
+    impl Event {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Event::Unspecified => "EVENT_UNSPECIFIED",
+                Event::TargetVersionSet => "EVENT_TARGET_VERSION_SET",
+                Event::UpgradeAttempted => "EVENT_UPGRADE_ATTEMPTED",
+                Event::UpgradeSucceeded => "EVENT_UPGRADE_SUCCEEDED",
+                Event::UpgradeFailed => "EVENT_UPGRADE_FAILED",
+                Event::TargetVersionReached => "EVENT_TARGET_VERSION_REACHED",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "EVENT_UNSPECIFIED" => Some(Self::Unspecified),
+                "EVENT_TARGET_VERSION_SET" => Some(Self::TargetVersionSet),
+                "EVENT_UPGRADE_ATTEMPTED" => Some(Self::UpgradeAttempted),
+                "EVENT_UPGRADE_SUCCEEDED" => Some(Self::UpgradeSucceeded),
+                "EVENT_UPGRADE_FAILED" => Some(Self::UpgradeFailed),
+                "EVENT_TARGET_VERSION_REACHED" => Some(Self::TargetVersionReached),
+                _ => None,
+            }
+        }
+    }
+}
+/// Request for the upgrade journal of the SNS.
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetUpgradeJournalRequest {}
+/// Response with the upgrade journal of the SNS.
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetUpgradeJournalResponse {
+    /// The currently deployed version of the SNS.
+    #[prost(message, optional, tag = "1")]
+    pub deployed_version: ::core::option::Option<governance::Version>,
+    /// The staged upgrade that the SNS is performing, if any.
+    #[prost(message, optional, tag = "2")]
+    pub staged_upgrade: ::core::option::Option<governance::StagedUpgrade>,
+    /// The most recent upgrade events, oldest first.
+    #[prost(message, repeated, tag = "3")]
+    pub entries: ::prost::alloc::vec::Vec<UpgradeJournalEntry>,
+}
+/// Request to preview what an ExecuteGenericNervousSystemFunction proposal
+/// would do, without submitting it. Governance calls the function's validator
+/// and decodes the payload the same way it would at submission time.
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PreviewExecuteGenericNervousSystemFunctionRequest {
+    #[prost(message, optional, tag = "1")]
+    pub execute_generic_nervous_system_function:
+        ::core::option::Option<ExecuteGenericNervousSystemFunction>,
+}
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PreviewExecuteGenericNervousSystemFunctionResponse {
+    #[prost(
+        oneof = "preview_execute_generic_nervous_system_function_response::Result",
+        tags = "1, 2"
+    )]
+    pub result:
+        ::core::option::Option<preview_execute_generic_nervous_system_function_response::Result>,
+}
+/// Nested message and enum types in `PreviewExecuteGenericNervousSystemFunctionResponse`.
+pub mod preview_execute_generic_nervous_system_function_response {
+    #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Preview {
+        /// The rendering returned by the function's validator method.
+        #[prost(string, tag = "1")]
+        pub validator_rendering: ::prost::alloc::string::String,
+        /// The payload decoded as the arguments of the target method, in Candid
+        /// text format. Unset if the function has no target_candid_interface.
+        #[prost(string, optional, tag = "2")]
+        pub decoded_payload: ::core::option::Option<::prost::alloc::string::String>,
+        /// The current module hash of the target canister. A proposal submitted
+        /// now would only execute if the target still has this module hash.
+        #[prost(bytes = "vec", optional, tag = "3")]
+        pub target_canister_module_hash: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    }
+    #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+    #[allow(clippy::large_enum_variant)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Result {
+        #[prost(message, tag = "1")]
+        Error(super::GovernanceError),
+        #[prost(message, tag = "2")]
+        Preview(Preview),
+    }
+}
 /// Request to fail an upgrade proposal that is Adopted but not Executed or
 /// Failed if it is past the time when it should have been marked as failed.
 /// This is useful in the case where the asynchronous process may have failed to
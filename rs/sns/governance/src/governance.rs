@@ -4,7 +4,9 @@ use crate::pb::v1::{
 };
 use crate::{
     canister_control::{
-        get_canister_id, perform_execute_generic_nervous_system_function_call,
+        get_canister_id, get_canister_module_hash,
+        perform_execute_generic_nervous_system_function_call,
+        perform_execute_generic_nervous_system_function_validate_and_render_call,
         upgrade_canister_directly,
     },
     ledger::ICRC1Ledger,
@@ -38,11 +40,13 @@ use crate::{
                 DisburseMaturityResponse, MergeMaturityResponse, StakeMaturityResponse,
             },
             neuron::{DissolveState, Followees},
+            preview_execute_generic_nervous_system_function_response,
             proposal::Action,
             transfer_sns_treasury_funds::TransferFrom,
-            Account as AccountProto, Ballot, ClaimSwapNeuronsError, ClaimSwapNeuronsRequest,
-            ClaimSwapNeuronsResponse, ClaimedSwapNeuronStatus, DefaultFollowees,
-            DeregisterDappCanisters, DisburseMaturityInProgress, Empty,
+            upgrade_journal_entry::Event as UpgradeJournalEvent,
+            Account as AccountProto, AdvanceSnsTargetVersion, Ballot, ClaimSwapNeuronsError,
+            ClaimSwapNeuronsRequest, ClaimSwapNeuronsResponse, ClaimedSwapNeuronStatus,
+            DefaultFollowees, DeregisterDappCanisters, DisburseMaturityInProgress, Empty,
             ExecuteGenericNervousSystemFunction, FailStuckUpgradeInProgressRequest,
             FailStuckUpgradeInProgressResponse, GetMaturityModulationRequest,
             GetMaturityModulationResponse, GetMetadataRequest, GetMetadataResponse, GetMode,
@@ -53,10 +57,11 @@ use crate::{
             ManageDappCanisterSettings, ManageLedgerParameters, ManageNeuron, ManageNeuronResponse,
             ManageSnsMetadata, MintSnsTokens, NervousSystemFunction, NervousSystemParameters,
             Neuron, NeuronId, NeuronPermission, NeuronPermissionList, NeuronPermissionType,
-            Proposal, ProposalData, ProposalDecisionStatus, ProposalId, ProposalRewardStatus,
-            RegisterDappCanisters, RewardEvent, Tally, TransferSnsTreasuryFunds,
-            UpgradeSnsControlledCanister, UpgradeSnsToNextVersion, Vote, VotingRewardsParameters,
-            WaitForQuietState,
+            PreviewExecuteGenericNervousSystemFunctionRequest,
+            PreviewExecuteGenericNervousSystemFunctionResponse, Proposal, ProposalData,
+            ProposalDecisionStatus, ProposalId, ProposalRewardStatus, RegisterDappCanisters,
+            RewardEvent, Tally, TransferSnsTreasuryFunds, UpgradeSnsControlledCanister,
+            UpgradeSnsToNextVersion, Vote, VotingRewardsParameters, WaitForQuietState,
         },
     },
     proposal::{
@@ -2042,7 +2047,7 @@ impl Governance {
                 }
             }
             Action::ExecuteGenericNervousSystemFunction(call) => {
-                self.perform_execute_generic_nervous_system_function(call)
+                self.perform_execute_generic_nervous_system_function(proposal_id, call)
                     .await
             }
             Action::AddGenericNervousSystemFunction(nervous_system_function) => {
@@ -2074,6 +2079,20 @@ impl Governance {
                 self.perform_manage_dapp_canister_settings(manage_dapp_canister_settings)
                     .await
             }
+            Action::AdvanceSnsTargetVersion(advance_sns_target_version) => {
+                log!(INFO, "Executing AdvanceSnsTargetVersion action",);
+                // Like for UpgradeSnsToNextVersion, `Ok(false)` means that the proposal is
+                // marked as executed or failed later, by Governance's heartbeat logic, once
+                // the target version is reached or one of the upgrade steps failed.
+                match self
+                    .perform_advance_sns_target_version(proposal_id, advance_sns_target_version)
+                    .await
+                {
+                    Ok(true) => Ok(()),
+                    Ok(false) => return,
+                    Err(e) => Err(e),
+                }
+            }
             // This should not be possible, because Proposal validation is performed when
             // a proposal is first made.
             Action::Unspecified(_) => Err(GovernanceError::new_with_message(
@@ -2322,6 +2341,7 @@ impl Governance {
     /// Executes a (non-native) nervous system function as a result of an adopted proposal.
     async fn perform_execute_generic_nervous_system_function(
         &self,
+        proposal_id: u64,
         call: ExecuteGenericNervousSystemFunction,
     ) -> Result<(), GovernanceError> {
         match self
@@ -2337,16 +2357,161 @@ impl Governance {
                 ),
             )),
             Some(function) => {
-                perform_execute_generic_nervous_system_function_call(
-                    &*self.env,
-                    function.clone(),
-                    call,
-                )
-                .await
+                let function = function.clone();
+
+                // Refuse to execute the proposal if the target canister's code changed since the
+                // proposal was submitted, as voters then did not see what will be executed.
+                // Proposals submitted before module hashes were recorded are not checked.
+                let expected_module_hash = self
+                    .get_proposal_data(ProposalId { id: proposal_id })
+                    .and_then(|proposal_data| proposal_data.target_canister_module_hash.clone());
+                if let Some(expected_module_hash) = expected_module_hash {
+                    let target_canister_id = ValidGenericNervousSystemFunction::try_from(&function)
+                        .map_err(|e| {
+                            GovernanceError::new_with_message(ErrorType::InvalidProposal, e)
+                        })?
+                        .target_canister_id;
+                    let module_hash =
+                        get_canister_module_hash(&*self.env, target_canister_id).await?;
+                    if module_hash.as_ref() != Some(&expected_module_hash) {
+                        return Err(GovernanceError::new_with_message(
+                            ErrorType::PreconditionFailed,
+                            format!(
+                                "The module hash of target canister {} changed since the proposal \
+                                 was submitted. Expected {}, but found {}.",
+                                target_canister_id,
+                                hex::encode(&expected_module_hash),
+                                module_hash.map_or("no module".to_string(), hex::encode),
+                            ),
+                        ));
+                    }
+                }
+
+                perform_execute_generic_nervous_system_function_call(&*self.env, function, call)
+                    .await
             }
         }
     }
 
+    /// Returns the current module hash of the canister that an
+    /// ExecuteGenericNervousSystemFunction proposal would call, or None if the
+    /// proposal does not refer to a valid generic function (such proposals are
+    /// rejected by validation).
+    ///
+    /// Returns an error if the target canister has no code installed, since a
+    /// proposal could then not be pinned to the code that voters review.
+    async fn get_generic_function_target_module_hash(
+        &self,
+        call: &ExecuteGenericNervousSystemFunction,
+    ) -> Result<Option<Vec<u8>>, GovernanceError> {
+        let Some(valid_function) = self
+            .proto
+            .id_to_nervous_system_functions
+            .get(&call.function_id)
+            .and_then(|function| ValidGenericNervousSystemFunction::try_from(function).ok())
+        else {
+            return Ok(None);
+        };
+
+        let target_canister_id = valid_function.target_canister_id;
+        match get_canister_module_hash(&*self.env, target_canister_id).await? {
+            None => Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!(
+                    "Target canister {} of NervousSystemFunction {} has no code installed.",
+                    target_canister_id, valid_function.id
+                ),
+            )),
+            Some(module_hash) => Ok(Some(module_hash)),
+        }
+    }
+
+    /// Previews what an ExecuteGenericNervousSystemFunction proposal would show
+    /// voters, without submitting it: the validator's rendering of the payload,
+    /// the payload decoded using the function's target_candid_interface, and the
+    /// module hash that the proposal would be pinned to.
+    ///
+    /// This does not modify Governance's state, but needs to be called as an
+    /// update, as it calls the validator canister and the management canister.
+    pub async fn preview_execute_generic_nervous_system_function(
+        &self,
+        request: PreviewExecuteGenericNervousSystemFunctionRequest,
+    ) -> PreviewExecuteGenericNervousSystemFunctionResponse {
+        let result = match self
+            .preview_execute_generic_nervous_system_function_impl(request)
+            .await
+        {
+            Ok(preview) => {
+                preview_execute_generic_nervous_system_function_response::Result::Preview(preview)
+            }
+            Err(error) => {
+                preview_execute_generic_nervous_system_function_response::Result::Error(error)
+            }
+        };
+
+        PreviewExecuteGenericNervousSystemFunctionResponse {
+            result: Some(result),
+        }
+    }
+
+    async fn preview_execute_generic_nervous_system_function_impl(
+        &self,
+        request: PreviewExecuteGenericNervousSystemFunctionRequest,
+    ) -> Result<preview_execute_generic_nervous_system_function_response::Preview, GovernanceError>
+    {
+        let invalid_proposal =
+            |e: String| GovernanceError::new_with_message(ErrorType::InvalidProposal, e);
+
+        let call = request
+            .execute_generic_nervous_system_function
+            .ok_or_else(|| {
+                GovernanceError::new_with_message(
+                    ErrorType::InvalidCommand,
+                    "execute_generic_nervous_system_function must be set.",
+                )
+            })?;
+        let function = self
+            .proto
+            .id_to_nervous_system_functions
+            .get(&call.function_id)
+            .filter(|function| *function != &*NERVOUS_SYSTEM_FUNCTION_DELETION_MARKER)
+            .cloned()
+            .ok_or_else(|| {
+                GovernanceError::new_with_message(
+                    ErrorType::NotFound,
+                    format!(
+                        "There is no generic NervousSystemFunction with id: {}",
+                        call.function_id
+                    ),
+                )
+            })?;
+        let valid_function =
+            ValidGenericNervousSystemFunction::try_from(&function).map_err(invalid_proposal)?;
+
+        // As in make_proposal, look up the target's module hash before the validator runs.
+        let target_canister_module_hash =
+            self.get_generic_function_target_module_hash(&call).await?;
+        let validator_rendering =
+            perform_execute_generic_nervous_system_function_validate_and_render_call(
+                &*self.env,
+                function,
+                call.clone(),
+            )
+            .await
+            .map_err(invalid_proposal)?;
+        let decoded_payload = valid_function
+            .decode_payload(&call.payload)
+            .map_err(invalid_proposal)?;
+
+        Ok(
+            preview_execute_generic_nervous_system_function_response::Preview {
+                validator_rendering,
+                decoded_payload,
+                target_canister_module_hash,
+            },
+        )
+    }
+
     /// Executes a ManageNervousSystemParameters proposal by updating Governance's
     /// NervousSystemParameters
     fn perform_manage_nervous_system_parameters(
@@ -2495,12 +2660,7 @@ impl Governance {
         let current_version = self.proto.deployed_version_or_panic();
         let root_canister_id = self.proto.root_canister_id_or_panic();
 
-        let UpgradeSnsParams {
-            next_version,
-            canister_type_to_upgrade,
-            new_wasm_hash,
-            canister_ids_to_upgrade,
-        } = get_upgrade_params(&*self.env, root_canister_id, &current_version)
+        let upgrade_sns_params = get_upgrade_params(&*self.env, root_canister_id, &current_version)
             .await
             .map_err(|e| {
                 GovernanceError::new_with_message(
@@ -2509,6 +2669,71 @@ impl Governance {
                 )
             })?;
 
+        self.upgrade_sns_to_next_version(proposal_id, current_version, upgrade_sns_params)
+            .await
+    }
+
+    /// Upgrades the SNS from `current_version` to the next version, as described by
+    /// `upgrade_sns_params`, and records the upgrade in the upgrade journal.
+    ///
+    /// Return `Ok(true)` if the upgrade was completed successfully, return `Ok(false)` if an
+    /// upgrade was successfully kicked-off, but its completion is pending.
+    pub(crate) async fn upgrade_sns_to_next_version(
+        &mut self,
+        proposal_id: u64,
+        current_version: Version,
+        upgrade_sns_params: UpgradeSnsParams,
+    ) -> Result<bool, GovernanceError> {
+        let next_version = upgrade_sns_params.next_version.clone();
+        self.record_upgrade_journal_entry(
+            proposal_id,
+            UpgradeJournalEvent::UpgradeAttempted,
+            Some(current_version.clone()),
+            Some(next_version.clone()),
+            None,
+        );
+
+        let result = self
+            .start_upgrade_sns_canisters(proposal_id, upgrade_sns_params)
+            .await;
+
+        match &result {
+            Ok(true) => self.record_upgrade_journal_entry(
+                proposal_id,
+                UpgradeJournalEvent::UpgradeSucceeded,
+                Some(current_version),
+                Some(next_version),
+                None,
+            ),
+            // The outcome is recorded by check_upgrade_status.
+            Ok(false) => (),
+            Err(error) => self.record_upgrade_journal_entry(
+                proposal_id,
+                UpgradeJournalEvent::UpgradeFailed,
+                Some(current_version),
+                Some(next_version),
+                Some(error.error_message.clone()),
+            ),
+        }
+
+        result
+    }
+
+    /// Return `Ok(true)` if the upgrade was completed successfully, return `Ok(false)` if an
+    /// upgrade was successfully kicked-off, but its completion is pending.
+    async fn start_upgrade_sns_canisters(
+        &mut self,
+        proposal_id: u64,
+        upgrade_sns_params: UpgradeSnsParams,
+    ) -> Result<bool, GovernanceError> {
+        let root_canister_id = self.proto.root_canister_id_or_panic();
+        let UpgradeSnsParams {
+            next_version,
+            canister_type_to_upgrade,
+            new_wasm_hash,
+            canister_ids_to_upgrade,
+        } = upgrade_sns_params;
+
         // SNS Swap is controlled by NNS Governance, so this SNS instance cannot upgrade it.
         // Simply set `deployed_version` to `next_version` version so that other SNS upgrades can
         // be executed, and let the Swap upgrade occur externally (e.g. by someone submitting an
@@ -2967,8 +3192,25 @@ impl Governance {
     ) -> Result<ProposalId, GovernanceError> {
         let now_seconds = self.env.now();
 
+        // Look up the code of an ExecuteGenericNervousSystemFunction proposal's target before its
+        // validator renders the payload, so that the proposal can only be executed against the
+        // code that voters were shown.
+        let target_canister_module_hash = match &proposal.action {
+            Some(Action::ExecuteGenericNervousSystemFunction(call)) => {
+                self.get_generic_function_target_module_hash(call).await?
+            }
+            _ => None,
+        };
+
         // Validate proposal
-        let rendering = self.validate_and_render_proposal(proposal).await?;
+        let mut rendering = self.validate_and_render_proposal(proposal).await?;
+        if let Some(module_hash) = &target_canister_module_hash {
+            rendering = format!(
+                "{}\n\n## Target Canister Module Hash:\n\n{}",
+                rendering,
+                hex::encode(module_hash)
+            );
+        }
 
         // This should not panic, because the proposal was just validated.
         let action = proposal.action.as_ref().expect("No action.");
@@ -3172,6 +3414,7 @@ impl Governance {
                 .reward_event_end_timestamp_seconds,
             minimum_yes_proportion_of_total: Some(minimum_yes_proportion_of_total),
             minimum_yes_proportion_of_exercised: Some(minimum_yes_proportion_of_exercised),
+            target_canister_module_hash,
         };
 
         proposal_data.wait_for_quiet_state = Some(WaitForQuietState {
@@ -4556,6 +4799,8 @@ impl Governance {
             self.check_upgrade_status().await;
         }
 
+        self.maybe_advance_staged_upgrade().await;
+
         let should_distribute_rewards = self.should_distribute_rewards();
 
         // Getting the total governance token supply from the ledger is expensive enough
@@ -5225,7 +5470,18 @@ impl Governance {
                     self.env.now(),
                     target_version
                 );
-                self.set_proposal_execution_status(proposal_id, Ok(()));
+                self.record_upgrade_journal_entry(
+                    proposal_id,
+                    UpgradeJournalEvent::UpgradeSucceeded,
+                    Some(deployed_version),
+                    Some(target_version.clone()),
+                    None,
+                );
+                // The proposal of a staged upgrade is only executed once its target version
+                // is reached (see maybe_advance_staged_upgrade).
+                if !self.is_staged_upgrade_proposal(proposal_id) {
+                    self.set_proposal_execution_status(proposal_id, Ok(()));
+                }
                 self.proto.deployed_version = Some(target_version);
                 self.proto.pending_version = None;
             }
@@ -5250,12 +5506,26 @@ impl Governance {
 
     // This method sets internal state to remove pending_version and sets the proposal status to
     // an error for an UpgradeSnsToNextVersion actions failure.  This unblocks further upgrade proposals.
+    // If the upgrade was a step of a staged upgrade, the staged upgrade is abandoned.
     fn fail_sns_upgrade_to_next_version_proposal(
         &mut self,
         proposal_id: u64,
         error: GovernanceError,
     ) {
         log!(ERROR, "{}", error.error_message);
+        self.record_upgrade_journal_entry(
+            proposal_id,
+            UpgradeJournalEvent::UpgradeFailed,
+            self.proto.deployed_version.clone(),
+            self.proto
+                .pending_version
+                .as_ref()
+                .and_then(|pending_version| pending_version.target_version.clone()),
+            Some(error.error_message.clone()),
+        );
+        if self.is_staged_upgrade_proposal(proposal_id) {
+            self.proto.staged_upgrade = None;
+        }
         let result = Err(error);
         self.set_proposal_execution_status(proposal_id, result);
         self.proto.pending_version = None;
@@ -5501,14 +5771,15 @@ thread_local! {
     static ATTEMPTED_FIXING_MEMORY_ALLOCATIONS: RefCell<bool> = RefCell::new(false);
 }
 
-fn err_if_another_upgrade_is_in_progress(
+pub(crate) fn err_if_another_upgrade_is_in_progress(
     id_to_proposal_data: &BTreeMap</* proposal ID */ u64, ProposalData>,
     executing_proposal_id: u64,
 ) -> Result<(), GovernanceError> {
-    let upgrade_action_ids: [u64; 3] = [
+    let upgrade_action_ids: [u64; 4] = [
         (&Action::UpgradeSnsControlledCanister(UpgradeSnsControlledCanister::default())).into(),
         (&Action::UpgradeSnsToNextVersion(UpgradeSnsToNextVersion::default())).into(),
         (&Action::ManageLedgerParameters(ManageLedgerParameters::default())).into(),
+        (&Action::AdvanceSnsTargetVersion(AdvanceSnsTargetVersion::default())).into(),
     ];
 
     for (other_proposal_id, proposal_data) in id_to_proposal_data {
@@ -5593,9 +5864,9 @@ mod tests {
             governance::SnsMetadata,
             manage_neuron_response,
             nervous_system_function::{FunctionType, GenericNervousSystemFunction},
-            neuron, Account as AccountProto, Motion, NeuronPermissionType, ProposalData,
-            ProposalId, Tally, UpgradeSnsControlledCanister, UpgradeSnsToNextVersion,
-            VotingRewardsParameters, WaitForQuietState,
+            neuron, Account as AccountProto, GetUpgradeJournalRequest, Motion,
+            NeuronPermissionType, ProposalData, ProposalId, Tally, UpgradeSnsControlledCanister,
+            UpgradeSnsToNextVersion, VotingRewardsParameters, WaitForQuietState,
         },
         reward,
         sns_upgrade::{
@@ -5990,6 +6261,7 @@ mod tests {
                         target_method_name: Some("test_method".to_string()),
                         validator_canister_id: Some(CanisterId::from_u64(1).get()),
                         validator_method_name: Some("test_validator_method".to_string()),
+                        target_candid_interface: None,
                     },
                 )),
             },
@@ -6811,6 +7083,14 @@ mod tests {
         test_disallow_concurrent_upgrade_execution(executing_action_id, action);
     }
 
+    #[test]
+    fn staged_upgrades_block_concurrent_sns_upgrades() {
+        let executing_action_id =
+            (&Action::AdvanceSnsTargetVersion(AdvanceSnsTargetVersion::default())).into();
+        let action = Action::UpgradeSnsToNextVersion(UpgradeSnsToNextVersion::default());
+        test_disallow_concurrent_upgrade_execution(executing_action_id, action);
+    }
+
     #[test]
     fn manage_ledger_parameters_block_concurrent_canister_upgrades() {
         let executing_action_id =
@@ -7669,6 +7949,130 @@ mod tests {
         assert!(proposal_data.failure_reason.is_none(),);
     }
 
+    #[test]
+    fn test_staged_upgrade_executes_proposal_once_target_version_is_reached() {
+        let root_canister_id = *TEST_ROOT_CANISTER_ID;
+        let governance_canister_id = *TEST_GOVERNANCE_CANISTER_ID;
+        let next_version = SnsVersion {
+            root_wasm_hash: vec![1, 2, 3],
+            governance_wasm_hash: vec![2, 3, 4],
+            ledger_wasm_hash: vec![3, 4, 5],
+            swap_wasm_hash: vec![4, 5, 6],
+            archive_wasm_hash: vec![5, 6, 7],
+            index_wasm_hash: vec![6, 7, 8],
+        };
+
+        let mut env = NativeEnvironment::new(Some(governance_canister_id));
+        // We set a status that matches the last step of the staged upgrade
+        env.set_call_canister_response(
+            root_canister_id,
+            "get_sns_canisters_summary",
+            Encode!(&GetSnsCanistersSummaryRequest {
+                update_canister_list: Some(true)
+            })
+            .unwrap(),
+            Ok(Encode!(&std_sns_canisters_summary_response()).unwrap()),
+        );
+
+        let current_version = {
+            let mut version = next_version.clone();
+            version.archive_wasm_hash = vec![1, 1, 1];
+            version
+        };
+
+        let now = env.now();
+        let proposal_id = 12;
+        let action = Action::AdvanceSnsTargetVersion(AdvanceSnsTargetVersion {
+            new_target: Some(next_version.clone().into()),
+        });
+        let mut governance = Governance::new(
+            GovernanceProto {
+                root_canister_id: Some(root_canister_id.get()),
+                deployed_version: Some(current_version.clone().into()),
+                pending_version: Some(UpgradeInProgress {
+                    target_version: Some(next_version.clone().into()),
+                    mark_failed_at_seconds: now + 5 * 60,
+                    checking_upgrade_lock: 0,
+                    proposal_id,
+                }),
+                staged_upgrade: Some(governance::StagedUpgrade {
+                    proposal_id,
+                    upgrade_steps: vec![
+                        current_version.clone().into(),
+                        next_version.clone().into(),
+                    ],
+                }),
+                // The proposal is already decided, and stays adopted until the staged upgrade
+                // is over.
+                proposals: btreemap! {
+                    proposal_id => ProposalData {
+                        action: (&action).into(),
+                        id: Some(proposal_id.into()),
+                        ballots: btreemap! {
+                            "neuron 1".to_string() => Ballot {
+                                vote: Vote::Yes as i32,
+                                voting_power: 9001,
+                                cast_timestamp_seconds: 1,
+                            },
+                        },
+                        wait_for_quiet_state: Some(WaitForQuietState::default()),
+                        decided_timestamp_seconds: now,
+                        proposal: Some(Proposal {
+                            title: "Advance Target Version Proposal".to_string(),
+                            action: Some(action),
+                            ..Default::default()
+                        }),
+                        latest_tally: Some(Tally {
+                            timestamp_seconds: now,
+                            yes: 100000000,
+                            no: 0,
+                            total: 100000000
+                        }),
+                        ..Default::default()
+                    }
+                },
+                ..basic_governance_proto()
+            }
+            .try_into()
+            .unwrap(),
+            Box::new(env),
+            Box::new(DoNothingLedger {}),
+            Box::new(DoNothingLedger {}),
+            Box::new(FakeCmc::new()),
+        );
+
+        // The step is checked, and the staged upgrade then finds the target version deployed.
+        governance.heartbeat().now_or_never();
+
+        assert!(governance.proto.pending_version.is_none());
+        assert!(governance.proto.staged_upgrade.is_none());
+        assert_eq!(
+            governance.proto.deployed_version.clone().unwrap(),
+            next_version.clone().into()
+        );
+        let proposal_data = governance.get_proposal_data(proposal_id).unwrap();
+        assert_ne!(proposal_data.executed_timestamp_seconds, 0);
+        assert!(proposal_data.failure_reason.is_none());
+
+        let journal = governance.get_upgrade_journal(GetUpgradeJournalRequest {});
+        assert_eq!(
+            journal
+                .entries
+                .iter()
+                .map(|entry| (entry.event(), entry.proposal_id))
+                .collect::<Vec<_>>(),
+            vec![
+                (UpgradeJournalEvent::UpgradeSucceeded, proposal_id),
+                (UpgradeJournalEvent::TargetVersionReached, proposal_id),
+            ]
+        );
+        assert_eq!(
+            journal.entries[0].from_version,
+            Some(current_version.into())
+        );
+        assert_eq!(journal.entries[0].to_version, Some(next_version.into()));
+    }
+
     #[test]
     fn test_check_upgrade_not_yet_failed_if_canister_summary_errs_and_before_mark_failed_at_time() {
         let root_canister_id = *TEST_ROOT_CANISTER_ID;
@@ -8654,12 +9058,95 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from(100).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
         assert_is_ok!(governance.perform_add_generic_nervous_system_function(valid));
     }
 
+    #[test]
+    fn test_execute_generic_nervous_system_function_fails_if_target_module_hash_changed() {
+        let governance_canister_id = *TEST_GOVERNANCE_CANISTER_ID;
+        let target_canister_id = CanisterId::from(200);
+        let submitted_module_hash = vec![1_u8; 32];
+        let current_module_hash = vec![2_u8; 32];
+
+        let mut env = NativeEnvironment::new(Some(governance_canister_id));
+        env.set_call_canister_response(
+            CanisterId::ic_00(),
+            "canister_info",
+            candid::encode_one(CanisterInfoRequest::new(target_canister_id, None)).unwrap(),
+            Ok(candid::encode_one(CanisterInfoResponse::new(
+                1,
+                vec![],
+                Some(current_module_hash.clone()),
+                vec![governance_canister_id.get()],
+            ))
+            .unwrap()),
+        );
+
+        let function = NervousSystemFunction {
+            id: 1000,
+            name: "a".to_string(),
+            description: None,
+            function_type: Some(FunctionType::GenericNervousSystemFunction(
+                GenericNervousSystemFunction {
+                    target_canister_id: Some(target_canister_id.get()),
+                    target_method_name: Some("test_method".to_string()),
+                    validator_canister_id: Some(CanisterId::from(100).get()),
+                    validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
+                },
+            )),
+        };
+        let proposal_data = |target_canister_module_hash| ProposalData {
+            id: Some(ProposalId { id: 1 }),
+            target_canister_module_hash,
+            ..Default::default()
+        };
+        let mut governance = Governance::new(
+            GovernanceProto {
+                id_to_nervous_system_functions: btreemap! { 1000 => function },
+                proposals: btreemap! { 1 => proposal_data(Some(submitted_module_hash)) },
+                ..basic_governance_proto()
+            }
+            .try_into()
+            .unwrap(),
+            Box::new(env),
+            Box::new(DoNothingLedger {}),
+            Box::new(DoNothingLedger {}),
+            Box::new(FakeCmc::new()),
+        );
+        let call = ExecuteGenericNervousSystemFunction {
+            function_id: 1000,
+            payload: vec![],
+        };
+
+        let err = governance
+            .perform_execute_generic_nervous_system_function(1, call.clone())
+            .now_or_never()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.error_type, ErrorType::PreconditionFailed as i32);
+        assert!(
+            err.error_message
+                .contains("changed since the proposal was submitted"),
+            "{}",
+            err.error_message
+        );
+
+        // Once the recorded module hash matches, the target method is called.
+        governance
+            .proto
+            .proposals
+            .insert(1, proposal_data(Some(current_module_hash)));
+        assert_is_ok!(governance
+            .perform_execute_generic_nervous_system_function(1, call)
+            .now_or_never()
+            .unwrap());
+    }
+
     fn default_governance_with_proto(governance_proto: GovernanceProto) -> Governance {
         Governance::new(
             governance_proto
@@ -9637,6 +10124,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
@@ -9659,6 +10147,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(invalid_canister_target.get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
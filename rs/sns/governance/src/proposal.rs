@@ -8,16 +8,19 @@ use crate::{
         proposal,
         proposal::Action,
         transfer_sns_treasury_funds::TransferFrom,
-        DeregisterDappCanisters, ExecuteGenericNervousSystemFunction, Governance, LogVisibility,
-        ManageDappCanisterSettings, ManageLedgerParameters, ManageSnsMetadata, MintSnsTokens,
-        Motion, NervousSystemFunction, NervousSystemParameters, Proposal, ProposalData,
-        ProposalDecisionStatus, ProposalRewardStatus, RegisterDappCanisters, Tally,
-        TransferSnsTreasuryFunds, UpgradeSnsControlledCanister, UpgradeSnsToNextVersion, Vote,
+        AdvanceSnsTargetVersion, DeregisterDappCanisters, ExecuteGenericNervousSystemFunction,
+        Governance, LogVisibility, ManageDappCanisterSettings, ManageLedgerParameters,
+        ManageSnsMetadata, MintSnsTokens, Motion, NervousSystemFunction, NervousSystemParameters,
+        Proposal, ProposalData, ProposalDecisionStatus, ProposalRewardStatus,
+        RegisterDappCanisters, Tally, TransferSnsTreasuryFunds, UpgradeSnsControlledCanister,
+        UpgradeSnsToNextVersion, Vote,
     },
-    sns_upgrade::{get_upgrade_params, UpgradeSnsParams},
+    sns_upgrade::{get_upgrade_params, get_upgrade_path, UpgradeSnsParams},
     types::{Environment, DEFAULT_TRANSFER_FEE},
     validate_chars_count, validate_len, validate_required_field,
 };
+use candid::types::{Function, TypeEnv};
+use candid_parser::{utils::CandidSource, IDLArgs};
 use dfn_core::api::CanisterId;
 use ic_base_types::PrincipalId;
 use ic_canister_log::log;
@@ -51,6 +54,10 @@ pub const MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS: usize = 700;
 /// The maximum number of GenericNervousSystemFunctions the system allows.
 pub const MAX_NUMBER_OF_GENERIC_NERVOUS_SYSTEM_FUNCTIONS: usize = 200_000;
 
+/// The maximum number of bytes in a GenericNervousSystemFunction's
+/// target_candid_interface.
+pub const MAX_TARGET_CANDID_INTERFACE_BYTES: usize = 30_000;
+
 /// The maximum number of dapps that can be managed in a single
 /// proposal (RegisterDappCanisters, DeregisterDappCanisters,
 /// or ManageDappCanisterSettings).
@@ -256,6 +263,16 @@ pub async fn validate_and_render_action(
         proposal::Action::ManageDappCanisterSettings(manage_dapp_canister_settings) => {
             validate_and_render_manage_dapp_canister_settings(manage_dapp_canister_settings)
         }
+        proposal::Action::AdvanceSnsTargetVersion(advance_sns_target_version) => {
+            let current_version = governance_proto.deployed_version_or_panic();
+
+            validate_and_render_advance_sns_target_version(
+                advance_sns_target_version,
+                env,
+                current_version,
+            )
+            .await
+        }
     }
 }
 
@@ -569,6 +586,63 @@ async fn validate_and_render_upgrade_sns_to_next_version(
     ))
 }
 
+/// Validates and renders a proposal with action AdvanceSnsTargetVersion.
+async fn validate_and_render_advance_sns_target_version(
+    advance_sns_target_version: &AdvanceSnsTargetVersion,
+    env: &dyn Environment,
+    current_version: Version,
+) -> Result<String, String> {
+    let new_target = advance_sns_target_version
+        .new_target
+        .as_ref()
+        .ok_or_else(|| "AdvanceSnsTargetVersion must specify a new target.".to_string())?;
+
+    let upgrade_path = get_upgrade_path(env, &current_version, new_target)
+        .await
+        .map_err(|e| {
+            format!(
+                "AdvanceSnsTargetVersion was invalid for the following reason: {}\n",
+                e
+            )
+        })?;
+
+    if upgrade_path.len() == 1 {
+        return Err(
+            "AdvanceSnsTargetVersion was invalid for the following reason: \
+             The new target is the current SNS version.\n"
+                .to_string(),
+        );
+    }
+
+    let mut rendered_steps = String::new();
+    for (step, version) in upgrade_path.iter().enumerate().skip(1) {
+        write!(
+            rendered_steps,
+            "\n### Step {}:\n{}\n",
+            step,
+            render_version(version)
+        )
+        .unwrap();
+    }
+
+    Ok(format!(
+        r"# Proposal to advance the SNS target version:
+
+## SNS Current Version:
+{}
+
+## SNS Target Version:
+{}
+
+## Upgrade Steps: {}
+{}",
+        render_version(&current_version),
+        render_version(new_target),
+        upgrade_path.len() - 1,
+        rendered_steps,
+    ))
+}
+
 #[derive(Debug)]
 pub(crate) struct ValidGenericNervousSystemFunction {
     pub id: u64,
@@ -576,6 +650,7 @@ pub(crate) struct ValidGenericNervousSystemFunction {
     pub target_method: String,
     pub validator_canister_id: CanisterId,
     pub validator_method: String,
+    pub target_candid_interface: Option<String>,
 }
 
 /// Validates a given canister id and adds a defect to a given list of defects if the there was no
@@ -596,6 +671,48 @@ fn validate_canister_id(
 
 impl ValidGenericNervousSystemFunction {
     pub const MIN_ID: u64 = 1000;
+
+    /// Decodes `payload` as the arguments of the target method, using the
+    /// function's target_candid_interface, and renders them in Candid text
+    /// format. Returns None if the function has no target_candid_interface.
+    pub fn decode_payload(&self, payload: &[u8]) -> Result<Option<String>, String> {
+        let Some(target_candid_interface) = &self.target_candid_interface else {
+            return Ok(None);
+        };
+
+        let (env, method) = load_candid_method(target_candid_interface, &self.target_method)?;
+        let args = IDLArgs::from_bytes_with_types(payload, &env, &method.args).map_err(|e| {
+            format!(
+                "The payload could not be decoded as the arguments of method {}: {}",
+                self.target_method, e
+            )
+        })?;
+
+        Ok(Some(args.to_string()))
+    }
+}
+
+/// Parses a Candid service definition and looks up one of its methods.
+fn load_candid_method(
+    candid_interface: &str,
+    method_name: &str,
+) -> Result<(TypeEnv, Function), String> {
+    let (env, actor) = CandidSource::Text(candid_interface)
+        .load()
+        .map_err(|e| format!("target_candid_interface could not be parsed: {}", e))?;
+    let actor =
+        actor.ok_or_else(|| "target_candid_interface does not define a service.".to_string())?;
+    let method = env
+        .get_method(&actor, method_name)
+        .map_err(|e| {
+            format!(
+                "target_candid_interface does not define method {}: {}",
+                method_name, e
+            )
+        })?
+        .clone();
+
+    Ok((env, method))
 }
 
 impl TryFrom<&NervousSystemFunction> for ValidGenericNervousSystemFunction {
@@ -647,6 +764,7 @@ impl TryFrom<&NervousSystemFunction> for ValidGenericNervousSystemFunction {
                 target_method_name,
                 validator_canister_id,
                 validator_method_name,
+                target_candid_interface,
             })) => {
                 // Validate the target_canister_id field.
                 let target_canister_id =
@@ -670,6 +788,22 @@ impl TryFrom<&NervousSystemFunction> for ValidGenericNervousSystemFunction {
                     defects.push("validator_method_name was empty.".to_string());
                 }
 
+                // Validate the target_candid_interface field, if present.
+                if let Some(target_candid_interface) = target_candid_interface {
+                    if target_candid_interface.len() > MAX_TARGET_CANDID_INTERFACE_BYTES {
+                        defects.push(format!(
+                            "target_candid_interface must be at most {} bytes.",
+                            MAX_TARGET_CANDID_INTERFACE_BYTES
+                        ));
+                    } else if let Some(target_method_name) = target_method_name {
+                        if let Err(e) =
+                            load_candid_method(target_candid_interface, target_method_name)
+                        {
+                            defects.push(e);
+                        }
+                    }
+                }
+
                 if !defects.is_empty() {
                     return Err(format!(
                         "ExecuteNervousSystemFunction was invalid for the following reason(s):\n{}",
@@ -683,6 +817,7 @@ impl TryFrom<&NervousSystemFunction> for ValidGenericNervousSystemFunction {
                     target_method: target_method_name.as_ref().unwrap().clone(),
                     validator_canister_id: validator_canister_id.unwrap(),
                     validator_method: validator_method_name.as_ref().unwrap().clone(),
+                    target_candid_interface: target_candid_interface.clone(),
                 })
             }
             _ => {
@@ -777,6 +912,15 @@ pub async fn validate_and_render_execute_nervous_system_function(
                     )
                     .await?;
 
+                // If the function's target registered its Candid interface, also show
+                // voters what the target method will actually be called with.
+                let decoded_payload = ValidGenericNervousSystemFunction::try_from(function)?
+                    .decode_payload(&execute.payload)?
+                    .map(|decoded_payload| {
+                        format!("\n\n## Decoded Payload:\n\n{}", decoded_payload)
+                    })
+                    .unwrap_or_default();
+
                 Ok(format!(
                     r"# Proposal to execute nervous system function:
 
@@ -786,8 +930,8 @@ pub async fn validate_and_render_execute_nervous_system_function(
 
 ## Payload:
 
-{}",
-                    function, rendering
+{}{}",
+                    function, rendering, decoded_payload
                 ))
             }
         }
@@ -1665,6 +1809,8 @@ mod tests {
             sns_initialization_parameters: "".to_string(),
             is_finalizing_disburse_maturity: None,
             maturity_modulation: None,
+            staged_upgrade: None,
+            upgrade_journal: vec![],
         }
     }
 
@@ -1930,6 +2076,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from_u64(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
@@ -2127,6 +2274,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from_u64(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
@@ -2173,6 +2321,7 @@ mod tests {
                         target_method_name: Some("test_method".to_string()),
                         validator_canister_id: Some(CanisterId::from_u64(i as u64).get()),
                         validator_method_name: Some("test_validator_method".to_string()),
+                        target_candid_interface: None,
                     },
                 )),
             };
@@ -2189,6 +2338,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from_u64(u64::MAX).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
@@ -2548,6 +2698,108 @@ Version {
         assert!(err.contains("Did not receive Root CanisterId from list_sns_canisters call"))
     }
 
+    #[test]
+    fn advance_sns_target_version_renders_upgrade_steps() {
+        let (env, governance_proto) = setup_for_upgrade_sns_to_next_version_validation_tests();
+        let current_version = governance_proto.deployed_version.clone().unwrap();
+        let new_target = Version {
+            root_wasm_hash: Sha256::hash(&[6]).to_vec(),
+            ..current_version
+        };
+        let action = Action::AdvanceSnsTargetVersion(AdvanceSnsTargetVersion {
+            new_target: Some(new_target),
+        });
+
+        let actual_text = validate_and_render_action(
+            &Some(action),
+            &env,
+            &governance_proto,
+            vec![FORBIDDEN_CANISTER],
+        )
+        .now_or_never()
+        .unwrap()
+        .unwrap();
+
+        let new_version_text = r"Version {
+    root: 67586e98fad27da0b9968bc039a1ef34c939b9b8e523a8bef89d478608c5ecf6,
+    governance: dbc1b4c900ffe48d575b5da5c638040125f65db0fe3e24494b76ea986457d986,
+    ledger: 084fed08b978af4d7d196a7446a86b58009e636b611db16211b65a9aadff29c5,
+    swap: e52d9c508c502347344d8c07ad91cbd6068afc75ff6292f062a09ca381c89e71,
+    archive: e77b9a9ae9e30b0dbdb6f510a264ef9de781501d7b6b92ae89eb059c5ab743db,
+    index: ca358758f6d27e6cf45272937977a748fd88391db679ceda7dc7bf1f005ee879,
+}";
+        let expected_text = format!(
+            r"# Proposal to advance the SNS target version:
+
+## SNS Current Version:
+Version {{
+    root: 4bf5122f344554c53bde2ebb8cd2b7e3d1600ad631c385a5d7cce23c7785459a,
+    governance: dbc1b4c900ffe48d575b5da5c638040125f65db0fe3e24494b76ea986457d986,
+    ledger: 084fed08b978af4d7d196a7446a86b58009e636b611db16211b65a9aadff29c5,
+    swap: e52d9c508c502347344d8c07ad91cbd6068afc75ff6292f062a09ca381c89e71,
+    archive: e77b9a9ae9e30b0dbdb6f510a264ef9de781501d7b6b92ae89eb059c5ab743db,
+    index: ca358758f6d27e6cf45272937977a748fd88391db679ceda7dc7bf1f005ee879,
+}}
+
+## SNS Target Version:
+{new_version_text}
+
+## Upgrade Steps: 1
+
+### Step 1:
+{new_version_text}
+"
+        );
+        assert_eq!(actual_text, expected_text);
+    }
+
+    #[test]
+    fn fail_validation_for_advance_sns_target_version_when_target_is_not_on_upgrade_path() {
+        let (mut env, governance_proto) = setup_for_upgrade_sns_to_next_version_validation_tests();
+        let current_version = governance_proto.deployed_version.clone().unwrap();
+        let next_version = Version {
+            root_wasm_hash: Sha256::hash(&[6]).to_vec(),
+            ..current_version.clone()
+        };
+        env.set_call_canister_response(
+            SNS_WASM_CANISTER_ID,
+            "get_next_sns_version",
+            Encode!(&GetNextSnsVersionRequest {
+                current_version: Some(next_version.into())
+            })
+            .unwrap(),
+            Ok(Encode!(&GetNextSnsVersionResponse { next_version: None }).unwrap()),
+        );
+
+        for (new_target, expected_error) in [
+            (None, "must specify a new target"),
+            (
+                Some(current_version.clone()),
+                "The new target is the current SNS version",
+            ),
+            (
+                Some(Version {
+                    governance_wasm_hash: Sha256::hash(&[42]).to_vec(),
+                    ..current_version
+                }),
+                "is not on the upgrade path",
+            ),
+        ] {
+            let action = Action::AdvanceSnsTargetVersion(AdvanceSnsTargetVersion { new_target });
+            let err = validate_and_render_action(
+                &Some(action),
+                &env,
+                &governance_proto,
+                vec![FORBIDDEN_CANISTER],
+            )
+            .now_or_never()
+            .unwrap()
+            .unwrap_err();
+
+            assert!(err.contains(expected_error), "{}", err);
+        }
+    }
+
     #[test]
     fn fail_validate_manage_sns_metadata() {
         let manage_sns_metadata = ManageSnsMetadata {
@@ -2588,6 +2840,7 @@ Version {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
@@ -2609,6 +2862,7 @@ Version {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
@@ -2628,6 +2882,7 @@ Version {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::ic_00().get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
@@ -2639,6 +2894,124 @@ Version {
         ));
     }
 
+    fn generic_function_with_candid_interface(
+        target_candid_interface: Option<String>,
+    ) -> NervousSystemFunction {
+        NervousSystemFunction {
+            id: 1000,
+            name: "a".to_string(),
+            description: None,
+            function_type: Some(FunctionType::GenericNervousSystemFunction(
+                GenericNervousSystemFunction {
+                    target_canister_id: Some(CanisterId::from(2).get()),
+                    target_method_name: Some("set_greeting".to_string()),
+                    validator_canister_id: Some(CanisterId::from(1).get()),
+                    validator_method_name: Some("validate_set_greeting".to_string()),
+                    target_candid_interface,
+                },
+            )),
+        }
+    }
+
+    #[test]
+    fn add_nervous_system_function_validates_target_candid_interface() {
+        let functions_map = BTreeMap::new();
+        let validate = |target_candid_interface: &str| {
+            validate_and_render_add_generic_nervous_system_function(
+                &hashset![FORBIDDEN_CANISTER],
+                &generic_function_with_candid_interface(Some(target_candid_interface.to_string())),
+                &functions_map,
+            )
+        };
+
+        assert_is_ok(validate(
+            "service : { set_greeting : (text, nat32) -> (); greet : () -> (text) query }",
+        ));
+
+        let err = validate("service : { greet : () -> (text) query }").unwrap_err();
+        assert!(
+            err.contains("does not define method set_greeting"),
+            "{}",
+            err
+        );
+
+        let err = validate("service : { set_greeting : (text) -> (").unwrap_err();
+        assert!(err.contains("could not be parsed"), "{}", err);
+
+        let err = validate(&"x".repeat(MAX_TARGET_CANDID_INTERFACE_BYTES + 1)).unwrap_err();
+        assert!(err.contains("must be at most"), "{}", err);
+    }
+
+    #[test]
+    fn execute_nervous_system_function_renders_decoded_payload() {
+        let function = generic_function_with_candid_interface(Some(
+            "service : { set_greeting : (text, nat32) -> () }".to_string(),
+        ));
+        let functions = btreemap! { 1000 => function };
+        let payload = Encode!(&"hello", &42_u32).unwrap();
+
+        let mut env = NativeEnvironment::new(Some(*SNS_GOVERNANCE_CANISTER_ID));
+        env.set_call_canister_response(
+            CanisterId::from(1),
+            "validate_set_greeting",
+            payload.clone(),
+            Ok(Encode!(&Result::<String, String>::Ok(
+                "Set the greeting.".to_string()
+            ))
+            .unwrap()),
+        );
+
+        let rendering = validate_and_render_execute_nervous_system_function(
+            &env,
+            &ExecuteGenericNervousSystemFunction {
+                function_id: 1000,
+                payload,
+            },
+            &functions,
+        )
+        .now_or_never()
+        .unwrap()
+        .unwrap();
+
+        assert!(
+            rendering.ends_with(
+                r#"## Payload:
+
+Set the greeting.
+
+## Decoded Payload:
+
+("hello", 42 : nat32)"#
+            ),
+            "{}",
+            rendering
+        );
+
+        // A payload that does not match the target method's arguments is rejected.
+        let payload = Encode!(&"hello").unwrap();
+        env.set_call_canister_response(
+            CanisterId::from(1),
+            "validate_set_greeting",
+            payload.clone(),
+            Ok(Encode!(&Result::<String, String>::Ok(
+                "Set the greeting.".to_string()
+            ))
+            .unwrap()),
+        );
+        let err = validate_and_render_execute_nervous_system_function(
+            &env,
+            &ExecuteGenericNervousSystemFunction {
+                function_id: 1000,
+                payload,
+            },
+            &functions,
+        )
+        .now_or_never()
+        .unwrap()
+        .unwrap_err();
+        assert!(err.contains("could not be decoded"), "{}", err);
+    }
+
     #[test]
     fn validate_and_render_transfer_sns_treasury_funds_renders_for_valid_inputs() {
         // Valid case
@@ -3210,6 +3583,7 @@ Version {
             ballots: btreemap!{},
             minimum_yes_proportion_of_total: None,
             minimum_yes_proportion_of_exercised: None,
+            target_canister_module_hash: None,
             failed_timestamp_seconds: 0,
             proposal_creation_timestamp_seconds: 1670488610, // 2022-12-08T08:36:50Z (Thu)
             initial_voting_period_seconds: 345_600, // 4 days
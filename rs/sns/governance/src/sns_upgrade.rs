@@ -1,8 +1,36 @@
-use crate::{pb::v1::governance::Version, proposal::render_version, types::Environment};
+use crate::{
+    governance::{err_if_another_upgrade_is_in_progress, Governance},
+    logs::{ERROR, INFO},
+    pb::v1::{
+        governance::{StagedUpgrade, Version},
+        governance_error::ErrorType,
+        upgrade_journal_entry::Event as UpgradeJournalEvent,
+        AdvanceSnsTargetVersion, GetUpgradeJournalRequest, GetUpgradeJournalResponse,
+        GovernanceError, UpgradeJournalEntry,
+    },
+    proposal::render_version,
+    types::Environment,
+};
 use candid::{Decode, Encode};
 use ic_base_types::{CanisterId, PrincipalId};
+use ic_canister_log::log;
 use ic_nervous_system_clients::canister_status::CanisterStatusResultV2;
 use ic_nns_constants::SNS_WASM_CANISTER_ID;
+use std::cell::Cell;
+
+/// The maximum number of upgrade steps between the deployed version and the target version of
+/// an AdvanceSnsTargetVersion proposal.
+pub const MAX_UPGRADE_PATH_LENGTH: usize = 100;
+
+/// The maximum number of entries kept in the upgrade journal. When there are more, the oldest
+/// entries are removed.
+pub const MAX_UPGRADE_JOURNAL_ENTRIES: usize = 1_000;
+
+thread_local! {
+    // Set while a step of a staged upgrade is being started, so that concurrent heartbeats do
+    // not start the same step twice.
+    static IS_STARTING_STAGED_UPGRADE_STEP: Cell<bool> = Cell::new(false);
+}
 
 /// A struct to represent all the types of SNS canisters Governance knows about.
 pub struct RunningSnsCanisters {
@@ -38,6 +66,17 @@ pub(crate) async fn get_upgrade_params(
         }
     };
 
+    get_upgrade_params_for_step(env, root_canister_id, current_version, next_version).await
+}
+
+/// Like `get_upgrade_params`, but for an upgrade to a known `next_version`, which must differ
+/// from `current_version` in the hash of exactly one canister type.
+pub(crate) async fn get_upgrade_params_for_step(
+    env: &dyn Environment,
+    root_canister_id: CanisterId,
+    current_version: &Version,
+    next_version: Version,
+) -> Result<UpgradeSnsParams, String> {
     let (canister_type_to_upgrade, new_wasm_hash) =
         canister_type_and_wasm_hash_for_upgrade(current_version, &next_version)?;
 
@@ -258,6 +297,41 @@ async fn get_next_version(env: &dyn Environment, current_version: &Version) -> O
     response.next_version.map(|v| v.into())
 }
 
+/// Returns the versions on the upgrade path from `current_version` to `target_version`, both
+/// included, following the upgrade path published by SNS-W one version at a time.
+pub(crate) async fn get_upgrade_path(
+    env: &dyn Environment,
+    current_version: &Version,
+    target_version: &Version,
+) -> Result<Vec<Version>, String> {
+    let mut upgrade_path = vec![current_version.clone()];
+    while upgrade_path.last() != Some(target_version) {
+        if upgrade_path.len() > MAX_UPGRADE_PATH_LENGTH {
+            return Err(format!(
+                "The target version is more than {} upgrade steps away from the current \
+                 SNS version: {}",
+                MAX_UPGRADE_PATH_LENGTH,
+                render_version(current_version)
+            ));
+        }
+
+        let last_version = upgrade_path.last().unwrap();
+        match get_next_version(env, last_version).await {
+            Some(next_version) => upgrade_path.push(next_version),
+            None => {
+                return Err(format!(
+                    "The target version {} is not on the upgrade path of the current SNS \
+                     version: {}",
+                    render_version(target_version),
+                    render_version(current_version)
+                ))
+            }
+        }
+    }
+
+    Ok(upgrade_path)
+}
+
 /// Returns all SNS canisters known by the Root canister.
 pub(crate) async fn get_all_sns_canisters(
     env: &dyn Environment,
@@ -307,6 +381,221 @@ pub(crate) async fn get_all_sns_canisters(
     })
 }
 
+impl Governance {
+    /// Sets the target version of the SNS, as proposed by an AdvanceSnsTargetVersion proposal.
+    ///
+    /// Return `Ok(true)` if the target version is already deployed, return `Ok(false)` if a
+    /// staged upgrade to the target version was started, in which case the proposal is
+    /// executed or failed by `maybe_advance_staged_upgrade` and `check_upgrade_status`.
+    pub(crate) async fn perform_advance_sns_target_version(
+        &mut self,
+        proposal_id: u64,
+        advance_sns_target_version: AdvanceSnsTargetVersion,
+    ) -> Result<bool, GovernanceError> {
+        err_if_another_upgrade_is_in_progress(&self.proto.proposals, proposal_id)?;
+
+        let new_target = advance_sns_target_version.new_target.ok_or_else(|| {
+            GovernanceError::new_with_message(
+                ErrorType::InvalidProposal,
+                "AdvanceSnsTargetVersion must specify a new target.",
+            )
+        })?;
+        let current_version = self.proto.deployed_version_or_panic();
+
+        let upgrade_steps = get_upgrade_path(&*self.env, &current_version, &new_target)
+            .await
+            .map_err(|e| {
+                GovernanceError::new_with_message(
+                    ErrorType::InvalidProposal,
+                    format!("Could not execute proposal: {}", e),
+                )
+            })?;
+
+        self.record_upgrade_journal_entry(
+            proposal_id,
+            UpgradeJournalEvent::TargetVersionSet,
+            Some(current_version.clone()),
+            Some(new_target.clone()),
+            None,
+        );
+
+        // The upgrade path only contains the current version if it is the target version.
+        if upgrade_steps.len() == 1 {
+            self.record_upgrade_journal_entry(
+                proposal_id,
+                UpgradeJournalEvent::TargetVersionReached,
+                Some(current_version),
+                Some(new_target),
+                None,
+            );
+            return Ok(true);
+        }
+
+        log!(
+            INFO,
+            "Starting a staged upgrade of {} steps to SNS version {}",
+            upgrade_steps.len() - 1,
+            render_version(&new_target),
+        );
+        self.proto.staged_upgrade = Some(StagedUpgrade {
+            proposal_id,
+            upgrade_steps,
+        });
+
+        Ok(false)
+    }
+
+    /// Starts the next step of the staged upgrade, if there is one and no upgrade is in
+    /// progress. Once the target version is deployed, the staged upgrade is over and its
+    /// proposal is marked as executed.
+    ///
+    /// The completion of each step is checked by `check_upgrade_status`, which also abandons
+    /// the staged upgrade (and fails its proposal) if the step fails.
+    pub(crate) async fn maybe_advance_staged_upgrade(&mut self) {
+        if self.proto.pending_version.is_some() {
+            return;
+        }
+        let Some(staged_upgrade) = self.proto.staged_upgrade.clone() else {
+            return;
+        };
+        let proposal_id = staged_upgrade.proposal_id;
+        let target_version = staged_upgrade.upgrade_steps.last().cloned();
+
+        let current_version = self.proto.deployed_version_or_panic();
+        let Some(position) = staged_upgrade
+            .upgrade_steps
+            .iter()
+            .position(|version| *version == current_version)
+        else {
+            self.fail_staged_upgrade(
+                proposal_id,
+                format!(
+                    "The deployed SNS version {} is not on the upgrade path to the target \
+                     version. Abandoning the staged upgrade.",
+                    render_version(&current_version)
+                ),
+            );
+            return;
+        };
+
+        let Some(next_version) = staged_upgrade.upgrade_steps.get(position + 1).cloned() else {
+            log!(
+                INFO,
+                "Staged upgrade of proposal {} reached its target version.",
+                proposal_id
+            );
+            self.record_upgrade_journal_entry(
+                proposal_id,
+                UpgradeJournalEvent::TargetVersionReached,
+                Some(current_version),
+                target_version,
+                None,
+            );
+            self.proto.staged_upgrade = None;
+            self.set_proposal_execution_status(proposal_id, Ok(()));
+            return;
+        };
+
+        // Other upgrades, e.g. of dapp canisters, have to finish before the next step starts.
+        if err_if_another_upgrade_is_in_progress(&self.proto.proposals, proposal_id).is_err() {
+            return;
+        }
+
+        if IS_STARTING_STAGED_UPGRADE_STEP.with(|is_starting| is_starting.replace(true)) {
+            return;
+        }
+        let result = self
+            .start_staged_upgrade_step(proposal_id, current_version, next_version)
+            .await;
+        IS_STARTING_STAGED_UPGRADE_STEP.with(|is_starting| is_starting.set(false));
+
+        if let Err(error) = result {
+            self.fail_staged_upgrade(proposal_id, error.error_message);
+        }
+    }
+
+    async fn start_staged_upgrade_step(
+        &mut self,
+        proposal_id: u64,
+        current_version: Version,
+        next_version: Version,
+    ) -> Result<(), GovernanceError> {
+        let root_canister_id = self.proto.root_canister_id_or_panic();
+        let upgrade_sns_params = get_upgrade_params_for_step(
+            &*self.env,
+            root_canister_id,
+            &current_version,
+            next_version,
+        )
+        .await
+        .map_err(|e| GovernanceError::new_with_message(ErrorType::External, e))?;
+
+        // `Ok(false)` means that the step was kicked-off, and its completion is checked
+        // by `check_upgrade_status`.
+        self.upgrade_sns_to_next_version(proposal_id, current_version, upgrade_sns_params)
+            .await
+            .map(|_| ())
+    }
+
+    /// Abandons the staged upgrade and fails its proposal.
+    fn fail_staged_upgrade(&mut self, proposal_id: u64, message: String) {
+        log!(ERROR, "Staged upgrade failed: {}", message);
+        self.proto.staged_upgrade = None;
+        self.set_proposal_execution_status(
+            proposal_id,
+            Err(GovernanceError::new_with_message(
+                ErrorType::External,
+                message,
+            )),
+        );
+    }
+
+    /// Returns true if the given proposal is the one that started the current staged upgrade.
+    pub(crate) fn is_staged_upgrade_proposal(&self, proposal_id: u64) -> bool {
+        self.proto
+            .staged_upgrade
+            .as_ref()
+            .map_or(false, |staged_upgrade| {
+                staged_upgrade.proposal_id == proposal_id
+            })
+    }
+
+    /// Appends an entry to the upgrade journal, removing the oldest entries if the journal is
+    /// full.
+    pub(crate) fn record_upgrade_journal_entry(
+        &mut self,
+        proposal_id: u64,
+        event: UpgradeJournalEvent,
+        from_version: Option<Version>,
+        to_version: Option<Version>,
+        failure_reason: Option<String>,
+    ) {
+        let upgrade_journal = &mut self.proto.upgrade_journal;
+        upgrade_journal.push(UpgradeJournalEntry {
+            timestamp_seconds: self.env.now(),
+            event: event as i32,
+            proposal_id,
+            from_version,
+            to_version,
+            failure_reason,
+        });
+        if upgrade_journal.len() > MAX_UPGRADE_JOURNAL_ENTRIES {
+            let excess = upgrade_journal.len() - MAX_UPGRADE_JOURNAL_ENTRIES;
+            upgrade_journal.drain(..excess);
+        }
+    }
+
+    /// Returns the upgrade journal, together with the deployed version and the staged upgrade
+    /// in progress, if any.
+    pub fn get_upgrade_journal(&self, _: GetUpgradeJournalRequest) -> GetUpgradeJournalResponse {
+        GetUpgradeJournalResponse {
+            deployed_version: self.proto.deployed_version.clone(),
+            staged_upgrade: self.proto.staged_upgrade.clone(),
+            entries: self.proto.upgrade_journal.clone(),
+        }
+    }
+}
+
 impl Version {
     /// Get the new hashes from next_version as a list of (SnsCanisterType, wasm_hash)
     pub(crate) fn changes_against(
@@ -21,13 +21,14 @@ use crate::{
             nervous_system_function::FunctionType,
             neuron::Followees,
             proposal::Action,
-            ClaimSwapNeuronsError, ClaimSwapNeuronsResponse, ClaimedSwapNeuronStatus,
-            DefaultFollowees, DeregisterDappCanisters, Empty, ExecuteGenericNervousSystemFunction,
-            GovernanceError, ManageDappCanisterSettings, ManageNeuronResponse, MintSnsTokens,
-            Motion, NervousSystemFunction, NervousSystemParameters, Neuron, NeuronId,
-            NeuronPermission, NeuronPermissionList, NeuronPermissionType, ProposalId,
-            RegisterDappCanisters, RewardEvent, TransferSnsTreasuryFunds,
-            UpgradeSnsControlledCanister, UpgradeSnsToNextVersion, Vote, VotingRewardsParameters,
+            AdvanceSnsTargetVersion, ClaimSwapNeuronsError, ClaimSwapNeuronsResponse,
+            ClaimedSwapNeuronStatus, DefaultFollowees, DeregisterDappCanisters, Empty,
+            ExecuteGenericNervousSystemFunction, GovernanceError, ManageDappCanisterSettings,
+            ManageNeuronResponse, MintSnsTokens, Motion, NervousSystemFunction,
+            NervousSystemParameters, Neuron, NeuronId, NeuronPermission, NeuronPermissionList,
+            NeuronPermissionType, ProposalId, RegisterDappCanisters, RewardEvent,
+            TransferSnsTreasuryFunds, UpgradeSnsControlledCanister, UpgradeSnsToNextVersion, Vote,
+            VotingRewardsParameters,
         },
     },
     proposal::ValidGenericNervousSystemFunction,
@@ -113,6 +114,9 @@ pub mod native_action_ids {
 
     /// ManageDappCanisterSettings Action.
     pub const MANAGE_DAPP_CANISTER_SETTINGS: u64 = 14;
+
+    /// AdvanceSnsTargetVersion Action.
+    pub const ADVANCE_SNS_TARGET_VERSION: u64 = 15;
 }
 
 impl governance::Mode {
@@ -1108,6 +1112,16 @@ impl From<Action> for NervousSystemFunction {
                 ),
                 function_type: Some(FunctionType::NativeNervousSystemFunction(Empty {})),
             },
+            Action::AdvanceSnsTargetVersion(_) => NervousSystemFunction {
+                id: native_action_ids::ADVANCE_SNS_TARGET_VERSION,
+                name: "Advance SNS target version".to_string(),
+                description: Some(
+                    "Proposal to upgrade the SNS to a target version, one step of the upgrade \
+                     path at a time."
+                        .to_string(),
+                ),
+                function_type: Some(FunctionType::NativeNervousSystemFunction(Empty {})),
+            },
         }
     }
 }
@@ -1448,6 +1462,7 @@ impl Action {
             Action::UpgradeSnsControlledCanister(_) => true,
             // Due to possible need of an emergency upgrade of the SNS
             Action::UpgradeSnsToNextVersion(_) => true,
+            Action::AdvanceSnsTargetVersion(_) => true,
             // Due to possible need of emergency functions defined as
             // GenericNervousSystemFunctions
             Action::ExecuteGenericNervousSystemFunction(_) => true,
@@ -1544,6 +1559,7 @@ impl Action {
             | RemoveGenericNervousSystemFunction(_)
             | ExecuteGenericNervousSystemFunction(_)
             | UpgradeSnsToNextVersion(_)
+            | AdvanceSnsTargetVersion(_)
             | ManageSnsMetadata(_)
             | ManageLedgerParameters(_)
             | RegisterDappCanisters(_)
@@ -1687,6 +1703,7 @@ impl From<&Action> for u64 {
             Action::ManageDappCanisterSettings(_) => {
                 native_action_ids::MANAGE_DAPP_CANISTER_SETTINGS
             }
+            Action::AdvanceSnsTargetVersion(_) => native_action_ids::ADVANCE_SNS_TARGET_VERSION,
         }
     }
 }
@@ -2148,6 +2165,12 @@ impl From<UpgradeSnsToNextVersion> for Action {
     }
 }
 
+impl From<AdvanceSnsTargetVersion> for Action {
+    fn from(advance_sns_target_version: AdvanceSnsTargetVersion) -> Action {
+        Action::AdvanceSnsTargetVersion(advance_sns_target_version)
+    }
+}
+
 impl From<TransferSnsTreasuryFunds> for Action {
     fn from(transfer_sns_treasury_funds: TransferSnsTreasuryFunds) -> Action {
         Action::TransferSnsTreasuryFunds(transfer_sns_treasury_funds)
@@ -2831,6 +2854,7 @@ pub(crate) mod tests {
                         target_method_name: Some("Foo".to_string()),
                         validator_canister_id: Some(*target_canister_id),
                         validator_method_name: Some("Bar".to_string()),
+                        target_candid_interface: None,
                     })),
                 }
             }
@@ -88,6 +88,7 @@ fn test_add_remove_and_execute_nervous_system_functions() {
                     target_method_name: Some("test_dapp_method".to_string()),
                     validator_canister_id: Some(dapp_canister.canister_id().get()),
                     validator_method_name: Some("test_dapp_method_validate".to_string()),
+                    target_candid_interface: None,
                 },
             )),
         };
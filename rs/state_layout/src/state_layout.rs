@@ -53,6 +53,9 @@ pub const SPLIT_MARKER_FILE: &str = "split_from.pbuf";
 pub const SUBNET_QUEUES_FILE: &str = "subnet_queues.pbuf";
 pub const SYSTEM_METADATA_FILE: &str = "system_metadata.pbuf";
 pub const STATS_FILE: &str = "stats.pbuf";
+/// Directory inside a state sync scratchpad where the progress of the state
+/// sync is persisted, so that it can be resumed after a restart.
+pub const STATE_SYNC_PROGRESS_DIR: &str = "state_sync_progress";
 
 /// `ReadOnly` is the access policy used for reading checkpoints. We
 /// don't want to ever modify persisted states.
@@ -423,6 +426,7 @@ impl StateLayout {
 
     fn init(&self) -> Result<(), LayoutError> {
         self.cleanup_tip()?;
+        self.preserve_interrupted_state_sync()?;
         self.cleanup_tmp()?;
         // This is for testing only. In production the Guest OS setup
         // would have already created the page_deltas directory, however
@@ -564,6 +568,75 @@ impl StateLayout {
         Ok(tmp.join(format!("state_sync_cache_{:016x}", height.get())))
     }
 
+    /// Returns the path where the scratchpad of a state sync that was
+    /// interrupted by a restart is kept until the state manager picks it up.
+    pub fn interrupted_state_sync(&self) -> PathBuf {
+        self.root.join("interrupted_state_sync")
+    }
+
+    /// Moves the most recent state sync scratchpad or cache that persisted
+    /// its progress out of the tmp directory, so that it survives
+    /// `cleanup_tmp` and the state sync can be resumed.
+    fn preserve_interrupted_state_sync(&self) -> Result<(), LayoutError> {
+        let tmp = self.tmp();
+        if !tmp.exists() {
+            return Ok(());
+        }
+        let entries = std::fs::read_dir(&tmp).map_err(|err| LayoutError::IoError {
+            path: tmp.clone(),
+            message: "Failed to list the temporary directory".to_string(),
+            io_err: err,
+        })?;
+        let mut latest: Option<(u64, PathBuf)> = None;
+        for entry in entries {
+            let path = entry
+                .map_err(|err| LayoutError::IoError {
+                    path: tmp.clone(),
+                    message: "Failed to list the temporary directory".to_string(),
+                    io_err: err,
+                })?
+                .path();
+            let height = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| {
+                    name.strip_prefix("state_sync_scratchpad_")
+                        .or_else(|| name.strip_prefix("state_sync_cache_"))
+                })
+                .and_then(|hex| u64::from_str_radix(hex, 16).ok());
+            if let Some(height) = height {
+                if path.join(STATE_SYNC_PROGRESS_DIR).is_dir()
+                    && latest.as_ref().map_or(true, |(h, _)| height > *h)
+                {
+                    latest = Some((height, path));
+                }
+            }
+        }
+
+        if let Some((height, path)) = latest {
+            let interrupted = self.interrupted_state_sync();
+            if interrupted.exists() {
+                std::fs::remove_dir_all(&interrupted).map_err(|err| LayoutError::IoError {
+                    path: interrupted.clone(),
+                    message: "Failed to remove the previously interrupted state sync".to_string(),
+                    io_err: err,
+                })?;
+            }
+            std::fs::rename(&path, &interrupted).map_err(|err| LayoutError::IoError {
+                path: path.clone(),
+                message: format!("Failed to preserve the interrupted state sync @{}", height),
+                io_err: err,
+            })?;
+            info!(
+                self.log,
+                "Preserved interrupted state sync @{} at {}",
+                height,
+                interrupted.display()
+            );
+        }
+        Ok(())
+    }
+
     fn cleanup_tip(&self) -> Result<(), LayoutError> {
         if self.tip_path().exists() {
             std::fs::remove_dir_all(self.tip_path()).map_err(|err| LayoutError::IoError {
@@ -80,6 +80,39 @@ fn test_state_layout_diverged_state_paths() {
     });
 }
 
+#[test]
+fn test_interrupted_state_sync_survives_restart() {
+    with_test_replica_logger(|log| {
+        let tempdir = tmpdir("state_layout");
+        let root_path = tempdir.path().to_path_buf();
+        let metrics_registry = ic_metrics::MetricsRegistry::new();
+        let state_layout =
+            StateLayout::try_new(log.clone(), root_path.clone(), &metrics_registry).unwrap();
+
+        // Only scratchpads and caches with persisted progress are kept, and
+        // only the most recent one of them.
+        let with_progress = [
+            state_layout.state_sync_cache(Height::new(3)).unwrap(),
+            state_layout.state_sync_scratchpad(Height::new(5)).unwrap(),
+        ];
+        for path in &with_progress {
+            std::fs::create_dir_all(path.join(STATE_SYNC_PROGRESS_DIR)).unwrap();
+        }
+        let without_progress = state_layout.state_sync_scratchpad(Height::new(9)).unwrap();
+        std::fs::create_dir_all(&without_progress).unwrap();
+        File::create(with_progress[1].join("marker")).unwrap();
+
+        let state_layout = StateLayout::try_new(log, root_path, &metrics_registry).unwrap();
+
+        let interrupted = state_layout.interrupted_state_sync();
+        assert!(interrupted.join("marker").exists());
+        assert!(interrupted.join(STATE_SYNC_PROGRESS_DIR).is_dir());
+        assert!(!with_progress[0].exists());
+        assert!(!with_progress[1].exists());
+        assert!(!without_progress.exists());
+    });
+}
+
 #[test]
 fn test_encode_decode_empty_controllers() {
     // A canister state with empty controllers.
@@ -23,9 +23,14 @@ pub struct StateSync {
 
 impl StateSync {
     pub fn new(state_manager: Arc<StateManagerImpl>, log: ReplicaLogger) -> Self {
+        let state_sync_refs = StateSyncRefs::new(log.clone());
+        state_sync_refs
+            .cache
+            .write()
+            .load_interrupted_sync(&state_manager.state_layout);
         Self {
             state_manager,
-            state_sync_refs: StateSyncRefs::new(log.clone()),
+            state_sync_refs,
             log,
         }
     }
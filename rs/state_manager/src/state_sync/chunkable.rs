@@ -27,6 +27,7 @@ use std::{
 };
 
 pub mod cache;
+pub(crate) mod progress;
 
 // If set to true, we validate chunks even in situations where it might not be
 // necessary.
@@ -809,6 +810,8 @@ impl IncompleteState {
             "state sync: start to make a checkpoint from the scratchpad"
         );
 
+        progress::remove(log, root);
+
         let ro_layout = CheckpointLayout::<ReadOnly>::new_untracked(root.to_path_buf(), height)
             .expect("failed to create checkpoint layout");
 
@@ -962,7 +965,7 @@ impl IncompleteState {
                         // StateSyncCacheEntry, so cloning the path is safe
                         root_old: cache_entry.path().to_path_buf(),
                         height_old: cache_entry.height,
-                        validate_data: false,
+                        validate_data: cache_entry.validate_data,
                     })
                 } else {
                     // This should be a special case that can only happen if the source of the
@@ -982,7 +985,7 @@ impl IncompleteState {
                 missing_chunks: cache_entry.missing_chunks.clone(),
                 root_old: cache_entry.path().to_path_buf(),
                 height_old: cache_entry.height,
-                validate_data: false,
+                validate_data: cache_entry.validate_data,
             }),
             (None, Some((checkpoint_manifest, checkpoint_old))) => {
                 let checkpoint_height = checkpoint_old.height();
@@ -1121,6 +1124,29 @@ impl IncompleteState {
     }
 }
 
+/// Converts the chunks that still need to be fetched, as stored in
+/// `DownloadState::Loading`, into indices into the manifest's chunk table.
+fn missing_chunk_indices(
+    fetch_chunks: &HashSet<usize>,
+    state_sync_file_group: &FileGroupChunks,
+) -> HashSet<usize> {
+    let mut missing_chunks: HashSet<usize> = Default::default();
+    for i in fetch_chunks.iter() {
+        assert_ne!(0, *i);
+        if *i < FILE_GROUP_CHUNK_ID_OFFSET as usize {
+            missing_chunks.insert(*i - FILE_CHUNK_ID_OFFSET);
+        } else {
+            // If it's a chunk group, the individual chunks are missing in the manifest,
+            // not the group
+            let chunks = state_sync_file_group
+                .get(&(*i as u32))
+                .expect("Unknown chunk group");
+            missing_chunks.extend(chunks.iter().map(|i| *i as usize));
+        }
+    }
+    missing_chunks
+}
+
 #[cfg(feature = "malicious_code")]
 fn maliciously_alter_chunk_data(
     mut chunk: Chunk,
@@ -1416,6 +1442,20 @@ impl Chunkable<StateSyncMessage> for IncompleteState {
                             //     2. `canister.pbuf` files are small so there will be only a handful of chunks after grouping.
                             fetch_chunks.insert(chunk_id as usize);
                         }
+                        if let Err(err) = progress::persist(
+                            &self.root,
+                            self.height,
+                            &self.root_hash,
+                            &manifest,
+                            &missing_chunk_indices(&fetch_chunks, &state_sync_file_group),
+                        ) {
+                            warn!(
+                                self.log,
+                                "Failed to persist the progress of state sync @{}, it won't be resumed after a restart: {}",
+                                self.height,
+                                err
+                            );
+                        }
                         let num_fetch_chunks = fetch_chunks.len();
                         self.state = DownloadState::Loading {
                             meta_manifest,
@@ -1525,6 +1565,16 @@ impl Chunkable<StateSyncMessage> for IncompleteState {
                     );
                 }
 
+                if let Err(err) = progress::record_completed_chunks(
+                    &self.root,
+                    chunk_table_indices.iter().map(|i| *i as usize),
+                ) {
+                    warn!(
+                        self.log,
+                        "Failed to record chunk {} of state sync @{}: {}", ix, self.height, err
+                    );
+                }
+
                 fetch_chunks.remove(&(ix as usize));
 
                 if fetch_chunks.is_empty() {
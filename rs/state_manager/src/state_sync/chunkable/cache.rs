@@ -36,6 +36,9 @@ pub struct StateSyncCacheEntry {
     pub height: Height,
     path: PathBuf,
     pub missing_chunks: HashSet<usize>,
+    /// Whether the chunks need to be validated before they are reused, which
+    /// is the case if they were written before the replica restarted.
+    pub validate_data: bool,
     log: ReplicaLogger,
 }
 
@@ -101,20 +104,7 @@ impl StateSyncCache {
         // For the cache we store indices into the manifest's chunk table as
         // missing_chunks.
         debug_assert!(!fetch_chunks.contains(&0));
-        let mut missing_chunks: HashSet<usize> = Default::default();
-        for i in fetch_chunks.into_iter() {
-            assert_ne!(0, i);
-            if i < FILE_GROUP_CHUNK_ID_OFFSET as usize {
-                missing_chunks.insert(i - FILE_CHUNK_ID_OFFSET);
-            } else {
-                // If it's a chunk group, the individual chunks are missing in the manifest,
-                // not the group
-                let chunks = state_sync_file_group
-                    .get(&(i as u32))
-                    .expect("Unknown chunk group");
-                missing_chunks.extend(chunks.iter().map(|i| *i as usize));
-            }
-        }
+        let missing_chunks = missing_chunk_indices(&fetch_chunks, &state_sync_file_group);
 
         debug_assert!(missing_chunks
             .iter()
@@ -147,11 +137,86 @@ impl StateSyncCache {
             height: sync.height,
             path: cache_root,
             missing_chunks,
+            validate_data: false,
             log: self.log.clone(),
         };
         self.entry = Some(Arc::new(entry));
     }
 
+    /// Turns the scratchpad of a state sync that was interrupted by a restart
+    /// into the cache entry, so that the chunks it already has are not fetched
+    /// again. If the same height is advertised again, the state sync resumes
+    /// where it left off.
+    ///
+    /// The interrupted state sync is dropped if its progress can't be read
+    /// back, or if there is already a checkpoint at its height or above.
+    pub fn load_interrupted_sync(&mut self, state_layout: &StateLayout) {
+        let interrupted = state_layout.interrupted_state_sync();
+        if !interrupted.exists() {
+            return;
+        }
+        let progress = match progress::load(&interrupted) {
+            Ok(progress) => progress,
+            Err(err) => {
+                warn!(
+                    self.log,
+                    "Failed to read the progress of the interrupted state sync at {}: {}",
+                    interrupted.display(),
+                    err
+                );
+                delete_folder(&self.log, &interrupted);
+                return;
+            }
+        };
+
+        let latest_checkpoint = state_layout
+            .checkpoint_heights()
+            .ok()
+            .and_then(|heights| heights.last().copied());
+        if let Some(checkpoint_height) = latest_checkpoint.filter(|h| progress.height <= *h) {
+            info!(
+                self.log,
+                "Dropping interrupted state sync @{}, there is already a checkpoint @{}",
+                progress.height,
+                checkpoint_height
+            );
+            delete_folder(&self.log, &interrupted);
+            return;
+        }
+
+        let cache_root = state_layout
+            .state_sync_cache(progress.height)
+            .expect("failed to create directory for state sync cache");
+        if let Err(err) = std::fs::rename(&interrupted, &cache_root) {
+            warn!(
+                self.log,
+                "Failed to create state sync cache at {}: {}",
+                cache_root.display(),
+                err
+            );
+            delete_folder(&self.log, &cache_root);
+            delete_folder(&self.log, &interrupted);
+            return;
+        }
+
+        info!(
+            self.log,
+            "Resuming interrupted state sync @{} (root hash {:?}) with {} of {} chunks missing",
+            progress.height,
+            progress.root_hash,
+            progress.missing_chunks.len(),
+            progress.manifest.chunk_table.len()
+        );
+        self.entry = Some(Arc::new(StateSyncCacheEntry {
+            manifest: progress.manifest,
+            height: progress.height,
+            path: cache_root,
+            missing_chunks: progress.missing_chunks,
+            validate_data: true,
+            log: self.log.clone(),
+        }));
+    }
+
     /// Passes an `IncompleteState` `sync` to the cache, moving out any data
     /// relevant to caching.
     ///
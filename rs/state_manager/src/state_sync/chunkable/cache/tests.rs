@@ -371,3 +371,47 @@ fn existing_folder() {
         assert!(env.cache.read().get().is_none());
     })
 }
+
+// A state sync interrupted by a restart becomes the cache entry, and its
+// chunks are validated before they are reused
+#[test]
+fn interrupted_sync() {
+    with_test_replica_logger(|log| {
+        let env = TestEnvironment::new(log);
+        let interrupted = env.state_layout.interrupted_state_sync();
+
+        // Progress that can't be read back is dropped
+        std::fs::create_dir_all(interrupted.join(ic_state_layout::STATE_SYNC_PROGRESS_DIR))
+            .unwrap();
+        env.cache.write().load_interrupted_sync(&env.state_layout);
+        assert!(env.cache.read().get().is_none());
+        assert!(!interrupted.exists());
+
+        let manifest = Manifest::new(V2, vec![], vec![]);
+        let root_hash = CryptoHashOfState::from(CryptoHash(
+            crate::manifest::manifest_hash(&manifest).to_vec(),
+        ));
+        progress::persist(
+            &interrupted,
+            Height::new(7),
+            &root_hash,
+            &manifest,
+            &Default::default(),
+        )
+        .unwrap();
+        env.cache.write().load_interrupted_sync(&env.state_layout);
+
+        assert!(!interrupted.exists());
+        let lock = env.cache.read();
+        let entry = lock.get().unwrap();
+        assert_eq!(entry.height, Height::new(7));
+        assert_eq!(entry.manifest, manifest);
+        assert!(entry.missing_chunks.is_empty());
+        assert!(entry.validate_data);
+        assert_eq!(
+            entry.path,
+            env.state_layout.state_sync_cache(Height::new(7)).unwrap()
+        );
+        assert!(entry.path.exists());
+    })
+}
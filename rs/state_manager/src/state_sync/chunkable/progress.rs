@@ -0,0 +1,127 @@
+//! Persists the progress of a state sync in its scratchpad, so that the state
+//! sync can be resumed after the replica restarts.
+//!
+//! Once the manifest is known, the scratchpad gets a progress directory with
+//! the target of the state sync, the manifest, and an append-only log of the
+//! chunks that were written to the scratchpad. None of it is trusted after a
+//! restart: the manifest is validated against the root hash, and the chunks
+//! are validated before they are reused.
+
+use super::*;
+use crate::state_sync::types::encode_manifest;
+use ic_state_layout::STATE_SYNC_PROGRESS_DIR;
+use ic_types::crypto::CryptoHash;
+use std::io::Write;
+
+#[cfg(test)]
+mod tests;
+
+const TARGET_FILE: &str = "target.bin";
+const MANIFEST_FILE: &str = "manifest.pbuf";
+const COMPLETED_CHUNKS_FILE: &str = "completed_chunks.bin";
+
+/// The progress of a state sync, as read back from a scratchpad.
+pub struct StateSyncProgress {
+    pub height: Height,
+    pub root_hash: CryptoHashOfState,
+    pub manifest: Manifest,
+    /// Indices into the manifest's chunk table of the chunks that are not
+    /// known to have been written to the scratchpad.
+    pub missing_chunks: HashSet<usize>,
+}
+
+fn progress_dir(root: &Path) -> PathBuf {
+    root.join(STATE_SYNC_PROGRESS_DIR)
+}
+
+fn encode_chunks(chunks: impl IntoIterator<Item = usize>) -> Vec<u8> {
+    chunks
+        .into_iter()
+        .flat_map(|ix| (ix as u32).to_le_bytes())
+        .collect()
+}
+
+/// Persists the target and the manifest of the state sync with scratchpad
+/// `root`, along with the chunks that are already in the scratchpad, i.e. all
+/// chunks of the manifest except `missing_chunks`.
+pub(crate) fn persist(
+    root: &Path,
+    height: Height,
+    root_hash: &CryptoHashOfState,
+    manifest: &Manifest,
+    missing_chunks: &HashSet<usize>,
+) -> std::io::Result<()> {
+    let dir = progress_dir(root);
+    std::fs::create_dir_all(&dir)?;
+
+    let mut target = height.get().to_le_bytes().to_vec();
+    target.extend_from_slice(&root_hash.get_ref().0);
+    std::fs::write(dir.join(TARGET_FILE), target)?;
+    std::fs::write(dir.join(MANIFEST_FILE), encode_manifest(manifest))?;
+
+    let completed = (0..manifest.chunk_table.len()).filter(|ix| !missing_chunks.contains(ix));
+    std::fs::write(dir.join(COMPLETED_CHUNKS_FILE), encode_chunks(completed))
+}
+
+/// Records that the given chunks were written to the scratchpad `root`.
+pub(crate) fn record_completed_chunks(
+    root: &Path,
+    chunks: impl IntoIterator<Item = usize>,
+) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(progress_dir(root).join(COMPLETED_CHUNKS_FILE))?;
+    file.write_all(&encode_chunks(chunks))
+}
+
+/// Removes the persisted progress from the scratchpad `root`, which must not
+/// end up in the checkpoint.
+pub(crate) fn remove(log: &ReplicaLogger, root: &Path) {
+    let dir = progress_dir(root);
+    if dir.exists() {
+        if let Err(err) = std::fs::remove_dir_all(&dir) {
+            fatal!(
+                log,
+                "Failed to remove state sync progress at {}: {}",
+                dir.display(),
+                err
+            );
+        }
+    }
+}
+
+/// Reads back the progress persisted in the scratchpad `root`.
+pub(crate) fn load(root: &Path) -> Result<StateSyncProgress, String> {
+    let dir = progress_dir(root);
+    let read = |name: &str| {
+        std::fs::read(dir.join(name)).map_err(|err| format!("failed to read {}: {}", name, err))
+    };
+
+    let target = read(TARGET_FILE)?;
+    if target.len() < 8 {
+        return Err(format!("truncated {}", TARGET_FILE));
+    }
+    let (height, hash) = target.split_at(8);
+    let height = Height::new(u64::from_le_bytes(
+        height.try_into().expect("the height has 8 bytes"),
+    ));
+    let root_hash = CryptoHashOfState::from(CryptoHash(hash.to_vec()));
+
+    let manifest = decode_manifest(&read(MANIFEST_FILE)?)?;
+    crate::manifest::validate_manifest(&manifest, &root_hash)
+        .map_err(|err| format!("invalid manifest: {}", err))?;
+
+    let mut missing_chunks: HashSet<usize> = (0..manifest.chunk_table.len()).collect();
+    // A restart may have cut off the last record, which is then ignored.
+    for record in read(COMPLETED_CHUNKS_FILE)?.chunks_exact(4) {
+        let ix = u32::from_le_bytes(record.try_into().expect("records have 4 bytes"));
+        missing_chunks.remove(&(ix as usize));
+    }
+
+    Ok(StateSyncProgress {
+        height,
+        root_hash,
+        manifest,
+        missing_chunks,
+    })
+}
@@ -0,0 +1,79 @@
+use super::*;
+use crate::manifest::{compute_manifest, manifest_hash, ManifestMetrics};
+use ic_logger::replica_logger::no_op_logger;
+use ic_metrics::MetricsRegistry;
+use ic_test_utilities_logger::with_test_replica_logger;
+use ic_types::state_sync::CURRENT_STATE_SYNC_VERSION;
+
+/// Computes the manifest of a checkpoint with three files of one chunk each.
+fn manifest_and_root_hash() -> (Manifest, CryptoHashOfState) {
+    let dir = tempfile::TempDir::new().expect("failed to create a temporary directory");
+    for (name, byte) in [("a.bin", 1u8), ("b.bin", 2u8), ("c.bin", 3u8)] {
+        std::fs::write(dir.path().join(name), vec![byte; 100]).unwrap();
+    }
+    let manifest = compute_manifest(
+        &mut scoped_threadpool::Pool::new(1),
+        &ManifestMetrics::new(&MetricsRegistry::new()),
+        &no_op_logger(),
+        CURRENT_STATE_SYNC_VERSION,
+        &CheckpointLayout::new_untracked(dir.path().to_path_buf(), Height::new(0)).unwrap(),
+        1024,
+        None,
+    )
+    .expect("failed to compute manifest");
+    let root_hash = CryptoHashOfState::from(CryptoHash(manifest_hash(&manifest).to_vec()));
+    (manifest, root_hash)
+}
+
+#[test]
+fn progress_round_trips_and_ignores_truncated_record() {
+    let (manifest, root_hash) = manifest_and_root_hash();
+    assert_eq!(manifest.chunk_table.len(), 3);
+    let scratchpad = tempfile::TempDir::new().unwrap();
+    let root = scratchpad.path();
+
+    persist(
+        root,
+        Height::new(42),
+        &root_hash,
+        &manifest,
+        &maplit::hashset! {1, 2},
+    )
+    .unwrap();
+    record_completed_chunks(root, vec![2]).unwrap();
+    // A record that was only partially written before a restart.
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(progress_dir(root).join(COMPLETED_CHUNKS_FILE))
+        .unwrap()
+        .write_all(&[1, 0])
+        .unwrap();
+
+    let progress = load(root).unwrap();
+    assert_eq!(progress.height, Height::new(42));
+    assert_eq!(progress.root_hash, root_hash);
+    assert_eq!(progress.manifest, manifest);
+    assert_eq!(progress.missing_chunks, maplit::hashset! {1});
+
+    with_test_replica_logger(|log| remove(&log, root));
+    assert!(!progress_dir(root).exists());
+    assert!(load(root).is_err());
+}
+
+#[test]
+fn progress_with_mismatching_root_hash_is_rejected() {
+    let (manifest, _) = manifest_and_root_hash();
+    let scratchpad = tempfile::TempDir::new().unwrap();
+    let root = scratchpad.path();
+
+    persist(
+        root,
+        Height::new(42),
+        &CryptoHashOfState::from(CryptoHash(vec![0; 32])),
+        &manifest,
+        &HashSet::new(),
+    )
+    .unwrap();
+
+    assert!(load(root).unwrap_err().contains("invalid manifest"));
+}
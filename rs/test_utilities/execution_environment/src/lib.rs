@@ -1459,6 +1459,28 @@ impl ExecutionTest {
         )
     }
 
+    /// Executes the update call in a sandbox on the current state, without
+    /// committing any of its effects. Returns the result, the instructions used
+    /// and the estimated cycles cost.
+    pub fn dry_run<S: ToString>(
+        &mut self,
+        canister_id: CanisterId,
+        method_name: S,
+        method_payload: Vec<u8>,
+    ) -> (Result<WasmResult, UserError>, NumInstructions, Cycles) {
+        let ingress = SignedIngressBuilder::new()
+            .sender(self.user_id())
+            .canister_id(canister_id)
+            .method_name(method_name)
+            .method_payload(method_payload)
+            .build();
+        self.exec_env.execute_dry_run(
+            ingress.content(),
+            Arc::new(self.state().clone()),
+            self.instruction_limit_without_dts,
+        )
+    }
+
     /// A low-level helper to generate the next message id.
     fn next_message_id(&mut self) -> MessageId {
         let message_id = self.message_id;
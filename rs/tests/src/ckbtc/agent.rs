@@ -135,6 +135,7 @@ async fn test_update_balance(agent: &CkBtcMinterAgent) {
     let args = UpdateBalanceArgs {
         owner: None,
         subaccount: Some(subaccount),
+        address_type: None,
     };
     let res = agent
         .update_balance(args)
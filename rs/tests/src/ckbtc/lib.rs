@@ -325,6 +325,8 @@ pub(crate) async fn install_kyt(
         minter_id,
         maintainers,
         mode: KytMode::AcceptAll,
+        quorum: None,
+        blocked_addresses: None,
     });
 
     install_rust_canister_from_path(
@@ -343,7 +345,13 @@ pub(crate) async fn set_kyt_api_key(
 ) {
     agent
         .update(kyt_canister, "set_api_key")
-        .with_arg(candid::Encode!(&SetApiKeyArg { api_key }).unwrap())
+        .with_arg(
+            candid::Encode!(&SetApiKeyArg {
+                api_key,
+                provider_kind: None,
+            })
+            .unwrap(),
+        )
         .call_and_wait()
         .await
         .expect("failed to set api key");
@@ -354,6 +362,8 @@ pub(crate) async fn upgrade_kyt(kyt_canister: &mut Canister<'_>, mode: KytMode)
         mode: Some(mode),
         maintainers: None,
         minter_id: None,
+        quorum: None,
+        blocked_addresses: None,
     });
 
     kyt_canister
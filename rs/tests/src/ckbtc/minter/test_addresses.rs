@@ -55,6 +55,7 @@ pub fn test_ckbtc_addresses(env: TestEnv) {
         let arg = GetBtcAddressArgs {
             owner: None,
             subaccount: None,
+            address_type: None,
         };
         let arg = Encode!(&arg).expect("Error while encoding arg.");
         let res = agent
@@ -76,6 +77,7 @@ pub fn test_ckbtc_addresses(env: TestEnv) {
         let arg = GetBtcAddressArgs {
             owner: None,
             subaccount: None,
+            address_type: None,
         };
         let arg = Encode!(&arg).expect("Error while encoding argument.");
         let res = agent
@@ -136,6 +136,7 @@ pub fn test_kyt(env: TestEnv) {
             .update_balance(UpdateBalanceArgs {
                 owner: None,
                 subaccount: Some(subaccount1),
+                address_type: None,
             })
             .await
             .expect("Error while calling update_balance")
@@ -165,6 +166,7 @@ pub fn test_kyt(env: TestEnv) {
             .update_balance(UpdateBalanceArgs {
                 owner: None,
                 subaccount: Some(subaccount1),
+                address_type: None,
             })
             .await
             .expect("Error while calling update_balance");
@@ -187,6 +189,7 @@ pub fn test_kyt(env: TestEnv) {
             .update_balance(UpdateBalanceArgs {
                 owner: None,
                 subaccount: Some(subaccount1),
+                address_type: None,
             })
             .await
             .expect("Error while calling update_balance")
@@ -221,6 +224,7 @@ pub fn test_kyt(env: TestEnv) {
             .update_balance(UpdateBalanceArgs {
                 owner: None,
                 subaccount: Some(subaccount1),
+                address_type: None,
             })
             .await
             .expect("Error while calling update_balance")
@@ -237,6 +241,7 @@ pub fn test_kyt(env: TestEnv) {
             .update_balance(UpdateBalanceArgs {
                 owner: None,
                 subaccount: Some(subaccount1),
+                address_type: None,
             })
             .await
             .expect("Error while calling update_balance")
@@ -325,6 +325,7 @@ pub async fn wait_for_update_balance(
         .update_balance(UpdateBalanceArgs {
             owner: None,
             subaccount,
+            address_type: None,
         })
         .await
         .expect("Error while calling update_balance");
@@ -336,6 +337,7 @@ pub async fn wait_for_update_balance(
             .update_balance(UpdateBalanceArgs {
                 owner: None,
                 subaccount,
+                address_type: None,
             })
             .await
             .expect("Error while calling update_balance");
@@ -352,6 +354,7 @@ pub async fn update_balance(
         .update_balance(UpdateBalanceArgs {
             owner: None,
             subaccount,
+            address_type: None,
         })
         .await
         .expect("Error while calling update_balance")
@@ -364,6 +367,7 @@ pub async fn update_balance_without_subaccount(
         .update_balance(UpdateBalanceArgs {
             owner: None,
             subaccount: None,
+            address_type: None,
         })
         .await
         .expect("Error while calling update_balance")
@@ -601,6 +605,7 @@ pub async fn assert_no_new_utxo(agent: &CkBtcMinterAgent, subaccount: &Subaccoun
         .update_balance(UpdateBalanceArgs {
             owner: None,
             subaccount: Some(*subaccount),
+            address_type: None,
         })
         .await
         .expect("Error while calling update_balance");
@@ -613,6 +618,7 @@ pub async fn assert_temporarily_unavailable(agent: &CkBtcMinterAgent, subaccount
         .update_balance(UpdateBalanceArgs {
             owner: None,
             subaccount: Some(*subaccount),
+            address_type: None,
         })
         .await
         .expect("Error while calling update_balance");
@@ -696,6 +696,31 @@ pub struct HttpReadStateResponse {
     pub certificate: Blob,
 }
 
+/// The response to a synchronous `call` request whose ingress message reached
+/// a terminal status before the replica stopped waiting for it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "status")]
+pub enum HttpSyncCallResponse {
+    /// `certificate` is the CBOR-encoded `Certificate` of the message's
+    /// `request_status` subtree, which holds the reply or the reject.
+    Replied { certificate: Blob },
+}
+
+/// The response to a `dry_run` request: the outcome of executing an update
+/// call against the latest certified state without committing any of its
+/// effects.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HttpDryRunResponse {
+    /// The reply or the reject the call would have produced.
+    pub outcome: HttpQueryResponse,
+    /// The number of instructions the execution used.
+    pub instructions_used: u64,
+    /// The cycles that inducting the ingress message and executing the call
+    /// would have cost the canister.
+    pub estimated_cycles_cost: u64,
+}
+
 /// A `Certificate` as defined in `<https://internetcomputer.org/docs/current/references/ic-interface-spec#certificate>`
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Certificate {